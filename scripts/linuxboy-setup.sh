@@ -16,74 +16,110 @@ if [ "$(id -u)" -ne 0 ]; then
   fi
 fi
 
-APT_CMD=""
-if command -v apt >/dev/null 2>&1; then
-  APT_CMD="apt"
-elif command -v apt-get >/dev/null 2>&1; then
-  APT_CMD="apt-get"
+if [ -f /etc/os-release ]; then
+  . /etc/os-release
 else
-  echo "apt/apt-get not found."
+  echo "/etc/os-release not found."
   exit 1
 fi
 
-APT_FLAGS=""
-if [ "$REINSTALL" -eq 1 ]; then
-  APT_FLAGS="--reinstall"
+PKG=""
+if command -v apt >/dev/null 2>&1 || command -v apt-get >/dev/null 2>&1; then
+  PKG="apt"
+elif command -v pacman >/dev/null 2>&1; then
+  PKG="pacman"
+else
+  echo "No supported package manager found (apt, pacman)."
+  exit 1
 fi
 
-apt_update() {
+setup_apt() {
+  APT_CMD="apt"
+  if ! command -v apt >/dev/null 2>&1; then
+    APT_CMD="apt-get"
+  fi
+
+  APT_FLAGS=""
+  if [ "$REINSTALL" -eq 1 ]; then
+    APT_FLAGS="--reinstall"
+  fi
+
+  if [ "${ID:-}" != "debian" ] && [ "${ID:-}" != "ubuntu" ]; then
+    echo "The apt setup currently supports Debian/Ubuntu."
+    exit 1
+  fi
+
+  if ! dpkg --print-foreign-architectures | grep -qx "i386"; then
+    $SUDO dpkg --add-architecture i386
+  fi
+
   $SUDO "$APT_CMD" update
-}
 
-apt_install() {
-  $SUDO "$APT_CMD" install $APT_FLAGS "$@"
-}
+  BASE_PACKAGES="curl ca-certificates python3"
+  VULKAN_PACKAGES="vulkan-tools libvulkan1 libvulkan1:i386"
+  MESA_PACKAGES="mesa-vulkan-drivers mesa-vulkan-drivers:i386 libgl1-mesa-dri:amd64 libgl1-mesa-dri:i386 libglx-mesa0:amd64 libglx-mesa0:i386"
 
-if [ -f /etc/os-release ]; then
-  . /etc/os-release
-else
-  echo "/etc/os-release not found."
-  exit 1
-fi
+  pkg_install $BASE_PACKAGES
+  pkg_install $VULKAN_PACKAGES
+  pkg_install $MESA_PACKAGES
 
-if [ "${ID:-}" != "debian" ] && [ "${ID:-}" != "ubuntu" ]; then
-  echo "This setup script currently supports Debian/Ubuntu."
-  exit 1
-fi
+  install_umu_deb
+}
 
-if ! dpkg --print-foreign-architectures | grep -qx "i386"; then
-  $SUDO dpkg --add-architecture i386
-fi
+setup_pacman() {
+  if ! grep -qx "[[:space:]]*\[multilib\][[:space:]]*" /etc/pacman.conf; then
+    echo "The multilib repository is not enabled in /etc/pacman.conf."
+    echo "Uncomment the [multilib] section, run 'sudo pacman -Syu', then run this script again."
+    exit 1
+  fi
 
-apt_update
+  BASE_PACKAGES="curl ca-certificates python"
+  VULKAN_PACKAGES="vulkan-tools vulkan-icd-loader lib32-vulkan-icd-loader"
+  MESA_PACKAGES="mesa lib32-mesa vulkan-radeon lib32-vulkan-radeon vulkan-intel lib32-vulkan-intel"
 
-BASE_PACKAGES="curl ca-certificates python3"
-VULKAN_PACKAGES="vulkan-tools libvulkan1 libvulkan1:i386"
-MESA_PACKAGES="mesa-vulkan-drivers mesa-vulkan-drivers:i386 libgl1-mesa-dri:amd64 libgl1-mesa-dri:i386 libglx-mesa0:amd64 libglx-mesa0:i386"
+  pkg_install $BASE_PACKAGES
+  pkg_install $VULKAN_PACKAGES
+  pkg_install $MESA_PACKAGES
 
-apt_install $BASE_PACKAGES
-apt_install $VULKAN_PACKAGES
-apt_install $MESA_PACKAGES
+  # umu-launcher is packaged in Arch's extra repository.
+  pkg_install umu-launcher
+}
 
-DEBIAN_ARCH="$(dpkg --print-architecture)"
-if [ "$DEBIAN_ARCH" != "amd64" ] && [ "$DEBIAN_ARCH" != "arm64" ] && [ "$DEBIAN_ARCH" != "armhf" ]; then
-  echo "Unsupported architecture: $DEBIAN_ARCH"
-  exit 1
-fi
+pkg_install() {
+  case "$PKG" in
+    apt)
+      $SUDO "$APT_CMD" install $APT_FLAGS "$@"
+      ;;
+    pacman)
+      if [ "$REINSTALL" -eq 1 ]; then
+        $SUDO pacman -S --noconfirm "$@"
+      else
+        $SUDO pacman -S --needed --noconfirm "$@"
+      fi
+      ;;
+  esac
+}
 
-if [ "${ID}" = "debian" ]; then
-  DISTRO_TAG="debian-${VERSION_ID}"
-else
-  if [ -z "${VERSION_CODENAME:-}" ]; then
-    echo "Missing VERSION_CODENAME for Ubuntu."
+install_umu_deb() {
+  DEBIAN_ARCH="$(dpkg --print-architecture)"
+  if [ "$DEBIAN_ARCH" != "amd64" ] && [ "$DEBIAN_ARCH" != "arm64" ] && [ "$DEBIAN_ARCH" != "armhf" ]; then
+    echo "Unsupported architecture: $DEBIAN_ARCH"
     exit 1
   fi
-  DISTRO_TAG="ubuntu-${VERSION_CODENAME}"
-fi
 
-UMU_API="https://api.github.com/repos/Open-Wine-Components/umu-launcher/releases/latest"
-ASSET_INFO="$(
-  curl -sL "$UMU_API" | DEBIAN_ARCH="$DEBIAN_ARCH" DISTRO_TAG="$DISTRO_TAG" python3 -c '
+  if [ "${ID}" = "debian" ]; then
+    DISTRO_TAG="debian-${VERSION_ID}"
+  else
+    if [ -z "${VERSION_CODENAME:-}" ]; then
+      echo "Missing VERSION_CODENAME for Ubuntu."
+      exit 1
+    fi
+    DISTRO_TAG="ubuntu-${VERSION_CODENAME}"
+  fi
+
+  UMU_API="https://api.github.com/repos/Open-Wine-Components/umu-launcher/releases/latest"
+  ASSET_INFO="$(
+    curl -sL "$UMU_API" | DEBIAN_ARCH="$DEBIAN_ARCH" DISTRO_TAG="$DISTRO_TAG" python3 -c '
 import json, os, sys
 data = json.load(sys.stdin)
 assets = data.get("assets", [])
@@ -116,30 +152,51 @@ if selected is None:
 
 print("{}|{}".format(selected["name"], selected["browser_download_url"]))
 '
-)"
+  )"
 
-if [ -z "$ASSET_INFO" ]; then
-  echo "No matching UMU .deb found for ${DISTRO_TAG} (${DEBIAN_ARCH})."
-  exit 1
-fi
+  if [ -z "$ASSET_INFO" ]; then
+    echo "No matching UMU .deb found for ${DISTRO_TAG} (${DEBIAN_ARCH})."
+    exit 1
+  fi
+
+  UMU_NAME="${ASSET_INFO%%|*}"
+  UMU_URL="${ASSET_INFO#*|}"
 
-UMU_NAME="${ASSET_INFO%%|*}"
-UMU_URL="${ASSET_INFO#*|}"
+  TMP_DIR="$(mktemp -d -t linuxboy-umu-XXXXXX)"
+  UMU_DEB="${TMP_DIR}/${UMU_NAME}"
 
-TMP_DIR="$(mktemp -d -t linuxboy-umu-XXXXXX)"
-UMU_DEB="${TMP_DIR}/${UMU_NAME}"
+  echo "Downloading ${UMU_NAME}..."
+  curl -L -o "$UMU_DEB" "$UMU_URL"
 
-echo "Downloading ${UMU_NAME}..."
-curl -L -o "$UMU_DEB" "$UMU_URL"
+  echo "Installing UMU..."
+  pkg_install "$UMU_DEB"
 
-echo "Installing UMU..."
-apt_install "$UMU_DEB"
+  rm -rf "$TMP_DIR"
+}
 
-rm -rf "$TMP_DIR"
+case "$PKG" in
+  apt) setup_apt ;;
+  pacman) setup_pacman ;;
+esac
 
 CACHE_DIR="$HOME/.linuxboy/cache/deps"
 mkdir -p "$CACHE_DIR"
 
+# Honour the download speed limit set in LinuxBoy.
+CURL_LIMIT=""
+RATE_KBPS="$(python3 -c '
+import json, os, sys
+try:
+    with open(os.path.expanduser("~/.linuxboy/downloads.json")) as f:
+        rate = json.load(f).get("max_rate_kbps") or 0
+except (OSError, ValueError):
+    rate = 0
+print(int(rate))
+' 2>/dev/null || echo 0)"
+if [ "${RATE_KBPS:-0}" -gt 0 ]; then
+  CURL_LIMIT="--limit-rate ${RATE_KBPS}k"
+fi
+
 download_if_missing() {
   url="$1"
   dest="$2"
@@ -147,13 +204,13 @@ download_if_missing() {
 
   if [ "$REINSTALL" -eq 1 ] || [ ! -f "$dest" ]; then
     if [ "$insecure" -eq 1 ]; then
-      if ! curl -L -k -o "$dest" "$url"; then
+      if ! curl -L -k $CURL_LIMIT -o "$dest" "$url"; then
         echo "Failed to download: $url"
         rm -f "$dest"
         return 1
       fi
     else
-      if ! curl -L -o "$dest" "$url"; then
+      if ! curl -L $CURL_LIMIT -o "$dest" "$url"; then
         echo "Failed to download: $url"
         rm -f "$dest"
         return 1
@@ -202,7 +259,7 @@ fi
 if [ -f "./Cargo.toml" ]; then
   if ! command -v cargo >/dev/null 2>&1; then
     echo "cargo not found, installing..."
-    apt_install cargo
+    pkg_install cargo
   fi
 
   echo "Building LinuxBoy..."
@@ -0,0 +1,354 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::capsule::Capsule;
+use crate::core::network;
+use crate::core::system_checker::SystemCheck;
+use crate::utils::{file_ops, host};
+
+const APPIMAGETOOL_URL: &str = "https://github.com/AppImage/appimagetool/releases/download/continuous/appimagetool-x86_64.AppImage";
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+/// Folder inside the AppDir holding the game's files.
+const GAME_DIR: &str = "game";
+/// umu-run downloads the latest GE-Proton for this value, so the AppImage
+/// runs on machines without LinuxBoy.
+const DEFAULT_PROTONPATH: &str = "GE-Proton";
+const UMU_URL: &str = "https://github.com/Open-Wine-Components/umu-launcher";
+const ICON_NAME: &str = "linuxboy-game";
+const ICON_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="256" height="256" viewBox="0 0 256 256">
+<rect x="16" y="64" width="224" height="128" rx="56" fill="#2e3440"/>
+<rect x="56" y="116" width="48" height="16" rx="4" fill="#eceff4"/>
+<rect x="72" y="100" width="16" height="48" rx="4" fill="#eceff4"/>
+<circle cx="172" cy="112" r="12" fill="#88c0d0"/>
+<circle cx="196" cy="136" r="12" fill="#a3be8c"/>
+</svg>
+"##;
+
+/// appimagetool, either from PATH or downloaded into the runtimes folder.
+pub struct AppImageTool {
+    path: PathBuf,
+}
+
+impl AppImageTool {
+    pub fn cache_path() -> PathBuf {
+        SystemCheck::get_runtimes_dir().join("appimagetool")
+    }
+
+    /// Prefer a system appimagetool, then a previously downloaded copy.
+    pub fn locate() -> Option<Self> {
+        let system = Command::new("which")
+            .arg("appimagetool")
+            .output()
+            .ok()
+            // The sandbox's copy can't be run on the host.
+            .filter(|_| !host::is_flatpak())
+            .filter(|output| output.status.success())
+            .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+            .filter(|path| path.is_file());
+        if let Some(path) = system {
+            return Some(Self { path });
+        }
+
+        let cached = Self::cache_path();
+        if cached.is_file() {
+            Some(Self { path: cached })
+        } else {
+            None
+        }
+    }
+
+    /// Download the continuous appimagetool build into the runtimes folder.
+    pub fn download() -> Result<Self> {
+        let dest = Self::cache_path();
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        println!("Downloading appimagetool from {}", APPIMAGETOOL_URL);
+        let client = network::client_builder()?.build()?;
+        let mut response = client.get(APPIMAGETOOL_URL).send()?;
+        if !response.status().is_success() {
+            anyhow::bail!("Download failed with status: {}", response.status());
+        }
+
+        let partial = dest.with_file_name("appimagetool.part");
+        let mut file = fs::File::create(&partial)
+            .with_context(|| format!("Failed to create {:?}", partial))?;
+        let mut buffer = vec![0u8; 256 * 1024];
+        let mut header = Vec::new();
+        loop {
+            let bytes_read = response.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            if header.len() < ELF_MAGIC.len() {
+                header.extend_from_slice(&buffer[..bytes_read]);
+            }
+            file.write_all(&buffer[..bytes_read])?;
+        }
+        drop(file);
+        if !header.starts_with(&ELF_MAGIC) {
+            let _ = fs::remove_file(&partial);
+            anyhow::bail!("Downloaded appimagetool is not an executable");
+        }
+
+        fs::set_permissions(&partial, fs::Permissions::from_mode(0o755))?;
+        fs::rename(&partial, &dest).context("Failed to install appimagetool")?;
+        Ok(Self { path: dest })
+    }
+
+    pub fn locate_or_download() -> Result<Self> {
+        match Self::locate() {
+            Some(tool) => Ok(tool),
+            None => Self::download(),
+        }
+    }
+
+    /// Command that squashes `app_dir` into the AppImage at `dest`.
+    fn command(&self, app_dir: &Path, dest: &Path) -> Command {
+        let mut cmd = Command::new(&self.path);
+        cmd.env("ARCH", "x86_64");
+        // appimagetool is an AppImage too; this runs it without FUSE.
+        cmd.env("APPIMAGE_EXTRACT_AND_RUN", "1");
+        cmd.arg("--no-appstream");
+        cmd.arg(app_dir);
+        cmd.arg(dest);
+        host::command(cmd)
+    }
+}
+
+/// Packs a capsule's game files, metadata and a launcher script that runs
+/// the game through umu-run into a single AppImage. The Wine prefix is not
+/// included: the AppImage is read-only, so the launcher creates one under
+/// the user's data folder on first run.
+pub struct AppImageBuilder;
+
+impl AppImageBuilder {
+    /// Build the AppImage at `dest`, downloading appimagetool first if
+    /// needed. `on_progress(copied, total)` follows the game file copy and
+    /// returns false to cancel.
+    pub fn build(
+        capsule: &Capsule,
+        dest: &Path,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<()> {
+        let game_folder = capsule
+            .game_folder()
+            .context("The game has no folder to package")?;
+        let exe = PathBuf::from(capsule.metadata.executables.main.path.trim());
+        let relative_exe = exe
+            .strip_prefix(&game_folder)
+            .context("The main executable is outside the game folder")?
+            .to_path_buf();
+        let tool = AppImageTool::locate_or_download()?;
+
+        let app_dir = dest.with_file_name(format!(".{}.AppDir", Self::slug(&capsule.name)));
+        if app_dir.exists() {
+            fs::remove_dir_all(&app_dir)
+                .with_context(|| format!("Failed to clear {:?}", app_dir))?;
+        }
+        let result =
+            Self::build_app_dir(capsule, &game_folder, &relative_exe, &app_dir, on_progress)
+                .and_then(|_| Self::squash(&tool, &app_dir, dest));
+        let _ = fs::remove_dir_all(&app_dir);
+        result
+    }
+
+    fn build_app_dir(
+        capsule: &Capsule,
+        game_folder: &Path,
+        relative_exe: &Path,
+        app_dir: &Path,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<()> {
+        let total = file_ops::dir_size(game_folder);
+        // The staged copy plus an AppImage that is rarely much smaller.
+        file_ops::ensure_free_space(app_dir, total.saturating_mul(2))?;
+
+        let mut copied = 0u64;
+        file_ops::copy_dir_with_progress(game_folder, &app_dir.join(GAME_DIR), &mut |chunk| {
+            copied += chunk;
+            on_progress(copied, total)
+        })
+        .context("Failed to copy the game files")?;
+
+        let mut metadata = capsule.metadata.clone();
+        Capsule::rebase_metadata_paths(&mut metadata, game_folder, Path::new(GAME_DIR));
+        let content =
+            serde_json::to_string_pretty(&metadata).context("Failed to serialize metadata")?;
+        fs::write(app_dir.join("metadata.json"), content)
+            .context("Failed to write metadata.json")?;
+
+        let app_run = app_dir.join("AppRun");
+        // The AppImage is read-only, so the prefix lives in the user's data folder.
+        let prefix = format!(
+            "\"${{WINEPREFIX:-${{XDG_DATA_HOME:-$HOME/.local/share}}/linuxboy-appimage/{}/prefix}}\"",
+            Self::slug(&capsule.name)
+        );
+        let script =
+            Self::launcher_script(capsule, &Path::new(GAME_DIR).join(relative_exe), &prefix);
+        fs::write(&app_run, script).context("Failed to write the launcher script")?;
+        fs::set_permissions(&app_run, fs::Permissions::from_mode(0o755))?;
+
+        fs::write(
+            app_dir.join(format!("{}.desktop", ICON_NAME)),
+            Self::desktop_entry(&capsule.name),
+        )
+        .context("Failed to write the desktop entry")?;
+        fs::write(app_dir.join(format!("{}.svg", ICON_NAME)), ICON_SVG)
+            .context("Failed to write the icon")?;
+        Ok(())
+    }
+
+    fn squash(tool: &AppImageTool, app_dir: &Path, dest: &Path) -> Result<()> {
+        let output = tool
+            .command(app_dir, dest)
+            .output()
+            .context("Failed to run appimagetool")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+            anyhow::bail!(
+                "appimagetool failed: {}",
+                tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    /// A shell script that sets up the game's umu environment, then runs
+    /// `exe` (relative to the script's folder, or the mounted AppImage) with
+    /// `prefix`, a shell word, as the Wine prefix.
+    pub fn launcher_script(capsule: &Capsule, exe: &Path, prefix: &str) -> String {
+        let metadata = &capsule.metadata;
+        // A start folder from the shortcut only survives packaging when it
+        // is inside the bundled files.
+        let work_dir = metadata
+            .executables
+            .main
+            .working_dir
+            .as_deref()
+            .map(Path::new)
+            .filter(|dir| dir.is_relative())
+            .or_else(|| exe.parent())
+            .unwrap_or(Path::new(""));
+
+        let mut script = String::from("#!/bin/sh\n");
+        script.push_str(&format!(
+            "# Generated by LinuxBoy. Runs {} through umu-run.\n",
+            capsule.name
+        ));
+        script.push_str("HERE=\"${APPDIR:-$(dirname \"$(readlink -f \"$0\")\")}\"\n");
+        script.push_str(&format!("export WINEPREFIX={}\n", prefix));
+        script.push_str(&format!(
+            "export PROTONPATH=\"${{PROTONPATH:-{}}}\"\n",
+            DEFAULT_PROTONPATH
+        ));
+        for (key, value) in metadata.umu_env() {
+            if Self::is_env_name(&key) {
+                script.push_str(&format!("export {}={}\n", key, Self::shell_quote(&value)));
+            }
+        }
+        script.push_str(&format!(
+            "\nif ! command -v umu-run >/dev/null 2>&1; then\n    \
+             echo {} >&2\n    exit 1\nfi\n",
+            Self::shell_quote(&format!(
+                "umu-run is needed to play {}: {}",
+                capsule.name, UMU_URL
+            ))
+        ));
+        script.push_str("mkdir -p \"$WINEPREFIX\"\n");
+        script.push_str(&format!(
+            "cd \"$HERE\"/{} || exit 1\n",
+            Self::shell_quote(&work_dir.to_string_lossy())
+        ));
+
+        let mut command = format!(
+            "exec umu-run \"$HERE\"/{}",
+            Self::shell_quote(&exe.to_string_lossy())
+        );
+        let mut args: Vec<String> = metadata
+            .executables
+            .main
+            .args
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        args.extend(
+            metadata
+                .protonfixes_tricks
+                .iter()
+                .map(|trick| format!("-pf_tricks={}", trick)),
+        );
+        args.extend(
+            metadata
+                .protonfixes_replace_cmds
+                .iter()
+                .map(|replace| format!("-pf_replace_cmd={}", replace)),
+        );
+        // No MangoHud outside LinuxBoy, so the FPS cap always goes to DXVK.
+        args.extend(
+            metadata
+                .protonfixes_dxvk_sets
+                .iter()
+                .cloned()
+                .chain(metadata.fps_limit_dxvk_sets())
+                .map(|option| format!("-pf_dxvk_set={}", option)),
+        );
+        for arg in args {
+            command.push(' ');
+            command.push_str(&Self::shell_quote(&arg));
+        }
+        command.push_str(" \"$@\"\n");
+        script.push_str(&command);
+        script
+    }
+
+    fn desktop_entry(name: &str) -> String {
+        format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec=AppRun\nIcon={}\n\
+             Categories=Game;\nTerminal=false\n",
+            name.replace('\n', " "),
+            ICON_NAME
+        )
+    }
+
+    pub fn shell_quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+
+    /// Custom env var names come from the user; skip ones `export` rejects.
+    fn is_env_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        matches!(chars.next(), Some(first) if first == '_' || first.is_ascii_alphabetic())
+            && chars.all(|ch| ch == '_' || ch.is_ascii_alphanumeric())
+    }
+
+    /// File-name-safe form of the game name, e.g. for the prefix folder.
+    fn slug(name: &str) -> String {
+        let slug: String = name
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() {
+                    ch.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let slug = slug
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        if slug.is_empty() {
+            "game".to_string()
+        } else {
+            slug
+        }
+    }
+}
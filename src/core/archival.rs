@@ -0,0 +1,201 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::core::backup::Codec;
+use crate::core::capsule::{Capsule, InstallState};
+use crate::utils::file_ops;
+
+const ARCHIVE_FILE: &str = "game-files.tar.zst";
+const INFO_FILE: &str = "game-files.json";
+
+/// What was packed, stored next to the archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveInfo {
+    /// The game's install folder, relative to the prefix.
+    pub root: PathBuf,
+    /// Size of the folder before it was packed.
+    pub unpacked_size: u64,
+    pub packed_size: u64,
+    pub archived_at: String,
+}
+
+/// Packs the game folder of a capsule into a zstd archive in its home, so
+/// large games that are rarely played take less space. The prefix stays as
+/// it is; only the install folder found by `Capsule::install_root` is packed.
+pub struct GameArchive;
+
+impl GameArchive {
+    pub fn archive_path(capsule: &Capsule) -> PathBuf {
+        capsule.home_path.join(ARCHIVE_FILE)
+    }
+
+    fn info_path(capsule: &Capsule) -> PathBuf {
+        capsule.home_path.join(INFO_FILE)
+    }
+
+    pub fn info(capsule: &Capsule) -> Option<ArchiveInfo> {
+        fs::read_to_string(Self::info_path(capsule))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    /// The folder `archive` packs, or why the game can't be archived.
+    pub fn game_root(capsule: &Capsule) -> Result<PathBuf> {
+        if capsule.metadata.install_state != InstallState::Installed {
+            anyhow::bail!("{} is not installed", capsule.name);
+        }
+        let root = capsule.install_root().with_context(|| {
+            format!(
+                "No game files of {} found inside its prefix; only those games can be archived",
+                capsule.name
+            )
+        })?;
+        // Installers given the default game folder leave it empty.
+        let has_files = WalkDir::new(&root)
+            .follow_links(false)
+            .into_iter()
+            .flatten()
+            .any(|entry| entry.file_type().is_file());
+        if !has_files {
+            anyhow::bail!("{:?} holds no game files to archive", root);
+        }
+        Ok(root)
+    }
+
+    /// Pack the game folder, mark the capsule archived and remove the
+    /// folder. `on_chunk` gets the bytes of each packed file and returns
+    /// false to cancel.
+    pub fn archive(
+        capsule: &mut Capsule,
+        on_chunk: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<ArchiveInfo> {
+        let root = Self::game_root(capsule)?;
+        let prefix_path = fs::canonicalize(capsule.home_path.join("prefix"))
+            .context("Failed to resolve the prefix")?;
+        let relative = root
+            .strip_prefix(&prefix_path)
+            .context("Game folder is outside the prefix")?
+            .to_path_buf();
+        let unpacked_size = file_ops::dir_size(&root);
+
+        let archive_path = Self::archive_path(capsule);
+        let partial_path = archive_path.with_extension("zst.part");
+        if let Err(e) = Self::write_archive(&prefix_path, &root, &partial_path, on_chunk) {
+            let _ = fs::remove_file(&partial_path);
+            return Err(e);
+        }
+        fs::rename(&partial_path, &archive_path).context("Failed to finalize game archive")?;
+
+        let info = ArchiveInfo {
+            root: relative,
+            unpacked_size,
+            packed_size: archive_path.metadata().map(|meta| meta.len()).unwrap_or(0),
+            archived_at: chrono::Local::now().to_rfc3339(),
+        };
+        let content =
+            serde_json::to_string_pretty(&info).context("Failed to serialize archive info")?;
+        fs::write(Self::info_path(capsule), content).context("Failed to write archive info")?;
+
+        // From here on the archive is the copy to keep; a folder that fails
+        // to go away completely is overwritten by the next unpack.
+        capsule.metadata.install_state = InstallState::Archived;
+        capsule.save_metadata()?;
+        fs::remove_dir_all(&root).with_context(|| format!("Failed to remove {:?}", root))?;
+        Ok(info)
+    }
+
+    /// Extract the game folder back into the prefix and mark the capsule
+    /// installed again. A cancelled or failed unpack removes what was
+    /// extracted and keeps the archive.
+    pub fn unpack(capsule: &mut Capsule, on_chunk: &mut dyn FnMut(u64) -> bool) -> Result<()> {
+        if capsule.metadata.install_state != InstallState::Archived {
+            anyhow::bail!("{} is not archived", capsule.name);
+        }
+        let archive_path = Self::archive_path(capsule);
+        if !archive_path.is_file() {
+            anyhow::bail!("Game archive not found at {:?}", archive_path);
+        }
+        let prefix_path = capsule.home_path.join("prefix");
+        let info = Self::info(capsule);
+        if let Some(info) = &info {
+            file_ops::ensure_free_space(&prefix_path, info.unpacked_size)?;
+        }
+
+        if let Err(e) = Self::extract(&archive_path, &prefix_path, on_chunk) {
+            if let Some(info) = &info {
+                let _ = fs::remove_dir_all(prefix_path.join(&info.root));
+            }
+            return Err(e);
+        }
+
+        capsule.metadata.install_state = InstallState::Installed;
+        capsule.save_metadata()?;
+        fs::remove_file(&archive_path)
+            .with_context(|| format!("Failed to remove {:?}", archive_path))?;
+        let _ = fs::remove_file(Self::info_path(capsule));
+        Ok(())
+    }
+
+    fn write_archive(
+        prefix_path: &Path,
+        root: &Path,
+        dest: &Path,
+        on_chunk: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<()> {
+        let file = File::create(dest).with_context(|| format!("Failed to create {:?}", dest))?;
+        let encoder = zstd::stream::write::Encoder::new(file, Codec::Zstd.default_level())?;
+        let mut builder = tar::Builder::new(encoder);
+        builder.follow_symlinks(false);
+
+        // Entries are named relative to the prefix, so the archive unpacks
+        // straight back into place.
+        for entry in WalkDir::new(root).follow_links(false) {
+            let entry = entry?;
+            let relative = entry
+                .path()
+                .strip_prefix(prefix_path)
+                .unwrap_or(entry.path());
+            builder
+                .append_path_with_name(entry.path(), relative)
+                .with_context(|| format!("Failed to archive {:?}", entry.path()))?;
+            let size = if entry.file_type().is_file() {
+                entry.metadata().map(|meta| meta.len()).unwrap_or(0)
+            } else {
+                0
+            };
+            if !on_chunk(size) {
+                anyhow::bail!("Archiving cancelled");
+            }
+        }
+
+        let encoder = builder.into_inner()?;
+        encoder.finish().context("Failed to finish game archive")?;
+        Ok(())
+    }
+
+    fn extract(
+        archive_path: &Path,
+        prefix_path: &Path,
+        on_chunk: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<()> {
+        let file = File::open(archive_path)
+            .with_context(|| format!("Failed to open {:?}", archive_path))?;
+        let decoder = zstd::stream::read::Decoder::new(file)?;
+        let mut archive = tar::Archive::new(decoder);
+        archive.set_preserve_permissions(true);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let size = entry.header().size().unwrap_or(0);
+            entry
+                .unpack_in(prefix_path)
+                .context("Failed to extract game archive")?;
+            if !on_chunk(size) {
+                anyhow::bail!("Unpacking cancelled");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,725 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+use crate::core::capsule::Capsule;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+const MANIFEST_VERSION: u32 = 1;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Codec {
+    #[default]
+    Gzip,
+    Zstd,
+}
+
+impl Codec {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Codec::Gzip => "tar.gz",
+            Codec::Zstd => "tar.zst",
+        }
+    }
+
+    pub fn level_range(&self) -> (i32, i32) {
+        match self {
+            Codec::Gzip => (1, 9),
+            Codec::Zstd => (1, 19),
+        }
+    }
+
+    pub fn default_level(&self) -> i32 {
+        match self {
+            Codec::Gzip => 6,
+            Codec::Zstd => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionOptions {
+    pub codec: Codec,
+    pub level: i32,
+}
+
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        Self {
+            codec: Codec::Zstd,
+            level: Codec::Zstd.default_level(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKind {
+    Full,
+    Incremental,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub kind: EntryKind,
+    pub size: u64,
+    pub modified: i64,
+    /// File name of the archive holding this entry's data. Directories are
+    /// recreated from the manifest and always point at the current archive.
+    pub archive: String,
+    /// SHA-256 of regular files, checked by `verify`.
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    pub archives: usize,
+    pub checked: usize,
+    pub missing: Vec<String>,
+    pub corrupt: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.corrupt.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        if self.is_ok() {
+            format!(
+                "Backup OK: {} file(s) checked across {} archive(s)",
+                self.checked, self.archives
+            )
+        } else {
+            format!(
+                "Backup damaged: {} corrupt, {} missing of {} file(s)",
+                self.corrupt.len(),
+                self.missing.len(),
+                self.checked + self.missing.len()
+            )
+        }
+    }
+}
+
+/// Stored as the first member of every backup archive. `files` always lists
+/// the whole capsule as it was when the backup was taken, so the newest
+/// manifest in a chain is enough to restore it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub version: u32,
+    pub capsule_name: String,
+    pub source_dir: PathBuf,
+    pub created_at: String,
+    pub kind: BackupKind,
+    /// Archive this incremental backup was taken against.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Informational; readers detect the codec from the archive header.
+    #[serde(default)]
+    pub codec: Codec,
+    #[serde(default)]
+    pub compression_level: i32,
+    pub files: Vec<ManifestEntry>,
+}
+
+/// Counts bytes as they are read and stops with an error once `on_bytes`
+/// returns `false`.
+struct ProgressReader<'a, R> {
+    inner: R,
+    on_bytes: &'a mut dyn FnMut(u64) -> bool,
+    cancelled: bool,
+}
+
+impl<'a, R: Read> ProgressReader<'a, R> {
+    fn new(inner: R, on_bytes: &'a mut dyn FnMut(u64) -> bool) -> Self {
+        Self {
+            inner,
+            on_bytes,
+            cancelled: false,
+        }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if !(self.on_bytes)(read as u64) {
+            self.cancelled = true;
+            return Err(std::io::Error::other("Cancelled"));
+        }
+        Ok(read)
+    }
+}
+
+/// Capsule backups as `.tar.gz` or `.tar.zst` archives. Incremental backups
+/// only store files whose size or mtime changed since their base and must be
+/// kept in the same folder as the archives they build on.
+///
+/// Long-running calls take `on_progress(done, total)` in bytes; returning
+/// `false` cancels.
+pub struct BackupManager;
+
+impl BackupManager {
+    /// Back up the whole capsule, or only what changed since `base` when given.
+    /// The archive is re-read and checked against the manifest before it is
+    /// moved into place.
+    pub fn create_archive(
+        capsule: &Capsule,
+        dest: &Path,
+        base: Option<&Path>,
+        compression: CompressionOptions,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<ExportManifest> {
+        let source_dir = fs::canonicalize(&capsule.capsule_dir)
+            .with_context(|| format!("Failed to resolve {:?}", capsule.capsule_dir))?;
+        let archive_name = Self::archive_name(dest)?;
+
+        let base_manifest = match base {
+            Some(base) => {
+                if base.parent() != dest.parent() {
+                    anyhow::bail!("Incremental backups must be stored next to their base archive");
+                }
+                let manifest = Self::read_manifest(base)?;
+                if manifest.capsule_name != capsule.name {
+                    anyhow::bail!(
+                        "{:?} is a backup of {}, not {}",
+                        base,
+                        manifest.capsule_name,
+                        capsule.name
+                    );
+                }
+                Some((Self::archive_name(base)?, manifest))
+            }
+            None => None,
+        };
+        let previous: HashMap<&str, &ManifestEntry> = base_manifest
+            .as_ref()
+            .map(|(_, manifest)| {
+                manifest
+                    .files
+                    .iter()
+                    .map(|entry| (entry.path.as_str(), entry))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let excluded = capsule.home_path.join("snapshots");
+        let excluded = fs::canonicalize(&excluded).unwrap_or(excluded);
+        let mut files = Vec::new();
+        let walker = WalkDir::new(&source_dir)
+            .follow_links(false)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| entry.path() != excluded);
+        for entry in walker {
+            let entry = entry?;
+            let relative = entry.path().strip_prefix(&source_dir).unwrap_or(entry.path());
+            if relative.as_os_str().is_empty() {
+                continue;
+            }
+            let metadata = entry.path().symlink_metadata()?;
+            let kind = if metadata.file_type().is_symlink() {
+                EntryKind::Symlink
+            } else if metadata.is_dir() {
+                EntryKind::Dir
+            } else {
+                EntryKind::File
+            };
+            let size = if kind == EntryKind::File { metadata.len() } else { 0 };
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or(0);
+            let path = relative.to_string_lossy().to_string();
+
+            let (archive, sha256) = match previous.get(path.as_str()) {
+                Some(old)
+                    if kind != EntryKind::Dir
+                        && old.kind == kind
+                        && old.size == size
+                        && old.modified == modified =>
+                {
+                    (old.archive.clone(), old.sha256.clone())
+                }
+                _ => (archive_name.clone(), None),
+            };
+            files.push(ManifestEntry {
+                path,
+                kind,
+                size,
+                modified,
+                archive,
+                sha256,
+            });
+        }
+
+        let mut manifest = ExportManifest {
+            version: MANIFEST_VERSION,
+            capsule_name: capsule.name.clone(),
+            source_dir: source_dir.clone(),
+            created_at: chrono::Local::now().to_rfc3339(),
+            kind: if base_manifest.is_some() {
+                BackupKind::Incremental
+            } else {
+                BackupKind::Full
+            },
+            base: base_manifest.map(|(name, _)| name),
+            codec: compression.codec,
+            compression_level: compression.level,
+            files,
+        };
+
+        // Changed files are read three times: hashing, archiving, verifying.
+        let stored: u64 = manifest
+            .files
+            .iter()
+            .filter(|entry| entry.kind == EntryKind::File && entry.archive == archive_name)
+            .map(|entry| entry.size)
+            .sum();
+        let total = stored * 3;
+        let mut done: u64 = 0;
+        let mut on_bytes = |bytes: u64| {
+            done += bytes;
+            on_progress(done, total)
+        };
+
+        for entry in manifest.files.iter_mut() {
+            if entry.kind != EntryKind::File || entry.archive != archive_name {
+                continue;
+            }
+            let path = source_dir.join(&entry.path);
+            let file = File::open(&path).with_context(|| format!("Failed to read {:?}", path))?;
+            entry.sha256 = Some(Self::sha256(file, &mut on_bytes)?);
+        }
+
+        let partial = dest.with_extension("part");
+        let written =
+            Self::write_archive(&source_dir, &manifest, &archive_name, &partial, &mut on_bytes)
+            .and_then(|_| {
+                let mut report = VerifyReport::default();
+                Self::verify_entries(
+                    &manifest,
+                    &archive_name,
+                    &partial,
+                    &mut report,
+                    &mut on_bytes,
+                )?;
+                if !report.is_ok() {
+                    anyhow::bail!(
+                        "Verification failed: {}. Files may have changed while backing up.",
+                        report.summary()
+                    );
+                }
+                Ok(())
+            });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+        fs::rename(&partial, dest).context("Failed to finalize backup archive")?;
+        println!(
+            "Created {:?} backup of {} at {:?}",
+            manifest.kind, capsule.name, dest
+        );
+        Ok(manifest)
+    }
+
+    pub fn read_manifest(archive_path: &Path) -> Result<ExportManifest> {
+        let mut archive = Self::open_archive(archive_path)?;
+        let mut entries = archive.entries()?;
+        let mut entry = entries
+            .next()
+            .context("Backup archive is empty")??;
+        if entry.path()?.as_ref() != Path::new(MANIFEST_NAME) {
+            anyhow::bail!("{:?} is not a LinuxBoy backup", archive_path);
+        }
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        let manifest: ExportManifest =
+            serde_json::from_str(&content).context("Failed to parse backup manifest")?;
+        if manifest.version > MANIFEST_VERSION {
+            anyhow::bail!("Backup was made by a newer LinuxBoy version");
+        }
+        Ok(manifest)
+    }
+
+    /// Find the newest backup of `capsule_name` in `dir`, to use as the base
+    /// of an incremental backup.
+    pub fn latest_backup(dir: &Path, capsule_name: &str) -> Option<PathBuf> {
+        let mut newest: Option<(String, PathBuf)> = None;
+        for entry in fs::read_dir(dir).ok()?.flatten() {
+            let path = entry.path();
+            let name = path.file_name()?.to_string_lossy().to_string();
+            if !name.ends_with(Codec::Gzip.extension()) && !name.ends_with(Codec::Zstd.extension())
+            {
+                continue;
+            }
+            let manifest = match Self::read_manifest(&path) {
+                Ok(manifest) if manifest.capsule_name == capsule_name => manifest,
+                _ => continue,
+            };
+            if newest
+                .as_ref()
+                .map(|(created_at, _)| manifest.created_at > *created_at)
+                .unwrap_or(true)
+            {
+                newest = Some((manifest.created_at, path));
+            }
+        }
+        newest.map(|(_, path)| path)
+    }
+
+    /// Re-read a backup and every archive it builds on, comparing file
+    /// contents against the checksums in the manifest.
+    pub fn verify(
+        archive_path: &Path,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<VerifyReport> {
+        let manifest = Self::read_manifest(archive_path)?;
+        let archive_dir = archive_path
+            .parent()
+            .context("Backup archive has no parent directory")?;
+
+        let mut archive_names: Vec<&str> = manifest
+            .files
+            .iter()
+            .filter(|entry| entry.kind != EntryKind::Dir)
+            .map(|entry| entry.archive.as_str())
+            .collect();
+        archive_names.sort_unstable();
+        archive_names.dedup();
+
+        let total: u64 = manifest.files.iter().map(|entry| entry.size).sum();
+        let mut done: u64 = 0;
+        let mut on_bytes = |bytes: u64| {
+            done += bytes;
+            on_progress(done, total)
+        };
+
+        let mut report = VerifyReport::default();
+        for archive_name in archive_names {
+            let path = archive_dir.join(archive_name);
+            if !path.is_file() {
+                report.missing.extend(
+                    manifest
+                        .files
+                        .iter()
+                        .filter(|entry| entry.kind != EntryKind::Dir && entry.archive == archive_name)
+                        .map(|entry| entry.path.clone()),
+                );
+                continue;
+            }
+            Self::verify_entries(&manifest, archive_name, &path, &mut report, &mut on_bytes)?;
+        }
+        Ok(report)
+    }
+
+    /// Restore a backup into `games_dir`, pulling unchanged files from the
+    /// archives an incremental backup builds on. Returns the new capsule
+    /// directory.
+    pub fn restore(
+        archive_path: &Path,
+        games_dir: &Path,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<PathBuf> {
+        let manifest = Self::read_manifest(archive_path)?;
+        let archive_dir = archive_path
+            .parent()
+            .context("Backup archive has no parent directory")?;
+
+        let target = games_dir.join(&manifest.capsule_name);
+        if target.exists() {
+            anyhow::bail!("{:?} already exists", target);
+        }
+        let staging = games_dir.join(format!(".{}.restore", manifest.capsule_name));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::create_dir_all(&staging)
+            .with_context(|| format!("Failed to create {:?}", staging))?;
+
+        if let Err(e) = Self::extract_chain(&manifest, archive_dir, &staging, on_progress) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+        if let Err(e) = fs::rename(&staging, &target) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e).context("Failed to move the restored capsule into place");
+        }
+
+        let mut capsule = Capsule::load_from_dir(&target)?;
+        Capsule::rebase_metadata_paths(&mut capsule.metadata, &manifest.source_dir, &target);
+        capsule.save_metadata()?;
+        println!("Restored {} into {:?}", capsule.name, target);
+        Ok(target)
+    }
+
+    fn extract_chain(
+        manifest: &ExportManifest,
+        archive_dir: &Path,
+        dest: &Path,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<()> {
+        let total: u64 = manifest.files.iter().map(|entry| entry.size).sum();
+        let mut done: u64 = 0;
+        let mut wanted: HashMap<&str, HashSet<PathBuf>> = HashMap::new();
+        for entry in &manifest.files {
+            let relative = Self::safe_relative(&entry.path)?;
+            if entry.kind == EntryKind::Dir {
+                fs::create_dir_all(dest.join(&relative))?;
+            } else {
+                wanted
+                    .entry(entry.archive.as_str())
+                    .or_default()
+                    .insert(relative);
+            }
+        }
+
+        for (archive_name, paths) in wanted {
+            let archive_path = archive_dir.join(archive_name);
+            if !archive_path.is_file() {
+                anyhow::bail!("Backup {:?} needed for this restore is missing", archive_path);
+            }
+            let mut archive = Self::open_archive(&archive_path)?;
+            let mut found = 0;
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let path = entry.path()?.to_path_buf();
+                let relative = match path.strip_prefix(FILES_DIR) {
+                    Ok(relative) => relative.to_path_buf(),
+                    Err(_) => continue,
+                };
+                if !paths.contains(&relative) {
+                    continue;
+                }
+                let target = dest.join(&relative);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                entry
+                    .unpack(&target)
+                    .with_context(|| format!("Failed to extract {:?}", relative))?;
+                found += 1;
+                done += entry.size();
+                if !on_progress(done, total) {
+                    anyhow::bail!("Cancelled");
+                }
+            }
+            if found != paths.len() {
+                anyhow::bail!(
+                    "{:?} is missing {} file(s) needed for this restore",
+                    archive_path,
+                    paths.len() - found
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the entries of `manifest` stored in `archive_name`, reading them
+    /// from `path`.
+    fn verify_entries(
+        manifest: &ExportManifest,
+        archive_name: &str,
+        path: &Path,
+        report: &mut VerifyReport,
+        on_bytes: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<()> {
+        let mut expected: HashMap<PathBuf, &ManifestEntry> = manifest
+            .files
+            .iter()
+            .filter(|entry| entry.kind != EntryKind::Dir && entry.archive == archive_name)
+            .map(|entry| (PathBuf::from(&entry.path), entry))
+            .collect();
+        report.archives += 1;
+
+        let mut archive = Self::open_archive(path)?;
+        for entry in archive.entries()? {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    // Everything after this point counts as missing.
+                    eprintln!("Failed to read {:?}: {}", path, e);
+                    break;
+                }
+            };
+            let entry_path = entry.path()?.to_path_buf();
+            let relative = match entry_path.strip_prefix(FILES_DIR) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => continue,
+            };
+            let manifest_entry = match expected.remove(&relative) {
+                Some(manifest_entry) => manifest_entry,
+                None => continue,
+            };
+            report.checked += 1;
+            if let Some(expected_hash) = &manifest_entry.sha256 {
+                let mut reader = ProgressReader::new(entry, &mut *on_bytes);
+                let result = Self::sha256(&mut reader, &mut |_| true);
+                if reader.cancelled {
+                    anyhow::bail!("Cancelled");
+                }
+                match result {
+                    Ok(actual) if &actual == expected_hash => {}
+                    _ => report.corrupt.push(manifest_entry.path.clone()),
+                }
+            }
+        }
+
+        report
+            .missing
+            .extend(expected.into_values().map(|entry| entry.path.clone()));
+        Ok(())
+    }
+
+    fn sha256<R: Read>(mut reader: R, on_bytes: &mut dyn FnMut(u64) -> bool) -> Result<String> {
+        let mut hasher = Sha256::new();
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let bytes_read = reader.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+            if !on_bytes(bytes_read as u64) {
+                anyhow::bail!("Cancelled");
+            }
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+
+    fn write_archive(
+        source_dir: &Path,
+        manifest: &ExportManifest,
+        archive_name: &str,
+        dest: &Path,
+        on_bytes: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<()> {
+        let file = File::create(dest).with_context(|| format!("Failed to create {:?}", dest))?;
+        let (min, max) = manifest.codec.level_range();
+        let level = manifest.compression_level.clamp(min, max);
+        match manifest.codec {
+            Codec::Gzip => {
+                let encoder = GzEncoder::new(file, Compression::new(level as u32));
+                let encoder =
+                    Self::append_entries(encoder, source_dir, manifest, archive_name, on_bytes)?;
+                encoder.finish().context("Failed to finish backup archive")?;
+            }
+            Codec::Zstd => {
+                let encoder = zstd::stream::write::Encoder::new(file, level)?;
+                let encoder =
+                    Self::append_entries(encoder, source_dir, manifest, archive_name, on_bytes)?;
+                encoder.finish().context("Failed to finish backup archive")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn append_entries<W: Write>(
+        writer: W,
+        source_dir: &Path,
+        manifest: &ExportManifest,
+        archive_name: &str,
+        on_bytes: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<W> {
+        let mut builder = tar::Builder::new(writer);
+        builder.follow_symlinks(false);
+
+        let content = serde_json::to_vec_pretty(manifest)
+            .context("Failed to serialize backup manifest")?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Local::now().timestamp().max(0) as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, MANIFEST_NAME, content.as_slice())?;
+
+        for entry in &manifest.files {
+            if entry.kind == EntryKind::Dir || entry.archive != archive_name {
+                continue;
+            }
+            let path = source_dir.join(&entry.path);
+            let name = Path::new(FILES_DIR).join(&entry.path);
+            if entry.kind == EntryKind::Symlink {
+                builder
+                    .append_path_with_name(&path, &name)
+                    .with_context(|| format!("Failed to archive {:?}", entry.path))?;
+                continue;
+            }
+
+            let file = File::open(&path).with_context(|| format!("Failed to read {:?}", path))?;
+            let metadata = file.metadata()?;
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&metadata);
+            let mut reader = ProgressReader::new(file.take(metadata.len()), &mut *on_bytes);
+            let result = builder.append_data(&mut header, &name, &mut reader);
+            if reader.cancelled {
+                anyhow::bail!("Cancelled");
+            }
+            result.with_context(|| format!("Failed to archive {:?}", entry.path))?;
+        }
+
+        Ok(builder.into_inner()?)
+    }
+
+    /// Open a backup archive, picking the decoder from its header.
+    fn open_archive(archive_path: &Path) -> Result<tar::Archive<Box<dyn Read>>> {
+        let mut file = File::open(archive_path)
+            .with_context(|| format!("Failed to open {:?}", archive_path))?;
+        let mut magic = [0u8; 4];
+        let read = file.read(&mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let reader: Box<dyn Read> = if read >= 4 && magic == ZSTD_MAGIC {
+            Box::new(zstd::stream::read::Decoder::new(file)?)
+        } else if read >= 2 && magic[..2] == GZIP_MAGIC {
+            Box::new(GzDecoder::new(file))
+        } else {
+            anyhow::bail!("{:?} is not a gzip or zstd archive", archive_path);
+        };
+        Ok(tar::Archive::new(reader))
+    }
+
+    fn archive_name(path: &Path) -> Result<String> {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .with_context(|| format!("{:?} is not a file path", path))
+    }
+
+    /// Reject manifest paths that would escape the restore directory.
+    fn safe_relative(path: &str) -> Result<PathBuf> {
+        let relative = PathBuf::from(path);
+        if relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            anyhow::bail!("Unsafe path in backup manifest: {}", path);
+        }
+        Ok(relative)
+    }
+}
@@ -1,13 +1,45 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::fs;
+use std::io::Write;
+
+use crate::core::display_server::DisplaySession;
+use crate::core::igdb::IgdbInfo;
+use crate::utils::file_ops;
+
+const METADATA_FILE: &str = "metadata.json";
+/// The last metadata that parsed, kept by `save_metadata`.
+const METADATA_BACKUP: &str = "metadata.json.bak";
+/// Window size for safe-mode launches of games without a virtual desktop.
+const SAFE_MODE_DESKTOP: &str = "1280x720";
+/// Common `WINEDEBUG` channels and what they log.
+pub const WINEDEBUG_CHANNELS: [(&str, &str); 7] = [
+    ("+loaddll", "DLLs as they load"),
+    ("+seh", "Exceptions and crashes"),
+    ("warn+all", "Every warning"),
+    ("+relay", "Every API call (very slow)"),
+    ("+timestamp", "Timestamps"),
+    ("+pid", "Process IDs"),
+    ("fixme-all", "Hide fixme messages"),
+];
+/// Folders in a prefix that hold installed games rather than being one.
+const GAME_CONTAINER_DIRS: [&str; 6] = [
+    "drive_c",
+    "Program Files",
+    "Program Files (x86)",
+    "games",
+    "Games",
+    "GOG Games",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum InstallState {
     Installing,
     Installed,
+    /// The game folder is packed into an archive in the capsule's home.
+    Archived,
 }
 
 impl Default for InstallState {
@@ -26,8 +58,21 @@ pub struct Capsule {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CapsuleMetadata {
+    /// Names the capsule's folders on disk; see `title` for what the
+    /// library shows.
     pub name: String,
     pub executables: ExecutableConfig,
+    /// Shown in the library instead of `name`.
+    #[serde(default)]
+    pub display_title: Option<String>,
+    /// Sorts the library instead of the title, e.g. "Witcher 3" for
+    /// "The Witcher 3".
+    #[serde(default)]
+    pub sort_title: Option<String>,
+    #[serde(default)]
+    pub release_year: Option<u32>,
+    #[serde(default)]
+    pub developer: Option<String>,
     #[serde(default)]
     pub game_id: Option<String>,
     #[serde(default)]
@@ -39,29 +84,191 @@ pub struct CapsuleMetadata {
     #[serde(default)]
     pub protonfixes_tricks: Vec<String>,
     #[serde(default)]
+    pub winetricks_verbs: Vec<String>,
+    #[serde(default)]
     pub protonfixes_replace_cmds: Vec<String>,
     #[serde(default)]
     pub protonfixes_dxvk_sets: Vec<String>,
     #[serde(default)]
     pub xalia_enabled: bool,
+    /// Launch with GameMode's `libgamemodeauto` preloaded.
+    #[serde(default)]
+    pub gamemode_enabled: bool,
+    /// Show the MangoHud overlay.
+    #[serde(default)]
+    pub mangohud_enabled: bool,
+    /// Frame rate cap, applied through MangoHud when its overlay is on and
+    /// through DXVK otherwise.
+    #[serde(default)]
+    pub fps_limit: Option<u32>,
+    /// Run the game inside a gamescope session.
+    #[serde(default)]
+    pub gamescope_enabled: bool,
+    /// Pass `--hdr-enabled` to gamescope and turn on Proton's HDR.
+    #[serde(default)]
+    pub gamescope_hdr: bool,
+    /// Pass `--adaptive-sync` to gamescope.
+    #[serde(default)]
+    pub gamescope_vrr: bool,
+    /// Where this game's screenshots are kept; `None` uses `screenshots`
+    /// in the capsule's home.
+    #[serde(default)]
+    pub screenshots_dir: Option<String>,
+    /// Use Proton's native Wayland driver (`PROTON_ENABLE_WAYLAND`).
+    #[serde(default)]
+    pub proton_wayland: bool,
+    /// Keep the game on XWayland even in a Wayland session.
+    #[serde(default)]
+    pub force_x11: bool,
+    /// `SDL_VIDEODRIVER`, e.g. `x11` or `wayland`.
+    #[serde(default)]
+    pub sdl_video_driver: Option<String>,
+    /// Run the game in a bubblewrap sandbox that only sees its capsule and
+    /// the runtime.
+    #[serde(default)]
+    pub sandbox_enabled: bool,
+    /// Let a sandboxed game reach the network.
+    #[serde(default = "default_true")]
+    pub sandbox_network: bool,
+    /// Start the game without network access, sandboxed or not.
+    #[serde(default)]
+    pub block_network: bool,
+    /// Launch command replacing the built-in runner, with `{exe}`, `{args}`,
+    /// `{prefix}` and `{proton}` placeholders; see `CommandTemplate`.
+    #[serde(default)]
+    pub command_template: Option<String>,
+    /// `WINEDEBUG` channels for game launches, e.g. `+loaddll,+seh`.
+    #[serde(default)]
+    pub wine_debug: Option<String>,
+    /// Clear `wine_debug` after the next launch.
+    #[serde(default)]
+    pub wine_debug_once: bool,
     pub wine_version: Option<String>,
+    /// Prefix was created with `WINEARCH=win32` for installers that refuse 64-bit.
+    /// Only system Wine honours it; Proton always runs a 64-bit prefix.
+    #[serde(default)]
+    pub win32_prefix: bool,
     pub dxvk_enabled: bool,
     pub vkd3d_enabled: bool,
     pub env_vars: Vec<(String, String)>,
+    #[serde(default)]
+    pub dll_overrides: Vec<DllOverride>,
+    /// Virtual desktop size such as `1280x720`; `None` runs without one.
+    #[serde(default)]
+    pub virtual_desktop: Option<String>,
+    /// `LANG` for the game, e.g. `ja_JP.UTF-8`.
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub lc_all: Option<String>,
+    /// `TZ` for the game, e.g. `Asia/Tokyo`.
+    #[serde(default)]
+    pub timezone: Option<String>,
     #[serde(default = "default_true")]
     pub install_vcredist: bool,
     #[serde(default = "default_true")]
     pub install_dxweb: bool,
+    /// Further redistributables to install, by `Redistributable::id`, e.g.
+    /// `dotnet48` or `dotnetdesktop8`.
+    #[serde(default)]
+    pub install_redistributables: Vec<String>,
     #[serde(default)]
     pub redistributables_installed: Vec<String>,
+    /// Redistributables whose installer failed on the last attempt, until
+    /// one succeeds.
+    #[serde(default)]
+    pub redistributables_failed: Vec<String>,
+    /// What the last IGDB lookup found, shown in Game Details.
+    #[serde(default)]
+    pub igdb: Option<IgdbInfo>,
     #[serde(default)]
     pub last_played: Option<String>,
     #[serde(default)]
     pub installer_path: Option<String>,
+    /// SHA-256 of the installer at `installer_path` when it first ran.
+    #[serde(default)]
+    pub installer_sha256: Option<String>,
+    /// Patch and DLC installers run since, oldest first.
+    #[serde(default)]
+    pub patches: Vec<AppliedPatch>,
+    #[serde(default)]
+    pub msi_silent: bool,
+    #[serde(default)]
+    pub msi_properties: Vec<String>,
+    #[serde(default)]
+    pub cloud_sync: bool,
     #[serde(default)]
     pub install_state: InstallState,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DllOverrideMode {
+    NativeBuiltin,
+    BuiltinNative,
+    Native,
+    Builtin,
+    Disabled,
+}
+
+impl DllOverrideMode {
+    pub const ALL: [DllOverrideMode; 5] = [
+        DllOverrideMode::NativeBuiltin,
+        DllOverrideMode::BuiltinNative,
+        DllOverrideMode::Native,
+        DllOverrideMode::Builtin,
+        DllOverrideMode::Disabled,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DllOverrideMode::NativeBuiltin => "Native, then builtin",
+            DllOverrideMode::BuiltinNative => "Builtin, then native",
+            DllOverrideMode::Native => "Native",
+            DllOverrideMode::Builtin => "Builtin",
+            DllOverrideMode::Disabled => "Disabled",
+        }
+    }
+
+    /// Mode as written in `WINEDLLOVERRIDES`.
+    fn env_value(&self) -> &'static str {
+        match self {
+            DllOverrideMode::NativeBuiltin => "n,b",
+            DllOverrideMode::BuiltinNative => "b,n",
+            DllOverrideMode::Native => "n",
+            DllOverrideMode::Builtin => "b",
+            DllOverrideMode::Disabled => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DllOverride {
+    pub dll: String,
+    pub mode: DllOverrideMode,
+}
+
+/// A patch or DLC installer run in the capsule's prefix after the game was
+/// installed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedPatch {
+    pub path: String,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    pub applied_at: String,
+    /// Whether the installer exited successfully.
+    pub success: bool,
+}
+
+impl AppliedPatch {
+    pub fn file_name(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutableConfig {
     pub main: ExecutableEntry,
@@ -76,61 +283,701 @@ pub struct ExecutableEntry {
     pub label: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub original_shortcut: Option<String>,
+    /// Folder to start the executable in, set in game settings or taken
+    /// from its shortcut. Defaults to the executable's folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+}
+
+impl ExecutableEntry {
+    /// Folder to start the executable in: the configured one when it
+    /// exists, otherwise the executable's own folder.
+    pub fn start_dir(&self) -> Option<PathBuf> {
+        self.working_dir
+            .as_deref()
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_dir())
+            .or_else(|| Path::new(self.path.trim()).parent().map(Path::to_path_buf))
+            .filter(|dir| dir.is_dir())
+    }
 }
 
 impl Capsule {
-    /// Scan a directory for capsule folders with metadata.json
-    pub fn scan_directory(dir: &Path) -> Result<Vec<Capsule>> {
-        let mut capsules = Vec::new();
-        
+    /// Scan a directory for capsule folders with metadata.json, handing them
+    /// to `on_batch` up to `batch_size` at a time as they are read. Returns
+    /// how many were found.
+    pub fn scan_directory(
+        dir: &Path,
+        batch_size: usize,
+        on_batch: &mut dyn FnMut(Vec<Capsule>),
+    ) -> Result<usize> {
         if !dir.exists() {
             fs::create_dir_all(dir)?;
-            return Ok(capsules);
+            return Ok(0);
         }
 
+        let mut found = 0;
+        let mut batch = Vec::new();
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
-            
+
             if path.is_dir() {
                 if let Ok(capsule) = Self::load_from_dir(&path) {
-                    capsules.push(capsule);
+                    batch.push(capsule);
+                    found += 1;
                 }
             }
+            if batch.len() >= batch_size.max(1) {
+                on_batch(std::mem::take(&mut batch));
+            }
         }
+        if !batch.is_empty() {
+            on_batch(batch);
+        }
+
+        Ok(found)
+    }
+
+    /// Capsule folders in `dir` whose metadata.json is missing or unreadable,
+    /// so they don't show up in `scan_directory`, but that have a backup or
+    /// a damaged file to recover from.
+    pub fn scan_damaged(dir: &Path) -> Vec<PathBuf> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        let mut damaged: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter(|path| {
+                (path.join(METADATA_FILE).exists() || path.join(METADATA_BACKUP).is_file())
+                    && Self::load_from_dir(path).is_err()
+            })
+            .collect();
+        damaged.sort();
+        damaged
+    }
+
+    /// Whether `capsule_dir` has a metadata backup that parses.
+    pub fn has_metadata_backup(capsule_dir: &Path) -> bool {
+        Self::read_metadata(&capsule_dir.join(METADATA_BACKUP)).is_ok()
+    }
 
-        Ok(capsules)
+    /// Replace a damaged metadata.json with the backup. The damaged file is
+    /// kept next to it as `metadata.json.damaged`.
+    pub fn recover_metadata(capsule_dir: &Path) -> Result<Capsule> {
+        let backup = capsule_dir.join(METADATA_BACKUP);
+        let metadata = Self::read_metadata(&backup)
+            .with_context(|| format!("No usable metadata backup in {:?}", capsule_dir))?;
+        let metadata_path = capsule_dir.join(METADATA_FILE);
+        if metadata_path.exists() {
+            fs::rename(&metadata_path, capsule_dir.join("metadata.json.damaged"))
+                .context("Failed to set the damaged metadata.json aside")?;
+        }
+        let capsule = Self::from_metadata(capsule_dir, metadata);
+        capsule.save_metadata()?;
+        println!("Recovered metadata for {} from backup", capsule.name);
+        Ok(capsule)
     }
 
     /// Load capsule information from a capsule directory
     pub fn load_from_dir(capsule_dir: &Path) -> Result<Capsule> {
-        let metadata_path = capsule_dir.join("metadata.json");
-        let content = fs::read_to_string(&metadata_path)
-            .context("Failed to read metadata.json")?;
-        let metadata: CapsuleMetadata = serde_json::from_str(&content)
-            .context("Failed to parse metadata.json")?;
+        let metadata = Self::read_metadata(&capsule_dir.join(METADATA_FILE))?;
+        Ok(Self::from_metadata(capsule_dir, metadata))
+    }
 
+    fn read_metadata(path: &Path) -> Result<CapsuleMetadata> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+    }
+
+    fn from_metadata(capsule_dir: &Path, metadata: CapsuleMetadata) -> Capsule {
         let name = metadata.name.clone();
         let home_path = capsule_dir.join(format!("{}.AppImage.home", name));
 
-        Ok(Capsule {
+        Capsule {
             name,
             capsule_dir: capsule_dir.to_path_buf(),
             home_path,
             metadata,
-        })
+        }
     }
 
+    /// Rewrite absolute paths in metadata that point inside `old_dir` so they
+    /// point inside `new_dir` instead.
+    pub fn rebase_metadata_paths(metadata: &mut CapsuleMetadata, old_dir: &Path, new_dir: &Path) {
+        fn rebase(value: &mut String, old_dir: &Path, new_dir: &Path) {
+            if let Ok(rest) = Path::new(value.as_str()).strip_prefix(old_dir) {
+                *value = new_dir.join(rest).to_string_lossy().to_string();
+            }
+        }
+
+        rebase(&mut metadata.executables.main.path, old_dir, new_dir);
+        if let Some(shortcut) = metadata.executables.main.original_shortcut.as_mut() {
+            rebase(shortcut, old_dir, new_dir);
+        }
+        if let Some(working_dir) = metadata.executables.main.working_dir.as_mut() {
+            rebase(working_dir, old_dir, new_dir);
+        }
+        for tool in &mut metadata.executables.tools {
+            rebase(&mut tool.path, old_dir, new_dir);
+            if let Some(working_dir) = tool.working_dir.as_mut() {
+                rebase(working_dir, old_dir, new_dir);
+            }
+        }
+        if let Some(game_dir) = metadata.game_dir.as_mut() {
+            rebase(game_dir, old_dir, new_dir);
+        }
+        if let Some(screenshots_dir) = metadata.screenshots_dir.as_mut() {
+            rebase(screenshots_dir, old_dir, new_dir);
+        }
+    }
+
+    /// Move the capsule directory under `target_parent`. When the new location is
+    /// outside the library, a symlink is left at the old library entry so the
+    /// capsule keeps showing up. Returns the new capsule directory.
+    pub fn relocate(
+        &self,
+        target_parent: &Path,
+        on_chunk: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<PathBuf> {
+        let entry_path = self.capsule_dir.clone();
+        let real_dir = fs::canonicalize(&entry_path)
+            .context("Failed to resolve capsule directory")?;
+        let entry_name = entry_path
+            .file_name()
+            .context("Capsule directory has no name")?;
+        let entry_location = entry_path
+            .parent()
+            .and_then(|parent| fs::canonicalize(parent).ok())
+            .map(|parent| parent.join(entry_name))
+            .unwrap_or_else(|| entry_path.clone());
+
+        fs::create_dir_all(target_parent)
+            .with_context(|| format!("Failed to create {:?}", target_parent))?;
+        let target_parent = fs::canonicalize(target_parent)
+            .with_context(|| format!("Failed to resolve {:?}", target_parent))?;
+        let folder_name = real_dir
+            .file_name()
+            .context("Capsule directory has no name")?;
+        let dest = target_parent.join(folder_name);
+        if dest == real_dir {
+            anyhow::bail!("Capsule is already in {:?}", target_parent);
+        }
+        if target_parent.starts_with(&real_dir) {
+            anyhow::bail!("Cannot move a capsule inside itself");
+        }
+
+        let entry_is_link = file_ops::is_symlink(&entry_path);
+        let reuses_entry = entry_is_link && dest == entry_location;
+        if dest.exists() && !reuses_entry {
+            anyhow::bail!("{:?} already exists", dest);
+        }
+
+        if entry_is_link {
+            fs::remove_file(&entry_path).context("Failed to remove library link")?;
+        }
+
+        if fs::rename(&real_dir, &dest).is_err() {
+            // Different filesystem: copy, then remove the original.
+            if let Err(e) = file_ops::copy_dir_with_progress(&real_dir, &dest, on_chunk) {
+                let _ = fs::remove_dir_all(&dest);
+                if entry_is_link {
+                    let _ = std::os::unix::fs::symlink(&real_dir, &entry_path);
+                }
+                return Err(e).context("Failed to copy capsule");
+            }
+            if let Err(e) = fs::remove_dir_all(&real_dir) {
+                eprintln!("Failed to remove old capsule directory {:?}: {}", real_dir, e);
+            }
+        }
+
+        let mut metadata = self.metadata.clone();
+        Self::rebase_metadata_paths(&mut metadata, &real_dir, &dest);
+        Self::rebase_metadata_paths(&mut metadata, &entry_path, &dest);
+        let moved = Capsule {
+            name: self.name.clone(),
+            capsule_dir: dest.clone(),
+            home_path: dest.join(format!("{}.AppImage.home", self.name)),
+            metadata,
+        };
+        moved.save_metadata()?;
+
+        if dest != entry_location {
+            std::os::unix::fs::symlink(&dest, &entry_path)
+                .context("Failed to link moved capsule into the library")?;
+        }
+
+        Ok(dest)
+    }
+
+    /// Rename the capsule: its directory, the `.AppImage.home` directory, the
+    /// metadata name, and metadata paths under either. Completed steps are rolled
+    /// back if a later one fails. The directory may be named apart from the
+    /// metadata, as with a `-N` suffix added to keep it unique.
+    pub fn rename(&self, new_name: &str) -> Result<Capsule> {
+        let entry_path = self.capsule_dir.clone();
+        let entry_parent = entry_path
+            .parent()
+            .context("Capsule directory has no parent")?
+            .to_path_buf();
+        let entry_is_link = file_ops::is_symlink(&entry_path);
+        let real_dir = fs::canonicalize(&entry_path)
+            .context("Failed to resolve capsule directory")?;
+        let real_parent = real_dir
+            .parent()
+            .context("Capsule directory has no parent")?
+            .to_path_buf();
+
+        let old_dir_name = entry_path
+            .file_name()
+            .context("Capsule directory has no name")?
+            .to_string_lossy()
+            .to_string();
+
+        let new_real_dir = real_parent.join(new_name);
+        let new_entry_path = entry_parent.join(new_name);
+        // Only the metadata name changes when the folder already has the new name.
+        let dir_renamed = old_dir_name != new_name;
+        if dir_renamed && (new_real_dir.exists() || (entry_is_link && new_entry_path.exists())) {
+            anyhow::bail!("A capsule named \"{}\" already exists", new_name);
+        }
+        let new_real_dir = if dir_renamed {
+            new_real_dir
+        } else {
+            real_dir.clone()
+        };
+
+        let old_home = real_dir.join(format!("{}.AppImage.home", self.name));
+        let new_home_in_old_dir = real_dir.join(format!("{}.AppImage.home", new_name));
+        let home_renamed = if old_home.exists() && old_home != new_home_in_old_dir {
+            fs::rename(&old_home, &new_home_in_old_dir)
+                .context("Failed to rename the .AppImage.home directory")?;
+            true
+        } else {
+            false
+        };
+
+        let undo_home = || {
+            if home_renamed {
+                let _ = fs::rename(&new_home_in_old_dir, &old_home);
+            }
+        };
+
+        if dir_renamed {
+            if let Err(e) = fs::rename(&real_dir, &new_real_dir) {
+                undo_home();
+                return Err(e).context("Failed to rename the capsule directory");
+            }
+        }
+
+        let undo_dir = || {
+            if dir_renamed {
+                let _ = fs::rename(&new_real_dir, &real_dir);
+            }
+            undo_home();
+        };
+
+        if entry_is_link && dir_renamed {
+            let relinked = fs::remove_file(&entry_path)
+                .and_then(|_| std::os::unix::fs::symlink(&new_real_dir, &new_entry_path));
+            if let Err(e) = relinked {
+                let _ = std::os::unix::fs::symlink(&real_dir, &entry_path);
+                undo_dir();
+                return Err(e).context("Failed to update the library link");
+            }
+        }
+
+        let new_home = new_real_dir.join(format!("{}.AppImage.home", new_name));
+        let mut metadata = self.metadata.clone();
+        metadata.name = new_name.to_string();
+        // Home paths first: they sit inside the capsule directory.
+        Self::rebase_metadata_paths(&mut metadata, &old_home, &new_home);
+        Self::rebase_metadata_paths(&mut metadata, &self.home_path, &new_home);
+        Self::rebase_metadata_paths(&mut metadata, &real_dir, &new_real_dir);
+        Self::rebase_metadata_paths(&mut metadata, &entry_path, &new_real_dir);
+
+        let renamed = Capsule {
+            name: new_name.to_string(),
+            capsule_dir: if entry_is_link {
+                new_entry_path.clone()
+            } else {
+                new_real_dir.clone()
+            },
+            home_path: new_home,
+            metadata,
+        };
+
+        if let Err(e) = renamed.save_metadata() {
+            if entry_is_link && dir_renamed {
+                let _ = fs::remove_file(&new_entry_path);
+                let _ = std::os::unix::fs::symlink(&real_dir, &entry_path);
+            }
+            undo_dir();
+            return Err(e);
+        }
+
+        Ok(renamed)
+    }
+
+    /// Location of the game's files when they live inside this capsule, under
+    /// the real capsule directory: the install folder in the prefix, or else
+    /// the configured game folder. `None` when there are none.
+    fn game_dir_inside(&self, real_dir: &Path) -> Option<PathBuf> {
+        if let Some(root) = self.install_root() {
+            return root.starts_with(real_dir).then_some(root);
+        }
+        let game_dir = self.recorded_game_dir()?;
+        let rest = game_dir
+            .strip_prefix(&self.capsule_dir)
+            .or_else(|_| game_dir.strip_prefix(real_dir))
+            .ok()?;
+        Some(real_dir.join(rest))
+    }
+
+    /// Copy this capsule into `new_dir` under `new_name`. With `share_game_files`
+    /// only the prefix is copied and the game folder is linked to the original.
+    pub fn duplicate(
+        &self,
+        new_dir: &Path,
+        new_name: &str,
+        share_game_files: bool,
+        on_chunk: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<Capsule> {
+        let real_dir = fs::canonicalize(&self.capsule_dir)
+            .context("Failed to resolve capsule directory")?;
+        if new_dir.exists() {
+            anyhow::bail!("{:?} already exists", new_dir);
+        }
+
+        let shared_game_dir = if share_game_files {
+            self.game_dir_inside(&real_dir)
+        } else {
+            None
+        };
+        let exclude: Vec<PathBuf> = shared_game_dir.iter().cloned().collect();
+
+        let result = (|| -> Result<Capsule> {
+            file_ops::copy_dir_excluding(&real_dir, new_dir, &exclude, on_chunk)
+                .context("Failed to copy capsule")?;
+
+            let copied_home = new_dir.join(format!("{}.AppImage.home", self.name));
+            let new_home = new_dir.join(format!("{}.AppImage.home", new_name));
+            if copied_home.exists() && copied_home != new_home {
+                fs::rename(&copied_home, &new_home)
+                    .context("Failed to rename the .AppImage.home directory")?;
+            }
+
+            let old_home = real_dir.join(format!("{}.AppImage.home", self.name));
+            let mut metadata = self.metadata.clone();
+            metadata.name = new_name.to_string();
+            metadata.last_played = None;
+            Self::rebase_metadata_paths(&mut metadata, &old_home, &new_home);
+            Self::rebase_metadata_paths(&mut metadata, &self.home_path, &new_home);
+            Self::rebase_metadata_paths(&mut metadata, &real_dir, new_dir);
+            Self::rebase_metadata_paths(&mut metadata, &self.capsule_dir, new_dir);
+
+            if let Some(original) = shared_game_dir.as_ref() {
+                // The same place in the copy, whose home may have been renamed.
+                let link_path = match original.strip_prefix(&old_home) {
+                    Ok(rest) => new_home.join(rest),
+                    Err(_) => new_dir.join(original.strip_prefix(&real_dir)?),
+                };
+                if let Some(parent) = link_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                std::os::unix::fs::symlink(original, &link_path)
+                    .context("Failed to link shared game files")?;
+            }
+
+            let duplicate = Capsule {
+                name: new_name.to_string(),
+                capsule_dir: new_dir.to_path_buf(),
+                home_path: new_home,
+                metadata,
+            };
+            duplicate.save_metadata()?;
+            Ok(duplicate)
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_dir_all(new_dir);
+        }
+        result
+    }
+
+    /// Folder holding the game's files: the recorded game folder, or the
+    /// folder of the main executable.
+    pub fn game_folder(&self) -> Option<PathBuf> {
+        self.recorded_game_dir()
+            .or_else(|| {
+                let exe = self.metadata.executables.main.path.trim();
+                if exe.is_empty() {
+                    return None;
+                }
+                Path::new(exe).parent().map(Path::to_path_buf)
+            })
+            .filter(|path| path.is_dir())
+    }
+
+    /// The recorded game folder, unless the game isn't in it. Installers are
+    /// given an empty `prefix/games/<name>` and usually install under
+    /// `drive_c` instead.
+    fn recorded_game_dir(&self) -> Option<PathBuf> {
+        let dir = self
+            .metadata
+            .game_dir
+            .as_deref()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)?;
+        if !file_ops::has_entries(&dir) {
+            return None;
+        }
+        let exe = Path::new(self.metadata.executables.main.path.trim());
+        if exe.as_os_str().is_empty() || exe.starts_with(&dir) {
+            return Some(dir);
+        }
+        match (fs::canonicalize(exe), fs::canonicalize(&dir)) {
+            (Ok(exe), Ok(real_dir)) if exe.starts_with(&real_dir) => Some(dir),
+            _ => None,
+        }
+    }
+
+    /// The top folder of the game's install inside the prefix, e.g.
+    /// `drive_c/Program Files/Game` for `.../Game/bin/game.exe`. `None` when
+    /// the game lives outside the prefix or the folder is empty.
+    pub fn install_root(&self) -> Option<PathBuf> {
+        let inside = self
+            .recorded_game_dir()
+            .unwrap_or_else(|| PathBuf::from(self.metadata.executables.main.path.trim()));
+        // Compare real paths; a moved capsule is reached through a link.
+        let inside = fs::canonicalize(inside).ok()?;
+        let prefix_path = fs::canonicalize(self.home_path.join("prefix")).ok()?;
+        let relative = inside.strip_prefix(&prefix_path).ok()?;
+        let mut root = prefix_path.clone();
+        for component in relative.components() {
+            let part = match component {
+                Component::Normal(part) => part,
+                _ => return None,
+            };
+            root.push(part);
+            let name = part.to_string_lossy();
+            if !GAME_CONTAINER_DIRS
+                .iter()
+                .any(|container| name.eq_ignore_ascii_case(container))
+            {
+                return file_ops::has_entries(&root).then_some(root);
+            }
+        }
+        None
+    }
+
+    pub fn screenshots_dir(&self) -> PathBuf {
+        self.metadata
+            .screenshots_dir
+            .as_deref()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.home_path.join("screenshots"))
+    }
+
+    /// Output of the most recent game launch.
+    pub fn launch_log_path(&self) -> PathBuf {
+        self.home_path.join("last_launch.log")
+    }
+
+    /// Write metadata.json through a temporary file and a rename, so a crash
+    /// leaves either the old or the new file. The previous file is kept as
+    /// `metadata.json.bak` when it still parses.
     pub fn save_metadata(&self) -> Result<()> {
-        let metadata_path = self.capsule_dir.join("metadata.json");
+        let metadata_path = self.capsule_dir.join(METADATA_FILE);
         let content = serde_json::to_string_pretty(&self.metadata)
             .context("Failed to serialize metadata.json")?;
-        fs::write(&metadata_path, content)
+
+        let partial = self.capsule_dir.join("metadata.json.tmp");
+        let mut file = fs::File::create(&partial)
+            .context("Failed to write metadata.json")?;
+        file.write_all(content.as_bytes())
+            .and_then(|_| file.sync_all())
             .context("Failed to write metadata.json")?;
+        drop(file);
+
+        if Self::read_metadata(&metadata_path).is_ok() {
+            if let Err(e) = fs::copy(&metadata_path, self.capsule_dir.join(METADATA_BACKUP)) {
+                eprintln!("Failed to back up metadata.json: {}", e);
+            }
+        }
+        fs::rename(&partial, &metadata_path)
+            .context("Failed to replace metadata.json")?;
         Ok(())
     }
 }
 
+impl CapsuleMetadata {
+    /// Environment umu-run gets for this game, apart from `WINEPREFIX` and
+    /// `PROTONPATH`. Later entries win, so custom env vars come last but one.
+    pub fn umu_env(&self) -> Vec<(String, String)> {
+        let game_id = self
+            .game_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .unwrap_or("umu-default");
+        let store = self
+            .store
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .unwrap_or("none");
+        let mut vars = vec![
+            ("GAMEID".to_string(), game_id.to_string()),
+            ("STORE".to_string(), store.to_string()),
+            (
+                "PROTON_USE_XALIA".to_string(),
+                if self.xalia_enabled { "1" } else { "0" }.to_string(),
+            ),
+        ];
+        if self.protonfixes_disable {
+            vars.push(("PROTONFIXES_DISABLE".to_string(), "1".to_string()));
+        }
+        let overrides = [
+            ("LANG", &self.locale),
+            ("LC_ALL", &self.lc_all),
+            ("TZ", &self.timezone),
+        ];
+        for (key, value) in overrides {
+            if let Some(value) = value.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+                vars.push((key.to_string(), value.to_string()));
+            }
+        }
+        for (key, value) in &self.env_vars {
+            let trimmed = key.trim();
+            if !trimmed.is_empty() {
+                vars.push((trimmed.to_string(), value.clone()));
+            }
+        }
+        if let Some(overrides) = self.dll_overrides_env() {
+            vars.push(("WINEDLLOVERRIDES".to_string(), overrides));
+        }
+        vars.extend(DisplaySession::launch_env(self));
+        vars
+    }
+
+    /// The title shown in the library: the display title when set,
+    /// otherwise the capsule name.
+    pub fn title(&self) -> &str {
+        self.display_title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .unwrap_or(&self.name)
+    }
+
+    /// What the library is sorted by, case-insensitively.
+    pub fn sort_key(&self) -> String {
+        self.sort_title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| self.title())
+            .to_lowercase()
+    }
+
+    /// "Developer, year", or whichever of the two is set.
+    pub fn byline(&self) -> Option<String> {
+        let developer = self
+            .developer
+            .as_deref()
+            .map(str::trim)
+            .filter(|developer| !developer.is_empty());
+        match (developer, self.release_year) {
+            (Some(developer), Some(year)) => Some(format!("{}, {}", developer, year)),
+            (Some(developer), None) => Some(developer.to_string()),
+            (None, Some(year)) => Some(year.to_string()),
+            (None, None) => None,
+        }
+    }
+
+    /// The custom launch command, when one is set.
+    pub fn custom_command(&self) -> Option<&str> {
+        self.command_template
+            .as_deref()
+            .filter(|template| !template.trim().is_empty())
+    }
+
+    /// The `WINEDEBUG` value for game launches, when channels are set.
+    pub fn wine_debug(&self) -> Option<&str> {
+        self.wine_debug
+            .as_deref()
+            .map(str::trim)
+            .filter(|channels| !channels.is_empty())
+    }
+
+    /// A copy for a troubleshooting launch after a crash: no Xalia,
+    /// gamescope or MangoHud, WineD3D instead of DXVK and VKD3D, and a
+    /// windowed virtual desktop. Nothing here is saved.
+    pub fn safe_mode(&self) -> Self {
+        let mut safe = self.clone();
+        safe.xalia_enabled = false;
+        safe.gamescope_enabled = false;
+        safe.mangohud_enabled = false;
+        safe.env_vars.retain(|(key, _)| key.trim() != "PROTON_USE_WINED3D");
+        safe.env_vars.push(("PROTON_USE_WINED3D".to_string(), "1".to_string()));
+        if safe.virtual_desktop.is_none() {
+            safe.virtual_desktop = Some(SAFE_MODE_DESKTOP.to_string());
+        }
+        safe
+    }
+
+    /// `-pf_dxvk_set` options for the FPS cap, for D3D9 and for D3D10/11.
+    /// A frame rate the user set in the DXVK options themselves wins.
+    pub fn fps_limit_dxvk_sets(&self) -> Vec<String> {
+        let limit = match self.fps_limit {
+            Some(limit) if limit > 0 => limit,
+            _ => return Vec::new(),
+        };
+        if self
+            .protonfixes_dxvk_sets
+            .iter()
+            .any(|option| option.contains("maxFrameRate"))
+        {
+            return Vec::new();
+        }
+        ["dxgi.maxFrameRate", "d3d9.maxFrameRate"]
+            .iter()
+            .map(|key| format!("{}={}", key, limit))
+            .collect()
+    }
+
+    /// `WINEDLLOVERRIDES` built from the override rows, followed by any value
+    /// already set in the custom env vars.
+    pub fn dll_overrides_env(&self) -> Option<String> {
+        let mut parts: Vec<String> = self
+            .dll_overrides
+            .iter()
+            .filter(|entry| !entry.dll.trim().is_empty())
+            .map(|entry| format!("{}={}", entry.dll.trim(), entry.mode.env_value()))
+            .collect();
+        if let Some((_, value)) = self
+            .env_vars
+            .iter()
+            .find(|(key, value)| key.trim() == "WINEDLLOVERRIDES" && !value.trim().is_empty())
+        {
+            parts.push(value.trim().to_string());
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(";"))
+        }
+    }
+}
+
 impl Default for CapsuleMetadata {
     fn default() -> Self {
         Self {
@@ -141,26 +988,62 @@ impl Default for CapsuleMetadata {
                     args: String::new(),
                     label: "Launch".to_string(),
                     original_shortcut: None,
+                    working_dir: None,
                 },
                 tools: Vec::new(),
             },
+            display_title: None,
+            sort_title: None,
+            release_year: None,
+            developer: None,
             game_id: None,
             store: None,
             game_dir: None,
             protonfixes_disable: false,
             protonfixes_tricks: Vec::new(),
+            winetricks_verbs: Vec::new(),
             protonfixes_replace_cmds: Vec::new(),
             protonfixes_dxvk_sets: Vec::new(),
             xalia_enabled: false,
+            gamemode_enabled: false,
+            mangohud_enabled: false,
+            fps_limit: None,
+            gamescope_enabled: false,
+            gamescope_hdr: false,
+            gamescope_vrr: false,
+            screenshots_dir: None,
+            proton_wayland: false,
+            force_x11: false,
+            sdl_video_driver: None,
+            sandbox_enabled: false,
+            sandbox_network: true,
+            block_network: false,
+            command_template: None,
+            wine_debug: None,
+            wine_debug_once: false,
             wine_version: None,
+            win32_prefix: false,
             dxvk_enabled: true,
             vkd3d_enabled: false,
             env_vars: Vec::new(),
+            dll_overrides: Vec::new(),
+            virtual_desktop: None,
+            locale: None,
+            lc_all: None,
+            timezone: None,
             install_vcredist: true,
             install_dxweb: true,
+            install_redistributables: Vec::new(),
             redistributables_installed: Vec::new(),
+            redistributables_failed: Vec::new(),
+            igdb: None,
             last_played: None,
             installer_path: None,
+            installer_sha256: None,
+            patches: Vec::new(),
+            msi_silent: false,
+            msi_properties: Vec::new(),
+            cloud_sync: false,
             install_state: InstallState::Installing,
         }
     }
@@ -0,0 +1,393 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::capsule::Capsule;
+use crate::core::network;
+use crate::core::saves::{SaveLocation, SaveManager};
+use crate::core::system_checker::SystemCheck;
+use crate::core::umu_database::UmuDatabase;
+use crate::utils::{file_ops, host};
+
+const ARCHIVE_NAME: &str = "saves.tar.zst";
+const STATE_NAME: &str = "state.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudBackend {
+    #[default]
+    Disabled,
+    Rclone,
+    WebDav,
+}
+
+/// Global cloud sync settings, stored in `~/.linuxboy/cloud_sync.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloudSyncConfig {
+    #[serde(default)]
+    pub backend: CloudBackend,
+    /// rclone destination such as `gdrive:LinuxBoy`.
+    #[serde(default)]
+    pub rclone_remote: String,
+    #[serde(default)]
+    pub webdav_url: String,
+    #[serde(default)]
+    pub webdav_username: String,
+    #[serde(default)]
+    pub webdav_password: String,
+}
+
+impl CloudSyncConfig {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("cloud_sync.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize cloud sync settings")?;
+        // Holds the WebDAV password.
+        file_ops::write_private(&path, content).context("Failed to write cloud sync settings")?;
+        Ok(())
+    }
+
+    pub fn is_configured(&self) -> bool {
+        match self.backend {
+            CloudBackend::Disabled => false,
+            CloudBackend::Rclone => !self.rclone_remote.trim().is_empty(),
+            CloudBackend::WebDav => !self.webdav_url.trim().is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    Auto,
+    Upload,
+    Download,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncOutcome {
+    Uploaded,
+    Downloaded,
+    UpToDate,
+    /// Both sides changed since the last sync; times are unix seconds.
+    Conflict {
+        local_modified: i64,
+        remote_modified: i64,
+    },
+}
+
+/// Written next to the archive on the remote.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemoteState {
+    modified: i64,
+    uploaded_at: String,
+    #[serde(default)]
+    host: String,
+}
+
+/// What both sides looked like after the last successful sync, stored in the
+/// capsule home.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LocalSyncState {
+    local_modified: i64,
+    remote_modified: i64,
+    synced_at: String,
+}
+
+pub struct CloudSync {
+    config: CloudSyncConfig,
+}
+
+impl CloudSync {
+    pub fn new(config: CloudSyncConfig) -> Self {
+        Self { config }
+    }
+
+    /// Sync one capsule's saves. `Auto` uploads or downloads whichever side
+    /// changed since the last sync and reports a conflict when both did.
+    pub fn sync(&self, capsule: &Capsule, direction: SyncDirection) -> Result<SyncOutcome> {
+        if !self.config.is_configured() {
+            anyhow::bail!("Cloud sync is not configured");
+        }
+
+        let key = Self::remote_key(capsule);
+        let locations = SaveManager::detect(capsule);
+        let local_modified = SaveManager::latest_modified(&locations);
+        let remote = self.fetch_state(&key)?;
+        let last = Self::load_local_state(capsule);
+
+        let direction = match direction {
+            SyncDirection::Auto => {
+                match (local_modified, &remote) {
+                    (None, None) => return Ok(SyncOutcome::UpToDate),
+                    (Some(_), None) => SyncDirection::Upload,
+                    (None, Some(_)) => SyncDirection::Download,
+                    (Some(local), Some(remote)) => {
+                        let local_changed = last
+                            .as_ref()
+                            .map(|last| local > last.local_modified)
+                            .unwrap_or(true);
+                        let remote_changed = last
+                            .as_ref()
+                            .map(|last| remote.modified != last.remote_modified)
+                            .unwrap_or(true);
+                        match (local_changed, remote_changed) {
+                            (false, false) => return Ok(SyncOutcome::UpToDate),
+                            (true, false) => SyncDirection::Upload,
+                            (false, true) => SyncDirection::Download,
+                            (true, true) if local == remote.modified => {
+                                Self::save_local_state(capsule, local, remote.modified)?;
+                                return Ok(SyncOutcome::UpToDate);
+                            }
+                            (true, true) => {
+                                return Ok(SyncOutcome::Conflict {
+                                    local_modified: local,
+                                    remote_modified: remote.modified,
+                                })
+                            }
+                        }
+                    }
+                }
+            }
+            other => other,
+        };
+
+        match direction {
+            SyncDirection::Download => {
+                let remote = remote.context("No saves for this game in the cloud")?;
+                self.download(capsule, &key)?;
+                let locations = SaveManager::detect(capsule);
+                let local = SaveManager::latest_modified(&locations).unwrap_or(remote.modified);
+                Self::save_local_state(capsule, local, remote.modified)?;
+                println!("Downloaded cloud saves for {}", capsule.name);
+                Ok(SyncOutcome::Downloaded)
+            }
+            _ => {
+                let local = local_modified
+                    .with_context(|| format!("No save folders found for {}", capsule.name))?;
+                self.upload(capsule, &key, &locations, local)?;
+                Self::save_local_state(capsule, local, local)?;
+                println!("Uploaded saves for {}", capsule.name);
+                Ok(SyncOutcome::Uploaded)
+            }
+        }
+    }
+
+    fn upload(
+        &self,
+        capsule: &Capsule,
+        key: &str,
+        locations: &[SaveLocation],
+        modified: i64,
+    ) -> Result<()> {
+        let archive_path = capsule.home_path.join("cloud_upload.tar.zst");
+        let result = SaveManager::export(capsule, locations, &archive_path)
+            .and_then(|_| self.put_file(&archive_path, key, ARCHIVE_NAME));
+        let _ = fs::remove_file(&archive_path);
+        result?;
+
+        let state = RemoteState {
+            modified,
+            uploaded_at: chrono::Local::now().to_rfc3339(),
+            host: fs::read_to_string("/etc/hostname")
+                .map(|host| host.trim().to_string())
+                .unwrap_or_default(),
+        };
+        let state_path = capsule.home_path.join("cloud_state.json");
+        let content = serde_json::to_string_pretty(&state)?;
+        fs::write(&state_path, content).context("Failed to write cloud state")?;
+        let result = self.put_file(&state_path, key, STATE_NAME);
+        let _ = fs::remove_file(&state_path);
+        result
+    }
+
+    fn download(&self, capsule: &Capsule, key: &str) -> Result<()> {
+        let archive_path = capsule.home_path.join("cloud_download.tar.zst");
+        let result = match self.get_file(key, ARCHIVE_NAME, &archive_path) {
+            Ok(true) => SaveManager::import(capsule, &archive_path),
+            Ok(false) => Err(anyhow::anyhow!("Cloud save archive is missing")),
+            Err(e) => Err(e),
+        };
+        let _ = fs::remove_file(&archive_path);
+        result
+    }
+
+    fn fetch_state(&self, key: &str) -> Result<Option<RemoteState>> {
+        let temp = std::env::temp_dir().join(format!(
+            "linuxboy-cloud-{}.json",
+            uuid::Uuid::new_v4().simple()
+        ));
+        let found = self.get_file(key, STATE_NAME, &temp);
+        let content = fs::read_to_string(&temp);
+        let _ = fs::remove_file(&temp);
+        if !found? {
+            return Ok(None);
+        }
+        let state = serde_json::from_str(&content?).context("Invalid cloud sync state")?;
+        Ok(Some(state))
+    }
+
+    /// Download a remote file; returns `false` when it does not exist.
+    fn get_file(&self, key: &str, name: &str, dest: &Path) -> Result<bool> {
+        match self.config.backend {
+            CloudBackend::Rclone => {
+                let mut cmd = Command::new("rclone");
+                cmd.arg("copyto").arg(self.rclone_path(key, name)).arg(dest);
+                let output = host::command(cmd)
+                    .output()
+                    .context("Failed to run rclone")?;
+                if output.status.success() {
+                    return Ok(dest.is_file());
+                }
+                // rclone exits with 3 (directory not found) or 4 (file not found).
+                match output.status.code() {
+                    Some(3) | Some(4) => Ok(false),
+                    _ => anyhow::bail!(
+                        "rclone failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ),
+                }
+            }
+            CloudBackend::WebDav => {
+                let response = self
+                    .webdav_request(reqwest::Method::GET, &self.webdav_url(&[key, name]))?
+                    .send()
+                    .context("WebDAV request failed")?;
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(false);
+                }
+                if !response.status().is_success() {
+                    anyhow::bail!("WebDAV download failed: {}", response.status());
+                }
+                let mut response = response;
+                let mut file =
+                    File::create(dest).with_context(|| format!("Failed to create {:?}", dest))?;
+                response.copy_to(&mut file).context("WebDAV download failed")?;
+                Ok(true)
+            }
+            CloudBackend::Disabled => anyhow::bail!("Cloud sync is not configured"),
+        }
+    }
+
+    fn put_file(&self, src: &Path, key: &str, name: &str) -> Result<()> {
+        match self.config.backend {
+            CloudBackend::Rclone => {
+                let mut cmd = Command::new("rclone");
+                cmd.arg("copyto").arg(src).arg(self.rclone_path(key, name));
+                let output = host::command(cmd)
+                    .output()
+                    .context("Failed to run rclone")?;
+                if !output.status.success() {
+                    anyhow::bail!(
+                        "rclone failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                Ok(())
+            }
+            CloudBackend::WebDav => {
+                let mkcol = reqwest::Method::from_bytes(b"MKCOL")?;
+                for url in [self.webdav_url(&[]), self.webdav_url(&[key])] {
+                    // 405 means the collection already exists.
+                    let _ = self
+                        .webdav_request(mkcol.clone(), &format!("{}/", url))?
+                        .send();
+                }
+                let file = File::open(src).with_context(|| format!("Failed to open {:?}", src))?;
+                let response = self
+                    .webdav_request(reqwest::Method::PUT, &self.webdav_url(&[key, name]))?
+                    .body(reqwest::blocking::Body::from(file))
+                    .send()
+                    .context("WebDAV request failed")?;
+                if !response.status().is_success() {
+                    anyhow::bail!("WebDAV upload failed: {}", response.status());
+                }
+                Ok(())
+            }
+            CloudBackend::Disabled => anyhow::bail!("Cloud sync is not configured"),
+        }
+    }
+
+    fn rclone_path(&self, key: &str, name: &str) -> String {
+        let remote = self.config.rclone_remote.trim();
+        if remote.ends_with(':') || remote.ends_with('/') {
+            format!("{}{}/{}", remote, key, name)
+        } else {
+            format!("{}/{}/{}", remote, key, name)
+        }
+    }
+
+    fn webdav_url(&self, parts: &[&str]) -> String {
+        let mut url = self.config.webdav_url.trim().trim_end_matches('/').to_string();
+        for part in parts {
+            url.push('/');
+            url.push_str(part);
+        }
+        url
+    }
+
+    fn webdav_request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> Result<reqwest::blocking::RequestBuilder> {
+        let client = network::client_builder()?
+            .build()
+            .context("Failed to create HTTP client")?;
+        let mut request = client.request(method, url);
+        if !self.config.webdav_username.is_empty() {
+            request = request.basic_auth(
+                &self.config.webdav_username,
+                Some(&self.config.webdav_password),
+            );
+        }
+        Ok(request)
+    }
+
+    /// Remote folder name for a game, stable across machines.
+    fn remote_key(capsule: &Capsule) -> String {
+        let key = UmuDatabase::normalize_title(&capsule.name);
+        if key.is_empty() {
+            "game".to_string()
+        } else {
+            key
+        }
+    }
+
+    fn local_state_path(capsule: &Capsule) -> PathBuf {
+        capsule.home_path.join("cloud_sync.json")
+    }
+
+    fn load_local_state(capsule: &Capsule) -> Option<LocalSyncState> {
+        let content = fs::read_to_string(Self::local_state_path(capsule)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn save_local_state(capsule: &Capsule, local_modified: i64, remote_modified: i64) -> Result<()> {
+        let state = LocalSyncState {
+            local_modified,
+            remote_modified,
+            synced_at: chrono::Local::now().to_rfc3339(),
+        };
+        fs::create_dir_all(&capsule.home_path)?;
+        let content = serde_json::to_string_pretty(&state)?;
+        fs::write(Self::local_state_path(capsule), content)
+            .context("Failed to write cloud sync state")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::core::capsule::Capsule;
+use crate::utils::file_ops;
+
+/// Smaller files save too little to be worth hashing.
+const MIN_FILE_SIZE: u64 = 16 * 1024;
+/// Prefix folders holding each game's own data rather than the Proton
+/// skeleton. Game install folders are skipped as well.
+const SKIPPED_DIRS: [&str; 1] = ["drive_c/users"];
+/// `FS_IOC_FIEMAP`, `_IOWR('f', 11, struct fiemap)`.
+const FS_IOC_FIEMAP: libc::c_ulong = 0xC020_660B;
+const FIEMAP_EXTENT_SHARED: u32 = 0x2000;
+
+/// A prefix file as it was when the prefixes were scanned.
+#[derive(Debug, Clone)]
+pub struct PrefixFile {
+    pub path: PathBuf,
+    modified: Option<SystemTime>,
+    dev: u64,
+    ino: u64,
+    /// Physical start of the file's data when the filesystem reports it as
+    /// shared, i.e. already reflinked.
+    shared_extent: Option<u64>,
+}
+
+impl PrefixFile {
+    /// Files that already share their data on disk count as one copy.
+    fn storage_key(&self) -> (u64, u64, bool) {
+        match self.shared_extent {
+            Some(physical) => (self.dev, physical, true),
+            None => (self.dev, self.ino, false),
+        }
+    }
+}
+
+/// Prefix files with the same contents.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub files: Vec<PrefixFile>,
+}
+
+impl DuplicateGroup {
+    /// Copies stored separately on disk.
+    fn stored_copies(&self) -> usize {
+        self.files
+            .iter()
+            .map(PrefixFile::storage_key)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Bytes freed if every copy shared one file's data.
+    pub fn reclaimable(&self) -> u64 {
+        self.stored_copies().saturating_sub(1) as u64 * self.size
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DedupeReport {
+    pub prefixes: usize,
+    pub files_scanned: usize,
+    pub scanned_bytes: u64,
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DedupeReport {
+    /// Separately stored copies that could share another file's data.
+    pub fn duplicate_files(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|group| group.stored_copies().saturating_sub(1))
+            .sum()
+    }
+
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.groups.iter().map(DuplicateGroup::reclaimable).sum()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} prefix(es), {} in {} file(s) scanned. {} duplicate file(s) take {} that \
+             deduplication can free.",
+            self.prefixes,
+            file_ops::format_bytes(self.scanned_bytes),
+            self.files_scanned,
+            self.duplicate_files(),
+            file_ops::format_bytes(self.reclaimable_bytes())
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DedupeOptions {
+    /// Fall back to hardlinks where reflinks aren't supported. Hardlinked
+    /// files are one file: a game that changes it in place changes it for
+    /// every prefix.
+    pub allow_hardlinks: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DedupeOutcome {
+    pub reflinked: usize,
+    pub hardlinked: usize,
+    /// Files changed since the scan or on a filesystem without reflinks.
+    pub skipped: usize,
+    pub linked_bytes: u64,
+    /// Growth of free space on the filesystems holding the prefixes.
+    pub freed_bytes: u64,
+}
+
+impl DedupeOutcome {
+    pub fn summary(&self) -> String {
+        format!(
+            "Deduplicated {} of prefix files ({} reflinked, {} hardlinked, {} skipped); \
+             {} freed on disk",
+            file_ops::format_bytes(self.linked_bytes),
+            self.reflinked,
+            self.hardlinked,
+            self.skipped,
+            file_ops::format_bytes(self.freed_bytes)
+        )
+    }
+}
+
+pub struct PrefixDedupe;
+
+impl PrefixDedupe {
+    /// Find files with identical contents across the prefixes of
+    /// `capsules`. Files are grouped by size first, so only same-sized
+    /// files are hashed; `on_progress` gets the hashed and total bytes and
+    /// returns false to cancel.
+    pub fn scan(
+        capsules: &[Capsule],
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<DedupeReport> {
+        let mut report = DedupeReport::default();
+        let mut seen_prefixes = HashSet::new();
+        let mut by_size: HashMap<u64, Vec<(PathBuf, fs::Metadata)>> = HashMap::new();
+
+        for capsule in capsules {
+            let prefix_path = match fs::canonicalize(capsule.home_path.join("prefix")) {
+                Ok(path) => path,
+                Err(_) => continue,
+            };
+            // Duplicated capsules may link to one prefix.
+            if !seen_prefixes.insert(prefix_path.clone()) {
+                continue;
+            }
+            report.prefixes += 1;
+
+            let mut skipped: Vec<PathBuf> = SKIPPED_DIRS
+                .iter()
+                .map(|dir| prefix_path.join(dir))
+                .collect();
+            skipped.extend(capsule.install_root());
+            let walker = WalkDir::new(&prefix_path)
+                .follow_links(false)
+                .into_iter()
+                .filter_entry(|entry| !skipped.iter().any(|dir| entry.path() == dir));
+            for entry in walker.flatten() {
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                report.files_scanned += 1;
+                report.scanned_bytes += metadata.len();
+                if metadata.len() >= MIN_FILE_SIZE {
+                    by_size
+                        .entry(metadata.len())
+                        .or_default()
+                        .push((entry.into_path(), metadata));
+                }
+            }
+        }
+
+        by_size.retain(|_, files| files.len() > 1);
+        let total: u64 = by_size
+            .iter()
+            .map(|(size, files)| size * files.len() as u64)
+            .sum();
+        let mut hashed: u64 = 0;
+
+        for (size, files) in by_size {
+            let mut by_hash: HashMap<String, Vec<PrefixFile>> = HashMap::new();
+            for (path, metadata) in files {
+                let digest = match Self::hash_file(&path, &mut |bytes| {
+                    hashed += bytes;
+                    on_progress(hashed, total)
+                }) {
+                    Ok(digest) => digest,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+                        anyhow::bail!("Scan cancelled")
+                    }
+                    // Unreadable files are left out.
+                    Err(_) => continue,
+                };
+                by_hash.entry(digest).or_default().push(PrefixFile {
+                    shared_extent: Self::shared_extent(&path),
+                    path,
+                    modified: metadata.modified().ok(),
+                    dev: metadata.dev(),
+                    ino: metadata.ino(),
+                });
+            }
+            report.groups.extend(
+                by_hash
+                    .into_values()
+                    .map(|files| DuplicateGroup { size, files })
+                    .filter(|group| group.reclaimable() > 0),
+            );
+        }
+
+        report
+            .groups
+            .sort_by_key(|group| Reverse(group.reclaimable()));
+        Ok(report)
+    }
+
+    /// Make the duplicates found by `scan` share the data of the first file
+    /// in their group: a reflink where the filesystem supports it, a
+    /// hardlink when `options` allow it. Files changed since the scan are
+    /// skipped.
+    pub fn run(report: &DedupeReport, options: DedupeOptions) -> Result<DedupeOutcome> {
+        let mut outcome = DedupeOutcome::default();
+        let mut devices: HashMap<u64, PathBuf> = HashMap::new();
+        for file in report.groups.iter().flat_map(|group| &group.files) {
+            devices.entry(file.dev).or_insert_with(|| file.path.clone());
+        }
+        let free_before: HashMap<u64, u64> = devices
+            .iter()
+            .filter_map(|(dev, path)| Some((*dev, file_ops::disk_space(path)?.0)))
+            .collect();
+
+        for group in &report.groups {
+            let source = &group.files[0];
+            if !Self::unchanged(source, group.size) {
+                outcome.skipped += group.files.len() - 1;
+                continue;
+            }
+            let mut stored = HashSet::from([source.storage_key()]);
+            for target in &group.files[1..] {
+                if !stored.insert(target.storage_key()) {
+                    continue;
+                }
+                if !Self::unchanged(target, group.size) {
+                    outcome.skipped += 1;
+                    continue;
+                }
+                match Self::replace(&source.path, target, options)
+                    .with_context(|| format!("Failed to deduplicate {:?}", target.path))?
+                {
+                    Some(LinkKind::Reflink) => outcome.reflinked += 1,
+                    Some(LinkKind::Hardlink) => outcome.hardlinked += 1,
+                    None => {
+                        outcome.skipped += 1;
+                        continue;
+                    }
+                }
+                outcome.linked_bytes += group.size;
+            }
+        }
+
+        outcome.freed_bytes = devices
+            .iter()
+            .filter_map(|(dev, path)| {
+                let after = file_ops::disk_space(path)?.0;
+                Some(after.saturating_sub(*free_before.get(dev)?))
+            })
+            .sum();
+        Ok(outcome)
+    }
+
+    fn unchanged(file: &PrefixFile, size: u64) -> bool {
+        match fs::symlink_metadata(&file.path) {
+            Ok(metadata) => {
+                metadata.is_file()
+                    && metadata.len() == size
+                    && metadata.ino() == file.ino
+                    && metadata.modified().ok() == file.modified
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Swap `target` for a reflink or hardlink of `source` through a
+    /// temporary file next to it. `None` when neither is possible.
+    fn replace(
+        source: &Path,
+        target: &PrefixFile,
+        options: DedupeOptions,
+    ) -> Result<Option<LinkKind>> {
+        let name = target
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let partial = target.path.with_file_name(format!(".{}.dedupe", name));
+        let _ = fs::remove_file(&partial);
+        let metadata = fs::metadata(&target.path)?;
+
+        let kind = if Self::reflink(source, &partial).is_ok() {
+            fs::set_permissions(&partial, metadata.permissions())?;
+            if let Ok(modified) = metadata.modified() {
+                File::options()
+                    .write(true)
+                    .open(&partial)?
+                    .set_modified(modified)?;
+            }
+            LinkKind::Reflink
+        } else {
+            let _ = fs::remove_file(&partial);
+            if !options.allow_hardlinks || fs::hard_link(source, &partial).is_err() {
+                return Ok(None);
+            }
+            LinkKind::Hardlink
+        };
+
+        if let Err(e) = fs::rename(&partial, &target.path) {
+            let _ = fs::remove_file(&partial);
+            return Err(e.into());
+        }
+        Ok(Some(kind))
+    }
+
+    /// Create `dest` sharing the data of `source` (`FICLONE`). Fails on
+    /// filesystems without copy-on-write and across filesystems.
+    fn reflink(source: &Path, dest: &Path) -> io::Result<()> {
+        let source = File::open(source)?;
+        let dest_file = File::options().write(true).create_new(true).open(dest)?;
+        if unsafe { libc::ioctl(dest_file.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Physical address of the file's first extent when it is shared with
+    /// another file.
+    fn shared_extent(path: &Path) -> Option<u64> {
+        let file = File::open(path).ok()?;
+        let mut request = FiemapRequest {
+            start: 0,
+            length: u64::MAX,
+            flags: 0,
+            mapped_extents: 0,
+            extent_count: 1,
+            reserved: 0,
+            extent: FiemapExtent::default(),
+        };
+        if unsafe { libc::ioctl(file.as_raw_fd(), FS_IOC_FIEMAP as _, &mut request) } != 0 {
+            return None;
+        }
+        (request.mapped_extents > 0 && request.extent.flags & FIEMAP_EXTENT_SHARED != 0)
+            .then_some(request.extent.physical)
+    }
+
+    fn hash_file(path: &Path, on_bytes: &mut dyn FnMut(u64) -> bool) -> io::Result<String> {
+        let mut file = File::open(path)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 256 * 1024];
+        loop {
+            let bytes_read = file.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+            if !on_bytes(bytes_read as u64) {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "Scan cancelled"));
+            }
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+enum LinkKind {
+    Reflink,
+    Hardlink,
+}
+
+/// `struct fiemap` with room for one extent.
+#[repr(C)]
+struct FiemapRequest {
+    start: u64,
+    length: u64,
+    flags: u32,
+    mapped_extents: u32,
+    extent_count: u32,
+    reserved: u32,
+    extent: FiemapExtent,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct FiemapExtent {
+    logical: u64,
+    physical: u64,
+    length: u64,
+    reserved64: [u64; 2],
+    flags: u32,
+    reserved: [u32; 3],
+}
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::core::capsule::{Capsule, InstallState};
+use crate::core::system_checker::SystemCheck;
+use crate::utils::file_ops;
+
+/// Below this much free space the disk is reported as nearly full.
+const LOW_SPACE_BYTES: u64 = 2 * 1_073_741_824;
+/// ...or below this share of the disk.
+const LOW_SPACE_PERCENT: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticFix {
+    /// Pick the main executable in game settings.
+    SelectExecutable,
+    /// Let umu create the prefix again.
+    InitializePrefix,
+    /// Install missing runtimes from System Setup.
+    OpenSystemSetup,
+    RemoveBrokenLinks(Vec<PathBuf>),
+}
+
+impl DiagnosticFix {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiagnosticFix::SelectExecutable => "Select executable",
+            DiagnosticFix::InitializePrefix => "Recreate prefix",
+            DiagnosticFix::OpenSystemSetup => "Open System Setup",
+            DiagnosticFix::RemoveBrokenLinks(_) => "Remove broken links",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub summary: String,
+    pub detail: String,
+    pub fix: Option<DiagnosticFix>,
+}
+
+/// Checks a capsule for the problems that usually keep a game from starting.
+pub struct PrefixDoctor;
+
+impl PrefixDoctor {
+    pub fn examine(capsule: &Capsule, system: &SystemCheck) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let prefix_path = capsule.home_path.join("prefix");
+
+        if capsule.metadata.install_state == InstallState::Installed {
+            let exe = capsule.metadata.executables.main.path.trim();
+            if exe.is_empty() {
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    summary: "No executable selected".to_string(),
+                    detail: "The game cannot start until a main executable is set.".to_string(),
+                    fix: Some(DiagnosticFix::SelectExecutable),
+                });
+            } else if !Path::new(exe).is_file() {
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    summary: "Executable not found".to_string(),
+                    detail: format!("{} does not exist.", exe),
+                    fix: Some(DiagnosticFix::SelectExecutable),
+                });
+            }
+        }
+
+        if !prefix_path.join("drive_c").is_dir() {
+            findings.push(Finding {
+                severity: Severity::Error,
+                summary: "Prefix is missing drive_c".to_string(),
+                detail: format!("{} is not a complete Wine prefix.", prefix_path.display()),
+                fix: Some(DiagnosticFix::InitializePrefix),
+            });
+        }
+
+        if !system.proton_installed {
+            findings.push(Finding {
+                severity: Severity::Error,
+                summary: "No Proton-GE runtime installed".to_string(),
+                detail: "Games and installers run through Proton-GE.".to_string(),
+                fix: Some(DiagnosticFix::OpenSystemSetup),
+            });
+        }
+
+        if !system.umu_installed {
+            findings.push(Finding {
+                severity: Severity::Error,
+                summary: "umu-run not found".to_string(),
+                detail: "UMU Launcher is needed to start anything in the prefix.".to_string(),
+                fix: Some(DiagnosticFix::OpenSystemSetup),
+            });
+        }
+
+        if let Some(warning) = system.display_session.launch_warning(&capsule.metadata) {
+            findings.push(Finding {
+                severity: Severity::Warning,
+                summary: "Game needs X11, but the session has none".to_string(),
+                detail: warning,
+                fix: None,
+            });
+        }
+
+        let game_dir = capsule.metadata.game_dir.as_deref().map(PathBuf::from);
+        if let Some(game_dir) = game_dir.as_ref() {
+            if file_ops::is_symlink(game_dir) && !game_dir.exists() {
+                let target = fs::read_link(game_dir).unwrap_or_default();
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    summary: "Linked game folder is missing".to_string(),
+                    detail: format!(
+                        "{} points to {}, which is gone. Reconnect the drive or move the game back.",
+                        game_dir.display(),
+                        target.display()
+                    ),
+                    fix: None,
+                });
+            }
+        }
+
+        let broken_links: Vec<PathBuf> = Self::broken_links(&capsule.home_path)
+            .into_iter()
+            .filter(|link| Some(link) != game_dir.as_ref())
+            .collect();
+        if !broken_links.is_empty() {
+            let mut detail = broken_links
+                .iter()
+                .take(5)
+                .map(|link| link.display().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if broken_links.len() > 5 {
+                detail.push_str(&format!("\n...and {} more", broken_links.len() - 5));
+            }
+            findings.push(Finding {
+                severity: Severity::Warning,
+                summary: format!("{} broken symlinks", broken_links.len()),
+                detail,
+                fix: Some(DiagnosticFix::RemoveBrokenLinks(broken_links)),
+            });
+        }
+
+        if let Some((available, total)) = file_ops::disk_space(&capsule.capsule_dir) {
+            if available < LOW_SPACE_BYTES || available * 100 < total * LOW_SPACE_PERCENT {
+                findings.push(Finding {
+                    severity: Severity::Warning,
+                    summary: "Disk nearly full".to_string(),
+                    detail: format!(
+                        "Only {} free of {}. Updates and saves may fail.",
+                        file_ops::format_bytes(available),
+                        file_ops::format_bytes(total)
+                    ),
+                    fix: None,
+                });
+            }
+        }
+
+        findings
+    }
+
+    pub fn remove_broken_links(links: &[PathBuf]) -> Result<usize> {
+        let mut removed = 0;
+        for link in links {
+            if file_ops::is_symlink(link) && !link.exists() {
+                fs::remove_file(link).with_context(|| format!("Failed to remove {:?}", link))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Dangling symlinks below `home_path`. `dosdevices` is skipped because
+    /// Wine keeps drive links there for devices that may not be present.
+    fn broken_links(home_path: &Path) -> Vec<PathBuf> {
+        WalkDir::new(home_path)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| entry.file_name() != "dosdevices")
+            .flatten()
+            .filter(|entry| entry.path_is_symlink() && !entry.path().exists())
+            .map(|entry| entry.into_path())
+            .collect()
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::core::system_checker::SystemCheck;
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+const IPC_TIMEOUT: Duration = Duration::from_secs(2);
+/// Discord listens on the first free of `discord-ipc-0` to `-9`.
+const IPC_SLOTS: u32 = 10;
+/// Where Flatpak and Snap builds of Discord put the socket, relative to
+/// `XDG_RUNTIME_DIR`.
+const IPC_SUBDIRS: [&str; 4] = [
+    "",
+    "app/com.discordapp.Discord",
+    "app/com.discordapp.DiscordCanary",
+    "snap.discord",
+];
+
+/// Rich Presence settings, stored in `~/.linuxboy/discord.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiscordSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// ID of a Discord application; Discord shows its name as the game.
+    #[serde(default)]
+    pub client_id: String,
+}
+
+impl DiscordSettings {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("discord.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize Discord settings")?;
+        fs::write(&path, content).context("Failed to write Discord settings")?;
+        Ok(())
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.enabled && !self.client_id.trim().is_empty()
+    }
+}
+
+/// What Discord should show: a game and when it started (Unix seconds).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activity {
+    pub game: String,
+    pub started: i64,
+}
+
+/// Rich Presence over Discord's local IPC socket. Updates are sent from a
+/// background thread, so a missing or slow Discord never blocks the UI;
+/// when Discord isn't running they are dropped.
+pub struct DiscordPresence {
+    updates: mpsc::Sender<Option<Activity>>,
+}
+
+impl DiscordPresence {
+    pub fn start(client_id: &str) -> Self {
+        let client_id = client_id.trim().to_string();
+        let (updates, received) = mpsc::channel::<Option<Activity>>();
+        thread::spawn(move || {
+            let mut connection: Option<UnixStream> = None;
+            for activity in received {
+                // Reconnect once per update in case Discord restarted.
+                for _ in 0..2 {
+                    if connection.is_none() {
+                        connection = Self::connect(&client_id).ok();
+                    }
+                    let stream = match connection.as_mut() {
+                        Some(stream) => stream,
+                        None => break,
+                    };
+                    match Self::set_activity(stream, activity.as_ref()) {
+                        Ok(()) => break,
+                        Err(_) => connection = None,
+                    }
+                }
+            }
+        });
+        Self { updates }
+    }
+
+    /// Show `activity`, or clear the presence with `None`.
+    pub fn update(&self, activity: Option<Activity>) {
+        let _ = self.updates.send(activity);
+    }
+
+    fn socket_paths() -> Vec<PathBuf> {
+        let runtime = env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp"));
+        IPC_SUBDIRS
+            .iter()
+            .flat_map(|subdir| {
+                let dir = runtime.join(subdir);
+                (0..IPC_SLOTS).map(move |slot| dir.join(format!("discord-ipc-{}", slot)))
+            })
+            .collect()
+    }
+
+    fn connect(client_id: &str) -> Result<UnixStream> {
+        let mut stream = Self::socket_paths()
+            .into_iter()
+            .find_map(|path| UnixStream::connect(path).ok())
+            .context("Discord is not running")?;
+        stream.set_read_timeout(Some(IPC_TIMEOUT))?;
+        stream.set_write_timeout(Some(IPC_TIMEOUT))?;
+        Self::send(
+            &mut stream,
+            OP_HANDSHAKE,
+            &json!({ "v": 1, "client_id": client_id }),
+        )?;
+        // READY, or an error for an unknown application ID.
+        let (op, reply) = Self::receive(&mut stream)?;
+        if op != OP_FRAME || reply["evt"] != "READY" {
+            anyhow::bail!("Discord refused the connection: {}", reply);
+        }
+        Ok(stream)
+    }
+
+    fn set_activity(stream: &mut UnixStream, activity: Option<&Activity>) -> Result<()> {
+        let activity = activity.map(|activity| {
+            json!({
+                "details": format!("Playing {}", activity.game),
+                "state": "via LinuxBoy",
+                "timestamps": { "start": activity.started },
+            })
+        });
+        Self::send(
+            stream,
+            OP_FRAME,
+            &json!({
+                "cmd": "SET_ACTIVITY",
+                "args": { "pid": std::process::id(), "activity": activity },
+                "nonce": uuid::Uuid::new_v4().to_string(),
+            }),
+        )?;
+        Self::receive(stream).map(|_| ())
+    }
+
+    fn send(stream: &mut UnixStream, op: u32, payload: &serde_json::Value) -> Result<()> {
+        let body = payload.to_string();
+        let mut frame = Vec::with_capacity(8 + body.len());
+        frame.extend_from_slice(&op.to_le_bytes());
+        frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        frame.extend_from_slice(body.as_bytes());
+        stream
+            .write_all(&frame)
+            .context("Failed to write to Discord")
+    }
+
+    fn receive(stream: &mut UnixStream) -> Result<(u32, serde_json::Value)> {
+        let mut header = [0u8; 8];
+        stream
+            .read_exact(&mut header)
+            .context("Failed to read from Discord")?;
+        let op = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let mut body = vec![0u8; len];
+        stream
+            .read_exact(&mut body)
+            .context("Failed to read from Discord")?;
+        let value = serde_json::from_slice(&body).context("Discord sent invalid JSON")?;
+        Ok((op, value))
+    }
+}
@@ -0,0 +1,102 @@
+use std::env;
+
+use crate::core::capsule::CapsuleMetadata;
+
+/// Fallback X display when X11 is forced and the session sets none.
+const DEFAULT_X_DISPLAY: &str = ":0";
+
+/// SDL video drivers offered per game; `None` lets SDL pick.
+pub const SDL_VIDEO_DRIVERS: [&str; 2] = ["x11", "wayland"];
+
+/// The display server the desktop session runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplaySession {
+    /// `xwayland` is false when no X display is exported, as on compositors
+    /// started without XWayland.
+    Wayland { xwayland: bool },
+    X11,
+    Unknown,
+}
+
+impl DisplaySession {
+    pub fn detect() -> Self {
+        let set = |name: &str| env::var_os(name).map(|value| !value.is_empty()).unwrap_or(false);
+        let session_type = env::var("XDG_SESSION_TYPE")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if set("WAYLAND_DISPLAY") || session_type == "wayland" {
+            DisplaySession::Wayland {
+                xwayland: set("DISPLAY"),
+            }
+        } else if set("DISPLAY") || session_type == "x11" {
+            DisplaySession::X11
+        } else {
+            DisplaySession::Unknown
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisplaySession::Wayland { xwayland: true } => "Wayland (with XWayland)",
+            DisplaySession::Wayland { xwayland: false } => "Wayland (no XWayland)",
+            DisplaySession::X11 => "X11",
+            DisplaySession::Unknown => "unknown",
+        }
+    }
+
+    /// Wayland with no X server for X11 clients to connect to.
+    pub fn is_wayland_only(&self) -> bool {
+        *self == DisplaySession::Wayland { xwayland: false }
+    }
+
+    /// Why the game won't open a window in this session, if it won't.
+    pub fn launch_warning(&self, metadata: &CapsuleMetadata) -> Option<String> {
+        if !self.is_wayland_only() || !Self::needs_x11(metadata) {
+            return None;
+        }
+        Some(if metadata.force_x11 {
+            "The game is forced to X11, but this Wayland session has no XWayland. \
+             Turn off Force X11 or start XWayland."
+                .to_string()
+        } else {
+            "Wine draws through X11 unless Proton's Wayland driver is enabled, and this \
+             Wayland session has no XWayland. Enable the Wayland driver in game settings \
+             or start XWayland."
+                .to_string()
+        })
+    }
+
+    /// Without Proton's Wayland driver, Wine windows are X11 windows.
+    pub fn needs_x11(metadata: &CapsuleMetadata) -> bool {
+        metadata.force_x11 || !metadata.proton_wayland
+    }
+
+    /// Environment for the game's display-server settings.
+    pub fn launch_env(metadata: &CapsuleMetadata) -> Vec<(String, String)> {
+        let mut vars = Vec::new();
+        if metadata.force_x11 {
+            // An empty WAYLAND_DISPLAY stops clients from finding the
+            // compositor; removing it would not survive flatpak-spawn.
+            vars.push(("WAYLAND_DISPLAY".to_string(), String::new()));
+            let display = env::var("DISPLAY")
+                .ok()
+                .filter(|display| !display.is_empty())
+                .unwrap_or_else(|| DEFAULT_X_DISPLAY.to_string());
+            vars.push(("DISPLAY".to_string(), display));
+        } else if metadata.proton_wayland {
+            vars.push(("PROTON_ENABLE_WAYLAND".to_string(), "1".to_string()));
+        }
+
+        let sdl_driver = metadata
+            .sdl_video_driver
+            .as_deref()
+            .map(str::trim)
+            .filter(|driver| !driver.is_empty())
+            .or(if metadata.force_x11 { Some("x11") } else { None });
+        if let Some(driver) = sdl_driver {
+            vars.push(("SDL_VIDEODRIVER".to_string(), driver.to_string()));
+        }
+        vars
+    }
+}
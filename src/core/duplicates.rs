@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::capsule::Capsule;
+use crate::core::umu_database::UmuDatabase;
+
+/// What a game being added has in common with one in the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateReason {
+    /// It was installed with the same installer.
+    Installer,
+    /// The selected file is its main executable.
+    Executable,
+    /// The selected file lies inside its game folder.
+    GameFolder,
+    /// The names match once case and punctuation are ignored.
+    Name,
+}
+
+impl DuplicateReason {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Installer => "was installed from the same installer",
+            Self::Executable => "already uses this executable",
+            Self::GameFolder => "already has this file in its game folder",
+            Self::Name => "has the same name",
+        }
+    }
+}
+
+/// A capsule the game being added probably already is.
+#[derive(Debug, Clone)]
+pub struct Duplicate {
+    pub capsule_dir: PathBuf,
+    pub title: String,
+    pub reason: DuplicateReason,
+}
+
+/// Finds the capsule a newly selected installer, executable or prefix
+/// belongs to, so adding it again isn't silently a second copy.
+pub struct DuplicateFinder;
+
+impl DuplicateFinder {
+    /// The capsule `path` came from, or else one named like `name`.
+    /// Capsules whose folder is gone are skipped.
+    pub fn find(capsules: &[Capsule], name: &str, path: &Path) -> Option<Duplicate> {
+        let path = real_path(path);
+        let by_path = capsules.iter().find_map(|capsule| {
+            let metadata = &capsule.metadata;
+            let installer = metadata.installer_path.as_deref().map(Path::new);
+            if installer.map(real_path).as_ref() == Some(&path) {
+                return Some((capsule, DuplicateReason::Installer));
+            }
+            let exe = metadata.executables.main.path.trim();
+            if !exe.is_empty() && real_path(Path::new(exe)) == path {
+                return Some((capsule, DuplicateReason::Executable));
+            }
+            capsule
+                .game_folder()
+                .filter(|folder| path.starts_with(real_path(folder)))
+                .map(|_| (capsule, DuplicateReason::GameFolder))
+        });
+
+        let wanted = UmuDatabase::normalize_title(name);
+        by_path
+            .or_else(|| {
+                if wanted.is_empty() {
+                    return None;
+                }
+                capsules
+                    .iter()
+                    .find(|capsule| {
+                        UmuDatabase::normalize_title(&capsule.name) == wanted
+                            || UmuDatabase::normalize_title(capsule.metadata.title()) == wanted
+                    })
+                    .map(|capsule| (capsule, DuplicateReason::Name))
+            })
+            .filter(|(capsule, _)| capsule.capsule_dir.is_dir())
+            .map(|(capsule, reason)| Duplicate {
+                capsule_dir: capsule.capsule_dir.clone(),
+                title: capsule.metadata.title().to_string(),
+                reason,
+            })
+    }
+}
+
+/// `path` with links resolved, so a capsule reached through a moved library
+/// still matches.
+fn real_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const INPUT_DIR: &str = "/dev/input";
+/// How often to look for newly plugged-in controllers.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+// Linux joystick API, see linux/joystick.h.
+const JS_EVENT_SIZE: usize = 8;
+const JS_EVENT_BUTTON: u8 = 0x01;
+const JS_EVENT_AXIS: u8 = 0x02;
+/// Set on the synthetic events describing the state at open time.
+const JS_EVENT_INIT: u8 = 0x80;
+const JSIOCGAXMAP: u32 = 0x8040_6a32;
+const JSIOCGBTNMAP: u32 = 0x8400_6a34;
+const AXIS_MAP_LEN: usize = 64;
+const BUTTON_MAP_LEN: usize = 512;
+
+// Input event codes, see linux/input-event-codes.h.
+const BTN_SOUTH: u16 = 0x130;
+const BTN_EAST: u16 = 0x131;
+const BTN_SELECT: u16 = 0x13a;
+const BTN_START: u16 = 0x13b;
+const BTN_DPAD_UP: u16 = 0x220;
+const BTN_DPAD_DOWN: u16 = 0x221;
+const BTN_DPAD_LEFT: u16 = 0x222;
+const BTN_DPAD_RIGHT: u16 = 0x223;
+const ABS_X: u8 = 0x00;
+const ABS_Y: u8 = 0x01;
+const ABS_HAT0X: u8 = 0x10;
+const ABS_HAT0Y: u8 = 0x11;
+
+/// Half the stick's travel counts as a push.
+const STICK_THRESHOLD: i16 = 16_384;
+
+/// What a controller input means for moving around the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Activate,
+    Back,
+}
+
+/// Reads every controller exposed through the kernel's joystick devices.
+/// Buttons and axes are identified by their input codes, so the A/B layout
+/// holds for Xbox, PlayStation and most generic pads.
+pub struct GamepadWatcher;
+
+impl GamepadWatcher {
+    /// Watch for controllers in the background, including ones plugged in
+    /// later, and call `on_action` for each navigation input.
+    pub fn spawn<F: Fn(GamepadAction) + Send + 'static>(on_action: F) {
+        let (actions, received) = mpsc::channel();
+        thread::spawn(move || {
+            for action in received {
+                on_action(action);
+            }
+        });
+
+        let open: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+        thread::spawn(move || loop {
+            for path in Self::joystick_devices() {
+                if open.lock().map(|set| set.contains(&path)).unwrap_or(true) {
+                    continue;
+                }
+                // Devices the user can't read stay closed; no need to log
+                // that every rescan.
+                let file = match File::open(&path) {
+                    Ok(file) => file,
+                    Err(_) => continue,
+                };
+                println!("Gamepad connected: {:?}", path);
+                if let Ok(mut set) = open.lock() {
+                    set.insert(path.clone());
+                }
+                let open = open.clone();
+                let actions = actions.clone();
+                thread::spawn(move || {
+                    Self::read_events(file, &actions);
+                    println!("Gamepad disconnected: {:?}", path);
+                    if let Ok(mut set) = open.lock() {
+                        set.remove(&path);
+                    }
+                });
+            }
+            thread::sleep(RESCAN_INTERVAL);
+        });
+    }
+
+    fn joystick_devices() -> Vec<PathBuf> {
+        let entries = match fs::read_dir(INPUT_DIR) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        entries
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("js"))
+            .map(|entry| entry.path())
+            .collect()
+    }
+
+    /// Forward actions until the device goes away or the receiver does.
+    fn read_events(mut file: File, actions: &mpsc::Sender<GamepadAction>) {
+        let (axis_map, button_map) = Self::mappings(&file);
+        let mut directions: HashMap<u8, i8> = HashMap::new();
+        let mut event = [0u8; JS_EVENT_SIZE];
+        while file.read_exact(&mut event).is_ok() {
+            let value = i16::from_ne_bytes([event[4], event[5]]);
+            let kind = event[6];
+            let number = event[7] as usize;
+            if kind & JS_EVENT_INIT != 0 {
+                continue;
+            }
+            let action = match kind {
+                JS_EVENT_BUTTON if value == 1 => button_map
+                    .get(number)
+                    .and_then(|&code| Self::button_action(code)),
+                JS_EVENT_AXIS => axis_map.get(number).and_then(|&axis| {
+                    let direction = if value <= -STICK_THRESHOLD {
+                        -1
+                    } else if value >= STICK_THRESHOLD {
+                        1
+                    } else {
+                        0
+                    };
+                    // Only the move away from center counts, not holding it.
+                    let previous = directions.insert(axis, direction).unwrap_or(0);
+                    if direction == previous {
+                        None
+                    } else {
+                        Self::axis_action(axis, direction)
+                    }
+                }),
+                _ => None,
+            };
+            if let Some(action) = action {
+                if actions.send(action).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn button_action(code: u16) -> Option<GamepadAction> {
+        match code {
+            BTN_SOUTH | BTN_START => Some(GamepadAction::Activate),
+            BTN_EAST | BTN_SELECT => Some(GamepadAction::Back),
+            BTN_DPAD_UP => Some(GamepadAction::Up),
+            BTN_DPAD_DOWN => Some(GamepadAction::Down),
+            BTN_DPAD_LEFT => Some(GamepadAction::Left),
+            BTN_DPAD_RIGHT => Some(GamepadAction::Right),
+            _ => None,
+        }
+    }
+
+    fn axis_action(axis: u8, direction: i8) -> Option<GamepadAction> {
+        match (axis, direction) {
+            (ABS_X | ABS_HAT0X, -1) => Some(GamepadAction::Left),
+            (ABS_X | ABS_HAT0X, 1) => Some(GamepadAction::Right),
+            (ABS_Y | ABS_HAT0Y, -1) => Some(GamepadAction::Up),
+            (ABS_Y | ABS_HAT0Y, 1) => Some(GamepadAction::Down),
+            _ => None,
+        }
+    }
+
+    /// The input codes behind the device's axis and button numbers. Falls
+    /// back to the Xbox controller layout when the driver won't say.
+    fn mappings(file: &File) -> (Vec<u8>, Vec<u16>) {
+        let fd = file.as_raw_fd();
+        let mut axes = [0u8; AXIS_MAP_LEN];
+        let mut buttons = [0u16; BUTTON_MAP_LEN];
+        let mapped = unsafe {
+            libc::ioctl(fd, JSIOCGAXMAP as _, axes.as_mut_ptr()) >= 0
+                && libc::ioctl(fd, JSIOCGBTNMAP as _, buttons.as_mut_ptr()) >= 0
+        };
+        if mapped {
+            (axes.to_vec(), buttons.to_vec())
+        } else {
+            (
+                vec![ABS_X, ABS_Y, 0x02, 0x03, 0x04, 0x05, ABS_HAT0X, ABS_HAT0Y],
+                vec![BTN_SOUTH, BTN_EAST, 0, 0, 0, 0, BTN_SELECT, BTN_START],
+            )
+        }
+    }
+}
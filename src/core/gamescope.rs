@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::core::capsule::CapsuleMetadata;
+use crate::core::display_server::DisplaySession;
+use crate::utils::host;
+
+const DRM_CLASS_DIR: &str = "/sys/class/drm";
+const EDID_BLOCK_SIZE: usize = 128;
+/// CTA-861 extension block tag.
+const CTA_EXTENSION_TAG: u8 = 0x02;
+/// CTA data block using an extended tag; the HDR static metadata block is
+/// extended tag 6.
+const CTA_EXTENDED_TAG: u8 = 7;
+const CTA_HDR_STATIC_METADATA: u8 = 6;
+const CTA_VENDOR_SPECIFIC: u8 = 3;
+/// SMPTE ST 2084 (PQ) in the HDR static metadata EOTF byte.
+const EOTF_PQ: u8 = 0x04;
+/// IEEE OUI AMD registered for its FreeSync vendor block.
+const AMD_OUI: [u8; 3] = [0x1A, 0x00, 0x00];
+/// Display range limits descriptor tag.
+const RANGE_LIMITS_TAG: u8 = 0xFD;
+
+/// What gamescope can do for games on this machine.
+#[derive(Debug, Clone, Default)]
+pub struct GamescopeSupport {
+    pub installed: bool,
+    /// A connected display advertises HDR10 (PQ) in its EDID.
+    pub hdr_display: bool,
+    /// A connected display advertises a refresh range wide enough for
+    /// adaptive sync, or AMD's FreeSync block.
+    pub vrr_display: bool,
+}
+
+impl GamescopeSupport {
+    pub fn detect() -> Self {
+        let mut support = Self {
+            installed: host::has_command("gamescope"),
+            ..Self::default()
+        };
+        let entries = match fs::read_dir(DRM_CLASS_DIR) {
+            Ok(entries) => entries,
+            Err(_) => return support,
+        };
+        for entry in entries.flatten() {
+            let connector = entry.path();
+            if !Self::is_connected(&connector) {
+                continue;
+            }
+            if let Ok(edid) = fs::read(connector.join("edid")) {
+                support.hdr_display |= Self::edid_supports_hdr(&edid);
+                support.vrr_display |= Self::edid_supports_vrr(&edid);
+            }
+        }
+        support
+    }
+
+    /// HDR needs a display that takes it and a session gamescope can pass
+    /// it through: nested inside a Wayland compositor, or on its own from a
+    /// TTY. Under X11 gamescope has no way to hand HDR to the desktop.
+    pub fn hdr_available(&self, session: DisplaySession) -> bool {
+        self.installed && self.hdr_display && !matches!(session, DisplaySession::X11)
+    }
+
+    pub fn vrr_available(&self) -> bool {
+        self.installed && self.vrr_display
+    }
+
+    /// Why the HDR toggle is off, for its tooltip.
+    pub fn hdr_unavailable_reason(&self, session: DisplaySession) -> Option<&'static str> {
+        if !self.installed {
+            Some("Install gamescope first")
+        } else if !self.hdr_display {
+            Some("No connected display reports HDR support")
+        } else if matches!(session, DisplaySession::X11) {
+            Some("HDR through gamescope needs a Wayland session")
+        } else {
+            None
+        }
+    }
+
+    pub fn vrr_unavailable_reason(&self) -> Option<&'static str> {
+        if !self.installed {
+            Some("Install gamescope first")
+        } else if !self.vrr_display {
+            Some("No connected display reports adaptive sync support")
+        } else {
+            None
+        }
+    }
+
+    /// Run `cmd` inside gamescope with the game's HDR and VRR options. The
+    /// environment and working directory move to the gamescope command,
+    /// which passes them on to the game. HDR and VRR are dropped when the
+    /// machine can't do them, so a capsule copied elsewhere still starts.
+    pub fn wrap(
+        &self,
+        cmd: Command,
+        metadata: &CapsuleMetadata,
+        session: DisplaySession,
+    ) -> Command {
+        let mut wrapped = Command::new("gamescope");
+        if let Some(dir) = cmd.get_current_dir() {
+            wrapped.current_dir(dir);
+        }
+        for (key, value) in cmd.get_envs() {
+            match value {
+                Some(value) => wrapped.env(key, value),
+                None => wrapped.env_remove(key),
+            };
+        }
+        if metadata.gamescope_hdr && self.hdr_available(session) {
+            wrapped.arg("--hdr-enabled");
+            // Proton only exposes HDR swapchains to the game when asked, and
+            // they reach gamescope through its WSI layer.
+            wrapped.env("PROTON_ENABLE_HDR", "1");
+            wrapped.env("DXVK_HDR", "1");
+            wrapped.env("ENABLE_GAMESCOPE_WSI", "1");
+        }
+        if metadata.gamescope_vrr && self.vrr_available() {
+            wrapped.arg("--adaptive-sync");
+        }
+        wrapped.arg("--");
+        wrapped.arg(cmd.get_program());
+        wrapped.args(cmd.get_args());
+        wrapped
+    }
+
+    fn is_connected(connector: &Path) -> bool {
+        fs::read_to_string(connector.join("status"))
+            .map(|status| status.trim() == "connected")
+            .unwrap_or(false)
+    }
+
+    /// Look for a CTA-861 HDR static metadata block listing the PQ curve.
+    fn edid_supports_hdr(edid: &[u8]) -> bool {
+        Self::cta_data_blocks(edid).any(|(tag, payload)| {
+            tag == CTA_EXTENDED_TAG
+                && payload.first() == Some(&CTA_HDR_STATIC_METADATA)
+                && payload
+                    .get(1)
+                    .map(|eotf| eotf & EOTF_PQ != 0)
+                    .unwrap_or(false)
+        })
+    }
+
+    /// FreeSync displays carry AMD's vendor block; other adaptive sync
+    /// displays only show it as a vertical refresh range much wider than a
+    /// fixed-rate panel's few hertz of tolerance.
+    fn edid_supports_vrr(edid: &[u8]) -> bool {
+        let freesync = Self::cta_data_blocks(edid).any(|(tag, payload)| {
+            tag == CTA_VENDOR_SPECIFIC && payload.get(0..3) == Some(&AMD_OUI[..])
+        });
+        if freesync {
+            return true;
+        }
+        // The four 18-byte descriptors of the base block.
+        (0..4)
+            .filter_map(|index| edid.get(54 + index * 18..72 + index * 18))
+            .filter(|descriptor| descriptor[..3] == [0, 0, 0] && descriptor[3] == RANGE_LIMITS_TAG)
+            .any(|descriptor| {
+                // Byte 4 flags add 255 Hz to the rates for high-refresh panels.
+                let min = descriptor[5] as u32 + if descriptor[4] & 0x01 != 0 { 255 } else { 0 };
+                let max = descriptor[6] as u32 + if descriptor[4] & 0x02 != 0 { 255 } else { 0 };
+                min > 0 && max * 2 >= min * 3
+            })
+    }
+
+    /// `(tag, payload)` for each data block in the EDID's CTA extensions.
+    fn cta_data_blocks(edid: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+        edid.chunks_exact(EDID_BLOCK_SIZE)
+            .skip(1)
+            .filter(|block| block[0] == CTA_EXTENSION_TAG)
+            .flat_map(|block| {
+                // Data blocks run from byte 4 up to the detailed timings.
+                let end = (block[2] as usize).clamp(4, EDID_BLOCK_SIZE - 1);
+                let mut blocks = Vec::new();
+                let mut pos = 4;
+                while pos < end {
+                    let tag = block[pos] >> 5;
+                    let len = (block[pos] & 0x1F) as usize;
+                    match block.get(pos + 1..pos + 1 + len) {
+                        Some(payload) if pos + 1 + len <= end => blocks.push((tag, payload)),
+                        _ => break,
+                    }
+                    pos += 1 + len;
+                }
+                blocks
+            })
+    }
+}
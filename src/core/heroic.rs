@@ -0,0 +1,388 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::capsule::{Capsule, CapsuleMetadata, InstallState};
+use crate::core::umu_database::UmuEntry;
+use crate::utils::file_ops;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeroicStore {
+    Gog,
+    Epic,
+}
+
+impl HeroicStore {
+    /// Store name as used by the UMU database and the `STORE` env var.
+    pub fn umu_store(&self) -> &'static str {
+        match self {
+            HeroicStore::Gog => "gog",
+            HeroicStore::Epic => "egs",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HeroicStore::Gog => "GOG",
+            HeroicStore::Epic => "Epic",
+        }
+    }
+}
+
+/// An installed Windows game managed by Heroic Games Launcher.
+#[derive(Debug, Clone)]
+pub struct HeroicGame {
+    pub app_name: String,
+    pub title: String,
+    pub store: HeroicStore,
+    pub install_path: PathBuf,
+    /// Main executable relative to `install_path`, when Heroic knows it.
+    pub executable: Option<PathBuf>,
+    pub wine_prefix: Option<PathBuf>,
+}
+
+impl HeroicGame {
+    /// UMU game id for this title, looked up by store and store-specific id.
+    pub fn umu_id(&self, entries: &[UmuEntry]) -> Option<String> {
+        entries
+            .iter()
+            .find(|entry| {
+                entry.store.as_deref() == Some(self.store.umu_store())
+                    && entry
+                        .codename
+                        .as_deref()
+                        .map(|codename| codename.eq_ignore_ascii_case(&self.app_name))
+                        .unwrap_or(false)
+            })
+            .and_then(|entry| entry.umu_id.clone())
+    }
+}
+
+#[derive(Deserialize)]
+struct GogInstalled {
+    #[serde(default)]
+    installed: Vec<GogInstalledEntry>,
+}
+
+#[derive(Deserialize)]
+struct GogInstalledEntry {
+    #[serde(rename = "appName")]
+    app_name: String,
+    #[serde(default)]
+    platform: String,
+    install_path: PathBuf,
+    #[serde(default)]
+    executable: String,
+    #[serde(default)]
+    is_dlc: bool,
+}
+
+#[derive(Deserialize)]
+struct GogLibrary {
+    #[serde(default)]
+    games: Vec<GogLibraryEntry>,
+}
+
+#[derive(Deserialize)]
+struct GogLibraryEntry {
+    app_name: String,
+    #[serde(default)]
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct GogInfo {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default, rename = "playTasks")]
+    play_tasks: Vec<GogPlayTask>,
+}
+
+#[derive(Deserialize)]
+struct GogPlayTask {
+    #[serde(default, rename = "isPrimary")]
+    is_primary: bool,
+    #[serde(default)]
+    path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LegendaryInstalled {
+    app_name: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    platform: String,
+    install_path: PathBuf,
+    #[serde(default)]
+    executable: String,
+    #[serde(default)]
+    is_dlc: bool,
+}
+
+pub struct HeroicLibrary;
+
+impl HeroicLibrary {
+    /// Heroic config folders for the native and Flatpak builds that exist.
+    pub fn config_dirs() -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        if let Some(config) = dirs::config_dir() {
+            dirs.push(config.join("heroic"));
+        }
+        if let Some(home) = dirs::home_dir() {
+            dirs.push(
+                home.join(".var")
+                    .join("app")
+                    .join("com.heroicgameslauncher.hgl")
+                    .join("config")
+                    .join("heroic"),
+            );
+        }
+        dirs.retain(|dir| dir.is_dir());
+        dirs
+    }
+
+    /// Installed Windows games from every Heroic install, skipping DLC and
+    /// games whose folder is gone.
+    pub fn installed_games() -> Vec<HeroicGame> {
+        let mut games = Vec::new();
+        for config_dir in Self::config_dirs() {
+            games.extend(Self::gog_games(&config_dir));
+            games.extend(Self::epic_games(&config_dir));
+        }
+        games.retain(|game| game.install_path.is_dir());
+        games.sort_by_key(|game| game.title.to_lowercase());
+        games
+    }
+
+    /// Create a capsule for `game` at `capsule_dir`, starting from `defaults`.
+    /// The game folder is linked in place; Heroic's prefix is copied when
+    /// `copy_prefix` is set, otherwise the capsule starts with a fresh prefix.
+    pub fn create_capsule(
+        game: &HeroicGame,
+        capsule_dir: &Path,
+        name: &str,
+        game_id: Option<String>,
+        copy_prefix: bool,
+        defaults: CapsuleMetadata,
+        on_chunk: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<Capsule> {
+        let result = Self::build_capsule(
+            game,
+            capsule_dir,
+            name,
+            game_id,
+            copy_prefix,
+            defaults,
+            on_chunk,
+        );
+        if result.is_err() {
+            let _ = fs::remove_dir_all(capsule_dir);
+        }
+        result
+    }
+
+    fn build_capsule(
+        game: &HeroicGame,
+        capsule_dir: &Path,
+        name: &str,
+        game_id: Option<String>,
+        copy_prefix: bool,
+        defaults: CapsuleMetadata,
+        on_chunk: &mut dyn FnMut(u64) -> bool,
+    ) -> Result<Capsule> {
+        let home_path = capsule_dir.join(format!("{}.AppImage.home", name));
+        let prefix_path = home_path.join("prefix");
+        fs::create_dir_all(&home_path)
+            .with_context(|| format!("Failed to create {:?}", home_path))?;
+
+        match game.wine_prefix.as_ref().filter(|_| copy_prefix) {
+            Some(source) => {
+                file_ops::copy_dir_with_progress(source, &prefix_path, on_chunk)
+                    .context("Failed to copy the Heroic prefix")?;
+            }
+            None => {
+                fs::create_dir_all(prefix_path.join("drive_c"))
+                    .context("Failed to create prefix")?;
+            }
+        }
+
+        let games_root = prefix_path.join("games");
+        fs::create_dir_all(&games_root).context("Failed to create games folder")?;
+        let folder = game
+            .install_path
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or_else(|| name.into());
+        let dest_dir = games_root.join(folder);
+        std::os::unix::fs::symlink(&game.install_path, &dest_dir)
+            .context("Failed to link game folder")?;
+
+        let mut metadata = CapsuleMetadata {
+            name: name.to_string(),
+            // The capsule name is cleaned up for use as a folder name.
+            display_title: Some(game.title.clone()).filter(|title| title != name),
+            install_state: InstallState::Installed,
+            game_id,
+            store: Some(game.store.umu_store().to_string()),
+            game_dir: Some(dest_dir.to_string_lossy().to_string()),
+            ..defaults
+        };
+        if let Some(executable) = &game.executable {
+            metadata.executables.main.path =
+                dest_dir.join(executable).to_string_lossy().to_string();
+        }
+
+        let capsule = Capsule {
+            name: name.to_string(),
+            capsule_dir: capsule_dir.to_path_buf(),
+            home_path,
+            metadata,
+        };
+        capsule.save_metadata()?;
+        Ok(capsule)
+    }
+
+    fn gog_games(config_dir: &Path) -> Vec<HeroicGame> {
+        let installed: GogInstalled =
+            match Self::read_json(&config_dir.join("gog_store").join("installed.json")) {
+                Some(installed) => installed,
+                None => return Vec::new(),
+            };
+
+        let mut titles: HashMap<String, String> = HashMap::new();
+        for path in [
+            config_dir.join("store_cache").join("gog_library.json"),
+            config_dir.join("gog_store").join("library.json"),
+        ] {
+            if let Some(library) = Self::read_json::<GogLibrary>(&path) {
+                for entry in library.games {
+                    if !entry.title.is_empty() {
+                        titles.entry(entry.app_name).or_insert(entry.title);
+                    }
+                }
+            }
+        }
+
+        installed
+            .installed
+            .into_iter()
+            .filter(|entry| !entry.is_dlc && entry.platform.eq_ignore_ascii_case("windows"))
+            .map(|entry| {
+                // GOG installs ship `goggame-<id>.info` with the title and launch tasks.
+                let info: Option<GogInfo> = Self::read_json(
+                    &entry
+                        .install_path
+                        .join(format!("goggame-{}.info", entry.app_name)),
+                );
+                let title = titles
+                    .get(&entry.app_name)
+                    .cloned()
+                    .or_else(|| info.as_ref().and_then(|info| info.name.clone()))
+                    .unwrap_or_else(|| {
+                        entry
+                            .install_path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_else(|| entry.app_name.clone())
+                    });
+                let executable = info
+                    .as_ref()
+                    .and_then(|info| Self::gog_primary_exe(&entry.install_path, info))
+                    .or_else(|| Self::relative_exe(&entry.install_path, &entry.executable));
+                HeroicGame {
+                    wine_prefix: Self::wine_prefix(config_dir, &entry.app_name),
+                    app_name: entry.app_name,
+                    title,
+                    store: HeroicStore::Gog,
+                    install_path: entry.install_path,
+                    executable,
+                }
+            })
+            .collect()
+    }
+
+    fn epic_games(config_dir: &Path) -> Vec<HeroicGame> {
+        let path = config_dir
+            .join("legendaryConfig")
+            .join("legendary")
+            .join("installed.json");
+        let installed: HashMap<String, LegendaryInstalled> = match Self::read_json(&path) {
+            Some(installed) => installed,
+            None => return Vec::new(),
+        };
+
+        installed
+            .into_values()
+            .filter(|entry| !entry.is_dlc && entry.platform.eq_ignore_ascii_case("windows"))
+            .map(|entry| HeroicGame {
+                wine_prefix: Self::wine_prefix(config_dir, &entry.app_name),
+                executable: Self::relative_exe(&entry.install_path, &entry.executable),
+                title: if entry.title.is_empty() {
+                    entry.app_name.clone()
+                } else {
+                    entry.title
+                },
+                app_name: entry.app_name,
+                store: HeroicStore::Epic,
+                install_path: entry.install_path,
+            })
+            .collect()
+    }
+
+    fn gog_primary_exe(install_path: &Path, info: &GogInfo) -> Option<PathBuf> {
+        let task = info
+            .play_tasks
+            .iter()
+            .find(|task| task.is_primary && task.path.is_some())
+            .or_else(|| info.play_tasks.iter().find(|task| task.path.is_some()))?;
+        Self::relative_exe(install_path, task.path.as_deref()?)
+    }
+
+    fn relative_exe(install_path: &Path, executable: &str) -> Option<PathBuf> {
+        let executable = executable.trim().replace('\\', "/");
+        if executable.is_empty() {
+            return None;
+        }
+        let path = Path::new(&executable);
+        let relative = path.strip_prefix(install_path).unwrap_or(path);
+        let relative = relative.strip_prefix("/").unwrap_or(relative).to_path_buf();
+        if install_path.join(&relative).is_file() {
+            Some(relative)
+        } else {
+            None
+        }
+    }
+
+    /// Prefix from Heroic's per-game config. Proton prefixes keep the actual
+    /// Wine prefix in `pfx/`.
+    fn wine_prefix(config_dir: &Path, app_name: &str) -> Option<PathBuf> {
+        let path = config_dir
+            .join("GamesConfig")
+            .join(format!("{}.json", app_name));
+        // The file also carries top-level keys like "version", so read it loosely.
+        let config: serde_json::Value = Self::read_json(&path)?;
+        let prefix = PathBuf::from(config.get(app_name)?.get("winePrefix")?.as_str()?);
+        let proton_prefix = prefix.join("pfx");
+        if proton_prefix.join("drive_c").is_dir() {
+            Some(proton_prefix)
+        } else if prefix.join("drive_c").is_dir() {
+            Some(prefix)
+        } else {
+            None
+        }
+    }
+
+    fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+        let content = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                eprintln!("Failed to parse {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::network;
+use crate::core::umu_database::UmuDatabase;
+
+const BASE_URL: &str = "https://howlongtobeat.com";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// The site's search endpoint moves and carries a key baked into its
+/// frontend bundle; this is only tried when the key can't be found.
+const FALLBACK_SEARCH_PATH: &str = "/api/search";
+
+/// Average completion times for a game, in seconds. Zero means HowLongToBeat
+/// has no submissions for that style of play.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HltbTimes {
+    pub game_id: u64,
+    pub game_name: String,
+    pub main: u64,
+    pub main_extra: u64,
+    pub completionist: u64,
+}
+
+impl HltbTimes {
+    pub fn page_url(&self) -> String {
+        format!("{}/game/{}", BASE_URL, self.game_id)
+    }
+
+    /// "Main story 12½ h • Main + extras 20 h • Completionist 41 h"
+    pub fn describe(&self) -> String {
+        [
+            ("Main story", self.main),
+            ("Main + extras", self.main_extra),
+            ("Completionist", self.completionist),
+        ]
+        .iter()
+        .map(|(label, seconds)| format!("{} {}", label, format_hours(*seconds)))
+        .collect::<Vec<_>>()
+        .join(" • ")
+    }
+}
+
+/// Hours rounded to the half hour, the way the site shows them.
+fn format_hours(seconds: u64) -> String {
+    if seconds == 0 {
+        return "--".to_string();
+    }
+    let halves = (seconds + 900) / 1800;
+    match (halves / 2, halves % 2) {
+        (0, _) => "½ h".to_string(),
+        (hours, 0) => format!("{} h", hours),
+        (hours, _) => format!("{}½ h", hours),
+    }
+}
+
+/// A cached lookup. `times` is `None` when the search found nothing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HltbLookup {
+    pub times: Option<HltbTimes>,
+    pub fetched_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    data: Vec<SearchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    game_id: u64,
+    game_name: String,
+    #[serde(default)]
+    comp_main: u64,
+    #[serde(default)]
+    comp_plus: u64,
+    #[serde(default)]
+    comp_100: u64,
+}
+
+pub struct HowLongToBeat;
+
+impl HowLongToBeat {
+    /// Cached times for `title`, if it was looked up before.
+    pub fn cached(title: &str) -> Option<HltbLookup> {
+        Self::load_cache().remove(&UmuDatabase::normalize_title(title))
+    }
+
+    /// Search HowLongToBeat for `title`, preferring an exact name match
+    /// over the site's first result, and cache the answer.
+    pub fn lookup(title: &str) -> Result<HltbLookup> {
+        let client = network::client_builder()?
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let search_path = Self::search_path(&client).unwrap_or_else(|e| {
+            eprintln!("HowLongToBeat search key not found, using fallback: {}", e);
+            FALLBACK_SEARCH_PATH.to_string()
+        });
+        let url = format!("{}{}", BASE_URL, search_path);
+        let body = json!({
+            "searchType": "games",
+            "searchTerms": title.split_whitespace().collect::<Vec<_>>(),
+            "searchPage": 1,
+            "size": 20,
+            "searchOptions": {
+                "games": {
+                    "userId": 0,
+                    "platform": "",
+                    "sortCategory": "popular",
+                    "rangeCategory": "main",
+                    "rangeTime": { "min": null, "max": null },
+                    "gameplay": { "perspective": "", "flow": "", "genre": "" },
+                    "rangeYear": { "min": "", "max": "" },
+                    "modifier": "",
+                },
+                "users": { "sortCategory": "postcount" },
+                "filter": "",
+                "sort": 0,
+                "randomizer": 0,
+            },
+        });
+        let response = client
+            .post(&url)
+            // The API turns away requests that don't look like the site's own.
+            .header(reqwest::header::REFERER, format!("{}/", BASE_URL))
+            .header(reqwest::header::ORIGIN, BASE_URL)
+            .json(&body)
+            .send()
+            .with_context(|| format!("Failed to request {}", url))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "HowLongToBeat search failed with status: {}",
+                response.status()
+            );
+        }
+        let results = response
+            .json::<SearchResponse>()
+            .context("Failed to parse HowLongToBeat search results")?
+            .data;
+
+        let wanted = UmuDatabase::normalize_title(title);
+        let best = results
+            .iter()
+            .find(|result| UmuDatabase::normalize_title(&result.game_name) == wanted)
+            .or_else(|| results.first());
+        let lookup = HltbLookup {
+            times: best.map(|result| HltbTimes {
+                game_id: result.game_id,
+                game_name: result.game_name.clone(),
+                main: result.comp_main,
+                main_extra: result.comp_plus,
+                completionist: result.comp_100,
+            }),
+            fetched_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+        };
+
+        let mut cache = Self::load_cache();
+        cache.insert(wanted, lookup.clone());
+        if let Err(e) = Self::save_cache(&cache) {
+            eprintln!("Failed to save HowLongToBeat cache: {}", e);
+        }
+        Ok(lookup)
+    }
+
+    /// The site's frontend calls `fetch("/api/<name>/".concat("<key>")...`
+    /// from its `_app` bundle; read the path and key from there.
+    fn search_path(client: &reqwest::blocking::Client) -> Result<String> {
+        let home = client
+            .get(BASE_URL)
+            .header(reqwest::header::REFERER, format!("{}/", BASE_URL))
+            .send()
+            .context("Failed to load the HowLongToBeat home page")?
+            .text()?;
+        let script = home
+            .split("src=\"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .find(|src| src.contains("/_app-") && src.ends_with(".js"))
+            .context("No app bundle on the home page")?;
+        let script_url = if script.starts_with("http") {
+            script.to_string()
+        } else {
+            format!("{}{}", BASE_URL, script)
+        };
+        let source = client
+            .get(&script_url)
+            .send()
+            .with_context(|| format!("Failed to load {}", script_url))?
+            .text()?;
+        Self::parse_search_path(&source).context("Search call not found in the app bundle")
+    }
+
+    fn parse_search_path(source: &str) -> Option<String> {
+        source
+            .match_indices("fetch(\"/api/")
+            .find_map(|(start, _)| {
+                let call = &source[start + "fetch(\"".len()..];
+                let path_end = call.find('"')?;
+                let mut path = call[..path_end].to_string();
+                let mut rest = &call[path_end + 1..];
+                // Key fragments follow as `.concat("abc")` calls.
+                while let Some(next) = rest.strip_prefix(".concat(\"") {
+                    let end = next.find('"')?;
+                    path.push_str(&next[..end]);
+                    rest = next[end..].strip_prefix("\")")?;
+                }
+                let mut parts = path["/api/".len()..].split('/');
+                let has_key = matches!(
+                    (parts.next(), parts.next()),
+                    (Some(name), Some(key)) if !name.is_empty() && !key.is_empty()
+                );
+                has_key.then_some(path)
+            })
+    }
+
+    fn load_cache() -> HashMap<String, HltbLookup> {
+        Self::cache_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_cache(cache: &HashMap<String, HltbLookup>) -> Result<()> {
+        let path = Self::cache_path().context("Home directory not available")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create cache dir {:?}", parent))?;
+        }
+        let content =
+            serde_json::to_string(cache).context("Failed to serialize HowLongToBeat cache")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write HowLongToBeat cache at {:?}", path))?;
+        Ok(())
+    }
+
+    fn cache_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| {
+            home.join(".linuxboy")
+                .join("cache")
+                .join("howlongtobeat.json")
+        })
+    }
+}
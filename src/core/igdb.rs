@@ -0,0 +1,320 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::network;
+use crate::core::system_checker::SystemCheck;
+use crate::core::umu_database::UmuDatabase;
+use crate::utils::file_ops;
+
+const TOKEN_URL: &str = "https://id.twitch.tv/oauth2/token";
+const GAMES_URL: &str = "https://api.igdb.com/v4/games";
+const IMAGE_URL: &str = "https://images.igdb.com/igdb/image/upload";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Search results compared against the title before settling for the first.
+const SEARCH_LIMIT: usize = 10;
+/// Artwork URLs kept per game.
+const ARTWORK_KEPT: usize = 4;
+/// A token this close to expiring is renewed before use.
+const TOKEN_MARGIN_SECS: u64 = 300;
+
+/// IGDB credentials, stored in `~/.linuxboy/igdb.json`. IGDB authenticates
+/// through a Twitch application of the user's own.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IgdbSettings {
+    #[serde(default)]
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: String,
+}
+
+impl IgdbSettings {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("igdb.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize IGDB settings")?;
+        file_ops::write_private(&path, content).context("Failed to write IGDB settings")?;
+        // New credentials need a new token.
+        let _ = fs::remove_file(Igdb::token_path());
+        Ok(())
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.client_id.trim().is_empty() && !self.client_secret.trim().is_empty()
+    }
+}
+
+/// What IGDB knows about a game, kept in the capsule's metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IgdbInfo {
+    pub id: u64,
+    /// The title IGDB matched.
+    pub name: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// First release, as Unix seconds.
+    #[serde(default)]
+    pub release_date: Option<i64>,
+    #[serde(default)]
+    pub developer: Option<String>,
+    #[serde(default)]
+    pub cover_url: Option<String>,
+    #[serde(default)]
+    pub artwork_urls: Vec<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    pub fetched_at: u64,
+}
+
+impl IgdbInfo {
+    /// "2015-05-19", when IGDB has a date.
+    pub fn release_date_text(&self) -> Option<String> {
+        self.release_date
+            .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+            .map(|date| date.format("%Y-%m-%d").to_string())
+    }
+
+    pub fn release_year(&self) -> Option<u32> {
+        self.release_date
+            .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+            .map(|date| date.format("%Y").to_string())
+            .and_then(|year| year.parse().ok())
+    }
+
+    /// Where the cover is kept once downloaded, in the capsule home.
+    pub fn cover_path(home_path: &Path) -> PathBuf {
+        home_path.join("igdb_cover.jpg")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedToken {
+    access_token: String,
+    expires_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GameResult {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    genres: Vec<Named>,
+    #[serde(default)]
+    first_release_date: Option<i64>,
+    #[serde(default)]
+    involved_companies: Vec<InvolvedCompany>,
+    #[serde(default)]
+    cover: Option<ImageRef>,
+    #[serde(default)]
+    artworks: Vec<ImageRef>,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InvolvedCompany {
+    company: Named,
+    #[serde(default)]
+    developer: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageRef {
+    image_id: String,
+}
+
+/// Looks games up on IGDB by title.
+pub struct Igdb {
+    settings: IgdbSettings,
+}
+
+impl Igdb {
+    pub fn new(settings: IgdbSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Search IGDB for `title`, preferring an exact name match over the
+    /// most relevant result. `Ok(None)` means nothing matched.
+    pub fn lookup(&self, title: &str) -> Result<Option<IgdbInfo>> {
+        if !self.settings.is_configured() {
+            anyhow::bail!("Add your IGDB client ID and secret in Preferences first");
+        }
+        let client = network::client_builder()?
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let token = self.token(&client)?;
+        let query = format!(
+            "search \"{}\"; fields name, summary, genres.name, first_release_date, \
+             involved_companies.company.name, involved_companies.developer, cover.image_id, \
+             artworks.image_id, url; limit {};",
+            title.replace(['"', '\\'], " "),
+            SEARCH_LIMIT
+        );
+        let response = client
+            .post(GAMES_URL)
+            .header("Client-ID", self.settings.client_id.trim())
+            .bearer_auth(&token)
+            .body(query)
+            .send()
+            .context("Failed to request IGDB")?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            // Revoked early; the next lookup gets a fresh one.
+            let _ = fs::remove_file(Self::token_path());
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("IGDB search failed with status: {}", response.status());
+        }
+        let results: Vec<GameResult> = response
+            .json()
+            .context("Failed to parse IGDB search results")?;
+
+        let wanted = UmuDatabase::normalize_title(title);
+        let best = results
+            .iter()
+            .position(|result| UmuDatabase::normalize_title(&result.name) == wanted)
+            .unwrap_or(0);
+        Ok(results.into_iter().nth(best).map(Self::info))
+    }
+
+    /// Download the cover to `dest`.
+    pub fn download_cover(info: &IgdbInfo, dest: &Path) -> Result<()> {
+        let url = info
+            .cover_url
+            .as_deref()
+            .context("IGDB has no cover for this game")?;
+        let client = network::client_builder()?
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let bytes = client
+            .get(url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to download {}", url))?
+            .bytes()?;
+        fs::write(dest, &bytes).with_context(|| format!("Failed to write {:?}", dest))?;
+        Ok(())
+    }
+
+    fn info(result: GameResult) -> IgdbInfo {
+        let image =
+            |size: &str, image: &ImageRef| format!("{}/{}/{}.jpg", IMAGE_URL, size, image.image_id);
+        IgdbInfo {
+            id: result.id,
+            name: result.name,
+            summary: result.summary.filter(|summary| !summary.trim().is_empty()),
+            genres: result.genres.into_iter().map(|genre| genre.name).collect(),
+            release_date: result.first_release_date,
+            developer: result
+                .involved_companies
+                .into_iter()
+                .find(|company| company.developer)
+                .map(|company| company.company.name),
+            cover_url: result
+                .cover
+                .as_ref()
+                .map(|cover| image("t_cover_big", cover)),
+            artwork_urls: result
+                .artworks
+                .iter()
+                .take(ARTWORK_KEPT)
+                .map(|artwork| image("t_1080p", artwork))
+                .collect(),
+            url: result.url,
+            fetched_at: now(),
+        }
+    }
+
+    /// A cached app access token, or a new one from Twitch.
+    fn token(&self, client: &reqwest::blocking::Client) -> Result<String> {
+        let cached = fs::read_to_string(Self::token_path())
+            .ok()
+            .and_then(|content| serde_json::from_str::<CachedToken>(&content).ok())
+            .filter(|token| token.expires_at > now() + TOKEN_MARGIN_SECS);
+        if let Some(token) = cached {
+            return Ok(token.access_token);
+        }
+
+        let response = client
+            .post(TOKEN_URL)
+            .query(&[
+                ("client_id", self.settings.client_id.trim()),
+                ("client_secret", self.settings.client_secret.trim()),
+                ("grant_type", "client_credentials"),
+            ])
+            .send()
+            .context("Failed to request an IGDB access token")?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Twitch refused the IGDB credentials (status {}); check the client ID and secret",
+                response.status()
+            );
+        }
+        let token: TokenResponse = response
+            .json()
+            .context("Failed to parse the IGDB access token")?;
+
+        let cached = CachedToken {
+            access_token: token.access_token.clone(),
+            expires_at: now() + token.expires_in,
+        };
+        if let Err(e) = Self::save_token(&cached) {
+            eprintln!("Failed to cache the IGDB access token: {}", e);
+        }
+        Ok(token.access_token)
+    }
+
+    fn save_token(token: &CachedToken) -> Result<()> {
+        let path = Self::token_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string(token).context("Failed to serialize IGDB token")?;
+        file_ops::write_private(&path, content)
+            .with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    fn token_path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir()
+            .join("cache")
+            .join("igdb_token.json")
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,65 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::core::capsule::CapsuleMetadata;
+
+/// How the installer a capsule was created from compares with what was
+/// recorded when it first ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallerCheck {
+    /// Same path, same SHA-256.
+    Unchanged(PathBuf),
+    /// The file is there but no checksum was recorded, as in capsules
+    /// created before checksums were kept.
+    Unrecorded(PathBuf),
+    /// No installer was recorded, or it is no longer at its path.
+    Missing(Option<PathBuf>),
+    /// The file at the recorded path hashes differently.
+    Changed(PathBuf),
+}
+
+/// The installer recorded in a capsule's metadata: its original path and
+/// SHA-256, so a later run notices it was replaced or moved.
+pub struct InstallerSource;
+
+impl InstallerSource {
+    /// Compare the recorded installer with the file on disk. Reads the
+    /// whole installer, so call it off the UI thread.
+    pub fn check(metadata: &CapsuleMetadata) -> Result<InstallerCheck> {
+        let path = match metadata.installer_path.as_deref() {
+            Some(path) if !path.trim().is_empty() => PathBuf::from(path),
+            _ => return Ok(InstallerCheck::Missing(None)),
+        };
+        if !path.is_file() {
+            return Ok(InstallerCheck::Missing(Some(path)));
+        }
+        let expected = match metadata.installer_sha256.as_deref() {
+            Some(expected) => expected,
+            None => return Ok(InstallerCheck::Unrecorded(path)),
+        };
+        if Self::sha256(&path)?.eq_ignore_ascii_case(expected) {
+            Ok(InstallerCheck::Unchanged(path))
+        } else {
+            Ok(InstallerCheck::Changed(path))
+        }
+    }
+
+    pub fn sha256(path: &Path) -> Result<String> {
+        let mut file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 256 * 1024];
+        loop {
+            let bytes_read = file
+                .read(&mut buffer)
+                .with_context(|| format!("Failed to read {:?}", path))?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::core::capsule::Capsule;
+
+const MANIFEST_NAME: &str = "file_manifest.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Hashes of every file in a game folder, keyed by path relative to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileManifest {
+    pub created_at: String,
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+impl FileManifest {
+    pub fn total_size(&self) -> u64 {
+        self.files.values().map(|entry| entry.size).sum()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    pub checked: usize,
+    pub modified: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        if self.is_clean() {
+            format!("Game files OK: {} file(s) checked", self.checked)
+        } else {
+            format!(
+                "Game files changed: {} modified, {} missing of {} file(s)",
+                self.modified.len(),
+                self.missing.len(),
+                self.checked
+            )
+        }
+    }
+}
+
+/// Records and checks a hash manifest for a capsule's game folder. The
+/// manifest lives in `<name>.AppImage.home/file_manifest.json`.
+pub struct IntegrityChecker {
+    game_dir: PathBuf,
+    manifest_path: PathBuf,
+}
+
+impl IntegrityChecker {
+    pub fn new(capsule: &Capsule, game_dir: &Path) -> Self {
+        Self {
+            game_dir: game_dir.to_path_buf(),
+            manifest_path: capsule.home_path.join(MANIFEST_NAME),
+        }
+    }
+
+    pub fn load_manifest(&self) -> Result<Option<FileManifest>> {
+        if !self.manifest_path.is_file() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.manifest_path)
+            .with_context(|| format!("Failed to read {:?}", self.manifest_path))?;
+        let manifest = serde_json::from_str(&content).context("Failed to parse file manifest")?;
+        Ok(Some(manifest))
+    }
+
+    /// Bytes that recording will read, for progress reporting.
+    pub fn folder_size(&self) -> u64 {
+        self.files().iter().map(|(_, size)| size).sum()
+    }
+
+    /// Hash every file in the game folder and save the manifest. The callback
+    /// gets each chunk read and returns false to cancel.
+    pub fn record(&self, on_bytes: &mut dyn FnMut(u64) -> bool) -> Result<FileManifest> {
+        let mut files = BTreeMap::new();
+        for (relative, size) in self.files() {
+            let sha256 = self.hash(&relative, on_bytes)?;
+            files.insert(relative, ManifestEntry { size, sha256 });
+        }
+
+        let manifest = FileManifest {
+            created_at: chrono::Local::now().to_rfc3339(),
+            files,
+        };
+        let content =
+            serde_json::to_string_pretty(&manifest).context("Failed to serialize file manifest")?;
+        fs::write(&self.manifest_path, content)
+            .with_context(|| format!("Failed to write {:?}", self.manifest_path))?;
+        Ok(manifest)
+    }
+
+    /// Re-hash the files listed in the manifest. Files whose size already
+    /// differs are reported without reading them.
+    pub fn verify(&self, on_bytes: &mut dyn FnMut(u64) -> bool) -> Result<IntegrityReport> {
+        let manifest = self
+            .load_manifest()?
+            .context("No file manifest recorded for this game")?;
+
+        let mut report = IntegrityReport::default();
+        for (relative, entry) in &manifest.files {
+            report.checked += 1;
+            let path = self.game_dir.join(relative);
+            let size = match fs::metadata(&path) {
+                Ok(metadata) if metadata.is_file() => metadata.len(),
+                _ => {
+                    report.missing.push(relative.clone());
+                    continue;
+                }
+            };
+            if size != entry.size {
+                if !on_bytes(entry.size) {
+                    anyhow::bail!("Cancelled");
+                }
+                report.modified.push(relative.clone());
+                continue;
+            }
+            if self.hash(relative, on_bytes)? != entry.sha256 {
+                report.modified.push(relative.clone());
+            }
+        }
+        Ok(report)
+    }
+
+    /// Regular files below the game folder as `(relative path, size)`.
+    fn files(&self) -> Vec<(String, u64)> {
+        WalkDir::new(&self.game_dir)
+            .follow_links(false)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| {
+                let size = entry.metadata().ok()?.len();
+                let relative = entry.path().strip_prefix(&self.game_dir).ok()?;
+                Some((relative.to_string_lossy().to_string(), size))
+            })
+            .collect()
+    }
+
+    fn hash(&self, relative: &str, on_bytes: &mut dyn FnMut(u64) -> bool) -> Result<String> {
+        let path = self.game_dir.join(relative);
+        let mut file = File::open(&path).with_context(|| format!("Failed to open {:?}", path))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 256 * 1024];
+        loop {
+            let bytes_read = file.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+            if !on_bytes(bytes_read as u64) {
+                anyhow::bail!("Cancelled");
+            }
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
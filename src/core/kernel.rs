@@ -0,0 +1,86 @@
+use std::fs;
+
+const MAX_MAP_COUNT_PATH: &str = "/proc/sys/vm/max_map_count";
+const OSRELEASE_PATH: &str = "/proc/sys/kernel/osrelease";
+/// What Fedora, Ubuntu and Arch now ship; SteamOS goes higher still.
+pub const RECOMMENDED_MAX_MAP_COUNT: u64 = 1_048_576;
+const SYSCTL_CONF: &str = "/etc/sysctl.d/80-linuxboy-max-map-count.conf";
+
+/// Kernel features that affect how well Proton runs.
+#[derive(Debug, Clone)]
+pub struct KernelCapabilities {
+    pub release: Option<String>,
+    /// `futex_waitv` (Linux 5.16+) backs Proton's fsync; without it Proton
+    /// falls back to the slower esync.
+    pub futex_waitv: bool,
+    pub max_map_count: Option<u64>,
+}
+
+impl KernelCapabilities {
+    pub fn detect() -> Self {
+        Self {
+            release: fs::read_to_string(OSRELEASE_PATH)
+                .ok()
+                .map(|release| release.trim().to_string()),
+            futex_waitv: Self::probe_futex_waitv(),
+            max_map_count: fs::read_to_string(MAX_MAP_COUNT_PATH)
+                .ok()
+                .and_then(|value| value.trim().parse().ok()),
+        }
+    }
+
+    /// Games such as Hogwarts Legacy and DayZ crash once they run out of
+    /// memory mappings at the old default of 65530.
+    pub fn max_map_count_ok(&self) -> bool {
+        self.max_map_count
+            .map(|count| count >= RECOMMENDED_MAX_MAP_COUNT)
+            .unwrap_or(true)
+    }
+
+    pub fn futex_explanation(&self) -> &'static str {
+        if self.futex_waitv {
+            "futex_waitv is available, so Proton uses fsync for faster thread synchronization."
+        } else {
+            "futex_waitv is missing (needs Linux 5.16 or newer), so Proton falls back to esync. \
+             Games run, but CPU-heavy ones may stutter more."
+        }
+    }
+
+    pub fn max_map_count_explanation(&self) -> String {
+        match self.max_map_count {
+            Some(count) if count >= RECOMMENDED_MAX_MAP_COUNT => {
+                format!("vm.max_map_count is {}.", count)
+            }
+            Some(count) => format!(
+                "vm.max_map_count is {}. Some modern games crash below {}.",
+                count, RECOMMENDED_MAX_MAP_COUNT
+            ),
+            None => format!("Could not read {}.", MAX_MAP_COUNT_PATH),
+        }
+    }
+
+    /// Raises the limit now and keeps it across reboots. Needs root.
+    pub fn max_map_count_fix_script() -> String {
+        format!(
+            "sysctl -w vm.max_map_count={count} && echo 'vm.max_map_count = {count}' > {conf}",
+            count = RECOMMENDED_MAX_MAP_COUNT,
+            conf = SYSCTL_CONF
+        )
+    }
+
+    /// Call `futex_waitv` with no futexes: kernels that have it reject the
+    /// arguments with EINVAL, older ones return ENOSYS.
+    fn probe_futex_waitv() -> bool {
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_futex_waitv,
+                std::ptr::null::<libc::c_void>(),
+                0u32,
+                0u32,
+                std::ptr::null::<libc::c_void>(),
+                libc::CLOCK_MONOTONIC,
+            )
+        };
+        result == 0 || std::io::Error::last_os_error().raw_os_error() != Some(libc::ENOSYS)
+    }
+}
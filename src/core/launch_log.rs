@@ -0,0 +1,169 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use crate::core::capsule::Capsule;
+use crate::core::system_checker::SystemCheck;
+
+const MB: u64 = 1024 * 1024;
+
+/// Limits for the game output kept per capsule, stored in
+/// `~/.linuxboy/logs.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSettings {
+    /// Size at which the current log is rotated, in MB.
+    #[serde(default = "default_max_size_mb")]
+    pub max_size_mb: u64,
+    /// Rotated logs kept next to the current one.
+    #[serde(default = "default_max_files")]
+    pub max_files: u32,
+}
+
+fn default_max_size_mb() -> u64 {
+    20
+}
+
+fn default_max_files() -> u32 {
+    3
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            max_size_mb: default_max_size_mb(),
+            max_files: default_max_files(),
+        }
+    }
+}
+
+impl LogSettings {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("logs.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize log settings")?;
+        fs::write(&path, content).context("Failed to write log settings")?;
+        Ok(())
+    }
+
+    fn max_bytes(&self) -> u64 {
+        self.max_size_mb.max(1) * MB
+    }
+}
+
+/// The game output log of a capsule, `last_launch.log` in its home, and
+/// its rotated copies `last_launch.1.log` (newest) to `last_launch.N.log`.
+pub struct LaunchLog;
+
+impl LaunchLog {
+    /// Rotate the previous launch's log and open a fresh one. The file is
+    /// opened for appending, so `enforce_limit` can empty it while the game
+    /// keeps writing.
+    pub fn open(capsule: &Capsule, settings: &LogSettings) -> Result<File> {
+        let path = capsule.launch_log_path();
+        if path.metadata().map(|meta| meta.len() > 0).unwrap_or(false) {
+            Self::rotate(&path, settings)?;
+        }
+        let _ = fs::remove_file(&path);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to create {:?}", path))
+    }
+
+    /// Rotate the current log when it has grown past the size limit. The
+    /// game's writes between the copy and the truncation are lost.
+    pub fn enforce_limit(capsule: &Capsule, settings: &LogSettings) -> Result<bool> {
+        let path = capsule.launch_log_path();
+        let size = path.metadata().map(|meta| meta.len()).unwrap_or(0);
+        if size <= settings.max_bytes() {
+            return Ok(false);
+        }
+        Self::rotate(&path, settings)?;
+        // Emptied in place: the game still holds the file open.
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_len(0))
+            .with_context(|| format!("Failed to truncate {:?}", path))?;
+        Ok(true)
+    }
+
+    /// Every log of the capsule, current one first.
+    pub fn files(capsule: &Capsule) -> Vec<PathBuf> {
+        let path = capsule.launch_log_path();
+        let mut files = Vec::new();
+        if path.exists() {
+            files.push(path.clone());
+        }
+        let mut index = 1;
+        loop {
+            let rotated = Self::rotated_path(&path, index);
+            if !rotated.exists() {
+                break;
+            }
+            files.push(rotated);
+            index += 1;
+        }
+        files
+    }
+
+    /// Remove every log of the capsule and return the bytes freed.
+    pub fn clear(capsule: &Capsule) -> Result<u64> {
+        let mut freed = 0;
+        for file in Self::files(capsule) {
+            let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            fs::remove_file(&file).with_context(|| format!("Failed to remove {:?}", file))?;
+            freed += size;
+        }
+        Ok(freed)
+    }
+
+    /// Copy `path` to `.1`, shifting older copies up and dropping those
+    /// past `max_files`. A copy rather than a rename, because the running
+    /// game may still write to `path`.
+    fn rotate(path: &Path, settings: &LogSettings) -> Result<()> {
+        let keep = settings.max_files;
+        // Copies past the limit, e.g. after it was lowered.
+        let mut index = keep + 1;
+        while fs::remove_file(Self::rotated_path(path, index)).is_ok() {
+            index += 1;
+        }
+        if keep == 0 {
+            return Ok(());
+        }
+        for index in (1..keep).rev() {
+            let from = Self::rotated_path(path, index);
+            if from.exists() {
+                fs::rename(&from, Self::rotated_path(path, index + 1))
+                    .with_context(|| format!("Failed to rotate {:?}", from))?;
+            }
+        }
+        fs::copy(path, Self::rotated_path(path, 1))
+            .with_context(|| format!("Failed to rotate {:?}", path))?;
+        Ok(())
+    }
+
+    fn rotated_path(path: &Path, index: u32) -> PathBuf {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        path.with_file_name(format!("{}.{}.log", stem, index))
+    }
+}
@@ -0,0 +1,66 @@
+use std::ffi::OsStr;
+use std::process::Command;
+
+/// Parts of variable names whose values are not shown.
+const SECRET_MARKERS: [&str; 6] = ["TOKEN", "SECRET", "PASSWORD", "PASSWD", "API_KEY", "AUTH"];
+
+/// Renders a launch command as shell lines that can be pasted into a
+/// terminal: the working folder, the variables set for the game and the
+/// command line. Variables inherited from LinuxBoy's own environment are
+/// left out.
+pub struct LaunchPreview;
+
+impl LaunchPreview {
+    pub fn render(cmd: &Command) -> String {
+        let mut lines = Vec::new();
+        if let Some(dir) = cmd.get_current_dir() {
+            lines.push(format!("cd {}", quote(dir.as_os_str())));
+        }
+
+        let mut envs: Vec<_> = cmd.get_envs().collect();
+        envs.sort_by(|a, b| a.0.cmp(b.0));
+        // `env` takes removals before assignments.
+        let mut command: Vec<String> = envs
+            .iter()
+            .filter(|(_, value)| value.is_none())
+            .map(|(key, _)| format!("-u {}", key.to_string_lossy()))
+            .collect();
+        for (key, value) in &envs {
+            let value = match value {
+                Some(_) if is_secret(key) => quote(OsStr::new("<hidden>")),
+                Some(value) => quote(value),
+                None => continue,
+            };
+            command.push(format!("{}={}", key.to_string_lossy(), value));
+        }
+        if !command.is_empty() {
+            command.insert(0, "env".to_string());
+        }
+        command.push(quote(cmd.get_program()));
+        let args: Vec<String> = cmd.get_args().map(quote).collect();
+        if !args.is_empty() {
+            command.push(args.join(" "));
+        }
+        lines.push(command.join(" \\\n  "));
+        lines.join("\n")
+    }
+}
+
+fn is_secret(key: &OsStr) -> bool {
+    let key = key.to_string_lossy().to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+/// `text` quoted for a POSIX shell when it needs it.
+fn quote(text: &OsStr) -> String {
+    let text = text.to_string_lossy();
+    let plain = !text.is_empty()
+        && text
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "_-./:=,+@%".contains(ch));
+    if plain {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
@@ -2,3 +2,50 @@ pub mod capsule;
 pub mod system_checker;
 pub mod runtime_manager;
 pub mod umu_database;
+pub mod snapshots;
+pub mod saves;
+pub mod sandbox;
+pub mod cloud_sync;
+pub mod backup;
+pub mod heroic;
+pub mod winetricks;
+pub mod registry;
+pub mod prefix_processes;
+pub mod diagnostics;
+pub mod repair;
+pub mod integrity;
+pub mod support_bundle;
+pub mod title_match;
+pub mod protonfixes;
+pub mod package_manager;
+pub mod umu_installer;
+pub mod display_server;
+pub mod kernel;
+pub mod network;
+pub mod redist;
+pub mod appimage_builder;
+pub mod archival;
+pub mod dedupe;
+pub mod duplicates;
+pub mod portable;
+pub mod shell_link;
+pub mod gamescope;
+pub mod gamepad;
+pub mod steam_deck;
+pub mod steam_shortcuts;
+pub mod discord;
+pub mod protondb;
+pub mod howlongtobeat;
+pub mod igdb;
+pub mod screenshots;
+pub mod play_history;
+pub mod templates;
+pub mod new_game_defaults;
+pub mod removal;
+pub mod runner;
+pub mod launch_preview;
+pub mod terminal;
+pub mod launch_log;
+pub mod mods;
+pub mod uninstaller;
+pub mod installer_source;
@@ -0,0 +1,560 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+use crate::core::capsule::Capsule;
+use crate::utils::host;
+
+const MODS_DIR: &str = "mods";
+const STATE_FILE: &str = ".mods.json";
+const STAGING_DIR: &str = ".staging";
+const BACKUP_DIR: &str = ".backup";
+
+/// Archives the mods folder accepts. Plain and gzip or zstd tarballs are
+/// read directly; the rest need `7z`, `bsdtar` or, for zip, `unzip` on the
+/// host.
+pub const ARCHIVE_SUFFIXES: [&str; 9] = [
+    ".zip", ".7z", ".rar", ".tar", ".tar.gz", ".tgz", ".tar.xz", ".tar.bz2", ".tar.zst",
+];
+
+/// How a mod's files are put into the game folder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployMode {
+    #[default]
+    Copy,
+    /// Links into the staging folder; saves space, but the game sees the
+    /// files change if the staged copy does.
+    Symlink,
+}
+
+/// A file a mod put into the game folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployedFile {
+    /// Relative to the game folder.
+    pub path: String,
+    /// The file it replaced was moved to the mod's backup folder.
+    pub backed_up: bool,
+}
+
+/// One archive or folder in the capsule's mods folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModEntry {
+    /// Its file or folder name in the mods folder.
+    pub id: String,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub mode: DeployMode,
+    /// When it was enabled relative to the other mods; a later mod wins
+    /// where two ship the same file.
+    #[serde(default)]
+    pub order: u64,
+    #[serde(default)]
+    pub files: Vec<DeployedFile>,
+    /// Folders created in the game folder for its files, relative to it.
+    #[serde(default)]
+    pub created_dirs: Vec<String>,
+    /// Why it couldn't be extracted, if it couldn't.
+    #[serde(skip)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ModState {
+    #[serde(default)]
+    mods: Vec<ModEntry>,
+    #[serde(default)]
+    next_order: u64,
+}
+
+/// The mods of one capsule. Archives or folders dropped into `mods` in the
+/// capsule home are extracted to `mods/.staging`; enabling one copies or
+/// links its files into the game folder and records each in a manifest,
+/// moving the files it replaces to `mods/.backup`, so disabling it puts the
+/// game back as it was.
+pub struct ModManager {
+    root: PathBuf,
+    game_dir: PathBuf,
+}
+
+impl ModManager {
+    pub fn new(capsule: &Capsule) -> Result<Self> {
+        let game_dir = capsule
+            .install_root()
+            .or_else(|| capsule.game_folder())
+            .context("The game folder is not known yet; finish setup first")?;
+        Ok(Self {
+            root: Self::mods_dir(capsule),
+            game_dir,
+        })
+    }
+
+    /// Where mods are dropped.
+    pub fn mods_dir(capsule: &Capsule) -> PathBuf {
+        capsule.home_path.join(MODS_DIR)
+    }
+
+    /// Pick up mods added to or removed from the mods folder and extract
+    /// new or updated archives. Mods that fail to extract are listed with
+    /// their error.
+    pub fn sync(&self) -> Result<Vec<ModEntry>> {
+        fs::create_dir_all(&self.root)
+            .with_context(|| format!("Failed to create {:?}", self.root))?;
+        let mut state = self.load_state();
+
+        let mut sources = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            if entry.path().is_dir() || is_archive(&name) {
+                sources.push(name);
+            }
+        }
+
+        // Enabled mods stay listed without their source so they can still
+        // be disabled.
+        let gone: Vec<String> = state
+            .mods
+            .iter()
+            .filter(|entry| !entry.enabled && !sources.contains(&entry.id))
+            .map(|entry| entry.id.clone())
+            .collect();
+        for id in &gone {
+            let _ = fs::remove_dir_all(self.root.join(STAGING_DIR).join(id));
+        }
+        state.mods.retain(|entry| !gone.contains(&entry.id));
+        for source in sources {
+            if !state.mods.iter().any(|entry| entry.id == source) {
+                state.mods.push(ModEntry {
+                    id: source,
+                    enabled: false,
+                    mode: DeployMode::default(),
+                    order: 0,
+                    files: Vec::new(),
+                    created_dirs: Vec::new(),
+                    error: None,
+                });
+            }
+        }
+
+        for entry in state.mods.iter_mut().filter(|entry| !entry.enabled) {
+            if let Err(e) = self.stage(&entry.id) {
+                eprintln!("Failed to extract mod {}: {:#}", entry.id, e);
+                entry.error = Some(format!("{:#}", e));
+            }
+        }
+        state.mods.sort_by_key(|entry| entry.id.to_lowercase());
+        self.save_state(&state)?;
+        Ok(state.mods)
+    }
+
+    /// Put the mod's files into the game folder. Returns how many were
+    /// deployed. A failure part way takes back what was already placed.
+    pub fn enable(&self, id: &str, mode: DeployMode) -> Result<usize> {
+        let mut state = self.load_state();
+        let index = Self::position(&state, id)?;
+        if state.mods[index].enabled {
+            return Ok(state.mods[index].files.len());
+        }
+        let staged = self
+            .staged_path(id)
+            .with_context(|| format!("{} has not been extracted", id))?;
+
+        state.mods[index].enabled = true;
+        state.mods[index].mode = mode;
+        state.mods[index].order = state.next_order;
+        state.next_order += 1;
+
+        let mut files: Vec<PathBuf> = WalkDir::new(&staged)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| !entry.file_type().is_dir())
+            .filter_map(|entry| {
+                entry
+                    .path()
+                    .strip_prefix(&staged)
+                    .ok()
+                    .map(Path::to_path_buf)
+            })
+            .collect();
+        files.sort();
+
+        for relative in files {
+            if let Err(e) = self.deploy(&mut state.mods[index], &staged, &relative, mode) {
+                let _ = self.undeploy(&mut state, index);
+                self.save_state(&state)?;
+                return Err(e);
+            }
+        }
+        let count = state.mods[index].files.len();
+        self.save_state(&state)?;
+        Ok(count)
+    }
+
+    /// Take the mod's files out of the game folder and restore what they
+    /// replaced. Returns how many were removed.
+    pub fn disable(&self, id: &str) -> Result<usize> {
+        let mut state = self.load_state();
+        let index = Self::position(&state, id)?;
+        if !state.mods[index].enabled {
+            return Ok(0);
+        }
+        let result = self.undeploy(&mut state, index);
+        // What was undone is recorded even when a file couldn't be.
+        self.save_state(&state)?;
+        result
+    }
+
+    fn position(state: &ModState, id: &str) -> Result<usize> {
+        state
+            .mods
+            .iter()
+            .position(|entry| entry.id == id)
+            .with_context(|| format!("No mod named {}", id))
+    }
+
+    fn deploy(
+        &self,
+        entry: &mut ModEntry,
+        staged: &Path,
+        relative: &Path,
+        mode: DeployMode,
+    ) -> Result<()> {
+        let target_relative = self.resolve(relative, &mut entry.created_dirs)?;
+        let target = self.game_dir.join(&target_relative);
+        let mut backed_up = false;
+        if let Ok(meta) = fs::symlink_metadata(&target) {
+            if meta.is_dir() {
+                anyhow::bail!(
+                    "{:?} is a folder in the game but a file in the mod",
+                    relative
+                );
+            }
+            let backup = self.backup_path(&entry.id, &target_relative);
+            move_file(&target, &backup)?;
+            backed_up = true;
+        }
+
+        let source = staged.join(relative);
+        let placed = match mode {
+            DeployMode::Copy => fs::copy(&source, &target).map(|_| ()),
+            DeployMode::Symlink => std::os::unix::fs::symlink(&source, &target),
+        };
+        if let Err(e) = placed {
+            if backed_up {
+                let _ = move_file(&self.backup_path(&entry.id, &target_relative), &target);
+            }
+            return Err(e).with_context(|| format!("Failed to place {:?}", target));
+        }
+        entry.files.push(DeployedFile {
+            path: target_relative.to_string_lossy().to_string(),
+            backed_up,
+        });
+        Ok(())
+    }
+
+    /// Remove the files of `state.mods[index]`. Where a mod enabled later
+    /// replaced the same file, that mod's backup holds this mod's version;
+    /// it gets this mod's backup instead, so disabling it later still
+    /// restores the original.
+    fn undeploy(&self, state: &mut ModState, index: usize) -> Result<usize> {
+        let entry = state.mods[index].clone();
+        let mut removed = 0;
+        let mut failed = None;
+        for file in entry.files.iter().rev() {
+            let above = state
+                .mods
+                .iter()
+                .enumerate()
+                .filter(|(other, other_entry)| {
+                    *other != index
+                        && other_entry.enabled
+                        && other_entry.order > entry.order
+                        && other_entry
+                            .files
+                            .iter()
+                            .any(|other| other.path == file.path)
+                })
+                .min_by_key(|(_, other_entry)| other_entry.order)
+                .map(|(other, _)| other);
+            let result = match above {
+                Some(other) => self.hand_over(state, &entry, file, other),
+                None => self.restore(&entry, file),
+            };
+            match result {
+                Ok(()) => removed += 1,
+                Err(e) => {
+                    eprintln!("Failed to restore {}: {:#}", file.path, e);
+                    failed.get_or_insert(e);
+                }
+            }
+        }
+
+        let mut dirs = entry.created_dirs.clone();
+        dirs.sort_by_key(|dir| std::cmp::Reverse(dir.len()));
+        for dir in dirs {
+            // Only removed when empty; other mods may have put files there.
+            let _ = fs::remove_dir(self.game_dir.join(dir));
+        }
+        let _ = fs::remove_dir_all(self.root.join(BACKUP_DIR).join(&entry.id));
+
+        let entry = &mut state.mods[index];
+        entry.enabled = false;
+        entry.files.clear();
+        entry.created_dirs.clear();
+        match failed {
+            Some(e) => Err(e),
+            None => Ok(removed),
+        }
+    }
+
+    /// Take `file` out of the game folder and move back what it replaced.
+    fn restore(&self, entry: &ModEntry, file: &DeployedFile) -> Result<()> {
+        let target = self.game_dir.join(&file.path);
+        if fs::symlink_metadata(&target).is_ok() {
+            fs::remove_file(&target).with_context(|| format!("Failed to remove {:?}", target))?;
+        }
+        if file.backed_up {
+            move_file(&self.backup_path(&entry.id, Path::new(&file.path)), &target)?;
+        }
+        Ok(())
+    }
+
+    /// Leave the game file of `state.mods[other]` in place and pass it the
+    /// original that `entry` had backed up.
+    fn hand_over(
+        &self,
+        state: &mut ModState,
+        entry: &ModEntry,
+        file: &DeployedFile,
+        other: usize,
+    ) -> Result<()> {
+        let other_entry = &mut state.mods[other];
+        let other_backup = self.backup_path(&other_entry.id, Path::new(&file.path));
+        if fs::symlink_metadata(&other_backup).is_ok() {
+            fs::remove_file(&other_backup)
+                .with_context(|| format!("Failed to remove {:?}", other_backup))?;
+        }
+        if file.backed_up {
+            move_file(
+                &self.backup_path(&entry.id, Path::new(&file.path)),
+                &other_backup,
+            )?;
+        }
+        if let Some(other_file) = other_entry
+            .files
+            .iter_mut()
+            .find(|other_file| other_file.path == file.path)
+        {
+            other_file.backed_up = file.backed_up;
+        }
+        Ok(())
+    }
+
+    /// `relative` as a path in the game folder, matching existing folders
+    /// regardless of case, since Windows mods rarely agree with the game on
+    /// it. Missing folders are created and recorded in `created_dirs`.
+    fn resolve(&self, relative: &Path, created_dirs: &mut Vec<String>) -> Result<PathBuf> {
+        let mut resolved = PathBuf::new();
+        let mut parts: Vec<&std::ffi::OsStr> = Vec::new();
+        for component in relative.components() {
+            match component {
+                Component::Normal(part) => parts.push(part),
+                _ => anyhow::bail!("{:?} points outside the game folder", relative),
+            }
+        }
+        let file_name = match parts.pop() {
+            Some(name) => name,
+            None => anyhow::bail!("Empty path in mod"),
+        };
+        for part in parts {
+            let dir = self.game_dir.join(&resolved);
+            let existing = fs::read_dir(&dir).ok().and_then(|entries| {
+                entries.filter_map(|entry| entry.ok()).find(|entry| {
+                    entry.path().is_dir()
+                        && entry
+                            .file_name()
+                            .to_string_lossy()
+                            .eq_ignore_ascii_case(&part.to_string_lossy())
+                })
+            });
+            match existing {
+                Some(entry) => resolved.push(entry.file_name()),
+                None => {
+                    resolved.push(part);
+                    let path = self.game_dir.join(&resolved);
+                    fs::create_dir(&path)
+                        .with_context(|| format!("Failed to create {:?}", path))?;
+                    created_dirs.push(resolved.to_string_lossy().to_string());
+                }
+            }
+        }
+        // Files too, so a mod's `data.pak` replaces the game's `Data.pak`.
+        let dir = self.game_dir.join(&resolved);
+        let existing = fs::read_dir(&dir).ok().and_then(|entries| {
+            entries.filter_map(|entry| entry.ok()).find(|entry| {
+                !entry.path().is_dir()
+                    && entry
+                        .file_name()
+                        .to_string_lossy()
+                        .eq_ignore_ascii_case(&file_name.to_string_lossy())
+            })
+        });
+        match existing {
+            Some(entry) => resolved.push(entry.file_name()),
+            None => resolved.push(file_name),
+        }
+        Ok(resolved)
+    }
+
+    /// Extract an archive mod to its staging folder unless it is there and
+    /// newer than the archive. Folder mods are used in place.
+    fn stage(&self, id: &str) -> Result<()> {
+        let source = self.root.join(id);
+        if source.is_dir() {
+            return Ok(());
+        }
+        let staging = self.root.join(STAGING_DIR).join(id);
+        let modified = |path: &Path| path.metadata().and_then(|meta| meta.modified()).ok();
+        if staging.is_dir() && modified(&staging) >= modified(&source) {
+            return Ok(());
+        }
+
+        let partial = self.root.join(STAGING_DIR).join(format!("{}.partial", id));
+        let _ = fs::remove_dir_all(&partial);
+        fs::create_dir_all(&partial).with_context(|| format!("Failed to create {:?}", partial))?;
+        if let Err(e) = extract(&source, &partial) {
+            let _ = fs::remove_dir_all(&partial);
+            return Err(e);
+        }
+        let _ = fs::remove_dir_all(&staging);
+        fs::rename(&partial, &staging).with_context(|| format!("Failed to stage {}", id))?;
+        Ok(())
+    }
+
+    fn staged_path(&self, id: &str) -> Option<PathBuf> {
+        let source = self.root.join(id);
+        if source.is_dir() {
+            return Some(source);
+        }
+        let staging = self.root.join(STAGING_DIR).join(id);
+        staging.is_dir().then_some(staging)
+    }
+
+    fn backup_path(&self, id: &str, relative: &Path) -> PathBuf {
+        self.root.join(BACKUP_DIR).join(id).join(relative)
+    }
+
+    fn load_state(&self) -> ModState {
+        fs::read_to_string(self.root.join(STATE_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_state(&self, state: &ModState) -> Result<()> {
+        let content =
+            serde_json::to_string_pretty(state).context("Failed to serialize mod state")?;
+        let path = self.root.join(STATE_FILE);
+        fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+}
+
+fn is_archive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ARCHIVE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Move a file, copying it when `to` is on another filesystem, as when the
+/// game folder lives outside the capsule.
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if fs::symlink_metadata(from)
+        .map(|meta| meta.file_type().is_symlink())
+        .unwrap_or(false)
+    {
+        let link = fs::read_link(from)?;
+        std::os::unix::fs::symlink(link, to)
+            .with_context(|| format!("Failed to move {:?}", from))?;
+    } else {
+        fs::copy(from, to).with_context(|| format!("Failed to move {:?}", from))?;
+    }
+    fs::remove_file(from).with_context(|| format!("Failed to remove {:?}", from))?;
+    Ok(())
+}
+
+fn extract(archive: &Path, dest: &Path) -> Result<()> {
+    let name = archive.to_string_lossy().to_lowercase();
+    let open = || File::open(archive).with_context(|| format!("Failed to open {:?}", archive));
+    if name.ends_with(".tar") {
+        return tar::Archive::new(open()?)
+            .unpack(dest)
+            .with_context(|| format!("Failed to extract {:?}", archive));
+    }
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        return tar::Archive::new(flate2::read::GzDecoder::new(open()?))
+            .unpack(dest)
+            .with_context(|| format!("Failed to extract {:?}", archive));
+    }
+    if name.ends_with(".tar.zst") {
+        return tar::Archive::new(zstd::stream::read::Decoder::new(open()?)?)
+            .unpack(dest)
+            .with_context(|| format!("Failed to extract {:?}", archive));
+    }
+
+    // 7z only strips the outer compression of these and leaves a .tar.
+    let compressed_tar = name.ends_with(".tar.xz") || name.ends_with(".tar.bz2");
+    let cmd = if compressed_tar {
+        let program = ["bsdtar", "tar"]
+            .into_iter()
+            .find(|program| host::has_command(program))
+            .context("Install tar or bsdtar to extract this mod")?;
+        let mut cmd = Command::new(program);
+        cmd.arg("-xf").arg(archive).arg("-C").arg(dest);
+        cmd
+    } else if host::has_command("7z") {
+        let mut cmd = Command::new("7z");
+        cmd.arg("x")
+            .arg("-y")
+            .arg(format!("-o{}", dest.display()))
+            .arg(archive);
+        cmd
+    } else if host::has_command("bsdtar") {
+        let mut cmd = Command::new("bsdtar");
+        cmd.arg("-xf").arg(archive).arg("-C").arg(dest);
+        cmd
+    } else if name.ends_with(".zip") && host::has_command("unzip") {
+        let mut cmd = Command::new("unzip");
+        cmd.arg("-q").arg("-o").arg(archive).arg("-d").arg(dest);
+        cmd
+    } else if name.ends_with(".zip") {
+        anyhow::bail!("Install unzip, 7z or bsdtar to extract zip mods");
+    } else {
+        anyhow::bail!("Install 7z or bsdtar to extract this mod");
+    };
+    let output = host::command(cmd)
+        .output()
+        .with_context(|| format!("Failed to extract {:?}", archive))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Extracting {:?} failed: {}",
+            archive,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::core::system_checker::SystemCheck;
+use crate::utils::file_ops;
+
+const USER_AGENT: &str = "LinuxBoy/0.1";
+
+/// An explicit HTTP proxy. When disabled, the standard `HTTP_PROXY`,
+/// `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` variables apply instead.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub port: u16,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+}
+
+impl ProxySettings {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("network.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize network settings")?;
+        // Holds the proxy password.
+        file_ops::write_private(&path, content).context("Failed to write network settings")?;
+        Ok(())
+    }
+
+    /// `http://host:port`, or `None` when no explicit proxy is set. A host
+    /// that already names a scheme keeps it.
+    pub fn proxy_url(&self) -> Option<String> {
+        let host = self.host.trim().trim_end_matches('/');
+        if !self.enabled || host.is_empty() {
+            return None;
+        }
+        let host = if host.contains("://") {
+            host.to_string()
+        } else {
+            format!("http://{}", host)
+        };
+        Some(if self.port > 0 {
+            format!("{}:{}", host, self.port)
+        } else {
+            host
+        })
+    }
+
+    fn proxy(&self) -> Result<Option<reqwest::Proxy>> {
+        let url = match self.proxy_url() {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+        let mut proxy = reqwest::Proxy::all(&url)
+            .with_context(|| format!("Invalid proxy address {}", url))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        if !self.username.trim().is_empty() {
+            proxy = proxy.basic_auth(self.username.trim(), &self.password);
+        }
+        Ok(Some(proxy))
+    }
+}
+
+/// Client builder every LinuxBoy HTTP request starts from, with the user
+/// agent and proxy settings applied.
+pub fn client_builder() -> Result<reqwest::blocking::ClientBuilder> {
+    let builder = reqwest::blocking::Client::builder().user_agent(USER_AGENT);
+    // An explicit proxy replaces the ones reqwest reads from the environment.
+    Ok(match ProxySettings::load().proxy()? {
+        Some(proxy) => builder.proxy(proxy),
+        None => builder,
+    })
+}
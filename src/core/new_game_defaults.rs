@@ -0,0 +1,103 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::core::capsule::CapsuleMetadata;
+use crate::core::system_checker::SystemCheck;
+
+/// Settings every new capsule starts with, stored in
+/// `~/.linuxboy/new_game_defaults.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewGameDefaults {
+    /// Used when the game's store isn't known from the UMU database or
+    /// the launcher it was imported from.
+    #[serde(default)]
+    pub store: Option<String>,
+    #[serde(default = "default_true")]
+    pub install_vcredist: bool,
+    #[serde(default = "default_true")]
+    pub install_dxweb: bool,
+    #[serde(default)]
+    pub xalia_enabled: bool,
+    #[serde(default)]
+    pub protonfixes_disable: bool,
+    #[serde(default)]
+    pub env_vars: Vec<(String, String)>,
+}
+
+impl Default for NewGameDefaults {
+    fn default() -> Self {
+        Self {
+            store: None,
+            install_vcredist: true,
+            install_dxweb: true,
+            xalia_enabled: false,
+            protonfixes_disable: false,
+            env_vars: Vec::new(),
+        }
+    }
+}
+
+impl NewGameDefaults {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("new_game_defaults.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize new game defaults")?;
+        fs::write(&path, content).context("Failed to write new game defaults")?;
+        Ok(())
+    }
+
+    pub fn apply(&self, metadata: &mut CapsuleMetadata) {
+        if self.store.is_some() {
+            metadata.store = self.store.clone();
+        }
+        metadata.install_vcredist = self.install_vcredist;
+        metadata.install_dxweb = self.install_dxweb;
+        metadata.xalia_enabled = self.xalia_enabled;
+        metadata.protonfixes_disable = self.protonfixes_disable;
+        for (key, value) in &self.env_vars {
+            metadata.env_vars.retain(|(existing, _)| existing != key);
+            metadata.env_vars.push((key.clone(), value.clone()));
+        }
+    }
+
+    /// `KEY=VALUE`, one per line, for editing.
+    pub fn env_vars_text(&self) -> String {
+        self.env_vars
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Read `KEY=VALUE` lines; blank lines and lines without a key are
+    /// skipped.
+    pub fn parse_env_vars(text: &str) -> Vec<(String, String)> {
+        text.lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                let key = key.trim();
+                (!key.is_empty()).then(|| (key.to_string(), value.trim().to_string()))
+            })
+            .collect()
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
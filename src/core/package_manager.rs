@@ -0,0 +1,226 @@
+use std::fs;
+use std::process::Command;
+
+use crate::utils::host;
+
+const PACMAN_CONF: &str = "/etc/pacman.conf";
+/// Turns on Debian multiarch so the :i386 packages can be installed.
+pub const ENABLE_I386_SCRIPT: &str = "dpkg --add-architecture i386 && apt-get update";
+
+/// The distribution package manager used to install graphics packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Apt,
+    Dnf,
+    Pacman,
+    Zypper,
+}
+
+impl PackageManager {
+    const ALL: [PackageManager; 4] = [
+        PackageManager::Apt,
+        PackageManager::Dnf,
+        PackageManager::Pacman,
+        PackageManager::Zypper,
+    ];
+
+    /// The first supported package manager found in PATH.
+    pub fn detect() -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|manager| host::has_command(manager.binary()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackageManager::Apt => "apt",
+            PackageManager::Dnf => "dnf",
+            PackageManager::Pacman => "pacman",
+            PackageManager::Zypper => "zypper",
+        }
+    }
+
+    fn binary(&self) -> &'static str {
+        match self {
+            PackageManager::Apt => "apt-get",
+            PackageManager::Dnf => "dnf",
+            PackageManager::Pacman => "pacman",
+            PackageManager::Zypper => "zypper",
+        }
+    }
+
+    /// Vulkan loader and tools, 64- and 32-bit.
+    pub fn vulkan_packages(&self) -> &'static [&'static str] {
+        match self {
+            PackageManager::Apt => &["vulkan-tools", "libvulkan1", "libvulkan1:i386"],
+            PackageManager::Dnf => &["vulkan-tools", "vulkan-loader", "vulkan-loader.i686"],
+            PackageManager::Pacman => &[
+                "vulkan-tools",
+                "vulkan-icd-loader",
+                "lib32-vulkan-icd-loader",
+            ],
+            PackageManager::Zypper => &["vulkan-tools", "libvulkan1", "libvulkan1-32bit"],
+        }
+    }
+
+    /// Mesa OpenGL and Vulkan drivers, 64- and 32-bit.
+    pub fn mesa_packages(&self) -> &'static [&'static str] {
+        match self {
+            PackageManager::Apt => &[
+                "mesa-vulkan-drivers",
+                "mesa-vulkan-drivers:i386",
+                "libgl1-mesa-dri:amd64",
+                "libgl1-mesa-dri:i386",
+                "libglx-mesa0:amd64",
+                "libglx-mesa0:i386",
+            ],
+            PackageManager::Dnf => &[
+                "mesa-vulkan-drivers",
+                "mesa-vulkan-drivers.i686",
+                "mesa-dri-drivers",
+                "mesa-dri-drivers.i686",
+                "mesa-libGL.i686",
+            ],
+            PackageManager::Pacman => &[
+                "mesa",
+                "lib32-mesa",
+                "vulkan-radeon",
+                "lib32-vulkan-radeon",
+                "vulkan-intel",
+                "lib32-vulkan-intel",
+            ],
+            PackageManager::Zypper => &[
+                "Mesa-dri",
+                "Mesa-dri-32bit",
+                "libvulkan_radeon",
+                "libvulkan_radeon-32bit",
+                "libvulkan_intel",
+                "libvulkan_intel-32bit",
+            ],
+        }
+    }
+
+    /// Whether an `[multilib]` section is enabled in pacman.conf. Arch ships
+    /// it commented out, and every lib32-* package lives there.
+    pub fn multilib_enabled() -> bool {
+        fs::read_to_string(host::host_file(PACMAN_CONF))
+            .map(|content| content.lines().any(|line| line.trim() == "[multilib]"))
+            .unwrap_or(false)
+    }
+
+    /// Whether 32-bit packages can be installed. Only apt needs this turned
+    /// on; a fresh Debian install has no foreign architectures.
+    pub fn i386_enabled(&self) -> bool {
+        if *self != PackageManager::Apt {
+            return true;
+        }
+        let mut dpkg = Command::new("dpkg");
+        dpkg.arg("--print-foreign-architectures");
+        host::command(dpkg)
+            .output()
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .any(|arch| arch.trim() == "i386")
+            })
+            .unwrap_or(false)
+    }
+
+    /// Something about the system's repositories the user should fix before
+    /// 32-bit games will run.
+    pub fn repository_warning(&self) -> Option<String> {
+        if *self == PackageManager::Pacman && !Self::multilib_enabled() {
+            return Some(format!(
+                "The multilib repository is not enabled in {}, so lib32 packages are skipped. \
+                 Uncomment the [multilib] section and run pacman -Syu to support 32-bit games.",
+                PACMAN_CONF
+            ));
+        }
+        None
+    }
+
+    /// Commands that install `packages` non-interactively, without privilege
+    /// escalation. pacman leaves out lib32 packages when multilib is off.
+    pub fn install_steps(&self, packages: &[String]) -> Vec<Vec<String>> {
+        let install: &[&str] = match self {
+            PackageManager::Apt => &["apt-get", "install", "-y"],
+            PackageManager::Dnf => &["dnf", "install", "-y"],
+            PackageManager::Pacman => &["pacman", "-S", "--needed", "--noconfirm"],
+            PackageManager::Zypper => &["zypper", "--non-interactive", "install"],
+        };
+        let to_args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        let skip_lib32 = *self == PackageManager::Pacman && !Self::multilib_enabled();
+        let mut install = to_args(install);
+        install.extend(
+            packages
+                .iter()
+                .filter(|package| !(skip_lib32 && package.starts_with("lib32-")))
+                .cloned(),
+        );
+        vec![install]
+    }
+
+    /// The same steps as one shell command line.
+    pub fn install_script(&self, packages: &[String]) -> String {
+        self.install_steps(packages)
+            .iter()
+            .map(|step| step.join(" "))
+            .collect::<Vec<_>>()
+            .join(" && ")
+    }
+
+    /// The command a user would paste into a terminal.
+    pub fn install_command(&self, packages: &[String]) -> String {
+        self.install_steps(packages)
+            .iter()
+            .map(|step| format!("sudo {}", step.join(" ")))
+            .collect::<Vec<_>>()
+            .join(" && ")
+    }
+}
+
+/// Optional tools a game can be launched with, installed from distro packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamingTool {
+    GameMode,
+    MangoHud,
+}
+
+impl GamingTool {
+    pub const ALL: [GamingTool; 2] = [GamingTool::GameMode, GamingTool::MangoHud];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GamingTool::GameMode => "GameMode",
+            GamingTool::MangoHud => "MangoHud",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            GamingTool::GameMode => "Switches the CPU governor and priorities while a game runs.",
+            GamingTool::MangoHud => "Overlay showing FPS, frame times and GPU load.",
+        }
+    }
+
+    /// The host command whose presence means the tool is installed.
+    pub fn command(&self) -> &'static str {
+        match self {
+            GamingTool::GameMode => "gamemoderun",
+            GamingTool::MangoHud => "mangohud",
+        }
+    }
+
+    /// Packages to install, with the 32-bit library where the distro splits
+    /// it out (32-bit games preload the 32-bit one).
+    pub fn packages(&self, manager: PackageManager) -> Vec<String> {
+        let packages: &[&str] = match (self, manager) {
+            (GamingTool::GameMode, PackageManager::Pacman) => &["gamemode", "lib32-gamemode"],
+            (GamingTool::GameMode, _) => &["gamemode"],
+            (GamingTool::MangoHud, PackageManager::Pacman) => &["mangohud", "lib32-mangohud"],
+            (GamingTool::MangoHud, _) => &["mangohud"],
+        };
+        packages.iter().map(|package| package.to_string()).collect()
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::capsule::Capsule;
+
+const HISTORY_FILE: &str = "play_history.json";
+/// Launches kept per game; older ones are dropped.
+const MAX_SESSIONS: usize = 500;
+/// This many failed launches in a row marks a game as crashing.
+const CRASH_STREAK: usize = 3;
+
+/// One launch of a game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Unix seconds.
+    pub started: u64,
+    pub duration_secs: u64,
+    /// `None` when the game was killed by a signal.
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    pub success: bool,
+}
+
+/// Launch history of a capsule, stored as `play_history.json` in its home.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayHistory {
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+}
+
+impl PlayHistory {
+    pub fn path(capsule: &Capsule) -> PathBuf {
+        capsule.home_path.join(HISTORY_FILE)
+    }
+
+    pub fn load(capsule: &Capsule) -> Self {
+        Self::load_from(&Self::path(capsule))
+    }
+
+    fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Append a finished launch to the capsule's history.
+    pub fn record(
+        capsule: &Capsule,
+        started: SystemTime,
+        exit_code: Option<i32>,
+        success: bool,
+    ) -> Result<()> {
+        let path = Self::path(capsule);
+        let mut history = Self::load_from(&path);
+        history.sessions.push(Session {
+            started: started
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+            duration_secs: started
+                .elapsed()
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0),
+            exit_code,
+            success,
+        });
+        if history.sessions.len() > MAX_SESSIONS {
+            let excess = history.sessions.len() - MAX_SESSIONS;
+            history.sessions.drain(..excess);
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            serde_json::to_string_pretty(&history).context("Failed to serialize play history")?;
+        fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    pub fn launch_count(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn last_crash(&self) -> Option<&Session> {
+        self.sessions.iter().rev().find(|session| !session.success)
+    }
+
+    pub fn last_success(&self) -> Option<&Session> {
+        self.sessions.iter().rev().find(|session| session.success)
+    }
+
+    pub fn average_session(&self) -> Option<Duration> {
+        if self.sessions.is_empty() {
+            return None;
+        }
+        let total: u64 = self
+            .sessions
+            .iter()
+            .map(|session| session.duration_secs)
+            .sum();
+        Some(Duration::from_secs(total / self.sessions.len() as u64))
+    }
+
+    pub fn total_played(&self) -> Duration {
+        Duration::from_secs(
+            self.sessions
+                .iter()
+                .map(|session| session.duration_secs)
+                .sum(),
+        )
+    }
+
+    /// The last few launches all failed.
+    pub fn is_crashing(&self) -> bool {
+        self.sessions.len() >= CRASH_STREAK
+            && self
+                .sessions
+                .iter()
+                .rev()
+                .take(CRASH_STREAK)
+                .all(|session| !session.success)
+    }
+}
+
+/// "2 h 05 min", "12 min" or "40 s".
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{} h {:02} min", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{} min", secs / 60)
+    } else {
+        format!("{} s", secs)
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::core::appimage_builder::AppImageBuilder;
+use crate::core::capsule::Capsule;
+use crate::utils::file_ops;
+
+const MANIFEST_NAME: &str = "bundle.json";
+const MANIFEST_VERSION: u32 = 1;
+const LAUNCHER_NAME: &str = "run.sh";
+/// Folder inside the bundle holding game files that lived outside the capsule.
+const GAME_DIR: &str = "game";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub capsule_name: String,
+    pub created_at: String,
+}
+
+/// A capsule laid out as a plain folder that can be copied to another
+/// machine: the capsule's own files (prefix included), the game files,
+/// `metadata.json` with paths relative to the bundle, and a `run.sh` that
+/// starts the game through umu-run without LinuxBoy.
+pub struct PortableBundle;
+
+impl PortableBundle {
+    pub fn is_bundle(dir: &Path) -> bool {
+        dir.join(MANIFEST_NAME).is_file() && dir.join("metadata.json").is_file()
+    }
+
+    pub fn read_manifest(dir: &Path) -> Result<BundleManifest> {
+        let content = fs::read_to_string(dir.join(MANIFEST_NAME))
+            .with_context(|| format!("{:?} is not a LinuxBoy bundle", dir))?;
+        let manifest: BundleManifest =
+            serde_json::from_str(&content).context("Failed to parse bundle.json")?;
+        if manifest.version > MANIFEST_VERSION {
+            anyhow::bail!(
+                "Bundle format {} is newer than this LinuxBoy supports",
+                manifest.version
+            );
+        }
+        Ok(manifest)
+    }
+
+    /// Write the bundle to `parent/<capsule name>`. Snapshots are left out.
+    /// Returns the bundle folder.
+    pub fn export(
+        capsule: &Capsule,
+        parent: &Path,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<PathBuf> {
+        let capsule_dir = fs::canonicalize(&capsule.capsule_dir)
+            .with_context(|| format!("Failed to resolve {:?}", capsule.capsule_dir))?;
+        let dest = parent.join(&capsule.name);
+        if dest.exists() {
+            anyhow::bail!("{:?} already exists", dest);
+        }
+        // Game files inside the capsule come along with it.
+        let game_folder = capsule
+            .game_folder()
+            .and_then(|folder| fs::canonicalize(folder).ok())
+            .filter(|folder| !folder.starts_with(&capsule_dir));
+
+        let snapshots = capsule.home_path.join("snapshots");
+        let snapshots = fs::canonicalize(&snapshots).unwrap_or(snapshots);
+        let total = file_ops::dir_size(&capsule_dir).saturating_sub(file_ops::dir_size(&snapshots))
+            + game_folder.as_deref().map(file_ops::dir_size).unwrap_or(0);
+        file_ops::ensure_free_space(parent, total)?;
+
+        let staging = parent.join(format!(".{}.bundle", capsule.name));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        let result = Self::write_bundle(
+            capsule,
+            &capsule_dir,
+            game_folder.as_deref(),
+            &snapshots,
+            &staging,
+            total,
+            on_progress,
+        )
+        .and_then(|_| fs::rename(&staging, &dest).context("Failed to move the bundle into place"));
+        if let Err(e) = result {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+        println!("Exported portable bundle of {} to {:?}", capsule.name, dest);
+        Ok(dest)
+    }
+
+    fn write_bundle(
+        capsule: &Capsule,
+        capsule_dir: &Path,
+        game_folder: Option<&Path>,
+        snapshots: &Path,
+        staging: &Path,
+        total: u64,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<()> {
+        let mut copied = 0u64;
+        let mut on_chunk = |chunk: u64| {
+            copied += chunk;
+            on_progress(copied, total)
+        };
+        file_ops::copy_dir_excluding(
+            capsule_dir,
+            staging,
+            &[snapshots.to_path_buf()],
+            &mut on_chunk,
+        )
+        .context("Failed to copy the capsule")?;
+
+        let mut metadata = capsule.metadata.clone();
+        if let Some(game_folder) = game_folder {
+            file_ops::copy_dir_with_progress(game_folder, &staging.join(GAME_DIR), &mut on_chunk)
+                .context("Failed to copy the game files")?;
+            Capsule::rebase_metadata_paths(&mut metadata, game_folder, Path::new(GAME_DIR));
+        }
+        // Paths relative to the bundle; import makes them absolute again.
+        Capsule::rebase_metadata_paths(&mut metadata, capsule_dir, Path::new(""));
+        Capsule::rebase_metadata_paths(&mut metadata, &capsule.capsule_dir, Path::new(""));
+        let bundled = Capsule {
+            name: capsule.name.clone(),
+            capsule_dir: staging.to_path_buf(),
+            home_path: staging.join(format!("{}.AppImage.home", capsule.name)),
+            metadata,
+        };
+        bundled.save_metadata()?;
+
+        let manifest = BundleManifest {
+            version: MANIFEST_VERSION,
+            capsule_name: capsule.name.clone(),
+            created_at: chrono::Local::now().to_rfc3339(),
+        };
+        let content = serde_json::to_string_pretty(&manifest)
+            .context("Failed to serialize bundle manifest")?;
+        fs::write(staging.join(MANIFEST_NAME), content).context("Failed to write bundle.json")?;
+
+        let exe = bundled.metadata.executables.main.path.trim();
+        if Path::new(exe).is_relative() && !exe.is_empty() {
+            let prefix = format!(
+                "\"$HERE\"/{}",
+                AppImageBuilder::shell_quote(&format!("{}.AppImage.home/prefix", capsule.name))
+            );
+            let launcher = staging.join(LAUNCHER_NAME);
+            fs::write(
+                &launcher,
+                AppImageBuilder::launcher_script(&bundled, Path::new(exe), &prefix),
+            )
+            .context("Failed to write run.sh")?;
+            fs::set_permissions(&launcher, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    }
+
+    /// Copy the bundle at `bundle_dir` into `games_dir` as a regular capsule.
+    /// Returns the new capsule directory.
+    pub fn import(
+        bundle_dir: &Path,
+        games_dir: &Path,
+        on_progress: &mut dyn FnMut(u64, u64) -> bool,
+    ) -> Result<PathBuf> {
+        let manifest = Self::read_manifest(bundle_dir)?;
+        let target = games_dir.join(&manifest.capsule_name);
+        if target.exists() {
+            anyhow::bail!("{:?} already exists", target);
+        }
+        let total = file_ops::dir_size(bundle_dir);
+        file_ops::ensure_free_space(games_dir, total)?;
+
+        let staging = games_dir.join(format!(".{}.import", manifest.capsule_name));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        let mut copied = 0u64;
+        let result = file_ops::copy_dir_excluding(
+            bundle_dir,
+            &staging,
+            &[
+                bundle_dir.join(MANIFEST_NAME),
+                bundle_dir.join(LAUNCHER_NAME),
+            ],
+            &mut |chunk| {
+                copied += chunk;
+                on_progress(copied, total)
+            },
+        )
+        .context("Failed to copy the bundle")
+        .and_then(|_| {
+            fs::rename(&staging, &target).context("Failed to move the capsule into place")
+        });
+        if let Err(e) = result {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+
+        let mut capsule = Capsule::load_from_dir(&target)?;
+        Capsule::rebase_metadata_paths(&mut capsule.metadata, Path::new(""), &target);
+        capsule.save_metadata()?;
+        println!(
+            "Imported portable bundle of {} into {:?}",
+            capsule.name, target
+        );
+        Ok(target)
+    }
+}
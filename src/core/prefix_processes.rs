@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::utils::host;
+
+/// Wine processes that belong to one capsule prefix.
+pub struct PrefixProcesses {
+    prefix_path: PathBuf,
+}
+
+impl PrefixProcesses {
+    pub fn new(prefix_path: &Path) -> Self {
+        Self {
+            prefix_path: prefix_path.to_path_buf(),
+        }
+    }
+
+    /// Stop everything running in the prefix: ask the runtime's wineserver to
+    /// shut down, kill the given process groups, then kill any process still
+    /// running with this `WINEPREFIX`. Returns how many stragglers were killed.
+    pub fn shutdown(&self, proton_path: Option<&Path>, pgids: &[i32]) -> usize {
+        if let Some(proton_path) = proton_path {
+            let wineserver = proton_path.join("files").join("bin").join("wineserver");
+            if wineserver.is_file() {
+                let wineserver_command = |flag: &str| {
+                    let mut cmd = Command::new(&wineserver);
+                    cmd.env("WINEPREFIX", &self.prefix_path).arg(flag);
+                    host::command(cmd)
+                };
+                match wineserver_command("-k").status() {
+                    Ok(_) => {
+                        // -w returns once the server has exited.
+                        let _ = wineserver_command("-w").status();
+                    }
+                    Err(e) => eprintln!("Failed to run wineserver -k: {}", e),
+                }
+            }
+        }
+
+        for pgid in pgids {
+            unsafe {
+                libc::kill(-pgid, libc::SIGKILL);
+            }
+        }
+
+        let mut killed = 0;
+        for pid in self.running_pids() {
+            if unsafe { libc::kill(pid, libc::SIGKILL) } == 0 {
+                killed += 1;
+            }
+        }
+        killed
+    }
+
+    /// Processes whose environment points `WINEPREFIX` at this prefix.
+    /// Inside Flatpak only the sandbox's own processes are visible, so this
+    /// finds nothing there and wineserver -k does the work.
+    fn running_pids(&self) -> Vec<i32> {
+        let prefix =
+            fs::canonicalize(&self.prefix_path).unwrap_or_else(|_| self.prefix_path.clone());
+        let own_pid = std::process::id() as i32;
+        let entries = match fs::read_dir("/proc") {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<i32>().ok())
+            .filter(|pid| *pid != own_pid)
+            .filter(|pid| {
+                let environ = match fs::read(format!("/proc/{}/environ", pid)) {
+                    Ok(environ) => environ,
+                    Err(_) => return false,
+                };
+                environ
+                    .split(|byte| *byte == 0)
+                    .filter_map(|var| var.strip_prefix(b"WINEPREFIX="))
+                    .any(|value| {
+                        let value = PathBuf::from(String::from_utf8_lossy(value).to_string());
+                        fs::canonicalize(&value).unwrap_or(value) == prefix
+                    })
+            })
+            .collect()
+    }
+}
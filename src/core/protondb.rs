@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::network;
+
+const SUMMARY_URL: &str = "https://www.protondb.com/api/v1/reports/summaries";
+const REPORT_URL: &str = "https://www.protondb.com/app";
+/// Ratings move slowly; look again after a week.
+const CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// ProtonDB's summary rating for a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtonDbTier {
+    Native,
+    Platinum,
+    Gold,
+    Silver,
+    Bronze,
+    Borked,
+    /// Too few reports for a rating yet.
+    Pending,
+}
+
+impl ProtonDbTier {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "native" => Some(Self::Native),
+            "platinum" => Some(Self::Platinum),
+            "gold" => Some(Self::Gold),
+            "silver" => Some(Self::Silver),
+            "bronze" => Some(Self::Bronze),
+            "borked" => Some(Self::Borked),
+            "pending" => Some(Self::Pending),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Native => "Native",
+            Self::Platinum => "Platinum",
+            Self::Gold => "Gold",
+            Self::Silver => "Silver",
+            Self::Bronze => "Bronze",
+            Self::Borked => "Borked",
+            Self::Pending => "Pending",
+        }
+    }
+
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            Self::Native => "protondb-native",
+            Self::Platinum => "protondb-platinum",
+            Self::Gold => "protondb-gold",
+            Self::Silver => "protondb-silver",
+            Self::Bronze => "protondb-bronze",
+            Self::Borked => "protondb-borked",
+            Self::Pending => "protondb-pending",
+        }
+    }
+}
+
+/// A cached lookup. `tier` is `None` when ProtonDB has no reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtonDbRating {
+    pub tier: Option<ProtonDbTier>,
+    pub fetched_at: u64,
+}
+
+impl ProtonDbRating {
+    pub fn is_fresh(&self) -> bool {
+        now().saturating_sub(self.fetched_at) < CACHE_TTL.as_secs()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Summary {
+    #[serde(default)]
+    tier: Option<String>,
+}
+
+pub struct ProtonDb;
+
+impl ProtonDb {
+    /// The Steam app ID behind a UMU game ID. Games sold on Steam are
+    /// listed in the UMU database as `umu-<appid>`; other IDs are names.
+    pub fn steam_app_id(game_id: &str) -> Option<u32> {
+        let id = game_id.trim().strip_prefix("umu-")?;
+        if id.is_empty() || !id.chars().all(|ch| ch.is_ascii_digit()) {
+            return None;
+        }
+        id.parse().ok()
+    }
+
+    pub fn report_url(app_id: u32) -> String {
+        format!("{}/{}", REPORT_URL, app_id)
+    }
+
+    /// Ask ProtonDB for the game's current tier.
+    pub fn fetch(app_id: u32) -> Result<ProtonDbRating> {
+        let client = network::client_builder()?
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let url = format!("{}/{}.json", SUMMARY_URL, app_id);
+        let response = client
+            .get(&url)
+            .send()
+            .with_context(|| format!("Failed to request {}", url))?;
+        let tier = if response.status() == reqwest::StatusCode::NOT_FOUND {
+            None
+        } else if !response.status().is_success() {
+            anyhow::bail!("ProtonDB request failed with status: {}", response.status());
+        } else {
+            let summary = response
+                .json::<Summary>()
+                .context("Failed to parse ProtonDB summary")?;
+            summary.tier.as_deref().and_then(ProtonDbTier::parse)
+        };
+        Ok(ProtonDbRating {
+            tier,
+            fetched_at: now(),
+        })
+    }
+
+    /// Ratings from the on-disk cache, stale ones included.
+    pub fn load_cache() -> HashMap<u32, ProtonDbRating> {
+        Self::cache_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_cache(ratings: &HashMap<u32, ProtonDbRating>) -> Result<()> {
+        let path = Self::cache_path().context("Home directory not available")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create cache dir {:?}", parent))?;
+        }
+        let content =
+            serde_json::to_string(ratings).context("Failed to serialize ProtonDB cache")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write ProtonDB cache at {:?}", path))?;
+        Ok(())
+    }
+
+    fn cache_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".linuxboy").join("cache").join("protondb.json"))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,424 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::core::network;
+use crate::core::umu_database::UmuEntry;
+
+const PROTONFIXES_RAW_URL: &str =
+    "https://raw.githubusercontent.com/Open-Wine-Components/umu-protonfixes/master";
+/// Where protonfixes looks for user fixes, below the home directory. It
+/// imports `localfixes.<game id>` from here before every launch.
+const LOCAL_FIXES_DIR: &str = ".config/protonfixes/localfixes";
+const PYTHON_KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// What a protonfixes game module does, read from its `util.*` calls.
+#[derive(Debug, Clone, Default)]
+pub struct ProtonFixSummary {
+    /// Module path inside umu-protonfixes, e.g. `gamefixes-gog/umu-1207658930.py`.
+    pub module: String,
+    pub verbs: Vec<String>,
+    pub replace_cmds: Vec<(String, String)>,
+    pub env_vars: Vec<(String, String)>,
+    pub dll_overrides: Vec<(String, String)>,
+    pub arguments: Vec<String>,
+    /// Other `util` helpers the module calls, by name.
+    pub other_calls: Vec<String>,
+}
+
+impl ProtonFixSummary {
+    pub fn is_empty(&self) -> bool {
+        self.verbs.is_empty()
+            && self.replace_cmds.is_empty()
+            && self.env_vars.is_empty()
+            && self.dll_overrides.is_empty()
+            && self.arguments.is_empty()
+            && self.other_calls.is_empty()
+    }
+
+    /// One line per kind of fix, for showing under the match list.
+    pub fn describe(&self) -> String {
+        let pairs = |values: &[(String, String)], separator: &str| {
+            values
+                .iter()
+                .map(|(key, value)| format!("{}{}{}", key, separator, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut lines = vec![format!("Protonfixes module: {}", self.module)];
+        if !self.verbs.is_empty() {
+            lines.push(format!("Winetricks verbs: {}", self.verbs.join(", ")));
+        }
+        if !self.replace_cmds.is_empty() {
+            lines.push(format!(
+                "Replaces commands: {}",
+                pairs(&self.replace_cmds, " → ")
+            ));
+        }
+        if !self.env_vars.is_empty() {
+            lines.push(format!("Environment: {}", pairs(&self.env_vars, "=")));
+        }
+        if !self.dll_overrides.is_empty() {
+            lines.push(format!(
+                "DLL overrides: {}",
+                pairs(&self.dll_overrides, "=")
+            ));
+        }
+        if !self.arguments.is_empty() {
+            lines.push(format!("Extra arguments: {}", self.arguments.join(" ")));
+        }
+        if !self.other_calls.is_empty() {
+            lines.push(format!("Also calls: {}", self.other_calls.join(", ")));
+        }
+        if self.is_empty() {
+            lines.push("The module makes no changes protonfixes can list.".to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+/// Looks up the umu-protonfixes module that a GAMEID/STORE pair pulls in.
+pub struct ProtonFixes;
+
+impl ProtonFixes {
+    /// Download and summarize the module for `entry`. `Ok(None)` means the
+    /// ID has no game-specific fixes.
+    pub fn fetch(entry: &UmuEntry) -> Result<Option<ProtonFixSummary>> {
+        let client = network::client_builder()?.build()?;
+
+        for module in Self::module_paths(entry) {
+            let url = format!("{}/{}", PROTONFIXES_RAW_URL, module);
+            let response = client
+                .get(&url)
+                .send()
+                .with_context(|| format!("Failed to request {}", url))?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                continue;
+            }
+            if !response.status().is_success() {
+                anyhow::bail!(
+                    "Protonfixes request failed with status: {}",
+                    response.status()
+                );
+            }
+            let source = response
+                .text()
+                .context("Failed to read protonfixes module")?;
+            return Ok(Some(Self::parse(&module, &source)));
+        }
+        Ok(None)
+    }
+
+    /// Candidate module paths in the order protonfixes tries them: the
+    /// store's folder, then the store-less `gamefixes-umu`.
+    pub fn module_paths(entry: &UmuEntry) -> Vec<String> {
+        let umu_id = match entry.umu_id.as_deref().map(str::trim) {
+            Some(umu_id) if !umu_id.is_empty() => umu_id,
+            _ => return Vec::new(),
+        };
+        let store = entry
+            .store
+            .as_deref()
+            .map(|store| store.trim().to_ascii_lowercase())
+            .filter(|store| !store.is_empty() && store != "none");
+
+        let mut paths = Vec::new();
+        if let Some(store) = store.as_deref() {
+            if store == "steam" {
+                // Steam modules are named after the bare app ID.
+                if let Some(app_id) = umu_id.strip_prefix("umu-") {
+                    paths.push(format!("gamefixes-steam/{}.py", app_id));
+                }
+            }
+            paths.push(format!("gamefixes-{}/{}.py", store, umu_id));
+        }
+        paths.push(format!("gamefixes-umu/{}.py", umu_id));
+        paths.dedup();
+        paths
+    }
+
+    pub fn parse(module: &str, source: &str) -> ProtonFixSummary {
+        let mut summary = ProtonFixSummary {
+            module: module.to_string(),
+            ..Default::default()
+        };
+
+        for (name, args) in Self::util_calls(source) {
+            let pair = || match args.as_slice() {
+                [first, second, ..] => Some((first.clone(), second.clone())),
+                _ => None,
+            };
+            match name.as_str() {
+                "protontricks" => summary.verbs.extend(args.into_iter().take(1)),
+                "replace_command" => summary.replace_cmds.extend(pair()),
+                "set_environment" => summary.env_vars.extend(pair()),
+                "winedll_override" => summary.dll_overrides.extend(pair()),
+                "append_argument" => summary.arguments.extend(args.into_iter().take(1)),
+                _ => {
+                    if !summary.other_calls.contains(&name) {
+                        summary.other_calls.push(name);
+                    }
+                }
+            }
+        }
+        summary
+    }
+
+    /// `util.<name>(...)` calls outside comments, with their string literal
+    /// arguments. Non-literal arguments are skipped.
+    fn util_calls(source: &str) -> Vec<(String, Vec<String>)> {
+        let code: String = source
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start();
+                if trimmed.starts_with('#') {
+                    ""
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut calls = Vec::new();
+        let mut rest = code.as_str();
+        while let Some(start) = rest.find("util.") {
+            let after = &rest[start + "util.".len()..];
+            let name: String = after
+                .chars()
+                .take_while(|ch| ch.is_ascii_alphanumeric() || *ch == '_')
+                .collect();
+            let after_name = &after[name.len()..];
+            rest = after_name;
+            if name.is_empty() || !after_name.starts_with('(') {
+                continue;
+            }
+
+            let (args, consumed) = Self::string_args(&after_name[1..]);
+            rest = &after_name[1 + consumed..];
+            calls.push((name, args));
+        }
+        calls
+    }
+
+    /// String literals up to the closing parenthesis; returns them with the
+    /// number of bytes read.
+    fn string_args(input: &str) -> (Vec<String>, usize) {
+        let mut args = Vec::new();
+        let mut depth = 0;
+        let mut chars = input.char_indices();
+        while let Some((index, ch)) = chars.next() {
+            match ch {
+                '(' => depth += 1,
+                ')' if depth == 0 => return (args, index + 1),
+                ')' => depth -= 1,
+                '\'' | '"' => {
+                    let mut value = String::new();
+                    let mut escaped = false;
+                    for (_, next) in chars.by_ref() {
+                        if escaped {
+                            value.push(next);
+                            escaped = false;
+                        } else if next == '\\' {
+                            escaped = true;
+                        } else if next == ch {
+                            break;
+                        } else {
+                            value.push(next);
+                        }
+                    }
+                    if depth == 0 {
+                        args.push(value);
+                    }
+                }
+                _ => {}
+            }
+        }
+        (args, input.len())
+    }
+}
+
+/// A user-written protonfixes module for one game, kept as
+/// `~/.config/protonfixes/localfixes/<GAMEID>.py`. protonfixes runs it at
+/// launch whenever the game starts with that `GAMEID` and protonfixes isn't
+/// disabled.
+pub struct LocalFix {
+    pub game_id: String,
+    pub path: PathBuf,
+}
+
+impl LocalFix {
+    /// The local fix for `game_id`; `None` for an empty or default ID,
+    /// which would apply the fix to unrelated games.
+    pub fn for_game(game_id: &str) -> Option<LocalFix> {
+        let game_id = game_id.trim();
+        let valid = !game_id.is_empty()
+            && game_id != "umu-default"
+            && game_id
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+        if !valid {
+            return None;
+        }
+        let path = Self::dir()?.join(format!("{}.py", game_id));
+        Some(LocalFix {
+            game_id: game_id.to_string(),
+            path,
+        })
+    }
+
+    pub fn dir() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(LOCAL_FIXES_DIR))
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    /// The saved module, or `None` when there is none yet.
+    pub fn load(&self) -> Option<String> {
+        fs::read_to_string(&self.path).ok()
+    }
+
+    /// Write the module; an empty one removes the file.
+    pub fn save(&self, source: &str) -> Result<()> {
+        if source.trim().is_empty() {
+            return self.remove();
+        }
+        let dir = self.path.parent().context("Local fix path has no parent")?;
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        // Lets older Pythons import the folder as a package.
+        let init = dir.join("__init__.py");
+        if !init.exists() {
+            fs::write(&init, "").with_context(|| format!("Failed to write {:?}", init))?;
+        }
+        let mut content = source.to_string();
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        fs::write(&self.path, content).with_context(|| format!("Failed to write {:?}", self.path))
+    }
+
+    pub fn remove(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)
+                .with_context(|| format!("Failed to remove {:?}", self.path))?;
+        }
+        Ok(())
+    }
+
+    /// A starting point showing the common `util` helpers.
+    pub fn template(&self, game_name: &str) -> String {
+        format!(
+            r#""""Local protonfixes for {name} ({game_id})."""
+
+from protonfixes import util
+
+
+def main() -> None:
+    """Runs before the game starts. Common helpers:
+
+    util.protontricks('d3dcompiler_47')
+    util.set_environment('DXVK_ASYNC', '1')
+    util.append_argument('-nointro')
+    """
+    pass
+"#,
+            name = game_name,
+            game_id = self.game_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    Keyword,
+    String,
+    Comment,
+}
+
+/// A highlighted stretch of Python source, in characters (not bytes), as
+/// `TextBuffer` offsets count them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: SyntaxKind,
+}
+
+/// Keywords, strings (including triple-quoted ones) and comments in Python
+/// source; enough to read a fix module, not a full tokenizer.
+pub fn python_syntax(source: &str) -> Vec<SyntaxSpan> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut spans = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        if ch == '#' {
+            let end = chars[index..]
+                .iter()
+                .position(|&ch| ch == '\n')
+                .map(|offset| index + offset)
+                .unwrap_or(chars.len());
+            spans.push(SyntaxSpan {
+                start: index,
+                end,
+                kind: SyntaxKind::Comment,
+            });
+            index = end;
+        } else if ch == '\'' || ch == '"' {
+            let triple = chars.get(index + 1) == Some(&ch) && chars.get(index + 2) == Some(&ch);
+            let end = string_end(&chars, index, triple);
+            spans.push(SyntaxSpan {
+                start: index,
+                end,
+                kind: SyntaxKind::String,
+            });
+            index = end;
+        } else if ch.is_alphabetic() || ch == '_' {
+            let start = index;
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            let word: String = chars[start..index].iter().collect();
+            if PYTHON_KEYWORDS.contains(&word.as_str()) {
+                spans.push(SyntaxSpan {
+                    start,
+                    end: index,
+                    kind: SyntaxKind::Keyword,
+                });
+            }
+        } else {
+            index += 1;
+        }
+    }
+    spans
+}
+
+/// The offset just past the string opening at `start`. Unterminated
+/// strings run to the end of the line, or of the source when triple-quoted.
+fn string_end(chars: &[char], start: usize, triple: bool) -> usize {
+    let quote = chars[start];
+    let mut index = start + if triple { 3 } else { 1 };
+    while index < chars.len() {
+        match chars[index] {
+            '\\' => index += 2,
+            '\n' if !triple => return index,
+            ch if ch == quote && !triple => return index + 1,
+            ch if ch == quote
+                && chars.get(index + 1) == Some(&quote)
+                && chars.get(index + 2) == Some(&quote) =>
+            {
+                return index + 3
+            }
+            _ => index += 1,
+        }
+    }
+    chars.len()
+}
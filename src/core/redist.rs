@@ -0,0 +1,694 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+use crate::core::network;
+use crate::core::registry::PrefixRegistry;
+use crate::core::runtime_manager::{GitHubAsset, RuntimeManager, TransferRate};
+use crate::core::system_checker::SystemCheck;
+
+const VCREDIST_LATEST_RELEASE: &str =
+    "https://api.github.com/repos/abbodi1406/vcredist/releases/latest";
+/// Release assets in order of preference.
+const VCREDIST_ASSETS: [&str; 2] = [
+    "VisualCppRedist_AIO_x86_x64.exe",
+    "VisualCppRedist_AIO_x86only.exe",
+];
+/// Microsoft's own installers for single VC++ runtimes. The 2013 and
+/// 2015-2022 links always point at the latest build.
+const VCREDIST_2010_X86_URL: &str = "https://download.microsoft.com/download/1/6/5/165255E7-1014-4D0A-B094-B6A430A6BFFC/vcredist_x86.exe";
+const VCREDIST_2010_X64_URL: &str = "https://download.microsoft.com/download/1/6/5/165255E7-1014-4D0A-B094-B6A430A6BFFC/vcredist_x64.exe";
+const VCREDIST_2013_X86_URL: &str = "https://aka.ms/highdpimfc2013x86enu";
+const VCREDIST_2013_X64_URL: &str = "https://aka.ms/highdpimfc2013x64enu";
+const VCREDIST_2015_X86_URL: &str = "https://aka.ms/vs/17/release/vc_redist.x86.exe";
+const VCREDIST_2015_X64_URL: &str = "https://aka.ms/vs/17/release/vc_redist.x64.exe";
+/// The 2010 installers predate the `/install` switch.
+const VCREDIST_2010_ARGS: &[&str] = &["/q", "/norestart"];
+const VCREDIST_ARGS: &[&str] = &["/install", "/quiet", "/norestart"];
+const DIRECTX_URL: &str = "https://download.microsoft.com/download/8/4/a/84a35bf1-dafe-4ae8-82af-ad2ae20b6b14/directx_Jun2010_redist.exe";
+/// The same checksum winetricks pins for this file.
+const DIRECTX_SHA256: &str = "8746ee1a84a083a90e37899d71d50d5c7c015e69688a466aa80447f011780c0d";
+/// Per-channel release metadata Microsoft publishes for .NET, with file
+/// URLs and SHA-512 hashes.
+const DOTNET_RELEASES_URL: &str =
+    "https://dotnetcli.blob.core.windows.net/dotnet/release-metadata/{channel}/releases.json";
+const DOTNET_DESKTOP_FILE: &str = "windowsdesktop-runtime-win-x64.exe";
+
+const DOTNET_DESKTOP_ARGS: &[&str] = &["/install", "/quiet", "/norestart"];
+const XNA40_URL: &str = "https://download.microsoft.com/download/A/C/2/AC2C903B-E6E8-42C2-9FD7-BEBAC362A930/xnafx40_redist.msi";
+
+#[derive(Debug, Deserialize)]
+struct GitHubLatestRelease {
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DotNetChannelReleases {
+    #[serde(rename = "latest-release")]
+    latest_release: String,
+    releases: Vec<DotNetRelease>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DotNetRelease {
+    #[serde(rename = "release-version")]
+    release_version: String,
+    windowsdesktop: Option<DotNetComponent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DotNetComponent {
+    #[serde(default)]
+    files: Vec<DotNetFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DotNetFile {
+    name: String,
+    url: String,
+    #[serde(default)]
+    hash: String,
+}
+
+#[derive(Debug, Clone)]
+enum Checksum {
+    Sha256(String),
+    Sha512(String),
+}
+
+impl Checksum {
+    fn matches(&self, path: &Path) -> Result<bool> {
+        let (expected, actual) = match self {
+            Checksum::Sha256(expected) => (expected, Self::digest::<Sha256>(path)?),
+            Checksum::Sha512(expected) => (expected, Self::digest::<Sha512>(path)?),
+        };
+        Ok(actual.eq_ignore_ascii_case(expected))
+    }
+
+    fn digest<D: Digest>(path: &Path) -> Result<String> {
+        let mut file = File::open(path)?;
+        let mut hasher = D::new();
+        let mut buffer = [0u8; 8192];
+        loop {
+            let bytes_read = file.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+/// Where to fetch an installer from and what it should hash to.
+struct RedistSource {
+    url: String,
+    size: Option<u64>,
+    checksum: Option<Checksum>,
+}
+
+/// Where a catalog entry's installer comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// A fixed URL, with its SHA-256 when the file never changes.
+    Url {
+        url: &'static str,
+        sha256: Option<&'static str>,
+    },
+    /// The first matching asset of a GitHub project's latest release.
+    GitHubRelease {
+        api: &'static str,
+        assets: &'static [&'static str],
+    },
+    /// The latest patch release of a .NET channel's x64 desktop runtime.
+    DotNetDesktop { channel: &'static str },
+    /// winetricks downloads and verifies the installer itself.
+    Winetricks,
+}
+
+/// How a redistributable is installed into a prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallMethod {
+    /// Run the cached installer with these silent arguments.
+    Installer(&'static [&'static str]),
+    /// `msiexec /i` the cached package with these arguments.
+    Msi(&'static [&'static str]),
+    /// Extract the cached DirectX package, then run DXSETUP.exe.
+    DirectX,
+    /// A winetricks verb.
+    Winetricks(&'static str),
+}
+
+/// Groups for the per-game checklist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Runtime,
+    VisualCpp,
+    DotNet,
+    Legacy,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [
+        Category::Runtime,
+        Category::VisualCpp,
+        Category::DotNet,
+        Category::Legacy,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Category::Runtime => "Runtimes",
+            Category::VisualCpp => "Visual C++ (single versions)",
+            Category::DotNet => ".NET",
+            Category::Legacy => "Legacy components",
+        }
+    }
+}
+
+/// A Windows component that can be installed into a game's prefix.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Redistributable {
+    id: &'static str,
+    label: &'static str,
+    pub description: &'static str,
+    pub category: Category,
+    source: Source,
+    install: InstallMethod,
+    /// File name in the dependency cache; empty for winetricks verbs.
+    cache_file: &'static str,
+    /// Paths under drive_c that mean the component is already in the
+    /// prefix. A trailing `*` matches by prefix; names match case-insensitively
+    /// and Wine's own builtin stand-ins don't count.
+    detect: &'static [&'static str],
+    /// Add/Remove Programs display names (matched by prefix,
+    /// case-insensitively) that the installer registers.
+    registered_as: &'static [&'static str],
+}
+
+pub const VCREDIST_ID: &str = "vcredist";
+pub const DIRECTX_ID: &str = "dxweb";
+
+/// Every component LinuxBoy knows how to install, in install order.
+pub static CATALOG: &[Redistributable] = &[
+    Redistributable {
+        id: VCREDIST_ID,
+        label: "VC++ AIO",
+        description: "Visual C++ 2005-2022 runtimes (abbodi1406's all-in-one package).",
+        category: Category::Runtime,
+        source: Source::GitHubRelease {
+            api: VCREDIST_LATEST_RELEASE,
+            assets: &VCREDIST_ASSETS,
+        },
+        // The AIO package picks its own silent mode.
+        install: InstallMethod::Installer(&[]),
+        cache_file: "vcredist_aio.exe",
+        detect: &[
+            "windows/system32/msvcp140.dll",
+            "windows/syswow64/msvcp140.dll",
+        ],
+        registered_as: &["Microsoft Visual C++ 2015-", "Microsoft Visual C++ 2022"],
+    },
+    // Single runtimes, for when the AIO package fails or installs more than
+    // a game wants. On a 64-bit prefix 32-bit DLLs live in syswow64.
+    Redistributable {
+        id: "vcredist2010_x86",
+        label: "VC++ 2010 (x86)",
+        description: "Visual C++ 2010 SP1 runtime for 32-bit programs (msvcr100, msvcp100).",
+        category: Category::VisualCpp,
+        source: Source::Url {
+            url: VCREDIST_2010_X86_URL,
+            sha256: None,
+        },
+        install: InstallMethod::Installer(VCREDIST_2010_ARGS),
+        cache_file: "vcredist_2010_x86.exe",
+        detect: &["windows/syswow64/msvcp100.dll"],
+        registered_as: &[],
+    },
+    Redistributable {
+        id: "vcredist2010_x64",
+        label: "VC++ 2010 (x64)",
+        description: "Visual C++ 2010 SP1 runtime for 64-bit programs (msvcr100, msvcp100).",
+        category: Category::VisualCpp,
+        source: Source::Url {
+            url: VCREDIST_2010_X64_URL,
+            sha256: None,
+        },
+        install: InstallMethod::Installer(VCREDIST_2010_ARGS),
+        cache_file: "vcredist_2010_x64.exe",
+        detect: &["windows/system32/msvcp100.dll"],
+        registered_as: &[],
+    },
+    Redistributable {
+        id: "vcredist2013_x86",
+        label: "VC++ 2013 (x86)",
+        description: "Visual C++ 2013 runtime for 32-bit programs (msvcr120, msvcp120).",
+        category: Category::VisualCpp,
+        source: Source::Url {
+            url: VCREDIST_2013_X86_URL,
+            sha256: None,
+        },
+        install: InstallMethod::Installer(VCREDIST_ARGS),
+        cache_file: "vcredist_2013_x86.exe",
+        detect: &["windows/syswow64/msvcp120.dll"],
+        registered_as: &["Microsoft Visual C++ 2013 Redistributable (x86)"],
+    },
+    Redistributable {
+        id: "vcredist2013_x64",
+        label: "VC++ 2013 (x64)",
+        description: "Visual C++ 2013 runtime for 64-bit programs (msvcr120, msvcp120).",
+        category: Category::VisualCpp,
+        source: Source::Url {
+            url: VCREDIST_2013_X64_URL,
+            sha256: None,
+        },
+        install: InstallMethod::Installer(VCREDIST_ARGS),
+        cache_file: "vcredist_2013_x64.exe",
+        detect: &["windows/system32/msvcp120.dll"],
+        registered_as: &["Microsoft Visual C++ 2013 Redistributable (x64)"],
+    },
+    Redistributable {
+        id: "vcredist2015_x86",
+        label: "VC++ 2015-2022 (x86)",
+        description: "Latest Visual C++ 14 runtime for 32-bit programs (vcruntime140, msvcp140).",
+        category: Category::VisualCpp,
+        source: Source::Url {
+            url: VCREDIST_2015_X86_URL,
+            sha256: None,
+        },
+        install: InstallMethod::Installer(VCREDIST_ARGS),
+        cache_file: "vcredist_2015-2022_x86.exe",
+        detect: &["windows/syswow64/msvcp140.dll"],
+        registered_as: &["Microsoft Visual C++ 2015-2022 Redistributable (x86)"],
+    },
+    Redistributable {
+        id: "vcredist2015_x64",
+        label: "VC++ 2015-2022 (x64)",
+        description: "Latest Visual C++ 14 runtime for 64-bit programs (vcruntime140, msvcp140).",
+        category: Category::VisualCpp,
+        source: Source::Url {
+            url: VCREDIST_2015_X64_URL,
+            sha256: None,
+        },
+        install: InstallMethod::Installer(VCREDIST_ARGS),
+        cache_file: "vcredist_2015-2022_x64.exe",
+        detect: &["windows/system32/msvcp140.dll"],
+        registered_as: &["Microsoft Visual C++ 2015-2022 Redistributable (x64)"],
+    },
+    Redistributable {
+        id: DIRECTX_ID,
+        label: "DirectX redist",
+        description: "DirectX June 2010 end-user runtime (d3dx9, d3dx10, XAudio2, XInput).",
+        category: Category::Runtime,
+        source: Source::Url {
+            url: DIRECTX_URL,
+            sha256: Some(DIRECTX_SHA256),
+        },
+        install: InstallMethod::DirectX,
+        cache_file: "directx_Jun2010_redist.exe",
+        detect: &[
+            "windows/system32/d3dx9_43.dll",
+            "windows/syswow64/d3dx9_43.dll",
+        ],
+        registered_as: &[],
+    },
+    // .NET Framework 4.8 has to replace Wine Mono, which winetricks knows
+    // how to do.
+    Redistributable {
+        id: "dotnet48",
+        label: ".NET Framework 4.8",
+        description: "For XNA, WinForms and older Unity launchers. Replaces Wine Mono.",
+        category: Category::DotNet,
+        source: Source::Winetricks,
+        install: InstallMethod::Winetricks("dotnet48"),
+        cache_file: "",
+        detect: &["windows/Microsoft.NET/Framework/v4.0.30319/clr.dll"],
+        registered_as: &["Microsoft .NET Framework 4.8"],
+    },
+    Redistributable {
+        id: "dotnetdesktop6",
+        label: ".NET Desktop Runtime 6.0",
+        description: "64-bit WinForms and WPF runtime.",
+        category: Category::DotNet,
+        source: Source::DotNetDesktop { channel: "6.0" },
+        install: InstallMethod::Installer(DOTNET_DESKTOP_ARGS),
+        cache_file: "windowsdesktop-runtime-6.0-win-x64.exe",
+        detect: &["Program Files/dotnet/shared/Microsoft.WindowsDesktop.App/6.0.*"],
+        registered_as: &["Microsoft Windows Desktop Runtime - 6.0"],
+    },
+    Redistributable {
+        id: "dotnetdesktop7",
+        label: ".NET Desktop Runtime 7.0",
+        description: "64-bit WinForms and WPF runtime.",
+        category: Category::DotNet,
+        source: Source::DotNetDesktop { channel: "7.0" },
+        install: InstallMethod::Installer(DOTNET_DESKTOP_ARGS),
+        cache_file: "windowsdesktop-runtime-7.0-win-x64.exe",
+        detect: &["Program Files/dotnet/shared/Microsoft.WindowsDesktop.App/7.0.*"],
+        registered_as: &["Microsoft Windows Desktop Runtime - 7.0"],
+    },
+    Redistributable {
+        id: "dotnetdesktop8",
+        label: ".NET Desktop Runtime 8.0",
+        description: "64-bit WinForms and WPF runtime.",
+        category: Category::DotNet,
+        source: Source::DotNetDesktop { channel: "8.0" },
+        install: InstallMethod::Installer(DOTNET_DESKTOP_ARGS),
+        cache_file: "windowsdesktop-runtime-8.0-win-x64.exe",
+        detect: &["Program Files/dotnet/shared/Microsoft.WindowsDesktop.App/8.0.*"],
+        registered_as: &["Microsoft Windows Desktop Runtime - 8.0"],
+    },
+    Redistributable {
+        id: "dotnetdesktop9",
+        label: ".NET Desktop Runtime 9.0",
+        description: "64-bit WinForms and WPF runtime.",
+        category: Category::DotNet,
+        source: Source::DotNetDesktop { channel: "9.0" },
+        install: InstallMethod::Installer(DOTNET_DESKTOP_ARGS),
+        cache_file: "windowsdesktop-runtime-9.0-win-x64.exe",
+        detect: &["Program Files/dotnet/shared/Microsoft.WindowsDesktop.App/9.0.*"],
+        registered_as: &["Microsoft Windows Desktop Runtime - 9.0"],
+    },
+    Redistributable {
+        id: "xna40",
+        label: "XNA Framework 4.0",
+        description: "Needed by games built on Microsoft XNA 4.0.",
+        category: Category::Legacy,
+        source: Source::Url {
+            url: XNA40_URL,
+            sha256: None,
+        },
+        install: InstallMethod::Msi(&["/qn", "/norestart"]),
+        cache_file: "xnafx40_redist.msi",
+        detect: &["windows/Microsoft.NET/assembly/GAC_32/Microsoft.Xna.Framework"],
+        registered_as: &["Microsoft XNA Framework Redistributable 4.0"],
+    },
+    Redistributable {
+        id: "physx",
+        label: "PhysX (legacy)",
+        description: "NVIDIA PhysX 9 system software for games that ship without it.",
+        category: Category::Legacy,
+        source: Source::Winetricks,
+        install: InstallMethod::Winetricks("physx"),
+        cache_file: "",
+        detect: &[
+            "windows/syswow64/PhysXLoader.dll",
+            "windows/system32/PhysXLoader.dll",
+        ],
+        registered_as: &["NVIDIA PhysX"],
+    },
+    Redistributable {
+        id: "openal",
+        label: "OpenAL",
+        description: "Creative's OpenAL runtime, for games that expect the native one.",
+        category: Category::Legacy,
+        source: Source::Winetricks,
+        install: InstallMethod::Winetricks("openal"),
+        cache_file: "",
+        detect: &[
+            "windows/syswow64/OpenAL32.dll",
+            "windows/system32/OpenAL32.dll",
+        ],
+        registered_as: &["OpenAL"],
+    },
+    Redistributable {
+        id: "directplay",
+        label: "DirectPlay",
+        description: "Native DirectPlay for the multiplayer modes of early-2000s games.",
+        category: Category::Legacy,
+        source: Source::Winetricks,
+        install: InstallMethod::Winetricks("directplay"),
+        cache_file: "",
+        detect: &["windows/syswow64/dpnet.dll", "windows/system32/dpnet.dll"],
+        registered_as: &[],
+    },
+];
+
+impl Redistributable {
+    pub fn get(id: &str) -> Option<&'static Redistributable> {
+        CATALOG.iter().find(|redist| redist.id == id)
+    }
+
+    /// VC++ and DirectX, which every game is offered.
+    pub fn defaults() -> Vec<&'static Redistributable> {
+        [VCREDIST_ID, DIRECTX_ID]
+            .iter()
+            .filter_map(|id| Self::get(id))
+            .collect()
+    }
+
+    /// Everything else, which a game opts into from its settings.
+    pub fn optional() -> Vec<&'static Redistributable> {
+        CATALOG
+            .iter()
+            .filter(|redist| !redist.is_default())
+            .collect()
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.id == VCREDIST_ID || self.id == DIRECTX_ID
+    }
+
+    /// The name recorded in `redistributables_installed`.
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn install_method(&self) -> InstallMethod {
+        self.install
+    }
+
+    /// The cached installer, or `None` when winetricks manages the download.
+    pub fn cache_path(&self) -> Option<PathBuf> {
+        if self.source == Source::Winetricks {
+            return None;
+        }
+        Some(SystemCheck::get_deps_dir().join(self.cache_file))
+    }
+
+    /// Whether the installer is ready to run without a download first.
+    pub fn is_cached(&self) -> bool {
+        self.cache_path().map(|path| path.is_file()).unwrap_or(true)
+    }
+
+    /// The catalog entries that look installed in the prefix already, e.g.
+    /// because the game's own installer or an imported prefix had them.
+    pub fn present_in(prefix_path: &Path) -> Vec<&'static Redistributable> {
+        let programs = PrefixRegistry::new(prefix_path).installed_programs();
+        CATALOG
+            .iter()
+            .filter(|redist| redist.detected(prefix_path, &programs))
+            .collect()
+    }
+
+    fn detected(&self, prefix_path: &Path, programs: &[String]) -> bool {
+        let registered = programs.iter().any(|program| {
+            let program = program.to_ascii_lowercase();
+            self.registered_as
+                .iter()
+                .any(|name| program.starts_with(&name.to_ascii_lowercase()))
+        });
+        let drive_c = prefix_path.join("drive_c");
+        registered
+            || self
+                .detect
+                .iter()
+                .any(|pattern| Self::find_match(&drive_c, pattern).is_some())
+    }
+
+    fn find_match(drive_c: &Path, pattern: &str) -> Option<PathBuf> {
+        let (parent, name) = match pattern.rsplit_once('/') {
+            Some((parent, name)) => (drive_c.join(parent), name),
+            None => (drive_c.to_path_buf(), pattern),
+        };
+        let (name, by_prefix) = match name.strip_suffix('*') {
+            Some(name) => (name.to_ascii_lowercase(), true),
+            None => (name.to_ascii_lowercase(), false),
+        };
+        fs::read_dir(parent)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| {
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_ascii_lowercase())
+                    .unwrap_or_default();
+                let matches = if by_prefix {
+                    file_name.starts_with(&name)
+                } else {
+                    file_name == name
+                };
+                matches && !Self::is_wine_builtin(path)
+            })
+    }
+
+    /// Wine fills system32 with its own DLLs, marked in the DOS stub.
+    fn is_wine_builtin(path: &Path) -> bool {
+        if !path.is_file() {
+            return false;
+        }
+        let mut header = [0u8; 128];
+        let read = File::open(path)
+            .and_then(|mut file| file.read(&mut header))
+            .unwrap_or(0);
+        let header = &header[..read];
+        [&b"Wine builtin DLL"[..], &b"Wine placeholder DLL"[..]]
+            .iter()
+            .any(|marker| header.windows(marker.len()).any(|window| window == *marker))
+    }
+
+    fn source(&self) -> Result<RedistSource> {
+        match self.source {
+            Source::Url { url, sha256 } => Ok(RedistSource {
+                url: url.to_string(),
+                size: None,
+                checksum: sha256.map(|hash| Checksum::Sha256(hash.to_string())),
+            }),
+            Source::GitHubRelease { api, assets } => {
+                let client = network::client_builder()?.build()?;
+                let release: GitHubLatestRelease = client
+                    .get(api)
+                    .send()
+                    .with_context(|| format!("Failed to fetch the latest {} release", self.label))?
+                    .error_for_status()?
+                    .json()
+                    .with_context(|| format!("Failed to parse {} release JSON", self.label))?;
+                let asset = assets
+                    .iter()
+                    .find_map(|name| release.assets.iter().find(|asset| asset.name == *name))
+                    .with_context(|| {
+                        format!("The latest {} release has no installer asset", self.label)
+                    })?;
+                Ok(RedistSource {
+                    url: asset.browser_download_url.clone(),
+                    size: Some(asset.size).filter(|size| *size > 0),
+                    checksum: asset
+                        .sha256()
+                        .map(|hash| Checksum::Sha256(hash.to_string())),
+                })
+            }
+            Source::DotNetDesktop { channel } => Self::dotnet_desktop_source(channel),
+            Source::Winetricks => anyhow::bail!("{} is downloaded by winetricks", self.label),
+        }
+    }
+
+    /// The latest patch release of the channel's x64 desktop runtime.
+    fn dotnet_desktop_source(channel: &str) -> Result<RedistSource> {
+        let client = network::client_builder()?.build()?;
+        let releases: DotNetChannelReleases = client
+            .get(DOTNET_RELEASES_URL.replace("{channel}", channel))
+            .send()
+            .with_context(|| format!("Failed to fetch .NET {} releases", channel))?
+            .error_for_status()?
+            .json()
+            .with_context(|| format!("Failed to parse .NET {} release metadata", channel))?;
+        let file = releases
+            .releases
+            .iter()
+            .find(|release| release.release_version == releases.latest_release)
+            .and_then(|release| release.windowsdesktop.as_ref())
+            .and_then(|component| {
+                component
+                    .files
+                    .iter()
+                    .find(|file| file.name == DOTNET_DESKTOP_FILE)
+            })
+            .with_context(|| {
+                format!(
+                    ".NET {} has no {}",
+                    releases.latest_release, DOTNET_DESKTOP_FILE
+                )
+            })?;
+        Ok(RedistSource {
+            url: file.url.clone(),
+            size: None,
+            checksum: Some(file.hash.clone())
+                .filter(|hash| !hash.is_empty())
+                .map(Checksum::Sha512),
+        })
+    }
+
+    /// Download the installer into the dependency cache, resuming a partial
+    /// download, and check it against the published checksum. A cached file
+    /// is kept unless `reinstall` is set.
+    pub fn download<F>(
+        &self,
+        reinstall: bool,
+        cancel: &AtomicBool,
+        mut progress_callback: F,
+    ) -> Result<PathBuf>
+    where
+        F: FnMut(String, f64),  // (status_text, progress_fraction)
+    {
+        let label = self.label();
+        let dest = self
+            .cache_path()
+            .with_context(|| format!("{} is not downloaded by LinuxBoy", label))?;
+        if reinstall {
+            let _ = fs::remove_file(&dest);
+        } else if dest.is_file() {
+            progress_callback(format!("{} already cached", label), 1.0);
+            return Ok(dest);
+        }
+
+        progress_callback(format!("Looking up {}...", label), 0.0);
+        let source = self.source()?;
+        let runtime_mgr = RuntimeManager::new();
+        let mut rate = TransferRate::new();
+        runtime_mgr.download_file(
+            &source.url,
+            &dest,
+            source.size,
+            cancel,
+            |downloaded, total| {
+                rate.update(downloaded);
+                let downloaded_mb = downloaded / 1_048_576;
+                if total == 0 {
+                    progress_callback(format!("Downloading {} ({} MB)", label, downloaded_mb), 0.0);
+                    return;
+                }
+                let speed = rate.describe(downloaded, total);
+                let status = if speed.is_empty() {
+                    format!(
+                        "Downloading {} ({} / {} MB)",
+                        label,
+                        downloaded_mb,
+                        total / 1_048_576
+                    )
+                } else {
+                    format!(
+                        "Downloading {} ({} / {} MB, {})",
+                        label,
+                        downloaded_mb,
+                        total / 1_048_576,
+                        speed
+                    )
+                };
+                progress_callback(status, downloaded as f64 / total as f64);
+            },
+        )?;
+
+        if let Some(checksum) = &source.checksum {
+            progress_callback(format!("Verifying {}...", label), 1.0);
+            if !checksum.matches(&dest)? {
+                let _ = fs::remove_file(&dest);
+                anyhow::bail!(
+                    "{} failed checksum verification; the download was removed",
+                    label
+                );
+            }
+        }
+
+        progress_callback(format!("{} downloaded", label), 1.0);
+        Ok(dest)
+    }
+}
@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DLL_OVERRIDES_KEY: &str = r"Software\Wine\DllOverrides";
+/// Where installers register themselves for Add/Remove Programs, relative to
+/// HKLM, for 64-bit and 32-bit programs.
+const UNINSTALL_KEYS: [&str; 2] = [
+    r"Software\Microsoft\Windows\CurrentVersion\Uninstall\",
+    r"Software\Wow6432Node\Microsoft\Windows\CurrentVersion\Uninstall\",
+];
+
+/// The curated registry settings editable from game settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistryTweaks {
+    /// `(dll, mode)` pairs from `HKCU\Software\Wine\DllOverrides`.
+    pub dll_overrides: Vec<(String, String)>,
+}
+
+/// A program registered for Add/Remove Programs in the prefix.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UninstallEntry {
+    pub display_name: Option<String>,
+    /// Folder the program was installed to, as a Windows path.
+    pub install_location: Option<String>,
+    /// Windows path of the file holding the program's icon, usually its
+    /// main executable.
+    pub display_icon: Option<String>,
+    /// Command line that removes the program.
+    pub uninstall_string: Option<String>,
+}
+
+/// Reads Wine's text registry files in a prefix and writes `.reg` patches
+/// for `regedit` to import.
+pub struct PrefixRegistry {
+    prefix_path: PathBuf,
+}
+
+impl PrefixRegistry {
+    pub fn new(prefix_path: &Path) -> Self {
+        Self {
+            prefix_path: prefix_path.to_path_buf(),
+        }
+    }
+
+    /// Current values of the curated keys, from `user.reg`.
+    pub fn read_tweaks(&self) -> RegistryTweaks {
+        RegistryTweaks {
+            dll_overrides: self.read_user_key(DLL_OVERRIDES_KEY),
+        }
+    }
+
+    /// A `.reg` file that turns `current` into `desired`, deleting values that
+    /// were removed.
+    pub fn tweaks_patch(current: &RegistryTweaks, desired: &RegistryTweaks) -> String {
+        let mut reg = String::from("REGEDIT4\n");
+        reg.push_str(&format!("\n[HKEY_CURRENT_USER\\{}]\n", DLL_OVERRIDES_KEY));
+        for (dll, _) in &current.dll_overrides {
+            if !desired
+                .dll_overrides
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case(dll))
+            {
+                reg.push_str(&format!("\"{}\"=-\n", Self::escape(dll)));
+            }
+        }
+        for (dll, mode) in &desired.dll_overrides {
+            reg.push_str(&format!(
+                "\"{}\"=\"{}\"\n",
+                Self::escape(dll),
+                Self::escape(mode)
+            ));
+        }
+        reg
+    }
+
+    /// Display names of the programs registered in Add/Remove Programs,
+    /// from `system.reg`.
+    pub fn installed_programs(&self) -> Vec<String> {
+        self.uninstall_entries()
+            .into_iter()
+            .filter_map(|entry| entry.display_name)
+            .collect()
+    }
+
+    /// Every program under the HKLM Uninstall keys of `system.reg`.
+    pub fn uninstall_entries(&self) -> Vec<UninstallEntry> {
+        self.read_sections("system.reg", |section| {
+            let section = section.to_ascii_lowercase();
+            UNINSTALL_KEYS
+                .iter()
+                .any(|key| section.starts_with(&key.to_ascii_lowercase()))
+        })
+        .into_iter()
+        .map(|(_, values)| {
+            let mut entry = UninstallEntry::default();
+            for (name, value) in values {
+                let value = Some(value).filter(|value| !value.trim().is_empty());
+                if name.eq_ignore_ascii_case("DisplayName") {
+                    entry.display_name = value;
+                } else if name.eq_ignore_ascii_case("InstallLocation") {
+                    entry.install_location = value;
+                } else if name.eq_ignore_ascii_case("DisplayIcon") {
+                    entry.display_icon = value.map(|icon| Self::strip_icon_index(&icon));
+                } else if name.eq_ignore_ascii_case("UninstallString") {
+                    entry.uninstall_string = value;
+                }
+            }
+            entry
+        })
+        .collect()
+    }
+
+    /// Where a `C:\...` path of the prefix is on the host.
+    pub fn host_path(&self, windows_path: &str) -> Option<PathBuf> {
+        let trimmed = windows_path.trim_matches('"').trim();
+        if trimmed.len() < 3 {
+            return None;
+        }
+        let normalized = trimmed.replace('/', "\\");
+        let lowered = normalized.to_ascii_lowercase();
+        if !lowered.starts_with("c:\\") {
+            return None;
+        }
+        let relative = &normalized[3..];
+        let sep = std::path::MAIN_SEPARATOR.to_string();
+        let host_rel = relative.replace('\\', &sep);
+        Some(self.prefix_path.join("drive_c").join(host_rel))
+    }
+
+    /// `"C:\Game\game.exe",0` to `C:\Game\game.exe`.
+    fn strip_icon_index(icon: &str) -> String {
+        let icon = icon.trim();
+        let path = match icon.rsplit_once(',') {
+            Some((path, index)) if index.trim().parse::<i32>().is_ok() => path,
+            _ => icon,
+        };
+        path.trim().trim_matches('"').to_string()
+    }
+
+    /// String values stored under `key` (relative to HKCU) in `user.reg`.
+    fn read_user_key(&self, key: &str) -> Vec<(String, String)> {
+        self.read_values("user.reg", |section| section.eq_ignore_ascii_case(key))
+    }
+
+    /// String values from the sections of a registry file that `in_section`
+    /// accepts.
+    fn read_values<F>(&self, file: &str, in_section: F) -> Vec<(String, String)>
+    where
+        F: Fn(&str) -> bool,
+    {
+        self.read_sections(file, in_section)
+            .into_iter()
+            .flat_map(|(_, values)| values)
+            .collect()
+    }
+
+    /// The sections of a registry file that `in_section` accepts, each with
+    /// its string values.
+    fn read_sections<F>(&self, file: &str, in_section: F) -> Vec<(String, Vec<(String, String)>)>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let content = match fs::read_to_string(self.prefix_path.join(file)) {
+            Ok(content) => content,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        let mut in_key = false;
+        for line in content.lines() {
+            if let Some(rest) = line.strip_prefix('[') {
+                let section = rest.split(']').next().unwrap_or_default().replace("\\\\", "\\");
+                in_key = in_section(&section);
+                if in_key {
+                    sections.push((section, Vec::new()));
+                }
+                continue;
+            }
+            if !in_key {
+                continue;
+            }
+            if let (Some((name, value)), Some((_, values))) =
+                (Self::parse_string_value(line), sections.last_mut())
+            {
+                values.push((name, value));
+            }
+        }
+        sections
+    }
+
+    /// Parse a `"name"="value"` line; other value types are skipped.
+    fn parse_string_value(line: &str) -> Option<(String, String)> {
+        let (name, rest) = Self::parse_quoted(line)?;
+        let (value, _) = Self::parse_quoted(rest.strip_prefix('=')?)?;
+        Some((name, value))
+    }
+
+    fn parse_quoted(input: &str) -> Option<(String, &str)> {
+        let body = input.strip_prefix('"')?;
+        let mut value = String::new();
+        let mut chars = body.char_indices();
+        while let Some((index, ch)) = chars.next() {
+            match ch {
+                '\\' => value.push(chars.next()?.1),
+                '"' => return Some((value, &body[index + 1..])),
+                _ => value.push(ch),
+            }
+        }
+        None
+    }
+
+    fn escape(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::capsule::Capsule;
+use crate::core::saves::SaveManager;
+use crate::core::system_checker::SystemCheck;
+use crate::utils::file_ops;
+
+/// What to do when a game is deleted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemovalOptions {
+    /// Remove only the Wine prefix; the capsule and the game files stay and
+    /// a fresh prefix is created on the next launch.
+    pub prefix_only: bool,
+    /// Archive the game's save folders before anything is removed.
+    pub keep_saves: bool,
+}
+
+pub struct CapsuleRemoval;
+
+impl CapsuleRemoval {
+    /// Where saves of deleted games are kept.
+    pub fn kept_saves_dir() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("kept-saves")
+    }
+
+    /// Delete a capsule, or only its prefix, as `options` say. `remove_dir`
+    /// does the removal itself, so the caller decides between deleting and
+    /// moving to the trash. Returns a summary for the log.
+    pub fn run(
+        capsule_dir: &Path,
+        options: RemovalOptions,
+        remove_dir: &dyn Fn(&Path) -> Result<()>,
+    ) -> Result<String> {
+        let mut notes = Vec::new();
+        let capsule = if options.keep_saves || options.prefix_only {
+            Some(Capsule::load_from_dir(capsule_dir)?)
+        } else {
+            None
+        };
+
+        if let (true, Some(capsule)) = (options.keep_saves, &capsule) {
+            match Self::keep_saves(capsule)? {
+                Some(archive) => notes.push(format!("saves kept in {:?}", archive)),
+                None => notes.push("no saves found".to_string()),
+            }
+        }
+
+        match capsule {
+            Some(mut capsule) if options.prefix_only => {
+                Self::remove_prefix(&capsule, remove_dir)?;
+                // Whatever was installed into the old prefix is gone.
+                capsule.metadata.redistributables_installed.clear();
+                capsule.metadata.redistributables_failed.clear();
+                capsule.save_metadata()?;
+                notes.insert(0, format!("Removed the prefix of {}", capsule.name));
+            }
+            _ => {
+                // Moved capsules are linked into the library; remove the real directory too.
+                if file_ops::is_symlink(capsule_dir) {
+                    let real_dir = fs::canonicalize(capsule_dir)
+                        .with_context(|| format!("Failed to resolve {:?}", capsule_dir))?;
+                    remove_dir(&real_dir)?;
+                    fs::remove_file(capsule_dir).context("Failed to remove library link")?;
+                } else {
+                    remove_dir(capsule_dir)?;
+                }
+                notes.insert(0, format!("Deleted capsule {:?}", capsule_dir));
+            }
+        }
+        Ok(notes.join("; "))
+    }
+
+    fn keep_saves(capsule: &Capsule) -> Result<Option<PathBuf>> {
+        let locations = SaveManager::detect(capsule);
+        if locations.is_empty() {
+            return Ok(None);
+        }
+        let dir = Self::kept_saves_dir();
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        let archive = dir.join(format!(
+            "{}-{}.tar.zst",
+            capsule.name,
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        SaveManager::export(capsule, &locations, &archive)?;
+        Ok(Some(archive))
+    }
+
+    /// Remove the prefix, parking the game's install folder next to it and
+    /// putting it back afterwards when it lives inside the prefix.
+    fn remove_prefix(capsule: &Capsule, remove_dir: &dyn Fn(&Path) -> Result<()>) -> Result<()> {
+        let prefix_path = capsule.home_path.join("prefix");
+        if !prefix_path.exists() {
+            return Ok(());
+        }
+        let game_root = capsule.install_root();
+        let has_game = !capsule.metadata.executables.main.path.trim().is_empty();
+        if has_game && game_root.is_none() && !Self::game_outside_prefix(capsule, &prefix_path) {
+            anyhow::bail!(
+                "Couldn't find the game's files in the prefix; set the game folder in Settings \
+                 or delete the whole game"
+            );
+        }
+        let parked = match &game_root {
+            Some(game_root) => {
+                let parked = capsule.capsule_dir.join(".game-files");
+                if parked.exists() {
+                    anyhow::bail!("{:?} is in the way; move it first", parked);
+                }
+                fs::rename(game_root, &parked)
+                    .with_context(|| format!("Failed to set aside {:?}", game_root))?;
+                Some(parked)
+            }
+            None => None,
+        };
+
+        let result = remove_dir(&prefix_path);
+
+        if let (Some(game_root), Some(parked)) = (&game_root, &parked) {
+            if let Some(parent) = game_root.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(parked, game_root).with_context(|| {
+                format!(
+                    "Failed to put the game files back; they are in {:?}",
+                    parked
+                )
+            })?;
+        }
+        result
+    }
+
+    /// Whether the game's files are somewhere removing the prefix can't
+    /// touch, as with a linked or external game folder.
+    fn game_outside_prefix(capsule: &Capsule, prefix_path: &Path) -> bool {
+        let folder = match capsule.game_folder().filter(|dir| file_ops::has_entries(dir)) {
+            Some(folder) => folder,
+            None => return false,
+        };
+        match (fs::canonicalize(folder), fs::canonicalize(prefix_path)) {
+            (Ok(folder), Ok(prefix_path)) => !folder.starts_with(prefix_path),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::core::capsule::Capsule;
+use crate::core::snapshots::SnapshotManager;
+use crate::utils::file_ops;
+
+/// Files Proton keeps next to the prefix to decide whether it needs upgrading.
+const RUNTIME_STATE_FILES: [&str; 4] = [
+    "version",
+    "config_info",
+    "tracked_files",
+    ".update-timestamp",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairAction {
+    RebuildPrefix,
+    ClearCache,
+    ResetRuntime,
+    FixPermissions,
+    FullRepair,
+}
+
+impl RepairAction {
+    pub const ALL: [RepairAction; 5] = [
+        RepairAction::FixPermissions,
+        RepairAction::ClearCache,
+        RepairAction::ResetRuntime,
+        RepairAction::RebuildPrefix,
+        RepairAction::FullRepair,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RepairAction::RebuildPrefix => "Rebuild prefix",
+            RepairAction::ClearCache => "Clear cache",
+            RepairAction::ResetRuntime => "Reset runtime",
+            RepairAction::FixPermissions => "Fix permissions",
+            RepairAction::FullRepair => "Full repair",
+        }
+    }
+
+    /// What the action does, shown before asking for confirmation.
+    pub fn description(&self) -> &'static str {
+        match self {
+            RepairAction::RebuildPrefix => {
+                "Replace the Wine prefix with a fresh one. Game files under prefix/games are kept, \
+                 but anything installed into drive_c is removed. A snapshot is taken first."
+            }
+            RepairAction::ClearCache => {
+                "Delete temporary files and shader caches. They are rebuilt the next time the game runs."
+            }
+            RepairAction::ResetRuntime => {
+                "Forget which Proton build set up the prefix so it is upgraded again on the next launch."
+            }
+            RepairAction::FixPermissions => {
+                "Make every file in the capsule readable and writable by you again."
+            }
+            RepairAction::FullRepair => {
+                "Fix permissions, clear caches and reset the runtime. The prefix itself is kept."
+            }
+        }
+    }
+
+    pub fn steps(&self) -> &'static [RepairStep] {
+        match self {
+            RepairAction::RebuildPrefix => &[
+                RepairStep::Snapshot,
+                RepairStep::RebuildPrefix,
+                RepairStep::InitializePrefix,
+            ],
+            RepairAction::ClearCache => &[RepairStep::ClearCache],
+            RepairAction::ResetRuntime => &[RepairStep::ResetRuntime, RepairStep::InitializePrefix],
+            RepairAction::FixPermissions => &[RepairStep::FixPermissions],
+            RepairAction::FullRepair => &[
+                RepairStep::FixPermissions,
+                RepairStep::ClearCache,
+                RepairStep::ResetRuntime,
+                RepairStep::InitializePrefix,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairStep {
+    Snapshot,
+    RebuildPrefix,
+    ClearCache,
+    ResetRuntime,
+    FixPermissions,
+    /// Let umu set the prefix up again; run by the caller, which owns the
+    /// launch environment.
+    InitializePrefix,
+}
+
+impl RepairStep {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RepairStep::Snapshot => "Taking a snapshot",
+            RepairStep::RebuildPrefix => "Creating a fresh prefix",
+            RepairStep::ClearCache => "Clearing caches",
+            RepairStep::ResetRuntime => "Resetting runtime state",
+            RepairStep::FixPermissions => "Fixing permissions",
+            RepairStep::InitializePrefix => "Initializing prefix",
+        }
+    }
+}
+
+/// Repairs for one capsule's home folder and prefix.
+pub struct RepairTools {
+    capsule: Capsule,
+    prefix_path: PathBuf,
+}
+
+impl RepairTools {
+    pub fn new(capsule: &Capsule) -> Self {
+        Self {
+            prefix_path: capsule.home_path.join("prefix"),
+            capsule: capsule.clone(),
+        }
+    }
+
+    /// Run one step other than `InitializePrefix`; returns a short summary.
+    pub fn run(&mut self, step: RepairStep) -> Result<String> {
+        match step {
+            RepairStep::Snapshot => {
+                SnapshotManager::for_capsule(&self.capsule)
+                    .create("Before rebuilding prefix", true)
+                    .context("Failed to snapshot prefix")?;
+                Ok("Snapshot taken".to_string())
+            }
+            RepairStep::RebuildPrefix => {
+                self.rebuild_prefix()?;
+                Ok("Prefix recreated".to_string())
+            }
+            RepairStep::ClearCache => {
+                let freed = self.clear_cache()?;
+                Ok(format!("Freed {}", file_ops::format_bytes(freed)))
+            }
+            RepairStep::ResetRuntime => {
+                self.reset_runtime()?;
+                Ok("Runtime state reset".to_string())
+            }
+            RepairStep::FixPermissions => {
+                let fixed = self.fix_permissions()?;
+                Ok(format!("Fixed permissions on {} entries", fixed))
+            }
+            RepairStep::InitializePrefix => anyhow::bail!("Prefix initialization runs through umu"),
+        }
+    }
+
+    /// Swap the prefix for an empty one, carrying `games/` over. The old prefix
+    /// is put back if anything fails before the swap completes.
+    fn rebuild_prefix(&self) -> Result<()> {
+        let parent = self
+            .prefix_path
+            .parent()
+            .context("Prefix has no parent directory")?;
+        let previous = parent.join("prefix.previous");
+        if previous.exists() {
+            fs::remove_dir_all(&previous)?;
+        }
+
+        if self.prefix_path.exists() {
+            fs::rename(&self.prefix_path, &previous)
+                .context("Failed to move the current prefix aside")?;
+        }
+        if let Err(e) = fs::create_dir_all(&self.prefix_path) {
+            let _ = fs::rename(&previous, &self.prefix_path);
+            return Err(e).context("Failed to create the new prefix");
+        }
+
+        let old_games = previous.join("games");
+        if old_games.exists() {
+            if let Err(e) = fs::rename(&old_games, self.prefix_path.join("games")) {
+                let _ = fs::remove_dir_all(&self.prefix_path);
+                let _ = fs::rename(&previous, &self.prefix_path);
+                return Err(e).context("Failed to carry game files over");
+            }
+        }
+
+        if previous.exists() {
+            fs::remove_dir_all(&previous).context("Failed to remove the old prefix")?;
+        }
+        Ok(())
+    }
+
+    /// Empty temp folders and the capsule's cache folder; returns bytes freed.
+    fn clear_cache(&self) -> Result<u64> {
+        let mut dirs = vec![
+            self.capsule.home_path.join(".cache"),
+            self.prefix_path
+                .join("drive_c")
+                .join("windows")
+                .join("temp"),
+        ];
+        if let Ok(users) = fs::read_dir(self.prefix_path.join("drive_c").join("users")) {
+            for user in users.flatten() {
+                dirs.push(user.path().join("Temp"));
+                dirs.push(user.path().join("AppData").join("Local").join("Temp"));
+            }
+        }
+
+        let mut freed = 0;
+        for dir in dirs {
+            // Old prefixes link Temp to the AppData folder; don't clear it twice.
+            if file_ops::is_symlink(&dir) || !dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir)?.flatten() {
+                let path = entry.path();
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    freed += file_ops::dir_size(&path);
+                    fs::remove_dir_all(&path)
+                        .with_context(|| format!("Failed to remove {:?}", path))?;
+                } else {
+                    if file_type.is_file() {
+                        freed += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+                    }
+                    fs::remove_file(&path)
+                        .with_context(|| format!("Failed to remove {:?}", path))?;
+                }
+            }
+        }
+        Ok(freed)
+    }
+
+    fn reset_runtime(&mut self) -> Result<()> {
+        for name in RUNTIME_STATE_FILES {
+            let path = self.prefix_path.join(name);
+            if path.exists() {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+            }
+        }
+        self.capsule.metadata.wine_version = None;
+        self.capsule.save_metadata()
+    }
+
+    /// Give the owner read/write (and search on folders) on everything in the
+    /// capsule home. Returns how many entries changed.
+    fn fix_permissions(&self) -> Result<usize> {
+        let mut fixed = 0;
+        for entry in WalkDir::new(&self.capsule.home_path).follow_links(false) {
+            let entry = entry?;
+            if entry.path_is_symlink() {
+                continue;
+            }
+            let required = if entry.file_type().is_dir() {
+                0o700
+            } else {
+                0o600
+            };
+            if Self::ensure_mode(entry.path(), required)? {
+                fixed += 1;
+            }
+        }
+        Ok(fixed)
+    }
+
+    fn ensure_mode(path: &Path, required: u32) -> Result<bool> {
+        let mut permissions = fs::metadata(path)?.permissions();
+        let mode = permissions.mode();
+        if mode & required == required {
+            return Ok(false);
+        }
+        permissions.set_mode(mode | required);
+        fs::set_permissions(path, permissions)
+            .with_context(|| format!("Failed to update permissions on {:?}", path))?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::capsule::CapsuleMetadata;
+use crate::core::system_checker::SystemCheck;
+use crate::utils::host;
+
+/// What starts games when `umu-run` isn't installed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackRunner {
+    /// Nothing; launches fail until umu-launcher is installed.
+    #[default]
+    None,
+    /// The selected Proton's own `proton run`, without the Steam Runtime.
+    Proton,
+    /// The system's `wine`.
+    Wine,
+}
+
+impl FallbackRunner {
+    pub const ALL: [FallbackRunner; 3] = [Self::None, Self::Proton, Self::Wine];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "Don't launch",
+            Self::Proton => "Proton directly (proton run)",
+            Self::Wine => "System Wine",
+        }
+    }
+}
+
+/// Launcher settings, stored in `~/.linuxboy/runner.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunnerSettings {
+    #[serde(default)]
+    pub fallback: FallbackRunner,
+}
+
+impl RunnerSettings {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("runner.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize runner settings")?;
+        fs::write(&path, content).context("Failed to write runner settings")?;
+        Ok(())
+    }
+}
+
+/// The program Windows executables are started with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runner {
+    Umu,
+    Proton,
+    Wine,
+}
+
+impl Runner {
+    /// `umu-run` when it is installed, otherwise the fallback picked in
+    /// Preferences, or why nothing can start games.
+    pub fn detect() -> Result<Runner> {
+        if host::has_command("umu-run") {
+            return Ok(Runner::Umu);
+        }
+        match RunnerSettings::load().fallback {
+            FallbackRunner::None => anyhow::bail!(
+                "umu-run not found in PATH; install umu-launcher or choose a fallback runner in \
+                 Preferences"
+            ),
+            FallbackRunner::Proton => Ok(Runner::Proton),
+            FallbackRunner::Wine if host::has_command("wine") => Ok(Runner::Wine),
+            FallbackRunner::Wine => anyhow::bail!("Neither umu-run nor wine found in PATH"),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Runner::Umu => "umu-run",
+            Runner::Proton => "proton run",
+            Runner::Wine => "wine",
+        }
+    }
+
+    /// A command running a Windows program in `prefix_path` with the game's
+    /// environment; the program and its arguments are appended by the caller.
+    /// `proton_path` is ignored by system Wine.
+    pub fn command(
+        self,
+        prefix_path: &Path,
+        proton_path: &Path,
+        metadata: &CapsuleMetadata,
+    ) -> Command {
+        let mut cmd = match self {
+            Runner::Umu => {
+                let mut cmd = Command::new("umu-run");
+                cmd.env("PROTONPATH", proton_path);
+                cmd
+            }
+            Runner::Proton => {
+                Self::link_pfx(prefix_path);
+                let mut cmd = Command::new(proton_path.join("proton"));
+                cmd.arg("run");
+                cmd.env("STEAM_COMPAT_DATA_PATH", prefix_path);
+                // Proton only reads it to copy Steam's client files, which
+                // don't exist outside Steam.
+                cmd.env("STEAM_COMPAT_CLIENT_INSTALL_PATH", "");
+                cmd
+            }
+            Runner::Wine => {
+                let mut cmd = Command::new("wine");
+                // Proton ignores WINEARCH, so only system Wine gets it.
+                if metadata.win32_prefix {
+                    cmd.env("WINEARCH", "win32");
+                }
+                cmd
+            }
+        };
+        cmd.env("WINEPREFIX", prefix_path);
+        let env = metadata.umu_env();
+        if self == Runner::Proton {
+            // GE-Proton applies protonfixes by this id; umu-run sets it from
+            // GAMEID.
+            if let Some((_, game_id)) = env.iter().find(|(key, _)| key == "GAMEID") {
+                cmd.env("UMU_ID", game_id);
+            }
+        }
+        cmd.envs(env);
+        cmd
+    }
+
+    /// Proton keeps its prefix in `pfx` below `STEAM_COMPAT_DATA_PATH`.
+    /// umu-run links `pfx` back to the prefix itself; do the same so both
+    /// runners share one prefix.
+    fn link_pfx(prefix_path: &Path) {
+        let pfx = prefix_path.join("pfx");
+        if fs::symlink_metadata(&pfx).is_ok() {
+            return;
+        }
+        if let Err(e) =
+            fs::create_dir_all(prefix_path).and_then(|_| std::os::unix::fs::symlink(".", &pfx))
+        {
+            eprintln!("Failed to link {:?}: {}", pfx, e);
+        }
+    }
+}
+
+/// A capsule's own launch command, replacing the runner's. The template is
+/// split into words like a shell would, with quotes grouping words. `{exe}`,
+/// `{prefix}` and `{proton}` are replaced inside words; a `{args}` word
+/// becomes the game's arguments, one word each.
+pub struct CommandTemplate;
+
+impl CommandTemplate {
+    pub const PLACEHOLDERS: [&'static str; 4] = ["{exe}", "{args}", "{prefix}", "{proton}"];
+
+    /// The command `template` describes, with the environment the built-in
+    /// runner would set, so `umu-run {exe} {args}` launches like the
+    /// default.
+    pub fn command(
+        template: &str,
+        exe: &Path,
+        args: &[String],
+        prefix_path: &Path,
+        proton_path: &Path,
+        metadata: &CapsuleMetadata,
+    ) -> Result<Command> {
+        let exe = exe.to_string_lossy();
+        let prefix = prefix_path.to_string_lossy();
+        let proton = proton_path.to_string_lossy();
+        let mut words = Vec::new();
+        for word in split_words(template)? {
+            if word == "{args}" {
+                words.extend(args.iter().cloned());
+                continue;
+            }
+            words.push(
+                word.replace("{exe}", &exe)
+                    .replace("{prefix}", &prefix)
+                    .replace("{proton}", &proton)
+                    .replace("{args}", &args.join(" ")),
+            );
+        }
+        let mut words = words.into_iter();
+        let program = match words.next() {
+            Some(program) => program,
+            None => anyhow::bail!("The launch command is empty"),
+        };
+
+        let mut cmd = Command::new(program);
+        cmd.args(words);
+        cmd.env("WINEPREFIX", prefix_path);
+        cmd.env("PROTONPATH", proton_path);
+        cmd.envs(metadata.umu_env());
+        Ok(cmd)
+    }
+}
+
+/// Whitespace-separated words, as a shell would split them: single or
+/// double quotes group words and are removed.
+pub fn split_words(text: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    for ch in text.chars() {
+        match (quote, ch) {
+            (Some(open), ch) if ch == open => quote = None,
+            (Some(_), ch) => current.push(ch),
+            (None, '"' | '\'') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, ch) if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, ch) => {
+                current.push(ch);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        anyhow::bail!("Unclosed quote in \"{}\"", text.trim());
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
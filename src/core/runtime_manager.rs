@@ -2,10 +2,34 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use std::fs::{self, File, OpenOptions};
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::network;
+use crate::core::system_checker::SystemCheck;
+use crate::utils::file_ops;
 
 const GITHUB_API_RELEASES: &str = "https://api.github.com/repos/GloriousEggroll/proton-ge-custom/releases";
+/// A Proton-GE tarball unpacks to roughly three times its size.
+const EXTRACTED_SIZE_FACTOR: u64 = 3;
+const GITHUB_URL_PREFIX: &str = "https://github.com/";
+/// Attempts per download source before moving on to the next mirror.
+const DOWNLOAD_ATTEMPTS: u32 = 4;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+/// Files at least this large are fetched over several connections.
+const SEGMENTED_MIN_SIZE: u64 = 64 * 1_048_576;
+const SEGMENT_COUNT: u64 = 4;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+/// Minimum time between transfer speed samples.
+const RATE_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+/// The rate limiter forgets older traffic after this long, so a pause
+/// doesn't turn into a burst at full speed.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProtonRelease {
@@ -20,6 +44,210 @@ pub struct GitHubAsset {
     pub name: String,
     pub browser_download_url: String,
     pub size: u64,
+    /// `sha256:<hex>`, on assets uploaded since GitHub started publishing
+    /// digests.
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+impl GitHubAsset {
+    pub fn sha256(&self) -> Option<&str> {
+        self.digest.as_deref()?.strip_prefix("sha256:")
+    }
+}
+
+/// Global download settings, read from `~/.linuxboy/downloads.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DownloadSettings {
+    /// Alternate hosts for GitHub release assets. Each entry is a URL
+    /// template: `{url}` is replaced with the full GitHub URL and `{path}`
+    /// with the part after `https://github.com/`. An entry without either
+    /// is used as a base URL for the path.
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    /// Download speed cap in KB/s; `None` or 0 is unlimited.
+    #[serde(default)]
+    pub max_rate_kbps: Option<u64>,
+}
+
+impl DownloadSettings {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("downloads.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize download settings")?;
+        fs::write(&path, content).context("Failed to write download settings")?;
+        Ok(())
+    }
+
+    fn rate_limiter(&self) -> Option<RateLimiter> {
+        self.max_rate_kbps
+            .filter(|rate| *rate > 0)
+            .map(|rate| RateLimiter::new(rate * 1024))
+    }
+
+    /// `url` followed by its mirrored forms. Only GitHub URLs are mirrored.
+    pub fn candidate_urls(&self, url: &str) -> Vec<String> {
+        let mut urls = vec![url.to_string()];
+        let path = match url.strip_prefix(GITHUB_URL_PREFIX) {
+            Some(path) => path,
+            None => return urls,
+        };
+        for mirror in self.mirrors.iter().map(|mirror| mirror.trim()) {
+            if mirror.is_empty() {
+                continue;
+            }
+            let mirrored = if mirror.contains("{url}") || mirror.contains("{path}") {
+                mirror.replace("{url}", url).replace("{path}", path)
+            } else {
+                format!("{}/{}", mirror.trim_end_matches('/'), path)
+            };
+            if !urls.contains(&mirrored) {
+                urls.push(mirrored);
+            }
+        }
+        urls
+    }
+}
+
+/// Caps the combined speed of every connection of a download by sleeping
+/// after reads that get ahead of the allowed rate.
+struct RateLimiter {
+    bytes_per_sec: u64,
+    /// Start of the current window and the bytes read since.
+    window: Mutex<(Instant, u64)>,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec,
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    fn consume(&self, bytes: u64) {
+        let delay = {
+            let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+            if window.0.elapsed() > RATE_LIMIT_WINDOW {
+                *window = (Instant::now(), 0);
+            }
+            window.1 += bytes;
+            let allowed = Duration::from_secs_f64(window.1 as f64 / self.bytes_per_sec as f64);
+            allowed.saturating_sub(window.0.elapsed())
+        };
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+    }
+}
+
+/// Returned when a download is stopped through its cancel flag. The partial
+/// file is kept, so the next download of the same file resumes.
+#[derive(Debug)]
+pub struct DownloadCancelled;
+
+impl std::fmt::Display for DownloadCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Download cancelled")
+    }
+}
+
+impl std::error::Error for DownloadCancelled {}
+
+/// How a failed download attempt should be followed up.
+enum AttemptError {
+    Cancelled,
+    /// Worth retrying from the same source after a pause.
+    Retry(anyhow::Error),
+    /// The source can't serve the file; try the next one.
+    NextSource(anyhow::Error),
+}
+
+impl From<std::io::Error> for AttemptError {
+    fn from(error: std::io::Error) -> Self {
+        AttemptError::Retry(error.into())
+    }
+}
+
+impl From<reqwest::Error> for AttemptError {
+    fn from(error: reqwest::Error) -> Self {
+        AttemptError::Retry(error.into())
+    }
+}
+
+/// Smoothed download speed and time remaining, for progress text.
+#[derive(Default)]
+pub struct TransferRate {
+    /// When and at how many bytes the last sample was taken; unset until
+    /// the first update, so data resumed from disk doesn't count as speed.
+    sample: Option<(Instant, u64)>,
+    bytes_per_sec: Option<f64>,
+}
+
+impl TransferRate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, downloaded: u64) {
+        let (sampled_at, sampled_bytes) = match self.sample {
+            Some(sample) => sample,
+            None => {
+                self.sample = Some((Instant::now(), downloaded));
+                return;
+            }
+        };
+        let elapsed = sampled_at.elapsed();
+        if elapsed < RATE_SAMPLE_INTERVAL {
+            return;
+        }
+        let sample = downloaded.saturating_sub(sampled_bytes) as f64 / elapsed.as_secs_f64();
+        // Exponential moving average, so one slow read doesn't swing the ETA.
+        self.bytes_per_sec = Some(match self.bytes_per_sec {
+            Some(rate) => rate * 0.7 + sample * 0.3,
+            None => sample,
+        });
+        self.sample = Some((Instant::now(), downloaded));
+    }
+
+    /// e.g. "5.3 MB/s, 1m 12s left"; empty until the first sample.
+    pub fn describe(&self, downloaded: u64, total: u64) -> String {
+        let rate = match self.bytes_per_sec {
+            Some(rate) => rate,
+            None => return String::new(),
+        };
+        let speed = if rate >= 1_048_576.0 {
+            format!("{:.1} MB/s", rate / 1_048_576.0)
+        } else {
+            format!("{:.0} KB/s", rate / 1024.0)
+        };
+        if rate < 1.0 || total <= downloaded {
+            return speed;
+        }
+        let seconds = ((total - downloaded) as f64 / rate).round() as u64;
+        let eta = if seconds >= 3600 {
+            format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+        } else if seconds >= 60 {
+            format!("{}m {:02}s", seconds / 60, seconds % 60)
+        } else {
+            format!("{}s", seconds)
+        };
+        format!("{}, {} left", speed, eta)
+    }
 }
 
 #[derive(Clone)]
@@ -30,7 +258,7 @@ pub struct RuntimeManager {
 #[allow(dead_code)]
 impl RuntimeManager {
     pub fn new() -> Self {
-        let runtimes_dir = crate::core::system_checker::SystemCheck::get_runtimes_dir();
+        let runtimes_dir = SystemCheck::get_runtimes_dir();
         Self { runtimes_dir }
     }
 
@@ -38,9 +266,7 @@ impl RuntimeManager {
     pub fn fetch_available_releases(&self) -> Result<Vec<ProtonRelease>> {
         println!("Fetching Proton-GE releases from GitHub...");
         
-        let client = reqwest::blocking::Client::builder()
-            .user_agent("LinuxBoy/0.1")
-            .build()?;
+        let client = network::client_builder()?.build()?;
 
         let response = client
             .get(GITHUB_API_RELEASES)
@@ -78,12 +304,17 @@ impl RuntimeManager {
             .find(|asset| asset.name.ends_with(".sha512sum"))
     }
 
-    /// Download a file from URL with resume support and progress callback
+    /// Download a file from URL with resume support and progress callback.
+    /// Failed attempts are retried with exponential backoff, resuming the
+    /// partial file, and then the configured mirrors are tried in turn.
+    /// Setting `cancel` stops the download with `DownloadCancelled`. The
+    /// speed cap from `DownloadSettings` applies across all connections.
     pub fn download_file<F>(
         &self,
         url: &str,
         dest_path: &Path,
         expected_size: Option<u64>,
+        cancel: &AtomicBool,
         mut progress_callback: F,
     ) -> Result<()>
     where
@@ -92,10 +323,96 @@ impl RuntimeManager {
         println!("Downloading: {}", url);
         println!("Destination: {:?}", dest_path);
 
-        let client = reqwest::blocking::Client::builder()
-            .user_agent("LinuxBoy/0.1")
-            .build()?;
+        let client = network::client_builder()?.build()?;
+
+        let settings = DownloadSettings::load();
+        let limiter = settings.rate_limiter();
+        let limiter = limiter.as_ref();
+        let mut last_error = None;
+        for source in settings.candidate_urls(url) {
+            if source != url {
+                println!("Trying mirror: {}", source);
+            }
+            for attempt in 1..=DOWNLOAD_ATTEMPTS {
+                match Self::download_attempt(
+                    &client,
+                    &source,
+                    dest_path,
+                    expected_size,
+                    cancel,
+                    limiter,
+                    &mut progress_callback,
+                ) {
+                    Ok(()) => return Ok(()),
+                    Err(AttemptError::Cancelled) => {
+                        println!("Download cancelled; keeping partial file");
+                        return Err(DownloadCancelled.into());
+                    }
+                    Err(AttemptError::NextSource(e)) => {
+                        eprintln!("Download from {} failed: {}", source, e);
+                        last_error = Some(e);
+                        break;
+                    }
+                    Err(AttemptError::Retry(e)) => {
+                        eprintln!(
+                            "Download attempt {}/{} from {} failed: {}",
+                            attempt, DOWNLOAD_ATTEMPTS, source, e
+                        );
+                        last_error = Some(e);
+                        if attempt < DOWNLOAD_ATTEMPTS {
+                            let delay = RETRY_BASE_DELAY
+                                .saturating_mul(1 << (attempt - 1))
+                                .min(RETRY_MAX_DELAY);
+                            let waited = Instant::now();
+                            while waited.elapsed() < delay {
+                                if cancel.load(Ordering::Relaxed) {
+                                    return Err(DownloadCancelled.into());
+                                }
+                                thread::sleep(PROGRESS_INTERVAL);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| anyhow::anyhow!("No download source for {}", url))
+            .context(format!("Failed to download {}", url)))
+    }
 
+    /// Status codes that mean the source doesn't have the file, as opposed
+    /// to being temporarily unavailable.
+    fn is_permanent_failure(status: reqwest::StatusCode) -> bool {
+        status.is_client_error()
+            && status != reqwest::StatusCode::REQUEST_TIMEOUT
+            && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn check_status(status: reqwest::StatusCode) -> std::result::Result<(), AttemptError> {
+        if status.is_success() {
+            Ok(())
+        } else if Self::is_permanent_failure(status) {
+            Err(AttemptError::NextSource(anyhow::anyhow!(
+                "Download failed with status: {}",
+                status
+            )))
+        } else {
+            Err(AttemptError::Retry(anyhow::anyhow!(
+                "Download failed with status: {}",
+                status
+            )))
+        }
+    }
+
+    fn download_attempt(
+        client: &reqwest::blocking::Client,
+        url: &str,
+        dest_path: &Path,
+        expected_size: Option<u64>,
+        cancel: &AtomicBool,
+        limiter: Option<&RateLimiter>,
+        progress_callback: &mut dyn FnMut(u64, u64),
+    ) -> std::result::Result<(), AttemptError> {
         let expected_size = expected_size.filter(|size| *size > 0);
         let filename = dest_path
             .file_name()
@@ -140,6 +457,23 @@ impl RuntimeManager {
             }
         }
 
+        // A single-stream .part keeps resuming as one stream.
+        if let Some(expected) = expected_size.filter(|size| *size >= SEGMENTED_MIN_SIZE) {
+            if existing == 0
+                && Self::download_segmented(
+                    client,
+                    url,
+                    dest_path,
+                    expected,
+                    cancel,
+                    limiter,
+                    progress_callback,
+                )?
+            {
+                return Ok(());
+            }
+        }
+
         let mut response = if existing > 0 {
             client
                 .get(url)
@@ -149,9 +483,7 @@ impl RuntimeManager {
             client.get(url).send()?
         };
 
-        if !response.status().is_success() {
-            anyhow::bail!("Download failed with status: {}", response.status());
-        }
+        Self::check_status(response.status())?;
 
         if existing > 0 && response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
             // Range requests are not supported; restart from scratch.
@@ -160,9 +492,7 @@ impl RuntimeManager {
                 fs::remove_file(&temp_path)?;
             }
             response = client.get(url).send()?;
-            if !response.status().is_success() {
-                anyhow::bail!("Download failed with status: {}", response.status());
-            }
+            Self::check_status(response.status())?;
         }
 
         let segment_size = response.content_length().unwrap_or(0);
@@ -188,6 +518,9 @@ impl RuntimeManager {
 
         let mut buffer = [0u8; 8192];
         loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(AttemptError::Cancelled);
+            }
             let bytes_read = response.read(&mut buffer)?;
             if bytes_read == 0 {
                 break;
@@ -195,6 +528,9 @@ impl RuntimeManager {
 
             file.write_all(&buffer[..bytes_read])?;
             downloaded += bytes_read as u64;
+            if let Some(limiter) = limiter {
+                limiter.consume(bytes_read as u64);
+            }
 
             // Report progress
             progress_callback(downloaded, total_size);
@@ -202,16 +538,205 @@ impl RuntimeManager {
 
         if let Some(expected) = expected_size {
             if downloaded < expected {
-                anyhow::bail!("Download incomplete: {} / {} bytes", downloaded, expected);
+                return Err(AttemptError::Retry(anyhow::anyhow!(
+                    "Download incomplete: {} / {} bytes",
+                    downloaded,
+                    expected
+                )));
             }
             if downloaded > expected {
                 let _ = fs::remove_file(&temp_path);
-                anyhow::bail!("Download size mismatch: {} / {} bytes", downloaded, expected);
+                return Err(AttemptError::NextSource(anyhow::anyhow!(
+                    "Download size mismatch: {} / {} bytes",
+                    downloaded,
+                    expected
+                )));
+            }
+        }
+
+        fs::rename(&temp_path, dest_path)?;
+        println!("Download complete!");
+        Ok(())
+    }
+
+    /// Part files of a segmented download of `dest_path`, one per segment.
+    pub fn segment_paths(dest_path: &Path) -> Vec<PathBuf> {
+        let filename = dest_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("download");
+        (0..SEGMENT_COUNT)
+            .map(|index| dest_path.with_file_name(format!("{}.part{}", filename, index)))
+            .collect()
+    }
+
+    /// Fetch `url` as `SEGMENT_COUNT` HTTP ranges in parallel, each into its
+    /// own part file so an interrupted segment resumes on the next attempt.
+    /// Returns false, without downloading, when the server ignores ranges.
+    fn download_segmented(
+        client: &reqwest::blocking::Client,
+        url: &str,
+        dest_path: &Path,
+        total: u64,
+        cancel: &AtomicBool,
+        limiter: Option<&RateLimiter>,
+        progress_callback: &mut dyn FnMut(u64, u64),
+    ) -> std::result::Result<bool, AttemptError> {
+        let segment_paths = Self::segment_paths(dest_path);
+        if !segment_paths.iter().any(|path| path.exists()) {
+            let probe = client
+                .get(url)
+                .header(reqwest::header::RANGE, "bytes=0-0")
+                .send()?;
+            Self::check_status(probe.status())?;
+            if probe.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+                return Ok(false);
+            }
+            println!("Downloading in {} segments", SEGMENT_COUNT);
+        } else {
+            println!("Resuming segmented download");
+        }
+
+        let segment_len = total.div_ceil(SEGMENT_COUNT);
+        let ranges: Vec<(u64, u64)> = (0..SEGMENT_COUNT)
+            .map(|index| {
+                let start = index * segment_len;
+                (start, ((index + 1) * segment_len).min(total) - 1)
+            })
+            .collect();
+        let downloaded = AtomicU64::new(
+            ranges
+                .iter()
+                .zip(&segment_paths)
+                .map(|((start, end), path)| {
+                    let len = path.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+                    len.min(end - start + 1)
+                })
+                .sum(),
+        );
+
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = ranges
+                .iter()
+                .zip(&segment_paths)
+                .map(|(&range, path)| {
+                    let downloaded = &downloaded;
+                    scope.spawn(move || {
+                        Self::download_segment(client, url, path, range, downloaded, cancel, limiter)
+                    })
+                })
+                .collect();
+            while !handles.iter().all(|handle| handle.is_finished()) {
+                progress_callback(downloaded.load(Ordering::Relaxed), total);
+                thread::sleep(PROGRESS_INTERVAL);
             }
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        Err(AttemptError::Retry(anyhow::anyhow!("Download thread panicked")))
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        progress_callback(downloaded.load(Ordering::Relaxed), total);
+        if cancel.load(Ordering::Relaxed) {
+            return Err(AttemptError::Cancelled);
+        }
+        for result in results {
+            result?;
         }
 
+        let filename = dest_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("download");
+        let temp_path = dest_path.with_file_name(format!("{}.part", filename));
+        let mut output = File::create(&temp_path)?;
+        for path in &segment_paths {
+            io::copy(&mut File::open(path)?, &mut output)?;
+        }
+        drop(output);
+        let joined = temp_path.metadata()?.len();
+        for path in &segment_paths {
+            let _ = fs::remove_file(path);
+        }
+        if joined != total {
+            let _ = fs::remove_file(&temp_path);
+            return Err(AttemptError::Retry(anyhow::anyhow!(
+                "Download size mismatch: {} / {} bytes",
+                joined,
+                total
+            )));
+        }
         fs::rename(&temp_path, dest_path)?;
         println!("Download complete!");
+        Ok(true)
+    }
+
+    /// Download bytes `start..=end` into `path`, continuing after what the
+    /// file already holds.
+    fn download_segment(
+        client: &reqwest::blocking::Client,
+        url: &str,
+        path: &Path,
+        (start, end): (u64, u64),
+        downloaded: &AtomicU64,
+        cancel: &AtomicBool,
+        limiter: Option<&RateLimiter>,
+    ) -> std::result::Result<(), AttemptError> {
+        let len = end - start + 1;
+        let mut existing = path.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if existing == len {
+            return Ok(());
+        }
+        if existing > len {
+            downloaded.fetch_sub(len, Ordering::Relaxed);
+            fs::remove_file(path)?;
+            existing = 0;
+        }
+
+        let mut response = client
+            .get(url)
+            .header(
+                reqwest::header::RANGE,
+                format!("bytes={}-{}", start + existing, end),
+            )
+            .send()?;
+        Self::check_status(response.status())?;
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(AttemptError::NextSource(anyhow::anyhow!(
+                "Server ignored the range request"
+            )));
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut written = existing;
+        let mut buffer = [0u8; 8192];
+        while written < len {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(AttemptError::Cancelled);
+            }
+            let bytes_read = response.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            let bytes_read = (bytes_read as u64).min(len - written) as usize;
+            file.write_all(&buffer[..bytes_read])?;
+            written += bytes_read as u64;
+            downloaded.fetch_add(bytes_read as u64, Ordering::Relaxed);
+            if let Some(limiter) = limiter {
+                limiter.consume(bytes_read as u64);
+            }
+        }
+
+        if written < len {
+            return Err(AttemptError::Retry(anyhow::anyhow!(
+                "Segment incomplete: {} / {} bytes",
+                written,
+                len
+            )));
+        }
         Ok(())
     }
 
@@ -239,11 +764,13 @@ impl RuntimeManager {
         Ok(actual.to_lowercase() == expected_sha256.to_lowercase())
     }
 
-    /// Download and install Proton-GE with progress callback
+    /// Download and install Proton-GE with progress callback. Setting
+    /// `cancel` stops the download and keeps the partial file for a resume.
     pub fn install_proton_ge<F>(
         &self,
         release: &ProtonRelease,
         reinstall: bool,
+        cancel: &AtomicBool,
         mut progress_callback: F,
     ) -> Result<PathBuf>
     where
@@ -272,6 +799,9 @@ impl RuntimeManager {
         if reinstall {
             let _ = fs::remove_file(&download_path);
             let _ = fs::remove_file(&partial_path);
+            for segment in Self::segment_paths(&download_path) {
+                let _ = fs::remove_file(segment);
+            }
         }
 
         // Download if not already cached (or if size doesn't match)
@@ -280,7 +810,20 @@ impl RuntimeManager {
             cached_size = download_path.metadata()?.len();
         }
 
-        if !download_path.exists() || expected_size.map(|size| cached_size != size).unwrap_or(false) {
+        let needs_download =
+            !download_path.exists() || expected_size.map(|size| cached_size != size).unwrap_or(false);
+        let partial_size = partial_path.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        let remaining_download = if needs_download {
+            targz_asset.size.saturating_sub(partial_size.max(cached_size))
+        } else {
+            0
+        };
+        file_ops::ensure_free_space(
+            &cache_dir,
+            remaining_download + targz_asset.size * EXTRACTED_SIZE_FACTOR,
+        )?;
+
+        if needs_download {
             let total_mb = targz_asset.size / 1_048_576;
             println!("Downloading {} ({} MB)...", filename, total_mb);
 
@@ -301,13 +844,24 @@ impl RuntimeManager {
                 progress_callback(format!("Downloading {} (0 / {} MB)", filename, total_mb), 0.0);
             }
 
-            self.download_file(download_url, &download_path, expected_size, |downloaded, total| {
+            let mut rate = TransferRate::new();
+            self.download_file(download_url, &download_path, expected_size, cancel, |downloaded, total| {
                 if total > 0 {
+                    rate.update(downloaded);
                     let progress = downloaded as f64 / total as f64;
                     let downloaded_mb = downloaded / 1_048_576;
                     let total_mb = total / 1_048_576;
+                    let speed = rate.describe(downloaded, total);
+                    let status = if speed.is_empty() {
+                        format!("Downloading {} ({} / {} MB)", filename, downloaded_mb, total_mb)
+                    } else {
+                        format!(
+                            "Downloading {} ({} / {} MB, {})",
+                            filename, downloaded_mb, total_mb, speed
+                        )
+                    };
                     progress_callback(
-                        format!("Downloading {} ({} / {} MB)", filename, downloaded_mb, total_mb),
+                        status,
                         progress * 0.9,  // Reserve 10% for extraction
                     );
                 }
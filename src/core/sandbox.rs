@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::capsule::Capsule;
+
+/// Host paths a sandboxed game sees read-only when they exist: the system
+/// libraries, drivers and configuration Proton and its runtime need.
+const SYSTEM_PATHS: [&str; 8] = [
+    "/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt",
+];
+/// Below the home directory, read-write: umu-launcher's runtime and caches,
+/// and protonfixes' settings with the user's local fixes.
+const UMU_HOME_PATHS: [&str; 3] = [".local/share/umu", ".cache/umu", ".config/protonfixes"];
+/// Below the home directory, read-only: where pip and the Flatpak shim put
+/// `umu-run`.
+const TOOL_HOME_PATHS: [&str; 2] = [".local/bin", ".local/lib"];
+
+/// Runs game launches inside bubblewrap (`bwrap`).
+pub struct Sandbox;
+
+impl Sandbox {
+    /// Wrap `cmd` so the game only sees the system, its own capsule, the
+    /// Proton runtime and umu's files; the rest of the home directory is an
+    /// empty tmpfs. The GPU, input devices and the session's runtime
+    /// directory (display, audio) stay reachable. Without `network` the game
+    /// gets an empty network namespace.
+    ///
+    /// Call this before `gamescope::wrap` and `host::command`.
+    pub fn wrap(cmd: Command, capsule: &Capsule, proton_path: &Path, network: bool) -> Command {
+        let mut wrapped = Self::bwrap(&cmd);
+        wrapped.arg("--die-with-parent");
+        for path in SYSTEM_PATHS {
+            wrapped.args(["--ro-bind-try", path, path]);
+        }
+        wrapped.args(["--dev-bind", "/dev", "/dev"]);
+        wrapped.args(["--proc", "/proc"]);
+        wrapped.args(["--ro-bind", "/sys", "/sys"]);
+        wrapped.args(["--tmpfs", "/tmp"]);
+        wrapped.args(["--bind-try", "/tmp/.X11-unix", "/tmp/.X11-unix"]);
+        if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+            wrapped
+                .arg("--bind-try")
+                .arg(&runtime_dir)
+                .arg(&runtime_dir);
+        }
+
+        if let Some(home) = dirs::home_dir() {
+            wrapped.arg("--tmpfs").arg(&home);
+            for relative in UMU_HOME_PATHS {
+                let path = home.join(relative);
+                // bwrap can only bind folders that exist.
+                let _ = fs::create_dir_all(&path);
+                wrapped.arg("--bind-try").arg(&path).arg(&path);
+            }
+            for relative in TOOL_HOME_PATHS {
+                let path = home.join(relative);
+                wrapped.arg("--ro-bind-try").arg(&path).arg(&path);
+            }
+        }
+
+        // Bound after the home tmpfs, which may contain them.
+        wrapped.arg("--ro-bind").arg(proton_path).arg(proton_path);
+        for dir in Self::capsule_dirs(capsule) {
+            wrapped.arg("--bind").arg(&dir).arg(&dir);
+        }
+        if !network {
+            wrapped.arg("--unshare-net");
+        }
+        Self::finish(wrapped, cmd)
+    }
+
+    /// Wrap `cmd` so it runs in an empty network namespace and sees the
+    /// rest of the system as usual.
+    ///
+    /// Call this before `gamescope::wrap` and `host::command`.
+    pub fn without_network(cmd: Command) -> Command {
+        let mut wrapped = Self::bwrap(&cmd);
+        wrapped.args(["--dev-bind", "/", "/"]);
+        wrapped.arg("--unshare-net");
+        Self::finish(wrapped, cmd)
+    }
+
+    /// The capsule folder and, for a moved capsule reached through a link,
+    /// the folder the link points at. Game folders linked into the prefix
+    /// from elsewhere, like Heroic installs, are included too.
+    fn capsule_dirs(capsule: &Capsule) -> Vec<PathBuf> {
+        let mut dirs = vec![capsule.capsule_dir.clone()];
+        let real_dir = fs::canonicalize(&capsule.capsule_dir).ok();
+        let game_dir = capsule
+            .metadata
+            .game_dir
+            .as_deref()
+            .and_then(|dir| fs::canonicalize(dir).ok());
+        for dir in [real_dir, game_dir].into_iter().flatten() {
+            if !dirs.iter().any(|existing| dir.starts_with(existing)) {
+                dirs.push(dir);
+            }
+        }
+        dirs
+    }
+
+    /// A `bwrap` command carrying over the environment and working folder
+    /// of `cmd`.
+    fn bwrap(cmd: &Command) -> Command {
+        let mut wrapped = Command::new("bwrap");
+        if let Some(dir) = cmd.get_current_dir() {
+            wrapped.current_dir(dir);
+            wrapped.arg("--chdir").arg(dir);
+        }
+        for (key, value) in cmd.get_envs() {
+            match value {
+                Some(value) => wrapped.env(key, value),
+                None => wrapped.env_remove(key),
+            };
+        }
+        wrapped
+    }
+
+    fn finish(mut wrapped: Command, cmd: Command) -> Command {
+        wrapped.arg("--");
+        wrapped.arg(cmd.get_program());
+        wrapped.args(cmd.get_args());
+        wrapped
+    }
+}
@@ -0,0 +1,291 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+use crate::core::capsule::Capsule;
+use crate::core::system_checker::SystemCheck;
+use crate::core::umu_database::UmuDatabase;
+use crate::utils::file_ops;
+
+const BUILTIN_RULES: &str = include_str!("save_rules.json");
+
+/// Folder names that show up in every prefix and never hold saves.
+const IGNORED_NAMES: &[&str] = &[
+    "microsoft",
+    "temp",
+    "mygames",
+    "mymusic",
+    "mypictures",
+    "myvideos",
+    "downloads",
+    "desktop",
+    "wine",
+    "nvidia",
+    "nvidiacorporation",
+    "d3dscache",
+    "apple",
+    "applecomputer",
+    "adobe",
+    "google",
+    "mozilla",
+    "unity",
+];
+
+/// Known save locations for a game. `paths` use `%DOCUMENTS%`, `%APPDATA%`,
+/// `%LOCALAPPDATA%`, `%LOCALLOW%`, `%SAVEDGAMES%` or `%USERPROFILE%` with `/`
+/// separators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveRule {
+    #[serde(default)]
+    pub titles: Vec<String>,
+    #[serde(default)]
+    pub game_ids: Vec<String>,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveSource {
+    Rule,
+    Detected,
+}
+
+#[derive(Debug, Clone)]
+pub struct SaveLocation {
+    pub path: PathBuf,
+    pub source: SaveSource,
+    pub size: u64,
+}
+
+impl SaveLocation {
+    /// Path relative to the prefix, for display.
+    pub fn display_path(&self, prefix_path: &Path) -> String {
+        self.path
+            .strip_prefix(prefix_path.join("drive_c"))
+            .map(|rest| format!("C:/{}", rest.to_string_lossy()))
+            .unwrap_or_else(|_| self.path.to_string_lossy().to_string())
+    }
+}
+
+pub struct SaveManager;
+
+impl SaveManager {
+    /// User-editable rules file, merged with the built-in rules.
+    pub fn rules_path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("save_rules.json")
+    }
+
+    /// Find save folders inside the capsule's prefix: rule matches first, then
+    /// folders in the usual places whose name matches the game.
+    pub fn detect(capsule: &Capsule) -> Vec<SaveLocation> {
+        let prefix_path = capsule.home_path.join("prefix");
+        let user_dirs = Self::user_dirs(&prefix_path);
+        let mut locations: Vec<SaveLocation> = Vec::new();
+
+        let name_compact = UmuDatabase::normalize_title(&capsule.name);
+        let game_id = capsule.metadata.game_id.as_deref().unwrap_or("");
+        for rule in Self::load_rules() {
+            let title_match = rule.titles.iter().any(|title| {
+                let title = UmuDatabase::normalize_title(title);
+                !title.is_empty() && name_compact.contains(&title)
+            });
+            let id_match = !game_id.is_empty() && rule.game_ids.iter().any(|id| id == game_id);
+            if !title_match && !id_match {
+                continue;
+            }
+            for user_dir in &user_dirs {
+                for pattern in &rule.paths {
+                    let path = Self::expand_rule_path(user_dir, pattern);
+                    if path.is_dir() {
+                        Self::push_unique(&mut locations, path, SaveSource::Rule);
+                    }
+                }
+            }
+        }
+
+        let mut fallback: Vec<PathBuf> = Vec::new();
+        for user_dir in &user_dirs {
+            for root in Self::search_roots(user_dir) {
+                let entries = match fs::read_dir(&root) {
+                    Ok(entries) => entries,
+                    Err(_) => continue,
+                };
+                let is_fallback_root = root.ends_with("Saved Games") || root.ends_with("My Games");
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if !path.is_dir() {
+                        continue;
+                    }
+                    let folder = UmuDatabase::normalize_title(
+                        &entry.file_name().to_string_lossy(),
+                    );
+                    if folder.is_empty() || IGNORED_NAMES.contains(&folder.as_str()) {
+                        continue;
+                    }
+                    if Self::names_match(&name_compact, &folder) {
+                        Self::push_unique(&mut locations, path, SaveSource::Detected);
+                    } else if is_fallback_root {
+                        fallback.push(path);
+                    }
+                }
+            }
+        }
+
+        if locations.is_empty() {
+            for path in fallback {
+                Self::push_unique(&mut locations, path, SaveSource::Detected);
+            }
+        }
+
+        locations
+    }
+
+    /// Write the given save folders to a `.tar.zst` archive, stored relative to
+    /// the prefix so they can be imported into any capsule of the same game.
+    pub fn export(capsule: &Capsule, locations: &[SaveLocation], dest: &Path) -> Result<usize> {
+        if locations.is_empty() {
+            anyhow::bail!("No save folders found for {}", capsule.name);
+        }
+        let prefix_path = capsule.home_path.join("prefix");
+        let file = File::create(dest).with_context(|| format!("Failed to create {:?}", dest))?;
+        let encoder = zstd::stream::write::Encoder::new(file, 3)?;
+        let mut builder = tar::Builder::new(encoder);
+        builder.follow_symlinks(false);
+
+        let mut count = 0;
+        for location in locations {
+            let relative = location
+                .path
+                .strip_prefix(&prefix_path)
+                .with_context(|| format!("{:?} is outside the prefix", location.path))?;
+            builder
+                .append_dir_all(relative, &location.path)
+                .with_context(|| format!("Failed to archive {:?}", location.path))?;
+            count += 1;
+        }
+
+        let encoder = builder.into_inner()?;
+        encoder.finish().context("Failed to finish save archive")?;
+        Ok(count)
+    }
+
+    /// Extract a save archive created by `export` into the capsule's prefix,
+    /// overwriting existing files.
+    pub fn import(capsule: &Capsule, archive_path: &Path) -> Result<()> {
+        let prefix_path = capsule.home_path.join("prefix");
+        if !prefix_path.is_dir() {
+            anyhow::bail!("Prefix not found at {:?}", prefix_path);
+        }
+        let file = File::open(archive_path)
+            .with_context(|| format!("Failed to open {:?}", archive_path))?;
+        let decoder = zstd::stream::read::Decoder::new(file)?;
+        let mut archive = tar::Archive::new(decoder);
+        archive
+            .unpack(&prefix_path)
+            .context("Failed to extract save archive")?;
+        Ok(())
+    }
+
+    /// Newest file modification time (unix seconds) across the save folders.
+    pub fn latest_modified(locations: &[SaveLocation]) -> Option<i64> {
+        locations
+            .iter()
+            .flat_map(|location| WalkDir::new(&location.path).into_iter().flatten())
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+            .filter_map(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs() as i64)
+            .max()
+    }
+
+    fn load_rules() -> Vec<SaveRule> {
+        let mut rules: Vec<SaveRule> = serde_json::from_str(BUILTIN_RULES).unwrap_or_default();
+        if let Ok(content) = fs::read_to_string(Self::rules_path()) {
+            match serde_json::from_str::<Vec<SaveRule>>(&content) {
+                Ok(user_rules) => rules.extend(user_rules),
+                Err(e) => eprintln!("Failed to parse save rules: {}", e),
+            }
+        }
+        rules
+    }
+
+    fn user_dirs(prefix_path: &Path) -> Vec<PathBuf> {
+        let users_dir = prefix_path.join("drive_c").join("users");
+        let mut dirs = Vec::new();
+        if let Ok(entries) = fs::read_dir(&users_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() && entry.file_name() != "Public" {
+                    dirs.push(path);
+                }
+            }
+        }
+        dirs
+    }
+
+    fn documents_dir(user_dir: &Path) -> PathBuf {
+        let documents = user_dir.join("Documents");
+        if documents.is_dir() {
+            documents
+        } else {
+            user_dir.join("My Documents")
+        }
+    }
+
+    fn search_roots(user_dir: &Path) -> Vec<PathBuf> {
+        let documents = Self::documents_dir(user_dir);
+        let appdata = user_dir.join("AppData");
+        let mut roots = vec![
+            documents.join("My Games"),
+            documents.clone(),
+            user_dir.join("Saved Games"),
+            appdata.join("Roaming"),
+            appdata.join("Local"),
+            appdata.join("LocalLow"),
+        ];
+        // LocalLow is usually <publisher>/<game>.
+        if let Ok(entries) = fs::read_dir(appdata.join("LocalLow")) {
+            for entry in entries.flatten() {
+                if entry.path().is_dir() {
+                    roots.push(entry.path());
+                }
+            }
+        }
+        roots
+    }
+
+    fn expand_rule_path(user_dir: &Path, pattern: &str) -> PathBuf {
+        let appdata = user_dir.join("AppData");
+        let expanded = pattern
+            .replace(
+                "%DOCUMENTS%",
+                &Self::documents_dir(user_dir).to_string_lossy(),
+            )
+            .replace("%APPDATA%", &appdata.join("Roaming").to_string_lossy())
+            .replace("%LOCALAPPDATA%", &appdata.join("Local").to_string_lossy())
+            .replace("%LOCALLOW%", &appdata.join("LocalLow").to_string_lossy())
+            .replace("%SAVEDGAMES%", &user_dir.join("Saved Games").to_string_lossy())
+            .replace("%USERPROFILE%", &user_dir.to_string_lossy());
+        PathBuf::from(expanded.replace('\\', "/"))
+    }
+
+    fn names_match(name_compact: &str, folder_compact: &str) -> bool {
+        if name_compact.is_empty() {
+            return false;
+        }
+        folder_compact == name_compact
+            || (folder_compact.len() >= 4 && name_compact.contains(folder_compact))
+            || (name_compact.len() >= 4 && folder_compact.contains(name_compact))
+    }
+
+    fn push_unique(locations: &mut Vec<SaveLocation>, path: PathBuf, source: SaveSource) {
+        if locations.iter().any(|existing| path.starts_with(&existing.path)) {
+            return;
+        }
+        locations.retain(|existing| !existing.path.starts_with(&path));
+        let size = file_ops::dir_size(&path);
+        locations.push(SaveLocation { path, source, size });
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::utils::host;
+
+/// gamescope's capture shortcut.
+pub const GAMESCOPE_SCREENSHOT_KEYS: &str = "Super+S";
+/// gamescope has no option for where captures go; it always writes
+/// `gamescope_<date>.png` here, on the host.
+const GAMESCOPE_SCREENSHOT_DIR: &str = "/tmp";
+const GAMESCOPE_SCREENSHOT_PATTERN: &str = "gamescope_*.png";
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "bmp", "webp"];
+
+pub struct Screenshots;
+
+impl Screenshots {
+    /// Move the captures gamescope took since `since` into `dest`. The
+    /// search and the move run on the host, where gamescope wrote them.
+    /// Returns how many were moved.
+    pub fn collect_gamescope(since: SystemTime, dest: &Path) -> Result<usize> {
+        let since = since
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let mut find = Command::new("find");
+        find.args([
+            GAMESCOPE_SCREENSHOT_DIR,
+            "-maxdepth",
+            "1",
+            "-type",
+            "f",
+            "-name",
+            GAMESCOPE_SCREENSHOT_PATTERN,
+            "-newermt",
+        ]);
+        find.arg(format!("@{}", since));
+        let output = host::command(find)
+            .output()
+            .context("Failed to look for gamescope screenshots")?;
+        let captures: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if captures.is_empty() {
+            return Ok(0);
+        }
+
+        fs::create_dir_all(dest).with_context(|| format!("Failed to create {:?}", dest))?;
+        let mut mv = Command::new("mv");
+        // Never overwrite an earlier capture with the same name.
+        mv.arg("-n").arg("-t").arg(dest).args(&captures);
+        let status = host::command(mv)
+            .status()
+            .context("Failed to move gamescope screenshots")?;
+        if !status.success() {
+            anyhow::bail!("mv exited with {}", status);
+        }
+        Ok(captures.len())
+    }
+
+    /// Number of images in `dir`, for labels.
+    pub fn count(dir: &Path) -> usize {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| {
+                        entry
+                            .path()
+                            .extension()
+                            .map(|ext| {
+                                IMAGE_EXTENSIONS
+                                    .iter()
+                                    .any(|image| ext.eq_ignore_ascii_case(image))
+                            })
+                            .unwrap_or(false)
+                    })
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+}
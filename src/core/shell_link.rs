@@ -0,0 +1,240 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+const HEADER_SIZE: usize = 0x4C;
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x0000_0001;
+const HAS_LINK_INFO: u32 = 0x0000_0002;
+const HAS_NAME: u32 = 0x0000_0004;
+const HAS_RELATIVE_PATH: u32 = 0x0000_0008;
+const HAS_WORKING_DIR: u32 = 0x0000_0010;
+const HAS_ARGUMENTS: u32 = 0x0000_0020;
+const HAS_ICON_LOCATION: u32 = 0x0000_0040;
+const IS_UNICODE: u32 = 0x0000_0080;
+
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x0000_0001;
+const ENVIRONMENT_BLOCK_SIGNATURE: u32 = 0xA000_0001;
+const ENVIRONMENT_BLOCK_SIZE: usize = 0x314;
+
+/// The fields of a Windows shell link (`.lnk`) needed to launch what it
+/// points at, read as described in [MS-SHLLINK].
+///
+/// [MS-SHLLINK]: https://learn.microsoft.com/openspecs/windows_protocols/ms-shllink
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShellLink {
+    /// Absolute Windows path of the target, from the link info or the
+    /// environment variable block.
+    pub target: Option<String>,
+    /// Target relative to the folder holding the link.
+    pub relative_path: Option<String>,
+    pub working_dir: Option<String>,
+    pub arguments: Option<String>,
+    /// File holding the icon, usually the program itself.
+    pub icon_location: Option<String>,
+}
+
+impl ShellLink {
+    pub fn read(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+        Self::parse(&bytes).with_context(|| format!("{:?} is not a valid shell link", path))
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.u32()? as usize != HEADER_SIZE || reader.take(16)? != LINK_CLSID {
+            anyhow::bail!("Missing shell link header");
+        }
+        let flags = reader.u32()?;
+        // File attributes, three timestamps and the file size.
+        reader.skip(4 + 8 * 3 + 4)?;
+        // Icon index, show command, hotkey and reserved fields.
+        reader.skip(4 + 4 + 2 + 2 + 4 + 4)?;
+
+        let mut link = ShellLink::default();
+
+        if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            let size = reader.u16()? as usize;
+            reader.skip(size)?;
+        }
+        if flags & HAS_LINK_INFO != 0 {
+            let start = reader.pos;
+            let size = reader.u32()? as usize;
+            reader.skip(size.checked_sub(4).context("Bad link info size")?)?;
+            link.target = Self::local_path(&bytes[start..start + size]);
+        }
+
+        let unicode = flags & IS_UNICODE != 0;
+        if flags & HAS_NAME != 0 {
+            reader.string_data(unicode)?;
+        }
+        if flags & HAS_RELATIVE_PATH != 0 {
+            link.relative_path = Some(reader.string_data(unicode)?);
+        }
+        if flags & HAS_WORKING_DIR != 0 {
+            link.working_dir = Some(reader.string_data(unicode)?);
+        }
+        if flags & HAS_ARGUMENTS != 0 {
+            link.arguments = Some(reader.string_data(unicode)?);
+        }
+        if flags & HAS_ICON_LOCATION != 0 {
+            link.icon_location = Some(reader.string_data(unicode)?);
+        }
+
+        if link.target.is_none() {
+            link.target = Self::environment_target(&mut reader);
+        }
+        Ok(link)
+    }
+
+    /// Whether the link opens a web page rather than a program.
+    pub fn mentions_url(&self) -> bool {
+        [&self.target, &self.relative_path, &self.arguments]
+            .into_iter()
+            .flatten()
+            .any(|value| {
+                let lowered = value.to_ascii_lowercase();
+                lowered.contains("http://") || lowered.contains("https://")
+            })
+    }
+
+    /// `LocalBasePath` followed by `CommonPathSuffix` from a LinkInfo
+    /// structure, preferring the Unicode copies when present.
+    fn local_path(info: &[u8]) -> Option<String> {
+        let field = |offset: usize| -> Option<usize> {
+            let bytes = info.get(offset..offset + 4)?;
+            Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+        };
+        let header_size = field(4)?;
+        let info_flags = field(8)? as u32;
+        if info_flags & VOLUME_ID_AND_LOCAL_BASE_PATH == 0 {
+            return None;
+        }
+        if header_size >= 0x24 {
+            let base = field(28).and_then(|offset| wide_string_at(info, offset));
+            let suffix = field(32).and_then(|offset| wide_string_at(info, offset));
+            if let Some(base) = base.filter(|base| !base.is_empty()) {
+                return Some(join_suffix(base, suffix.unwrap_or_default()));
+            }
+        }
+        let base = ansi_string_at(info, field(16)?)?;
+        let suffix = field(24)
+            .and_then(|offset| ansi_string_at(info, offset))
+            .unwrap_or_default();
+        Some(join_suffix(base, suffix)).filter(|path| !path.is_empty())
+    }
+
+    /// The target from an EnvironmentVariableDataBlock, with the variables
+    /// Wine prefixes define expanded.
+    fn environment_target(reader: &mut Reader) -> Option<String> {
+        loop {
+            let size = reader.u32().ok()? as usize;
+            if size < 8 {
+                return None;
+            }
+            let signature = reader.u32().ok()?;
+            let block = reader.take(size - 8).ok()?;
+            if signature != ENVIRONMENT_BLOCK_SIGNATURE || size != ENVIRONMENT_BLOCK_SIZE {
+                continue;
+            }
+            let target = wide_string_at(block, 260)
+                .filter(|value| !value.is_empty())
+                .or_else(|| ansi_string_at(block, 0))?;
+            return Some(expand_environment(&target));
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .context("Shell link is truncated")?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .context("Shell link is truncated")?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// A StringData entry: a character count followed by that many UTF-16
+    /// or codepage characters.
+    fn string_data(&mut self, unicode: bool) -> Result<String> {
+        let count = self.u16()? as usize;
+        if unicode {
+            Ok(decode_utf16(self.take(count * 2)?))
+        } else {
+            Ok(String::from_utf8_lossy(self.take(count)?).to_string())
+        }
+    }
+}
+
+fn decode_utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn ansi_string_at(bytes: &[u8], offset: usize) -> Option<String> {
+    let rest = bytes.get(offset..)?;
+    let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+    Some(String::from_utf8_lossy(&rest[..end]).to_string())
+}
+
+fn wide_string_at(bytes: &[u8], offset: usize) -> Option<String> {
+    bytes.get(offset..).map(decode_utf16)
+}
+
+fn join_suffix(base: String, suffix: String) -> String {
+    if suffix.is_empty() || base.ends_with('\\') {
+        base + &suffix
+    } else {
+        format!("{}\\{}", base, suffix)
+    }
+}
+
+/// Expand the folder variables installers use in shortcut targets.
+fn expand_environment(value: &str) -> String {
+    const VARIABLES: [(&str, &str); 6] = [
+        ("%programfiles(x86)%", r"C:\Program Files (x86)"),
+        ("%programfiles%", r"C:\Program Files"),
+        ("%programw6432%", r"C:\Program Files"),
+        ("%systemdrive%", "C:"),
+        ("%systemroot%", r"C:\windows"),
+        ("%windir%", r"C:\windows"),
+    ];
+    let lowered = value.to_ascii_lowercase();
+    for (name, expansion) in VARIABLES {
+        if lowered.starts_with(name) {
+            return format!("{}{}", expansion, &value[name.len()..]);
+        }
+    }
+    value.to_string()
+}
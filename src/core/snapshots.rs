@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::core::capsule::Capsule;
+
+const SNAPSHOT_EXTENSION: &str = "tar.zst";
+const AUTOMATIC_SNAPSHOTS_KEPT: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub label: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub automatic: bool,
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// Prefix snapshots for one capsule, stored as `.tar.zst` archives in
+/// `<name>.AppImage.home/snapshots/`. Game files under `prefix/games` and the
+/// game's install folder elsewhere in the prefix (e.g. under `drive_c`) are
+/// not included; restoring keeps the current ones.
+pub struct SnapshotManager {
+    prefix_path: PathBuf,
+    snapshots_dir: PathBuf,
+    /// Folders left out of snapshots, relative to the prefix.
+    game_dirs: Vec<PathBuf>,
+}
+
+impl SnapshotManager {
+    pub fn new(home_path: &Path) -> Self {
+        // The home sits in the capsule folder, whose metadata says where the
+        // game was installed.
+        let capsule = home_path
+            .parent()
+            .and_then(|capsule_dir| Capsule::load_from_dir(capsule_dir).ok());
+        Self::with_capsule(home_path, capsule.as_ref())
+    }
+
+    pub fn for_capsule(capsule: &Capsule) -> Self {
+        Self::with_capsule(&capsule.home_path, Some(capsule))
+    }
+
+    fn with_capsule(home_path: &Path, capsule: Option<&Capsule>) -> Self {
+        let prefix_path = home_path.join("prefix");
+        let mut game_dirs = vec![PathBuf::from("games")];
+        let install_root = capsule.and_then(Capsule::install_root).and_then(|root| {
+            let real_prefix = fs::canonicalize(&prefix_path).ok()?;
+            root.strip_prefix(real_prefix).ok().map(Path::to_path_buf)
+        });
+        game_dirs.extend(install_root.filter(|root| !root.starts_with("games")));
+        Self {
+            prefix_path,
+            snapshots_dir: home_path.join("snapshots"),
+            game_dirs,
+        }
+    }
+
+    /// List snapshots, newest first.
+    pub fn list(&self) -> Result<Vec<SnapshotInfo>> {
+        let mut snapshots = Vec::new();
+        if !self.snapshots_dir.is_dir() {
+            return Ok(snapshots);
+        }
+
+        for entry in fs::read_dir(&self.snapshots_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            if let Ok(info) = serde_json::from_str::<SnapshotInfo>(&content) {
+                if self.archive_path(&info.id).is_file() {
+                    snapshots.push(info);
+                }
+            }
+        }
+
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(snapshots)
+    }
+
+    /// The newest automatic snapshot taken after `since` (Unix seconds):
+    /// the state to go back to when a change made since then broke the
+    /// game.
+    pub fn latest_automatic_since(&self, since: u64) -> Option<SnapshotInfo> {
+        self.list().ok()?.into_iter().find(|info| {
+            info.automatic
+                && chrono::DateTime::parse_from_rfc3339(&info.created_at)
+                    .map(|created| created.timestamp() >= since as i64)
+                    .unwrap_or(false)
+        })
+    }
+
+    /// Archive the prefix (minus the game's files) under a new snapshot id.
+    pub fn create(&self, label: &str, automatic: bool) -> Result<SnapshotInfo> {
+        if !self.prefix_path.is_dir() {
+            anyhow::bail!("Prefix not found at {:?}", self.prefix_path);
+        }
+        fs::create_dir_all(&self.snapshots_dir)
+            .with_context(|| format!("Failed to create {:?}", self.snapshots_dir))?;
+
+        let now = chrono::Local::now();
+        let mut id = now.format("%Y%m%d-%H%M%S").to_string();
+        if self.archive_path(&id).exists() {
+            id = format!("{}-{}", id, &uuid::Uuid::new_v4().simple().to_string()[..6]);
+        }
+
+        let archive_path = self.archive_path(&id);
+        let partial_path = archive_path.with_extension("zst.part");
+        if let Err(e) = self.write_archive(&partial_path) {
+            let _ = fs::remove_file(&partial_path);
+            return Err(e);
+        }
+        fs::rename(&partial_path, &archive_path)
+            .context("Failed to finalize snapshot archive")?;
+
+        let info = SnapshotInfo {
+            id: id.clone(),
+            label: label.trim().to_string(),
+            created_at: now.to_rfc3339(),
+            automatic,
+            size: archive_path.metadata().map(|meta| meta.len()).unwrap_or(0),
+        };
+        let content = serde_json::to_string_pretty(&info)
+            .context("Failed to serialize snapshot info")?;
+        fs::write(self.info_path(&id), content).context("Failed to write snapshot info")?;
+
+        if automatic {
+            self.prune_automatic(AUTOMATIC_SNAPSHOTS_KEPT)?;
+        }
+
+        println!("Created snapshot {} for {:?}", id, self.prefix_path);
+        Ok(info)
+    }
+
+    /// Replace the prefix with the contents of a snapshot. The current game
+    /// folders are carried over; on failure the original prefix is put back.
+    pub fn restore(&self, id: &str) -> Result<()> {
+        let archive_path = self.archive_path(id);
+        if !archive_path.is_file() {
+            anyhow::bail!("Snapshot {} not found", id);
+        }
+
+        let parent = self
+            .prefix_path
+            .parent()
+            .context("Prefix has no parent directory")?;
+        let staging = parent.join("prefix.restore");
+        let previous = parent.join("prefix.previous");
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        if previous.exists() {
+            fs::remove_dir_all(&previous)?;
+        }
+
+        let file = File::open(&archive_path)
+            .with_context(|| format!("Failed to open {:?}", archive_path))?;
+        let decoder = zstd::stream::read::Decoder::new(file)?;
+        let mut archive = tar::Archive::new(decoder);
+        archive.set_preserve_permissions(true);
+        if let Err(e) = archive.unpack(&staging) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e).context("Failed to extract snapshot");
+        }
+
+        let mut carried = Vec::new();
+        for game_dir in &self.game_dirs {
+            let current = self.prefix_path.join(game_dir);
+            if !current.exists() {
+                continue;
+            }
+            // Older snapshots may hold a stale copy of the game.
+            let restored = staging.join(game_dir);
+            let moved = if restored.exists() {
+                fs::remove_dir_all(&restored)
+            } else {
+                Ok(())
+            }
+            .and_then(|_| match restored.parent() {
+                Some(parent) => fs::create_dir_all(parent),
+                None => Ok(()),
+            })
+            .and_then(|_| fs::rename(&current, &restored));
+            if let Err(e) = moved {
+                self.carry_back(&staging, &carried);
+                let _ = fs::remove_dir_all(&staging);
+                return Err(e).context("Failed to carry game files over");
+            }
+            carried.push(game_dir.clone());
+        }
+
+        if self.prefix_path.exists() {
+            if let Err(e) = fs::rename(&self.prefix_path, &previous) {
+                self.carry_back(&staging, &carried);
+                let _ = fs::remove_dir_all(&staging);
+                return Err(e).context("Failed to move the current prefix aside");
+            }
+        }
+
+        if let Err(e) = fs::rename(&staging, &self.prefix_path) {
+            let _ = fs::rename(&previous, &self.prefix_path);
+            self.carry_back(&staging, &carried);
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e).context("Failed to activate the restored prefix");
+        }
+
+        let _ = fs::remove_dir_all(&previous);
+        println!("Restored snapshot {} into {:?}", id, self.prefix_path);
+        Ok(())
+    }
+
+    /// Undo carrying game folders into `staging` after a failed restore.
+    fn carry_back(&self, staging: &Path, carried: &[PathBuf]) {
+        for game_dir in carried {
+            let _ = fs::rename(staging.join(game_dir), self.prefix_path.join(game_dir));
+        }
+    }
+
+    pub fn delete(&self, id: &str) -> Result<()> {
+        let archive_path = self.archive_path(id);
+        if archive_path.exists() {
+            fs::remove_file(&archive_path)
+                .with_context(|| format!("Failed to remove {:?}", archive_path))?;
+        }
+        let info_path = self.info_path(id);
+        if info_path.exists() {
+            fs::remove_file(&info_path)
+                .with_context(|| format!("Failed to remove {:?}", info_path))?;
+        }
+        Ok(())
+    }
+
+    fn prune_automatic(&self, keep: usize) -> Result<()> {
+        let automatic: Vec<SnapshotInfo> = self
+            .list()?
+            .into_iter()
+            .filter(|info| info.automatic)
+            .collect();
+        for info in automatic.iter().skip(keep) {
+            self.delete(&info.id)?;
+        }
+        Ok(())
+    }
+
+    fn write_archive(&self, dest: &Path) -> Result<()> {
+        let file = File::create(dest).with_context(|| format!("Failed to create {:?}", dest))?;
+        let encoder = zstd::stream::write::Encoder::new(file, 3)?;
+        let mut builder = tar::Builder::new(encoder);
+        builder.follow_symlinks(false);
+
+        let skipped: Vec<PathBuf> = self
+            .game_dirs
+            .iter()
+            .map(|dir| self.prefix_path.join(dir))
+            .collect();
+        let walker = WalkDir::new(&self.prefix_path)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| !skipped.iter().any(|dir| entry.path() == dir));
+        for entry in walker {
+            let entry = entry?;
+            let relative = entry
+                .path()
+                .strip_prefix(&self.prefix_path)
+                .unwrap_or(entry.path());
+            if relative.as_os_str().is_empty() {
+                continue;
+            }
+            builder
+                .append_path_with_name(entry.path(), relative)
+                .with_context(|| format!("Failed to archive {:?}", entry.path()))?;
+        }
+
+        let encoder = builder.into_inner()?;
+        encoder.finish().context("Failed to finish snapshot archive")?;
+        Ok(())
+    }
+
+    fn archive_path(&self, id: &str) -> PathBuf {
+        self.snapshots_dir.join(format!("{}.{}", id, SNAPSHOT_EXTENSION))
+    }
+
+    fn info_path(&self, id: &str) -> PathBuf {
+        self.snapshots_dir.join(format!("{}.json", id))
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::core::system_checker::SystemCheck;
+use crate::core::umu_installer::UmuInstallMethod;
+
+const DMI_DIR: &str = "/sys/devices/virtual/dmi/id";
+const MOUNTS_PATH: &str = "/proc/mounts";
+/// SteamOS mounts removable media here.
+const MEDIA_DIR: &str = "/run/media/";
+/// The Deck's panel; gamescope runs games at this size in Game Mode.
+pub const DECK_RESOLUTION: &str = "1280x800";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeckModel {
+    /// "Jupiter" in the firmware.
+    Lcd,
+    /// "Galileo" in the firmware.
+    Oled,
+}
+
+/// Steam Deck hardware and SteamOS, as far as they matter to LinuxBoy.
+#[derive(Debug, Clone, Default)]
+pub struct SteamDeck {
+    pub model: Option<DeckModel>,
+    pub steamos: bool,
+    /// Running inside Steam's Game Mode session rather than the desktop.
+    pub game_mode: bool,
+}
+
+impl SteamDeck {
+    pub fn detect() -> Self {
+        let dmi = |name: &str| {
+            fs::read_to_string(format!("{}/{}", DMI_DIR, name))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        let model = if dmi("board_vendor") == "Valve" || dmi("sys_vendor") == "Valve" {
+            match dmi("product_name").as_str() {
+                "Jupiter" => Some(DeckModel::Lcd),
+                "Galileo" => Some(DeckModel::Oled),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+        Self {
+            model,
+            steamos: UmuInstallMethod::os_release_id().as_deref() == Some("steamos"),
+            game_mode: desktop.eq_ignore_ascii_case("gamescope")
+                || env::var("SteamGamepadUI").is_ok_and(|value| value == "1"),
+        }
+    }
+
+    /// A Deck, or another device running SteamOS.
+    pub fn detected(&self) -> bool {
+        self.model.is_some() || self.steamos
+    }
+
+    pub fn label(&self) -> String {
+        let device = match self.model {
+            Some(DeckModel::Lcd) => "Steam Deck (LCD)",
+            Some(DeckModel::Oled) => "Steam Deck (OLED)",
+            None if self.steamos => "SteamOS device",
+            None => return "Not a Steam Deck".to_string(),
+        };
+        if self.game_mode {
+            format!("{}, Game Mode", device)
+        } else {
+            device.to_string()
+        }
+    }
+
+    /// Where the microSD card is mounted, if one is in.
+    pub fn sd_card_mount() -> Option<PathBuf> {
+        let mounts = fs::read_to_string(MOUNTS_PATH).ok()?;
+        mounts.lines().find_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            // Spaces in mount points are written as \040.
+            let mount = fields.next()?.replace("\\040", " ");
+            (device.starts_with("/dev/mmcblk") && mount.starts_with(MEDIA_DIR))
+                .then(|| PathBuf::from(mount))
+        })
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Steam Deck preferences, stored in `~/.linuxboy/steam_deck.json`. They
+/// only take effect on a Deck or SteamOS device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeckSettings {
+    /// Deck-sized virtual desktop and similar defaults for new games.
+    #[serde(default = "default_true")]
+    pub tailored_defaults: bool,
+    /// Keep the library in `Games` on the microSD card.
+    #[serde(default)]
+    pub sd_card_library: bool,
+    /// Offer to add a game to Steam once it is installed.
+    #[serde(default = "default_true")]
+    pub prompt_add_to_steam: bool,
+}
+
+impl Default for DeckSettings {
+    fn default() -> Self {
+        Self {
+            tailored_defaults: true,
+            sd_card_library: false,
+            prompt_add_to_steam: true,
+        }
+    }
+}
+
+impl DeckSettings {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("steam_deck.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize Steam Deck settings")?;
+        fs::write(&path, content).context("Failed to write Steam Deck settings")?;
+        Ok(())
+    }
+
+    /// The library folder on the microSD card, when that is wanted and a
+    /// card is in.
+    pub fn sd_card_games_dir(&self, deck: &SteamDeck) -> Option<PathBuf> {
+        if !deck.detected() || !self.sd_card_library {
+            return None;
+        }
+        SteamDeck::sd_card_mount().map(|mount| mount.join("Games"))
+    }
+
+    /// Virtual desktop size new games start with.
+    pub fn default_desktop_size(&self, deck: &SteamDeck) -> Option<&'static str> {
+        (deck.detected() && self.tailored_defaults).then_some(DECK_RESOLUTION)
+    }
+}
@@ -0,0 +1,267 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::utils::host;
+
+// Binary KeyValues as Steam writes shortcuts.vdf.
+const TYPE_MAP: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT: u8 = 0x02;
+const TYPE_END: u8 = 0x08;
+
+/// Steam installs, native first, then the Flatpak.
+const STEAM_ROOTS: [&str; 3] = [
+    ".steam/steam",
+    ".local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Map(Vec<(String, Value)>),
+    String(String),
+    Int(u32),
+}
+
+/// A non-Steam game entry in the Steam library.
+#[derive(Debug, Clone)]
+pub struct SteamShortcut {
+    pub app_name: String,
+    pub exe: String,
+    pub start_dir: String,
+    pub launch_options: String,
+}
+
+impl SteamShortcut {
+    /// The id Steam derives for a shortcut, used for its artwork and
+    /// `steam://rungameid` links.
+    pub fn app_id(&self) -> u32 {
+        crc32(format!("{}{}", self.exe, self.app_name).as_bytes()) | 0x8000_0000
+    }
+
+    fn to_value(&self) -> Value {
+        let string = |key: &str, value: &str| (key.to_string(), Value::String(value.to_string()));
+        let int = |key: &str, value: u32| (key.to_string(), Value::Int(value));
+        Value::Map(vec![
+            int("appid", self.app_id()),
+            string("AppName", &self.app_name),
+            string("Exe", &self.exe),
+            string("StartDir", &self.start_dir),
+            string("icon", ""),
+            string("ShortcutPath", ""),
+            string("LaunchOptions", &self.launch_options),
+            int("IsHidden", 0),
+            int("AllowDesktopConfig", 1),
+            int("AllowOverlay", 1),
+            int("OpenVR", 0),
+            int("Devkit", 0),
+            string("DevkitGameID", ""),
+            int("DevkitOverrideAppID", 0),
+            int("LastPlayTime", 0),
+            string("FlatpakAppID", ""),
+            ("tags".to_string(), Value::Map(Vec::new())),
+        ])
+    }
+}
+
+/// Reads and extends the current Steam user's `shortcuts.vdf`.
+pub struct SteamShortcuts;
+
+impl SteamShortcuts {
+    /// `shortcuts.vdf` of the most recently active Steam account.
+    pub fn shortcuts_file() -> Option<PathBuf> {
+        let home = dirs::home_dir()?;
+        STEAM_ROOTS.iter().find_map(|root| {
+            let userdata = home.join(root).join("userdata");
+            fs::read_dir(&userdata)
+                .ok()?
+                .flatten()
+                .filter(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    // "0" holds anonymous data, not an account.
+                    name != "0" && name.chars().all(|ch| ch.is_ascii_digit())
+                })
+                .filter_map(|entry| {
+                    let config = entry.path().join("config");
+                    let modified = fs::metadata(&config)
+                        .and_then(|meta| meta.modified())
+                        .ok()?;
+                    Some((modified, config.join("shortcuts.vdf")))
+                })
+                .max_by_key(|(modified, _)| *modified)
+                .map(|(_, path)| path)
+        })
+    }
+
+    /// Steam rewrites shortcuts.vdf when it exits, dropping entries added
+    /// while it ran.
+    pub fn steam_running() -> bool {
+        let mut pgrep = Command::new("pgrep");
+        pgrep.args(["-x", "steam"]);
+        host::command(pgrep)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    /// Add `shortcut` unless an entry with the same name and command is
+    /// already there. Returns whether it was added.
+    pub fn add(shortcut: &SteamShortcut) -> Result<bool> {
+        let path = Self::shortcuts_file().context("No Steam account found on this machine")?;
+        let mut root = if path.is_file() {
+            let bytes = fs::read(&path).with_context(|| format!("Failed to read {:?}", path))?;
+            Self::parse(&bytes)
+                .with_context(|| format!("{:?} is not a valid shortcuts file", path))?
+        } else {
+            Vec::new()
+        };
+        if !root
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("shortcuts"))
+        {
+            root.push(("shortcuts".to_string(), Value::Map(Vec::new())));
+        }
+        let entries = root
+            .iter_mut()
+            .find(|(key, _)| key.eq_ignore_ascii_case("shortcuts"))
+            .and_then(|(_, value)| match value {
+                Value::Map(entries) => Some(entries),
+                _ => None,
+            })
+            .context("Unexpected shortcuts file layout")?;
+
+        let exists = entries.iter().any(|(_, entry)| {
+            let field = |name: &str| match entry {
+                Value::Map(fields) => fields.iter().find_map(|(key, value)| match value {
+                    Value::String(value) if key.eq_ignore_ascii_case(name) => Some(value.as_str()),
+                    _ => None,
+                }),
+                _ => None,
+            };
+            field("AppName") == Some(shortcut.app_name.as_str())
+                && field("Exe") == Some(shortcut.exe.as_str())
+                && field("LaunchOptions") == Some(shortcut.launch_options.as_str())
+        });
+        if exists {
+            return Ok(false);
+        }
+        let index = entries
+            .iter()
+            .filter_map(|(key, _)| key.parse::<usize>().ok())
+            .max()
+            .map(|last| last + 1)
+            .unwrap_or(0);
+        entries.push((index.to_string(), shortcut.to_value()));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut bytes = Vec::new();
+        Self::write_map(&mut bytes, &root);
+        let temp = path.with_extension("vdf.linuxboy");
+        fs::write(&temp, bytes).with_context(|| format!("Failed to write {:?}", temp))?;
+        fs::rename(&temp, &path).with_context(|| format!("Failed to replace {:?}", path))?;
+        println!("Added {} to Steam in {:?}", shortcut.app_name, path);
+        Ok(true)
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Vec<(String, Value)>> {
+        Self::read_map(bytes, &mut 0)
+    }
+
+    fn read_map(bytes: &[u8], pos: &mut usize) -> Result<Vec<(String, Value)>> {
+        let mut entries = Vec::new();
+        loop {
+            let kind = *bytes.get(*pos).context("Shortcuts file is truncated")?;
+            *pos += 1;
+            if kind == TYPE_END {
+                return Ok(entries);
+            }
+            let key = read_cstring(bytes, pos)?;
+            let value = match kind {
+                TYPE_MAP => Value::Map(Self::read_map(bytes, pos)?),
+                TYPE_STRING => Value::String(read_cstring(bytes, pos)?),
+                TYPE_INT => {
+                    let raw = bytes
+                        .get(*pos..*pos + 4)
+                        .context("Shortcuts file is truncated")?;
+                    *pos += 4;
+                    Value::Int(u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]))
+                }
+                other => anyhow::bail!("Unsupported value type {:#04x} for {}", other, key),
+            };
+            entries.push((key, value));
+        }
+    }
+
+    fn write_map(out: &mut Vec<u8>, entries: &[(String, Value)]) {
+        for (key, value) in entries {
+            let kind = match value {
+                Value::Map(_) => TYPE_MAP,
+                Value::String(_) => TYPE_STRING,
+                Value::Int(_) => TYPE_INT,
+            };
+            out.push(kind);
+            out.extend_from_slice(key.as_bytes());
+            out.push(0);
+            match value {
+                Value::Map(entries) => Self::write_map(out, entries),
+                Value::String(text) => {
+                    out.extend_from_slice(text.as_bytes());
+                    out.push(0);
+                }
+                Value::Int(number) => out.extend_from_slice(&number.to_le_bytes()),
+            }
+        }
+        out.push(TYPE_END);
+    }
+
+    /// A shortcut that starts `capsule_dir` through this LinuxBoy.
+    pub fn linuxboy_shortcut(name: &str, capsule_dir: &Path) -> Result<SteamShortcut> {
+        let launch = format!("--launch {}", quote(&capsule_dir.to_string_lossy()));
+        let (exe, launch_options) = if host::is_flatpak() {
+            (quote("flatpak"), format!("run com.linuxboy.app {}", launch))
+        } else {
+            let exe = std::env::current_exe().context("Failed to find the LinuxBoy executable")?;
+            (quote(&exe.to_string_lossy()), launch)
+        };
+        Ok(SteamShortcut {
+            app_name: name.to_string(),
+            exe,
+            start_dir: quote(&capsule_dir.to_string_lossy()),
+            launch_options,
+        })
+    }
+}
+
+fn read_cstring(bytes: &[u8], pos: &mut usize) -> Result<String> {
+    let rest = bytes.get(*pos..).context("Shortcuts file is truncated")?;
+    let end = rest
+        .iter()
+        .position(|&byte| byte == 0)
+        .context("Shortcuts file is truncated")?;
+    *pos += end + 1;
+    Ok(String::from_utf8_lossy(&rest[..end]).to_string())
+}
+
+/// Steam splits Exe and StartDir on spaces unless they are quoted.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value)
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::path::Path;
+use std::process::Command;
+
+use crate::core::capsule::Capsule;
+use crate::core::diagnostics::{PrefixDoctor, Severity};
+use crate::core::system_checker::SystemCheck;
+use crate::utils::host;
+
+/// Only the end of the launch log is kept; that's where crashes show up.
+const LAUNCH_LOG_LIMIT: usize = 512 * 1024;
+/// Env var names containing any of these have their values redacted.
+const SECRET_MARKERS: [&str; 5] = ["TOKEN", "KEY", "SECRET", "PASSWORD", "AUTH"];
+
+/// A `.tar.gz` with what's needed to look into a problem with one capsule:
+/// sanitized metadata, the last launch log, prefix doctor findings, the
+/// system check and GPU, umu and Proton versions.
+pub struct SupportBundle;
+
+impl SupportBundle {
+    pub fn default_file_name(capsule: &Capsule) -> String {
+        format!(
+            "linuxboy-support-{}-{}.tar.gz",
+            capsule.name,
+            chrono::Local::now().format("%Y%m%d-%H%M")
+        )
+    }
+
+    pub fn write(
+        capsule: &Capsule,
+        system: &SystemCheck,
+        runtimes: &[String],
+        dest: &Path,
+    ) -> Result<()> {
+        let file = File::create(dest).with_context(|| format!("Failed to create {:?}", dest))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let root = dest
+            .file_name()
+            .map(|name| {
+                name.to_string_lossy()
+                    .trim_end_matches(".tar.gz")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "linuxboy-support".to_string());
+
+        let mut files = vec![
+            ("metadata.json", Self::sanitized_metadata(capsule)?),
+            ("diagnostics.txt", Self::diagnostics(capsule, system)),
+            ("system.txt", Self::system_report(system)),
+            ("gpu.txt", Self::gpu_report()),
+            ("versions.txt", Self::versions(capsule, runtimes)),
+        ];
+        if let Some(log) = Self::launch_log(capsule) {
+            files.push(("last_launch.log", log));
+        }
+
+        for (name, content) in files {
+            let content = Self::redact_home(&content);
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(chrono::Local::now().timestamp().max(0) as u64);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("{}/{}", root, name),
+                    content.as_bytes(),
+                )
+                .with_context(|| format!("Failed to add {} to the bundle", name))?;
+        }
+
+        builder
+            .into_inner()
+            .context("Failed to finish support bundle")?
+            .finish()
+            .context("Failed to finish support bundle")?;
+        Ok(())
+    }
+
+    fn sanitized_metadata(capsule: &Capsule) -> Result<String> {
+        let mut metadata = capsule.metadata.clone();
+        for (key, value) in metadata.env_vars.iter_mut() {
+            let upper = key.to_ascii_uppercase();
+            if SECRET_MARKERS.iter().any(|marker| upper.contains(marker)) {
+                *value = "<redacted>".to_string();
+            }
+        }
+        serde_json::to_string_pretty(&metadata).context("Failed to serialize metadata")
+    }
+
+    fn diagnostics(capsule: &Capsule, system: &SystemCheck) -> String {
+        let findings = PrefixDoctor::examine(capsule, system);
+        if findings.is_empty() {
+            return "No problems found.\n".to_string();
+        }
+        findings
+            .iter()
+            .map(|finding| {
+                let severity = match finding.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                format!("[{}] {}\n{}\n\n", severity, finding.summary, finding.detail)
+            })
+            .collect()
+    }
+
+    fn system_report(system: &SystemCheck) -> String {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        let mut report = format!(
+            "Status: {:?}\nVulkan tools: {}\nMesa drivers: {}\nProton-GE: {}\nUMU Launcher: {}\n\
+             VCRedist cache: {}\nDirectX redist cache: {}\nGameMode: {}\nMangoHud: {}\nDisplay session: {}\n",
+            system.status,
+            yes_no(system.vulkan_installed),
+            yes_no(system.mesa_installed),
+            yes_no(system.proton_installed),
+            yes_no(system.umu_installed),
+            yes_no(system.vcredist_cached),
+            yes_no(system.dxweb_cached),
+            yes_no(system.gamemode_installed),
+            yes_no(system.mangohud_installed),
+            system.display_session.label(),
+        );
+        report.push_str(&format!(
+            "gamescope: {}\nHDR display: {}\nVRR display: {}\n",
+            yes_no(system.gamescope.installed),
+            yes_no(system.gamescope.hdr_display),
+            yes_no(system.gamescope.vrr_display),
+        ));
+        report.push_str(&format!(
+            "futex_waitv: {}\nvm.max_map_count: {}\n",
+            yes_no(system.kernel.futex_waitv),
+            system
+                .kernel
+                .max_map_count
+                .map(|count| count.to_string())
+                .unwrap_or_else(|| "unknown".to_string())
+        ));
+        report.push_str(&format!(
+            "Package manager: {}\n",
+            system
+                .package_manager
+                .map(|manager| manager.name())
+                .unwrap_or("unknown")
+        ));
+        if !system.missing_packages.is_empty() {
+            report.push_str(&format!(
+                "Missing packages: {}\n",
+                system.missing_packages.join(" ")
+            ));
+        }
+        report.push_str(&format!(
+            "\n$ uname -a\n{}",
+            Self::command_output("uname", &["-a"])
+        ));
+        if let Ok(os_release) = fs::read_to_string(host::host_file("/etc/os-release")) {
+            report.push_str(&format!("\n/etc/os-release\n{}", os_release));
+        }
+        report
+    }
+
+    fn gpu_report() -> String {
+        let lspci = Self::command_output("lspci", &["-k"]);
+        // Keep only display controllers and the driver lines that follow them.
+        let mut gpus = String::new();
+        let mut in_gpu = false;
+        for line in lspci.lines() {
+            if !line.starts_with(char::is_whitespace) {
+                in_gpu = line.contains("VGA") || line.contains("3D") || line.contains("Display");
+            }
+            if in_gpu {
+                gpus.push_str(line);
+                gpus.push('\n');
+            }
+        }
+
+        format!(
+            "$ lspci -k (display devices)\n{}\n$ vulkaninfo --summary\n{}\n$ glxinfo -B\n{}",
+            gpus,
+            Self::command_output("vulkaninfo", &["--summary"]),
+            Self::command_output("glxinfo", &["-B"])
+        )
+    }
+
+    fn versions(capsule: &Capsule, runtimes: &[String]) -> String {
+        format!(
+            "LinuxBoy: {}\numu-run: {}\nCapsule runtime: {}\nInstalled runtimes: {}\n",
+            env!("CARGO_PKG_VERSION"),
+            Self::command_output("umu-run", &["--version"]).trim(),
+            capsule
+                .metadata
+                .wine_version
+                .as_deref()
+                .unwrap_or("unknown"),
+            if runtimes.is_empty() {
+                "none".to_string()
+            } else {
+                runtimes.join(", ")
+            }
+        )
+    }
+
+    fn launch_log(capsule: &Capsule) -> Option<String> {
+        let content = fs::read(capsule.launch_log_path()).ok()?;
+        let start = content.len().saturating_sub(LAUNCH_LOG_LIMIT);
+        Some(String::from_utf8_lossy(&content[start..]).to_string())
+    }
+
+    fn command_output(program: &str, args: &[&str]) -> String {
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        match host::command(cmd).output() {
+            Ok(output) => {
+                let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+                text.push_str(&String::from_utf8_lossy(&output.stderr));
+                text
+            }
+            Err(e) => format!("(not available: {})\n", e),
+        }
+    }
+
+    /// Replace the home folder, which usually contains the user name, with `~`.
+    fn redact_home(content: &str) -> String {
+        match dirs::home_dir() {
+            Some(home) => content.replace(home.to_string_lossy().as_ref(), "~"),
+            None => content.to_string(),
+        }
+    }
+}
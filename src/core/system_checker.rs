@@ -1,6 +1,12 @@
 use std::path::PathBuf;
 use std::process::Command;
 
+use crate::core::display_server::DisplaySession;
+use crate::core::gamescope::GamescopeSupport;
+use crate::core::kernel::KernelCapabilities;
+use crate::core::package_manager::{GamingTool, PackageManager, ENABLE_I386_SCRIPT};
+use crate::utils::host;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SystemStatus {
     AllInstalled,     // Everything ready (green)
@@ -17,7 +23,20 @@ pub struct SystemCheck {
     pub umu_installed: bool,
     pub vcredist_cached: bool,
     pub dxweb_cached: bool,
-    pub missing_apt_packages: Vec<String>,
+    pub package_manager: Option<PackageManager>,
+    pub missing_packages: Vec<String>,
+    /// Repository setup needed before the missing packages can install.
+    pub package_warning: Option<String>,
+    /// apt only: i386 multiarch must be enabled before the :i386 packages.
+    pub needs_i386: bool,
+    /// Optional; these don't affect the overall status.
+    pub gamemode_installed: bool,
+    pub mangohud_installed: bool,
+    pub gamescope: GamescopeSupport,
+    /// bubblewrap, for sandboxed launches.
+    pub bwrap_installed: bool,
+    pub display_session: DisplaySession,
+    pub kernel: KernelCapabilities,
 }
 
 impl SystemCheck {
@@ -29,31 +48,41 @@ impl SystemCheck {
         let umu_installed = Self::check_command("umu-run");
         let vcredist_cached = Self::vcredist_cache_path().is_file();
         let dxweb_cached = Self::dxweb_cache_path().is_file();
+        let gamemode_installed = Self::check_command(GamingTool::GameMode.command());
+        let mangohud_installed = Self::check_command(GamingTool::MangoHud.command());
+        let gamescope = GamescopeSupport::detect();
+        let bwrap_installed = Self::check_command("bwrap");
+        let display_session = DisplaySession::detect();
+        let kernel = KernelCapabilities::detect();
 
-        let mut missing_apt_packages = Vec::new();
+        let package_manager = PackageManager::detect();
+        // Without a known package manager, list the Debian names as before.
+        let package_names = package_manager.unwrap_or(PackageManager::Apt);
+        let package_warning = package_manager.and_then(|manager| manager.repository_warning());
+        let needs_i386 = package_manager
+            .map(|manager| !manager.i386_enabled())
+            .unwrap_or(false);
+        let mut missing_packages = Vec::new();
         
         if !vulkan_installed {
-            missing_apt_packages.push("vulkan-tools".to_string());
-            missing_apt_packages.push("libvulkan1".to_string());
-            missing_apt_packages.push("libvulkan1:i386".to_string());
+            missing_packages.extend(
+                package_names.vulkan_packages().iter().map(|name| name.to_string()),
+            );
         }
         
         if !mesa_installed {
-            missing_apt_packages.push("mesa-vulkan-drivers".to_string());
-            missing_apt_packages.push("mesa-vulkan-drivers:i386".to_string());
-            missing_apt_packages.push("libgl1-mesa-dri:amd64".to_string());
-            missing_apt_packages.push("libgl1-mesa-dri:i386".to_string());
-            missing_apt_packages.push("libglx-mesa0:amd64".to_string());
-            missing_apt_packages.push("libglx-mesa0:i386".to_string());
+            missing_packages.extend(
+                package_names.mesa_packages().iter().map(|name| name.to_string()),
+            );
         }
 
         // Determine overall status
-        let apt_ok = vulkan_installed && mesa_installed;
+        let packages_ok = vulkan_installed && mesa_installed;
         let runtimes_ok = proton_installed && umu_installed;
 
-        let status = if apt_ok && runtimes_ok {
+        let status = if packages_ok && runtimes_ok {
             SystemStatus::AllInstalled
-        } else if apt_ok || runtimes_ok {
+        } else if packages_ok || runtimes_ok {
             SystemStatus::PartiallyInstalled
         } else {
             SystemStatus::NothingInstalled
@@ -78,10 +107,51 @@ impl SystemCheck {
             "  DirectX redist cache: {}",
             if dxweb_cached { "downloaded" } else { "missing" }
         );
-        if missing_apt_packages.is_empty() {
-            println!("  Missing apt packages: none");
+        println!(
+            "  GameMode: {}",
+            if gamemode_installed { "installed" } else { "missing" }
+        );
+        println!(
+            "  MangoHud: {}",
+            if mangohud_installed { "installed" } else { "missing" }
+        );
+        println!(
+            "  gamescope: {} (HDR display {}, VRR display {})",
+            if gamescope.installed { "installed" } else { "missing" },
+            if gamescope.hdr_display { "yes" } else { "no" },
+            if gamescope.vrr_display { "yes" } else { "no" }
+        );
+        println!(
+            "  bubblewrap: {}",
+            if bwrap_installed { "installed" } else { "missing" }
+        );
+        println!("  Display session: {}", display_session.label());
+        println!(
+            "  Kernel: {} (futex_waitv {})",
+            kernel.release.as_deref().unwrap_or("unknown"),
+            if kernel.futex_waitv { "available" } else { "missing" }
+        );
+        println!(
+            "  vm.max_map_count: {}",
+            kernel
+                .max_map_count
+                .map(|count| count.to_string())
+                .unwrap_or_else(|| "unknown".to_string())
+        );
+        println!(
+            "  Package manager: {}",
+            package_manager.map(|manager| manager.name()).unwrap_or("unknown")
+        );
+        if missing_packages.is_empty() {
+            println!("  Missing packages: none");
         } else {
-            println!("  Missing apt packages: {}", missing_apt_packages.join(" "));
+            println!("  Missing packages: {}", missing_packages.join(" "));
+        }
+        if needs_i386 {
+            println!("  i386 multiarch: not enabled");
+        }
+        if let Some(warning) = &package_warning {
+            println!("  Package warning: {}", warning);
         }
         println!("  Overall status: {:?}", status);
 
@@ -93,15 +163,22 @@ impl SystemCheck {
             umu_installed,
             vcredist_cached,
             dxweb_cached,
-            missing_apt_packages,
+            package_manager,
+            missing_packages,
+            package_warning,
+            needs_i386,
+            gamemode_installed,
+            mangohud_installed,
+            gamescope,
+            bwrap_installed,
+            display_session,
+            kernel,
         }
     }
 
     /// Get the LinuxBoy config directory
     pub fn get_linuxboy_dir() -> PathBuf {
-        dirs::home_dir()
-            .unwrap_or_default()
-            .join(".linuxboy")
+        host::writable_dir(dirs::home_dir().unwrap_or_default().join(".linuxboy"))
     }
 
     /// Get runtimes directory
@@ -129,17 +206,15 @@ impl SystemCheck {
 
     /// Check if a command exists in PATH
     fn check_command(cmd: &str) -> bool {
-        Command::new("which")
-            .arg(cmd)
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+        host::has_command(cmd)
     }
 
     /// Check if Mesa drivers are installed
     fn check_mesa() -> bool {
         // Check if mesa is installed by looking for vulkaninfo output
-        if let Ok(output) = Command::new("vulkaninfo").arg("--summary").output() {
+        let mut vulkaninfo = Command::new("vulkaninfo");
+        vulkaninfo.arg("--summary");
+        if let Ok(output) = host::command(vulkaninfo).output() {
             if output.status.success() {
                 let stdout = String::from_utf8_lossy(&output.stdout);
                 // Look for Intel/AMD drivers (Mesa)
@@ -169,6 +244,33 @@ impl SystemCheck {
         false
     }
 
+    /// Terminal command that installs the missing packages.
+    pub fn install_command(&self) -> Option<String> {
+        if self.missing_packages.is_empty() {
+            return None;
+        }
+        let install = self
+            .package_manager?
+            .install_command(&self.missing_packages);
+        if self.needs_i386 {
+            let enable = ENABLE_I386_SCRIPT
+                .split(" && ")
+                .map(|step| format!("sudo {}", step))
+                .collect::<Vec<_>>()
+                .join(" && ");
+            Some(format!("{} && {}", enable, install))
+        } else {
+            Some(install)
+        }
+    }
+
+    pub fn tool_installed(&self, tool: GamingTool) -> bool {
+        match tool {
+            GamingTool::GameMode => self.gamemode_installed,
+            GamingTool::MangoHud => self.mangohud_installed,
+        }
+    }
+
     /// Get a human-readable status message
     pub fn status_message(&self) -> String {
         match self.status {
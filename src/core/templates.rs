@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::core::capsule::{CapsuleMetadata, DllOverride};
+use crate::core::system_checker::SystemCheck;
+
+/// The launch settings of a capsule that make sense on another game: no
+/// executable, folders, store IDs or install state. The runtime isn't part
+/// of it; every game runs on the newest installed Proton.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchProfile {
+    #[serde(default)]
+    pub env_vars: Vec<(String, String)>,
+    #[serde(default)]
+    pub dll_overrides: Vec<DllOverride>,
+    #[serde(default)]
+    pub protonfixes_disable: bool,
+    #[serde(default)]
+    pub protonfixes_tricks: Vec<String>,
+    #[serde(default)]
+    pub protonfixes_replace_cmds: Vec<String>,
+    #[serde(default)]
+    pub protonfixes_dxvk_sets: Vec<String>,
+    #[serde(default)]
+    pub winetricks_verbs: Vec<String>,
+    #[serde(default)]
+    pub xalia_enabled: bool,
+    #[serde(default)]
+    pub gamemode_enabled: bool,
+    #[serde(default)]
+    pub mangohud_enabled: bool,
+    #[serde(default)]
+    pub fps_limit: Option<u32>,
+    #[serde(default)]
+    pub gamescope_enabled: bool,
+    #[serde(default)]
+    pub gamescope_hdr: bool,
+    #[serde(default)]
+    pub gamescope_vrr: bool,
+    #[serde(default)]
+    pub proton_wayland: bool,
+    #[serde(default)]
+    pub force_x11: bool,
+    #[serde(default)]
+    pub sdl_video_driver: Option<String>,
+    #[serde(default)]
+    pub sandbox_enabled: bool,
+    #[serde(default = "default_true")]
+    pub sandbox_network: bool,
+    #[serde(default)]
+    pub block_network: bool,
+    #[serde(default)]
+    pub command_template: Option<String>,
+    #[serde(default)]
+    pub virtual_desktop: Option<String>,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub lc_all: Option<String>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl LaunchProfile {
+    pub fn from_metadata(metadata: &CapsuleMetadata) -> Self {
+        Self {
+            env_vars: metadata.env_vars.clone(),
+            dll_overrides: metadata.dll_overrides.clone(),
+            protonfixes_disable: metadata.protonfixes_disable,
+            protonfixes_tricks: metadata.protonfixes_tricks.clone(),
+            protonfixes_replace_cmds: metadata.protonfixes_replace_cmds.clone(),
+            protonfixes_dxvk_sets: metadata.protonfixes_dxvk_sets.clone(),
+            winetricks_verbs: metadata.winetricks_verbs.clone(),
+            xalia_enabled: metadata.xalia_enabled,
+            gamemode_enabled: metadata.gamemode_enabled,
+            mangohud_enabled: metadata.mangohud_enabled,
+            fps_limit: metadata.fps_limit,
+            gamescope_enabled: metadata.gamescope_enabled,
+            gamescope_hdr: metadata.gamescope_hdr,
+            gamescope_vrr: metadata.gamescope_vrr,
+            proton_wayland: metadata.proton_wayland,
+            force_x11: metadata.force_x11,
+            sdl_video_driver: metadata.sdl_video_driver.clone(),
+            sandbox_enabled: metadata.sandbox_enabled,
+            sandbox_network: metadata.sandbox_network,
+            block_network: metadata.block_network,
+            command_template: metadata.command_template.clone(),
+            virtual_desktop: metadata.virtual_desktop.clone(),
+            locale: metadata.locale.clone(),
+            lc_all: metadata.lc_all.clone(),
+            timezone: metadata.timezone.clone(),
+        }
+    }
+
+    /// Replace the launch settings of `metadata` with this profile's.
+    pub fn apply(&self, metadata: &mut CapsuleMetadata) {
+        metadata.env_vars = self.env_vars.clone();
+        metadata.dll_overrides = self.dll_overrides.clone();
+        metadata.protonfixes_disable = self.protonfixes_disable;
+        metadata.protonfixes_tricks = self.protonfixes_tricks.clone();
+        metadata.protonfixes_replace_cmds = self.protonfixes_replace_cmds.clone();
+        metadata.protonfixes_dxvk_sets = self.protonfixes_dxvk_sets.clone();
+        metadata.winetricks_verbs = self.winetricks_verbs.clone();
+        metadata.xalia_enabled = self.xalia_enabled;
+        metadata.gamemode_enabled = self.gamemode_enabled;
+        metadata.mangohud_enabled = self.mangohud_enabled;
+        metadata.fps_limit = self.fps_limit;
+        metadata.gamescope_enabled = self.gamescope_enabled;
+        metadata.gamescope_hdr = self.gamescope_hdr;
+        metadata.gamescope_vrr = self.gamescope_vrr;
+        metadata.proton_wayland = self.proton_wayland;
+        metadata.force_x11 = self.force_x11;
+        metadata.sdl_video_driver = self.sdl_video_driver.clone();
+        metadata.sandbox_enabled = self.sandbox_enabled;
+        metadata.sandbox_network = self.sandbox_network;
+        metadata.block_network = self.block_network;
+        metadata.command_template = self.command_template.clone();
+        metadata.virtual_desktop = self.virtual_desktop.clone();
+        metadata.locale = self.locale.clone();
+        metadata.lc_all = self.lc_all.clone();
+        metadata.timezone = self.timezone.clone();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigTemplate {
+    pub name: String,
+    pub profile: LaunchProfile,
+}
+
+/// Saved templates, stored in `~/.linuxboy/templates.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigTemplates {
+    #[serde(default)]
+    pub templates: Vec<ConfigTemplate>,
+    /// Template applied to every new capsule.
+    #[serde(default)]
+    pub default_template: Option<String>,
+}
+
+impl ConfigTemplates {
+    pub fn path() -> PathBuf {
+        SystemCheck::get_linuxboy_dir().join("templates.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize templates")?;
+        fs::write(&path, content).context("Failed to write templates")?;
+        Ok(())
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.templates
+            .iter()
+            .map(|template| template.name.clone())
+            .collect()
+    }
+
+    pub fn find(&self, name: &str) -> Option<&ConfigTemplate> {
+        self.templates.iter().find(|template| template.name == name)
+    }
+
+    /// Add `template`, replacing one with the same name.
+    pub fn insert(&mut self, template: ConfigTemplate) {
+        match self
+            .templates
+            .iter_mut()
+            .find(|existing| existing.name == template.name)
+        {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.templates.retain(|template| template.name != name);
+        if self.default_template.as_deref() == Some(name) {
+            self.default_template = None;
+        }
+    }
+
+    /// Apply the template for new games, if one is set, to `metadata`.
+    pub fn apply_default(&self, metadata: &mut CapsuleMetadata) {
+        if let Some(template) = self
+            .default_template
+            .as_deref()
+            .and_then(|name| self.find(name))
+        {
+            template.profile.apply(metadata);
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
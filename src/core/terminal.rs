@@ -0,0 +1,75 @@
+use std::process::Command;
+
+use crate::utils::host;
+
+/// Terminal emulators tried in order, with the arguments that make them run
+/// a command and stay in the foreground until it exits.
+const TERMINALS: [(&str, &[&str]); 9] = [
+    ("konsole", &["-e"]),
+    ("gnome-terminal", &["--wait", "--"]),
+    ("xfce4-terminal", &["--disable-server", "-x"]),
+    ("alacritty", &["-e"]),
+    ("kitty", &[]),
+    ("foot", &[]),
+    ("wezterm", &["start", "--always-new-process", "--"]),
+    ("x-terminal-emulator", &["-e"]),
+    ("xterm", &["-e"]),
+];
+
+/// Keeps the window open after the game exits so its last output can be
+/// read, and passes the game's exit status on.
+const HOLD_SCRIPT: &str = "\"$@\"; status=$?; echo; \
+    echo \"The game exited with status $status. Press Enter to close.\"; \
+    read _; exit $status";
+
+/// A terminal emulator to show a game's output live.
+#[derive(Debug, Clone)]
+pub struct Terminal {
+    pub program: String,
+    exec_args: Vec<String>,
+}
+
+impl Terminal {
+    /// `$TERMINAL` when set, otherwise the first known terminal installed on
+    /// the host.
+    pub fn detect() -> Option<Terminal> {
+        if let Ok(program) = std::env::var("TERMINAL") {
+            let program = program.trim();
+            if !program.is_empty() && host::has_command(program) {
+                return Some(Terminal {
+                    program: program.to_string(),
+                    exec_args: vec!["-e".to_string()],
+                });
+            }
+        }
+        TERMINALS
+            .iter()
+            .find(|(program, _)| host::has_command(program))
+            .map(|(program, args)| Terminal {
+                program: program.to_string(),
+                exec_args: args.iter().map(|arg| arg.to_string()).collect(),
+            })
+    }
+
+    /// Run `cmd` in a new terminal window. The environment and working
+    /// directory move to the terminal, which passes them on.
+    ///
+    /// Call this after `gamescope::wrap` and before `host::command`.
+    pub fn wrap(&self, cmd: Command) -> Command {
+        let mut wrapped = Command::new(&self.program);
+        if let Some(dir) = cmd.get_current_dir() {
+            wrapped.current_dir(dir);
+        }
+        for (key, value) in cmd.get_envs() {
+            match value {
+                Some(value) => wrapped.env(key, value),
+                None => wrapped.env_remove(key),
+            };
+        }
+        wrapped.args(&self.exec_args);
+        wrapped.args(["sh", "-c", HOLD_SCRIPT, "sh"]);
+        wrapped.arg(cmd.get_program());
+        wrapped.args(cmd.get_args());
+        wrapped
+    }
+}
@@ -0,0 +1,279 @@
+/// Fuzzy matching of user-typed game titles against UMU database titles.
+///
+/// Titles are reduced to lowercase word tokens with roman numerals turned into
+/// numbers, so "Final Fantasy VII", "final fantasy 7" and "FFVII" can meet.
+/// Scores are confidences between 0 and 1.
+pub struct TitleMatcher {
+    tokens: Vec<String>,
+    compact: String,
+}
+
+/// Candidates below this confidence are not offered.
+pub const MIN_CONFIDENCE: f64 = 0.75;
+
+impl TitleMatcher {
+    pub fn new(input: &str) -> Self {
+        let tokens = Self::tokens(input);
+        Self {
+            compact: tokens.concat(),
+            tokens,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.compact.is_empty()
+    }
+
+    /// Confidence that `title` names the same game, or `None` below
+    /// `MIN_CONFIDENCE`.
+    pub fn score_title(&self, title: &str) -> Option<f64> {
+        let tokens = Self::tokens(title);
+        let compact = tokens.concat();
+        if self.is_empty() || compact.is_empty() {
+            return None;
+        }
+
+        let score = if compact == self.compact {
+            1.0
+        } else if self.tokens.iter().all(|token| tokens.contains(token)) {
+            // Same title plus a subtitle or edition suffix.
+            let extra = tokens.len().saturating_sub(self.tokens.len()) as f64;
+            (0.95 - 0.02 * extra).max(0.85)
+        } else if self.compact.len() >= 4 && compact.contains(&self.compact) {
+            0.88
+        } else if let Some(score) = self.score_initials(&tokens) {
+            score
+        } else {
+            let similarity = Self::jaro_winkler(&self.compact, &compact);
+            let overlap = Self::trigram_similarity(&self.compact, &compact);
+            let mut score = similarity.max(overlap) * 0.9;
+            // "GTA V" and "GTA IV" spell almost the same but are different games,
+            // as are "Mega Man X" and "Mega Man 10".
+            let is_digits = |token: &&String| token.chars().all(|ch| ch.is_ascii_digit());
+            let numbers_missing = self
+                .tokens
+                .iter()
+                .filter(is_digits)
+                .any(|number| !tokens.contains(number))
+                || tokens
+                    .iter()
+                    .filter(is_digits)
+                    .any(|number| !self.tokens.contains(number));
+            if numbers_missing {
+                score *= 0.8;
+            }
+            score
+        };
+
+        Some(score).filter(|score| *score >= MIN_CONFIDENCE)
+    }
+
+    /// Confidence for the database's acronym field ("FF7", "GTAV").
+    pub fn score_acronym(&self, acronym: &str) -> Option<f64> {
+        let acronym_tokens = Self::tokens(acronym);
+        let acronym = acronym_tokens.concat();
+        if acronym.len() < 2 || self.is_empty() {
+            return None;
+        }
+        let forms = self.acronym_forms();
+        if Self::numeral_forms(&acronym_tokens)
+            .iter()
+            .any(|form| forms.contains(form))
+        {
+            Some(0.9)
+        } else if acronym.len() >= 3 && self.tokens.contains(&acronym) {
+            Some(0.8)
+        } else {
+            None
+        }
+    }
+
+    /// The input typed as an abbreviation of `tokens`: the initials of the
+    /// whole title, or of its leading words when the title has a subtitle.
+    fn score_initials(&self, tokens: &[String]) -> Option<f64> {
+        let initials: Vec<String> = tokens
+            .iter()
+            .map(|token| {
+                if token.chars().all(|ch| ch.is_ascii_digit()) {
+                    token.clone()
+                } else {
+                    token.chars().take(1).collect()
+                }
+            })
+            .collect();
+        if initials.len() < 2 {
+            return None;
+        }
+
+        let forms = self.acronym_forms();
+        if forms.contains(&initials.concat()) {
+            return Some(0.9);
+        }
+        (2..initials.len())
+            .rev()
+            .find(|len| forms.contains(&initials[..*len].concat()))
+            .map(|len| 0.9 - 0.05 * (initials.len() - len) as f64)
+    }
+
+    /// The input as typed, plus with a trailing roman numeral glued onto the
+    /// last word converted ("ffvii" -> "ff7").
+    fn acronym_forms(&self) -> Vec<String> {
+        Self::numeral_forms(&self.tokens)
+    }
+
+    fn numeral_forms(tokens: &[String]) -> Vec<String> {
+        let compact = tokens.concat();
+        let mut forms = vec![compact.clone()];
+        if let Some(last) = tokens.last() {
+            let head = &compact[..compact.len() - last.len()];
+            for split in 1..last.len() {
+                let (letters, numeral) = last.split_at(split);
+                // "megamanx" is not "megaman10".
+                if numeral == "x" {
+                    continue;
+                }
+                if let Some(number) = Self::roman_to_number(numeral) {
+                    forms.push(format!("{}{}{}", head, letters, number));
+                }
+            }
+        }
+        forms
+    }
+
+    fn tokens(value: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut current = String::new();
+        for ch in value.chars() {
+            if ch.is_ascii_alphanumeric() {
+                current.push(ch.to_ascii_lowercase());
+            } else if ch == '&' {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+                words.push("and".to_string());
+            } else if ch == '\'' || ch == '\u{2019}' {
+                // "Assassin's" and "Assassins" are the same word.
+            } else if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.is_empty() {
+            words.push(current);
+        }
+
+        let count = words.len();
+        (0..count)
+            .map(|index| {
+                let word = &words[index];
+                // A lone "i" is only a numeral at the end ("Diablo I").
+                if word == "i" && index + 1 < count {
+                    return word.clone();
+                }
+                // A lone "x" is usually a letter ("Mega Man X"); it is only
+                // read as 10 next to another number ("Final Fantasy X-2").
+                if word == "x" {
+                    let beside_number = [index.checked_sub(1), Some(index + 1)]
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|neighbour| words.get(neighbour))
+                        .any(|neighbour| Self::is_number(neighbour));
+                    if !beside_number {
+                        return word.clone();
+                    }
+                }
+                match Self::roman_to_number(word) {
+                    Some(number) => number.to_string(),
+                    None => word.clone(),
+                }
+            })
+            .collect()
+    }
+
+    fn is_number(word: &str) -> bool {
+        word.chars().all(|ch| ch.is_ascii_digit())
+            || (word != "x" && Self::roman_to_number(word).is_some())
+    }
+
+    fn roman_to_number(value: &str) -> Option<u32> {
+        const NUMERALS: [&str; 20] = [
+            "i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix", "x", "xi", "xii", "xiii",
+            "xiv", "xv", "xvi", "xvii", "xviii", "xix", "xx",
+        ];
+        NUMERALS
+            .iter()
+            .position(|numeral| *numeral == value)
+            .map(|index| index as u32 + 1)
+    }
+
+    fn jaro_winkler(a: &str, b: &str) -> f64 {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+
+        let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+        let mut a_matched = vec![false; a.len()];
+        let mut b_matched = vec![false; b.len()];
+        let mut matches = 0usize;
+        for (i, ch) in a.iter().enumerate() {
+            let start = i.saturating_sub(window);
+            let end = (i + window + 1).min(b.len());
+            for j in start..end {
+                if !b_matched[j] && b[j] == *ch {
+                    a_matched[i] = true;
+                    b_matched[j] = true;
+                    matches += 1;
+                    break;
+                }
+            }
+        }
+        if matches == 0 {
+            return 0.0;
+        }
+
+        let a_order = a
+            .iter()
+            .zip(&a_matched)
+            .filter(|(_, m)| **m)
+            .map(|(ch, _)| ch);
+        let b_order = b
+            .iter()
+            .zip(&b_matched)
+            .filter(|(_, m)| **m)
+            .map(|(ch, _)| ch);
+        let transpositions = a_order.zip(b_order).filter(|(x, y)| x != y).count() / 2;
+
+        let m = matches as f64;
+        let jaro =
+            (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+        let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count() as f64;
+        jaro + prefix * 0.1 * (1.0 - jaro)
+    }
+
+    /// Dice coefficient over character trigrams.
+    fn trigram_similarity(a: &str, b: &str) -> f64 {
+        let trigrams = |value: &str| -> Vec<String> {
+            let padded: Vec<char> = format!("  {} ", value).chars().collect();
+            padded
+                .windows(3)
+                .map(|window| window.iter().collect())
+                .collect()
+        };
+        let a = trigrams(a);
+        let mut b = trigrams(b);
+        let total = a.len() + b.len();
+        let mut shared = 0;
+        for trigram in &a {
+            if let Some(index) = b.iter().position(|other| other == trigram) {
+                b.swap_remove(index);
+                shared += 1;
+            }
+        }
+        if total == 0 {
+            0.0
+        } else {
+            2.0 * shared as f64 / total as f64
+        }
+    }
+}
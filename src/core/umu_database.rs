@@ -2,8 +2,13 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::network;
 
 const UMU_DATABASE_URL: &str = "https://umu.openwinecomponents.org/umu_api.php";
+/// How long a downloaded database is used before asking the server again.
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UmuEntry {
@@ -23,25 +28,95 @@ pub struct UmuEntry {
     pub notes: Option<String>,
 }
 
+/// When the cache was last checked against the server, and the validators
+/// to send with the next conditional request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CacheInfo {
+    fetched_at: u64,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+}
+
 pub struct UmuDatabase;
 
 impl UmuDatabase {
-    pub fn load_or_fetch() -> Result<Vec<UmuEntry>> {
-        match Self::fetch_entries() {
-            Ok(entries) => {
-                let _ = Self::write_cache(&entries);
-                Ok(entries)
-            }
-            Err(fetch_err) => {
-                if let Ok(entries) = Self::read_cache() {
-                    Ok(entries)
-                } else {
-                    Err(fetch_err)
+    /// Entries from the on-disk cache, if there is one.
+    pub fn load_cached() -> Option<Vec<UmuEntry>> {
+        match Self::read_cache() {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                if Self::cache_path().map(|path| path.exists()).unwrap_or(false) {
+                    eprintln!("Ignoring UMU cache: {:#}", e);
                 }
+                None
             }
         }
     }
 
+    /// True when the cache was checked against the server within the TTL.
+    pub fn cache_is_fresh() -> bool {
+        let info = match Self::read_cache_info() {
+            Some(info) => info,
+            None => return false,
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        now.saturating_sub(info.fetched_at) < CACHE_TTL.as_secs()
+    }
+
+    /// Ask the server for a newer database, sending the cached ETag and
+    /// Last-Modified. Returns `None` when the cached copy is still current.
+    pub fn refresh() -> Result<Option<Vec<UmuEntry>>> {
+        let mut info = if Self::cache_path().map(|path| path.is_file()).unwrap_or(false) {
+            Self::read_cache_info().unwrap_or_default()
+        } else {
+            CacheInfo::default()
+        };
+
+        let client = network::client_builder()?.build()?;
+        let mut request = client.get(UMU_DATABASE_URL);
+        if let Some(etag) = &info.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &info.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+        let response = request.send().context("Failed to request UMU database")?;
+
+        info.fetched_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            let _ = Self::write_cache_info(&info);
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("UMU database request failed with status: {}", response.status());
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        info.etag = header(reqwest::header::ETAG);
+        info.last_modified = header(reqwest::header::LAST_MODIFIED);
+
+        let entries = response
+            .json::<Vec<UmuEntry>>()
+            .context("Failed to parse UMU database response")?;
+        Self::write_cache(&entries)?;
+        let _ = Self::write_cache_info(&info);
+        Ok(Some(entries))
+    }
+
     pub fn normalize_title(title: &str) -> String {
         title
             .chars()
@@ -50,14 +125,6 @@ impl UmuDatabase {
             .collect()
     }
 
-    fn fetch_entries() -> Result<Vec<UmuEntry>> {
-        let response = reqwest::blocking::get(UMU_DATABASE_URL)
-            .context("Failed to request UMU database")?;
-        response
-            .json::<Vec<UmuEntry>>()
-            .context("Failed to parse UMU database response")
-    }
-
     fn read_cache() -> Result<Vec<UmuEntry>> {
         let path = Self::cache_path().context("Home directory not available")?;
         let content = fs::read_to_string(&path)
@@ -77,6 +144,23 @@ impl UmuDatabase {
         Ok(())
     }
 
+    fn read_cache_info() -> Option<CacheInfo> {
+        let content = fs::read_to_string(Self::cache_info_path()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn write_cache_info(info: &CacheInfo) -> Result<()> {
+        let path = Self::cache_info_path().context("Home directory not available")?;
+        let content = serde_json::to_string(info).context("Failed to serialize UMU cache info")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write UMU cache info at {:?}", path))?;
+        Ok(())
+    }
+
+    fn cache_info_path() -> Option<PathBuf> {
+        Self::cache_path().map(|path| path.with_file_name("umu_database.meta.json"))
+    }
+
     fn cache_path() -> Option<PathBuf> {
         dirs::home_dir().map(|home| home.join(".linuxboy").join("cache").join("umu_database.json"))
     }
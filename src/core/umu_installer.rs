@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::core::network;
+use crate::core::package_manager::PackageManager;
+use crate::core::runtime_manager::GitHubAsset;
+use crate::core::system_checker::SystemCheck;
+use crate::utils::host;
+
+const UMU_LATEST_RELEASE: &str =
+    "https://api.github.com/repos/Open-Wine-Components/umu-launcher/releases/latest";
+const UMU_FLATPAK_ID: &str = "org.openwinecomponents.umu.umu-launcher";
+const UMU_PYPI_PACKAGE: &str = "umu-launcher";
+
+#[derive(Debug, Deserialize)]
+struct UmuRelease {
+    assets: Vec<GitHubAsset>,
+}
+
+/// How umu-launcher gets installed on this system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UmuInstallMethod {
+    /// Debian and Ubuntu: the release .deb through the setup script, which
+    /// needs a terminal for sudo.
+    SetupScript,
+    /// Fedora and Arch package umu-launcher in their main repositories.
+    DistroPackage(PackageManager),
+    /// The release's Flatpak bundle, with a `umu-run` shim in ~/.local/bin.
+    Flatpak,
+    Pipx,
+    Pip,
+}
+
+impl UmuInstallMethod {
+    /// The best method for the detected distro, falling back to Flatpak,
+    /// then pipx, then pip.
+    pub fn detect(package_manager: Option<PackageManager>) -> Option<Self> {
+        match package_manager {
+            Some(PackageManager::Apt) => {
+                let os_id = Self::os_release_id();
+                if matches!(os_id.as_deref(), Some("debian") | Some("ubuntu")) {
+                    return Some(UmuInstallMethod::SetupScript);
+                }
+            }
+            Some(manager @ (PackageManager::Dnf | PackageManager::Pacman)) => {
+                return Some(UmuInstallMethod::DistroPackage(manager));
+            }
+            Some(PackageManager::Zypper) | None => {}
+        }
+
+        if host::has_command("flatpak") {
+            Some(UmuInstallMethod::Flatpak)
+        } else if host::has_command("pipx") {
+            Some(UmuInstallMethod::Pipx)
+        } else if host::has_command("python3") {
+            Some(UmuInstallMethod::Pip)
+        } else {
+            None
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            UmuInstallMethod::SetupScript => "setup script (.deb)".to_string(),
+            UmuInstallMethod::DistroPackage(manager) => format!("{} package", manager.name()),
+            UmuInstallMethod::Flatpak => "Flatpak".to_string(),
+            UmuInstallMethod::Pipx => "pipx".to_string(),
+            UmuInstallMethod::Pip => "pip (user install)".to_string(),
+        }
+    }
+
+    /// Whether `install` can run from the app; the setup script can't.
+    pub fn installs_in_app(&self) -> bool {
+        *self != UmuInstallMethod::SetupScript
+    }
+
+    /// Install umu-launcher. Distro packages go through pkexec; the other
+    /// methods install for the current user only.
+    pub fn install(&self) -> Result<()> {
+        match self {
+            UmuInstallMethod::SetupScript => {
+                anyhow::bail!("Run the setup script in a terminal to install the UMU .deb")
+            }
+            UmuInstallMethod::DistroPackage(manager) => {
+                let script = manager.install_script(&["umu-launcher".to_string()]);
+                Self::run("pkexec", &["sh", "-c", &script])
+            }
+            UmuInstallMethod::Flatpak => Self::install_flatpak(),
+            UmuInstallMethod::Pipx => Self::run("pipx", &["install", UMU_PYPI_PACKAGE]),
+            UmuInstallMethod::Pip => Self::run(
+                "python3",
+                &["-m", "pip", "install", "--user", UMU_PYPI_PACKAGE],
+            ),
+        }
+    }
+
+    fn install_flatpak() -> Result<()> {
+        let client = network::client_builder()?.build()?;
+        let release: UmuRelease = client
+            .get(UMU_LATEST_RELEASE)
+            .send()
+            .context("Failed to fetch the latest umu-launcher release")?
+            .error_for_status()?
+            .json()
+            .context("Failed to parse umu-launcher release JSON")?;
+        let asset = release
+            .assets
+            .iter()
+            .find(|asset| asset.name.ends_with(".flatpak"))
+            .context("The latest umu-launcher release has no Flatpak bundle")?;
+
+        // Not /tmp: a sandboxed LinuxBoy's /tmp is invisible to the host flatpak.
+        let temp_dir = SystemCheck::get_cache_dir().join("umu-flatpak");
+        fs::create_dir_all(&temp_dir)?;
+        let bundle = temp_dir.join(&asset.name);
+        let mut response = client
+            .get(&asset.browser_download_url)
+            .send()
+            .context("Failed to download the umu-launcher Flatpak")?
+            .error_for_status()?;
+        let mut file = fs::File::create(&bundle)?;
+        response.copy_to(&mut file)?;
+        drop(file);
+
+        let bundle_arg = bundle.to_string_lossy().to_string();
+        let result = Self::run(
+            "flatpak",
+            &["install", "--user", "-y", "--bundle", &bundle_arg],
+        );
+        let _ = fs::remove_dir_all(&temp_dir);
+        result?;
+
+        Self::write_flatpak_shim()
+    }
+
+    /// LinuxBoy calls `umu-run` by name, so point one at the Flatpak.
+    fn write_flatpak_shim() -> Result<()> {
+        let bin_dir = Self::user_bin_dir().context("Home directory not available")?;
+        fs::create_dir_all(&bin_dir)?;
+        let shim = bin_dir.join("umu-run");
+        fs::write(
+            &shim,
+            format!(
+                "#!/bin/sh\nexec flatpak run --command=umu-run {} \"$@\"\n",
+                UMU_FLATPAK_ID
+            ),
+        )
+        .with_context(|| format!("Failed to write {:?}", shim))?;
+        fs::set_permissions(&shim, fs::Permissions::from_mode(0o755))?;
+        Ok(())
+    }
+
+    /// pipx, pip --user and the Flatpak shim all install into ~/.local/bin,
+    /// which desktop sessions don't always put on PATH.
+    pub fn add_user_bin_to_path() {
+        let bin_dir = match Self::user_bin_dir() {
+            Some(bin_dir) => bin_dir,
+            None => return,
+        };
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let mut paths: Vec<PathBuf> = std::env::split_paths(&path).collect();
+        if paths.contains(&bin_dir) {
+            return;
+        }
+        paths.push(bin_dir);
+        if let Ok(joined) = std::env::join_paths(paths) {
+            std::env::set_var("PATH", joined);
+        }
+    }
+
+    fn user_bin_dir() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".local").join("bin"))
+    }
+
+    pub fn os_release_id() -> Option<String> {
+        let content = fs::read_to_string(host::host_file("/etc/os-release"))
+            .or_else(|_| fs::read_to_string("/run/host/os-release"))
+            .ok()?;
+        content.lines().find_map(|line| {
+            line.strip_prefix("ID=")
+                .map(|value| value.trim_matches('"').to_string())
+        })
+    }
+
+    fn run(program: &str, args: &[&str]) -> Result<()> {
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        let output = host::command(cmd)
+            .output()
+            .with_context(|| format!("Failed to run {}", program))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!(
+                "{} {} failed: {}",
+                program,
+                args.join(" "),
+                stderr.lines().last().unwrap_or("unknown error")
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::core::capsule::Capsule;
+use crate::core::registry::PrefixRegistry;
+use crate::core::umu_database::UmuDatabase;
+use crate::utils::file_ops;
+
+/// How deep below the install folder to look for uninstaller files.
+const SCAN_DEPTH: usize = 2;
+
+/// A way to remove the game from its prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Uninstaller {
+    pub label: String,
+    /// Host path of the uninstaller, or a Wine builtin like `msiexec`.
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+pub struct Uninstallers;
+
+impl Uninstallers {
+    /// The game's uninstallers: what its installer registered for
+    /// Add/Remove Programs, then `unins000.exe`-style files in its install
+    /// folder. Entries of other programs in the prefix, like runtimes, are
+    /// left out.
+    pub fn find(capsule: &Capsule) -> Vec<Uninstaller> {
+        let prefix_path = capsule.home_path.join("prefix");
+        let registry = PrefixRegistry::new(&prefix_path);
+        let install_root = capsule.install_root();
+        let game_name = UmuDatabase::normalize_title(&capsule.name);
+        let mut found: Vec<Uninstaller> = Vec::new();
+        // Where the game's own entries say it was installed, for when the
+        // capsule doesn't know its install folder.
+        let mut registered_roots: Vec<PathBuf> = Vec::new();
+
+        for entry in registry.uninstall_entries() {
+            let (program, args) = match entry.uninstall_string.as_deref().and_then(split_command) {
+                Some(command) => command,
+                None => continue,
+            };
+            let is_msi = is_msiexec(&program);
+            let host_program = if is_msi {
+                None
+            } else {
+                match registry.host_path(&program).filter(|path| path.is_file()) {
+                    Some(path) => Some(path),
+                    None => continue,
+                }
+            };
+
+            let name_matches = entry.display_name.as_deref().is_some_and(|name| {
+                let name = UmuDatabase::normalize_title(name);
+                !name.is_empty() && !game_name.is_empty() && name.contains(&game_name)
+            });
+            let location_matches = install_root.as_deref().is_some_and(|root| {
+                entry
+                    .install_location
+                    .as_deref()
+                    .and_then(|location| registry.host_path(location))
+                    .into_iter()
+                    .chain(host_program.clone())
+                    .any(|path| is_inside(&path, root))
+            });
+            if !name_matches && !location_matches {
+                continue;
+            }
+            registered_roots.extend(
+                entry
+                    .install_location
+                    .as_deref()
+                    .and_then(|location| registry.host_path(location))
+                    .filter(|path| file_ops::has_entries(path)),
+            );
+
+            let label = entry.display_name.clone().unwrap_or_else(|| {
+                host_program
+                    .as_deref()
+                    .map(file_label)
+                    .unwrap_or_else(|| program.clone())
+            });
+            let uninstaller = Uninstaller {
+                label,
+                program: match host_program {
+                    Some(path) => path.to_string_lossy().to_string(),
+                    None => "msiexec".to_string(),
+                },
+                args,
+            };
+            if !found
+                .iter()
+                .any(|existing| existing.program == uninstaller.program)
+            {
+                found.push(uninstaller);
+            }
+        }
+
+        let scanned = match install_root {
+            Some(root) => vec![root],
+            None => registered_roots,
+        };
+        for root in &scanned {
+            for entry in WalkDir::new(root)
+                .max_depth(SCAN_DEPTH)
+                .into_iter()
+                .flatten()
+                .filter(|entry| entry.file_type().is_file())
+            {
+                let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+                if !name.starts_with("unins") || !name.ends_with(".exe") {
+                    continue;
+                }
+                let program = entry.path().to_string_lossy().to_string();
+                if !found.iter().any(|existing| existing.program == program) {
+                    found.push(Uninstaller {
+                        label: file_label(entry.path()),
+                        program,
+                        args: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        found
+    }
+}
+
+fn file_label(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn is_inside(path: &Path, root: &Path) -> bool {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    path.starts_with(root)
+}
+
+fn is_msiexec(program: &str) -> bool {
+    let name = program
+        .rsplit(['\\', '/'])
+        .next()
+        .unwrap_or(program)
+        .to_ascii_lowercase();
+    name == "msiexec" || name == "msiexec.exe"
+}
+
+/// Split an `UninstallString` into the program and its arguments. The
+/// program may be quoted; unquoted paths with spaces end at `.exe`.
+fn split_command(command: &str) -> Option<(String, Vec<String>)> {
+    let command = command.trim();
+    let (program, rest) = if let Some(quoted) = command.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else {
+        match command.to_ascii_lowercase().find(".exe") {
+            Some(index) => command.split_at(index + ".exe".len()),
+            None => command.split_once(' ').unwrap_or((command, "")),
+        }
+    };
+    let program = program.trim();
+    if program.is_empty() {
+        return None;
+    }
+    Some((program.to_string(), split_args(rest)))
+}
+
+/// Whitespace-separated arguments; double quotes group words.
+fn split_args(rest: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for ch in rest.chars() {
+        match ch {
+            '"' => quoted = !quoted,
+            ch if ch.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            ch => current.push(ch),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::network;
+use crate::core::system_checker::SystemCheck;
+use crate::utils::host;
+
+const WINETRICKS_URL: &str =
+    "https://raw.githubusercontent.com/Winetricks/winetricks/master/src/winetricks";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerbCategory {
+    Dlls,
+    Fonts,
+    Settings,
+}
+
+impl VerbCategory {
+    pub const ALL: [VerbCategory; 3] = [VerbCategory::Dlls, VerbCategory::Fonts, VerbCategory::Settings];
+
+    /// Category name as winetricks spells it on the command line.
+    fn command(&self) -> &'static str {
+        match self {
+            VerbCategory::Dlls => "dlls",
+            VerbCategory::Fonts => "fonts",
+            VerbCategory::Settings => "settings",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            VerbCategory::Dlls => "DLLs",
+            VerbCategory::Fonts => "Fonts",
+            VerbCategory::Settings => "Settings",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WinetricksVerb {
+    pub name: String,
+    pub description: String,
+    pub category: VerbCategory,
+}
+
+/// A winetricks script, either from PATH or downloaded into the runtimes folder.
+pub struct Winetricks {
+    path: PathBuf,
+}
+
+impl Winetricks {
+    pub fn cache_path() -> PathBuf {
+        SystemCheck::get_runtimes_dir().join("winetricks")
+    }
+
+    /// Prefer a system winetricks, then a previously downloaded copy.
+    pub fn locate() -> Option<Self> {
+        let system = Command::new("which")
+            .arg("winetricks")
+            .output()
+            .ok()
+            // Inside Flatpak this finds the sandbox's copy, which the host
+            // can't run; use a downloaded one instead.
+            .filter(|_| !host::is_flatpak())
+            .filter(|output| output.status.success())
+            .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+            .filter(|path| path.is_file());
+        if let Some(path) = system {
+            return Some(Self { path });
+        }
+
+        let cached = Self::cache_path();
+        if cached.is_file() {
+            Some(Self { path: cached })
+        } else {
+            None
+        }
+    }
+
+    /// Download the latest winetricks script into the runtimes folder.
+    pub fn download() -> Result<Self> {
+        let dest = Self::cache_path();
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        println!("Downloading winetricks from {}", WINETRICKS_URL);
+        let client = network::client_builder()?.build()?;
+        let mut response = client.get(WINETRICKS_URL).send()?;
+        if !response.status().is_success() {
+            anyhow::bail!("Download failed with status: {}", response.status());
+        }
+        let mut script = Vec::new();
+        response.read_to_end(&mut script)?;
+        if !script.starts_with(b"#!") {
+            anyhow::bail!("Downloaded winetricks is not a shell script");
+        }
+
+        let partial = dest.with_file_name("winetricks.part");
+        fs::write(&partial, &script).with_context(|| format!("Failed to write {:?}", partial))?;
+        fs::set_permissions(&partial, fs::Permissions::from_mode(0o755))?;
+        fs::rename(&partial, &dest).context("Failed to install winetricks")?;
+        Ok(Self { path: dest })
+    }
+
+    pub fn locate_or_download() -> Result<Self> {
+        match Self::locate() {
+            Some(winetricks) => Ok(winetricks),
+            None => Self::download(),
+        }
+    }
+
+    /// Verbs from the dll, font and settings categories, in winetricks' order.
+    pub fn list_verbs(&self, proton_path: Option<&Path>) -> Result<Vec<WinetricksVerb>> {
+        let mut verbs = Vec::new();
+        for category in VerbCategory::ALL {
+            let mut cmd = Command::new(&self.path);
+            if let Some(proton_path) = proton_path {
+                Self::apply_wine_env(&mut cmd, proton_path);
+            }
+            cmd.arg(category.command()).arg("list");
+            let output = host::command(cmd)
+                .output()
+                .context("Failed to run winetricks")?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "winetricks {} list failed: {}",
+                    category.command(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            verbs.extend(Self::parse_list(&String::from_utf8_lossy(&output.stdout), category));
+        }
+        Ok(verbs)
+    }
+
+    /// Command that applies `verbs` to `prefix_path` unattended, using the
+    /// Wine build shipped with the Proton runtime.
+    pub fn command(&self, prefix_path: &Path, proton_path: &Path, verbs: &[String]) -> Command {
+        let mut cmd = Command::new(&self.path);
+        Self::apply_wine_env(&mut cmd, proton_path);
+        cmd.env("WINEPREFIX", prefix_path);
+        cmd.arg("-q");
+        cmd.args(verbs);
+        host::command(cmd)
+    }
+
+    /// Verbs winetricks has recorded in the prefix's `winetricks.log`.
+    pub fn installed_verbs(prefix_path: &Path) -> Vec<String> {
+        let content = match fs::read_to_string(prefix_path.join("winetricks.log")) {
+            Ok(content) => content,
+            Err(_) => return Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('-'))
+            .filter(|line| seen.insert(line.to_string()))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn apply_wine_env(cmd: &mut Command, proton_path: &Path) {
+        let bin_dir = proton_path.join("files").join("bin");
+        // Without a Proton build, as with the system Wine runner, winetricks
+        // finds wine on its own.
+        if !bin_dir.join("wine").is_file() {
+            return;
+        }
+        cmd.env("WINE", bin_dir.join("wine"));
+        cmd.env("WINESERVER", bin_dir.join("wineserver"));
+    }
+
+    fn parse_list(output: &str, category: VerbCategory) -> Vec<WinetricksVerb> {
+        output
+            .lines()
+            .filter(|line| !line.starts_with(char::is_whitespace) && !line.starts_with("Using"))
+            .filter_map(|line| {
+                let mut parts = line.splitn(2, char::is_whitespace);
+                let name = parts.next()?.trim();
+                if name.is_empty() || name.starts_with('=') {
+                    return None;
+                }
+                Some(WinetricksVerb {
+                    name: name.to_string(),
+                    description: parts.next().unwrap_or_default().trim().to_string(),
+                    category,
+                })
+            })
+            .collect()
+    }
+}
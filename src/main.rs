@@ -2,11 +2,13 @@ mod core;
 mod ui;
 mod utils;
 
+use core::umu_installer::UmuInstallMethod;
 use relm4::{RelmApp, set_global_css};
-use ui::main_window::MainWindow;
+use ui::main_window::{LaunchOptions, MainWindow};
 
 fn main() {
+    UmuInstallMethod::add_user_bin_to_path();
     let app = RelmApp::new("com.linuxboy.app");
     set_global_css(include_str!("ui/style.css"));
-    app.run::<MainWindow>(());
+    app.run::<MainWindow>(LaunchOptions::from_args());
 }
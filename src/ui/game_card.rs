@@ -0,0 +1,809 @@
+use gtk4::prelude::*;
+use gtk4::{Box, Button, CheckButton, Image, Label, MenuButton, Orientation, Popover, Widget};
+use relm4::factory::{DynamicIndex, FactoryComponent, FactorySender};
+use relm4::RelmWidgetExt;
+
+use crate::core::capsule::{Capsule, InstallState};
+use crate::core::protondb::{ProtonDb, ProtonDbTier};
+use crate::core::repair::RepairAction;
+use crate::ui::main_window::{MainWindowMsg, WineTool};
+use std::path::PathBuf;
+
+/// The parts of a capsule a card shows. Compared against the current
+/// capsule so a card only redraws when something it displays changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardInfo {
+    pub capsule_dir: PathBuf,
+    /// The display title.
+    pub name: String,
+    /// Developer and release year.
+    pub byline: Option<String>,
+    pub prefix_dir: PathBuf,
+    pub game_folder: Option<PathBuf>,
+    pub store: Option<String>,
+    pub installing: bool,
+    /// The game folder is packed away; playing unpacks it first.
+    pub archived: bool,
+    pub exe_missing: bool,
+    /// The last few launches all exited with an error.
+    pub crashing: bool,
+    /// Steam app ID and ProtonDB tier, once the rating has been fetched.
+    pub protondb: Option<(u32, ProtonDbTier)>,
+}
+
+impl CardInfo {
+    pub fn from_capsule(capsule: &Capsule) -> Self {
+        Self {
+            capsule_dir: capsule.capsule_dir.clone(),
+            name: capsule.metadata.title().to_string(),
+            byline: capsule.metadata.byline(),
+            prefix_dir: capsule.home_path.join("prefix"),
+            game_folder: capsule.game_folder(),
+            store: capsule
+                .metadata
+                .store
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string),
+            installing: capsule.metadata.install_state == InstallState::Installing,
+            archived: capsule.metadata.install_state == InstallState::Archived,
+            exe_missing: capsule.metadata.executables.main.path.trim().is_empty(),
+            crashing: false,
+            protondb: None,
+        }
+    }
+}
+
+/// Jobs the main window is running for a capsule, and how the library is
+/// laid out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CardState {
+    /// Big-screen tile with only the play controls.
+    pub tv_mode: bool,
+    /// The library is picking games for a batch action.
+    pub select_mode: bool,
+    pub selected: bool,
+    pub installer_running: bool,
+    pub preparing: bool,
+    pub deps_running: bool,
+    pub snapshot_running: bool,
+    pub winetricks_running: bool,
+    pub registry_running: bool,
+    pub shutdown_running: bool,
+    pub repair_running: bool,
+    pub uninstall_running: bool,
+    pub patch_running: bool,
+    pub mods_running: bool,
+    pub archive_running: bool,
+    pub sync_running: bool,
+    pub game_running: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum CardAction {
+    Details,
+    Edit,
+    Rename,
+    EditInfo,
+    Duplicate,
+    Move,
+    Export,
+    ExportAppImage,
+    ExportBundle,
+    AddToSteam,
+    OpenProtonDb,
+    OpenPrefixFolder,
+    OpenGameFolder,
+    ViewScreenshots,
+    WineTool(WineTool),
+    Repair(RepairAction),
+    Diagnose,
+    LaunchCommand,
+    RunInTerminal,
+    ClearLogs,
+    ShutDown,
+    InstallPatch,
+    Mods,
+    Uninstall,
+    Archive,
+    Delete,
+    KillInstall,
+    ResumeInstall,
+    FinishSetup,
+    Play,
+}
+
+#[derive(Debug)]
+pub enum GameCardMsg {
+    Action(CardAction),
+    Selected(bool),
+}
+
+/// One capsule in the library list.
+pub struct GameCard {
+    info: CardInfo,
+    state: CardState,
+}
+
+impl GameCard {
+    pub fn capsule_dir(&self) -> &PathBuf {
+        &self.info.capsule_dir
+    }
+
+    pub fn is_current(&self, info: &CardInfo, state: &CardState) -> bool {
+        &self.info == info && &self.state == state
+    }
+
+    pub fn set(&mut self, info: CardInfo, state: CardState) {
+        self.info = info;
+        self.state = state;
+    }
+
+    fn status_text(&self) -> &'static str {
+        if self.info.installing {
+            "Installing"
+        } else if self.info.archived {
+            "Archived"
+        } else {
+            "Installed"
+        }
+    }
+
+    fn status_class(&self) -> &'static str {
+        if self.info.installing {
+            "pill-warning"
+        } else if self.info.archived {
+            "pill-archived"
+        } else {
+            "pill-installed"
+        }
+    }
+
+    fn detail_text(&self) -> &'static str {
+        let state = &self.state;
+        if state.deps_running {
+            "Installing dependencies"
+        } else if state.snapshot_running {
+            "Working on prefix snapshot"
+        } else if state.winetricks_running {
+            "Running winetricks"
+        } else if state.registry_running {
+            "Updating registry"
+        } else if state.shutdown_running {
+            "Shutting down prefix"
+        } else if state.repair_running {
+            "Repairing prefix"
+        } else if state.uninstall_running {
+            "Running uninstaller"
+        } else if state.patch_running {
+            "Installing patch or DLC"
+        } else if state.mods_running {
+            "Updating mods"
+        } else if state.archive_running {
+            if self.info.archived {
+                "Unpacking game files"
+            } else {
+                "Packing game files"
+            }
+        } else if state.sync_running {
+            "Syncing saves"
+        } else if state.game_running {
+            "Game running"
+        } else if self.info.installing {
+            if state.preparing {
+                "Preparing runtime"
+            } else if state.installer_running {
+                "Installer running"
+            } else {
+                "Installer paused"
+            }
+        } else if self.info.archived {
+            "Game files packed to save space"
+        } else if self.info.exe_missing {
+            "Select executable to finish setup"
+        } else {
+            "Ready to play"
+        }
+    }
+
+    fn protondb_text(&self) -> String {
+        match self.info.protondb {
+            Some((_, tier)) => format!("ProtonDB: {}", tier.label()),
+            None => String::new(),
+        }
+    }
+
+    fn protondb_class(&self) -> &'static str {
+        self.info
+            .protondb
+            .map(|(_, tier)| tier.css_class())
+            .unwrap_or_default()
+    }
+
+    fn store_text(&self) -> String {
+        format!("Store: {}", self.info.store.as_deref().unwrap_or_default())
+    }
+
+    /// Style a popover entry and make it close the popover when clicked.
+    fn connect_popover_item(
+        popover: &Popover,
+        button: &Button,
+        sender: &FactorySender<Self>,
+        action: CardAction,
+    ) {
+        Self::style_popover_item(popover, button);
+        let sender = sender.clone();
+        button.connect_clicked(move |_| sender.input(GameCardMsg::Action(action)));
+    }
+
+    fn style_popover_item(popover: &Popover, button: &Button) {
+        button.add_css_class("flat");
+        button.set_halign(gtk4::Align::Fill);
+        if let Some(child) = button.child() {
+            child.set_halign(gtk4::Align::Start);
+        }
+        let popover = popover.clone();
+        button.connect_clicked(move |_| popover.popdown());
+    }
+
+    /// Style every button below `widget` as a popover entry; the buttons
+    /// send their own actions.
+    fn close_on_click(popover: &Popover, widget: &Widget) {
+        let mut child = widget.first_child();
+        while let Some(current) = child {
+            match current.downcast_ref::<Button>() {
+                Some(button) => Self::style_popover_item(popover, button),
+                None => Self::close_on_click(popover, &current),
+            }
+            child = current.next_sibling();
+        }
+    }
+
+    fn append_popover_item(
+        popover: &Popover,
+        list: &Box,
+        label: &str,
+        sender: &FactorySender<Self>,
+        action: CardAction,
+    ) {
+        let button = Button::with_label(label);
+        Self::connect_popover_item(popover, &button, sender, action);
+        list.append(&button);
+    }
+}
+
+#[relm4::factory(pub)]
+impl FactoryComponent for GameCard {
+    type Init = (CardInfo, CardState);
+    type Input = GameCardMsg;
+    type Output = MainWindowMsg;
+    type CommandOutput = ();
+    type ParentWidget = Box;
+
+    view! {
+        #[root]
+        Box {
+            set_orientation: Orientation::Vertical,
+            set_spacing: 8,
+            set_margin_bottom: 12,
+            set_hexpand: true,
+            set_css_classes: &["card"],
+            #[watch]
+            set_class_active: ("tv-tile", self.state.tv_mode),
+            #[watch]
+            set_class_active: ("card-selected", self.state.select_mode && self.state.selected),
+
+            append = &Box {
+                set_orientation: Orientation::Horizontal,
+                set_spacing: 10,
+                set_hexpand: true,
+
+                append = &CheckButton {
+                    #[watch]
+                    set_visible: self.state.select_mode,
+                    #[watch]
+                    set_active: self.state.selected,
+                    set_tooltip_text: Some("Select for a batch action"),
+                    connect_toggled[sender] => move |check| {
+                        sender.input(GameCardMsg::Selected(check.is_active()));
+                    },
+                },
+
+                append = &Image {
+                    set_icon_name: Some("applications-games-symbolic"),
+                    #[watch]
+                    set_pixel_size: if self.state.tv_mode { 64 } else { 24 },
+                    set_halign: gtk4::Align::Start,
+                },
+
+                append = &Label {
+                    #[watch]
+                    set_label: &self.info.name,
+                    set_halign: gtk4::Align::Start,
+                    set_hexpand: true,
+                    set_css_classes: &["card-title"],
+                },
+
+                append = &Box {
+                    set_hexpand: true,
+                },
+
+                append = &Button {
+                    #[watch]
+                    set_label: &self.protondb_text(),
+                    #[watch]
+                    set_visible: self.info.protondb.is_some(),
+                    #[watch]
+                    set_css_classes: &["flat", "pill", self.protondb_class()],
+                    set_tooltip_text: Some("Open this game's reports on ProtonDB"),
+                    connect_clicked => GameCardMsg::Action(CardAction::OpenProtonDb),
+                },
+
+                append = &Label {
+                    set_label: "Crashing",
+                    set_css_classes: &["pill", "pill-missing"],
+                    set_tooltip_text: Some("The last three launches exited with an error"),
+                    #[watch]
+                    set_visible: self.info.crashing,
+                },
+
+                append = &Label {
+                    #[watch]
+                    set_label: self.status_text(),
+                    #[watch]
+                    set_css_classes: &["pill", self.status_class()],
+                },
+            },
+
+            append = &Label {
+                #[watch]
+                set_label: self.detail_text(),
+                set_css_classes: &["muted"],
+                set_halign: gtk4::Align::Start,
+                set_margin_top: 2,
+            },
+
+            append = &Label {
+                #[watch]
+                set_label: self.info.byline.as_deref().unwrap_or_default(),
+                #[watch]
+                set_visible: self.info.byline.is_some(),
+                set_css_classes: &["muted"],
+                set_halign: gtk4::Align::Start,
+            },
+
+            append = &Label {
+                #[watch]
+                set_label: &self.store_text(),
+                #[watch]
+                set_visible: self.info.store.is_some(),
+                set_css_classes: &["muted"],
+                set_halign: gtk4::Align::Start,
+            },
+
+            append = &Box {
+                set_orientation: Orientation::Horizontal,
+                set_spacing: 8,
+                set_halign: gtk4::Align::Start,
+
+                // Library management stays on the desktop layout. Everything
+                // but Edit sits in the More menu so the card stays narrow.
+                append = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 8,
+                    #[watch]
+                    set_visible: !self.state.tv_mode,
+
+                    append = &Button {
+                        set_label: "Edit",
+                        add_css_class: "flat",
+                        connect_clicked => GameCardMsg::Action(CardAction::Edit),
+                    },
+
+                    append = &MenuButton {
+                        set_label: "More",
+                        add_css_class: "flat",
+                        set_tooltip_text: Some("Everything else you can do with this game"),
+
+                        #[wrap(Some)]
+                        #[name = "more_popover"]
+                        set_popover = &Popover {
+                            #[wrap(Some)]
+                            #[name = "more_menu"]
+                            set_child = &Box {
+                                set_orientation: Orientation::Horizontal,
+                                set_spacing: 12,
+
+                                append = &Box {
+                                    set_orientation: Orientation::Vertical,
+                                    set_spacing: 2,
+
+                                    append = &Label {
+                                        set_label: "Library",
+                                        set_css_classes: &["muted"],
+                                        set_halign: gtk4::Align::Start,
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Details",
+                                        connect_clicked => GameCardMsg::Action(CardAction::Details),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Rename",
+                                        connect_clicked => GameCardMsg::Action(CardAction::Rename),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Title & info...",
+                                        set_tooltip_text: Some(
+                                            "Change how the game is shown and sorted",
+                                        ),
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::EditInfo),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Duplicate",
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::Duplicate),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Move...",
+                                        connect_clicked => GameCardMsg::Action(CardAction::Move),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Export...",
+                                        connect_clicked => GameCardMsg::Action(CardAction::Export),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Export as AppImage...",
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::ExportAppImage),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Portable bundle...",
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::ExportBundle),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Add to Steam",
+                                        set_tooltip_text: Some(
+                                            "Start this game from your Steam library",
+                                        ),
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::AddToSteam),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Delete",
+                                        add_css_class: "destructive-action",
+                                        connect_clicked => GameCardMsg::Action(CardAction::Delete),
+                                    },
+                                },
+
+                                append = &Box {
+                                    set_orientation: Orientation::Vertical,
+                                    set_spacing: 2,
+
+                                    append = &Label {
+                                        set_label: "Game",
+                                        set_css_classes: &["muted"],
+                                        set_halign: gtk4::Align::Start,
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Open prefix folder",
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::OpenPrefixFolder),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Open game folder",
+                                        #[watch]
+                                        set_visible: self.info.game_folder.is_some(),
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::OpenGameFolder),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "View screenshots",
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::ViewScreenshots),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Diagnose",
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::Diagnose),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Show launch command",
+                                        set_tooltip_text: Some(
+                                            "The command and environment Play would use, without \
+                                             starting the game",
+                                        ),
+                                        #[watch]
+                                        set_visible: !self.info.installing,
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::LaunchCommand),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Run in terminal",
+                                        set_tooltip_text: Some(
+                                            "Start the game once in a terminal window to watch \
+                                             Wine's output live",
+                                        ),
+                                        #[watch]
+                                        set_visible: !self.info.installing
+                                            && !self.info.exe_missing
+                                            && !self.info.archived
+                                            && !self.state.game_running,
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::RunInTerminal),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Clear logs",
+                                        set_tooltip_text: Some("Remove the game's launch logs"),
+                                        #[watch]
+                                        set_visible: !self.info.installing
+                                            && !self.state.game_running,
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::ClearLogs),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Shut down prefix",
+                                        set_tooltip_text: Some(
+                                            "Stop every Wine process still running in this \
+                                             capsule's prefix",
+                                        ),
+                                        #[watch]
+                                        set_visible: !self.state.preparing
+                                            && !self.state.shutdown_running,
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::ShutDown),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Install patch/DLC...",
+                                        set_tooltip_text: Some(
+                                            "Run an update or DLC installer in this game's prefix",
+                                        ),
+                                        #[watch]
+                                        set_visible: !self.info.installing
+                                            && !self.info.archived
+                                            && !self.state.patch_running,
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::InstallPatch),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Mods...",
+                                        set_tooltip_text: Some(
+                                            "Turn mods dropped into the capsule's mods folder on \
+                                             and off",
+                                        ),
+                                        #[watch]
+                                        set_visible: !self.info.installing && !self.info.archived,
+                                        connect_clicked => GameCardMsg::Action(CardAction::Mods),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Uninstall...",
+                                        set_tooltip_text: Some(
+                                            "Run the game's own uninstaller and keep the capsule \
+                                             for a reinstall",
+                                        ),
+                                        #[watch]
+                                        set_visible: !self.info.installing
+                                            && !self.info.archived
+                                            && !self.state.uninstall_running,
+                                        connect_clicked =>
+                                            GameCardMsg::Action(CardAction::Uninstall),
+                                    },
+
+                                    append = &Button {
+                                        set_label: "Archive to disk",
+                                        set_tooltip_text: Some(
+                                            "Pack the game folder into a compressed archive until \
+                                             the next launch",
+                                        ),
+                                        #[watch]
+                                        set_visible: !self.info.installing
+                                            && !self.info.archived
+                                            && !self.state.archive_running,
+                                        connect_clicked => GameCardMsg::Action(CardAction::Archive),
+                                    },
+                                },
+
+                                append = &Box {
+                                    set_orientation: Orientation::Vertical,
+                                    set_spacing: 2,
+                                    #[watch]
+                                    set_visible: !self.state.preparing,
+
+                                    append = &Label {
+                                        set_label: "Wine tools",
+                                        set_css_classes: &["muted"],
+                                        set_halign: gtk4::Align::Start,
+                                    },
+
+                                    #[name = "tools_list"]
+                                    append = &Box {
+                                        set_orientation: Orientation::Vertical,
+                                        set_spacing: 2,
+                                    },
+
+                                    append = &Label {
+                                        set_label: "Repair",
+                                        set_css_classes: &["muted"],
+                                        set_halign: gtk4::Align::Start,
+                                        set_margin_top: 8,
+                                    },
+
+                                    #[name = "repair_list"]
+                                    append = &Box {
+                                        set_orientation: Orientation::Vertical,
+                                        set_spacing: 2,
+                                    },
+                                },
+                            },
+                        },
+                    },
+                },
+
+                append = &Button {
+                    set_label: "Kill installer",
+                    add_css_class: "destructive-action",
+                    #[watch]
+                    set_visible: self.info.installing && self.state.installer_running,
+                    connect_clicked => GameCardMsg::Action(CardAction::KillInstall),
+                },
+
+                append = &Button {
+                    set_label: "Resume setup",
+                    add_css_class: "suggested-action",
+                    #[watch]
+                    set_visible: self.info.installing
+                        && !self.state.installer_running
+                        && !self.state.preparing,
+                    connect_clicked => GameCardMsg::Action(CardAction::ResumeInstall),
+                },
+
+                append = &Button {
+                    set_label: "Finish setup",
+                    add_css_class: "flat",
+                    #[watch]
+                    set_visible: self.info.installing
+                        && !self.state.installer_running
+                        && !self.state.preparing,
+                    connect_clicked => GameCardMsg::Action(CardAction::FinishSetup),
+                },
+
+                append = &Button {
+                    add_css_class: "suggested-action",
+                    #[watch]
+                    set_label: if self.state.game_running {
+                        "Running"
+                    } else if self.info.archived {
+                        "Unpack and play"
+                    } else {
+                        "Play"
+                    },
+                    #[watch]
+                    set_sensitive: !self.state.game_running && !self.state.archive_running,
+                    #[watch]
+                    set_visible: !self.info.installing && !self.info.exe_missing,
+                    connect_clicked => GameCardMsg::Action(CardAction::Play),
+                },
+            },
+        }
+    }
+
+    fn init_model(init: Self::Init, _index: &DynamicIndex, _sender: FactorySender<Self>) -> Self {
+        let (info, state) = init;
+        Self { info, state }
+    }
+
+    fn init_widgets(
+        &mut self,
+        _index: &DynamicIndex,
+        root: Self::Root,
+        _returned_widget: &Widget,
+        sender: FactorySender<Self>,
+    ) -> Self::Widgets {
+        let widgets = view_output!();
+
+        Self::close_on_click(&widgets.more_popover, widgets.more_menu.upcast_ref());
+        for tool in WineTool::ALL {
+            Self::append_popover_item(
+                &widgets.more_popover,
+                &widgets.tools_list,
+                tool.label(),
+                &sender,
+                CardAction::WineTool(tool),
+            );
+        }
+        for action in RepairAction::ALL {
+            Self::append_popover_item(
+                &widgets.more_popover,
+                &widgets.repair_list,
+                action.label(),
+                &sender,
+                CardAction::Repair(action),
+            );
+        }
+
+        widgets
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: FactorySender<Self>) {
+        match msg {
+            GameCardMsg::Selected(selected) => {
+                if selected != self.state.selected {
+                    let _ = sender.output(MainWindowMsg::SetGameSelected {
+                        capsule_dir: self.info.capsule_dir.clone(),
+                        selected,
+                    });
+                }
+            }
+            GameCardMsg::Action(action) => {
+                let capsule_dir = self.info.capsule_dir.clone();
+                let output = match action {
+                    CardAction::Details => MainWindowMsg::OpenGameDetails(capsule_dir),
+                    CardAction::Edit => MainWindowMsg::EditGame(capsule_dir),
+                    CardAction::Rename => MainWindowMsg::RenameCapsule(capsule_dir),
+                    CardAction::EditInfo => MainWindowMsg::EditGameInfo(capsule_dir),
+                    CardAction::Duplicate => MainWindowMsg::DuplicateCapsule(capsule_dir),
+                    CardAction::Move => MainWindowMsg::MoveCapsule(capsule_dir),
+                    CardAction::Export => MainWindowMsg::ExportCapsule(capsule_dir),
+                    CardAction::ExportAppImage => MainWindowMsg::ExportAppImage(capsule_dir),
+                    CardAction::ExportBundle => MainWindowMsg::ExportPortableBundle(capsule_dir),
+                    CardAction::AddToSteam => MainWindowMsg::AddToSteam(capsule_dir),
+                    CardAction::OpenProtonDb => match self.info.protondb {
+                        Some((app_id, _)) => MainWindowMsg::OpenUrl(ProtonDb::report_url(app_id)),
+                        None => return,
+                    },
+                    CardAction::OpenPrefixFolder => {
+                        MainWindowMsg::OpenFolder(self.info.prefix_dir.clone())
+                    }
+                    CardAction::OpenGameFolder => match &self.info.game_folder {
+                        Some(folder) => MainWindowMsg::OpenFolder(folder.clone()),
+                        None => return,
+                    },
+                    CardAction::ViewScreenshots => MainWindowMsg::ViewScreenshots(capsule_dir),
+                    CardAction::WineTool(tool) => MainWindowMsg::RunWineTool { capsule_dir, tool },
+                    CardAction::Repair(action) => MainWindowMsg::RepairRequested {
+                        capsule_dir,
+                        action,
+                    },
+                    CardAction::Diagnose => MainWindowMsg::OpenDiagnostics(capsule_dir),
+                    CardAction::LaunchCommand => MainWindowMsg::ShowLaunchCommand(capsule_dir),
+                    CardAction::RunInTerminal => MainWindowMsg::LaunchGameInTerminal(capsule_dir),
+                    CardAction::ClearLogs => MainWindowMsg::ClearLogs(capsule_dir),
+                    CardAction::ShutDown => MainWindowMsg::ShutDownPrefix(capsule_dir),
+                    CardAction::InstallPatch => MainWindowMsg::InstallPatch(capsule_dir),
+                    CardAction::Mods => MainWindowMsg::OpenMods(capsule_dir),
+                    CardAction::Uninstall => MainWindowMsg::UninstallGame(capsule_dir),
+                    CardAction::Archive => MainWindowMsg::ArchiveGame(capsule_dir),
+                    CardAction::Delete => MainWindowMsg::DeleteGame(capsule_dir),
+                    CardAction::KillInstall => MainWindowMsg::KillInstall(capsule_dir),
+                    CardAction::ResumeInstall => MainWindowMsg::ResumeInstall(capsule_dir),
+                    CardAction::FinishSetup => MainWindowMsg::MarkInstallComplete(capsule_dir),
+                    CardAction::Play => MainWindowMsg::LaunchGame(capsule_dir),
+                };
+                let _ = sender.output(output);
+            }
+        }
+    }
+}
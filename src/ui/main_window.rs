@@ -1,40 +1,156 @@
 use gtk4::prelude::*;
+use gtk4::gio;
 use gtk4::{
-    ApplicationWindow, Box, Button, CheckButton, Dialog, Entry, FileChooserAction,
-    FileChooserNative, FileFilter, Image, Label, ListBox, ListBoxRow, Orientation, ResponseType,
-    ScrolledWindow, SelectionMode,
+    ApplicationWindow, Box, Button, CheckButton, Dialog, DirectionType, DropDown, Entry, Expander,
+    FileChooserAction, FileChooserNative, FileFilter, Grid, Image, Label, ListBox, ListBoxRow,
+    MenuButton, Notebook, Picture,
+    Orientation, Popover, ProgressBar, ResponseType, ScrolledWindow, SearchEntry, SelectionMode,
+    SpinButton, TextBuffer, TextTag, TextView, WrapMode,
 };
 use relm4::{Component, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
 use relm4::component::{ComponentController, Controller};
-
-use crate::core::capsule::{Capsule, CapsuleMetadata, InstallState};
+use relm4::factory::FactoryVecDeque;
+
+use crate::core::appimage_builder::AppImageBuilder;
+use crate::core::archival::{ArchiveInfo, GameArchive};
+use crate::core::backup::{BackupManager, Codec, CompressionOptions, VerifyReport};
+use crate::core::capsule::{
+    AppliedPatch, Capsule, CapsuleMetadata, DllOverride, DllOverrideMode, InstallState,
+    WINEDEBUG_CHANNELS,
+};
+use crate::core::cloud_sync::{
+    CloudBackend, CloudSync, CloudSyncConfig, SyncDirection, SyncOutcome,
+};
+use crate::core::dedupe::{DedupeOptions, DedupeOutcome, DedupeReport, PrefixDedupe};
+use crate::core::duplicates::{Duplicate, DuplicateFinder};
+use crate::core::diagnostics::{DiagnosticFix, PrefixDoctor, Severity};
+use crate::core::discord::{Activity, DiscordPresence, DiscordSettings};
+use crate::core::display_server::SDL_VIDEO_DRIVERS;
+use crate::core::gamepad::{GamepadAction, GamepadWatcher};
+use crate::core::heroic::{HeroicGame, HeroicLibrary};
+use crate::core::howlongtobeat::{HltbLookup, HowLongToBeat};
+use crate::core::igdb::{Igdb, IgdbInfo, IgdbSettings};
+use crate::core::installer_source::{InstallerCheck, InstallerSource};
+use crate::core::integrity::{IntegrityChecker, IntegrityReport};
+use crate::core::network::ProxySettings;
+use crate::core::package_manager::GamingTool;
+use crate::core::play_history::{self, PlayHistory};
+use crate::core::portable::PortableBundle;
+use crate::core::prefix_processes::PrefixProcesses;
+use crate::core::protondb::{ProtonDb, ProtonDbRating};
+use crate::core::redist::{Category, InstallMethod, Redistributable, DIRECTX_ID, VCREDIST_ID};
+use crate::core::protonfixes::{
+    python_syntax, LocalFix, ProtonFixSummary, ProtonFixes, SyntaxKind,
+};
+use crate::core::registry::{PrefixRegistry, RegistryTweaks};
+use crate::core::repair::{RepairAction, RepairStep, RepairTools};
+use crate::core::sandbox::Sandbox;
 use crate::core::runtime_manager::RuntimeManager;
+use crate::core::saves::{SaveManager, SaveSource};
+use crate::core::screenshots::{Screenshots, GAMESCOPE_SCREENSHOT_KEYS};
+use crate::core::shell_link::ShellLink;
+use crate::core::snapshots::{SnapshotInfo, SnapshotManager};
+use crate::core::steam_deck::{DeckSettings, SteamDeck};
+use crate::core::steam_shortcuts::SteamShortcuts;
+use crate::core::support_bundle::SupportBundle;
+use crate::core::terminal::Terminal;
+use crate::core::new_game_defaults::NewGameDefaults;
+use crate::core::removal::{CapsuleRemoval, RemovalOptions};
+use crate::core::launch_log::{LaunchLog, LogSettings};
+use crate::core::launch_preview::LaunchPreview;
+use crate::core::mods::{DeployMode, ModEntry, ModManager, ARCHIVE_SUFFIXES};
+use crate::core::runner::{split_words, CommandTemplate, FallbackRunner, Runner, RunnerSettings};
+use crate::core::uninstaller::{Uninstaller, Uninstallers};
 use crate::core::system_checker::{SystemCheck, SystemStatus};
+use crate::core::templates::{ConfigTemplate, ConfigTemplates, LaunchProfile};
+use crate::core::title_match::TitleMatcher;
 use crate::core::umu_database::{UmuDatabase, UmuEntry};
+use crate::core::winetricks::{VerbCategory, Winetricks, WinetricksVerb};
+use crate::ui::game_card::{CardInfo, CardState, GameCard};
 use crate::ui::system_setup_dialog::{SystemSetupDialog, SystemSetupMsg, SystemSetupOutput};
-use std::cell::Cell;
+use crate::utils::file_ops;
+use crate::utils::host;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
 use std::os::unix::process::CommandExt;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::rc::Rc;
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::{fs, io, thread};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{fs, thread};
 use walkdir::WalkDir;
 
+/// Free space required before running an installer, as a multiple of its size.
+const INSTALLER_SPACE_FACTOR: u64 = 2;
+/// Cached executable candidates, in the capsule's home folder.
+const EXE_SCAN_CACHE_FILE: &str = "exe-candidates.json";
+/// Executables scoring this close to the best one make the guess ambiguous.
+const EXE_GUESS_MARGIN: i32 = 15;
+/// Most candidates listed in the executable picker.
+const EXE_PICKER_LIMIT: usize = 8;
+/// Score added for the executable an installer registered as its icon.
+const REGISTRY_ICON_BONUS: i32 = 60;
+/// Score added for executables inside a registered install location.
+const INSTALL_LOCATION_BONUS: i32 = 35;
+/// A failed game that ran for less than this crashed on start.
+const QUICK_CRASH_SECS: i64 = 15;
+/// How often the launch log of a running game is checked against its size limit.
+const LOG_CHECK_SECS: u64 = 5;
+
 #[derive(Debug)]
 pub enum MainWindowMsg {
     LoadCapsules,
+    CapsulesScanned {
+        generation: u64,
+        capsules: Vec<Capsule>,
+        first: bool,
+    },
+    CapsuleScanFinished {
+        generation: u64,
+        result: Result<(usize, Vec<PathBuf>), String>,
+    },
     OpenAddGame,
     AddGameModeChosen(AddGameMode),
     OpenSystemSetup,
     GamePathSelected(PathBuf),
     AddGameCancelled,
+    DuplicateGameChosen {
+        capsule_dir: PathBuf,
+        choice: DuplicateChoice,
+    },
     ExistingSourceFolderSelected(PathBuf),
     ExistingSourceFolderCancelled,
-    ExistingGameLocationConfirmed(String),
+    ExistingGameLocationConfirmed {
+        folder: String,
+        transfer: ExistingGameTransfer,
+    },
     ExistingGameLocationCancelled,
+    PrefixImportConfirmed(ExistingGameTransfer),
+    PrefixImportCancelled,
+    PrefixImportFinished {
+        job: PrefixImportJob,
+        result: Result<(), String>,
+    },
+    CopyProgress {
+        copied: u64,
+        total: u64,
+    },
+    ExistingGameCopyFinished {
+        job: ExistingGameJob,
+        result: Result<(), String>,
+    },
     GameNameConfirmed(String),
+    MsiOptionsConfirmed {
+        silent: bool,
+        properties: Vec<String>,
+    },
+    PrefixArchChosen {
+        win32: bool,
+    },
     InstallerStarted {
         capsule_dir: PathBuf,
         pgid: i32,
@@ -50,31 +166,87 @@ pub enum MainWindowMsg {
         store: Option<String>,
     },
     UmuMatchDialogClosed,
+    LoadProtonFixes(UmuEntry),
+    ProtonFixesLoaded {
+        key: String,
+        result: Result<Option<ProtonFixSummary>, String>,
+    },
     SaveGameSettings {
         capsule_dir: PathBuf,
         exe_path: String,
+        working_dir: Option<String>,
         game_id: Option<String>,
         store: Option<String>,
         install_vcredist: bool,
         install_dxweb: bool,
+        install_redistributables: Vec<String>,
         protonfixes_disable: bool,
         xalia_enabled: bool,
-        protonfixes_tricks: Vec<String>,
+        gamemode_enabled: bool,
+        mangohud_enabled: bool,
+        fps_limit: Option<u32>,
+        gamescope_enabled: bool,
+        gamescope_hdr: bool,
+        gamescope_vrr: bool,
+        screenshots_dir: Option<String>,
+        proton_wayland: bool,
+        force_x11: bool,
+        sdl_video_driver: Option<String>,
+        sandbox_enabled: bool,
+        sandbox_network: bool,
+        block_network: bool,
+        command_template: Option<String>,
+        wine_debug: Option<String>,
+        wine_debug_once: bool,
         protonfixes_replace_cmds: Vec<String>,
         protonfixes_dxvk_sets: Vec<String>,
+        dll_overrides: Vec<DllOverride>,
+        virtual_desktop: Option<String>,
+        locale: Option<String>,
+        lc_all: Option<String>,
+        timezone: Option<String>,
+        cloud_sync: bool,
     },
     SettingsDialogClosed,
+    /// `None` when the user wants to enter the executable in settings.
+    ExecutableChosen {
+        capsule_dir: PathBuf,
+        choice: Option<ExecutableGuess>,
+    },
+    ExecutablePickerClosed,
+    RescanExecutables(PathBuf),
+    ExecutablesRescanned {
+        capsule_dir: PathBuf,
+        path: Option<PathBuf>,
+    },
     DependenciesSelected {
         capsule_dir: PathBuf,
         install_vcredist: bool,
         install_dxweb: bool,
+        install_redistributables: Vec<String>,
+    },
+    OpenDependencies(PathBuf),
+    /// Install one redistributable again from the dependency dialog;
+    /// `force` runs its installer even when it looks installed.
+    InstallDependency {
+        capsule_dir: PathBuf,
+        id: String,
         force: bool,
     },
+    DependencyStep {
+        label: String,
+        fraction: f64,
+    },
+    DependencyLog(String),
+    CancelDependencyInstall,
     DependenciesFinished {
         capsule_dir: PathBuf,
         installed: Vec<String>,
+        failed: Vec<String>,
+        cancelled: bool,
     },
     DependenciesDialogClosed,
+    DependencyProgressClosed,
     GameStarted {
         capsule_dir: PathBuf,
         pgid: i32,
@@ -84,43 +256,499 @@ pub enum MainWindowMsg {
         success: bool,
     },
     LaunchGame(PathBuf),
+    /// Launch once with troubleshooting settings after a crash.
+    LaunchGameSafe(PathBuf),
+    /// Launch once in a terminal window to watch Wine's output.
+    LaunchGameInTerminal(PathBuf),
+    /// The prefix was snapshotted before its first launch on `runtime`;
+    /// record the runtime and carry on launching.
+    RuntimeSnapshotFinished {
+        capsule_dir: PathBuf,
+        runtime: String,
+        safe_mode: bool,
+        in_terminal: bool,
+    },
+    SafeRetryDialogClosed,
+    SaveTemplatePrompt(PathBuf),
+    SaveTemplate {
+        capsule_dir: PathBuf,
+        name: String,
+    },
+    TemplateNameDialogClosed,
+    ApplyTemplate {
+        capsule_dir: PathBuf,
+        name: String,
+    },
+    DeleteTemplate(String),
+    DefaultTemplateChosen(Option<String>),
+    NewGameDefaultsSaved(NewGameDefaults),
+    ToggleSelectMode,
+    SetGameSelected {
+        capsule_dir: PathBuf,
+        selected: bool,
+    },
+    SelectAllGames,
+    BatchBackup,
+    BatchBackupFolderSelected(PathBuf),
+    BatchBackupFinished(Result<String, String>),
+    BatchApplyTemplate(String),
+    BatchDelete,
     EditGame(PathBuf),
     DeleteGame(PathBuf),
+    DeleteConfirmed {
+        capsule_dirs: Vec<PathBuf>,
+        options: RemovalOptions,
+        trash: bool,
+    },
+    CapsuleRemovalFinished(Result<String, String>),
+    DeleteDialogClosed,
+    InstallPatch(PathBuf),
+    RunPatchInstaller {
+        capsule_dir: PathBuf,
+        installer_path: PathBuf,
+    },
+    PatchInstallerFinished {
+        capsule_dir: PathBuf,
+        patch: AppliedPatch,
+    },
+    OpenMods(PathBuf),
+    ModsLoaded {
+        capsule_dir: PathBuf,
+        result: Result<Vec<ModEntry>, String>,
+        status: Option<String>,
+    },
+    ToggleMod {
+        capsule_dir: PathBuf,
+        id: String,
+        enable: bool,
+    },
+    ModsDialogClosed,
+    UninstallGame(PathBuf),
+    RunUninstaller {
+        capsule_dir: PathBuf,
+        uninstaller: Uninstaller,
+    },
+    UninstallerFinished {
+        capsule_dir: PathBuf,
+        result: Result<bool, String>,
+    },
+    UninstallDialogClosed,
+    ArchiveGame(PathBuf),
+    ArchiveFinished {
+        capsule_dir: PathBuf,
+        result: Result<ArchiveInfo, String>,
+    },
+    UnpackFinished {
+        capsule_dir: PathBuf,
+        result: Result<(), String>,
+        play: bool,
+    },
+    MoveCapsule(PathBuf),
+    MoveCapsuleTargetSelected {
+        capsule_dir: PathBuf,
+        target_dir: PathBuf,
+    },
+    CapsuleMoveFinished {
+        capsule_dir: PathBuf,
+        result: Result<PathBuf, String>,
+    },
+    CapsuleFolderDialogClosed,
+    RenameCapsule(PathBuf),
+    RenameCapsuleConfirmed {
+        capsule_dir: PathBuf,
+        new_name: String,
+    },
+    RenameDialogClosed,
+    EditGameInfo(PathBuf),
+    GameInfoSaved {
+        capsule_dir: PathBuf,
+        display_title: Option<String>,
+        sort_title: Option<String>,
+        release_year: Option<u32>,
+        developer: Option<String>,
+    },
+    GameInfoDialogClosed,
+    DuplicateCapsule(PathBuf),
+    DuplicateCapsuleConfirmed {
+        capsule_dir: PathBuf,
+        new_name: String,
+        share_game_files: bool,
+    },
+    DuplicateDialogClosed,
+    CapsuleDuplicateFinished(Result<PathBuf, String>),
+    OpenSnapshots(PathBuf),
+    RecordFileManifest(PathBuf),
+    FileManifestRecorded(Result<usize, String>),
+    VerifyGameFiles(PathBuf),
+    GameFilesVerified(Result<IntegrityReport, String>),
+    OpenDiagnostics(PathBuf),
+    ShowLaunchCommand(PathBuf),
+    ClearLogs(PathBuf),
+    LaunchCommandDialogClosed,
+    OpenGameDetails(PathBuf),
+    GameDetailsClosed,
+    HowLongToBeatFetched {
+        capsule_dir: PathBuf,
+        result: Result<HltbLookup, String>,
+    },
+    IgdbFetched {
+        capsule_dir: PathBuf,
+        result: Result<Option<IgdbInfo>, String>,
+    },
+    GenerateSupportBundle {
+        capsule_dir: PathBuf,
+        dest: PathBuf,
+    },
+    SupportBundleFinished(Result<PathBuf, String>),
+    DiagnosticsDialogClosed,
+    ApplyDiagnosticFix {
+        capsule_dir: PathBuf,
+        fix: DiagnosticFix,
+    },
+    RepairRequested {
+        capsule_dir: PathBuf,
+        action: RepairAction,
+    },
+    RepairConfirmed {
+        capsule_dir: PathBuf,
+        action: RepairAction,
+    },
+    RepairDialogClosed,
+    RepairProgress {
+        step: String,
+        fraction: f64,
+    },
+    PrefixRepairFinished {
+        capsule_dir: PathBuf,
+        result: Result<String, String>,
+    },
+    SnapshotRequested {
+        capsule_dir: PathBuf,
+        action: SnapshotAction,
+    },
+    SnapshotFinished {
+        capsule_dir: PathBuf,
+        result: Result<String, String>,
+    },
+    SnapshotsDialogClosed,
+    OpenLocalFix(PathBuf),
+    /// Write the game's local protonfix; empty source removes it.
+    SaveLocalFix {
+        capsule_dir: PathBuf,
+        source: String,
+    },
+    LocalFixDialogClosed,
+    OpenWinetricks(PathBuf),
+    WinetricksVerbsLoaded(Result<Vec<WinetricksVerb>, String>),
+    RunWinetricks {
+        capsule_dir: PathBuf,
+        verbs: Vec<String>,
+    },
+    WinetricksLog(String),
+    WinetricksFinished {
+        capsule_dir: PathBuf,
+        verbs: Vec<String>,
+        success: bool,
+    },
+    WinetricksDialogClosed,
+    ImportRegistryFile {
+        capsule_dir: PathBuf,
+        path: PathBuf,
+    },
+    ApplyRegistryTweaks {
+        capsule_dir: PathBuf,
+        current: RegistryTweaks,
+        desired: RegistryTweaks,
+    },
+    RegistryJobFinished {
+        capsule_dir: PathBuf,
+        result: Result<String, String>,
+    },
+    ExportSaves {
+        capsule_dir: PathBuf,
+        dest: PathBuf,
+    },
+    ImportSaves {
+        capsule_dir: PathBuf,
+        archive: PathBuf,
+    },
+    SavesTransferFinished(Result<String, String>),
+    OpenHeroicImport,
+    HeroicGameChosen {
+        game: HeroicGame,
+        copy_prefix: bool,
+    },
+    HeroicDialogClosed,
+    HeroicImportFinished(Result<PathBuf, String>),
+    ExportCapsule(PathBuf),
+    ExportOptionsConfirmed {
+        capsule_dir: PathBuf,
+        compression: CompressionOptions,
+        incremental: bool,
+    },
+    ExportDestinationSelected {
+        capsule_dir: PathBuf,
+        dest: PathBuf,
+        compression: CompressionOptions,
+        incremental: bool,
+    },
+    ExportDialogClosed,
+    CapsuleExportFinished(Result<String, String>),
+    ExportAppImage(PathBuf),
+    AppImageDestinationSelected {
+        capsule_dir: PathBuf,
+        dest: PathBuf,
+    },
+    AppImageExportFinished(Result<String, String>),
+    ExportPortableBundle(PathBuf),
+    PortableBundleTargetSelected {
+        capsule_dir: PathBuf,
+        parent: PathBuf,
+    },
+    PortableBundleExportFinished(Result<String, String>),
+    ImportPortableBundle,
+    PortableBundleSelected(PathBuf),
+    ImportCapsule,
+    ImportArchiveSelected(PathBuf),
+    CapsuleImportFinished(Result<PathBuf, String>),
+    VerifyBackup,
+    VerifyArchiveSelected(PathBuf),
+    BackupVerifyFinished(Result<VerifyReport, String>),
+    BackupFileDialogClosed,
+    OpenCloudSyncSettings,
+    CloudSyncSettingsSaved(CloudSyncConfig),
+    CloudSyncDialogClosed,
+    OpenNetworkSettings,
+    NetworkSettingsSaved(ProxySettings),
+    NetworkDialogClosed,
+    OpenStorage,
+    DedupeScanProgress {
+        hashed: u64,
+        total: u64,
+    },
+    DedupeScanFinished(Result<DedupeReport, String>),
+    DedupeRequested(DedupeOptions),
+    DedupeFinished(Result<DedupeOutcome, String>),
+    StorageDialogClosed,
+    RecoverCapsuleMetadata(PathBuf),
+    MetadataRecoveryDialogClosed,
+    SyncSaves {
+        capsule_dir: PathBuf,
+        direction: SyncDirection,
+    },
+    CloudSyncFinished {
+        capsule_dir: PathBuf,
+        result: Result<SyncOutcome, String>,
+    },
     ResumeInstall(PathBuf),
+    InstallerChecked {
+        capsule_dir: PathBuf,
+        result: Result<InstallerCheck, String>,
+    },
+    RelocateInstaller(PathBuf),
+    ResumeInstallWith {
+        capsule_dir: PathBuf,
+        installer_path: PathBuf,
+    },
+    InstallerCheckDialogClosed,
+    InstallerHashed {
+        capsule_dir: PathBuf,
+        installer_path: PathBuf,
+        result: Result<String, String>,
+    },
     KillInstall(PathBuf),
+    ShutDownPrefix(PathBuf),
+    OpenFolder(PathBuf),
+    ViewScreenshots(PathBuf),
+    OpenUrl(String),
+    ProtonDbRatingFetched {
+        app_id: u32,
+        result: Result<ProtonDbRating, String>,
+    },
+    RunWineTool {
+        capsule_dir: PathBuf,
+        tool: WineTool,
+    },
+    PrefixShutDownFinished {
+        capsule_dir: PathBuf,
+        killed: usize,
+    },
     MarkInstallComplete(PathBuf),
     SystemSetupOutput(SystemSetupOutput),
+    Gamepad(GamepadAction),
+    ToggleTvMode,
+    OpenPreferences,
+    DeckSettingsSaved(DeckSettings),
+    DiscordSettingsSaved(DiscordSettings),
+    RunnerSettingsSaved(RunnerSettings),
+    LogSettingsSaved(LogSettings),
+    IgdbSettingsSaved(IgdbSettings),
+    PreferencesDialogClosed,
+    AddToSteam(PathBuf),
+    AddToSteamDialogClosed,
+}
+
+/// Command-line options for the main window.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    /// Start fullscreen in the big-screen layout.
+    pub tv_mode: bool,
+    /// `--launch <capsule dir>`: start that game and quit once it exits, as
+    /// Steam shortcuts do.
+    pub launch: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        Self {
+            tv_mode: args.iter().any(|arg| arg == "--tv-mode"),
+            launch: args
+                .iter()
+                .position(|arg| arg == "--launch")
+                .and_then(|index| args.get(index + 1))
+                .map(PathBuf::from),
+        }
+    }
 }
 
 pub struct MainWindow {
     capsules: Vec<Capsule>,
+    /// Bumped per library scan so results of a superseded scan are dropped.
+    scan_generation: u64,
     games_dir: PathBuf,
+    /// Reloads the library when capsule folders appear or disappear; only
+    /// held to keep the monitor alive.
+    _library_monitor: Option<gio::FileMonitor>,
     system_check: SystemCheck,
     system_setup_dialog: Option<Controller<SystemSetupDialog>>,
     runtime_mgr: RuntimeManager,
     add_game_dialog: Option<Dialog>,
     game_path_dialog: Option<FileChooserNative>,
+    capsule_folder_dialog: Option<FileChooserNative>,
+    backup_file_dialog: Option<FileChooserNative>,
+    export_dialog: Option<Dialog>,
+    heroic_dialog: Option<Dialog>,
     name_dialog: Option<Dialog>,
+    rename_dialog: Option<Dialog>,
+    game_info_dialog: Option<Dialog>,
+    duplicate_dialog: Option<Dialog>,
+    snapshots_dialog: Option<Dialog>,
+    local_fix_dialog: Option<Dialog>,
+    diagnostics_dialog: Option<Dialog>,
+    launch_command_dialog: Option<Dialog>,
+    details_dialog: Option<Dialog>,
+    details_capsule: Option<PathBuf>,
+    details_hltb_label: Option<Label>,
+    details_hltb_buttons: Option<(Button, Button)>,
+    details_igdb: Option<(Label, Button)>,
+    repair_dialog: Option<Dialog>,
+    winetricks_dialog: Option<Dialog>,
+    winetricks_log: Option<TextView>,
+    cloud_sync_dialog: Option<Dialog>,
+    network_dialog: Option<Dialog>,
+    storage_dialog: Option<Dialog>,
+    storage_status_label: Option<Label>,
+    storage_progress_bar: Option<ProgressBar>,
+    /// Stops a running duplicate scan when the storage dialog closes.
+    dedupe_scan_cancel: Option<Arc<AtomicBool>>,
+    dedupe_report: Option<DedupeReport>,
+    dedupe_running: bool,
+    metadata_recovery_dialog: Option<Dialog>,
+    /// Damaged capsules already offered for recovery this session.
+    recovery_offered: HashSet<PathBuf>,
     settings_dialog: Option<Dialog>,
+    settings_exe_field: Option<ExecutableField>,
+    executable_picker: Option<Dialog>,
     umu_match_dialog: Option<Dialog>,
+    existing_game_dialog: Option<Dialog>,
+    installer_check_dialog: Option<Dialog>,
+    mods_dialog: Option<Dialog>,
+    mods_capsule: Option<PathBuf>,
+    mods_list: Option<ListBox>,
+    mods_status: Option<Label>,
+    mods_link_check: Option<CheckButton>,
+    umu_fixes_label: Option<Label>,
+    umu_fixes_key: Option<String>,
+    protonfixes_cache: HashMap<String, Option<ProtonFixSummary>>,
     dependency_dialog: Option<Dialog>,
+    dependency_progress_dialog: Option<Dialog>,
+    dependency_step_label: Option<Label>,
+    dependency_progress_bar: Option<ProgressBar>,
+    dependency_log: Option<TextView>,
+    dependency_cancel: Option<Arc<AtomicBool>>,
     existing_location_dialog: Option<Dialog>,
+    prefix_import_dialog: Option<Dialog>,
+    copy_dialog: Option<Dialog>,
+    copy_status_label: Option<Label>,
+    copy_progress_bar: Option<ProgressBar>,
     pending_add_mode: Option<AddGameMode>,
     pending_game_path: Option<PathBuf>,
     pending_source_folder: Option<PathBuf>,
     pending_game_name: Option<String>,
     pending_game_id: Option<String>,
     pending_store: Option<String>,
+    pending_msi_silent: bool,
+    pending_win32_prefix: bool,
+    pending_msi_properties: Vec<String>,
     pending_settings_capsule: Option<PathBuf>,
     active_installs: HashMap<PathBuf, i32>,
     active_games: HashMap<PathBuf, i32>,
+    /// Running games in start order; the last one is shown on Discord.
+    playing: Vec<(PathBuf, Activity)>,
     preparing_installs: HashSet<PathBuf>,
     dependency_installs: HashSet<PathBuf>,
+    snapshot_jobs: HashSet<PathBuf>,
+    winetricks_jobs: HashSet<PathBuf>,
+    registry_jobs: HashSet<PathBuf>,
+    shutdown_jobs: HashSet<PathBuf>,
+    repair_jobs: HashSet<PathBuf>,
+    uninstall_jobs: HashSet<PathBuf>,
+    patch_jobs: HashSet<PathBuf>,
+    mod_jobs: HashSet<PathBuf>,
+    uninstall_dialog: Option<Dialog>,
+    archive_jobs: HashSet<PathBuf>,
+    winetricks_verbs: Vec<WinetricksVerb>,
+    winetricks_pending: Option<PathBuf>,
+    cloud_sync_jobs: HashSet<PathBuf>,
+    cloud_sync_config: CloudSyncConfig,
     umu_entries: Vec<UmuEntry>,
     umu_loaded: bool,
     umu_load_error: Option<String>,
-    games_list: Box,
+    /// ProtonDB ratings by Steam app ID, mirrored in the on-disk cache.
+    protondb_ratings: HashMap<u32, ProtonDbRating>,
+    protondb_fetching: HashSet<u32>,
+    /// Capsules whose last launches all failed, from their play history.
+    crashing_games: HashSet<PathBuf>,
+    game_cards: FactoryVecDeque<GameCard>,
+    empty_library_card: Box,
     library_count_label: Label,
+    library_page: Box,
+    library_body: Box,
+    tv_mode: bool,
+    steam_deck: SteamDeck,
+    deck_settings: DeckSettings,
+    discord_settings: DiscordSettings,
+    discord: Option<DiscordPresence>,
+    preferences_dialog: Option<Dialog>,
+    add_to_steam_dialog: Option<Dialog>,
+    safe_retry_dialog: Option<Dialog>,
+    templates: ConfigTemplates,
+    new_game_defaults: NewGameDefaults,
+    template_name_dialog: Option<Dialog>,
+    select_mode: bool,
+    selected_games: HashSet<PathBuf>,
+    select_button: Button,
+    selection_bar: Box,
+    selection_count_label: Label,
+    selection_actions: Box,
+    selection_template_menu: MenuButton,
+    selection_template_list: Box,
+    delete_dialog: Option<Dialog>,
+    /// Games running with safe settings.
+    safe_mode_games: HashSet<PathBuf>,
+    /// Quit once this game exits; set by `--launch`.
+    launch_only: Option<PathBuf>,
     root_window: ApplicationWindow,
 }
 
@@ -128,91 +756,146 @@ pub struct MainWindow {
 pub(crate) enum AddGameMode {
     Installer,
     Existing,
+    Prefix,
+}
+
+/// What to do when a game being added is already in the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DuplicateChoice {
+    OpenExisting,
+    Reinstall,
+    CreateDuplicate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExistingGameTransfer {
+    Copy,
+    Move,
+    Link,
 }
 
 #[derive(Debug, Clone)]
-struct UmuMatch {
-    entry: UmuEntry,
-    score: i32,
+pub(crate) struct ExistingGameJob {
+    capsule_dir: PathBuf,
+    home_path: PathBuf,
+    name: String,
+    dest_dir: PathBuf,
+    exe_path: PathBuf,
+    game_id: Option<String>,
+    store: Option<String>,
+    win32_prefix: bool,
 }
 
 #[derive(Debug, Clone)]
-struct ExecutableGuess {
-    path: PathBuf,
-    shortcut: Option<PathBuf>,
-    score: i32,
+pub(crate) struct PrefixImportJob {
+    capsule_dir: PathBuf,
+    home_path: PathBuf,
+    name: String,
+    game_id: Option<String>,
+    store: Option<String>,
 }
 
-impl MainWindow {
-    const DEP_VCREDIST: &'static str = "vcredist";
-    const DEP_DXWEB: &'static str = "dxweb";
+#[derive(Debug, Clone)]
+pub(crate) enum SnapshotAction {
+    Create(String),
+    Restore(String),
+    Delete(String),
+}
 
-    fn normalize_words(value: &str) -> Vec<String> {
-        let mut words = Vec::new();
-        let mut current = String::new();
-        for ch in value.chars() {
-            if ch.is_ascii_alphanumeric() {
-                current.push(ch.to_ascii_lowercase());
-            } else if !current.is_empty() {
-                words.push(current);
-                current = String::new();
-            }
-        }
-        if !current.is_empty() {
-            words.push(current);
-        }
-        words
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WineTool {
+    Winecfg,
+    Regedit,
+    Taskmgr,
+    Control,
+}
 
-    fn score_match(input: &str, candidate: &str) -> Option<i32> {
-        let input_compact = UmuDatabase::normalize_title(input);
-        let candidate_compact = UmuDatabase::normalize_title(candidate);
-        if input_compact.is_empty() || candidate_compact.is_empty() {
-            return None;
+impl WineTool {
+    pub(crate) const ALL: [WineTool; 4] = [
+        WineTool::Winecfg,
+        WineTool::Regedit,
+        WineTool::Taskmgr,
+        WineTool::Control,
+    ];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            WineTool::Winecfg => "Wine configuration",
+            WineTool::Regedit => "Registry editor",
+            WineTool::Taskmgr => "Task manager",
+            WineTool::Control => "Control panel",
         }
+    }
 
-        if candidate_compact == input_compact {
-            return Some(0);
+    fn command(&self) -> &'static str {
+        match self {
+            WineTool::Winecfg => "winecfg",
+            WineTool::Regedit => "regedit",
+            WineTool::Taskmgr => "taskmgr",
+            WineTool::Control => "control",
         }
+    }
+}
 
-        if candidate_compact.contains(&input_compact) {
-            return Some(1);
-        }
+#[derive(Debug, Clone)]
+struct UmuMatch {
+    entry: UmuEntry,
+    /// Match confidence between 0 and 1.
+    confidence: f64,
+}
 
-        let input_words = Self::normalize_words(input);
-        let candidate_words = Self::normalize_words(candidate);
-        if !input_words.is_empty()
-            && input_words
-                .iter()
-                .all(|word| candidate_words.contains(word))
-        {
-            return Some(2);
-        }
+/// UMU entries that share a title, usually the same game on several stores.
+#[derive(Debug, Clone)]
+struct UmuMatchGroup {
+    title: String,
+    confidence: f64,
+    entries: Vec<UmuEntry>,
+}
 
-        if input_compact.contains(&candidate_compact) && candidate_compact.len() >= 4 {
-            return Some(3);
-        }
+#[derive(Clone)]
+struct DllOverrideRow {
+    container: Box,
+    dll_entry: Entry,
+    mode_dropdown: DropDown,
+}
 
-        None
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ExecutableGuess {
+    path: PathBuf,
+    shortcut: Option<PathBuf>,
+    /// Arguments and start folder from the shortcut, if it had them.
+    arguments: Option<String>,
+    working_dir: Option<PathBuf>,
+    /// Install folder registered by the installer that holds the executable.
+    #[serde(default)]
+    install_location: Option<PathBuf>,
+    score: i32,
+}
 
-    fn score_acronym(input: &str, acronym: &str) -> Option<i32> {
-        let input_compact = UmuDatabase::normalize_title(input);
-        let acronym_compact = UmuDatabase::normalize_title(acronym);
-        if input_compact.is_empty() || acronym_compact.is_empty() {
-            return None;
-        }
+/// What installers registered under the prefix's Uninstall keys, as host
+/// paths.
+struct UninstallHints {
+    icons: Vec<PathBuf>,
+    locations: Vec<PathBuf>,
+}
 
-        if input_compact == acronym_compact {
-            return Some(0);
-        }
+/// Executable candidates found in a prefix, saved next to it so finishing
+/// an install does not walk the whole prefix again when nothing changed.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExecutableScanCache {
+    fingerprint: Vec<(PathBuf, u64)>,
+    candidates: Vec<ExecutableGuess>,
+}
 
-        if input_compact.contains(&acronym_compact) && acronym_compact.len() >= 3 {
-            return Some(2);
-        }
+/// The executable field of the open settings dialog, filled in when a
+/// re-scan finishes.
+struct ExecutableField {
+    capsule_dir: PathBuf,
+    entry: Entry,
+    rescan_button: Button,
+}
 
-        None
-    }
+impl MainWindow {
 
     fn parse_list_input(value: &str) -> Vec<String> {
         value
@@ -230,6 +913,18 @@ impl MainWindow {
             .unwrap_or(false)
     }
 
+    fn is_msi_file(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("msi"))
+            .unwrap_or(false)
+    }
+
+    fn host_path_to_windows(path: &Path) -> String {
+        // Wine maps the host root to Z:, so absolute paths translate directly.
+        format!("Z:{}", path.to_string_lossy().replace('/', "\\"))
+    }
+
     fn compact_name(value: &str) -> String {
         UmuDatabase::normalize_title(value)
     }
@@ -352,137 +1047,45 @@ impl MainWindow {
     }
 
     fn windows_path_to_host(prefix_path: &Path, windows_path: &str) -> Option<PathBuf> {
-        let trimmed = windows_path.trim_matches('"').trim();
-        if trimmed.len() < 3 {
-            return None;
-        }
-        let normalized = trimmed.replace('/', "\\");
-        let lowered = normalized.to_ascii_lowercase();
-        if !lowered.starts_with("c:\\") {
-            return None;
-        }
-        let relative = &normalized[3..];
-        let sep = std::path::MAIN_SEPARATOR.to_string();
-        let host_rel = relative.replace('\\', &sep);
-        Some(prefix_path.join("drive_c").join(host_rel))
-    }
-
-    fn extract_windows_paths_from_text(text: &str) -> Vec<String> {
-        let mut results = Vec::new();
-        let bytes = text.as_bytes();
-        if bytes.len() < 4 {
-            return results;
-        }
-        let mut i = 0;
-        while i + 2 < bytes.len() {
-            if bytes[i].is_ascii_alphabetic() && bytes[i + 1] == b':' && bytes[i + 2] == b'\\' {
-                let mut end = i + 3;
-                while end < bytes.len() {
-                    let b = bytes[end];
-                    if b.is_ascii_alphanumeric()
-                        || matches!(b, b'\\' | b'/' | b'.' | b'_' | b'-' | b' ' | b'(' | b')')
-                    {
-                        end += 1;
-                    } else {
-                        break;
-                    }
-                }
-                let candidate = &text[i..end];
-                let lower = candidate.to_ascii_lowercase();
-                if let Some(idx) = lower.rfind(".exe") {
-                    let trimmed = candidate[..idx + 4].to_string();
-                    results.push(trimmed);
-                }
-                i = end;
-            } else {
-                i += 1;
-            }
-        }
-        results
-    }
-
-    fn extract_ascii_sequences(bytes: &[u8]) -> Vec<String> {
-        let mut sequences = Vec::new();
-        let mut current: Vec<u8> = Vec::new();
-        for &b in bytes {
-            if b.is_ascii_graphic() || b == b' ' {
-                current.push(b);
-            } else {
-                if current.len() >= 6 {
-                    sequences.push(String::from_utf8_lossy(&current).to_string());
-                }
-                current.clear();
-            }
-        }
-        if current.len() >= 6 {
-            sequences.push(String::from_utf8_lossy(&current).to_string());
-        }
-        sequences
+        PrefixRegistry::new(prefix_path).host_path(windows_path)
     }
 
-    fn extract_utf16le_sequences(bytes: &[u8]) -> Vec<String> {
-        let mut sequences = Vec::new();
-        let mut current: Vec<u8> = Vec::new();
-        let mut i = 0;
-        while i + 1 < bytes.len() {
-            let b0 = bytes[i];
-            let b1 = bytes[i + 1];
-            if b1 == 0 && (b0 == 0 || b0.is_ascii_graphic() || b0 == b' ') {
-                if b0 == 0 {
-                    if current.len() >= 6 {
-                        sequences.push(String::from_utf8_lossy(&current).to_string());
-                    }
-                    current.clear();
-                } else {
-                    current.push(b0);
+    /// Resolve a shell link's relative target against the folder holding
+    /// the link, folding away `..` components.
+    fn resolve_link_relative_path(shortcut: &Path, relative: &str) -> Option<PathBuf> {
+        let mut resolved = shortcut.parent()?.to_path_buf();
+        for part in relative.split(['\\', '/']) {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    resolved.pop();
                 }
-                i += 2;
-            } else {
-                if current.len() >= 6 {
-                    sequences.push(String::from_utf8_lossy(&current).to_string());
-                }
-                current.clear();
-                i += 1;
+                part => resolved.push(part),
             }
         }
-        if current.len() >= 6 {
-            sequences.push(String::from_utf8_lossy(&current).to_string());
-        }
-        sequences
-    }
-
-    fn extract_windows_exe_paths_from_lnk(path: &Path) -> Vec<String> {
-        let mut results = Vec::new();
-        let bytes = match fs::read(path) {
-            Ok(bytes) => bytes,
-            Err(_) => return results,
-        };
-        for seq in Self::extract_ascii_sequences(&bytes)
-            .into_iter()
-            .chain(Self::extract_utf16le_sequences(&bytes))
-        {
-            results.extend(Self::extract_windows_paths_from_text(&seq));
-        }
-        results.sort();
-        results.dedup();
-        results
+        Some(resolved)
     }
 
-    fn lnk_contains_http_url(path: &Path) -> bool {
-        let bytes = match fs::read(path) {
-            Ok(bytes) => bytes,
-            Err(_) => return false,
-        };
-        for seq in Self::extract_ascii_sequences(&bytes)
+    /// Where the link's program lives in the prefix: its absolute target,
+    /// then its relative path, then the icon file, which is usually the
+    /// program itself when the target is stored only as a shell item list.
+    fn shortcut_target_on_host(prefix_path: &Path, shortcut: &Path, link: &ShellLink) -> Option<PathBuf> {
+        let from_target = link
+            .target
+            .as_deref()
+            .and_then(|target| Self::windows_path_to_host(prefix_path, target));
+        let from_relative = link
+            .relative_path
+            .as_deref()
+            .and_then(|relative| Self::resolve_link_relative_path(shortcut, relative));
+        let from_icon = link
+            .icon_location
+            .as_deref()
+            .and_then(|icon| Self::windows_path_to_host(prefix_path, icon));
+        [from_target, from_relative, from_icon]
             .into_iter()
-            .chain(Self::extract_utf16le_sequences(&bytes))
-        {
-            let lowered = seq.to_ascii_lowercase();
-            if lowered.contains("http://") || lowered.contains("https://") {
-                return true;
-            }
-        }
-        false
+            .flatten()
+            .find(|path| path.is_file() && Self::is_exe_file(path) && !Self::is_ignored_exe(path))
     }
 
     fn collect_shortcuts(prefix_path: &Path) -> Vec<PathBuf> {
@@ -531,25 +1134,36 @@ impl MainWindow {
     ) -> Vec<ExecutableGuess> {
         let mut candidates = Vec::new();
         for shortcut in Self::collect_shortcuts(prefix_path) {
-            if Self::lnk_contains_http_url(&shortcut) {
-                continue;
-            }
-            for windows_path in Self::extract_windows_exe_paths_from_lnk(&shortcut) {
-                if let Some(host_path) = Self::windows_path_to_host(prefix_path, &windows_path) {
-                    if host_path.is_file()
-                        && Self::is_exe_file(&host_path)
-                        && !Self::is_ignored_exe(&host_path)
-                    {
-                        let score =
-                            Self::score_exe_candidate(&host_path, Some(&shortcut), capsule_name, game_dir);
-                        candidates.push(ExecutableGuess {
-                            path: host_path,
-                            shortcut: Some(shortcut.clone()),
-                            score,
-                        });
-                    }
+            let link = match ShellLink::read(&shortcut) {
+                Ok(link) => link,
+                Err(e) => {
+                    eprintln!("Skipping shortcut: {:#}", e);
+                    continue;
                 }
+            };
+            if link.mentions_url() {
+                continue;
             }
+            let host_path = match Self::shortcut_target_on_host(prefix_path, &shortcut, &link) {
+                Some(path) => path,
+                None => continue,
+            };
+            let score = Self::score_exe_candidate(&host_path, Some(&shortcut), capsule_name, game_dir);
+            candidates.push(ExecutableGuess {
+                path: host_path,
+                shortcut: Some(shortcut.clone()),
+                install_location: None,
+                arguments: link
+                    .arguments
+                    .map(|args| args.trim().to_string())
+                    .filter(|args| !args.is_empty()),
+                working_dir: link
+                    .working_dir
+                    .as_deref()
+                    .and_then(|dir| Self::windows_path_to_host(prefix_path, dir))
+                    .filter(|dir| dir.is_dir()),
+                score,
+            });
         }
         candidates
     }
@@ -558,6 +1172,7 @@ impl MainWindow {
         prefix_path: &Path,
         capsule_name: &str,
         game_dir: Option<&Path>,
+        install_locations: &[PathBuf],
     ) -> Vec<ExecutableGuess> {
         let mut roots = Vec::new();
         if let Some(game_root) = game_dir {
@@ -568,6 +1183,11 @@ impl MainWindow {
         roots.push(drive_c.join("Program Files (x86)"));
         roots.push(drive_c.join("GOG Games"));
         roots.push(drive_c.join("Games"));
+        for location in install_locations {
+            if !roots.iter().any(|root| location.starts_with(root)) {
+                roots.push(location.clone());
+            }
+        }
 
         if roots.iter().all(|root| !root.is_dir()) {
             roots.clear();
@@ -594,6 +1214,9 @@ impl MainWindow {
                     candidates.push(ExecutableGuess {
                         path: entry.path().to_path_buf(),
                         shortcut: None,
+                        arguments: None,
+                        working_dir: None,
+                        install_location: None,
                         score,
                     });
                 }
@@ -602,34 +1225,248 @@ impl MainWindow {
         candidates
     }
 
-    fn guess_executable(capsule: &Capsule) -> Option<ExecutableGuess> {
-        let prefix_path = capsule
-            .capsule_dir
-            .join(format!("{}.AppImage.home", capsule.name))
-            .join("prefix");
+    /// Modification times of the registry and the folders the executable
+    /// scan starts from. Installing or removing a game touches one of them.
+    fn exe_scan_fingerprint(prefix_path: &Path, game_dir: Option<&Path>) -> Vec<(PathBuf, u64)> {
+        let drive_c = prefix_path.join("drive_c");
+        let mut dirs = vec![
+            // Installers register themselves in system.reg.
+            prefix_path.join("system.reg"),
+            drive_c.clone(),
+            drive_c.join("Program Files"),
+            drive_c.join("Program Files (x86)"),
+            drive_c.join("GOG Games"),
+            drive_c.join("Games"),
+        ];
+        if let Ok(entries) = fs::read_dir(drive_c.join("users")) {
+            for entry in entries.flatten() {
+                let user_dir = entry.path();
+                dirs.push(user_dir.join("Desktop"));
+                dirs.push(user_dir.join("Start Menu").join("Programs"));
+            }
+        }
+        dirs.extend(game_dir.map(Path::to_path_buf));
+        dirs.into_iter()
+            .filter_map(|dir| {
+                let modified = fs::metadata(&dir).and_then(|meta| meta.modified()).ok()?;
+                let millis = modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64;
+                Some((dir, millis))
+            })
+            .collect()
+    }
+
+    fn read_exe_scan_cache(
+        cache_path: &Path,
+        fingerprint: &[(PathBuf, u64)],
+    ) -> Option<Vec<ExecutableGuess>> {
+        let content = fs::read_to_string(cache_path).ok()?;
+        let cache: ExecutableScanCache = serde_json::from_str(&content).ok()?;
+        if cache.fingerprint != fingerprint {
+            return None;
+        }
+        Some(
+            cache
+                .candidates
+                .into_iter()
+                .filter(|guess| guess.path.is_file())
+                .collect(),
+        )
+    }
+
+    /// The candidates from the last scan if the prefix has not changed
+    /// since, without scanning.
+    fn cached_executable_candidates(capsule: &Capsule) -> Option<Vec<ExecutableGuess>> {
+        let prefix_path = capsule.home_path.join("prefix");
         let game_dir = capsule
             .metadata
             .game_dir
             .as_deref()
             .map(PathBuf::from)
             .filter(|path| path.is_dir());
+        let fingerprint = Self::exe_scan_fingerprint(&prefix_path, game_dir.as_deref());
+        Self::read_exe_scan_cache(&capsule.home_path.join(EXE_SCAN_CACHE_FILE), &fingerprint)
+    }
 
-        let mut candidates =
-            Self::find_exe_from_shortcuts(&prefix_path, &capsule.name, game_dir.as_deref());
-        if candidates.is_empty() {
-            candidates =
-                Self::find_exe_from_dirs(&prefix_path, &capsule.name, game_dir.as_deref());
+    /// Executables and install folders the prefix's installers registered
+    /// for Add/Remove Programs. Locations that are a whole drive or a top
+    /// level folder like Program Files say nothing about the game.
+    fn uninstall_hints(prefix_path: &Path) -> UninstallHints {
+        let drive_c = prefix_path.join("drive_c");
+        let mut hints = UninstallHints {
+            icons: Vec::new(),
+            locations: Vec::new(),
+        };
+        for entry in PrefixRegistry::new(prefix_path).uninstall_entries() {
+            if let Some(icon) = entry
+                .display_icon
+                .as_deref()
+                .and_then(|icon| Self::windows_path_to_host(prefix_path, icon))
+                .filter(|path| {
+                    path.is_file() && Self::is_exe_file(path) && !Self::is_ignored_exe(path)
+                })
+            {
+                hints.icons.push(icon);
+            }
+            if let Some(location) = entry
+                .install_location
+                .as_deref()
+                .and_then(|location| Self::windows_path_to_host(prefix_path, location))
+                .filter(|path| path.is_dir())
+                .filter(|path| {
+                    path.strip_prefix(&drive_c)
+                        .is_ok_and(|rest| rest.components().count() > 1)
+                })
+            {
+                hints.locations.push(location);
+            }
+        }
+        hints
+    }
+
+    /// Executable candidates for the capsule, best first. Uses the cached
+    /// list unless `rescan` is set or the prefix changed since it was saved.
+    fn executable_candidates(capsule: &Capsule, rescan: bool) -> Vec<ExecutableGuess> {
+        let prefix_path = capsule.home_path.join("prefix");
+        let game_dir = capsule
+            .metadata
+            .game_dir
+            .as_deref()
+            .map(PathBuf::from)
+            .filter(|path| path.is_dir());
+        let cache_path = capsule.home_path.join(EXE_SCAN_CACHE_FILE);
+        let fingerprint = Self::exe_scan_fingerprint(&prefix_path, game_dir.as_deref());
+
+        if !rescan {
+            if let Some(candidates) = Self::read_exe_scan_cache(&cache_path, &fingerprint) {
+                return candidates;
+            }
+        }
+
+        let hints = Self::uninstall_hints(&prefix_path);
+        let mut candidates =
+            Self::find_exe_from_shortcuts(&prefix_path, &capsule.name, game_dir.as_deref());
+        for icon in &hints.icons {
+            if !candidates.iter().any(|guess| &guess.path == icon) {
+                candidates.push(ExecutableGuess {
+                    path: icon.clone(),
+                    shortcut: None,
+                    arguments: None,
+                    working_dir: None,
+                    install_location: None,
+                    score: Self::score_exe_candidate(
+                        icon,
+                        None,
+                        &capsule.name,
+                        game_dir.as_deref(),
+                    ),
+                });
+            }
+        }
+        if candidates.is_empty() {
+            candidates = Self::find_exe_from_dirs(
+                &prefix_path,
+                &capsule.name,
+                game_dir.as_deref(),
+                &hints.locations,
+            );
+        }
+        // What the installer registered beats anything guessed from names.
+        for guess in &mut candidates {
+            if hints.icons.contains(&guess.path) {
+                guess.score += REGISTRY_ICON_BONUS;
+            }
+            if let Some(location) = hints
+                .locations
+                .iter()
+                .find(|location| guess.path.starts_with(location))
+            {
+                guess.score += INSTALL_LOCATION_BONUS;
+                guess.install_location = Some(location.clone());
+            }
         }
         candidates.sort_by(|a, b| b.score.cmp(&a.score));
-        candidates.into_iter().next()
+        // Several shortcuts often point at the same program; keep the best.
+        let mut seen = HashSet::new();
+        candidates.retain(|guess| seen.insert(guess.path.clone()));
+
+        let cache = ExecutableScanCache {
+            fingerprint,
+            candidates,
+        };
+        match serde_json::to_string_pretty(&cache) {
+            Ok(content) => {
+                if let Err(e) = fs::write(&cache_path, content) {
+                    eprintln!("Failed to save executable scan cache: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize executable scan cache: {}", e),
+        }
+        cache.candidates
+    }
+
+    /// The best candidate, unless another one scores close enough that the
+    /// user should pick.
+    fn confident_guess(candidates: &[ExecutableGuess]) -> Option<&ExecutableGuess> {
+        match candidates {
+            [best, next, ..] if best.score - next.score < EXE_GUESS_MARGIN => None,
+            [best, ..] => Some(best),
+            [] => None,
+        }
     }
 
-    fn vcredist_cache_path() -> PathBuf {
-        SystemCheck::vcredist_cache_path()
+    /// Make the guessed executable the capsule's main one, taking the
+    /// arguments and start folder from the shortcut it came from.
+    fn apply_executable_guess(metadata: &mut CapsuleMetadata, guess: ExecutableGuess) {
+        let main = &mut metadata.executables.main;
+        main.path = guess.path.to_string_lossy().to_string();
+        main.original_shortcut = guess
+            .shortcut
+            .map(|path| path.to_string_lossy().to_string());
+        if let Some(arguments) = guess.arguments {
+            main.args = arguments;
+        }
+        main.working_dir = guess
+            .working_dir
+            .map(|dir| dir.to_string_lossy().to_string());
+        // Installers get an empty default game folder and mostly install
+        // elsewhere; the registered install location is the real one.
+        let unused_game_dir = match metadata.game_dir.as_deref() {
+            Some(dir) => !file_ops::has_entries(Path::new(dir)) || !guess.path.starts_with(dir),
+            None => true,
+        };
+        if unused_game_dir {
+            if let Some(location) = guess.install_location {
+                metadata.game_dir = Some(location.to_string_lossy().to_string());
+            } else if metadata
+                .game_dir
+                .as_deref()
+                .is_some_and(|dir| !file_ops::has_entries(Path::new(dir)))
+            {
+                metadata.game_dir = None;
+            }
+        }
     }
 
-    fn dxweb_cache_path() -> PathBuf {
-        SystemCheck::dxweb_cache_path()
+    /// The redistributables a game is set to install, in install order.
+    fn wanted_redistributables(
+        install_vcredist: bool,
+        install_dxweb: bool,
+        install_redistributables: &[String],
+    ) -> Vec<&'static Redistributable> {
+        let mut wanted = Vec::new();
+        if install_vcredist {
+            wanted.extend(Redistributable::get(VCREDIST_ID));
+        }
+        if install_dxweb {
+            wanted.extend(Redistributable::get(DIRECTX_ID));
+        }
+        wanted.extend(
+            install_redistributables
+                .iter()
+                .filter_map(|id| Redistributable::get(id))
+                .filter(|redist| !redist.is_default()),
+        );
+        wanted
     }
 
     fn is_dependency_installed(metadata: &CapsuleMetadata, dep: &str) -> bool {
@@ -639,17 +1476,22 @@ impl MainWindow {
             .any(|item| item == dep)
     }
 
-    fn should_prompt_dependencies(&self, metadata: &CapsuleMetadata) -> bool {
-        let wants_vcredist = metadata.install_vcredist;
-        let wants_dxweb = metadata.install_dxweb;
-        if !wants_vcredist && !wants_dxweb {
-            return false;
-        }
-        let vcredist_pending =
-            wants_vcredist && !Self::is_dependency_installed(metadata, Self::DEP_VCREDIST);
-        let dxweb_pending =
-            wants_dxweb && !Self::is_dependency_installed(metadata, Self::DEP_DXWEB);
-        vcredist_pending || dxweb_pending
+    /// Components the game wants that are neither recorded in its metadata
+    /// nor found in the prefix, e.g. one imported with VC++ already in it.
+    fn should_prompt_dependencies(&self, capsule_dir: &Path, metadata: &CapsuleMetadata) -> bool {
+        let prefix_path = capsule_dir
+            .join(format!("{}.AppImage.home", metadata.name))
+            .join("prefix");
+        let present = Redistributable::present_in(&prefix_path);
+        Self::wanted_redistributables(
+            metadata.install_vcredist,
+            metadata.install_dxweb,
+            &metadata.install_redistributables,
+        )
+        .iter()
+        .any(|redist| {
+            !Self::is_dependency_installed(metadata, redist.id()) && !present.contains(redist)
+        })
     }
 
     fn resolve_relative_game_folder(name: &str, input: &str) -> String {
@@ -683,24 +1525,6 @@ impl MainWindow {
         path
     }
 
-    fn copy_dir_recursive(src: &Path, dest: &Path) -> io::Result<()> {
-        if !dest.exists() {
-            fs::create_dir_all(dest)?;
-        }
-        for entry in fs::read_dir(src)? {
-            let entry = entry?;
-            let file_type = entry.file_type()?;
-            let from = entry.path();
-            let to = dest.join(entry.file_name());
-            if file_type.is_dir() {
-                Self::copy_dir_recursive(&from, &to)?;
-            } else {
-                fs::copy(&from, &to)?;
-            }
-        }
-        Ok(())
-    }
-
     fn update_library_labels(&self) {
         self.library_count_label
             .set_label(&format!("{} games", self.capsules.len()));
@@ -770,6 +1594,18 @@ impl MainWindow {
             .unwrap_or(false);
 
         let candidate = match mode {
+            AddGameMode::Prefix => {
+                // Proton keeps the Wine prefix in `pfx/`; name it after the parent.
+                let folder = path
+                    .file_name()
+                    .map(|value| value.to_string_lossy().to_string())
+                    .filter(|value| !value.trim().is_empty());
+                if folder.as_deref() == Some("pfx") {
+                    parent
+                } else {
+                    folder.map(|value| value.trim_start_matches('.').to_string())
+                }
+            }
             AddGameMode::Existing => {
                 if parent_ok {
                     parent
@@ -807,12 +1643,85 @@ impl MainWindow {
         base
     }
 
+    /// Watch the games folder for capsules added, removed or renamed outside
+    /// LinuxBoy. Bursts of events, such as a folder being copied in, reload
+    /// the library once they settle.
+    /// `~/Games`, or `Games` on the microSD card when the Deck settings ask
+    /// for it and a card is in.
+    fn library_dir(steam_deck: &SteamDeck, deck_settings: &DeckSettings) -> PathBuf {
+        match deck_settings.sd_card_games_dir(steam_deck) {
+            Some(dir) if fs::create_dir_all(&dir).is_ok() => dir,
+            _ => host::writable_dir(dirs::home_dir().unwrap_or_default().join("Games")),
+        }
+    }
+
+    /// Close LinuxBoy after the game started with `--launch` is done, so
+    /// Steam sees the shortcut stop.
+    /// Show the most recently started game that is still running on Discord,
+    /// or clear the presence once none are.
+    fn update_discord_presence(&self) {
+        if let Some(discord) = &self.discord {
+            discord.update(self.playing.last().map(|(_, activity)| activity.clone()));
+        }
+    }
+
+    fn quit_if_launch_done(&self, capsule_dir: &Path) {
+        if self.launch_only.as_deref() == Some(capsule_dir) {
+            self.root_window.close();
+        }
+    }
+
+    fn watch_games_dir(games_dir: &Path, sender: ComponentSender<Self>) -> Option<gio::FileMonitor> {
+        if let Err(e) = fs::create_dir_all(games_dir) {
+            eprintln!("Failed to create {:?}: {}", games_dir, e);
+            return None;
+        }
+        let monitor = match gio::File::for_path(games_dir)
+            .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, None::<&gio::Cancellable>)
+        {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                eprintln!("Failed to watch {:?}: {}", games_dir, e);
+                return None;
+            }
+        };
+
+        let pending: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+        monitor.connect_changed(move |_, file, other_file, event| {
+            let relevant = matches!(
+                event,
+                gio::FileMonitorEvent::Created
+                    | gio::FileMonitorEvent::Deleted
+                    | gio::FileMonitorEvent::MovedIn
+                    | gio::FileMonitorEvent::MovedOut
+                    | gio::FileMonitorEvent::Renamed
+            );
+            // Hidden entries are LinuxBoy's own staging folders.
+            let visible = |file: &gio::File| {
+                file.basename()
+                    .map(|name| !name.to_string_lossy().starts_with('.'))
+                    .unwrap_or(false)
+            };
+            if !relevant || !(visible(file) || other_file.map(visible).unwrap_or(false)) {
+                return;
+            }
+
+            if let Some(source) = pending.borrow_mut().take() {
+                source.remove();
+            }
+            let pending_clone = pending.clone();
+            let sender = sender.clone();
+            let source = glib::timeout_add_local_once(Duration::from_millis(500), move || {
+                pending_clone.borrow_mut().take();
+                sender.input(MainWindowMsg::LoadCapsules);
+            });
+            *pending.borrow_mut() = Some(source);
+        });
+        Some(monitor)
+    }
+
     fn has_command(cmd: &str) -> bool {
-        Command::new("which")
-            .arg(cmd)
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+        host::has_command(cmd)
     }
 
     fn open_add_game_dialog(&mut self, sender: ComponentSender<Self>) {
@@ -828,6 +1737,7 @@ impl MainWindow {
         dialog.add_button("Cancel", ResponseType::Cancel);
         dialog.add_button("Install from installer", ResponseType::Accept);
         dialog.add_button("Add existing game", ResponseType::Apply);
+        dialog.add_button("Import capsule...", ResponseType::Other(1));
 
         let content = dialog.content_area();
         let layout = Box::new(Orientation::Vertical, 8);
@@ -838,16 +1748,56 @@ impl MainWindow {
         title.set_css_classes(&["section-title"]);
 
         let hint = Label::new(Some(
-            "Installers run through UMU. Existing games will be copied into the prefix.",
+            "Installers run through UMU. Existing games are copied, moved, or linked into the prefix.",
         ));
         hint.set_halign(gtk4::Align::Start);
         hint.set_wrap(true);
         hint.set_css_classes(&["muted"]);
 
+        let prefix_button = Button::with_label("Import an existing Wine prefix...");
+        prefix_button.set_halign(gtk4::Align::Start);
+        prefix_button.add_css_class("flat");
+
+        let heroic_button = Button::with_label("Import from Heroic Games Launcher...");
+        heroic_button.set_halign(gtk4::Align::Start);
+        heroic_button.add_css_class("flat");
+
+        let bundle_button = Button::with_label("Import a portable bundle...");
+        bundle_button.set_halign(gtk4::Align::Start);
+        bundle_button.add_css_class("flat");
+
+        let verify_button = Button::with_label("Verify a backup...");
+        verify_button.set_halign(gtk4::Align::Start);
+        verify_button.add_css_class("flat");
+
         layout.append(&title);
         layout.append(&hint);
+        layout.append(&prefix_button);
+        layout.append(&heroic_button);
+        layout.append(&bundle_button);
+        layout.append(&verify_button);
         content.append(&layout);
 
+        let dialog_clone = dialog.clone();
+        prefix_button.connect_clicked(move |_| {
+            dialog_clone.response(ResponseType::Other(4));
+        });
+
+        let dialog_clone = dialog.clone();
+        heroic_button.connect_clicked(move |_| {
+            dialog_clone.response(ResponseType::Other(3));
+        });
+
+        let dialog_clone = dialog.clone();
+        bundle_button.connect_clicked(move |_| {
+            dialog_clone.response(ResponseType::Other(5));
+        });
+
+        let dialog_clone = dialog.clone();
+        verify_button.connect_clicked(move |_| {
+            dialog_clone.response(ResponseType::Other(2));
+        });
+
         let sender_clone = sender.clone();
         let handled = Rc::new(Cell::new(false));
         let handled_clone = handled.clone();
@@ -862,6 +1812,25 @@ impl MainWindow {
                 ResponseType::Apply => {
                     sender_clone.input(MainWindowMsg::AddGameModeChosen(AddGameMode::Existing));
                 }
+                ResponseType::Other(1) => {
+                    sender_clone.input(MainWindowMsg::AddGameCancelled);
+                    sender_clone.input(MainWindowMsg::ImportCapsule);
+                }
+                ResponseType::Other(2) => {
+                    sender_clone.input(MainWindowMsg::AddGameCancelled);
+                    sender_clone.input(MainWindowMsg::VerifyBackup);
+                }
+                ResponseType::Other(3) => {
+                    sender_clone.input(MainWindowMsg::AddGameCancelled);
+                    sender_clone.input(MainWindowMsg::OpenHeroicImport);
+                }
+                ResponseType::Other(4) => {
+                    sender_clone.input(MainWindowMsg::AddGameModeChosen(AddGameMode::Prefix));
+                }
+                ResponseType::Other(5) => {
+                    sender_clone.input(MainWindowMsg::AddGameCancelled);
+                    sender_clone.input(MainWindowMsg::ImportPortableBundle);
+                }
                 _ => {
                     sender_clone.input(MainWindowMsg::AddGameCancelled);
                 }
@@ -882,24 +1851,32 @@ impl MainWindow {
         let title = match mode {
             AddGameMode::Installer => "Select Installer",
             AddGameMode::Existing => "Select Game Executable",
+            AddGameMode::Prefix => "Select Wine Prefix",
+        };
+        let action = if mode == AddGameMode::Prefix {
+            FileChooserAction::SelectFolder
+        } else {
+            FileChooserAction::Open
         };
         let dialog = FileChooserNative::builder()
             .title(title)
-            .action(FileChooserAction::Open)
+            .action(action)
             .accept_label("Select")
             .cancel_label("Cancel")
             .transient_for(&self.root_window)
             .build();
 
-        let filter = FileFilter::new();
-        filter.add_suffix("exe");
-        if mode == AddGameMode::Installer {
-            filter.add_suffix("msi");
-            filter.set_name(Some("Windows installers (.exe, .msi)"));
-        } else {
-            filter.set_name(Some("Windows executables (.exe)"));
+        if mode != AddGameMode::Prefix {
+            let filter = FileFilter::new();
+            filter.add_suffix("exe");
+            if mode == AddGameMode::Installer {
+                filter.add_suffix("msi");
+                filter.set_name(Some("Windows installers (.exe, .msi)"));
+            } else {
+                filter.set_name(Some("Windows executables (.exe)"));
+            }
+            dialog.add_filter(&filter);
         }
-        dialog.add_filter(&filter);
 
         let sender_clone = sender.clone();
         let handled = Rc::new(Cell::new(false));
@@ -963,14 +1940,83 @@ impl MainWindow {
         content.append(&label);
         content.append(&entry);
 
+        let is_msi = self
+            .pending_game_path
+            .as_deref()
+            .map(Self::is_msi_file)
+            .unwrap_or(false)
+            && self.pending_add_mode == Some(AddGameMode::Installer);
+        let msi_silent_check = CheckButton::with_label("Silent install (/qn)");
+        let msi_properties_entry = Entry::new();
+        msi_properties_entry
+            .set_placeholder_text(Some("INSTALLDIR=\"C:\\Games\\My Game\" ALLUSERS=1"));
+        if is_msi {
+            dialog.set_default_height(260);
+            let msi_label = Label::new(Some("MSI properties (optional)"));
+            msi_label.set_halign(gtk4::Align::Start);
+            content.append(&msi_silent_check);
+            content.append(&msi_label);
+            content.append(&msi_properties_entry);
+        }
+
+        // Imported prefixes keep whatever architecture they already have.
+        let creates_prefix = self.pending_add_mode != Some(AddGameMode::Prefix);
+        let win32_check = CheckButton::with_label("Create a 32-bit prefix (WINEARCH=win32)");
+        if creates_prefix {
+            let advanced_box = Box::new(Orientation::Vertical, 4);
+            // Proton ignores WINEARCH and always creates a 64-bit prefix.
+            let wine_runner = matches!(Runner::detect(), Ok(Runner::Wine));
+            win32_check.set_sensitive(wine_runner);
+            let win32_hint = Label::new(Some(if wine_runner {
+                "Only for old installers that refuse to run in a 64-bit prefix."
+            } else {
+                "Only for old installers that refuse to run in a 64-bit prefix. Needs the \
+                 System Wine runner; Proton always creates a 64-bit prefix."
+            }));
+            win32_hint.set_halign(gtk4::Align::Start);
+            win32_hint.set_wrap(true);
+            win32_hint.set_css_classes(&["muted"]);
+            advanced_box.append(&win32_check);
+            advanced_box.append(&win32_hint);
+            let advanced = Expander::new(Some("Advanced"));
+            advanced.set_child(Some(&advanced_box));
+            content.append(&advanced);
+        }
+
         let sender_clone = sender.clone();
         let handled = Rc::new(Cell::new(false));
         let handled_clone = handled.clone();
         dialog.connect_response(move |dialog, response| {
-            if handled_clone.replace(true) {
+            if handled_clone.get() {
                 return;
             }
+            // Quoted values keep their spaces; the dialog stays open to fix
+            // an unclosed quote.
+            let msi_properties = if response == ResponseType::Accept && is_msi {
+                match split_words(&msi_properties_entry.text()) {
+                    Ok(properties) => Some(properties),
+                    Err(e) => {
+                        msi_properties_entry.set_css_classes(&["error"]);
+                        msi_properties_entry.set_tooltip_text(Some(&e.to_string()));
+                        return;
+                    }
+                }
+            } else {
+                None
+            };
+            handled_clone.set(true);
             if response == ResponseType::Accept {
+                if let Some(properties) = msi_properties {
+                    sender_clone.input(MainWindowMsg::MsiOptionsConfirmed {
+                        silent: msi_silent_check.is_active(),
+                        properties,
+                    });
+                }
+                if creates_prefix {
+                    sender_clone.input(MainWindowMsg::PrefixArchChosen {
+                        win32: win32_check.is_active(),
+                    });
+                }
                 let name = entry.text().to_string();
                 sender_clone.input(MainWindowMsg::GameNameConfirmed(name));
             } else {
@@ -984,186 +2030,401 @@ impl MainWindow {
         self.name_dialog = Some(dialog);
     }
 
-    fn open_existing_game_location_dialog(
+    fn open_existing_game_dialog(
         &mut self,
         sender: ComponentSender<Self>,
-        game_name: String,
+        duplicate: Duplicate,
+        add_mode: AddGameMode,
     ) {
-        if self.existing_location_dialog.is_some() {
+        if self.existing_game_dialog.is_some() {
             return;
         }
 
         let dialog = Dialog::builder()
-            .title("Game Folder")
+            .title("Already in Your Library")
             .modal(true)
             .transient_for(&self.root_window)
             .build();
+        dialog.set_default_width(420);
+        dialog.set_resizable(false);
         dialog.add_button("Cancel", ResponseType::Cancel);
-        dialog.add_button("Continue", ResponseType::Accept);
+        dialog.add_button("Create duplicate", ResponseType::Other(2));
+        // Only an installer has something to run into the existing prefix.
+        if add_mode == AddGameMode::Installer {
+            dialog.add_button("Reinstall into existing", ResponseType::Other(1));
+        }
+        dialog.add_button("Open existing", ResponseType::Accept);
+        dialog.set_default_response(ResponseType::Accept);
 
         let content = dialog.content_area();
-        let layout = Box::new(Orientation::Vertical, 8);
-        layout.set_margin_all(12);
-
-        let title = Label::new(Some("Choose where to copy the game files"));
+        content.set_margin_all(16);
+        content.set_spacing(10);
+        let title = Label::new(Some(&format!(
+            "{} {}",
+            duplicate.title,
+            duplicate.reason.describe()
+        )));
         title.set_halign(gtk4::Align::Start);
+        title.set_wrap(true);
+        title.set_xalign(0.0);
         title.set_css_classes(&["section-title"]);
-
-        let hint = Label::new(Some(
-            "Path is relative to the prefix 'games' folder.",
-        ));
+        let hint = Label::new(Some(if add_mode == AddGameMode::Installer {
+            "Open the game you already have, run this installer again in its capsule to \
+             repair or update it, or add a second copy in its own capsule."
+        } else {
+            "Open the game you already have, or add a second copy in its own capsule."
+        }));
         hint.set_halign(gtk4::Align::Start);
         hint.set_wrap(true);
+        hint.set_xalign(0.0);
         hint.set_css_classes(&["muted"]);
-
-        let location_label = Label::new(Some("Game folder (inside prefix/games)"));
-        location_label.set_halign(gtk4::Align::Start);
-        let location_entry = Entry::new();
-        location_entry.set_placeholder_text(Some("e.g., MyGame"));
-        location_entry.set_text(&game_name);
-
-        layout.append(&title);
-        layout.append(&hint);
-        layout.append(&location_label);
-        layout.append(&location_entry);
-        content.append(&layout);
+        content.append(&title);
+        content.append(&hint);
 
         let sender_clone = sender.clone();
+        let capsule_dir = duplicate.capsule_dir;
+        let handled = Rc::new(Cell::new(false));
+        let handled_clone = handled.clone();
         dialog.connect_response(move |dialog, response| {
-            if response == ResponseType::Accept {
-                sender_clone.input(MainWindowMsg::ExistingGameLocationConfirmed(
-                    location_entry.text().to_string(),
-                ));
-            } else {
-                sender_clone.input(MainWindowMsg::ExistingGameLocationCancelled);
+            if handled_clone.replace(true) {
+                return;
+            }
+            let choice = match response {
+                ResponseType::Accept => Some(DuplicateChoice::OpenExisting),
+                ResponseType::Other(1) => Some(DuplicateChoice::Reinstall),
+                ResponseType::Other(2) => Some(DuplicateChoice::CreateDuplicate),
+                _ => None,
+            };
+            match choice {
+                Some(choice) => sender_clone.input(MainWindowMsg::DuplicateGameChosen {
+                    capsule_dir: capsule_dir.clone(),
+                    choice,
+                }),
+                None => sender_clone.input(MainWindowMsg::AddGameCancelled),
             }
             dialog.close();
         });
 
         dialog.show();
-        self.existing_location_dialog = Some(dialog);
+        self.existing_game_dialog = Some(dialog);
     }
 
-    fn open_existing_source_folder_dialog(&mut self, sender: ComponentSender<Self>) {
-        if self.game_path_dialog.is_some() {
+    fn open_rename_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.rename_dialog.is_some() {
             return;
         }
 
-        let dialog = FileChooserNative::builder()
-            .title("Select Game Folder to Copy")
-            .action(FileChooserAction::SelectFolder)
-            .accept_label("Select")
-            .cancel_label("Cancel")
+        let current_name = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule.name,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let dialog = Dialog::builder()
+            .title("Rename Game")
+            .modal(true)
             .transient_for(&self.root_window)
             .build();
+        dialog.set_default_width(420);
+        dialog.set_resizable(false);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Rename", ResponseType::Accept);
+        dialog.set_default_response(ResponseType::Accept);
+
+        let content = dialog.content_area();
+        content.set_margin_all(16);
+        content.set_spacing(10);
+        let label = Label::new(Some("New name"));
+        label.set_halign(gtk4::Align::Start);
+        label.set_css_classes(&["section-title"]);
+        let entry = Entry::new();
+        entry.set_hexpand(true);
+        entry.set_text(&current_name);
+        entry.set_activates_default(true);
+        content.append(&label);
+        content.append(&entry);
 
         let sender_clone = sender.clone();
         dialog.connect_response(move |dialog, response| {
             if response == ResponseType::Accept {
-                if let Some(file) = dialog.file() {
-                    if let Some(path) = file.path() {
-                        sender_clone.input(MainWindowMsg::ExistingSourceFolderSelected(path));
-                    } else {
-                        sender_clone.input(MainWindowMsg::ExistingSourceFolderCancelled);
-                    }
-                } else {
-                    sender_clone.input(MainWindowMsg::ExistingSourceFolderCancelled);
-                }
-            } else {
-                sender_clone.input(MainWindowMsg::ExistingSourceFolderCancelled);
+                sender_clone.input(MainWindowMsg::RenameCapsuleConfirmed {
+                    capsule_dir: capsule_dir.clone(),
+                    new_name: entry.text().to_string(),
+                });
             }
-            dialog.destroy();
+            sender_clone.input(MainWindowMsg::RenameDialogClosed);
+            dialog.close();
         });
 
         dialog.show();
-        self.game_path_dialog = Some(dialog);
-    }
-
-    fn start_umu_db_sync(sender: ComponentSender<Self>) {
-        thread::spawn(move || match UmuDatabase::load_or_fetch() {
-            Ok(entries) => sender.input(MainWindowMsg::UmuDatabaseLoaded(entries)),
-            Err(e) => sender.input(MainWindowMsg::UmuDatabaseFailed(e.to_string())),
-        });
+        self.rename_dialog = Some(dialog);
     }
 
-    fn open_umu_match_dialog(
-        &mut self,
-        sender: ComponentSender<Self>,
-        game_name: String,
-        matches: Vec<UmuMatch>,
-    ) {
-        if self.umu_match_dialog.is_some() {
+    /// Edit how a game is shown and sorted in the library. Unlike renaming,
+    /// nothing on disk changes.
+    fn open_game_info_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.game_info_dialog.is_some() {
             return;
         }
 
-        let dialog = Dialog::builder()
-            .title("Match UMU Game")
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        let metadata = &capsule.metadata;
+
+        let dialog = Dialog::builder()
+            .title("Title & Info")
             .modal(true)
             .transient_for(&self.root_window)
             .build();
-        dialog.add_button("Skip", ResponseType::Cancel);
-        dialog.add_button("Use Selection", ResponseType::Accept);
+        dialog.set_default_width(420);
+        dialog.set_resizable(false);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Save", ResponseType::Accept);
+        dialog.set_default_response(ResponseType::Accept);
 
         let content = dialog.content_area();
-        let layout = Box::new(Orientation::Vertical, 8);
-        layout.set_margin_all(12);
-
-        let title = Label::new(Some(&format!(
-            "Select the UMU match for \"{}\"",
-            game_name
+        content.set_margin_all(16);
+        content.set_spacing(10);
+        let hint = Label::new(Some(&format!(
+            "Changes only how the game is shown and sorted. Its folder stays \"{}\"; use \
+             Rename to change that.",
+            capsule.name
         )));
-        title.set_halign(gtk4::Align::Start);
-        title.set_wrap(true);
-        title.set_css_classes(&["section-title"]);
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+        content.append(&hint);
+
+        let grid = Grid::new();
+        grid.set_row_spacing(8);
+        grid.set_column_spacing(12);
+        let fields = [
+            ("Title", metadata.display_title.clone(), capsule.name.clone()),
+            (
+                "Sort as",
+                metadata.sort_title.clone(),
+                "Same as the title".to_string(),
+            ),
+            (
+                "Release year",
+                metadata.release_year.map(|year| year.to_string()),
+                "e.g., 2015".to_string(),
+            ),
+            ("Developer", metadata.developer.clone(), String::new()),
+        ];
+        let mut entries = Vec::new();
+        for (row, (label_text, value, placeholder)) in fields.into_iter().enumerate() {
+            let label = Label::new(Some(label_text));
+            label.set_xalign(0.0);
+            let entry = Entry::new();
+            entry.set_hexpand(true);
+            entry.set_text(value.as_deref().unwrap_or_default());
+            entry.set_placeholder_text(Some(&placeholder));
+            entry.set_activates_default(true);
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(&entry, 1, row as i32, 1, 1);
+            entries.push(entry);
+        }
+        content.append(&grid);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                let year_text = entries[2].text().trim().to_string();
+                let release_year = year_text.parse::<u32>().ok();
+                if release_year.is_none() && !year_text.is_empty() {
+                    eprintln!("Ignoring release year {:?}; it isn't a number", year_text);
+                }
+                sender_clone.input(MainWindowMsg::GameInfoSaved {
+                    capsule_dir: capsule_dir.clone(),
+                    display_title: MainWindow::optional_text(&entries[0]),
+                    sort_title: MainWindow::optional_text(&entries[1]),
+                    release_year,
+                    developer: MainWindow::optional_text(&entries[3]),
+                });
+            }
+            sender_clone.input(MainWindowMsg::GameInfoDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.game_info_dialog = Some(dialog);
+    }
+
+    fn open_duplicate_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.duplicate_dialog.is_some() {
+            return;
+        }
+
+        let current_name = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule.name,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let dialog = Dialog::builder()
+            .title("Duplicate Game")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(420);
+        dialog.set_resizable(false);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Duplicate", ResponseType::Accept);
+        dialog.set_default_response(ResponseType::Accept);
 
+        let content = dialog.content_area();
+        content.set_margin_all(16);
+        content.set_spacing(10);
+        let label = Label::new(Some("Name for the copy"));
+        label.set_halign(gtk4::Align::Start);
+        label.set_css_classes(&["section-title"]);
+        let entry = Entry::new();
+        entry.set_hexpand(true);
+        entry.set_text(&format!("{} (copy)", current_name));
+        entry.set_activates_default(true);
+        let share_check = CheckButton::with_label("Prefix only (share game files with the original)");
         let hint = Label::new(Some(
-            "Pick the correct storefront entry. If none match, click Skip.",
+            "Useful for trying a different runtime or mod setup without copying the whole game.",
         ));
         hint.set_halign(gtk4::Align::Start);
         hint.set_wrap(true);
         hint.set_css_classes(&["muted"]);
+        content.append(&label);
+        content.append(&entry);
+        content.append(&share_check);
+        content.append(&hint);
 
-        let listbox = ListBox::new();
-        listbox.set_selection_mode(SelectionMode::Single);
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender_clone.input(MainWindowMsg::DuplicateCapsuleConfirmed {
+                    capsule_dir: capsule_dir.clone(),
+                    new_name: entry.text().to_string(),
+                    share_game_files: share_check.is_active(),
+                });
+            }
+            sender_clone.input(MainWindowMsg::DuplicateDialogClosed);
+            dialog.close();
+        });
 
-        for candidate in &matches {
-            let entry = &candidate.entry;
-            let row = ListBoxRow::new();
-            let row_box = Box::new(Orientation::Vertical, 4);
-            row_box.set_margin_all(8);
+        dialog.show();
+        self.duplicate_dialog = Some(dialog);
+    }
 
-            let title_text = entry.title.as_deref().unwrap_or("Unknown title");
-            let title_label = Label::new(Some(title_text));
-            title_label.set_halign(gtk4::Align::Start);
-            title_label.set_wrap(true);
-            title_label.set_css_classes(&["card-title"]);
+    fn start_capsule_duplicate(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        new_name: String,
+        share_game_files: bool,
+    ) {
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
 
-            let umu_id = entry.umu_id.as_deref().unwrap_or("unknown");
-            let store = entry.store.as_deref().unwrap_or("unknown");
-            let codename = entry.codename.as_deref().unwrap_or("unknown");
-            let detail_text = format!("UMU ID: {umu_id} • Store: {store} • Codename: {codename}");
-            let detail_label = Label::new(Some(&detail_text));
-            detail_label.set_halign(gtk4::Align::Start);
-            detail_label.set_wrap(true);
-            detail_label.set_css_classes(&["muted"]);
+        let new_dir = self.unique_game_dir(&new_name);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&format!("Duplicating {}", capsule.name), cancel.clone());
 
-            row_box.append(&title_label);
-            row_box.append(&detail_label);
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let total = file_ops::dir_size(&capsule_dir);
+            let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: 0, total });
+
+            let mut copied: u64 = 0;
+            let mut last_report = Instant::now();
+            let result = capsule
+                .duplicate(&new_dir, &new_name, share_game_files, &mut |bytes| {
+                    copied += bytes;
+                    if last_report.elapsed() >= Duration::from_millis(100) {
+                        last_report = Instant::now();
+                        let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                    }
+                    !cancel.load(Ordering::Relaxed)
+                })
+                .map(|duplicate| duplicate.capsule_dir)
+                .map_err(|e| e.to_string());
 
-            if let Some(notes) = entry.notes.as_deref() {
-                if !notes.trim().is_empty() {
-                    let notes_label = Label::new(Some(notes));
-                    notes_label.set_halign(gtk4::Align::Start);
-                    notes_label.set_wrap(true);
-                    notes_label.set_css_classes(&["muted"]);
-                    row_box.append(&notes_label);
-                }
-            }
+            let _ = sender_clone.input(MainWindowMsg::CapsuleDuplicateFinished(result));
+        });
+    }
+
+    fn open_heroic_import_dialog(&mut self, sender: ComponentSender<Self>) {
+        if self.heroic_dialog.is_some() {
+            return;
+        }
+
+        let games = HeroicLibrary::installed_games();
+
+        let dialog = Dialog::builder()
+            .title("Import from Heroic")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .default_width(520)
+            .default_height(420)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Import", ResponseType::Accept);
+        dialog.set_response_sensitive(ResponseType::Accept, !games.is_empty());
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some("Installed Heroic games"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let hint = Label::new(Some(if games.is_empty() {
+            "No installed Windows games were found in Heroic's GOG or Epic library."
+        } else {
+            "Game files stay where Heroic installed them and are linked into the capsule."
+        }));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
 
+        let listbox = ListBox::new();
+        listbox.set_selection_mode(SelectionMode::Single);
+        for game in &games {
+            let row_box = Box::new(Orientation::Vertical, 2);
+            row_box.set_margin_all(6);
+            let name = Label::new(Some(&game.title));
+            name.set_halign(gtk4::Align::Start);
+            let exe_text = game
+                .executable
+                .as_ref()
+                .map(|exe| exe.to_string_lossy().to_string())
+                .unwrap_or_else(|| "executable not found".to_string());
+            let detail = Label::new(Some(&format!(
+                "{} • {} • {}",
+                game.store.label(),
+                game.install_path.to_string_lossy(),
+                exe_text
+            )));
+            detail.set_halign(gtk4::Align::Start);
+            detail.set_wrap(true);
+            detail.set_css_classes(&["muted"]);
+            row_box.append(&name);
+            row_box.append(&detail);
+            let row = ListBoxRow::new();
             row.set_child(Some(&row_box));
             listbox.append(&row);
         }
-
         if let Some(first_row) = listbox.row_at_index(0) {
             listbox.select_row(Some(&first_row));
         }
@@ -1172,241 +2433,316 @@ impl MainWindow {
         scroller.set_vexpand(true);
         scroller.set_child(Some(&listbox));
 
+        let copy_prefix_check =
+            CheckButton::with_label("Copy Heroic's Wine prefix (keeps saves and settings)");
+        copy_prefix_check.set_active(true);
+
         layout.append(&title);
         layout.append(&hint);
         layout.append(&scroller);
+        layout.append(&copy_prefix_check);
         content.append(&layout);
 
         let sender_clone = sender.clone();
-        let matches_clone = matches.clone();
         dialog.connect_response(move |dialog, response| {
             if response == ResponseType::Accept {
-                if let Some(row) = listbox.selected_row() {
-                    let index = row.index();
-                    if index >= 0 {
-                        if let Some(selected) = matches_clone.get(index as usize) {
-                            sender_clone.input(MainWindowMsg::UmuMatchChosen {
-                                game_id: selected.entry.umu_id.clone(),
-                                store: selected.entry.store.clone(),
-                            });
-                        } else {
-                            sender_clone.input(MainWindowMsg::UmuMatchChosen {
-                                game_id: None,
-                                store: None,
-                            });
-                        }
-                    } else {
-                        sender_clone.input(MainWindowMsg::UmuMatchChosen {
-                            game_id: None,
-                            store: None,
-                        });
-                    }
-                } else {
-                    sender_clone.input(MainWindowMsg::UmuMatchChosen {
-                        game_id: None,
-                        store: None,
+                let selected = listbox
+                    .selected_row()
+                    .and_then(|row| usize::try_from(row.index()).ok())
+                    .and_then(|index| games.get(index));
+                if let Some(game) = selected {
+                    sender_clone.input(MainWindowMsg::HeroicGameChosen {
+                        game: game.clone(),
+                        copy_prefix: copy_prefix_check.is_active(),
                     });
                 }
-            } else {
-                sender_clone.input(MainWindowMsg::UmuMatchChosen {
-                    game_id: None,
-                    store: None,
-                });
             }
-
-            sender_clone.input(MainWindowMsg::UmuMatchDialogClosed);
+            sender_clone.input(MainWindowMsg::HeroicDialogClosed);
             dialog.close();
         });
 
         dialog.show();
-        self.umu_match_dialog = Some(dialog);
+        self.heroic_dialog = Some(dialog);
     }
 
-    fn open_dependency_dialog(
+    fn start_heroic_import(
         &mut self,
         sender: ComponentSender<Self>,
-        capsule_dir: PathBuf,
-        metadata: CapsuleMetadata,
+        game: HeroicGame,
+        copy_prefix: bool,
     ) {
-        if self.dependency_dialog.is_some() {
+        let name = Self::sanitize_name(&game.title);
+        if name.is_empty() {
+            eprintln!("Game name cannot be empty");
+            return;
+        }
+        if let Err(e) = fs::create_dir_all(&self.games_dir) {
+            eprintln!("Failed to create games directory: {}", e);
             return;
         }
+        let capsule_dir = self.unique_game_dir(&name);
+        let game_id = game.umu_id(&self.umu_entries);
+        if game_id.is_none() {
+            println!("No UMU entry for {} ({}), using defaults", game.title, game.app_name);
+        }
+
+        let defaults = self.new_game_metadata();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&format!("Importing {}", game.title), cancel.clone());
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let total = match (&game.wine_prefix, copy_prefix) {
+                (Some(prefix), true) => file_ops::dir_size(prefix),
+                _ => 0,
+            };
+            let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: 0, total });
+
+            let mut copied: u64 = 0;
+            let mut last_report = Instant::now();
+            let result = HeroicLibrary::create_capsule(
+                &game,
+                &capsule_dir,
+                &name,
+                game_id,
+                copy_prefix,
+                defaults,
+                &mut |bytes| {
+                    copied += bytes;
+                    if last_report.elapsed() >= Duration::from_millis(100) {
+                        last_report = Instant::now();
+                        let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                    }
+                    !cancel.load(Ordering::Relaxed)
+                },
+            )
+            .map(|capsule| capsule.capsule_dir)
+            .map_err(|e| e.to_string());
+
+            let _ = sender_clone.input(MainWindowMsg::HeroicImportFinished(result));
+        });
+    }
 
-        let vcredist_cached = Self::vcredist_cache_path().is_file();
-        let dxweb_cached = Self::dxweb_cache_path().is_file();
+    fn open_existing_game_location_dialog(
+        &mut self,
+        sender: ComponentSender<Self>,
+        game_name: String,
+    ) {
+        if self.existing_location_dialog.is_some() {
+            return;
+        }
 
         let dialog = Dialog::builder()
-            .title("Install Dependencies")
+            .title("Game Folder")
             .modal(true)
             .transient_for(&self.root_window)
             .build();
-        dialog.add_button("Skip", ResponseType::Cancel);
-        dialog.add_button("Install", ResponseType::Accept);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Continue", ResponseType::Accept);
 
         let content = dialog.content_area();
         let layout = Box::new(Orientation::Vertical, 8);
         layout.set_margin_all(12);
 
-        let title = Label::new(Some("Install optional dependencies?"));
+        let title = Label::new(Some("Choose where to place the game files"));
         title.set_halign(gtk4::Align::Start);
-        title.set_wrap(true);
         title.set_css_classes(&["section-title"]);
 
         let hint = Label::new(Some(
-            "These installers are cached by linuxboy-setup.sh. Disable any you don't want.",
+            "Path is relative to the prefix 'games' folder.",
         ));
         hint.set_halign(gtk4::Align::Start);
         hint.set_wrap(true);
         hint.set_css_classes(&["muted"]);
 
-        let vcredist_row = Box::new(Orientation::Vertical, 4);
-        let vcredist_check = CheckButton::with_label("VC++ Redistributables (AIO)");
-        vcredist_check.set_active(metadata.install_vcredist && vcredist_cached);
-        vcredist_check.set_sensitive(vcredist_cached);
-        let vcredist_status = Label::new(Some(if vcredist_cached {
-            "Cached"
-        } else {
-            "Not downloaded (run setup script)"
-        }));
-        vcredist_status.set_halign(gtk4::Align::Start);
-        vcredist_status.set_css_classes(&["muted"]);
-        vcredist_row.append(&vcredist_check);
-        vcredist_row.append(&vcredist_status);
-
-        let dxweb_row = Box::new(Orientation::Vertical, 4);
-        let dxweb_check = CheckButton::with_label("DirectX (June 2010) Redist");
-        dxweb_check.set_active(metadata.install_dxweb && dxweb_cached);
-        dxweb_check.set_sensitive(dxweb_cached);
-        let dxweb_status = Label::new(Some(if dxweb_cached {
-            "Cached"
-        } else {
-            "Not downloaded (run setup script)"
-        }));
-        dxweb_status.set_halign(gtk4::Align::Start);
-        dxweb_status.set_css_classes(&["muted"]);
-        dxweb_row.append(&dxweb_check);
-        dxweb_row.append(&dxweb_status);
+        let location_label = Label::new(Some("Game folder (inside prefix/games)"));
+        location_label.set_halign(gtk4::Align::Start);
+        let location_entry = Entry::new();
+        location_entry.set_placeholder_text(Some("e.g., MyGame"));
+        location_entry.set_text(&game_name);
+
+        let transfer_label = Label::new(Some("How should the game files be added?"));
+        transfer_label.set_halign(gtk4::Align::Start);
+        let copy_check = CheckButton::with_label("Copy (keeps the original folder)");
+        copy_check.set_active(true);
+        let move_check = CheckButton::with_label("Move into prefix");
+        move_check.set_group(Some(&copy_check));
+        let link_check = CheckButton::with_label("Link in place (no extra disk space)");
+        link_check.set_group(Some(&copy_check));
 
         layout.append(&title);
         layout.append(&hint);
-        layout.append(&vcredist_row);
-        layout.append(&dxweb_row);
+        layout.append(&location_label);
+        layout.append(&location_entry);
+        layout.append(&transfer_label);
+        layout.append(&copy_check);
+        layout.append(&move_check);
+        layout.append(&link_check);
         content.append(&layout);
 
         let sender_clone = sender.clone();
-        let capsule_dir_clone = capsule_dir.clone();
-        let vcredist_check_clone = vcredist_check.clone();
-        let dxweb_check_clone = dxweb_check.clone();
         dialog.connect_response(move |dialog, response| {
             if response == ResponseType::Accept {
-                sender_clone.input(MainWindowMsg::DependenciesSelected {
-                    capsule_dir: capsule_dir_clone.clone(),
-                    install_vcredist: vcredist_check_clone.is_active(),
-                    install_dxweb: dxweb_check_clone.is_active(),
-                    force: false,
+                let transfer = if move_check.is_active() {
+                    ExistingGameTransfer::Move
+                } else if link_check.is_active() {
+                    ExistingGameTransfer::Link
+                } else {
+                    ExistingGameTransfer::Copy
+                };
+                sender_clone.input(MainWindowMsg::ExistingGameLocationConfirmed {
+                    folder: location_entry.text().to_string(),
+                    transfer,
                 });
+            } else {
+                sender_clone.input(MainWindowMsg::ExistingGameLocationCancelled);
             }
-            sender_clone.input(MainWindowMsg::DependenciesDialogClosed);
             dialog.close();
         });
 
         dialog.show();
-        self.dependency_dialog = Some(dialog);
+        self.existing_location_dialog = Some(dialog);
     }
 
-    fn start_dependency_install(
-        &mut self,
-        sender: ComponentSender<Self>,
-        capsule_dir: PathBuf,
-        metadata: CapsuleMetadata,
-        install_vcredist: bool,
-        install_dxweb: bool,
-        force: bool,
-    ) {
-        if !Self::has_command("umu-run") {
-            eprintln!("umu-run not found in PATH");
+    fn open_existing_source_folder_dialog(&mut self, sender: ComponentSender<Self>) {
+        if self.game_path_dialog.is_some() {
             return;
         }
 
-        let proton_path = match self.runtime_mgr.latest_installed() {
-            Ok(Some(path)) => path,
-            Ok(None) => {
-                eprintln!("No Proton-GE runtime installed");
-                return;
-            }
-            Err(e) => {
-                eprintln!("Failed to resolve Proton-GE runtime: {}", e);
-                return;
-            }
-        };
-
-        let home_path = capsule_dir.join(format!("{}.AppImage.home", metadata.name));
-        let prefix_path = home_path.join("prefix");
+        let dialog = FileChooserNative::builder()
+            .title("Select Game Folder")
+            .action(FileChooserAction::SelectFolder)
+            .accept_label("Select")
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
 
-        let mut tasks: Vec<(&'static str, PathBuf)> = Vec::new();
-        if install_vcredist && (force || !Self::is_dependency_installed(&metadata, Self::DEP_VCREDIST))
-        {
-            let path = Self::vcredist_cache_path();
-            if path.is_file() {
-                tasks.push((Self::DEP_VCREDIST, path));
-            } else {
-                eprintln!("VC++ installer not cached; run linuxboy-setup.sh");
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(file) = dialog.file() {
+                    if let Some(path) = file.path() {
+                        sender_clone.input(MainWindowMsg::ExistingSourceFolderSelected(path));
+                    } else {
+                        sender_clone.input(MainWindowMsg::ExistingSourceFolderCancelled);
+                    }
+                } else {
+                    sender_clone.input(MainWindowMsg::ExistingSourceFolderCancelled);
+                }
+            } else {
+                sender_clone.input(MainWindowMsg::ExistingSourceFolderCancelled);
             }
+            dialog.destroy();
+        });
+
+        dialog.show();
+        self.game_path_dialog = Some(dialog);
+    }
+
+    fn open_copy_progress_dialog(&mut self, title: &str, cancel: Arc<AtomicBool>) {
+        if self.copy_dialog.is_some() {
+            return;
         }
 
-        if install_dxweb && (force || !Self::is_dependency_installed(&metadata, Self::DEP_DXWEB)) {
-            let path = Self::dxweb_cache_path();
-            if path.is_file() {
-                tasks.push((Self::DEP_DXWEB, path));
-            } else {
-                eprintln!("DirectX redist not cached; run linuxboy-setup.sh");
-            }
+        let dialog = Dialog::builder()
+            .title(title)
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(420);
+        dialog.set_deletable(false);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let status = Label::new(Some("Calculating size..."));
+        status.set_halign(gtk4::Align::Start);
+        status.set_css_classes(&["muted"]);
+
+        let progress = ProgressBar::new();
+        progress.set_show_text(true);
+
+        layout.append(&status);
+        layout.append(&progress);
+        content.append(&layout);
+
+        // Keep the dialog up until the worker reports back; closing only requests cancel.
+        dialog.connect_close_request(|_| glib::Propagation::Stop);
+        dialog.connect_response(move |dialog, _| {
+            cancel.store(true, Ordering::Relaxed);
+            dialog.set_response_sensitive(ResponseType::Cancel, false);
+        });
+
+        dialog.show();
+        self.copy_dialog = Some(dialog);
+        self.copy_status_label = Some(status);
+        self.copy_progress_bar = Some(progress);
+    }
+
+    fn close_copy_progress_dialog(&mut self) {
+        if let Some(dialog) = self.copy_dialog.take() {
+            dialog.destroy();
         }
+        self.copy_status_label = None;
+        self.copy_progress_bar = None;
+    }
 
-        if tasks.is_empty() {
+    fn is_capsule_busy(&self, capsule_dir: &Path) -> bool {
+        self.active_games.contains_key(capsule_dir)
+            || self.active_installs.contains_key(capsule_dir)
+            || self.preparing_installs.contains(capsule_dir)
+            || self.dependency_installs.contains(capsule_dir)
+            || self.snapshot_jobs.contains(capsule_dir)
+            || self.winetricks_jobs.contains(capsule_dir)
+            || self.registry_jobs.contains(capsule_dir)
+            || self.shutdown_jobs.contains(capsule_dir)
+            || self.repair_jobs.contains(capsule_dir)
+            || self.uninstall_jobs.contains(capsule_dir)
+            || self.patch_jobs.contains(capsule_dir)
+            || self.mod_jobs.contains(capsule_dir)
+            || self.archive_jobs.contains(capsule_dir)
+            || self.dedupe_running
+            || self.cloud_sync_jobs.contains(capsule_dir)
+    }
+
+    fn open_move_capsule_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.capsule_folder_dialog.is_some() {
             return;
         }
 
-        self.dependency_installs.insert(capsule_dir.clone());
-        self.rebuild_games_list(sender.clone());
+        let dialog = FileChooserNative::builder()
+            .title("Move Capsule To")
+            .action(FileChooserAction::SelectFolder)
+            .accept_label("Move Here")
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
 
         let sender_clone = sender.clone();
-        thread::spawn(move || {
-            let mut installed: Vec<String> = Vec::new();
-            for (dep, path) in tasks {
-                let success = if dep == Self::DEP_DXWEB {
-                    Self::install_directx_redist(&prefix_path, &proton_path, &metadata, &path)
-                } else {
-                    let mut cmd = Self::umu_base_command(&prefix_path, &proton_path, &metadata);
-                    cmd.env("PROTON_USE_XALIA", "0");
-                    cmd.arg(&path);
-                    match cmd.status() {
-                        Ok(status) => status.success(),
-                        Err(e) => {
-                            eprintln!("Failed to run dependency installer {:?}: {}", path, e);
-                            false
-                        }
-                    }
-                };
-
-                if success {
-                    installed.push(dep.to_string());
-                } else {
-                    eprintln!("Dependency installer failed: {:?}", path);
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    sender_clone.input(MainWindowMsg::MoveCapsuleTargetSelected {
+                        capsule_dir: capsule_dir.clone(),
+                        target_dir: path,
+                    });
                 }
             }
-
-            let _ = sender_clone.input(MainWindowMsg::DependenciesFinished {
-                capsule_dir,
-                installed,
-            });
+            sender_clone.input(MainWindowMsg::CapsuleFolderDialogClosed);
+            dialog.destroy();
         });
+
+        dialog.show();
+        self.capsule_folder_dialog = Some(dialog);
     }
 
-    fn start_game(
+    fn start_capsule_move(
         &mut self,
         sender: ComponentSender<Self>,
         capsule_dir: PathBuf,
+        target_dir: PathBuf,
     ) {
         let capsule = match Capsule::load_from_dir(&capsule_dir) {
             Ok(capsule) => capsule,
@@ -1416,345 +2752,487 @@ impl MainWindow {
             }
         };
 
-        if capsule.metadata.executables.main.path.trim().is_empty() {
-            eprintln!("No executable configured for {}", capsule.name);
-            return;
-        }
-
-        if !Self::has_command("umu-run") {
-            eprintln!("umu-run not found in PATH");
-            return;
-        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&format!("Moving {}", capsule.name), cancel.clone());
 
-        let proton_path = match self.runtime_mgr.latest_installed() {
-            Ok(Some(path)) => path,
-            Ok(None) => {
-                eprintln!("No Proton-GE runtime installed");
-                return;
-            }
-            Err(e) => {
-                eprintln!("Failed to resolve Proton-GE runtime: {}", e);
-                return;
-            }
-        };
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let total = file_ops::dir_size(&capsule_dir);
+            let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: 0, total });
+
+            let mut copied: u64 = 0;
+            let mut last_report = Instant::now();
+            let result = capsule
+                .relocate(&target_dir, &mut |bytes| {
+                    copied += bytes;
+                    if last_report.elapsed() >= Duration::from_millis(100) {
+                        last_report = Instant::now();
+                        let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                    }
+                    !cancel.load(Ordering::Relaxed)
+                })
+                .map_err(|e| e.to_string());
 
-        let home_path = capsule.capsule_dir.join(format!("{}.AppImage.home", capsule.name));
-        let prefix_path = home_path.join("prefix");
+            let _ = sender_clone.input(MainWindowMsg::CapsuleMoveFinished { capsule_dir, result });
+        });
+    }
 
-        if !Self::run_umu_preflight(&prefix_path, &proton_path, &capsule.metadata) {
-            eprintln!("UMU runtime preload failed.");
+    fn open_export_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.export_dialog.is_some() {
             return;
         }
 
-        let exe_path = PathBuf::from(&capsule.metadata.executables.main.path);
-        let mut cmd = Self::umu_base_command(&prefix_path, &proton_path, &capsule.metadata);
-        cmd.arg(&exe_path);
-        if let Some(exe_dir) = exe_path.parent().filter(|dir| dir.is_dir()) {
-            cmd.current_dir(exe_dir);
-        }
+        let dialog = Dialog::builder()
+            .title("Export Capsule")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Choose file...", ResponseType::Accept);
 
-        let args = capsule.metadata.executables.main.args.trim();
-        if !args.is_empty() {
-            cmd.args(args.split_whitespace());
-        }
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
 
-        for trick in &capsule.metadata.protonfixes_tricks {
-            cmd.arg(format!("-pf_tricks={}", trick));
-        }
-        for replace in &capsule.metadata.protonfixes_replace_cmds {
-            cmd.arg(format!("-pf_replace_cmd={}", replace));
-        }
-        for option in &capsule.metadata.protonfixes_dxvk_sets {
-            cmd.arg(format!("-pf_dxvk_set={}", option));
-        }
+        let defaults = CompressionOptions::default();
+
+        let codec_label = Label::new(Some("Compression"));
+        codec_label.set_halign(gtk4::Align::Start);
+        let zstd_check = CheckButton::with_label("zstd (fast, recommended)");
+        let gzip_check = CheckButton::with_label("gzip (compatible)");
+        gzip_check.set_group(Some(&zstd_check));
+        zstd_check.set_active(defaults.codec == Codec::Zstd);
+        gzip_check.set_active(defaults.codec == Codec::Gzip);
+
+        let level_row = Box::new(Orientation::Horizontal, 8);
+        let level_label = Label::new(Some("Level"));
+        let (min, max) = defaults.codec.level_range();
+        let level_spin = SpinButton::with_range(min as f64, max as f64, 1.0);
+        level_spin.set_value(defaults.level as f64);
+        level_row.append(&level_label);
+        level_row.append(&level_spin);
+
+        let level_spin_clone = level_spin.clone();
+        zstd_check.connect_toggled(move |check| {
+            let codec = if check.is_active() { Codec::Zstd } else { Codec::Gzip };
+            let (min, max) = codec.level_range();
+            level_spin_clone.set_range(min as f64, max as f64);
+            level_spin_clone.set_value(codec.default_level() as f64);
+        });
 
-        unsafe {
-            cmd.pre_exec(|| {
-                libc::setpgid(0, 0);
-                Ok(())
-            });
-        }
+        let incremental_check = CheckButton::with_label("Incremental (only changes since the last backup)");
+        let incremental_hint = Label::new(Some(
+            "Builds on the newest backup of this game in the chosen folder. Keep all backups of a chain together.",
+        ));
+        incremental_hint.set_halign(gtk4::Align::Start);
+        incremental_hint.set_wrap(true);
+        incremental_hint.set_css_classes(&["muted"]);
+
+        layout.append(&codec_label);
+        layout.append(&zstd_check);
+        layout.append(&gzip_check);
+        layout.append(&level_row);
+        layout.append(&incremental_check);
+        layout.append(&incremental_hint);
+        content.append(&layout);
 
         let sender_clone = sender.clone();
-        thread::spawn(move || {
-            let mut child = match cmd.spawn() {
-                Ok(child) => child,
-                Err(e) => {
-                    eprintln!("Failed to launch game: {}", e);
-                    let _ = sender_clone.input(MainWindowMsg::GameFinished {
-                        capsule_dir,
-                        success: false,
-                    });
-                    return;
-                }
-            };
-
-            let pid = child.id() as i32;
-            let pgid = unsafe { libc::getpgid(pid) };
-            if pgid > 0 {
-                let _ = sender_clone.input(MainWindowMsg::GameStarted {
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                let codec = if zstd_check.is_active() {
+                    Codec::Zstd
+                } else {
+                    Codec::Gzip
+                };
+                sender_clone.input(MainWindowMsg::ExportOptionsConfirmed {
                     capsule_dir: capsule_dir.clone(),
-                    pgid,
+                    compression: CompressionOptions {
+                        codec,
+                        level: level_spin.value_as_int(),
+                    },
+                    incremental: incremental_check.is_active(),
                 });
             }
-
-            let success = child.wait().map(|status| status.success()).unwrap_or(false);
-            let _ = sender_clone.input(MainWindowMsg::GameFinished {
-                capsule_dir,
-                success,
-            });
+            sender_clone.input(MainWindowMsg::ExportDialogClosed);
+            dialog.close();
         });
+
+        dialog.show();
+        self.export_dialog = Some(dialog);
     }
 
-    fn finalize_pending_game(
+    fn open_export_destination_dialog(
         &mut self,
         sender: ComponentSender<Self>,
-        game_id: Option<String>,
-        store: Option<String>,
+        capsule_dir: PathBuf,
+        compression: CompressionOptions,
+        incremental: bool,
     ) {
-        self.pending_add_mode = None;
-        self.pending_game_id = None;
-        self.pending_store = None;
-        let installer_path = match self.pending_game_path.take() {
-            Some(path) => path,
-            None => {
-                eprintln!("No installer path selected");
-                self.pending_game_name = None;
+        if self.backup_file_dialog.is_some() {
+            return;
+        }
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
                 return;
             }
         };
 
-        let name = match self.pending_game_name.take() {
-            Some(name) => name,
-            None => {
-                eprintln!("No pending game name available");
-                return;
+        let dialog = FileChooserNative::builder()
+            .title("Export Capsule To")
+            .action(FileChooserAction::Save)
+            .accept_label("Export")
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_current_name(&format!(
+            "{}-{}{}.{}",
+            capsule.name,
+            chrono::Local::now().format("%Y%m%d-%H%M"),
+            if incremental { "-inc" } else { "" },
+            compression.codec.extension()
+        ));
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    sender_clone.input(MainWindowMsg::ExportDestinationSelected {
+                        capsule_dir: capsule_dir.clone(),
+                        dest: path,
+                        compression,
+                        incremental,
+                    });
+                }
             }
-        };
+            sender_clone.input(MainWindowMsg::BackupFileDialogClosed);
+            dialog.destroy();
+        });
 
-        if let Err(e) = fs::create_dir_all(&self.games_dir) {
-            eprintln!("Failed to create games directory: {}", e);
-            return;
-        }
+        dialog.show();
+        self.backup_file_dialog = Some(dialog);
+    }
 
-        let capsule_dir = self.unique_game_dir(&name);
-        if let Err(e) = fs::create_dir_all(&capsule_dir) {
-            eprintln!("Failed to create capsule directory: {}", e);
+    fn open_backup_file_dialog(
+        &mut self,
+        sender: ComponentSender<Self>,
+        title: &str,
+        accept_label: &str,
+        verify: bool,
+    ) {
+        if self.backup_file_dialog.is_some() {
             return;
         }
 
-        let mut metadata = CapsuleMetadata::default();
-        metadata.name = name.clone();
-        metadata.installer_path = Some(installer_path.to_string_lossy().to_string());
-        metadata.install_state = InstallState::Installing;
-        metadata.game_id = game_id;
-        metadata.store = store;
-        let home_path = capsule_dir.join(format!("{}.AppImage.home", name));
-        let prefix_path = home_path.join("prefix");
-        let default_game_dir = prefix_path.join("games").join(&metadata.name);
-        metadata.game_dir = Some(default_game_dir.to_string_lossy().to_string());
+        let dialog = FileChooserNative::builder()
+            .title(title)
+            .action(FileChooserAction::Open)
+            .accept_label(accept_label)
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
 
-        self.start_installer(&sender, capsule_dir, metadata, installer_path);
-        sender.input(MainWindowMsg::LoadCapsules);
+        let filter = FileFilter::new();
+        filter.add_pattern("*.tar.zst");
+        filter.add_pattern("*.tar.gz");
+        filter.set_name(Some("LinuxBoy backups (.tar.zst, .tar.gz)"));
+        dialog.add_filter(&filter);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    if verify {
+                        sender_clone.input(MainWindowMsg::VerifyArchiveSelected(path));
+                    } else {
+                        sender_clone.input(MainWindowMsg::ImportArchiveSelected(path));
+                    }
+                }
+            }
+            sender_clone.input(MainWindowMsg::BackupFileDialogClosed);
+            dialog.destroy();
+        });
+
+        dialog.show();
+        self.backup_file_dialog = Some(dialog);
     }
 
-    fn finalize_existing_game(
+    fn start_capsule_export(
         &mut self,
         sender: ComponentSender<Self>,
-        target_input: String,
+        capsule_dir: PathBuf,
+        dest: PathBuf,
+        compression: CompressionOptions,
+        incremental: bool,
     ) {
-        self.pending_add_mode = None;
-        let exe_path = match self.pending_game_path.take() {
-            Some(path) => path,
-            None => {
-                eprintln!("No game executable selected");
-                self.pending_game_name = None;
-                self.pending_game_id = None;
-                self.pending_store = None;
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
                 return;
             }
         };
 
-        let name = match self.pending_game_name.take() {
-            Some(name) => name,
-            None => {
-                eprintln!("No pending game name available");
-                self.pending_game_id = None;
-                self.pending_store = None;
-                return;
-            }
-        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&format!("Exporting {}", capsule.name), cancel.clone());
 
-        let source_dir = match self.pending_source_folder.take() {
-            Some(path) => path,
-            None => {
-                eprintln!("No source folder selected");
-                self.pending_game_id = None;
-                self.pending_store = None;
-                return;
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let base = if incremental {
+                dest.parent()
+                    .and_then(|dir| BackupManager::latest_backup(dir, &capsule.name))
+                    .filter(|base| base != &dest)
+            } else {
+                None
+            };
+            if incremental && base.is_none() {
+                println!("No earlier backup of {} found; creating a full backup", capsule.name);
             }
-        };
 
-        let game_id = self.pending_game_id.take();
-        let store = self.pending_store.take();
+            let mut last_report = Instant::now();
+            let result = BackupManager::create_archive(
+                &capsule,
+                &dest,
+                base.as_deref(),
+                compression,
+                &mut |done, total| {
+                    if last_report.elapsed() >= Duration::from_millis(100) {
+                        last_report = Instant::now();
+                        let _ = sender_clone
+                            .input(MainWindowMsg::CopyProgress { copied: done, total });
+                    }
+                    !cancel.load(Ordering::Relaxed)
+                },
+            )
+            .map(|manifest| {
+                format!(
+                    "Exported {} ({:?}, {} files) to {:?}",
+                    capsule.name,
+                    manifest.kind,
+                    manifest.files.len(),
+                    dest
+                )
+            })
+            .map_err(|e| e.to_string());
+
+            let _ = sender_clone.input(MainWindowMsg::CapsuleExportFinished(result));
+        });
+    }
 
-        if let Err(e) = fs::create_dir_all(&self.games_dir) {
-            eprintln!("Failed to create games directory: {}", e);
+    fn open_batch_backup_folder_dialog(&mut self, sender: ComponentSender<Self>) {
+        if self.capsule_folder_dialog.is_some() {
             return;
         }
 
-        let capsule_dir = self.unique_game_dir(&name);
-        if let Err(e) = fs::create_dir_all(&capsule_dir) {
-            eprintln!("Failed to create capsule directory: {}", e);
-            return;
-        }
+        let dialog = FileChooserNative::builder()
+            .title("Back Up Selected Games To")
+            .action(FileChooserAction::SelectFolder)
+            .accept_label("Back Up Here")
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
 
-        let home_path = capsule_dir.join(format!("{}.AppImage.home", name));
-        let prefix_path = home_path.join("prefix");
-        let games_root = prefix_path.join("games");
-        if let Err(e) = fs::create_dir_all(prefix_path.join("drive_c")) {
-            eprintln!("Failed to create prefix: {}", e);
-            return;
-        }
-        if let Err(e) = fs::create_dir_all(&games_root) {
-            eprintln!("Failed to create games folder: {}", e);
-            return;
-        }
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    sender_clone.input(MainWindowMsg::BatchBackupFolderSelected(path));
+                }
+            }
+            sender_clone.input(MainWindowMsg::CapsuleFolderDialogClosed);
+            dialog.destroy();
+        });
 
-        let relative_folder = Self::resolve_relative_game_folder(&name, &target_input);
-        let mut dest_dir = games_root.join(relative_folder);
-        dest_dir = Self::unique_path(dest_dir);
+        dialog.show();
+        self.capsule_folder_dialog = Some(dialog);
+    }
 
-        let mut should_copy = true;
-        if let (Ok(src), Ok(dest)) = (fs::canonicalize(&source_dir), fs::canonicalize(&dest_dir)) {
-            if src == dest {
-                should_copy = false;
+    /// Full backup of every selected game into `folder`, one archive each,
+    /// with the default compression.
+    fn start_batch_backup(&mut self, sender: ComponentSender<Self>, folder: PathBuf) {
+        let mut capsules = Vec::new();
+        for capsule_dir in self.selected_capsule_dirs() {
+            if self.is_capsule_busy(&capsule_dir) {
+                eprintln!("Skipping {:?}: it is busy", capsule_dir);
+                continue;
+            }
+            match Capsule::load_from_dir(&capsule_dir) {
+                Ok(capsule) => capsules.push(capsule),
+                Err(e) => eprintln!("Failed to load capsule: {}", e),
             }
         }
-
-        if exe_path.strip_prefix(&source_dir).is_err() {
-            eprintln!("Selected executable is not inside the chosen folder.");
+        if capsules.is_empty() {
             return;
         }
 
-        if should_copy {
-            if let Err(e) = Self::copy_dir_recursive(&source_dir, &dest_dir) {
-                eprintln!("Failed to copy game files: {}", e);
-                return;
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(
+            &format!("Backing up {} games", capsules.len()),
+            cancel.clone(),
+        );
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let compression = CompressionOptions::default();
+            let stamp = chrono::Local::now().format("%Y%m%d-%H%M").to_string();
+            let mut failures = Vec::new();
+            let mut done = 0;
+            for capsule in &capsules {
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let dest = folder.join(format!(
+                    "{}-{}.{}",
+                    capsule.name,
+                    stamp,
+                    compression.codec.extension()
+                ));
+                let mut last_report = Instant::now();
+                let result = BackupManager::create_archive(
+                    capsule,
+                    &dest,
+                    None,
+                    compression,
+                    &mut |copied, total| {
+                        if last_report.elapsed() >= Duration::from_millis(100) {
+                            last_report = Instant::now();
+                            let _ =
+                                sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                        }
+                        !cancel.load(Ordering::Relaxed)
+                    },
+                );
+                match result {
+                    Ok(_) => done += 1,
+                    Err(e) => failures.push(format!("{}: {}", capsule.name, e)),
+                }
             }
-        }
 
-        let relative_exe = exe_path
-            .strip_prefix(&source_dir)
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| {
-                exe_path
-                    .file_name()
-                    .map(PathBuf::from)
-                    .unwrap_or_else(PathBuf::new)
-            });
-        let new_exe_path = dest_dir.join(relative_exe);
+            let result = if failures.is_empty() {
+                Ok(format!("Backed up {} games to {:?}", done, folder))
+            } else {
+                Err(format!(
+                    "Backed up {} of {} games; failed: {}",
+                    done,
+                    capsules.len(),
+                    failures.join("; ")
+                ))
+            };
+            let _ = sender_clone.input(MainWindowMsg::BatchBackupFinished(result));
+        });
+    }
 
-        let mut metadata = CapsuleMetadata::default();
-        metadata.name = name.clone();
-        metadata.install_state = InstallState::Installed;
-        metadata.executables.main.path = new_exe_path.to_string_lossy().to_string();
-        metadata.game_id = game_id;
-        metadata.store = store;
-        metadata.game_dir = Some(dest_dir.to_string_lossy().to_string());
+    /// Ask how to delete one or more games: the whole capsule or only its
+    /// prefix, with or without a copy of the saves, to the trash or for good.
+    fn open_delete_dialog(&mut self, sender: ComponentSender<Self>, capsule_dirs: Vec<PathBuf>) {
+        if self.delete_dialog.is_some() {
+            return;
+        }
 
-        let capsule = Capsule {
-            name: metadata.name.clone(),
-            capsule_dir: capsule_dir.clone(),
-            home_path,
-            metadata: metadata.clone(),
+        let names: Vec<String> = self
+            .capsules
+            .iter()
+            .filter(|capsule| capsule_dirs.contains(&capsule.capsule_dir))
+            .map(|capsule| capsule.name.clone())
+            .collect();
+        let heading = match names.as_slice() {
+            [name] => format!("Delete {}?", name),
+            _ => format!("Delete {} games?", capsule_dirs.len()),
         };
 
-        if let Err(e) = capsule.save_metadata() {
-            eprintln!("Failed to save metadata: {}", e);
-            return;
+        let dialog = Dialog::builder()
+            .title("Delete")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(460);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Delete", ResponseType::Accept);
+        if let Some(button) = dialog.widget_for_response(ResponseType::Accept) {
+            button.add_css_class("destructive-action");
         }
 
-        if self.should_prompt_dependencies(&metadata) {
-            self.open_dependency_dialog(sender.clone(), capsule_dir.clone(), metadata);
-        }
-        sender.input(MainWindowMsg::LoadCapsules);
-    }
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
 
-    fn find_umu_matches(&self, title: &str) -> Vec<UmuMatch> {
-        if !self.umu_loaded || self.umu_entries.is_empty() {
-            return Vec::new();
+        let title = Label::new(Some(&heading));
+        title.set_halign(gtk4::Align::Start);
+        title.set_wrap(true);
+        title.set_css_classes(&["section-title"]);
+        layout.append(&title);
+        if names.len() > 1 {
+            let list = Label::new(Some(&names.join("\n")));
+            list.set_halign(gtk4::Align::Start);
+            list.set_wrap(true);
+            layout.append(&list);
         }
 
-        let normalized_input = UmuDatabase::normalize_title(title);
-        if normalized_input.is_empty() {
-            return Vec::new();
+        let whole_check = CheckButton::with_label("Delete the whole capsule");
+        let prefix_check =
+            CheckButton::with_label("Remove only the Wine prefix, keep the game files");
+        prefix_check.set_group(Some(&whole_check));
+        whole_check.set_active(true);
+        let prefix_hint = Label::new(Some(
+            "The game stays in the library with its settings; a fresh prefix is created \
+             the next time it starts.",
+        ));
+        prefix_hint.set_halign(gtk4::Align::Start);
+        prefix_hint.set_wrap(true);
+        prefix_hint.set_css_classes(&["muted"]);
+
+        let saves_check = CheckButton::with_label("Keep a copy of the saves");
+        let saves_hint = Label::new(Some(&format!(
+            "Save folders are archived to {} first; use Import saves to bring them back.",
+            CapsuleRemoval::kept_saves_dir().display()
+        )));
+        saves_hint.set_halign(gtk4::Align::Start);
+        saves_hint.set_wrap(true);
+        saves_hint.set_css_classes(&["muted"]);
+
+        let trash_check = CheckButton::with_label("Move to the trash instead of deleting");
+        trash_check.set_active(true);
+
+        let busy_hint = Label::new(Some("Games that are running or busy are skipped."));
+        busy_hint.set_halign(gtk4::Align::Start);
+        busy_hint.set_css_classes(&["muted"]);
+
+        layout.append(&whole_check);
+        layout.append(&prefix_check);
+        layout.append(&prefix_hint);
+        layout.append(&saves_check);
+        layout.append(&saves_hint);
+        layout.append(&trash_check);
+        if capsule_dirs.len() > 1 {
+            layout.append(&busy_hint);
         }
+        content.append(&layout);
 
-        let mut matches: Vec<UmuMatch> = Vec::new();
-        for entry in &self.umu_entries {
-            let mut best_score: Option<i32> = None;
-            if let Some(entry_title) = entry.title.as_deref() {
-                if let Some(score) = Self::score_match(title, entry_title) {
-                    best_score = Some(score);
-                }
-            }
-            if let Some(acronym) = entry.acronym.as_deref() {
-                if let Some(score) = Self::score_acronym(title, acronym) {
-                    best_score = Some(best_score.map_or(score, |current| current.min(score)));
-                }
-            }
-            if let Some(score) = best_score {
-                matches.push(UmuMatch {
-                    entry: entry.clone(),
-                    score,
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender_clone.input(MainWindowMsg::DeleteConfirmed {
+                    capsule_dirs: capsule_dirs.clone(),
+                    options: RemovalOptions {
+                        prefix_only: prefix_check.is_active(),
+                        keep_saves: saves_check.is_active(),
+                    },
+                    trash: trash_check.is_active(),
                 });
             }
-        }
-
-        if matches.is_empty() {
-            return matches;
-        }
-
-        let mut seen = HashSet::new();
-        matches.retain(|candidate| {
-            let key = (
-                candidate.entry.umu_id.clone().unwrap_or_default(),
-                candidate.entry.store.clone().unwrap_or_default(),
-                candidate.entry.codename.clone().unwrap_or_default(),
-            );
-            seen.insert(key)
-        });
-
-        matches.sort_by(|a, b| {
-            a.score
-                .cmp(&b.score)
-                .then_with(|| {
-                    let a_title = a.entry.title.as_deref().unwrap_or("");
-                    let b_title = b.entry.title.as_deref().unwrap_or("");
-                    a_title.len().cmp(&b_title.len())
-                })
-                .then_with(|| {
-                    let a_title = a.entry.title.as_deref().unwrap_or("");
-                    let b_title = b.entry.title.as_deref().unwrap_or("");
-                    a_title.cmp(b_title)
-                })
-                .then_with(|| {
-                    let a_store = a.entry.store.as_deref().unwrap_or("");
-                    let b_store = b.entry.store.as_deref().unwrap_or("");
-                    a_store.cmp(b_store)
-                })
+            sender_clone.input(MainWindowMsg::DeleteDialogClosed);
+            dialog.close();
         });
 
-        matches.truncate(20);
-        matches
+        dialog.show();
+        self.delete_dialog = Some(dialog);
     }
 
-    fn open_game_settings_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
-        if self.settings_dialog.is_some() {
+    fn open_uninstall_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.uninstall_dialog.is_some() {
             return;
         }
-
         let capsule = match Capsule::load_from_dir(&capsule_dir) {
             Ok(capsule) => capsule,
             Err(e) => {
@@ -1762,1288 +3240,10367 @@ impl MainWindow {
                 return;
             }
         };
+        let uninstallers = Uninstallers::find(&capsule);
 
         let dialog = Dialog::builder()
-            .title("Game Settings")
+            .title("Uninstall")
             .modal(true)
             .transient_for(&self.root_window)
             .build();
+        dialog.set_default_width(440);
         dialog.add_button("Cancel", ResponseType::Cancel);
-        dialog.add_button("Save", ResponseType::Accept);
+        if !uninstallers.is_empty() {
+            dialog.add_button("Run Uninstaller", ResponseType::Accept);
+            if let Some(button) = dialog.widget_for_response(ResponseType::Accept) {
+                button.add_css_class("destructive-action");
+            }
+        }
 
         let content = dialog.content_area();
         let layout = Box::new(Orientation::Vertical, 8);
         layout.set_margin_all(12);
 
-        let exe_label = Label::new(Some("Executable"));
-        exe_label.set_halign(gtk4::Align::Start);
-
-        let exe_row = Box::new(Orientation::Horizontal, 8);
-        exe_row.set_hexpand(true);
+        let title = Label::new(Some(&format!("Uninstall {}?", capsule.name)));
+        title.set_halign(gtk4::Align::Start);
+        title.set_wrap(true);
+        title.set_css_classes(&["section-title"]);
+        layout.append(&title);
 
-        let exe_entry = Entry::new();
-        exe_entry.set_hexpand(true);
-        exe_entry.set_placeholder_text(Some("Path to game executable (.exe)"));
-        if !capsule.metadata.executables.main.path.trim().is_empty() {
-            exe_entry.set_text(&capsule.metadata.executables.main.path);
+        let labels: Vec<String> = uninstallers
+            .iter()
+            .map(|uninstaller| uninstaller.label.clone())
+            .collect();
+        let label_refs: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let dropdown = DropDown::from_strings(&label_refs);
+        dropdown.set_hexpand(true);
+        let description = Label::new(Some(if uninstallers.is_empty() {
+            "No uninstaller was found for this game in its prefix. Delete removes the game \
+             instead."
+        } else {
+            "Runs the game's own uninstaller in its prefix. The capsule, its settings and \
+             saves stay; once the game is gone the capsule goes back to setup, so Resume \
+             setup reinstalls it."
+        }));
+        description.set_halign(gtk4::Align::Start);
+        description.set_wrap(true);
+        description.set_xalign(0.0);
+        description.set_css_classes(&["muted"]);
+        if uninstallers.len() > 1 {
+            layout.append(&dropdown);
         }
+        layout.append(&description);
+        content.append(&layout);
 
-        let exe_entry_clone = exe_entry.clone();
-        let root_window = self.root_window.clone();
-        let browse_button = Button::with_label("Browse");
-        browse_button.connect_clicked(move |_| {
-            let dialog = FileChooserNative::builder()
-                .title("Select Game Executable")
-                .action(FileChooserAction::Open)
-                .accept_label("Select")
-                .cancel_label("Cancel")
-                .transient_for(&root_window)
-                .build();
-
-            let filter = FileFilter::new();
-            filter.add_suffix("exe");
-            filter.set_name(Some("Windows executables (.exe)"));
-            dialog.add_filter(&filter);
-
-            let exe_entry_inner = exe_entry_clone.clone();
-            dialog.connect_response(move |dialog, response| {
-                if response == ResponseType::Accept {
-                    if let Some(file) = dialog.file() {
-                        if let Some(path) = file.path() {
-                            exe_entry_inner.set_text(&path.to_string_lossy());
-                        }
-                    }
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(uninstaller) = uninstallers.get(dropdown.selected() as usize) {
+                    sender_clone.input(MainWindowMsg::RunUninstaller {
+                        capsule_dir: capsule_dir.clone(),
+                        uninstaller: uninstaller.clone(),
+                    });
                 }
-                dialog.destroy();
-            });
-
-            dialog.show();
+            }
+            sender_clone.input(MainWindowMsg::UninstallDialogClosed);
+            dialog.close();
         });
 
-        exe_row.append(&exe_entry);
-        exe_row.append(&browse_button);
+        dialog.show();
+        self.uninstall_dialog = Some(dialog);
+    }
 
-        let game_id_label = Label::new(Some("UMU Game ID (optional)"));
-        game_id_label.set_halign(gtk4::Align::Start);
-        let game_id_entry = Entry::new();
-        game_id_entry.set_placeholder_text(Some("e.g., umu-starcitizen"));
-        if let Some(game_id) = &capsule.metadata.game_id {
-            game_id_entry.set_text(game_id);
+    /// Run an uninstaller through umu and report whether the game's
+    /// executable is gone afterwards.
+    fn start_uninstaller(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        uninstaller: Uninstaller,
+    ) {
+        if self.is_capsule_busy(&capsule_dir) {
+            return;
         }
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        let proton_path = match self.runner_runtime() {
+            Some(path) => path,
+            None => return,
+        };
 
-        let store_label = Label::new(Some("Store (optional)"));
-        store_label.set_halign(gtk4::Align::Start);
-        let store_entry = Entry::new();
-        store_entry.set_placeholder_text(Some("e.g., steam, gog, egs, none"));
-        if let Some(store) = &capsule.metadata.store {
-            store_entry.set_text(store);
-        }
-
-        let deps_title = Label::new(Some("Dependencies"));
-        deps_title.set_halign(gtk4::Align::Start);
-        deps_title.set_css_classes(&["section-title"]);
-        let deps_hint = Label::new(Some(
-            "Requires cached installers from linuxboy-setup.sh.",
-        ));
-        deps_hint.set_halign(gtk4::Align::Start);
-        deps_hint.set_wrap(true);
-        deps_hint.set_css_classes(&["muted"]);
-
-        let vcredist_check = CheckButton::with_label("Install VC++ Redistributables (AIO)");
-        vcredist_check.set_active(capsule.metadata.install_vcredist);
-        let dxweb_check = CheckButton::with_label("Install DirectX (June 2010) Redist");
-        dxweb_check.set_active(capsule.metadata.install_dxweb);
-
-        let install_deps_button = Button::with_label("Install dependencies now");
-        install_deps_button.add_css_class("suggested-action");
-
-        let input_title = Label::new(Some("Input & UI"));
-        input_title.set_halign(gtk4::Align::Start);
-        input_title.set_css_classes(&["section-title"]);
-
-        let xalia_check = CheckButton::with_label("Enable Xalia controller UI layer (may disable mouse)");
-        xalia_check.set_active(capsule.metadata.xalia_enabled);
-
-        let pf_title = Label::new(Some("Protonfixes Overrides"));
-        pf_title.set_halign(gtk4::Align::Start);
-        pf_title.set_css_classes(&["section-title"]);
-
-        let pf_disable = CheckButton::with_label("Disable Protonfixes for this game");
-        pf_disable.set_active(capsule.metadata.protonfixes_disable);
+        let prefix_path = capsule.home_path.join("prefix");
+        let mut cmd = Self::umu_base_command(&prefix_path, &proton_path, &capsule.metadata);
+        cmd.arg(&uninstaller.program).args(&uninstaller.args);
+        let main_exe = PathBuf::from(capsule.metadata.executables.main.path.trim());
+        let install_root = capsule.install_root();
 
-        let pf_tricks_label = Label::new(Some("Winetricks / Protontricks verbs"));
-        pf_tricks_label.set_halign(gtk4::Align::Start);
-        let pf_tricks_entry = Entry::new();
-        pf_tricks_entry.set_placeholder_text(Some("xliveless d3dcompiler_47"));
-        if !capsule.metadata.protonfixes_tricks.is_empty() {
-            pf_tricks_entry.set_text(&capsule.metadata.protonfixes_tricks.join(" "));
-        }
+        self.uninstall_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+        println!("Running {} for {}", uninstaller.label, capsule.name);
 
-        let pf_replace_label = Label::new(Some("Command replacements"));
-        pf_replace_label.set_halign(gtk4::Align::Start);
-        let pf_replace_entry = Entry::new();
-        pf_replace_entry.set_placeholder_text(Some("/launcher.exe=/game.exe"));
-        if !capsule.metadata.protonfixes_replace_cmds.is_empty() {
-            pf_replace_entry.set_text(&capsule.metadata.protonfixes_replace_cmds.join(" "));
-        }
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            // umu waits for every process in the prefix, so uninstallers that
+            // relaunch themselves from a temp copy are waited for too.
+            let result = match host::command(cmd).status() {
+                Ok(status) => {
+                    if !status.success() {
+                        eprintln!("{} exited with {}", uninstaller.label, status);
+                    }
+                    let removed = if main_exe.as_os_str().is_empty() {
+                        install_root.is_some_and(|root| !file_ops::has_entries(&root))
+                    } else {
+                        !main_exe.exists()
+                    };
+                    Ok(removed)
+                }
+                Err(e) => Err(format!("Failed to run {}: {}", uninstaller.program, e)),
+            };
+            let _ = sender_clone.input(MainWindowMsg::UninstallerFinished { capsule_dir, result });
+        });
+    }
 
-        let pf_dxvk_label = Label::new(Some("DXVK options"));
-        pf_dxvk_label.set_halign(gtk4::Align::Start);
-        let pf_dxvk_entry = Entry::new();
-        pf_dxvk_entry.set_placeholder_text(Some("dxgi.maxFrameRate=60"));
-        if !capsule.metadata.protonfixes_dxvk_sets.is_empty() {
-            pf_dxvk_entry.set_text(&capsule.metadata.protonfixes_dxvk_sets.join(" "));
+    fn open_patch_chooser(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.capsule_folder_dialog.is_some() {
+            return;
         }
 
-        layout.append(&exe_label);
-        layout.append(&exe_row);
-        layout.append(&game_id_label);
-        layout.append(&game_id_entry);
-        layout.append(&store_label);
-        layout.append(&store_entry);
-        layout.append(&deps_title);
-        layout.append(&deps_hint);
-        layout.append(&vcredist_check);
-        layout.append(&dxweb_check);
-        layout.append(&install_deps_button);
-        layout.append(&input_title);
-        layout.append(&xalia_check);
-        layout.append(&pf_title);
-        layout.append(&pf_disable);
-        layout.append(&pf_tricks_label);
-        layout.append(&pf_tricks_entry);
-        layout.append(&pf_replace_label);
-        layout.append(&pf_replace_entry);
-        layout.append(&pf_dxvk_label);
-        layout.append(&pf_dxvk_entry);
-        content.append(&layout);
+        let dialog = FileChooserNative::builder()
+            .title("Select Patch or DLC Installer")
+            .action(FileChooserAction::Open)
+            .accept_label("Install")
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
+        let filter = FileFilter::new();
+        filter.add_suffix("exe");
+        filter.add_suffix("msi");
+        filter.set_name(Some("Windows installers (.exe, .msi)"));
+        dialog.add_filter(&filter);
 
         let sender_clone = sender.clone();
-        let capsule_dir_clone = capsule_dir.clone();
-        let exe_entry_clone = exe_entry.clone();
-        let game_id_entry_clone = game_id_entry.clone();
-        let store_entry_clone = store_entry.clone();
-        let vcredist_check_clone = vcredist_check.clone();
-        let dxweb_check_clone = dxweb_check.clone();
-        let xalia_check_clone = xalia_check.clone();
-        let pf_disable_clone = pf_disable.clone();
-        let pf_tricks_entry_clone = pf_tricks_entry.clone();
-        let pf_replace_entry_clone = pf_replace_entry.clone();
-        let pf_dxvk_entry_clone = pf_dxvk_entry.clone();
         dialog.connect_response(move |dialog, response| {
             if response == ResponseType::Accept {
-                let exe_path = exe_entry_clone.text().to_string();
-                let game_id_text = game_id_entry_clone.text().trim().to_string();
-                let store_text = store_entry_clone.text().trim().to_string();
-                let install_vcredist = vcredist_check_clone.is_active();
-                let install_dxweb = dxweb_check_clone.is_active();
-                let protonfixes_disable = pf_disable_clone.is_active();
-                let xalia_enabled = xalia_check_clone.is_active();
-                let protonfixes_tricks = MainWindow::parse_list_input(&pf_tricks_entry_clone.text());
-                let protonfixes_replace_cmds =
-                    MainWindow::parse_list_input(&pf_replace_entry_clone.text());
-                let protonfixes_dxvk_sets = MainWindow::parse_list_input(&pf_dxvk_entry_clone.text());
-                let game_id = if game_id_text.is_empty() {
-                    None
-                } else {
-                    Some(game_id_text)
-                };
-                let store = if store_text.is_empty() {
-                    None
-                } else {
-                    Some(store_text)
-                };
-                sender_clone.input(MainWindowMsg::SaveGameSettings {
-                    capsule_dir: capsule_dir_clone.clone(),
-                    exe_path,
-                    game_id,
-                    store,
-                    install_vcredist,
-                    install_dxweb,
-                    protonfixes_disable,
-                    xalia_enabled,
-                    protonfixes_tricks,
-                    protonfixes_replace_cmds,
-                    protonfixes_dxvk_sets,
-                });
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    sender_clone.input(MainWindowMsg::RunPatchInstaller {
+                        capsule_dir: capsule_dir.clone(),
+                        installer_path: path,
+                    });
+                }
             }
-
-            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
-            dialog.close();
-        });
-
-        let sender_clone = sender.clone();
-        let capsule_dir_clone = capsule_dir.clone();
-        let exe_entry_clone = exe_entry.clone();
-        let game_id_entry_clone = game_id_entry.clone();
-        let store_entry_clone = store_entry.clone();
-        let vcredist_check_clone = vcredist_check.clone();
-        let dxweb_check_clone = dxweb_check.clone();
-        let xalia_check_clone = xalia_check.clone();
-        let pf_disable_clone = pf_disable.clone();
-        let pf_tricks_entry_clone = pf_tricks_entry.clone();
-        let pf_replace_entry_clone = pf_replace_entry.clone();
-        let pf_dxvk_entry_clone = pf_dxvk_entry.clone();
-        let dialog_clone = dialog.clone();
-        install_deps_button.connect_clicked(move |_| {
-            let exe_path = exe_entry_clone.text().to_string();
-            let game_id_text = game_id_entry_clone.text().trim().to_string();
-            let store_text = store_entry_clone.text().trim().to_string();
-            let install_vcredist = vcredist_check_clone.is_active();
-            let install_dxweb = dxweb_check_clone.is_active();
-            let protonfixes_disable = pf_disable_clone.is_active();
-            let xalia_enabled = xalia_check_clone.is_active();
-            let protonfixes_tricks = MainWindow::parse_list_input(&pf_tricks_entry_clone.text());
-            let protonfixes_replace_cmds =
-                MainWindow::parse_list_input(&pf_replace_entry_clone.text());
-            let protonfixes_dxvk_sets = MainWindow::parse_list_input(&pf_dxvk_entry_clone.text());
-            let game_id = if game_id_text.is_empty() {
-                None
-            } else {
-                Some(game_id_text)
-            };
-            let store = if store_text.is_empty() {
-                None
-            } else {
-                Some(store_text)
-            };
-            sender_clone.input(MainWindowMsg::SaveGameSettings {
-                capsule_dir: capsule_dir_clone.clone(),
-                exe_path,
-                game_id,
-                store,
-                install_vcredist,
-                install_dxweb,
-                protonfixes_disable,
-                xalia_enabled,
-                protonfixes_tricks,
-                protonfixes_replace_cmds,
-                protonfixes_dxvk_sets,
-            });
-            sender_clone.input(MainWindowMsg::DependenciesSelected {
-                capsule_dir: capsule_dir_clone.clone(),
-                install_vcredist,
-                install_dxweb,
-                force: true,
-            });
-            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
-            dialog_clone.close();
+            sender_clone.input(MainWindowMsg::CapsuleFolderDialogClosed);
+            dialog.destroy();
         });
 
         dialog.show();
-        self.settings_dialog = Some(dialog);
+        self.capsule_folder_dialog = Some(dialog);
     }
 
-    fn start_installer(
+    /// Run a patch or DLC installer in the capsule's existing prefix, like
+    /// `start_installer` but leaving the capsule's own installer and setup
+    /// state alone.
+    fn start_patch_installer(
         &mut self,
-        sender: &ComponentSender<Self>,
+        sender: ComponentSender<Self>,
         capsule_dir: PathBuf,
-        mut metadata: CapsuleMetadata,
         installer_path: PathBuf,
     ) {
-        if !Self::has_command("umu-run") {
-            eprintln!("umu-run not found in PATH");
+        if self.is_capsule_busy(&capsule_dir) {
+            eprintln!("Cannot install a patch while the capsule is busy");
             return;
         }
-
-        let proton_path = match self.runtime_mgr.latest_installed() {
-            Ok(Some(path)) => path,
-            Ok(None) => {
-                eprintln!("No Proton-GE runtime installed");
-                return;
-            }
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
             Err(e) => {
-                eprintln!("Failed to resolve Proton-GE runtime: {}", e);
+                eprintln!("Failed to load capsule: {}", e);
                 return;
             }
         };
+        let proton_path = match self.runner_runtime() {
+            Some(path) => path,
+            None => return,
+        };
 
-        let home_path = capsule_dir.join(format!("{}.AppImage.home", metadata.name));
-        let prefix_path = home_path.join("prefix");
-        if let Err(e) = fs::create_dir_all(prefix_path.join("drive_c")) {
-            eprintln!("Failed to create prefix: {}", e);
+        let prefix_path = capsule.home_path.join("prefix");
+        let installer_size = fs::metadata(&installer_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        if let Err(e) =
+            file_ops::ensure_free_space(&prefix_path, installer_size * INSTALLER_SPACE_FACTOR)
+        {
+            eprintln!("Not starting the patch installer: {}", e);
             return;
         }
-        if let Err(e) = fs::create_dir_all(prefix_path.join("games")) {
-            eprintln!("Failed to create games folder: {}", e);
+        // The game's MSI options, like its INSTALLDIR, are for its own
+        // installer; a patch picks its own.
+        let mut patch_metadata = capsule.metadata.clone();
+        patch_metadata.msi_silent = false;
+        patch_metadata.msi_properties.clear();
+        let mut cmd =
+            Self::installer_command(&prefix_path, &proton_path, &patch_metadata, &installer_path);
+
+        self.patch_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+        println!("Installing {:?} into {}", installer_path, capsule.name);
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let sha256 = match InstallerSource::sha256(&installer_path) {
+                Ok(sha256) => Some(sha256),
+                Err(e) => {
+                    eprintln!("Failed to hash the patch installer: {}", e);
+                    None
+                }
+            };
+            let success = match cmd.status() {
+                Ok(status) => {
+                    if !status.success() {
+                        eprintln!("Patch installer exited with {}", status);
+                    }
+                    status.success()
+                }
+                Err(e) => {
+                    eprintln!("Failed to run the patch installer: {}", e);
+                    false
+                }
+            };
+            let patch = AppliedPatch {
+                path: installer_path.to_string_lossy().to_string(),
+                sha256,
+                applied_at: chrono::Local::now().to_rfc3339(),
+                success,
+            };
+            let _ =
+                sender_clone.input(MainWindowMsg::PatchInstallerFinished { capsule_dir, patch });
+        });
+    }
+
+    fn open_mods_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.mods_dialog.is_some() {
             return;
         }
-        if let Some(game_dir) = metadata.game_dir.as_deref() {
-            let path = PathBuf::from(game_dir);
-            if let Err(e) = fs::create_dir_all(&path) {
-                eprintln!("Failed to create default game folder: {}", e);
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
                 return;
             }
-        }
+        };
 
-        metadata.installer_path = Some(installer_path.to_string_lossy().to_string());
-        metadata.install_state = InstallState::Installing;
+        let dialog = Dialog::builder()
+            .title(format!("Mods - {}", capsule.metadata.title()))
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(520);
+        dialog.set_default_height(420);
+        dialog.add_button("Close", ResponseType::Close);
 
-        let capsule = Capsule {
-            name: metadata.name.clone(),
-            capsule_dir: capsule_dir.clone(),
-            home_path,
-            metadata: metadata.clone(),
-        };
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
 
-        if let Err(e) = capsule.save_metadata() {
-            eprintln!("Failed to save metadata: {}", e);
-            return;
-        }
+        let hint = Label::new(Some(&format!(
+            "Drop mod archives ({}) or folders into the mods folder and press Refresh. \
+             Enabling a mod puts its files into the game folder; disabling it restores the \
+             files it replaced. Mods enabled later win where two change the same file.",
+            ARCHIVE_SUFFIXES.join(", ")
+        )));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_xalign(0.0);
+        hint.set_css_classes(&["muted"]);
+        layout.append(&hint);
 
-        self.preparing_installs.insert(capsule_dir.clone());
-        self.rebuild_games_list(sender.clone());
+        let list = ListBox::new();
+        list.set_selection_mode(SelectionMode::None);
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_child(Some(&list));
+        layout.append(&scroller);
+
+        let link_check = CheckButton::with_label("Link files instead of copying them");
+        link_check.set_tooltip_text(Some(
+            "Saves space; applies to mods enabled from now on",
+        ));
+        layout.append(&link_check);
+
+        let status = Label::new(Some("Reading mods..."));
+        status.set_halign(gtk4::Align::Start);
+        status.set_wrap(true);
+        status.set_xalign(0.0);
+        layout.append(&status);
+
+        let buttons = Box::new(Orientation::Horizontal, 8);
+        let folder_button = Button::with_label("Open mods folder");
+        let refresh_button = Button::with_label("Refresh");
+        buttons.append(&folder_button);
+        buttons.append(&refresh_button);
+        layout.append(&buttons);
+        content.append(&layout);
 
-        let env_metadata = metadata.clone();
         let sender_clone = sender.clone();
-        thread::spawn(move || {
-            println!("Preloading UMU runtime...");
-            if !Self::run_umu_preflight(&prefix_path, &proton_path, &env_metadata) {
-                eprintln!("UMU runtime preload failed.");
-                let _ = sender_clone.input(MainWindowMsg::InstallerFinished {
-                    capsule_dir,
-                    success: false,
-                });
+        let mods_dir = ModManager::mods_dir(&capsule);
+        folder_button.connect_clicked(move |_| {
+            if let Err(e) = fs::create_dir_all(&mods_dir) {
+                eprintln!("Failed to create {:?}: {}", mods_dir, e);
                 return;
             }
+            sender_clone.input(MainWindowMsg::OpenFolder(mods_dir.clone()));
+        });
 
-            let mut cmd = Self::umu_base_command(&prefix_path, &proton_path, &env_metadata);
-            // Avoid Xalia UI automation errors during installers.
-            cmd.env("PROTON_USE_XALIA", "0");
-            cmd.arg(&installer_path);
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        refresh_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::OpenMods(capsule_dir_clone.clone()));
+        });
 
-            unsafe {
-                cmd.pre_exec(|| {
-                    libc::setpgid(0, 0);
-                    Ok(())
-                });
-            }
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, _| {
+            sender_clone.input(MainWindowMsg::ModsDialogClosed);
+            dialog.close();
+        });
 
-            let mut child = match cmd.spawn() {
-                Ok(child) => child,
-                Err(e) => {
-                    eprintln!("Failed to launch installer: {}", e);
-                    let _ = sender_clone.input(MainWindowMsg::InstallerFinished {
-                        capsule_dir,
-                        success: false,
-                    });
-                    return;
-                }
-            };
+        dialog.show();
+        self.mods_dialog = Some(dialog);
+        self.mods_capsule = Some(capsule_dir);
+        self.mods_list = Some(list);
+        self.mods_status = Some(status);
+        self.mods_link_check = Some(link_check);
+    }
 
-            let pid = child.id() as i32;
-            let pgid = unsafe { libc::getpgid(pid) };
-            if pgid > 0 {
-                let _ = sender_clone.input(MainWindowMsg::InstallerStarted {
+    fn fill_mods_list(&self, sender: &ComponentSender<Self>, entries: &[ModEntry]) {
+        let (list, capsule_dir) = match (&self.mods_list, &self.mods_capsule) {
+            (Some(list), Some(capsule_dir)) => (list, capsule_dir),
+            _ => return,
+        };
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        if entries.is_empty() {
+            let empty = Label::new(Some("No mods in the mods folder yet."));
+            empty.set_css_classes(&["muted"]);
+            empty.set_margin_all(12);
+            list.append(&empty);
+            return;
+        }
+
+        for entry in entries {
+            let row = Box::new(Orientation::Vertical, 2);
+            row.set_margin_all(6);
+            let check = CheckButton::with_label(&entry.id);
+            check.set_active(entry.enabled);
+            check.set_sensitive(entry.error.is_none() || entry.enabled);
+            let detail = match (&entry.error, entry.enabled) {
+                (_, true) => format!(
+                    "Enabled, {} {}{}",
+                    entry.files.len(),
+                    if entry.files.len() == 1 { "file" } else { "files" },
+                    if entry.mode == DeployMode::Symlink { " linked" } else { "" }
+                ),
+                (Some(error), false) => format!("Couldn't extract: {}", error),
+                (None, false) => "Disabled".to_string(),
+            };
+            let detail_label = Label::new(Some(&detail));
+            detail_label.set_halign(gtk4::Align::Start);
+            detail_label.set_wrap(true);
+            detail_label.set_xalign(0.0);
+            detail_label.set_css_classes(&["muted"]);
+            row.append(&check);
+            row.append(&detail_label);
+            list.append(&row);
+
+            let sender_clone = sender.clone();
+            let capsule_dir = capsule_dir.clone();
+            let id = entry.id.clone();
+            check.connect_toggled(move |check| {
+                sender_clone.input(MainWindowMsg::ToggleMod {
                     capsule_dir: capsule_dir.clone(),
-                    pgid,
+                    id: id.clone(),
+                    enable: check.is_active(),
                 });
-            }
+            });
+        }
+    }
 
-            let success = child.wait().map(|status| status.success()).unwrap_or(false);
-            let _ = sender_clone.input(MainWindowMsg::InstallerFinished {
+    /// Sync the capsule's mods folder in the background and show the result
+    /// in the mods dialog.
+    fn load_mods(&self, sender: &ComponentSender<Self>, capsule_dir: PathBuf) {
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let result = Capsule::load_from_dir(&capsule_dir)
+                .and_then(|capsule| ModManager::new(&capsule))
+                .and_then(|manager| manager.sync())
+                .map_err(|e| format!("{:#}", e));
+            sender_clone.input(MainWindowMsg::ModsLoaded {
                 capsule_dir,
-                success,
+                result,
+                status: None,
             });
         });
     }
 
-    fn umu_base_command(
-        prefix_path: &PathBuf,
-        proton_path: &PathBuf,
-        metadata: &CapsuleMetadata,
-    ) -> Command {
-        let mut cmd = Command::new("umu-run");
-        cmd.env("WINEPREFIX", prefix_path);
-        cmd.env("PROTONPATH", proton_path);
-        let game_id = metadata
-            .game_id
-            .as_deref()
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .unwrap_or("umu-default");
-        let store = metadata
-            .store
-            .as_deref()
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .unwrap_or("none");
-        cmd.env("GAMEID", game_id);
-        cmd.env("STORE", store);
-        cmd.env("PROTON_USE_XALIA", if metadata.xalia_enabled { "1" } else { "0" });
-        if metadata.protonfixes_disable {
-            cmd.env("PROTONFIXES_DISABLE", "1");
-        }
-        for (key, value) in &metadata.env_vars {
-            let trimmed = key.trim();
-            if !trimmed.is_empty() {
-                cmd.env(trimmed, value);
-            }
-        }
-        cmd
-    }
-
-    fn install_directx_redist(
-        prefix_path: &PathBuf,
-        proton_path: &PathBuf,
-        metadata: &CapsuleMetadata,
-        redist_path: &Path,
-    ) -> bool {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos();
-        let temp_dir_name = format!("linuxboy-dxredist-{}", nanos);
-        let host_temp_dir = prefix_path
-            .join("drive_c")
-            .join("linuxboy-temp")
-            .join(&temp_dir_name);
-        let windows_temp_dir = format!("C:\\\\linuxboy-temp\\\\{}", temp_dir_name);
-
-        if let Err(e) = fs::create_dir_all(&host_temp_dir) {
-            eprintln!("Failed to create DirectX temp dir: {}", e);
-            return false;
-        }
-
-        let extract_arg = format!("/T:{}", windows_temp_dir);
-        let mut extract_cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
-        extract_cmd.env("PROTON_USE_XALIA", "0");
-        extract_cmd.arg(redist_path);
-        extract_cmd.arg("/Q");
-        extract_cmd.arg(extract_arg);
-        extract_cmd.arg("/C");
-        let extracted = match extract_cmd.status() {
-            Ok(status) => status.success(),
+    fn start_game_archive(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        let mut capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
             Err(e) => {
-                eprintln!("Failed to extract DirectX redist: {}", e);
-                false
+                eprintln!("Failed to load capsule: {}", e);
+                return;
             }
         };
-        if !extracted {
-            let _ = fs::remove_dir_all(&host_temp_dir);
-            return false;
-        }
-
-        let dxsetup_path = host_temp_dir.join("DXSETUP.exe");
-        if !dxsetup_path.is_file() {
-            eprintln!("DirectX redist extraction missing DXSETUP.exe");
-            let _ = fs::remove_dir_all(&host_temp_dir);
-            return false;
-        }
-
-        let mut install_cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
-        install_cmd.env("PROTON_USE_XALIA", "0");
-        install_cmd.arg(&dxsetup_path);
-        install_cmd.arg("/silent");
-        let success = match install_cmd.status() {
-            Ok(status) => status.success(),
+        let game_root = match GameArchive::game_root(&capsule) {
+            Ok(root) => root,
             Err(e) => {
-                eprintln!("Failed to run DXSETUP.exe: {}", e);
-                false
+                eprintln!("{}", e);
+                return;
             }
         };
-        let _ = fs::remove_dir_all(&host_temp_dir);
-        success
-    }
-
-    fn run_umu_preflight(
-        prefix_path: &PathBuf,
-        proton_path: &PathBuf,
-        metadata: &CapsuleMetadata,
-    ) -> bool {
-        let mut cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
-        // Avoid Xalia UI automation errors during preflight.
-        cmd.env("PROTON_USE_XALIA", "0");
-        // Run a harmless command to force prefix/runtime initialization.
-        cmd.arg("cmd");
-        cmd.arg("/c");
-        cmd.arg("exit");
-        match cmd.status() {
-            Ok(status) => status.success(),
-            Err(e) => {
-                eprintln!("Failed to preload UMU runtime: {}", e);
-                false
-            }
-        }
-    }
-
-    fn rebuild_games_list(&mut self, sender: ComponentSender<Self>) {
-        let list = &self.games_list;
-        while let Some(child) = list.first_child() {
-            list.remove(&child);
-        }
-
-        if self.capsules.is_empty() {
-            let empty = Box::new(Orientation::Horizontal, 12);
-            empty.set_margin_all(8);
-            empty.set_css_classes(&["card"]);
-
-            let icon = Image::from_icon_name("applications-games-symbolic");
-            icon.set_pixel_size(28);
-            icon.set_halign(gtk4::Align::Start);
-            icon.set_valign(gtk4::Align::Start);
-
-            let text = Box::new(Orientation::Vertical, 6);
-            text.set_hexpand(true);
 
-            let title = Label::new(Some("No games yet"));
-            title.set_css_classes(&["card-title"]);
-            title.set_halign(gtk4::Align::Start);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&format!("Archiving {}", capsule.name), cancel.clone());
+        self.archive_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
 
-            let subtitle = Label::new(Some(
-                "Add an installer to create your first portable capsule.",
-            ));
-            subtitle.set_css_classes(&["muted"]);
-            subtitle.set_halign(gtk4::Align::Start);
-            subtitle.set_wrap(true);
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let total = file_ops::dir_size(&game_root);
+            let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: 0, total });
+
+            let mut copied: u64 = 0;
+            let mut last_report = Instant::now();
+            let result = GameArchive::archive(&mut capsule, &mut |bytes| {
+                copied += bytes;
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map_err(|e| e.to_string());
 
-            text.append(&title);
-            text.append(&subtitle);
+            let _ = sender_clone.input(MainWindowMsg::ArchiveFinished { capsule_dir, result });
+        });
+    }
 
-            empty.append(&icon);
-            empty.append(&text);
-            list.append(&empty);
+    /// Unpack an archived game, then start it when `play` is set.
+    fn start_game_unpack(
+        &mut self,
+        sender: ComponentSender<Self>,
+        mut capsule: Capsule,
+        play: bool,
+    ) {
+        let capsule_dir = capsule.capsule_dir.clone();
+        if self.archive_jobs.contains(&capsule_dir) {
             return;
         }
+        let total = GameArchive::info(&capsule)
+            .map(|info| info.unpacked_size)
+            .unwrap_or(0);
 
-        for capsule in &self.capsules {
-            let card = Box::new(Orientation::Vertical, 8);
-            card.set_margin_bottom(12);
-            card.set_hexpand(true);
-            card.set_css_classes(&["card"]);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&format!("Unpacking {}", capsule.name), cancel.clone());
+        self.archive_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
 
-            let header = Box::new(Orientation::Horizontal, 10);
-            header.set_hexpand(true);
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: 0, total });
+
+            let mut copied: u64 = 0;
+            let mut last_report = Instant::now();
+            let result = GameArchive::unpack(&mut capsule, &mut |bytes| {
+                copied += bytes;
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map_err(|e| e.to_string());
 
-            let icon = Image::from_icon_name("applications-games-symbolic");
-            icon.set_pixel_size(24);
-            icon.set_halign(gtk4::Align::Start);
+            let _ = sender_clone.input(MainWindowMsg::UnpackFinished {
+                capsule_dir,
+                result,
+                play,
+            });
+        });
+    }
 
-            let name = Label::new(Some(&capsule.name));
-            name.set_halign(gtk4::Align::Start);
-            name.set_hexpand(true);
-            name.set_css_classes(&["card-title"]);
+    fn start_capsule_removal(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dirs: Vec<PathBuf>,
+        options: RemovalOptions,
+        trash: bool,
+    ) {
+        let capsule_dirs: Vec<PathBuf> = capsule_dirs
+            .into_iter()
+            .filter(|capsule_dir| {
+                let busy = self.is_capsule_busy(capsule_dir);
+                if busy {
+                    eprintln!("Skipping {:?}: it is busy", capsule_dir);
+                }
+                !busy
+            })
+            .collect();
+        if capsule_dirs.is_empty() {
+            return;
+        }
 
-            let status_text = match capsule.metadata.install_state {
-                InstallState::Installing => "Installing",
-                InstallState::Installed => "Installed",
-            };
-            let status_class = match capsule.metadata.install_state {
-                InstallState::Installing => "pill-warning",
-                InstallState::Installed => "pill-installed",
-            };
-            let status = Label::new(Some(status_text));
-            status.set_css_classes(&["pill", status_class]);
-
-            let spacer = Box::new(Orientation::Horizontal, 0);
-            spacer.set_hexpand(true);
-
-            header.append(&icon);
-            header.append(&name);
-            header.append(&spacer);
-            header.append(&status);
-
-            let installing = capsule.metadata.install_state == InstallState::Installing;
-            let is_running = self.active_installs.contains_key(&capsule.capsule_dir);
-            let is_preparing = self.preparing_installs.contains(&capsule.capsule_dir);
-            let deps_running = self.dependency_installs.contains(&capsule.capsule_dir);
-            let game_running = self.active_games.contains_key(&capsule.capsule_dir);
-            let exe_missing = capsule.metadata.executables.main.path.trim().is_empty();
-            let detail_text = if deps_running {
-                "Installing dependencies"
-            } else if game_running {
-                "Game running"
-            } else if installing {
-                if is_preparing {
-                    "Preparing runtime"
-                } else if is_running {
-                    "Installer running"
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let remove_dir = |path: &Path| -> anyhow::Result<()> {
+                if trash {
+                    gio::File::for_path(path)
+                        .trash(None::<&gio::Cancellable>)
+                        .map_err(|e| {
+                            anyhow::anyhow!("Failed to move {:?} to the trash: {}", path, e)
+                        })
                 } else {
-                    "Installer paused"
+                    fs::remove_dir_all(path)
+                        .map_err(|e| anyhow::anyhow!("Failed to delete {:?}: {}", path, e))
                 }
-            } else if exe_missing {
-                "Select executable to finish setup"
-            } else {
-                "Ready to play"
             };
+            for capsule_dir in capsule_dirs {
+                let result = CapsuleRemoval::run(&capsule_dir, options, &remove_dir)
+                    .map_err(|e| e.to_string());
+                let _ = sender_clone.input(MainWindowMsg::CapsuleRemovalFinished(result));
+            }
+        });
+    }
 
-            let detail = Label::new(Some(detail_text));
-            detail.set_css_classes(&["muted"]);
-            detail.set_halign(gtk4::Align::Start);
-            detail.set_margin_top(2);
+    fn open_appimage_destination_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.backup_file_dialog.is_some() {
+            return;
+        }
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
 
-            let actions = Box::new(Orientation::Horizontal, 8);
-            actions.set_halign(gtk4::Align::Start);
+        let dialog = FileChooserNative::builder()
+            .title("Export as AppImage")
+            .action(FileChooserAction::Save)
+            .accept_label("Export")
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_current_name(&format!("{}-x86_64.AppImage", capsule.name.replace(' ', "_")));
 
-            let edit_dir = capsule.capsule_dir.clone();
-            let edit_sender = sender.clone();
-            let edit_button = Button::with_label("Edit");
-            edit_button.add_css_class("flat");
-            edit_button.connect_clicked(move |_| {
-                edit_sender.input(MainWindowMsg::EditGame(edit_dir.clone()));
-            });
-            actions.append(&edit_button);
-
-            let delete_dir = capsule.capsule_dir.clone();
-            let delete_sender = sender.clone();
-            let delete_button = Button::with_label("Delete");
-            delete_button.add_css_class("destructive-action");
-            delete_button.connect_clicked(move |_| {
-                delete_sender.input(MainWindowMsg::DeleteGame(delete_dir.clone()));
-            });
-            actions.append(&delete_button);
-
-            if installing && is_running {
-                let kill_dir = capsule.capsule_dir.clone();
-                let kill_sender = sender.clone();
-                let kill_button = Button::with_label("Kill installer");
-                kill_button.add_css_class("destructive-action");
-                kill_button.connect_clicked(move |_| {
-                    kill_sender.input(MainWindowMsg::KillInstall(kill_dir.clone()));
-                });
-                actions.append(&kill_button);
-            } else if installing && !is_preparing {
-                let resume_dir = capsule.capsule_dir.clone();
-                let resume_sender = sender.clone();
-                let resume_button = Button::with_label("Resume setup");
-                resume_button.add_css_class("suggested-action");
-                resume_button.connect_clicked(move |_| {
-                    resume_sender.input(MainWindowMsg::ResumeInstall(resume_dir.clone()));
-                });
-                actions.append(&resume_button);
-
-                let finish_dir = capsule.capsule_dir.clone();
-                let finish_sender = sender.clone();
-                let finish_button = Button::with_label("Finish setup");
-                finish_button.add_css_class("flat");
-                finish_button.connect_clicked(move |_| {
-                    finish_sender.input(MainWindowMsg::MarkInstallComplete(finish_dir.clone()));
-                });
-                actions.append(&finish_button);
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    sender_clone.input(MainWindowMsg::AppImageDestinationSelected {
+                        capsule_dir: capsule_dir.clone(),
+                        dest: path,
+                    });
+                }
             }
+            sender_clone.input(MainWindowMsg::BackupFileDialogClosed);
+            dialog.destroy();
+        });
 
-            if !installing && !exe_missing {
-                let play_dir = capsule.capsule_dir.clone();
-                let play_sender = sender.clone();
-                let play_button = Button::with_label(if game_running { "Running" } else { "Play" });
-                play_button.add_css_class("suggested-action");
-                play_button.set_sensitive(!game_running);
-                play_button.connect_clicked(move |_| {
-                    play_sender.input(MainWindowMsg::LaunchGame(play_dir.clone()));
-                });
-                actions.append(&play_button);
+        dialog.show();
+        self.backup_file_dialog = Some(dialog);
+    }
+
+    fn start_appimage_export(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf, dest: PathBuf) {
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
             }
+        };
 
-            card.append(&header);
-            card.append(&detail);
-            if let Some(store) = capsule
-                .metadata
-                .store
-                .as_deref()
-                .map(str::trim)
-                .filter(|value| !value.is_empty())
-            {
-                let store_label = Label::new(Some(&format!("Store: {}", store)));
-                store_label.set_css_classes(&["muted"]);
-                store_label.set_halign(gtk4::Align::Start);
-                card.append(&store_label);
-            }
-            card.append(&actions);
-            list.append(&card);
-        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&format!("Packaging {}", capsule.name), cancel.clone());
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let mut last_report = Instant::now();
+            let result = AppImageBuilder::build(&capsule, &dest, &mut |copied, total| {
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map(|_| format!("Exported {} as {:?}", capsule.name, dest))
+            .map_err(|e| format!("{:#}", e));
+
+            let _ = sender_clone.input(MainWindowMsg::AppImageExportFinished(result));
+        });
     }
-}
 
-#[allow(unused_assignments)]
-#[relm4::component(pub)]
-impl SimpleComponent for MainWindow {
-    type Init = ();
-    type Input = MainWindowMsg;
-    type Output = ();
+    /// Folder picker for both bundle export (the folder to write into) and
+    /// import (the bundle itself).
+    fn open_portable_bundle_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: Option<PathBuf>) {
+        if self.capsule_folder_dialog.is_some() {
+            return;
+        }
 
-    view! {
-        #[root]
-        ApplicationWindow {
-            set_title: Some("LinuxBoy"),
-            set_default_width: 840,
-            set_default_height: 720,
+        let (title, accept_label) = if capsule_dir.is_some() {
+            ("Export Portable Bundle To", "Export Here")
+        } else {
+            ("Import Portable Bundle", "Import")
+        };
+        let dialog = FileChooserNative::builder()
+            .title(title)
+            .action(FileChooserAction::SelectFolder)
+            .accept_label(accept_label)
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
 
-            #[wrap(Some)]
-            set_child = &Box {
-                set_orientation: Orientation::Vertical,
-                set_spacing: 0,
-                set_hexpand: true,
-                set_vexpand: true,
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    match &capsule_dir {
+                        Some(capsule_dir) => {
+                            sender_clone.input(MainWindowMsg::PortableBundleTargetSelected {
+                                capsule_dir: capsule_dir.clone(),
+                                parent: path,
+                            });
+                        }
+                        None => sender_clone.input(MainWindowMsg::PortableBundleSelected(path)),
+                    }
+                }
+            }
+            sender_clone.input(MainWindowMsg::CapsuleFolderDialogClosed);
+            dialog.destroy();
+        });
 
-                // Header bar
-                append = &Box {
-                    set_orientation: Orientation::Horizontal,
-                    set_spacing: 12,
-                    set_margin_start: 20,
-                    set_margin_end: 20,
-                    set_margin_top: 24,
-                    set_margin_bottom: 14,
-                    set_css_classes: &["topbar"],
+        dialog.show();
+        self.capsule_folder_dialog = Some(dialog);
+    }
 
-                    append = &Image {
-                        set_icon_name: Some("applications-games-symbolic"),
-                        set_pixel_size: 28,
-                    },
+    fn start_portable_bundle_export(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        parent: PathBuf,
+    ) {
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
 
-                    append = &Box {
-                        set_orientation: Orientation::Vertical,
-                        set_spacing: 0,
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&format!("Bundling {}", capsule.name), cancel.clone());
 
-                        append = &Label {
-                            set_label: "LinuxBoy",
-                            set_css_classes: &["app-title"],
-                            set_halign: gtk4::Align::Start,
-                        },
-                    },
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let mut last_report = Instant::now();
+            let result = PortableBundle::export(&capsule, &parent, &mut |copied, total| {
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map(|dest| format!("Exported {} as a portable bundle to {:?}", capsule.name, dest))
+            .map_err(|e| format!("{:#}", e));
 
-                    append = &Box {
-                        set_hexpand: true,
-                    },
+            let _ = sender_clone.input(MainWindowMsg::PortableBundleExportFinished(result));
+        });
+    }
 
-                    append = &Button {
-                        set_css_classes: &["accent"],
-                        #[wrap(Some)]
-                        set_child = &Box {
-                            set_orientation: Orientation::Horizontal,
-                            set_spacing: 6,
+    fn start_portable_bundle_import(&mut self, sender: ComponentSender<Self>, bundle_dir: PathBuf) {
+        if !PortableBundle::is_bundle(&bundle_dir) {
+            eprintln!("{:?} is not a LinuxBoy portable bundle", bundle_dir);
+            return;
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog("Importing bundle", cancel.clone());
 
-                            append = &Image {
-                                set_icon_name: Some("list-add-symbolic"),
-                                set_pixel_size: 16,
-                            },
+        let games_dir = self.games_dir.clone();
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let mut last_report = Instant::now();
+            let result = PortableBundle::import(&bundle_dir, &games_dir, &mut |copied, total| {
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map_err(|e| format!("{:#}", e));
 
-                            append = &Label {
-                                set_label: "Add Game",
-                            },
-                        },
-                        connect_clicked => MainWindowMsg::OpenAddGame,
-                    },
-                },
+            let _ = sender_clone.input(MainWindowMsg::CapsuleImportFinished(result));
+        });
+    }
 
-                // Main content area
-                append = &Box {
-                    set_orientation: Orientation::Vertical,
-                    set_hexpand: true,
-                    set_vexpand: true,
-                    set_margin_start: 12,
-                    set_margin_end: 12,
-                    set_margin_top: 10,
+    fn start_capsule_import(&mut self, sender: ComponentSender<Self>, archive_path: PathBuf) {
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog("Importing capsule", cancel.clone());
 
-                    #[local_ref]
-                    library_page -> Box {},
-                },
+        let games_dir = self.games_dir.clone();
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let mut last_report = Instant::now();
+            let result = BackupManager::restore(&archive_path, &games_dir, &mut |done, total| {
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: done, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map_err(|e| e.to_string());
 
-                // Status bar
-                append = &Box {
-                    set_orientation: Orientation::Horizontal,
-                    set_spacing: 12,
-                    set_margin_start: 20,
-                    set_margin_end: 20,
-                    set_margin_top: 24,
-                    set_margin_bottom: 28,
-                    set_css_classes: &["status-bar"],
+            let _ = sender_clone.input(MainWindowMsg::CapsuleImportFinished(result));
+        });
+    }
 
-                    append = &Label {
-                        #[watch]
-                        set_label: &format!("{} games", model.capsules.len()),
-                        set_css_classes: &["muted"],
-                    },
+    fn start_backup_verify(&mut self, sender: ComponentSender<Self>, archive_path: PathBuf) {
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog("Verifying backup", cancel.clone());
 
-                    append = &Box {
-                        set_hexpand: true,
-                    },
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let mut last_report = Instant::now();
+            let result = BackupManager::verify(&archive_path, &mut |done, total| {
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: done, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map_err(|e| e.to_string());
 
-                    append = &Button {
-                        #[watch]
-                        set_label: &match model.system_check.status {
-                            SystemStatus::AllInstalled => "System Ready",
-                            SystemStatus::PartiallyInstalled => "Setup Incomplete",
-                            SystemStatus::NothingInstalled => "Setup Required",
-                        },
-                        #[watch]
-                        set_css_classes: &match model.system_check.status {
-                            SystemStatus::AllInstalled => ["pill", "pill-installed"],
-                            SystemStatus::PartiallyInstalled => ["pill", "pill-warning"],
-                            SystemStatus::NothingInstalled => ["pill", "pill-missing"],
-                        },
-                        #[watch]
-                        set_tooltip_text: Some(&model.system_check.status_message()),
-                        set_halign: gtk4::Align::End,
-                        connect_clicked => MainWindowMsg::OpenSystemSetup,
-                    },
-                },
-            },
-        }
+            let _ = sender_clone.input(MainWindowMsg::BackupVerifyFinished(result));
+        });
     }
 
-    fn init(
-        _init: Self::Init,
-        root: Self::Root,
+    fn start_integrity_job(
+        &mut self,
         sender: ComponentSender<Self>,
-    ) -> ComponentParts<Self> {
-        let games_dir = dirs::home_dir()
-            .unwrap_or_default()
-            .join("Games");
+        capsule_dir: PathBuf,
+        verify: bool,
+    ) {
+        if self.is_capsule_busy(&capsule_dir) {
+            eprintln!("Cannot check game files while the capsule is busy");
+            return;
+        }
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        let game_folder = match capsule.game_folder() {
+            Some(folder) => folder,
+            None => {
+                eprintln!("No game folder found for {}", capsule.name);
+                return;
+            }
+        };
 
-        // Check system on startup
-        let system_check = SystemCheck::check();
-        println!("System check: {:?}", system_check.status);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let title = if verify {
+            "Verifying game files"
+        } else {
+            "Recording file manifest"
+        };
+        self.open_copy_progress_dialog(title, cancel.clone());
 
-        let games_list = Box::new(Orientation::Vertical, 16);
-        games_list.set_margin_all(0);
-        games_list.set_valign(gtk4::Align::Start);
-        games_list.set_hexpand(true);
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let checker = IntegrityChecker::new(&capsule, &game_folder);
+            let total = if verify {
+                checker
+                    .load_manifest()
+                    .ok()
+                    .flatten()
+                    .map(|manifest| manifest.total_size())
+                    .unwrap_or(0)
+            } else {
+                checker.folder_size()
+            };
+            let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: 0, total });
+
+            let mut done: u64 = 0;
+            let mut last_report = Instant::now();
+            let mut on_bytes = |bytes: u64| {
+                done += bytes;
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: done, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            };
+            if verify {
+                let result = checker.verify(&mut on_bytes).map_err(|e| e.to_string());
+                let _ = sender_clone.input(MainWindowMsg::GameFilesVerified(result));
+            } else {
+                let result = checker
+                    .record(&mut on_bytes)
+                    .map(|manifest| manifest.files.len())
+                    .map_err(|e| e.to_string());
+                let _ = sender_clone.input(MainWindowMsg::FileManifestRecorded(result));
+            }
+        });
+    }
 
-        let library_count_label = Label::new(None);
-        library_count_label.set_css_classes(&["muted"]);
-        library_count_label.set_halign(gtk4::Align::Start);
+    /// Serve the cached database right away, then refresh it in the
+    /// background once it is older than the cache TTL.
+    fn start_umu_db_sync(sender: ComponentSender<Self>) {
+        thread::spawn(move || {
+            let cached = UmuDatabase::load_cached();
+            let has_cache = cached.is_some();
+            if let Some(entries) = cached {
+                sender.input(MainWindowMsg::UmuDatabaseLoaded(entries));
+                if UmuDatabase::cache_is_fresh() {
+                    return;
+                }
+            }
 
-        let library_page = Box::new(Orientation::Vertical, 16);
-        library_page.set_margin_all(20);
-        library_page.set_hexpand(true);
-        library_page.set_vexpand(true);
-        library_page.set_halign(gtk4::Align::Start);
+            match UmuDatabase::refresh() {
+                Ok(Some(entries)) => sender.input(MainWindowMsg::UmuDatabaseLoaded(entries)),
+                Ok(None) => println!("UMU database is up to date."),
+                Err(e) if has_cache => eprintln!("UMU database refresh failed: {}", e),
+                Err(e) => sender.input(MainWindowMsg::UmuDatabaseFailed(e.to_string())),
+            }
+        });
+    }
 
-        let library_header = Box::new(Orientation::Horizontal, 12);
-        library_header.set_hexpand(true);
-        library_header.set_halign(gtk4::Align::Start);
+    /// Move focus around, or press the focused widget, in whichever LinuxBoy
+    /// window is in front. Ignored while another application has focus.
+    fn handle_gamepad(action: GamepadAction) {
+        let window = gtk4::Window::list_toplevels()
+            .into_iter()
+            .filter_map(|widget| widget.downcast::<gtk4::Window>().ok())
+            .find(|window| window.is_active());
+        let window = match window {
+            Some(window) => window,
+            None => return,
+        };
+        window.set_focus_visible(true);
+        let focus = GtkWindowExt::focus(&window);
+        // An open menu keeps focus to itself, so navigate and close it first.
+        let popover = focus
+            .as_ref()
+            .and_then(|widget| widget.ancestor(Popover::static_type()))
+            .and_then(|widget| widget.downcast::<Popover>().ok());
+
+        let direction = match action {
+            GamepadAction::Up => DirectionType::Up,
+            GamepadAction::Down => DirectionType::Down,
+            GamepadAction::Left => DirectionType::Left,
+            GamepadAction::Right => DirectionType::Right,
+            GamepadAction::Activate => {
+                if let Some(widget) = focus {
+                    widget.activate();
+                }
+                return;
+            }
+            GamepadAction::Back => {
+                if let Some(popover) = popover {
+                    popover.popdown();
+                } else if window.transient_for().is_some() {
+                    // Same as Escape; closing the main window would quit.
+                    window.close();
+                }
+                return;
+            }
+        };
+        match popover {
+            Some(popover) => popover.child_focus(direction),
+            None => window.child_focus(direction),
+        };
+    }
 
-        let library_icon = Image::from_icon_name("folder-open-symbolic");
-        library_icon.set_pixel_size(24);
+    fn open_umu_match_dialog(
+        &mut self,
+        sender: ComponentSender<Self>,
+        game_name: String,
+        matches: Vec<UmuMatch>,
+    ) {
+        if self.umu_match_dialog.is_some() {
+            return;
+        }
 
-        let library_title = Label::new(Some("Library"));
-        library_title.set_css_classes(&["section-title"]);
-        library_title.set_halign(gtk4::Align::Start);
+        // One row per title; the same game on several storefronts becomes a
+        // store choice inside the row.
+        let mut groups: Vec<UmuMatchGroup> = Vec::new();
+        for candidate in &matches {
+            let title = candidate
+                .entry
+                .title
+                .clone()
+                .unwrap_or_else(|| "Unknown title".to_string());
+            let key = Self::compact_name(&title);
+            match groups
+                .iter_mut()
+                .find(|group| Self::compact_name(&group.title) == key)
+            {
+                Some(group) => group.entries.push(candidate.entry.clone()),
+                None => groups.push(UmuMatchGroup {
+                    title,
+                    confidence: candidate.confidence,
+                    entries: vec![candidate.entry.clone()],
+                }),
+            }
+        }
+        let mut stores: Vec<String> = groups
+            .iter()
+            .flat_map(|group| group.entries.iter().map(Self::umu_store_name))
+            .collect();
+        stores.sort();
+        stores.dedup();
 
-        let library_spacer = Box::new(Orientation::Horizontal, 0);
-        library_spacer.set_hexpand(true);
+        let dialog = Dialog::builder()
+            .title("Match UMU Game")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Skip", ResponseType::Cancel);
+        dialog.add_button("Use Selection", ResponseType::Accept);
 
-        library_header.append(&library_icon);
-        library_header.append(&library_title);
-        library_header.append(&library_spacer);
-        library_header.append(&library_count_label);
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
 
-        let library_body = Box::new(Orientation::Vertical, 0);
-        library_body.set_halign(gtk4::Align::Start);
-        library_body.set_hexpand(true);
-        library_body.set_vexpand(true);
-        library_body.set_width_request(840);
+        let title = Label::new(Some(&format!(
+            "Select the UMU match for \"{}\"",
+            game_name
+        )));
+        title.set_halign(gtk4::Align::Start);
+        title.set_wrap(true);
+        title.set_css_classes(&["section-title"]);
 
-        let games_scroller = ScrolledWindow::new();
-        games_scroller.set_hexpand(true);
-        games_scroller.set_vexpand(true);
-        games_scroller.set_child(Some(&games_list));
-        library_body.append(&games_scroller);
+        let hint = Label::new(Some(
+            "Pick the correct game and the store you own it on. If none match, click Skip.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
 
-        library_page.append(&library_header);
-        library_page.append(&library_body);
+        let filter_row = Box::new(Orientation::Horizontal, 8);
+        let filter_label = Label::new(Some("Bought on"));
+        let mut filter_options = vec!["Any store".to_string()];
+        filter_options.extend(stores.iter().cloned());
+        let filter_refs: Vec<&str> = filter_options.iter().map(String::as_str).collect();
+        let store_filter = DropDown::from_strings(&filter_refs);
+        filter_row.append(&filter_label);
+        filter_row.append(&store_filter);
+        filter_row.set_visible(stores.len() > 1);
 
-        let model = MainWindow {
-            capsules: Vec::new(),
-            games_dir,
-            system_check,
-            system_setup_dialog: None,
-            runtime_mgr: RuntimeManager::new(),
-            add_game_dialog: None,
-            game_path_dialog: None,
-            name_dialog: None,
-            settings_dialog: None,
-            umu_match_dialog: None,
-            dependency_dialog: None,
-            existing_location_dialog: None,
-            pending_add_mode: None,
-            pending_game_path: None,
-            pending_source_folder: None,
-            pending_game_name: None,
-            pending_game_id: None,
-            pending_store: None,
-            pending_settings_capsule: None,
-            active_installs: HashMap::new(),
-            active_games: HashMap::new(),
-            preparing_installs: HashSet::new(),
-            dependency_installs: HashSet::new(),
-            umu_entries: Vec::new(),
-            umu_loaded: false,
-            umu_load_error: None,
-            games_list: games_list.clone(),
-            library_count_label,
-            root_window: root.clone(),
-        };
+        let listbox = ListBox::new();
+        listbox.set_selection_mode(SelectionMode::Single);
 
-        model.update_library_labels();
+        let mut store_dropdowns: Vec<Option<DropDown>> = Vec::new();
+        for group in &groups {
+            let row = ListBoxRow::new();
+            let row_box = Box::new(Orientation::Vertical, 4);
+            row_box.set_margin_all(8);
 
-        let widgets = view_output!();
+            let title_label = Label::new(Some(&group.title));
+            title_label.set_halign(gtk4::Align::Start);
+            title_label.set_wrap(true);
+            title_label.set_css_classes(&["card-title"]);
+            row_box.append(&title_label);
 
-        // Load capsules on startup
-        sender.input(MainWindowMsg::LoadCapsules);
-        Self::start_umu_db_sync(sender.clone());
+            let confidence = (group.confidence * 100.0).round() as u32;
+            if let [entry] = group.entries.as_slice() {
+                let umu_id = entry.umu_id.as_deref().unwrap_or("unknown");
+                let store = entry.store.as_deref().unwrap_or("unknown");
+                let codename = entry.codename.as_deref().unwrap_or("unknown");
+                let detail_text = format!(
+                    "{confidence}% match • UMU ID: {umu_id} • Store: {store} • Codename: {codename}"
+                );
+                let detail_label = Label::new(Some(&detail_text));
+                detail_label.set_halign(gtk4::Align::Start);
+                detail_label.set_wrap(true);
+                detail_label.set_css_classes(&["muted"]);
+                row_box.append(&detail_label);
+                store_dropdowns.push(None);
+            } else {
+                let store_row = Box::new(Orientation::Horizontal, 8);
+                let detail_label = Label::new(Some(&format!("{confidence}% match • Store:")));
+                detail_label.set_css_classes(&["muted"]);
+                let options: Vec<String> = group
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        format!(
+                            "{} ({})",
+                            Self::umu_store_name(entry),
+                            entry.umu_id.as_deref().unwrap_or("unknown")
+                        )
+                    })
+                    .collect();
+                let option_refs: Vec<&str> = options.iter().map(String::as_str).collect();
+                let store_dropdown = DropDown::from_strings(&option_refs);
+                store_row.append(&detail_label);
+                store_row.append(&store_dropdown);
+                row_box.append(&store_row);
+                store_dropdowns.push(Some(store_dropdown));
+            }
 
-        ComponentParts { model, widgets }
-    }
+            if let Some(notes) = group
+                .entries
+                .iter()
+                .filter_map(|entry| entry.notes.as_deref())
+                .find(|notes| !notes.trim().is_empty())
+            {
+                let notes_label = Label::new(Some(notes));
+                notes_label.set_halign(gtk4::Align::Start);
+                notes_label.set_wrap(true);
+                notes_label.set_css_classes(&["muted"]);
+                row_box.append(&notes_label);
+            }
 
-    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
-        match msg {
-            MainWindowMsg::LoadCapsules => {
-                match Capsule::scan_directory(&self.games_dir) {
-                    Ok(capsules) => {
-                        self.capsules = capsules;
-                        println!("Loaded {} capsules", self.capsules.len());
-                        self.update_library_labels();
-                        self.rebuild_games_list(sender.clone());
+            row.set_child(Some(&row_box));
+            listbox.append(&row);
+        }
+
+        if let Some(first_row) = listbox.row_at_index(0) {
+            listbox.select_row(Some(&first_row));
+        }
+
+        let groups = Rc::new(groups);
+        let store_dropdowns = Rc::new(store_dropdowns);
+        let selected_store: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+
+        {
+            let groups = groups.clone();
+            let selected_store = selected_store.clone();
+            listbox.set_filter_func(move |row| {
+                let store = selected_store.borrow();
+                let store = match store.as_deref() {
+                    Some(store) => store,
+                    None => return true,
+                };
+                usize::try_from(row.index())
+                    .ok()
+                    .and_then(|index| groups.get(index))
+                    .map(|group| {
+                        group
+                            .entries
+                            .iter()
+                            .any(|entry| Self::umu_store_name(entry) == store)
+                    })
+                    .unwrap_or(false)
+            });
+        }
+
+        {
+            let groups = groups.clone();
+            let store_dropdowns = store_dropdowns.clone();
+            let selected_store = selected_store.clone();
+            let listbox = listbox.clone();
+            store_filter.connect_selected_notify(move |dropdown| {
+                let store = (dropdown.selected() as usize)
+                    .checked_sub(1)
+                    .and_then(|index| stores.get(index).cloned());
+                // Point every multi-store row at the chosen store.
+                if let Some(store) = store.as_deref() {
+                    for (group, store_dropdown) in groups.iter().zip(store_dropdowns.iter()) {
+                        let position = group
+                            .entries
+                            .iter()
+                            .position(|entry| Self::umu_store_name(entry) == store);
+                        if let (Some(store_dropdown), Some(position)) = (store_dropdown, position) {
+                            store_dropdown.set_selected(position as u32);
+                        }
                     }
-                    Err(e) => {
-                        eprintln!("Failed to load capsules: {}", e);
+                }
+                *selected_store.borrow_mut() = store;
+                listbox.invalidate_filter();
+
+                let visible_selected = listbox
+                    .selected_row()
+                    .map(|row| row.is_child_visible())
+                    .unwrap_or(false);
+                if !visible_selected {
+                    let mut index = 0;
+                    while let Some(row) = listbox.row_at_index(index) {
+                        if row.is_child_visible() {
+                            listbox.select_row(Some(&row));
+                            break;
+                        }
+                        index += 1;
                     }
                 }
+            });
+        }
+
+        let selected_entry = {
+            let groups = groups.clone();
+            let store_dropdowns = store_dropdowns.clone();
+            let listbox = listbox.clone();
+            Rc::new(move || -> Option<UmuEntry> {
+                let row = listbox.selected_row().filter(|row| row.is_child_visible())?;
+                let index = usize::try_from(row.index()).ok()?;
+                let entry_index = store_dropdowns
+                    .get(index)?
+                    .as_ref()
+                    .map(|dropdown| dropdown.selected() as usize)
+                    .unwrap_or(0);
+                groups.get(index)?.entries.get(entry_index).cloned()
+            })
+        };
+
+        // Protonfixes pulled in by the highlighted entry, loaded on demand.
+        let fixes_title = Label::new(Some("Fixes applied"));
+        fixes_title.set_halign(gtk4::Align::Start);
+        fixes_title.set_css_classes(&["card-title"]);
+        let fixes_label = Label::new(None);
+        fixes_label.set_halign(gtk4::Align::Start);
+        fixes_label.set_wrap(true);
+        fixes_label.set_selectable(true);
+        fixes_label.set_css_classes(&["muted"]);
+
+        let request_fixes = {
+            let selected_entry = selected_entry.clone();
+            let sender = sender.clone();
+            Rc::new(move || {
+                if let Some(entry) = selected_entry() {
+                    sender.input(MainWindowMsg::LoadProtonFixes(entry));
+                }
+            })
+        };
+        {
+            let request_fixes = request_fixes.clone();
+            listbox.connect_row_selected(move |_, _| request_fixes());
+        }
+        for store_dropdown in store_dropdowns.iter().flatten() {
+            let request_fixes = request_fixes.clone();
+            store_dropdown.connect_selected_notify(move |_| request_fixes());
+        }
+
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_child(Some(&listbox));
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&filter_row);
+        layout.append(&scroller);
+        layout.append(&fixes_title);
+        layout.append(&fixes_label);
+        content.append(&layout);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            let selected = if response == ResponseType::Accept {
+                selected_entry()
+            } else {
+                None
+            };
+            sender_clone.input(MainWindowMsg::UmuMatchChosen {
+                game_id: selected.as_ref().and_then(|entry| entry.umu_id.clone()),
+                store: selected.as_ref().and_then(|entry| entry.store.clone()),
+            });
+
+            sender_clone.input(MainWindowMsg::UmuMatchDialogClosed);
+            dialog.close();
+        });
+
+        self.umu_fixes_label = Some(fixes_label);
+        request_fixes();
+        dialog.show();
+        self.umu_match_dialog = Some(dialog);
+    }
+
+    fn show_protonfixes(&self, result: &Result<Option<ProtonFixSummary>, String>) {
+        let label = match &self.umu_fixes_label {
+            Some(label) => label,
+            None => return,
+        };
+        let text = match result {
+            Ok(Some(summary)) => summary.describe(),
+            Ok(None) => {
+                "No game-specific protonfixes; only the global defaults apply.".to_string()
+            }
+            Err(e) => format!("Could not load protonfixes: {}", e),
+        };
+        label.set_text(&text);
+    }
+
+    fn umu_store_name(entry: &UmuEntry) -> String {
+        entry
+            .store
+            .as_deref()
+            .map(str::trim)
+            .filter(|store| !store.is_empty())
+            .unwrap_or("unknown")
+            .to_string()
+    }
+
+    fn open_dependency_dialog(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        metadata: CapsuleMetadata,
+    ) {
+        if self.dependency_dialog.is_some() {
+            return;
+        }
+
+        let dialog = Dialog::builder()
+            .title("Install Dependencies")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Skip", ResponseType::Cancel);
+        dialog.add_button("Install", ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some("Install optional dependencies?"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_wrap(true);
+        title.set_css_classes(&["section-title"]);
+
+        let hint = Label::new(Some(
+            "Installers are downloaded once and cached for every game. Disable any you don't want.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+
+        layout.append(&title);
+        layout.append(&hint);
+
+        // VC++ and DirectX are always offered; anything else only when the
+        // game asked for it in its settings.
+        let wanted = Self::wanted_redistributables(
+            metadata.install_vcredist,
+            metadata.install_dxweb,
+            &metadata.install_redistributables,
+        );
+        let mut offered = Redistributable::defaults();
+        for redist in &wanted {
+            if !offered.contains(redist) {
+                offered.push(*redist);
+            }
+        }
+        let prefix_path = capsule_dir
+            .join(format!("{}.AppImage.home", metadata.name))
+            .join("prefix");
+        let in_prefix = Redistributable::present_in(&prefix_path);
+
+        // Failed ones are offered again even when the game doesn't ask for
+        // them any more.
+        for id in &metadata.redistributables_failed {
+            if let Some(redist) = Redistributable::get(id) {
+                if !offered.contains(&redist) {
+                    offered.push(redist);
+                }
             }
-            MainWindowMsg::OpenAddGame => {
-                println!("Open add game dialog");
-                self.open_add_game_dialog(sender);
+        }
+
+        let mut redist_checks = Vec::new();
+        for redist in offered {
+            let cached = redist.is_cached();
+            let present = in_prefix.contains(&redist);
+            let failed = metadata
+                .redistributables_failed
+                .iter()
+                .any(|id| id == redist.id());
+            let recorded = Self::is_dependency_installed(&metadata, redist.id());
+            let row = Box::new(Orientation::Vertical, 4);
+            let check = CheckButton::with_label(redist.label());
+            check.set_tooltip_text(Some(redist.description));
+            check.set_active((wanted.contains(&redist) || failed) && cached && !present);
+            check.set_sensitive(cached);
+            let status = Label::new(Some(match (failed, present, cached, redist.cache_path()) {
+                (true, _, _, _) => "Failed: the installer reported an error last time",
+                (false, true, _, _) => "Already in this prefix",
+                (false, false, true, Some(_)) => "Cached",
+                (false, false, true, None) => "Downloaded by winetricks during install",
+                (false, false, false, _) => "Not downloaded",
+            }));
+            status.set_halign(gtk4::Align::Start);
+            status.set_css_classes(&["muted"]);
+            if failed {
+                status.set_css_classes(&["error"]);
             }
-            MainWindowMsg::AddGameModeChosen(mode) => {
-                self.add_game_dialog = None;
-                self.pending_add_mode = Some(mode);
-                self.open_game_path_dialog(sender, mode);
+            row.append(&check);
+            row.append(&status);
+            if !cached {
+                row.append(&Self::redist_download_button(redist, &check, &status));
+            } else if failed || present || recorded {
+                let actions = Box::new(Orientation::Horizontal, 8);
+                if failed {
+                    actions.append(&Self::dependency_item_button(
+                        "Retry",
+                        redist,
+                        false,
+                        &sender,
+                        &capsule_dir,
+                        &dialog,
+                    ));
+                }
+                actions.append(&Self::dependency_item_button(
+                    "Force reinstall",
+                    redist,
+                    true,
+                    &sender,
+                    &capsule_dir,
+                    &dialog,
+                ));
+                row.append(&actions);
             }
-            MainWindowMsg::GamePathSelected(path) => {
-                self.game_path_dialog = None;
-                self.pending_game_path = Some(path);
-                self.open_name_dialog(sender);
+            layout.append(&row);
+            redist_checks.push((redist, check));
+        }
+        content.append(&layout);
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                let selected = Self::selected_redistributables(&redist_checks);
+                sender_clone.input(MainWindowMsg::DependenciesSelected {
+                    capsule_dir: capsule_dir_clone.clone(),
+                    install_vcredist: selected.iter().any(|redist| redist.id() == VCREDIST_ID),
+                    install_dxweb: selected.iter().any(|redist| redist.id() == DIRECTX_ID),
+                    install_redistributables: Self::optional_redistributable_ids(&selected),
+                });
             }
-            MainWindowMsg::AddGameCancelled => {
-                self.add_game_dialog = None;
-                self.game_path_dialog = None;
-                self.name_dialog = None;
-                if let Some(dialog) = &self.umu_match_dialog {
-                    dialog.close();
+            sender_clone.input(MainWindowMsg::DependenciesDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.dependency_dialog = Some(dialog);
+    }
+
+    fn selected_redistributables(
+        checks: &[(&'static Redistributable, CheckButton)],
+    ) -> Vec<&'static Redistributable> {
+        checks
+            .iter()
+            .filter(|(_, check)| check.is_active())
+            .map(|(redist, _)| *redist)
+            .collect()
+    }
+
+    /// Ids for `install_redistributables`: everything but VC++ and DirectX,
+    /// which have their own settings.
+    fn optional_redistributable_ids(selected: &[&'static Redistributable]) -> Vec<String> {
+        selected
+            .iter()
+            .filter(|redist| !redist.is_default())
+            .map(|redist| redist.id().to_string())
+            .collect()
+    }
+
+    /// A button in the dependency dialog that installs just `redist`,
+    /// skipping the checks for an earlier install when `force` is set.
+    fn dependency_item_button(
+        label: &str,
+        redist: &'static Redistributable,
+        force: bool,
+        sender: &ComponentSender<Self>,
+        capsule_dir: &Path,
+        dialog: &Dialog,
+    ) -> Button {
+        let button = Button::with_label(label);
+        button.set_css_classes(&["secondary"]);
+        if force {
+            button.set_tooltip_text(Some(
+                "Run the installer again even if this component looks installed",
+            ));
+        }
+
+        let sender = sender.clone();
+        let capsule_dir = capsule_dir.to_path_buf();
+        let dialog = dialog.clone();
+        button.connect_clicked(move |_| {
+            sender.input(MainWindowMsg::InstallDependency {
+                capsule_dir: capsule_dir.clone(),
+                id: redist.id().to_string(),
+                force,
+            });
+            sender.input(MainWindowMsg::DependenciesDialogClosed);
+            dialog.close();
+        });
+        button
+    }
+
+    /// A button that downloads `redist` into the cache, reporting progress
+    /// in `status` and enabling `check` once the installer is available.
+    fn redist_download_button(
+        redist: &'static Redistributable,
+        check: &CheckButton,
+        status: &Label,
+    ) -> Button {
+        let button = Button::with_label("Download");
+        button.set_halign(gtk4::Align::Start);
+        button.set_css_classes(&["secondary"]);
+
+        let check = check.clone();
+        let status = status.clone();
+        button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            status.set_text(&format!("Preparing {} download...", redist.label()));
+
+            enum RedistUpdate {
+                Progress(String),
+                Done(Result<(), String>),
+            }
+            let (tx, rx) = std::sync::mpsc::channel::<RedistUpdate>();
+            thread::spawn(move || {
+                let cancel = AtomicBool::new(false);
+                let result = redist
+                    .download(false, &cancel, |status, _| {
+                        let _ = tx.send(RedistUpdate::Progress(status));
+                    })
+                    .map(|_| ())
+                    .map_err(|e| format!("{:#}", e));
+                let _ = tx.send(RedistUpdate::Done(result));
+            });
+
+            let button = button.clone();
+            let check = check.clone();
+            let status = status.clone();
+            glib::timeout_add_local(Duration::from_millis(100), move || {
+                while let Ok(update) = rx.try_recv() {
+                    match update {
+                        RedistUpdate::Progress(text) => status.set_text(&text),
+                        RedistUpdate::Done(Ok(())) => {
+                            status.set_text("Cached");
+                            button.set_visible(false);
+                            check.set_sensitive(true);
+                            check.set_active(true);
+                            return glib::ControlFlow::Break;
+                        }
+                        RedistUpdate::Done(Err(e)) => {
+                            eprintln!("{} download failed: {}", redist.label(), e);
+                            status.set_text(&format!("Download failed: {}", e));
+                            button.set_sensitive(true);
+                            return glib::ControlFlow::Break;
+                        }
+                    }
+                }
+                glib::ControlFlow::Continue
+            });
+        });
+        button
+    }
+
+    fn start_dependency_install(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        metadata: CapsuleMetadata,
+        redistributables: Vec<&'static Redistributable>,
+        force: bool,
+    ) {
+        let proton_path = match self.runner_runtime() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let home_path = capsule_dir.join(format!("{}.AppImage.home", metadata.name));
+        let prefix_path = home_path.join("prefix");
+
+        let mut tasks: Vec<&'static Redistributable> = Vec::new();
+        for redist in redistributables {
+            if !force && Self::is_dependency_installed(&metadata, redist.id()) {
+                continue;
+            }
+            if redist.is_cached() {
+                tasks.push(redist);
+            } else {
+                eprintln!(
+                    "{} installer not cached; download it from the dependency dialog",
+                    redist.label()
+                );
+            }
+        }
+
+        if tasks.is_empty() {
+            return;
+        }
+        // One progress dialog, so one install at a time.
+        if !self.dependency_installs.is_empty() {
+            eprintln!("Another dependency install is still running");
+            return;
+        }
+
+        self.dependency_installs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.dependency_cancel = Some(cancel.clone());
+        self.open_dependency_progress_dialog(sender.clone(), &metadata.name);
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let log = |line: String| sender_clone.input(MainWindowMsg::DependencyLog(line));
+            let snapshot_label = format!(
+                "Before installing {}",
+                tasks.iter().map(|redist| redist.id()).collect::<Vec<_>>().join(", ")
+            );
+            if let Err(e) = SnapshotManager::new(&home_path).create(&snapshot_label, true) {
+                eprintln!("Failed to snapshot prefix before dependency install: {}", e);
+                log(format!("Could not snapshot the prefix first: {}", e));
+            }
+
+            let in_prefix = Redistributable::present_in(&prefix_path);
+            let total = tasks.len();
+            let mut installed: Vec<String> = Vec::new();
+            let mut failed: Vec<String> = Vec::new();
+            let mut cancelled = false;
+            for (index, redist) in tasks.into_iter().enumerate() {
+                // Installers are never interrupted; cancelling skips the rest.
+                if cancel.load(Ordering::Relaxed) {
+                    cancelled = true;
+                    log("Cancelled; skipping the remaining installers".to_string());
+                    break;
+                }
+                let label = redist.label();
+                sender_clone.input(MainWindowMsg::DependencyStep {
+                    label: format!("Installing {} ({} of {})", label, index + 1, total),
+                    fraction: index as f64 / total as f64,
+                });
+                if !force && in_prefix.contains(&redist) {
+                    log(format!("{} is already in the prefix; skipping", label));
+                    installed.push(redist.id().to_string());
+                    continue;
+                }
+
+                let success = Self::install_redistributable(
+                    redist,
+                    &prefix_path,
+                    &proton_path,
+                    &metadata,
+                    &sender_clone,
+                );
+                if success {
+                    log(format!("✓ {} installed", label));
+                    installed.push(redist.id().to_string());
+                } else {
+                    eprintln!("Dependency installer failed: {}", label);
+                    log(format!("✗ {} failed", label));
+                    failed.push(redist.id().to_string());
+                }
+            }
+
+            let _ = sender_clone.input(MainWindowMsg::DependenciesFinished {
+                capsule_dir,
+                installed,
+                failed,
+                cancelled,
+            });
+        });
+    }
+
+    fn install_redistributable(
+        redist: &'static Redistributable,
+        prefix_path: &PathBuf,
+        proton_path: &PathBuf,
+        metadata: &CapsuleMetadata,
+        sender: &ComponentSender<Self>,
+    ) -> bool {
+        let log = |line: String| sender.input(MainWindowMsg::DependencyLog(line));
+        let cached = redist.cache_path();
+        match (redist.install_method(), cached) {
+            (InstallMethod::Installer(args), Some(path)) => {
+                log(format!("Running {} {}", path.display(), args.join(" ")));
+                let mut cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
+                cmd.env("PROTON_USE_XALIA", "0");
+                cmd.arg(&path);
+                cmd.args(args);
+                Self::run_dependency_command(host::command(cmd), sender)
+            }
+            (InstallMethod::Msi(args), Some(path)) => {
+                log(format!("Running msiexec /i {} {}", path.display(), args.join(" ")));
+                let mut cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
+                cmd.env("PROTON_USE_XALIA", "0");
+                cmd.arg("msiexec");
+                cmd.arg("/i");
+                cmd.arg(Self::host_path_to_windows(&path));
+                cmd.args(args);
+                Self::run_dependency_command(host::command(cmd), sender)
+            }
+            (InstallMethod::DirectX, Some(path)) => {
+                Self::install_directx_redist(prefix_path, proton_path, metadata, &path, sender)
+            }
+            (InstallMethod::Winetricks(verb), _) => match Winetricks::locate_or_download() {
+                Ok(winetricks) => {
+                    log(format!("Running winetricks {}", verb));
+                    let cmd = winetricks.command(prefix_path, proton_path, &[verb.to_string()]);
+                    Self::run_dependency_command(cmd, sender)
+                }
+                Err(e) => {
+                    log(format!("winetricks is not available: {}", e));
+                    false
+                }
+            },
+            (_, None) => {
+                log(format!("{} has no cached installer", redist.label()));
+                false
+            }
+        }
+    }
+
+    fn open_dependency_progress_dialog(
+        &mut self,
+        sender: ComponentSender<Self>,
+        game_name: &str,
+    ) {
+        if let Some(dialog) = self.dependency_progress_dialog.take() {
+            dialog.close();
+        }
+
+        let dialog = Dialog::builder()
+            .title("Installing Dependencies")
+            .modal(false)
+            .transient_for(&self.root_window)
+            .default_width(560)
+            .default_height(420)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Close", ResponseType::Close);
+        dialog.set_response_sensitive(ResponseType::Close, false);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some(&format!("Installing dependencies for {}", game_name)));
+        title.set_halign(gtk4::Align::Start);
+        title.set_wrap(true);
+        title.set_css_classes(&["section-title"]);
+
+        let step_label = Label::new(Some("Snapshotting the prefix..."));
+        step_label.set_halign(gtk4::Align::Start);
+        step_label.set_wrap(true);
+
+        let progress_bar = ProgressBar::new();
+
+        let log_view = TextView::new();
+        log_view.set_editable(false);
+        log_view.set_cursor_visible(false);
+        log_view.set_monospace(true);
+        log_view.set_wrap_mode(WrapMode::WordChar);
+        let log_scroller = ScrolledWindow::new();
+        log_scroller.set_vexpand(true);
+        log_scroller.set_min_content_height(200);
+        log_scroller.set_child(Some(&log_view));
+
+        layout.append(&title);
+        layout.append(&step_label);
+        layout.append(&progress_bar);
+        layout.append(&log_scroller);
+        content.append(&layout);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Cancel {
+                dialog.set_response_sensitive(ResponseType::Cancel, false);
+                sender.input(MainWindowMsg::CancelDependencyInstall);
+                return;
+            }
+            // Closing early leaves the install running; the game card still
+            // shows it.
+            sender.input(MainWindowMsg::DependencyProgressClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.dependency_progress_dialog = Some(dialog);
+        self.dependency_step_label = Some(step_label);
+        self.dependency_progress_bar = Some(progress_bar);
+        self.dependency_log = Some(log_view);
+    }
+
+    fn append_dependency_log(&self, line: &str) {
+        if let Some(view) = &self.dependency_log {
+            let buffer = view.buffer();
+            let mut end = buffer.end_iter();
+            buffer.insert(&mut end, line);
+            buffer.insert(&mut end, "\n");
+            view.scroll_to_iter(&mut buffer.end_iter(), 0.0, false, 0.0, 1.0);
+        }
+    }
+
+    /// Run an installer, streaming its stdout and stderr to the dependency
+    /// progress log. `cmd` must already be wrapped for the host.
+    fn run_dependency_command(mut cmd: Command, sender: &ComponentSender<Self>) -> bool {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        match cmd.spawn() {
+            Ok(mut child) => {
+                let stderr_reader = child.stderr.take().map(|stderr| {
+                    let sender_inner = sender.clone();
+                    thread::spawn(move || {
+                        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                            sender_inner.input(MainWindowMsg::DependencyLog(line));
+                        }
+                    })
+                });
+                if let Some(stdout) = child.stdout.take() {
+                    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                        sender.input(MainWindowMsg::DependencyLog(line));
+                    }
+                }
+                if let Some(reader) = stderr_reader {
+                    let _ = reader.join();
+                }
+                child.wait().map(|status| status.success()).unwrap_or(false)
+            }
+            Err(e) => {
+                eprintln!("Failed to run dependency installer: {}", e);
+                sender.input(MainWindowMsg::DependencyLog(format!(
+                    "Failed to start {}: {}",
+                    cmd.get_program().to_string_lossy(),
+                    e
+                )));
+                false
+            }
+        }
+    }
+
+    /// The command `start_game` runs for `capsule`: the runner or the
+    /// game's custom command, wrapped for the sandbox, gamescope, `terminal`
+    /// and Flatpak as configured. Output and the process group are set up by
+    /// the caller.
+    fn launch_command(
+        &self,
+        capsule: &Capsule,
+        prefix_path: &PathBuf,
+        proton_path: &PathBuf,
+        terminal: Option<&Terminal>,
+    ) -> Result<Command, String> {
+        let exe_path = PathBuf::from(&capsule.metadata.executables.main.path);
+        let mut game_args: Vec<String> = capsule
+            .metadata
+            .executables
+            .main
+            .args
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        for trick in &capsule.metadata.protonfixes_tricks {
+            game_args.push(format!("-pf_tricks={}", trick));
+        }
+        for replace in &capsule.metadata.protonfixes_replace_cmds {
+            game_args.push(format!("-pf_replace_cmd={}", replace));
+        }
+        for option in &capsule.metadata.protonfixes_dxvk_sets {
+            game_args.push(format!("-pf_dxvk_set={}", option));
+        }
+
+        let mut cmd = match capsule.metadata.custom_command() {
+            Some(template) => match CommandTemplate::command(
+                template,
+                &exe_path,
+                &game_args,
+                prefix_path,
+                proton_path,
+                &capsule.metadata,
+            ) {
+                Ok(cmd) => cmd,
+                Err(e) => {
+                    return Err(format!("Invalid launch command for {}: {}", capsule.name, e))
+                }
+            },
+            None => {
+                let mut cmd =
+                    Self::umu_base_command(prefix_path, proton_path, &capsule.metadata);
+                if Self::push_virtual_desktop(&mut cmd, &capsule.metadata) {
+                    cmd.arg(Self::host_path_to_windows(&exe_path));
+                } else {
+                    cmd.arg(&exe_path);
+                }
+                cmd.args(&game_args);
+                cmd
+            }
+        };
+        if let Some(start_dir) = capsule.metadata.executables.main.start_dir() {
+            cmd.current_dir(start_dir);
+        }
+        if let Some(channels) = capsule.metadata.wine_debug() {
+            cmd.env("WINEDEBUG", channels);
+        }
+        self.push_gaming_tools(&mut cmd, &capsule.metadata);
+        let metadata = &capsule.metadata;
+        if metadata.sandbox_enabled || metadata.block_network {
+            if !self.system_check.bwrap_installed {
+                return Err(format!(
+                    "{} needs bubblewrap (bwrap) for its sandbox or network block",
+                    capsule.name
+                ));
+            }
+            cmd = if metadata.sandbox_enabled {
+                let network = metadata.sandbox_network && !metadata.block_network;
+                Sandbox::wrap(cmd, capsule, proton_path, network)
+            } else {
+                Sandbox::without_network(cmd)
+            };
+        }
+        if metadata.gamescope_enabled && self.system_check.gamescope.installed {
+            cmd = self.system_check.gamescope.wrap(
+                cmd,
+                metadata,
+                self.system_check.display_session,
+            );
+        }
+        if let Some(terminal) = terminal {
+            cmd = terminal.wrap(cmd);
+        }
+
+        Ok(host::command(cmd))
+    }
+
+    /// Launch the game, with `CapsuleMetadata::safe_mode` settings when
+    /// `safe_mode` is set, and in a terminal window when `in_terminal` is.
+    fn start_game(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        safe_mode: bool,
+        in_terminal: bool,
+    ) {
+        let mut capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        if capsule.metadata.install_state == InstallState::Archived {
+            self.start_game_unpack(sender, capsule, true);
+            return;
+        }
+
+        if capsule.metadata.executables.main.path.trim().is_empty() {
+            eprintln!("No executable configured for {}", capsule.name);
+            return;
+        }
+
+        let terminal = if in_terminal {
+            match Terminal::detect() {
+                Some(terminal) => Some(terminal),
+                None => {
+                    eprintln!(
+                        "No terminal emulator found; install one such as xterm or set $TERMINAL"
+                    );
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        let custom_command = capsule.metadata.custom_command().is_some();
+        let runner = Runner::detect();
+        match &runner {
+            _ if custom_command => {}
+            Ok(Runner::Umu) => {}
+            Ok(runner) => println!(
+                "umu-run not found; launching {} with {}",
+                capsule.name,
+                runner.label()
+            ),
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        }
+
+        // System Wine runs without a Proton-GE runtime.
+        let system_wine = !custom_command && matches!(runner, Ok(Runner::Wine));
+        let proton_path = match self.runtime_mgr.latest_installed() {
+            Ok(Some(path)) => path,
+            Ok(None) if system_wine => PathBuf::new(),
+            Ok(None) => {
+                eprintln!("No Proton-GE runtime installed");
+                return;
+            }
+            Err(e) => {
+                eprintln!("Failed to resolve Proton-GE runtime: {}", e);
+                return;
+            }
+        };
+
+        let home_path = capsule.capsule_dir.join(format!("{}.AppImage.home", capsule.name));
+        let prefix_path = home_path.join("prefix");
+
+        let runtime_name = proton_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        if runtime_name.is_some() && capsule.metadata.wine_version != runtime_name {
+            if let (Some(previous), Some(current)) =
+                (capsule.metadata.wine_version.clone(), runtime_name.clone())
+            {
+                if self.snapshot_jobs.contains(&capsule_dir) {
+                    return;
+                }
+                // The preflight upgrades the prefix in place; keep a way back
+                // first. The launch resumes once the snapshot is taken.
+                self.snapshot_jobs.insert(capsule_dir.clone());
+                self.refresh_game_cards();
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let label = format!("Before switching from {} to {}", previous, current);
+                    if let Err(e) = SnapshotManager::new(&home_path).create(&label, true) {
+                        eprintln!("Failed to snapshot prefix before runtime change: {}", e);
+                    }
+                    let _ = sender_clone.input(MainWindowMsg::RuntimeSnapshotFinished {
+                        capsule_dir,
+                        runtime: current,
+                        safe_mode,
+                        in_terminal,
+                    });
+                });
+                return;
+            }
+            let mut updated = capsule.clone();
+            updated.metadata.wine_version = runtime_name;
+            if let Err(e) = updated.save_metadata() {
+                eprintln!("Failed to update metadata: {}", e);
+            }
+        }
+
+        if safe_mode {
+            println!("Launching {} with safe settings", capsule.name);
+            capsule.metadata = capsule.metadata.safe_mode();
+        }
+
+        if !custom_command
+            && !Self::run_umu_preflight(&prefix_path, &proton_path, &capsule.metadata)
+        {
+            eprintln!("UMU runtime preload failed.");
+            return;
+        }
+
+        if let Some(warning) = self
+            .system_check
+            .display_session
+            .launch_warning(&capsule.metadata)
+        {
+            eprintln!("Warning: {}", warning);
+        }
+
+        let mut cmd =
+            match self.launch_command(&capsule, &prefix_path, &proton_path, terminal.as_ref()) {
+                Ok(cmd) => cmd,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+        if capsule.metadata.wine_debug_once && capsule.metadata.wine_debug().is_some() {
+            // Reload so a safe-mode launch doesn't save its settings.
+            match Capsule::load_from_dir(&capsule_dir) {
+                Ok(mut saved) => {
+                    saved.metadata.wine_debug = None;
+                    saved.metadata.wine_debug_once = false;
+                    if let Err(e) = saved.save_metadata() {
+                        eprintln!("Failed to clear WINEDEBUG channels: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Failed to load capsule: {}", e),
+            }
+        }
+        let in_gamescope =
+            capsule.metadata.gamescope_enabled && self.system_check.gamescope.installed;
+        let screenshots_dir = capsule.screenshots_dir();
+
+        // Keep the game's output for support bundles. In a terminal the
+        // output is shown there instead.
+        let log_settings = LogSettings::load();
+        let mut log_watch = None;
+        match &terminal {
+            Some(terminal) => println!("Launching {} in {}", capsule.name, terminal.program),
+            None => match LaunchLog::open(&capsule, &log_settings)
+                .and_then(|log| Ok((log.try_clone()?, log)))
+            {
+                Ok((stdout_log, stderr_log)) => {
+                    cmd.stdout(stdout_log);
+                    cmd.stderr(stderr_log);
+                    println!("Writing game output to {:?}", capsule.launch_log_path());
+                    log_watch = Some((capsule.clone(), log_settings));
+                }
+                Err(e) => eprintln!("Failed to create launch log: {}", e),
+            },
+        }
+
+        unsafe {
+            cmd.pre_exec(|| {
+                libc::setpgid(0, 0);
+                Ok(())
+            });
+        }
+
+        if safe_mode {
+            self.safe_mode_games.insert(capsule_dir.clone());
+        }
+        let sender_clone = sender.clone();
+        let started = SystemTime::now();
+        let capsule_clone = capsule.clone();
+        thread::spawn(move || {
+            let mut child = match cmd.spawn() {
+                Ok(child) => child,
+                Err(e) => {
+                    eprintln!("Failed to launch game: {}", e);
+                    let _ = sender_clone.input(MainWindowMsg::GameFinished {
+                        capsule_dir,
+                        success: false,
+                    });
+                    return;
+                }
+            };
+
+            let pid = child.id() as i32;
+            let pgid = unsafe { libc::getpgid(pid) };
+            if pgid > 0 {
+                let _ = sender_clone.input(MainWindowMsg::GameStarted {
+                    capsule_dir: capsule_dir.clone(),
+                    pgid,
+                });
+            }
+
+            let game_exited = Arc::new(AtomicBool::new(false));
+            if let Some((capsule, settings)) = log_watch {
+                let game_exited = game_exited.clone();
+                thread::spawn(move || {
+                    while !game_exited.load(Ordering::Relaxed) {
+                        thread::sleep(Duration::from_secs(LOG_CHECK_SECS));
+                        match LaunchLog::enforce_limit(&capsule, &settings) {
+                            Ok(true) => println!("Rotated the launch log of {}", capsule.name),
+                            Ok(false) => {}
+                            Err(e) => {
+                                eprintln!("Failed to rotate launch log: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+
+            let status = child.wait();
+            game_exited.store(true, Ordering::Relaxed);
+            let success = status.as_ref().map(|status| status.success()).unwrap_or(false);
+            let exit_code = status.ok().and_then(|status| status.code());
+            if let Err(e) = PlayHistory::record(&capsule_clone, started, exit_code, success) {
+                eprintln!("Failed to record play session: {}", e);
+            }
+            if in_gamescope {
+                match Screenshots::collect_gamescope(started, &screenshots_dir) {
+                    Ok(0) => {}
+                    Ok(moved) => println!("Moved {} screenshots to {:?}", moved, screenshots_dir),
+                    Err(e) => eprintln!("Failed to collect screenshots: {}", e),
+                }
+            }
+            let _ = sender_clone.input(MainWindowMsg::GameFinished {
+                capsule_dir,
+                success,
+            });
+        });
+    }
+
+    fn run_wine_tool(&self, capsule_dir: &Path, tool: WineTool) {
+        let capsule = match Capsule::load_from_dir(capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let proton_path = match self.runner_runtime() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let prefix_path = capsule.home_path.join("prefix");
+        let mut cmd = Self::umu_base_command(&prefix_path, &proton_path, &capsule.metadata);
+        cmd.arg(tool.command());
+
+        thread::spawn(move || match host::command(cmd).status() {
+            Ok(status) if !status.success() => {
+                eprintln!("{} exited with {} for {}", tool.command(), status, capsule.name);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to run {}: {}", tool.command(), e),
+        });
+    }
+
+    /// Carry on adding the named game once it is known not to be, or to be
+    /// wanted as, a second copy of a library game.
+    fn continue_add_game(
+        &mut self,
+        sender: ComponentSender<Self>,
+        name: String,
+        add_mode: AddGameMode,
+    ) {
+        let matches = self.find_umu_matches(&name);
+        if !matches.is_empty() {
+            self.open_umu_match_dialog(sender, name, matches);
+            return;
+        }
+        match add_mode {
+            AddGameMode::Installer => {
+                self.finalize_pending_game(sender, None, None);
+            }
+            AddGameMode::Existing => {
+                self.pending_game_id = None;
+                self.pending_store = None;
+                self.open_existing_source_folder_dialog(sender);
+            }
+            AddGameMode::Prefix => {
+                self.pending_game_id = None;
+                self.pending_store = None;
+                self.open_prefix_import_dialog(sender);
+            }
+        }
+    }
+
+    /// Run the pending installer again in a library game's capsule, keeping
+    /// its prefix, saves and settings.
+    fn reinstall_into_existing(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        let installer_path = self.pending_game_path.take();
+        let msi_silent = std::mem::take(&mut self.pending_msi_silent);
+        let msi_properties = std::mem::take(&mut self.pending_msi_properties);
+        self.pending_add_mode = None;
+        self.pending_game_name = None;
+        self.pending_game_id = None;
+        self.pending_store = None;
+        self.pending_win32_prefix = false;
+        let installer_path = match installer_path {
+            Some(path) => path,
+            None => {
+                eprintln!("No installer path selected");
+                return;
+            }
+        };
+        if self.active_games.contains_key(&capsule_dir)
+            || self.active_installs.contains_key(&capsule_dir)
+        {
+            eprintln!("Close the game before reinstalling it");
+            return;
+        }
+
+        let mut capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        if Self::is_msi_file(&installer_path) {
+            capsule.metadata.msi_silent = msi_silent;
+            capsule.metadata.msi_properties = msi_properties;
+        }
+        println!(
+            "Reinstalling {} from {:?}",
+            capsule.metadata.title(),
+            installer_path
+        );
+        self.start_installer(&sender, capsule_dir, capsule.metadata, installer_path);
+        self.refresh_game_cards();
+    }
+
+    /// Ask what to do when the installer Resume setup would run is gone or
+    /// no longer the file the game was installed from.
+    fn open_installer_check_dialog(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        check: InstallerCheck,
+    ) {
+        if self.installer_check_dialog.is_some() {
+            return;
+        }
+
+        let (heading, detail, changed_path) = match check {
+            InstallerCheck::Changed(path) => (
+                "The installer has changed",
+                format!(
+                    "{} is not the file this game was installed from. It may be another \
+                     version or a damaged download.",
+                    path.display()
+                ),
+                Some(path),
+            ),
+            InstallerCheck::Missing(Some(path)) => (
+                "The installer is missing",
+                format!("Nothing is at {} any more. Choose where it is now.", path.display()),
+                None,
+            ),
+            _ => (
+                "No installer recorded",
+                "Choose the installer to run for this game.".to_string(),
+                None,
+            ),
+        };
+
+        let dialog = Dialog::builder()
+            .title("Resume Setup")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(420);
+        dialog.set_resizable(false);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Choose installer...", ResponseType::Other(1));
+        if changed_path.is_some() {
+            dialog.add_button("Use it anyway", ResponseType::Accept);
+        }
+        dialog.set_default_response(ResponseType::Other(1));
+
+        let content = dialog.content_area();
+        content.set_margin_all(16);
+        content.set_spacing(10);
+        let title = Label::new(Some(heading));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+        let hint = Label::new(Some(&detail));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_xalign(0.0);
+        content.append(&title);
+        content.append(&hint);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            match (response, &changed_path) {
+                (ResponseType::Accept, Some(path)) => {
+                    sender_clone.input(MainWindowMsg::ResumeInstallWith {
+                        capsule_dir: capsule_dir.clone(),
+                        installer_path: path.clone(),
+                    });
+                }
+                (ResponseType::Other(1), _) => {
+                    sender_clone.input(MainWindowMsg::RelocateInstaller(capsule_dir.clone()));
+                }
+                _ => {}
+            }
+            sender_clone.input(MainWindowMsg::InstallerCheckDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.installer_check_dialog = Some(dialog);
+    }
+
+    fn open_installer_chooser(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.capsule_folder_dialog.is_some() {
+            return;
+        }
+
+        let dialog = FileChooserNative::builder()
+            .title("Select Installer")
+            .action(FileChooserAction::Open)
+            .accept_label("Select")
+            .cancel_label("Cancel")
+            .transient_for(&self.root_window)
+            .build();
+        let filter = FileFilter::new();
+        filter.add_suffix("exe");
+        filter.add_suffix("msi");
+        filter.set_name(Some("Windows installers (.exe, .msi)"));
+        dialog.add_filter(&filter);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    sender_clone.input(MainWindowMsg::ResumeInstallWith {
+                        capsule_dir: capsule_dir.clone(),
+                        installer_path: path,
+                    });
+                }
+            }
+            sender_clone.input(MainWindowMsg::CapsuleFolderDialogClosed);
+            dialog.destroy();
+        });
+
+        dialog.show();
+        self.capsule_folder_dialog = Some(dialog);
+    }
+
+    fn finalize_pending_game(
+        &mut self,
+        sender: ComponentSender<Self>,
+        game_id: Option<String>,
+        store: Option<String>,
+    ) {
+        self.pending_add_mode = None;
+        self.pending_game_id = None;
+        self.pending_store = None;
+        let msi_silent = std::mem::take(&mut self.pending_msi_silent);
+        let win32_prefix = std::mem::take(&mut self.pending_win32_prefix);
+        let msi_properties = std::mem::take(&mut self.pending_msi_properties);
+        let installer_path = match self.pending_game_path.take() {
+            Some(path) => path,
+            None => {
+                eprintln!("No installer path selected");
+                self.pending_game_name = None;
+                return;
+            }
+        };
+
+        let name = match self.pending_game_name.take() {
+            Some(name) => name,
+            None => {
+                eprintln!("No pending game name available");
+                return;
+            }
+        };
+
+        if let Err(e) = fs::create_dir_all(&self.games_dir) {
+            eprintln!("Failed to create games directory: {}", e);
+            return;
+        }
+
+        let capsule_dir = self.unique_game_dir(&name);
+        if let Err(e) = fs::create_dir_all(&capsule_dir) {
+            eprintln!("Failed to create capsule directory: {}", e);
+            return;
+        }
+
+        let mut metadata = self.new_game_metadata();
+        metadata.name = name.clone();
+        metadata.installer_path = Some(installer_path.to_string_lossy().to_string());
+        metadata.install_state = InstallState::Installing;
+        metadata.game_id = game_id;
+        if store.is_some() {
+            metadata.store = store;
+        }
+        metadata.msi_silent = msi_silent;
+        metadata.msi_properties = msi_properties;
+        metadata.win32_prefix = win32_prefix;
+        let home_path = capsule_dir.join(format!("{}.AppImage.home", name));
+        let prefix_path = home_path.join("prefix");
+        let default_game_dir = prefix_path.join("games").join(&metadata.name);
+        metadata.game_dir = Some(default_game_dir.to_string_lossy().to_string());
+
+        self.start_installer(&sender, capsule_dir, metadata, installer_path);
+        sender.input(MainWindowMsg::LoadCapsules);
+    }
+
+    fn finalize_existing_game(
+        &mut self,
+        sender: ComponentSender<Self>,
+        target_input: String,
+        transfer: ExistingGameTransfer,
+    ) {
+        self.pending_add_mode = None;
+        let exe_path = match self.pending_game_path.take() {
+            Some(path) => path,
+            None => {
+                eprintln!("No game executable selected");
+                self.pending_game_name = None;
+                self.pending_game_id = None;
+                self.pending_store = None;
+                return;
+            }
+        };
+
+        let name = match self.pending_game_name.take() {
+            Some(name) => name,
+            None => {
+                eprintln!("No pending game name available");
+                self.pending_game_id = None;
+                self.pending_store = None;
+                return;
+            }
+        };
+
+        let source_dir = match self.pending_source_folder.take() {
+            Some(path) => path,
+            None => {
+                eprintln!("No source folder selected");
+                self.pending_game_id = None;
+                self.pending_store = None;
+                return;
+            }
+        };
+
+        let game_id = self.pending_game_id.take();
+        let store = self.pending_store.take();
+
+        if let Err(e) = fs::create_dir_all(&self.games_dir) {
+            eprintln!("Failed to create games directory: {}", e);
+            return;
+        }
+
+        let capsule_dir = self.unique_game_dir(&name);
+        if let Err(e) = fs::create_dir_all(&capsule_dir) {
+            self.abandon_new_capsule(
+                &capsule_dir,
+                &format!("Failed to create capsule directory: {}", e),
+            );
+            return;
+        }
+
+        let home_path = capsule_dir.join(format!("{}.AppImage.home", name));
+        let prefix_path = home_path.join("prefix");
+        let games_root = prefix_path.join("games");
+        if let Err(e) = fs::create_dir_all(prefix_path.join("drive_c")) {
+            self.abandon_new_capsule(&capsule_dir, &format!("Failed to create prefix: {}", e));
+            return;
+        }
+        if let Err(e) = fs::create_dir_all(&games_root) {
+            self.abandon_new_capsule(
+                &capsule_dir,
+                &format!("Failed to create games folder: {}", e),
+            );
+            return;
+        }
+
+        let relative_folder = Self::resolve_relative_game_folder(&name, &target_input);
+        let mut dest_dir = games_root.join(relative_folder);
+        dest_dir = Self::unique_path(dest_dir);
+
+        let mut should_copy = true;
+        if let (Ok(src), Ok(dest)) = (fs::canonicalize(&source_dir), fs::canonicalize(&dest_dir)) {
+            if src == dest {
+                should_copy = false;
+            }
+        }
+
+        if exe_path.strip_prefix(&source_dir).is_err() {
+            self.abandon_new_capsule(
+                &capsule_dir,
+                "Selected executable is not inside the chosen folder.",
+            );
+            return;
+        }
+
+        let relative_exe = exe_path
+            .strip_prefix(&source_dir)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                exe_path
+                    .file_name()
+                    .map(PathBuf::from)
+                    .unwrap_or_else(PathBuf::new)
+            });
+        let new_exe_path = dest_dir.join(relative_exe);
+
+        let job = ExistingGameJob {
+            capsule_dir,
+            home_path,
+            name,
+            dest_dir,
+            exe_path: new_exe_path,
+            game_id,
+            store,
+            win32_prefix: std::mem::take(&mut self.pending_win32_prefix),
+        };
+
+        if !should_copy {
+            self.complete_existing_game(sender, job);
+            return;
+        }
+
+        match transfer {
+            ExistingGameTransfer::Link => {
+                // A nested folder such as "Studio/Game" needs its parent first.
+                let linked = match job.dest_dir.parent() {
+                    Some(parent) => fs::create_dir_all(parent),
+                    None => Ok(()),
+                }
+                .and_then(|_| std::os::unix::fs::symlink(&source_dir, &job.dest_dir));
+                if let Err(e) = linked {
+                    self.abandon_new_capsule(
+                        &job.capsule_dir,
+                        &format!("Failed to link game folder: {}", e),
+                    );
+                    return;
+                }
+                self.complete_existing_game(sender, job);
+            }
+            ExistingGameTransfer::Copy | ExistingGameTransfer::Move => {
+                self.start_existing_game_copy(sender, source_dir, job, transfer);
+            }
+        }
+    }
+
+    fn start_existing_game_copy(
+        &mut self,
+        sender: ComponentSender<Self>,
+        source_dir: PathBuf,
+        job: ExistingGameJob,
+        transfer: ExistingGameTransfer,
+    ) {
+        let is_move = transfer == ExistingGameTransfer::Move;
+        let title = if is_move {
+            format!("Moving {}", job.name)
+        } else {
+            format!("Copying {}", job.name)
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&title, cancel.clone());
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            // A rename is instant when the source is on the same filesystem.
+            if is_move && fs::rename(&source_dir, &job.dest_dir).is_ok() {
+                let _ = sender_clone.input(MainWindowMsg::ExistingGameCopyFinished {
+                    job,
+                    result: Ok(()),
+                });
+                return;
+            }
+
+            let total = file_ops::dir_size(&source_dir);
+            if let Err(e) = file_ops::ensure_free_space(&job.dest_dir, total) {
+                let _ = sender_clone.input(MainWindowMsg::ExistingGameCopyFinished {
+                    job,
+                    result: Err(e.to_string()),
+                });
+                return;
+            }
+            let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: 0, total });
+
+            let mut copied: u64 = 0;
+            let mut last_report = Instant::now();
+            let result = file_ops::copy_dir_with_progress(&source_dir, &job.dest_dir, &mut |bytes| {
+                copied += bytes;
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map_err(|e| e.to_string());
+
+            if is_move && result.is_ok() {
+                if let Err(e) = fs::remove_dir_all(&source_dir) {
+                    eprintln!("Failed to remove original game folder: {}", e);
+                }
+            }
+
+            let _ = sender_clone.input(MainWindowMsg::ExistingGameCopyFinished { job, result });
+        });
+    }
+
+    /// Give up on a capsule that was only half created: remove it so the
+    /// library doesn't show an empty game, and tell the user why.
+    fn abandon_new_capsule(&self, capsule_dir: &Path, message: &str) {
+        eprintln!("{}", message);
+        if capsule_dir.exists() {
+            if let Err(e) = fs::remove_dir_all(capsule_dir) {
+                eprintln!("Failed to clean up capsule: {}", e);
+            }
+        }
+
+        let dialog = Dialog::builder()
+            .title("Could Not Add Game")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Close", ResponseType::Close);
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+        let label = Label::new(Some(message));
+        label.set_halign(gtk4::Align::Start);
+        label.set_wrap(true);
+        layout.append(&label);
+        dialog.content_area().append(&layout);
+        dialog.connect_response(|dialog, _| dialog.close());
+        dialog.show();
+    }
+
+    /// What a new capsule starts from: the defaults from Preferences, then
+    /// the template for new games on top.
+    fn new_game_metadata(&self) -> CapsuleMetadata {
+        let mut metadata = CapsuleMetadata::default();
+        self.new_game_defaults.apply(&mut metadata);
+        self.templates.apply_default(&mut metadata);
+        metadata
+    }
+
+    fn complete_existing_game(&mut self, sender: ComponentSender<Self>, job: ExistingGameJob) {
+        let ExistingGameJob {
+            capsule_dir,
+            home_path,
+            name,
+            dest_dir,
+            exe_path,
+            game_id,
+            store,
+            win32_prefix,
+        } = job;
+
+        let mut metadata = self.new_game_metadata();
+        metadata.name = name;
+        metadata.install_state = InstallState::Installed;
+        metadata.executables.main.path = exe_path.to_string_lossy().to_string();
+        metadata.game_id = game_id;
+        if store.is_some() {
+            metadata.store = store;
+        }
+        metadata.game_dir = Some(dest_dir.to_string_lossy().to_string());
+        metadata.win32_prefix = win32_prefix;
+
+        let capsule = Capsule {
+            name: metadata.name.clone(),
+            capsule_dir: capsule_dir.clone(),
+            home_path,
+            metadata: metadata.clone(),
+        };
+
+        if let Err(e) = capsule.save_metadata() {
+            eprintln!("Failed to save metadata: {}", e);
+            return;
+        }
+
+        if self.should_prompt_dependencies(&capsule_dir, &metadata) {
+            self.open_dependency_dialog(sender.clone(), capsule_dir.clone(), metadata);
+        }
+        sender.input(MainWindowMsg::LoadCapsules);
+    }
+
+    /// Accept either a Wine prefix or a Proton compatdata folder holding one in `pfx/`.
+    fn resolve_wine_prefix(path: &Path) -> Option<PathBuf> {
+        if path.join("drive_c").is_dir() {
+            Some(path.to_path_buf())
+        } else if path.join("pfx").join("drive_c").is_dir() {
+            Some(path.join("pfx"))
+        } else {
+            None
+        }
+    }
+
+    fn open_prefix_import_dialog(&mut self, sender: ComponentSender<Self>) {
+        if self.prefix_import_dialog.is_some() {
+            return;
+        }
+
+        let dialog = Dialog::builder()
+            .title("Import Prefix")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Import", ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some("How should the prefix be added?"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let source = self
+            .pending_game_path
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let hint = Label::new(Some(&format!(
+            "{}\nThe main executable is guessed from its shortcuts and program folders afterwards.",
+            source
+        )));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+
+        let copy_check = CheckButton::with_label("Copy (keeps the original prefix)");
+        copy_check.set_active(true);
+        let move_check = CheckButton::with_label("Adopt (move it into the capsule)");
+        move_check.set_group(Some(&copy_check));
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&copy_check);
+        layout.append(&move_check);
+        content.append(&layout);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                let transfer = if move_check.is_active() {
+                    ExistingGameTransfer::Move
+                } else {
+                    ExistingGameTransfer::Copy
+                };
+                sender_clone.input(MainWindowMsg::PrefixImportConfirmed(transfer));
+            } else {
+                sender_clone.input(MainWindowMsg::PrefixImportCancelled);
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+        self.prefix_import_dialog = Some(dialog);
+    }
+
+    fn finalize_prefix_import(
+        &mut self,
+        sender: ComponentSender<Self>,
+        transfer: ExistingGameTransfer,
+    ) {
+        self.pending_add_mode = None;
+        let game_id = self.pending_game_id.take();
+        let store = self.pending_store.take();
+        let source_prefix = match self.pending_game_path.take() {
+            Some(path) => path,
+            None => {
+                eprintln!("No prefix selected");
+                self.pending_game_name = None;
+                return;
+            }
+        };
+        let name = match self.pending_game_name.take() {
+            Some(name) => name,
+            None => {
+                eprintln!("No pending game name available");
+                return;
+            }
+        };
+
+        if let Err(e) = fs::create_dir_all(&self.games_dir) {
+            eprintln!("Failed to create games directory: {}", e);
+            return;
+        }
+        let capsule_dir = self.unique_game_dir(&name);
+        let home_path = capsule_dir.join(format!("{}.AppImage.home", name));
+        if let Err(e) = fs::create_dir_all(&home_path) {
+            eprintln!("Failed to create capsule directory: {}", e);
+            return;
+        }
+        let prefix_path = home_path.join("prefix");
+
+        let job = PrefixImportJob {
+            capsule_dir,
+            home_path,
+            name,
+            game_id,
+            store,
+        };
+
+        let is_move = transfer == ExistingGameTransfer::Move;
+        let title = if is_move {
+            format!("Moving prefix for {}", job.name)
+        } else {
+            format!("Copying prefix for {}", job.name)
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(&title, cancel.clone());
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            if is_move && fs::rename(&source_prefix, &prefix_path).is_ok() {
+                let _ = sender_clone.input(MainWindowMsg::PrefixImportFinished {
+                    job,
+                    result: Ok(()),
+                });
+                return;
+            }
+
+            let total = file_ops::dir_size(&source_prefix);
+            if let Err(e) = file_ops::ensure_free_space(&prefix_path, total) {
+                let _ = sender_clone.input(MainWindowMsg::PrefixImportFinished {
+                    job,
+                    result: Err(e.to_string()),
+                });
+                return;
+            }
+            let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied: 0, total });
+
+            let mut copied: u64 = 0;
+            let mut last_report = Instant::now();
+            let result = file_ops::copy_dir_with_progress(&source_prefix, &prefix_path, &mut |bytes| {
+                copied += bytes;
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::CopyProgress { copied, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map_err(|e| e.to_string());
+
+            if is_move && result.is_ok() {
+                if let Err(e) = fs::remove_dir_all(&source_prefix) {
+                    eprintln!("Failed to remove original prefix: {}", e);
+                }
+            }
+
+            let _ = sender_clone.input(MainWindowMsg::PrefixImportFinished { job, result });
+        });
+    }
+
+    fn complete_prefix_import(&mut self, sender: ComponentSender<Self>, job: PrefixImportJob) {
+        let PrefixImportJob {
+            capsule_dir,
+            home_path,
+            name,
+            game_id,
+            store,
+        } = job;
+
+        let win32_prefix = fs::read_to_string(home_path.join("prefix").join("system.reg"))
+            .map(|content| content.lines().take(10).any(|line| line.trim() == "#arch=win32"))
+            .unwrap_or(false);
+        let defaults = self.new_game_metadata();
+        let metadata = CapsuleMetadata {
+            name: name.clone(),
+            install_state: InstallState::Installed,
+            game_id,
+            store: store.or(defaults.store),
+            win32_prefix,
+            ..defaults
+        };
+        let mut capsule = Capsule {
+            name,
+            capsule_dir: capsule_dir.clone(),
+            home_path,
+            metadata,
+        };
+
+        let candidates = Self::executable_candidates(&capsule, false);
+        let guess = Self::confident_guess(&candidates).cloned();
+        if let Some(guess) = guess.clone() {
+            println!("Proposed main executable: {:?}", guess.path);
+            Self::apply_executable_guess(&mut capsule.metadata, guess);
+        }
+
+        if let Err(e) = capsule.save_metadata() {
+            eprintln!("Failed to save metadata: {}", e);
+            return;
+        }
+
+        sender.input(MainWindowMsg::LoadCapsules);
+        if guess.is_none() && !candidates.is_empty() {
+            self.open_executable_picker(sender, capsule_dir, candidates);
+        } else {
+            // Let the user confirm or replace the guessed executable.
+            self.open_game_settings_dialog(sender, capsule_dir);
+        }
+    }
+
+    fn find_umu_matches(&self, title: &str) -> Vec<UmuMatch> {
+        if !self.umu_loaded || self.umu_entries.is_empty() {
+            return Vec::new();
+        }
+
+        let matcher = TitleMatcher::new(title);
+        if matcher.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<UmuMatch> = Vec::new();
+        for entry in &self.umu_entries {
+            let title_score = entry
+                .title
+                .as_deref()
+                .and_then(|entry_title| matcher.score_title(entry_title));
+            let acronym_score = entry
+                .acronym
+                .as_deref()
+                .and_then(|acronym| matcher.score_acronym(acronym));
+            let best_score = match (title_score, acronym_score) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, b) => a.or(b),
+            };
+            if let Some(confidence) = best_score {
+                matches.push(UmuMatch {
+                    entry: entry.clone(),
+                    confidence,
+                });
+            }
+        }
+
+        if matches.is_empty() {
+            return matches;
+        }
+
+        let mut seen = HashSet::new();
+        matches.retain(|candidate| {
+            let key = (
+                candidate.entry.umu_id.clone().unwrap_or_default(),
+                candidate.entry.store.clone().unwrap_or_default(),
+                candidate.entry.codename.clone().unwrap_or_default(),
+            );
+            seen.insert(key)
+        });
+
+        matches.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then_with(|| {
+                    let a_title = a.entry.title.as_deref().unwrap_or("");
+                    let b_title = b.entry.title.as_deref().unwrap_or("");
+                    a_title.len().cmp(&b_title.len())
+                })
+                .then_with(|| {
+                    let a_title = a.entry.title.as_deref().unwrap_or("");
+                    let b_title = b.entry.title.as_deref().unwrap_or("");
+                    a_title.cmp(b_title)
+                })
+                .then_with(|| {
+                    let a_store = a.entry.store.as_deref().unwrap_or("");
+                    let b_store = b.entry.store.as_deref().unwrap_or("");
+                    a_store.cmp(b_store)
+                })
+        });
+
+        matches.truncate(20);
+        matches
+    }
+
+    /// Pick an executable by browsing the capsule's drive and game folder.
+    /// Only folders and .exe files are listed; detected candidates, and the
+    /// folders leading to them, are highlighted.
+    fn open_exe_browser(
+        root_window: &ApplicationWindow,
+        roots: &[(String, PathBuf)],
+        suggested: &[PathBuf],
+        exe_entry: &Entry,
+    ) {
+        let dialog = Dialog::builder()
+            .title("Select Game Executable")
+            .modal(true)
+            .transient_for(root_window)
+            .default_width(560)
+            .default_height(520)
+            .build();
+        dialog.add_button("Other location...", ResponseType::Other(1));
+        dialog.add_button("Cancel", ResponseType::Cancel);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let on_pick: Rc<dyn Fn(&Path)> = {
+            let dialog = dialog.clone();
+            let exe_entry = exe_entry.clone();
+            Rc::new(move |path: &Path| {
+                exe_entry.set_text(&path.to_string_lossy());
+                dialog.close();
+            })
+        };
+        let suggested: Rc<Vec<PathBuf>> = Rc::new(suggested.to_vec());
+
+        if !suggested.is_empty() {
+            let detected_title = Label::new(Some("Detected"));
+            detected_title.set_halign(gtk4::Align::Start);
+            detected_title.set_css_classes(&["card-title"]);
+            layout.append(&detected_title);
+            for path in suggested.iter().take(EXE_PICKER_LIMIT) {
+                let shown = roots
+                    .iter()
+                    .find_map(|(_, root)| path.strip_prefix(root).ok())
+                    .unwrap_or(path.as_path());
+                let button = Button::with_label(&shown.to_string_lossy());
+                button.add_css_class("flat");
+                button.add_css_class("exe-suggested");
+                button.set_halign(gtk4::Align::Start);
+                let on_pick = on_pick.clone();
+                let path = path.clone();
+                button.connect_clicked(move |_| on_pick(&path));
+                layout.append(&button);
+            }
+        }
+
+        let tree = Box::new(Orientation::Vertical, 2);
+        for (label, root) in roots {
+            if root.is_dir() {
+                let expander = Self::exe_browser_folder(label, root, &suggested, &on_pick);
+                expander.set_expanded(true);
+                tree.append(&expander);
+            }
+        }
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_child(Some(&tree));
+        layout.append(&scroller);
+        content.append(&layout);
+
+        let root_window = root_window.clone();
+        let exe_entry = exe_entry.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Other(1) {
+                Self::open_exe_file_chooser(&root_window, &exe_entry);
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    }
+
+    /// A folder in the executable browser, filled in when first expanded.
+    fn exe_browser_folder(
+        label: &str,
+        dir: &Path,
+        suggested: &Rc<Vec<PathBuf>>,
+        on_pick: &Rc<dyn Fn(&Path)>,
+    ) -> Expander {
+        let expander = Expander::new(Some(label));
+        if suggested.iter().any(|path| path.starts_with(dir)) {
+            expander.add_css_class("exe-suggested");
+        }
+        let children = Box::new(Orientation::Vertical, 2);
+        children.set_margin_start(16);
+        expander.set_child(Some(&children));
+
+        let loaded = Cell::new(false);
+        let dir = dir.to_path_buf();
+        let suggested = suggested.clone();
+        let on_pick = on_pick.clone();
+        expander.connect_expanded_notify(move |expander| {
+            if !expander.is_expanded() || loaded.replace(true) {
+                return;
+            }
+            Self::fill_exe_browser_folder(&dir, &children, &suggested, &on_pick);
+        });
+        expander
+    }
+
+    fn fill_exe_browser_folder(
+        dir: &Path,
+        children: &Box,
+        suggested: &Rc<Vec<PathBuf>>,
+        on_pick: &Rc<dyn Fn(&Path)>,
+    ) {
+        let mut folders = Vec::new();
+        let mut executables = Vec::new();
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                // Symlinked folders are left out; Wine links some to $HOME.
+                let file_type = match entry.file_type() {
+                    Ok(file_type) => file_type,
+                    Err(_) => continue,
+                };
+                let path = entry.path();
+                if file_type.is_dir() {
+                    folders.push(path);
+                } else if file_type.is_file() && Self::is_exe_file(&path) {
+                    executables.push(path);
+                }
+            }
+        }
+        let sort_key = |path: &PathBuf| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+        };
+        folders.sort_by_key(sort_key);
+        executables.sort_by_key(sort_key);
+
+        for folder in folders {
+            let name = folder
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            children.append(&Self::exe_browser_folder(&name, &folder, suggested, on_pick));
+        }
+        for exe in executables {
+            let name = exe
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let button = Button::with_label(&name);
+            button.add_css_class("flat");
+            button.set_halign(gtk4::Align::Start);
+            if suggested.contains(&exe) {
+                button.add_css_class("exe-suggested");
+                button.set_tooltip_text(Some("Detected as a likely game executable"));
+            }
+            let on_pick = on_pick.clone();
+            button.connect_clicked(move |_| on_pick(&exe));
+            children.append(&button);
+        }
+        if children.first_child().is_none() {
+            let empty = Label::new(Some("No folders or executables"));
+            empty.set_halign(gtk4::Align::Start);
+            empty.set_css_classes(&["muted"]);
+            children.append(&empty);
+        }
+    }
+
+    /// The system file chooser, for executables outside the capsule.
+    fn open_exe_file_chooser(root_window: &ApplicationWindow, exe_entry: &Entry) {
+        let dialog = FileChooserNative::builder()
+            .title("Select Game Executable")
+            .action(FileChooserAction::Open)
+            .accept_label("Select")
+            .cancel_label("Cancel")
+            .transient_for(root_window)
+            .build();
+
+        let filter = FileFilter::new();
+        filter.add_suffix("exe");
+        filter.set_name(Some("Windows executables (.exe)"));
+        dialog.add_filter(&filter);
+
+        let exe_entry = exe_entry.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(file) = dialog.file() {
+                    if let Some(path) = file.path() {
+                        exe_entry.set_text(&path.to_string_lossy());
+                    }
+                }
+            }
+            dialog.destroy();
+        });
+
+        dialog.show();
+    }
+
+    /// Ask which executable to use: a picker when there are candidates,
+    /// otherwise the settings dialog to enter one by hand.
+    fn choose_executable(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        let candidates = Capsule::load_from_dir(&capsule_dir)
+            .map(|capsule| Self::executable_candidates(&capsule, false))
+            .unwrap_or_default();
+        if candidates.is_empty() {
+            self.open_game_settings_dialog(sender, capsule_dir);
+        } else {
+            self.open_executable_picker(sender, capsule_dir, candidates);
+        }
+    }
+
+    fn open_executable_picker(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        mut candidates: Vec<ExecutableGuess>,
+    ) {
+        if self.executable_picker.is_some() {
+            return;
+        }
+        candidates.truncate(EXE_PICKER_LIMIT);
+        let prefix_path = Capsule::load_from_dir(&capsule_dir)
+            .map(|capsule| capsule.home_path.join("prefix"))
+            .ok();
+
+        let dialog = Dialog::builder()
+            .title("Choose Game Executable")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Enter manually...", ResponseType::Other(1));
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Use Selection", ResponseType::Accept);
+        dialog.set_default_response(ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some("Which program starts the game?"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let hint = Label::new(Some(
+            "Several executables look like the game. They are listed best match first.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+
+        let listbox = ListBox::new();
+        listbox.set_selection_mode(SelectionMode::Single);
+        for guess in &candidates {
+            let row = ListBoxRow::new();
+            let row_box = Box::new(Orientation::Vertical, 4);
+            row_box.set_margin_all(8);
+
+            let name = guess
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let name_label = Label::new(Some(&name));
+            name_label.set_halign(gtk4::Align::Start);
+            name_label.set_css_classes(&["card-title"]);
+
+            let shown_path = prefix_path
+                .as_deref()
+                .and_then(|prefix| guess.path.strip_prefix(prefix).ok())
+                .unwrap_or(guess.path.as_path());
+            let path_label = Label::new(Some(&shown_path.to_string_lossy()));
+            path_label.set_halign(gtk4::Align::Start);
+            path_label.set_wrap(true);
+            path_label.set_selectable(true);
+            path_label.set_css_classes(&["muted"]);
+
+            let size = fs::metadata(&guess.path)
+                .map(|meta| file_ops::format_bytes(meta.len()))
+                .unwrap_or_else(|_| "unknown size".to_string());
+            let source = match &guess.shortcut {
+                Some(shortcut) => format!(
+                    "From shortcut \"{}\"",
+                    shortcut
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                ),
+                None => "Found by scanning the prefix".to_string(),
+            };
+            let detail_label = Label::new(Some(&format!(
+                "Score {} • {} • {}",
+                guess.score, size, source
+            )));
+            detail_label.set_halign(gtk4::Align::Start);
+            detail_label.set_wrap(true);
+            detail_label.set_css_classes(&["muted"]);
+
+            row_box.append(&name_label);
+            row_box.append(&path_label);
+            row_box.append(&detail_label);
+            row.set_child(Some(&row_box));
+            listbox.append(&row);
+        }
+        if let Some(first_row) = listbox.row_at_index(0) {
+            listbox.select_row(Some(&first_row));
+        }
+
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_min_content_height(320);
+        scroller.set_child(Some(&listbox));
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&scroller);
+        content.append(&layout);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            let choice = listbox
+                .selected_row()
+                .and_then(|row| usize::try_from(row.index()).ok())
+                .and_then(|index| candidates.get(index).cloned());
+            match response {
+                ResponseType::Accept if choice.is_some() => {
+                    sender_clone.input(MainWindowMsg::ExecutableChosen {
+                        capsule_dir: capsule_dir.clone(),
+                        choice,
+                    });
+                }
+                ResponseType::Accept | ResponseType::Other(1) => {
+                    sender_clone.input(MainWindowMsg::ExecutableChosen {
+                        capsule_dir: capsule_dir.clone(),
+                        choice: None,
+                    });
+                }
+                _ => {}
+            }
+            sender_clone.input(MainWindowMsg::ExecutablePickerClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.executable_picker = Some(dialog);
+    }
+
+    fn open_game_settings_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.settings_dialog.is_some() {
+            return;
+        }
+
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let dialog = Dialog::builder()
+            .title("Game Settings")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Save", ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let exe_label = Label::new(Some("Executable"));
+        exe_label.set_halign(gtk4::Align::Start);
+
+        let exe_row = Box::new(Orientation::Horizontal, 8);
+        exe_row.set_hexpand(true);
+
+        let exe_entry = Entry::new();
+        exe_entry.set_hexpand(true);
+        exe_entry.set_placeholder_text(Some("Path to game executable (.exe)"));
+        if !capsule.metadata.executables.main.path.trim().is_empty() {
+            exe_entry.set_text(&capsule.metadata.executables.main.path);
+        }
+
+        let drive_c = capsule.home_path.join("prefix").join("drive_c");
+        let mut browse_roots = vec![("C: drive".to_string(), drive_c.clone())];
+        if let Some(game_dir) = capsule
+            .metadata
+            .game_dir
+            .as_deref()
+            .map(PathBuf::from)
+            .filter(|dir| !dir.starts_with(&drive_c))
+        {
+            browse_roots.push(("Game folder".to_string(), game_dir));
+        }
+        let suggested: Vec<PathBuf> = Self::cached_executable_candidates(&capsule)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|guess| guess.path)
+            .collect();
+        let exe_entry_clone = exe_entry.clone();
+        let root_window = self.root_window.clone();
+        let browse_button = Button::with_label("Browse");
+        browse_button.connect_clicked(move |_| {
+            Self::open_exe_browser(&root_window, &browse_roots, &suggested, &exe_entry_clone);
+        });
+
+        let rescan_button = Button::with_label("Re-scan");
+        rescan_button.set_tooltip_text(Some(
+            "Search the prefix for executables again and pick the most likely one",
+        ));
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        rescan_button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            button.set_label("Scanning...");
+            sender_clone.input(MainWindowMsg::RescanExecutables(capsule_dir_clone.clone()));
+        });
+
+        exe_row.append(&exe_entry);
+        exe_row.append(&browse_button);
+        exe_row.append(&rescan_button);
+        self.settings_exe_field = Some(ExecutableField {
+            capsule_dir: capsule_dir.clone(),
+            entry: exe_entry.clone(),
+            rescan_button,
+        });
+
+        let working_dir_label = Label::new(Some("Start in (optional)"));
+        working_dir_label.set_halign(gtk4::Align::Start);
+        let working_dir_row = Box::new(Orientation::Horizontal, 8);
+        working_dir_row.set_hexpand(true);
+        let working_dir_entry = Entry::new();
+        working_dir_entry.set_hexpand(true);
+        working_dir_entry.set_placeholder_text(Some("Folder of the executable"));
+        if let Some(working_dir) = &capsule.metadata.executables.main.working_dir {
+            working_dir_entry.set_text(working_dir);
+        }
+        let working_dir_entry_clone = working_dir_entry.clone();
+        let root_window = self.root_window.clone();
+        let working_dir_button = Button::with_label("Browse");
+        working_dir_button.connect_clicked(move |_| {
+            let dialog = FileChooserNative::builder()
+                .title("Select Start Folder")
+                .action(FileChooserAction::SelectFolder)
+                .accept_label("Select")
+                .cancel_label("Cancel")
+                .transient_for(&root_window)
+                .build();
+
+            let working_dir_entry_inner = working_dir_entry_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        working_dir_entry_inner.set_text(&path.to_string_lossy());
+                    }
+                }
+                dialog.destroy();
+            });
+
+            dialog.show();
+        });
+        working_dir_row.append(&working_dir_entry);
+        working_dir_row.append(&working_dir_button);
+
+        let game_id_label = Label::new(Some("UMU Game ID (optional)"));
+        game_id_label.set_halign(gtk4::Align::Start);
+        let game_id_entry = Entry::new();
+        game_id_entry.set_placeholder_text(Some("e.g., umu-starcitizen"));
+        if let Some(game_id) = &capsule.metadata.game_id {
+            game_id_entry.set_text(game_id);
+        }
+
+        let store_label = Label::new(Some("Store (optional)"));
+        store_label.set_halign(gtk4::Align::Start);
+        let store_entry = Entry::new();
+        store_entry.set_placeholder_text(Some("e.g., steam, gog, egs, none"));
+        if let Some(store) = &capsule.metadata.store {
+            store_entry.set_text(store);
+        }
+
+        let deps_title = Label::new(Some("Dependencies"));
+        deps_title.set_halign(gtk4::Align::Start);
+        deps_title.set_css_classes(&["section-title"]);
+        let deps_hint = Label::new(Some(
+            "Installers are downloaded when you first install dependencies for a game.",
+        ));
+        deps_hint.set_halign(gtk4::Align::Start);
+        deps_hint.set_wrap(true);
+        deps_hint.set_css_classes(&["muted"]);
+
+        let vcredist_check = CheckButton::with_label("Install VC++ Redistributables (AIO)");
+        vcredist_check.set_active(capsule.metadata.install_vcredist);
+        vcredist_check.set_tooltip_text(Some(
+            "Every runtime from 2005 to 2022 in one package. If it fails, turn it off and pick \
+             single versions under Visual C++ below.",
+        ));
+        let dxweb_check = CheckButton::with_label("Install DirectX (June 2010) Redist");
+        dxweb_check.set_active(capsule.metadata.install_dxweb);
+
+        // Everything else in the component catalog, grouped by category.
+        let components_box = Box::new(Orientation::Vertical, 4);
+        let in_prefix = Redistributable::present_in(&capsule.home_path.join("prefix"));
+        let mut redist_checks = Vec::new();
+        for category in Category::ALL {
+            let entries: Vec<_> = Redistributable::optional()
+                .into_iter()
+                .filter(|redist| redist.category == category)
+                .collect();
+            if entries.is_empty() {
+                continue;
+            }
+            let category_label = Label::new(Some(category.label()));
+            category_label.set_halign(gtk4::Align::Start);
+            components_box.append(&category_label);
+            for redist in entries {
+                let label = if in_prefix.contains(&redist) {
+                    format!("{} (in prefix)", redist.label())
+                } else {
+                    redist.label().to_string()
+                };
+                let check = CheckButton::with_label(&label);
+                check.set_tooltip_text(Some(redist.description));
+                check.set_active(
+                    capsule
+                        .metadata
+                        .install_redistributables
+                        .iter()
+                        .any(|id| id == redist.id()),
+                );
+                components_box.append(&check);
+                redist_checks.push((redist, check));
+            }
+        }
+        let redist_checks = Rc::new(redist_checks);
+
+        let install_deps_button = Button::with_label("Install dependencies...");
+        install_deps_button.add_css_class("suggested-action");
+
+        let snapshots_button = Button::with_label("Prefix snapshots...");
+        snapshots_button.set_halign(gtk4::Align::Start);
+
+        let winetricks_title = Label::new(Some("Winetricks"));
+        winetricks_title.set_halign(gtk4::Align::Start);
+        winetricks_title.set_css_classes(&["section-title"]);
+        let winetricks_text = if capsule.metadata.winetricks_verbs.is_empty() {
+            "No verbs installed yet.".to_string()
+        } else {
+            format!("Installed: {}", capsule.metadata.winetricks_verbs.join(", "))
+        };
+        let winetricks_label = Label::new(Some(&winetricks_text));
+        winetricks_label.set_halign(gtk4::Align::Start);
+        winetricks_label.set_wrap(true);
+        winetricks_label.set_css_classes(&["muted"]);
+        let winetricks_button = Button::with_label("Browse verbs...");
+        winetricks_button.set_halign(gtk4::Align::Start);
+
+        let integrity_title = Label::new(Some("File Integrity"));
+        integrity_title.set_halign(gtk4::Align::Start);
+        integrity_title.set_css_classes(&["section-title"]);
+        let game_folder = capsule.game_folder();
+        let manifest = game_folder.as_ref().and_then(|folder| {
+            IntegrityChecker::new(&capsule, folder)
+                .load_manifest()
+                .unwrap_or_else(|e| {
+                    eprintln!("Failed to load file manifest: {}", e);
+                    None
+                })
+        });
+        let integrity_text = match (&game_folder, &manifest) {
+            (None, _) => "Select the game executable to record a file manifest.".to_string(),
+            (Some(_), None) => "No file manifest recorded.".to_string(),
+            (Some(_), Some(manifest)) => format!(
+                "Manifest of {} files recorded {}.",
+                manifest.files.len(),
+                chrono::DateTime::parse_from_rfc3339(&manifest.created_at)
+                    .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|_| manifest.created_at.clone())
+            ),
+        };
+        let integrity_label = Label::new(Some(&integrity_text));
+        integrity_label.set_halign(gtk4::Align::Start);
+        integrity_label.set_wrap(true);
+        integrity_label.set_css_classes(&["muted"]);
+        let integrity_row = Box::new(Orientation::Horizontal, 8);
+        let record_manifest_button = Button::with_label("Record file manifest");
+        record_manifest_button.set_sensitive(game_folder.is_some());
+        let verify_files_button = Button::with_label("Verify files");
+        verify_files_button.set_sensitive(manifest.is_some());
+        integrity_row.append(&record_manifest_button);
+        integrity_row.append(&verify_files_button);
+
+        let saves_title = Label::new(Some("Saves"));
+        saves_title.set_halign(gtk4::Align::Start);
+        saves_title.set_css_classes(&["section-title"]);
+
+        let prefix_path = capsule.home_path.join("prefix");
+        let save_locations = SaveManager::detect(&capsule);
+        let saves_text = if save_locations.is_empty() {
+            "No save folders detected.".to_string()
+        } else {
+            save_locations
+                .iter()
+                .map(|location| {
+                    let origin = match location.source {
+                        SaveSource::Rule => "known location",
+                        SaveSource::Detected => "detected",
+                    };
+                    format!(
+                        "{} ({}, {})",
+                        location.display_path(&prefix_path),
+                        file_ops::format_bytes(location.size),
+                        origin
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let saves_label = Label::new(Some(&saves_text));
+        saves_label.set_halign(gtk4::Align::Start);
+        saves_label.set_wrap(true);
+        saves_label.set_selectable(true);
+        saves_label.set_css_classes(&["muted"]);
+
+        let saves_row = Box::new(Orientation::Horizontal, 8);
+        let export_saves_button = Button::with_label("Export saves...");
+        export_saves_button.set_sensitive(!save_locations.is_empty());
+        let import_saves_button = Button::with_label("Import saves...");
+        let sync_saves_button = Button::with_label("Sync now");
+        let cloud_configured = self.cloud_sync_config.is_configured();
+        sync_saves_button.set_sensitive(cloud_configured);
+        saves_row.append(&export_saves_button);
+        saves_row.append(&import_saves_button);
+        saves_row.append(&sync_saves_button);
+
+        let cloud_sync_check = CheckButton::with_label("Sync saves to the cloud when the game exits");
+        cloud_sync_check.set_active(capsule.metadata.cloud_sync);
+        if !cloud_configured {
+            cloud_sync_check.set_tooltip_text(Some("Set up a remote under Cloud Sync first"));
+        }
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        sync_saves_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SyncSaves {
+                capsule_dir: capsule_dir_clone.clone(),
+                direction: SyncDirection::Auto,
+            });
+        });
+
+        let root_window = self.root_window.clone();
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let default_export_name = format!(
+            "{}-saves-{}.tar.zst",
+            capsule.name,
+            chrono::Local::now().format("%Y%m%d")
+        );
+        export_saves_button.connect_clicked(move |_| {
+            let dialog = FileChooserNative::builder()
+                .title("Export Saves")
+                .action(FileChooserAction::Save)
+                .accept_label("Export")
+                .cancel_label("Cancel")
+                .transient_for(&root_window)
+                .build();
+            dialog.set_current_name(&default_export_name);
+
+            let sender_inner = sender_clone.clone();
+            let capsule_dir_inner = capsule_dir_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        sender_inner.input(MainWindowMsg::ExportSaves {
+                            capsule_dir: capsule_dir_inner.clone(),
+                            dest: path,
+                        });
+                    }
+                }
+                dialog.destroy();
+            });
+
+            dialog.show();
+        });
+
+        let root_window = self.root_window.clone();
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        import_saves_button.connect_clicked(move |_| {
+            let dialog = FileChooserNative::builder()
+                .title("Import Saves")
+                .action(FileChooserAction::Open)
+                .accept_label("Import")
+                .cancel_label("Cancel")
+                .transient_for(&root_window)
+                .build();
+
+            let filter = FileFilter::new();
+            filter.add_pattern("*.tar.zst");
+            filter.set_name(Some("Save archives (.tar.zst)"));
+            dialog.add_filter(&filter);
+
+            let sender_inner = sender_clone.clone();
+            let capsule_dir_inner = capsule_dir_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        sender_inner.input(MainWindowMsg::ImportSaves {
+                            capsule_dir: capsule_dir_inner.clone(),
+                            archive: path,
+                        });
+                    }
+                }
+                dialog.destroy();
+            });
+
+            dialog.show();
+        });
+
+        let input_title = Label::new(Some("Input & UI"));
+        input_title.set_halign(gtk4::Align::Start);
+        input_title.set_css_classes(&["section-title"]);
+
+        let xalia_check = CheckButton::with_label("Enable Xalia controller UI layer (may disable mouse)");
+        xalia_check.set_active(capsule.metadata.xalia_enabled);
+
+        let performance_title = Label::new(Some("Performance"));
+        performance_title.set_halign(gtk4::Align::Start);
+        performance_title.set_css_classes(&["section-title"]);
+
+        let gamemode_check = CheckButton::with_label("Run with GameMode");
+        gamemode_check.set_active(capsule.metadata.gamemode_enabled);
+        let mangohud_check = CheckButton::with_label("Show MangoHud overlay");
+        mangohud_check.set_active(capsule.metadata.mangohud_enabled);
+        for (check, tool) in [
+            (&gamemode_check, GamingTool::GameMode),
+            (&mangohud_check, GamingTool::MangoHud),
+        ] {
+            if !self.system_check.tool_installed(tool) {
+                // Still allow turning it off if it was enabled before.
+                check.set_sensitive(check.is_active());
+                check.set_tooltip_text(Some(&format!(
+                    "Install {} from System Setup first",
+                    tool.label()
+                )));
+            }
+        }
+
+        let fps_row = Box::new(Orientation::Horizontal, 8);
+        let fps_label = Label::new(Some("FPS cap"));
+        let fps_spin = SpinButton::with_range(0.0, 1000.0, 1.0);
+        fps_spin.set_value(capsule.metadata.fps_limit.unwrap_or(0) as f64);
+        fps_spin.set_tooltip_text(Some(
+            "0 means no cap. Uses MangoHud's limiter when its overlay is on, \
+             DXVK's otherwise (Direct3D 9 to 11 only).",
+        ));
+        let fps_hint = Label::new(Some("0 = no limit"));
+        fps_hint.set_css_classes(&["muted"]);
+        fps_row.append(&fps_label);
+        fps_row.append(&fps_spin);
+        fps_row.append(&fps_hint);
+
+        let gamescope = &self.system_check.gamescope;
+        let session = self.system_check.display_session;
+        let gamescope_check = CheckButton::with_label("Run inside gamescope");
+        gamescope_check.set_active(capsule.metadata.gamescope_enabled);
+        if !gamescope.installed {
+            gamescope_check.set_sensitive(gamescope_check.is_active());
+            gamescope_check.set_tooltip_text(Some("Install gamescope to use it"));
+        }
+        let hdr_check = CheckButton::with_label("HDR");
+        hdr_check.set_active(capsule.metadata.gamescope_hdr);
+        hdr_check.set_margin_start(24);
+        let vrr_check = CheckButton::with_label("Adaptive sync (VRR)");
+        vrr_check.set_active(capsule.metadata.gamescope_vrr);
+        vrr_check.set_margin_start(24);
+        let gamescope_options = [
+            (hdr_check.clone(), gamescope.hdr_unavailable_reason(session)),
+            (vrr_check.clone(), gamescope.vrr_unavailable_reason()),
+        ];
+        for (check, reason) in &gamescope_options {
+            if let Some(reason) = reason {
+                check.set_tooltip_text(Some(reason));
+            }
+        }
+        let update_gamescope_options = move |enabled: bool| {
+            for (check, reason) in &gamescope_options {
+                // Still allow turning it off if it was enabled before.
+                check.set_sensitive(enabled && (reason.is_none() || check.is_active()));
+            }
+        };
+        update_gamescope_options(gamescope_check.is_active());
+
+        let screenshots_label = Label::new(Some("Screenshots folder (optional)"));
+        screenshots_label.set_halign(gtk4::Align::Start);
+        let screenshots_row = Box::new(Orientation::Horizontal, 8);
+        screenshots_row.set_hexpand(true);
+        let screenshots_entry = Entry::new();
+        screenshots_entry.set_hexpand(true);
+        let default_screenshots = capsule.home_path.join("screenshots");
+        screenshots_entry.set_placeholder_text(Some(&default_screenshots.to_string_lossy()));
+        if let Some(dir) = &capsule.metadata.screenshots_dir {
+            screenshots_entry.set_text(dir);
+        }
+        let screenshots_entry_clone = screenshots_entry.clone();
+        let root_window = self.root_window.clone();
+        let screenshots_button = Button::with_label("Browse");
+        screenshots_button.connect_clicked(move |_| {
+            let dialog = FileChooserNative::builder()
+                .title("Select Screenshots Folder")
+                .action(FileChooserAction::SelectFolder)
+                .accept_label("Select")
+                .cancel_label("Cancel")
+                .transient_for(&root_window)
+                .build();
+
+            let screenshots_entry_inner = screenshots_entry_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        screenshots_entry_inner.set_text(&path.to_string_lossy());
+                    }
+                }
+                dialog.destroy();
+            });
+
+            dialog.show();
+        });
+        screenshots_row.append(&screenshots_entry);
+        screenshots_row.append(&screenshots_button);
+        let screenshots_hint = Label::new(Some(&format!(
+            "Inside gamescope, press {} to take a screenshot; LinuxBoy moves them here when \
+             the game exits.",
+            GAMESCOPE_SCREENSHOT_KEYS
+        )));
+        screenshots_hint.set_halign(gtk4::Align::Start);
+        screenshots_hint.set_wrap(true);
+        screenshots_hint.set_css_classes(&["muted"]);
+        gamescope_check.connect_toggled(move |check| update_gamescope_options(check.is_active()));
+
+        let registry_layout = Box::new(Orientation::Vertical, 8);
+        registry_layout.set_margin_all(12);
+
+        let registry_title = Label::new(Some("Registry"));
+        registry_title.set_halign(gtk4::Align::Start);
+        registry_title.set_css_classes(&["section-title"]);
+        let registry_hint = Label::new(Some(
+            "Changes are written to the prefix with regedit when you save.",
+        ));
+        registry_hint.set_halign(gtk4::Align::Start);
+        registry_hint.set_wrap(true);
+        registry_hint.set_css_classes(&["muted"]);
+
+        let registry_tweaks = PrefixRegistry::new(&prefix_path).read_tweaks();
+
+        let registry_dll_label = Label::new(Some("DLL overrides (HKCU\\Software\\Wine\\DllOverrides)"));
+        registry_dll_label.set_halign(gtk4::Align::Start);
+        let registry_dll_entry = Entry::new();
+        registry_dll_entry.set_placeholder_text(Some("d3d9=native,builtin dinput8=native"));
+        registry_dll_entry.set_text(
+            &registry_tweaks
+                .dll_overrides
+                .iter()
+                .map(|(dll, mode)| format!("{}={}", dll, mode))
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+
+        let import_reg_button = Button::with_label("Import .reg file...");
+        import_reg_button.set_halign(gtk4::Align::Start);
+
+        registry_layout.append(&registry_title);
+        registry_layout.append(&registry_hint);
+        registry_layout.append(&registry_dll_label);
+        registry_layout.append(&registry_dll_entry);
+        registry_layout.append(&import_reg_button);
+
+        let root_window = self.root_window.clone();
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        import_reg_button.connect_clicked(move |_| {
+            let dialog = FileChooserNative::builder()
+                .title("Import Registry File")
+                .action(FileChooserAction::Open)
+                .accept_label("Import")
+                .cancel_label("Cancel")
+                .transient_for(&root_window)
+                .build();
+
+            let filter = FileFilter::new();
+            filter.add_suffix("reg");
+            filter.set_name(Some("Registry files (.reg)"));
+            dialog.add_filter(&filter);
+
+            let sender_inner = sender_clone.clone();
+            let capsule_dir_inner = capsule_dir_clone.clone();
+            dialog.connect_response(move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        sender_inner.input(MainWindowMsg::ImportRegistryFile {
+                            capsule_dir: capsule_dir_inner.clone(),
+                            path,
+                        });
+                    }
+                }
+                dialog.destroy();
+            });
+
+            dialog.show();
+        });
+
+        let display_title = Label::new(Some("Display"));
+        display_title.set_halign(gtk4::Align::Start);
+        display_title.set_css_classes(&["section-title"]);
+        let desktop_row = Box::new(Orientation::Horizontal, 8);
+        let desktop_check = CheckButton::with_label("Run in a virtual desktop");
+        desktop_check.set_active(capsule.metadata.virtual_desktop.is_some());
+        let desktop_dropdown = Self::virtual_desktop_dropdown(
+            capsule.metadata.virtual_desktop.as_deref(),
+            self.deck_settings
+                .default_desktop_size(&self.steam_deck)
+                .unwrap_or("1280x720"),
+        );
+        desktop_dropdown.set_sensitive(desktop_check.is_active());
+        let desktop_dropdown_clone = desktop_dropdown.clone();
+        desktop_check.connect_toggled(move |check| {
+            desktop_dropdown_clone.set_sensitive(check.is_active());
+        });
+        desktop_row.append(&desktop_check);
+        desktop_row.append(&desktop_dropdown);
+
+        let sandbox_title = Label::new(Some("Sandbox & Network"));
+        sandbox_title.set_halign(gtk4::Align::Start);
+        sandbox_title.set_css_classes(&["section-title"]);
+        let sandbox_hint = Label::new(Some(
+            "Runs the game with bubblewrap. It sees this capsule, the system and the Proton \
+             runtime, but not the rest of your home folder.",
+        ));
+        sandbox_hint.set_halign(gtk4::Align::Start);
+        sandbox_hint.set_wrap(true);
+        sandbox_hint.set_css_classes(&["muted"]);
+        let sandbox_check = CheckButton::with_label("Run in a sandbox");
+        sandbox_check.set_active(capsule.metadata.sandbox_enabled);
+        if !self.system_check.bwrap_installed {
+            sandbox_check.set_sensitive(sandbox_check.is_active());
+            sandbox_check.set_tooltip_text(Some("Install bubblewrap (bwrap) to use it"));
+        }
+        let sandbox_network_check = CheckButton::with_label("Allow network access");
+        sandbox_network_check.set_active(capsule.metadata.sandbox_network);
+        sandbox_network_check.set_margin_start(24);
+        let block_network_check = CheckButton::with_label("Block network access");
+        block_network_check.set_active(capsule.metadata.block_network);
+        block_network_check.set_tooltip_text(Some(
+            "For single-player games that phone home or show launcher ads. Works without \
+             the sandbox and also needs bubblewrap.",
+        ));
+        if !self.system_check.bwrap_installed {
+            block_network_check.set_sensitive(block_network_check.is_active());
+        }
+        sandbox_network_check
+            .set_sensitive(sandbox_check.is_active() && !block_network_check.is_active());
+        let update_sandbox_network = {
+            let sandbox_check = sandbox_check.clone();
+            let sandbox_network_check = sandbox_network_check.clone();
+            let block_network_check = block_network_check.clone();
+            move || {
+                sandbox_network_check.set_sensitive(
+                    sandbox_check.is_active() && !block_network_check.is_active(),
+                );
+            }
+        };
+        let update_clone = update_sandbox_network.clone();
+        sandbox_check.connect_toggled(move |_| update_clone());
+        block_network_check.connect_toggled(move |_| update_sandbox_network());
+
+        let session_label = Label::new(Some(&format!(
+            "Session: {}",
+            self.system_check.display_session.label()
+        )));
+        session_label.set_halign(gtk4::Align::Start);
+        session_label.set_css_classes(&["muted"]);
+        let wayland_check = CheckButton::with_label("Use Proton's Wayland driver (PROTON_ENABLE_WAYLAND)");
+        wayland_check.set_active(capsule.metadata.proton_wayland);
+        let force_x11_check = CheckButton::with_label("Force X11 through XWayland");
+        force_x11_check.set_active(capsule.metadata.force_x11);
+        wayland_check.set_sensitive(!force_x11_check.is_active());
+        let wayland_check_clone = wayland_check.clone();
+        force_x11_check.connect_toggled(move |check| {
+            wayland_check_clone.set_sensitive(!check.is_active());
+        });
+        let sdl_row = Box::new(Orientation::Horizontal, 8);
+        let sdl_label = Label::new(Some("SDL video driver"));
+        let sdl_dropdown = Self::sdl_driver_dropdown(capsule.metadata.sdl_video_driver.as_deref());
+        sdl_row.append(&sdl_label);
+        sdl_row.append(&sdl_dropdown);
+        let display_warning = Label::new(None);
+        display_warning.set_halign(gtk4::Align::Start);
+        display_warning.set_wrap(true);
+        if let Some(warning) = self
+            .system_check
+            .display_session
+            .launch_warning(&capsule.metadata)
+        {
+            display_warning.set_text(&warning);
+        } else {
+            display_warning.set_visible(false);
+        }
+
+        let locale_title = Label::new(Some("Locale & Time Zone"));
+        locale_title.set_halign(gtk4::Align::Start);
+        locale_title.set_css_classes(&["section-title"]);
+        let locale_hint = Label::new(Some("Leave empty to use the system settings."));
+        locale_hint.set_halign(gtk4::Align::Start);
+        locale_hint.set_css_classes(&["muted"]);
+        let locale_label = Label::new(Some("Locale (LANG)"));
+        locale_label.set_halign(gtk4::Align::Start);
+        let locale_entry = Entry::new();
+        locale_entry.set_placeholder_text(Some("e.g., ja_JP.UTF-8"));
+        locale_entry.set_text(capsule.metadata.locale.as_deref().unwrap_or_default());
+        let lc_all_label = Label::new(Some("LC_ALL"));
+        lc_all_label.set_halign(gtk4::Align::Start);
+        let lc_all_entry = Entry::new();
+        lc_all_entry.set_placeholder_text(Some("e.g., ja_JP.UTF-8"));
+        lc_all_entry.set_text(capsule.metadata.lc_all.as_deref().unwrap_or_default());
+        let timezone_label = Label::new(Some("Time zone (TZ)"));
+        timezone_label.set_halign(gtk4::Align::Start);
+        let timezone_entry = Entry::new();
+        timezone_entry.set_placeholder_text(Some("e.g., Asia/Tokyo"));
+        timezone_entry.set_text(capsule.metadata.timezone.as_deref().unwrap_or_default());
+
+        let pf_title = Label::new(Some("Protonfixes Overrides"));
+        pf_title.set_halign(gtk4::Align::Start);
+        pf_title.set_css_classes(&["section-title"]);
+
+        let pf_disable = CheckButton::with_label("Disable Protonfixes for this game");
+        pf_disable.set_active(capsule.metadata.protonfixes_disable);
+
+        let pf_replace_label = Label::new(Some("Command replacements"));
+        pf_replace_label.set_halign(gtk4::Align::Start);
+        let pf_replace_entry = Entry::new();
+        pf_replace_entry.set_placeholder_text(Some("/launcher.exe=/game.exe"));
+        if !capsule.metadata.protonfixes_replace_cmds.is_empty() {
+            pf_replace_entry.set_text(&capsule.metadata.protonfixes_replace_cmds.join(" "));
+        }
+
+        let pf_dxvk_label = Label::new(Some("DXVK options"));
+        pf_dxvk_label.set_halign(gtk4::Align::Start);
+        let pf_dxvk_entry = Entry::new();
+        pf_dxvk_entry.set_placeholder_text(Some("dxgi.maxFrameRate=60"));
+        if !capsule.metadata.protonfixes_dxvk_sets.is_empty() {
+            pf_dxvk_entry.set_text(&capsule.metadata.protonfixes_dxvk_sets.join(" "));
+        }
+
+        let local_fix = capsule
+            .metadata
+            .game_id
+            .as_deref()
+            .and_then(LocalFix::for_game);
+        let local_fix_button = Button::with_label(
+            if local_fix.as_ref().map(LocalFix::exists).unwrap_or(false) {
+                "Edit local fix..."
+            } else {
+                "Write local fix..."
+            },
+        );
+        local_fix_button.set_halign(gtk4::Align::Start);
+        local_fix_button.set_sensitive(local_fix.is_some());
+        local_fix_button.set_tooltip_text(Some(if local_fix.is_some() {
+            "Your own protonfixes module for this game, run at every launch"
+        } else {
+            "Set and save a GAMEID first; local fixes are named after it"
+        }));
+
+        let command_title = Label::new(Some("Custom Launch Command"));
+        command_title.set_halign(gtk4::Align::Start);
+        command_title.set_css_classes(&["section-title"]);
+        let command_hint = Label::new(Some(&format!(
+            "Replaces how LinuxBoy runs the game; leave empty for the built-in runner. \
+             Placeholders: {}. The game's environment is still set, but the virtual \
+             desktop and prefix preparation are left to the command.",
+            CommandTemplate::PLACEHOLDERS.join(", ")
+        )));
+        command_hint.set_halign(gtk4::Align::Start);
+        command_hint.set_wrap(true);
+        command_hint.set_css_classes(&["muted"]);
+        let command_entry = Entry::new();
+        command_entry.set_placeholder_text(Some("e.g., {proton}/proton run {exe} {args}"));
+        command_entry.set_text(capsule.metadata.command_template.as_deref().unwrap_or_default());
+
+        let debug_title = Label::new(Some("Wine Debugging"));
+        debug_title.set_halign(gtk4::Align::Start);
+        debug_title.set_css_classes(&["section-title"]);
+        let debug_hint = Label::new(Some(
+            "Sets WINEDEBUG when the game starts. The output goes to last_launch.log in the \
+             capsule home, which can grow quickly with busy channels.",
+        ));
+        debug_hint.set_halign(gtk4::Align::Start);
+        debug_hint.set_wrap(true);
+        debug_hint.set_css_classes(&["muted"]);
+        let current_channels: Vec<&str> = capsule
+            .metadata
+            .wine_debug()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|channel| !channel.is_empty())
+            .collect();
+        let debug_grid = Grid::new();
+        debug_grid.set_column_spacing(12);
+        let debug_checks: Vec<(&'static str, CheckButton)> = WINEDEBUG_CHANNELS
+            .iter()
+            .enumerate()
+            .map(|(index, (channel, description))| {
+                let check = CheckButton::with_label(channel);
+                check.set_tooltip_text(Some(*description));
+                check.set_active(current_channels.contains(channel));
+                debug_grid.attach(&check, (index % 2) as i32, (index / 2) as i32, 1, 1);
+                (*channel, check)
+            })
+            .collect();
+        let debug_label = Label::new(Some("Other channels (comma-separated)"));
+        debug_label.set_halign(gtk4::Align::Start);
+        let debug_entry = Entry::new();
+        debug_entry.set_placeholder_text(Some("e.g., +d3d,+dinput"));
+        debug_entry.set_text(
+            &current_channels
+                .iter()
+                .filter(|channel| !WINEDEBUG_CHANNELS.iter().any(|(known, _)| known == *channel))
+                .copied()
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        let debug_once_check = CheckButton::with_label("Only for the next launch");
+        debug_once_check.set_active(capsule.metadata.wine_debug_once);
+
+        let template_title = Label::new(Some("Template"));
+        template_title.set_halign(gtk4::Align::Start);
+        template_title.set_css_classes(&["section-title"]);
+        let template_hint = Label::new(Some(
+            "Templates hold launch settings: environment, DLL overrides, protonfixes, tools, \
+             display and locale. Saving one uses this game's saved settings; applying one \
+             replaces them.",
+        ));
+        template_hint.set_halign(gtk4::Align::Start);
+        template_hint.set_wrap(true);
+        template_hint.set_css_classes(&["muted"]);
+        let template_names = self.templates.names();
+        let template_refs: Vec<&str> = template_names.iter().map(String::as_str).collect();
+        let template_dropdown = DropDown::from_strings(&template_refs);
+        template_dropdown.set_hexpand(true);
+        template_dropdown.set_sensitive(!template_names.is_empty());
+        let apply_template_button = Button::with_label("Apply");
+        apply_template_button.set_sensitive(!template_names.is_empty());
+        let save_template_button = Button::with_label("Save as Template...");
+        let template_row = Box::new(Orientation::Horizontal, 8);
+        template_row.append(&template_dropdown);
+        template_row.append(&apply_template_button);
+        template_row.append(&save_template_button);
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        apply_template_button.connect_clicked(move |_| {
+            let name = match template_names.get(template_dropdown.selected() as usize) {
+                Some(name) => name.clone(),
+                None => return,
+            };
+            // Reopen the settings so they show what the template changed.
+            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
+            sender_clone.input(MainWindowMsg::ApplyTemplate {
+                capsule_dir: capsule_dir_clone.clone(),
+                name,
+            });
+            sender_clone.input(MainWindowMsg::EditGame(capsule_dir_clone.clone()));
+            dialog_clone.close();
+        });
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        save_template_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SaveTemplatePrompt(capsule_dir_clone.clone()));
+        });
+
+        layout.append(&exe_label);
+        layout.append(&exe_row);
+        layout.append(&working_dir_label);
+        layout.append(&working_dir_row);
+        layout.append(&game_id_label);
+        layout.append(&game_id_entry);
+        layout.append(&store_label);
+        layout.append(&store_entry);
+        layout.append(&deps_title);
+        layout.append(&deps_hint);
+        layout.append(&vcredist_check);
+        layout.append(&dxweb_check);
+        layout.append(&components_box);
+        layout.append(&install_deps_button);
+        layout.append(&snapshots_button);
+        layout.append(&winetricks_title);
+        layout.append(&winetricks_label);
+        layout.append(&winetricks_button);
+        layout.append(&integrity_title);
+        layout.append(&integrity_label);
+        layout.append(&integrity_row);
+        layout.append(&saves_title);
+        layout.append(&saves_label);
+        layout.append(&saves_row);
+        layout.append(&cloud_sync_check);
+        layout.append(&input_title);
+        layout.append(&xalia_check);
+        layout.append(&performance_title);
+        layout.append(&gamemode_check);
+        layout.append(&mangohud_check);
+        layout.append(&fps_row);
+        layout.append(&gamescope_check);
+        layout.append(&hdr_check);
+        layout.append(&vrr_check);
+        layout.append(&screenshots_label);
+        layout.append(&screenshots_row);
+        layout.append(&screenshots_hint);
+        layout.append(&sandbox_title);
+        layout.append(&sandbox_hint);
+        layout.append(&sandbox_check);
+        layout.append(&sandbox_network_check);
+        layout.append(&block_network_check);
+        layout.append(&display_title);
+        layout.append(&desktop_row);
+        layout.append(&session_label);
+        layout.append(&wayland_check);
+        layout.append(&force_x11_check);
+        layout.append(&sdl_row);
+        layout.append(&display_warning);
+        layout.append(&locale_title);
+        layout.append(&locale_hint);
+        layout.append(&locale_label);
+        layout.append(&locale_entry);
+        layout.append(&lc_all_label);
+        layout.append(&lc_all_entry);
+        layout.append(&timezone_label);
+        layout.append(&timezone_entry);
+        layout.append(&pf_title);
+        layout.append(&pf_disable);
+        layout.append(&pf_replace_label);
+        layout.append(&pf_replace_entry);
+        layout.append(&pf_dxvk_label);
+        layout.append(&pf_dxvk_entry);
+        layout.append(&local_fix_button);
+        layout.append(&command_title);
+        layout.append(&command_hint);
+        layout.append(&command_entry);
+        layout.append(&debug_title);
+        layout.append(&debug_hint);
+        layout.append(&debug_grid);
+        layout.append(&debug_label);
+        layout.append(&debug_entry);
+        layout.append(&debug_once_check);
+        layout.append(&template_title);
+        layout.append(&template_hint);
+        layout.append(&template_row);
+
+        let dll_layout = Box::new(Orientation::Vertical, 8);
+        dll_layout.set_margin_all(12);
+        let dll_title = Label::new(Some("DLL Overrides"));
+        dll_title.set_halign(gtk4::Align::Start);
+        dll_title.set_css_classes(&["section-title"]);
+        let dll_hint = Label::new(Some(
+            "Set through WINEDLLOVERRIDES when the game starts. Use the DLL name without .dll.",
+        ));
+        dll_hint.set_halign(gtk4::Align::Start);
+        dll_hint.set_wrap(true);
+        dll_hint.set_css_classes(&["muted"]);
+        let dll_rows_box = Box::new(Orientation::Vertical, 6);
+        let dll_rows: Rc<RefCell<Vec<DllOverrideRow>>> = Rc::new(RefCell::new(Vec::new()));
+        for entry in &capsule.metadata.dll_overrides {
+            Self::append_dll_override_row(&dll_rows_box, &dll_rows, Some(entry));
+        }
+        let add_dll_button = Button::with_label("Add override");
+        add_dll_button.set_halign(gtk4::Align::Start);
+        let dll_rows_box_clone = dll_rows_box.clone();
+        let dll_rows_clone = dll_rows.clone();
+        add_dll_button.connect_clicked(move |_| {
+            Self::append_dll_override_row(&dll_rows_box_clone, &dll_rows_clone, None);
+        });
+        dll_layout.append(&dll_title);
+        dll_layout.append(&dll_hint);
+        dll_layout.append(&dll_rows_box);
+        dll_layout.append(&add_dll_button);
+
+        let notebook = Notebook::new();
+        notebook.append_page(&layout, Some(&Label::new(Some("General"))));
+        notebook.append_page(&dll_layout, Some(&Label::new(Some("DLL Overrides"))));
+        notebook.append_page(&registry_layout, Some(&Label::new(Some("Registry"))));
+        content.append(&notebook);
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let exe_entry_clone = exe_entry.clone();
+        let working_dir_entry_clone = working_dir_entry.clone();
+        let game_id_entry_clone = game_id_entry.clone();
+        let store_entry_clone = store_entry.clone();
+        let vcredist_check_clone = vcredist_check.clone();
+        let dxweb_check_clone = dxweb_check.clone();
+        let redist_checks_clone = redist_checks.clone();
+        let xalia_check_clone = xalia_check.clone();
+        let gamemode_check_clone = gamemode_check.clone();
+        let mangohud_check_clone = mangohud_check.clone();
+        let fps_spin_clone = fps_spin.clone();
+        let gamescope_check_clone = gamescope_check.clone();
+        let hdr_check_clone = hdr_check.clone();
+        let vrr_check_clone = vrr_check.clone();
+        let screenshots_entry_clone = screenshots_entry.clone();
+        let wayland_check_clone = wayland_check.clone();
+        let force_x11_check_clone = force_x11_check.clone();
+        let sdl_dropdown_clone = sdl_dropdown.clone();
+        let sandbox_check_clone = sandbox_check.clone();
+        let sandbox_network_check_clone = sandbox_network_check.clone();
+        let block_network_check_clone = block_network_check.clone();
+        let command_entry_clone = command_entry.clone();
+        let debug_checks_clone = debug_checks.clone();
+        let debug_entry_clone = debug_entry.clone();
+        let debug_once_check_clone = debug_once_check.clone();
+        let pf_disable_clone = pf_disable.clone();
+        let pf_replace_entry_clone = pf_replace_entry.clone();
+        let pf_dxvk_entry_clone = pf_dxvk_entry.clone();
+        let cloud_sync_check_clone = cloud_sync_check.clone();
+        let dll_rows_clone = dll_rows.clone();
+        let desktop_check_clone = desktop_check.clone();
+        let desktop_dropdown_clone = desktop_dropdown.clone();
+        let locale_entry_clone = locale_entry.clone();
+        let lc_all_entry_clone = lc_all_entry.clone();
+        let timezone_entry_clone = timezone_entry.clone();
+        let registry_dll_entry_clone = registry_dll_entry.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                let exe_path = exe_entry_clone.text().to_string();
+                let game_id_text = game_id_entry_clone.text().trim().to_string();
+                let store_text = store_entry_clone.text().trim().to_string();
+                let install_vcredist = vcredist_check_clone.is_active();
+                let install_dxweb = dxweb_check_clone.is_active();
+                let install_redistributables = MainWindow::optional_redistributable_ids(
+                    &MainWindow::selected_redistributables(&redist_checks_clone),
+                );
+                let protonfixes_disable = pf_disable_clone.is_active();
+                let xalia_enabled = xalia_check_clone.is_active();
+                let protonfixes_replace_cmds =
+                    MainWindow::parse_list_input(&pf_replace_entry_clone.text());
+                let protonfixes_dxvk_sets = MainWindow::parse_list_input(&pf_dxvk_entry_clone.text());
+                let game_id = if game_id_text.is_empty() {
+                    None
+                } else {
+                    Some(game_id_text)
+                };
+                let store = if store_text.is_empty() {
+                    None
+                } else {
+                    Some(store_text)
+                };
+                sender_clone.input(MainWindowMsg::SaveGameSettings {
+                    capsule_dir: capsule_dir_clone.clone(),
+                    exe_path,
+                    working_dir: MainWindow::optional_text(&working_dir_entry_clone),
+                    game_id,
+                    store,
+                    install_vcredist,
+                    install_dxweb,
+                    install_redistributables,
+                    protonfixes_disable,
+                    xalia_enabled,
+                    gamemode_enabled: gamemode_check_clone.is_active(),
+                    mangohud_enabled: mangohud_check_clone.is_active(),
+                    fps_limit: MainWindow::fps_limit_value(&fps_spin_clone),
+                    gamescope_enabled: gamescope_check_clone.is_active(),
+                    gamescope_hdr: hdr_check_clone.is_active(),
+                    gamescope_vrr: vrr_check_clone.is_active(),
+                    screenshots_dir: MainWindow::optional_text(&screenshots_entry_clone),
+                    proton_wayland: wayland_check_clone.is_active(),
+                    force_x11: force_x11_check_clone.is_active(),
+                    sdl_video_driver: MainWindow::selected_sdl_driver(&sdl_dropdown_clone),
+                    sandbox_enabled: sandbox_check_clone.is_active(),
+                    sandbox_network: sandbox_network_check_clone.is_active(),
+                    block_network: block_network_check_clone.is_active(),
+                    command_template: MainWindow::optional_text(&command_entry_clone),
+                    wine_debug: MainWindow::selected_wine_debug(
+                        &debug_checks_clone,
+                        &debug_entry_clone,
+                    ),
+                    wine_debug_once: debug_once_check_clone.is_active(),
+                    protonfixes_replace_cmds,
+                    protonfixes_dxvk_sets,
+                    dll_overrides: MainWindow::collect_dll_overrides(&dll_rows_clone.borrow()),
+                    virtual_desktop: MainWindow::selected_virtual_desktop(
+                        &desktop_check_clone,
+                        &desktop_dropdown_clone,
+                    ),
+                    locale: MainWindow::optional_text(&locale_entry_clone),
+                    lc_all: MainWindow::optional_text(&lc_all_entry_clone),
+                    timezone: MainWindow::optional_text(&timezone_entry_clone),
+                    cloud_sync: cloud_sync_check_clone.is_active(),
+                });
+
+                let desired = RegistryTweaks {
+                    dll_overrides: MainWindow::parse_list_input(&registry_dll_entry_clone.text())
+                        .into_iter()
+                        .filter_map(|item| {
+                            let (dll, mode) = item.split_once('=')?;
+                            Some((dll.trim().to_string(), mode.trim().to_string()))
+                        })
+                        .filter(|(dll, _)| !dll.is_empty())
+                        .collect(),
+                };
+                if desired != registry_tweaks {
+                    sender_clone.input(MainWindowMsg::ApplyRegistryTweaks {
+                        capsule_dir: capsule_dir_clone.clone(),
+                        current: registry_tweaks.clone(),
+                        desired,
+                    });
+                }
+            }
+
+            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
+            dialog.close();
+        });
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let exe_entry_clone = exe_entry.clone();
+        let working_dir_entry_clone = working_dir_entry.clone();
+        let game_id_entry_clone = game_id_entry.clone();
+        let store_entry_clone = store_entry.clone();
+        let vcredist_check_clone = vcredist_check.clone();
+        let dxweb_check_clone = dxweb_check.clone();
+        let redist_checks_clone = redist_checks.clone();
+        let xalia_check_clone = xalia_check.clone();
+        let gamemode_check_clone = gamemode_check.clone();
+        let mangohud_check_clone = mangohud_check.clone();
+        let fps_spin_clone = fps_spin.clone();
+        let gamescope_check_clone = gamescope_check.clone();
+        let hdr_check_clone = hdr_check.clone();
+        let vrr_check_clone = vrr_check.clone();
+        let screenshots_entry_clone = screenshots_entry.clone();
+        let wayland_check_clone = wayland_check.clone();
+        let force_x11_check_clone = force_x11_check.clone();
+        let sdl_dropdown_clone = sdl_dropdown.clone();
+        let sandbox_check_clone = sandbox_check.clone();
+        let sandbox_network_check_clone = sandbox_network_check.clone();
+        let block_network_check_clone = block_network_check.clone();
+        let command_entry_clone = command_entry.clone();
+        let debug_checks_clone = debug_checks.clone();
+        let debug_entry_clone = debug_entry.clone();
+        let debug_once_check_clone = debug_once_check.clone();
+        let pf_disable_clone = pf_disable.clone();
+        let pf_replace_entry_clone = pf_replace_entry.clone();
+        let pf_dxvk_entry_clone = pf_dxvk_entry.clone();
+        let cloud_sync_check_clone = cloud_sync_check.clone();
+        let dll_rows_clone = dll_rows.clone();
+        let desktop_check_clone = desktop_check.clone();
+        let desktop_dropdown_clone = desktop_dropdown.clone();
+        let locale_entry_clone = locale_entry.clone();
+        let lc_all_entry_clone = lc_all_entry.clone();
+        let timezone_entry_clone = timezone_entry.clone();
+        let dialog_clone = dialog.clone();
+        install_deps_button.connect_clicked(move |_| {
+            let exe_path = exe_entry_clone.text().to_string();
+            let game_id_text = game_id_entry_clone.text().trim().to_string();
+            let store_text = store_entry_clone.text().trim().to_string();
+            let install_vcredist = vcredist_check_clone.is_active();
+            let install_dxweb = dxweb_check_clone.is_active();
+            let install_redistributables = MainWindow::optional_redistributable_ids(
+                &MainWindow::selected_redistributables(&redist_checks_clone),
+            );
+            let protonfixes_disable = pf_disable_clone.is_active();
+            let xalia_enabled = xalia_check_clone.is_active();
+            let protonfixes_replace_cmds =
+                MainWindow::parse_list_input(&pf_replace_entry_clone.text());
+            let protonfixes_dxvk_sets = MainWindow::parse_list_input(&pf_dxvk_entry_clone.text());
+            let game_id = if game_id_text.is_empty() {
+                None
+            } else {
+                Some(game_id_text)
+            };
+            let store = if store_text.is_empty() {
+                None
+            } else {
+                Some(store_text)
+            };
+            sender_clone.input(MainWindowMsg::SaveGameSettings {
+                capsule_dir: capsule_dir_clone.clone(),
+                exe_path,
+                working_dir: MainWindow::optional_text(&working_dir_entry_clone),
+                game_id,
+                store,
+                install_vcredist,
+                install_dxweb,
+                install_redistributables,
+                protonfixes_disable,
+                xalia_enabled,
+                gamemode_enabled: gamemode_check_clone.is_active(),
+                mangohud_enabled: mangohud_check_clone.is_active(),
+                fps_limit: MainWindow::fps_limit_value(&fps_spin_clone),
+                gamescope_enabled: gamescope_check_clone.is_active(),
+                gamescope_hdr: hdr_check_clone.is_active(),
+                gamescope_vrr: vrr_check_clone.is_active(),
+                screenshots_dir: MainWindow::optional_text(&screenshots_entry_clone),
+                proton_wayland: wayland_check_clone.is_active(),
+                force_x11: force_x11_check_clone.is_active(),
+                sdl_video_driver: MainWindow::selected_sdl_driver(&sdl_dropdown_clone),
+                sandbox_enabled: sandbox_check_clone.is_active(),
+                sandbox_network: sandbox_network_check_clone.is_active(),
+                block_network: block_network_check_clone.is_active(),
+                command_template: MainWindow::optional_text(&command_entry_clone),
+                wine_debug: MainWindow::selected_wine_debug(
+                    &debug_checks_clone,
+                    &debug_entry_clone,
+                ),
+                wine_debug_once: debug_once_check_clone.is_active(),
+                protonfixes_replace_cmds,
+                protonfixes_dxvk_sets,
+                dll_overrides: MainWindow::collect_dll_overrides(&dll_rows_clone.borrow()),
+                virtual_desktop: MainWindow::selected_virtual_desktop(
+                    &desktop_check_clone,
+                    &desktop_dropdown_clone,
+                ),
+                locale: MainWindow::optional_text(&locale_entry_clone),
+                lc_all: MainWindow::optional_text(&lc_all_entry_clone),
+                timezone: MainWindow::optional_text(&timezone_entry_clone),
+                cloud_sync: cloud_sync_check_clone.is_active(),
+            });
+            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
+            sender_clone.input(MainWindowMsg::OpenDependencies(capsule_dir_clone.clone()));
+            dialog_clone.close();
+        });
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        local_fix_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
+            sender_clone.input(MainWindowMsg::OpenLocalFix(capsule_dir_clone.clone()));
+            dialog_clone.close();
+        });
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        snapshots_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
+            sender_clone.input(MainWindowMsg::OpenSnapshots(capsule_dir_clone.clone()));
+            dialog_clone.close();
+        });
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        winetricks_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
+            sender_clone.input(MainWindowMsg::OpenWinetricks(capsule_dir_clone.clone()));
+            dialog_clone.close();
+        });
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        record_manifest_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
+            sender_clone.input(MainWindowMsg::RecordFileManifest(capsule_dir_clone.clone()));
+            dialog_clone.close();
+        });
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        verify_files_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SettingsDialogClosed);
+            sender_clone.input(MainWindowMsg::VerifyGameFiles(capsule_dir_clone.clone()));
+            dialog_clone.close();
+        });
+
+        dialog.show();
+        self.settings_dialog = Some(dialog);
+    }
+
+    fn open_winetricks_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.winetricks_dialog.is_some() {
+            return;
+        }
+
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        let installed: HashSet<String> = capsule.metadata.winetricks_verbs.iter().cloned().collect();
+
+        let dialog = Dialog::builder()
+            .title("Winetricks")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(620);
+        dialog.set_default_height(560);
+        dialog.add_button("Close", ResponseType::Close);
+        dialog.add_button("Run selected", ResponseType::Accept);
+        dialog.set_response_sensitive(ResponseType::Accept, !self.is_capsule_busy(&capsule_dir));
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some(&format!("Winetricks for {}", capsule.name)));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let hint = Label::new(Some(
+            "Verbs run in this capsule's prefix using the Proton runtime. A snapshot is taken first.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+
+        let search_entry = SearchEntry::builder()
+            .placeholder_text("Search verbs")
+            .build();
+
+        let category_row = Box::new(Orientation::Horizontal, 8);
+        let all_check = CheckButton::with_label("All");
+        all_check.set_active(true);
+        category_row.append(&all_check);
+        let mut category_checks = vec![(None, all_check.clone())];
+        for category in VerbCategory::ALL {
+            let check = CheckButton::with_label(category.label());
+            check.set_group(Some(&all_check));
+            category_row.append(&check);
+            category_checks.push((Some(category), check));
+        }
+
+        let listbox = ListBox::new();
+        listbox.set_selection_mode(SelectionMode::None);
+        let mut verb_checks = Vec::new();
+        for verb in &self.winetricks_verbs {
+            let row_box = Box::new(Orientation::Vertical, 2);
+            row_box.set_margin_all(4);
+            let label = if installed.contains(&verb.name) {
+                format!("{} (installed)", verb.name)
+            } else {
+                verb.name.clone()
+            };
+            let check = CheckButton::with_label(&label);
+            let description = Label::new(Some(&format!(
+                "{} • {}",
+                verb.category.label(),
+                verb.description
+            )));
+            description.set_halign(gtk4::Align::Start);
+            description.set_wrap(true);
+            description.set_margin_start(28);
+            description.set_css_classes(&["muted"]);
+            row_box.append(&check);
+            row_box.append(&description);
+            let row = ListBoxRow::new();
+            row.set_child(Some(&row_box));
+            listbox.append(&row);
+            verb_checks.push(check);
+        }
+
+        let selected_category: Rc<Cell<Option<VerbCategory>>> = Rc::new(Cell::new(None));
+        let verbs = Rc::new(self.winetricks_verbs.clone());
+        let search_clone = search_entry.clone();
+        let category_clone = selected_category.clone();
+        let verbs_clone = verbs.clone();
+        listbox.set_filter_func(move |row| {
+            let verb = match usize::try_from(row.index()).ok().and_then(|index| verbs_clone.get(index)) {
+                Some(verb) => verb,
+                None => return true,
+            };
+            if let Some(category) = category_clone.get() {
+                if verb.category != category {
+                    return false;
+                }
+            }
+            let query = search_clone.text().trim().to_lowercase();
+            query.is_empty()
+                || verb.name.to_lowercase().contains(&query)
+                || verb.description.to_lowercase().contains(&query)
+        });
+
+        let listbox_clone = listbox.clone();
+        search_entry.connect_search_changed(move |_| {
+            listbox_clone.invalidate_filter();
+        });
+        for (category, check) in category_checks {
+            let listbox_clone = listbox.clone();
+            let category_clone = selected_category.clone();
+            check.connect_toggled(move |check| {
+                if check.is_active() {
+                    category_clone.set(category);
+                    listbox_clone.invalidate_filter();
+                }
+            });
+        }
+
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_child(Some(&listbox));
+
+        let log_label = Label::new(Some("Log"));
+        log_label.set_halign(gtk4::Align::Start);
+        let log_view = TextView::new();
+        log_view.set_editable(false);
+        log_view.set_cursor_visible(false);
+        log_view.set_monospace(true);
+        log_view.set_wrap_mode(WrapMode::WordChar);
+        let log_scroller = ScrolledWindow::new();
+        log_scroller.set_min_content_height(140);
+        log_scroller.set_child(Some(&log_view));
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&search_entry);
+        layout.append(&category_row);
+        layout.append(&scroller);
+        layout.append(&log_label);
+        layout.append(&log_scroller);
+        content.append(&layout);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                let selected: Vec<String> = verbs
+                    .iter()
+                    .zip(verb_checks.iter())
+                    .filter(|(_, check)| check.is_active())
+                    .map(|(verb, _)| verb.name.clone())
+                    .collect();
+                if !selected.is_empty() {
+                    sender_clone.input(MainWindowMsg::RunWinetricks {
+                        capsule_dir: capsule_dir.clone(),
+                        verbs: selected,
+                    });
+                }
+                return;
+            }
+            sender_clone.input(MainWindowMsg::WinetricksDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.winetricks_dialog = Some(dialog);
+        self.winetricks_log = Some(log_view);
+    }
+
+    fn append_winetricks_log(&self, line: &str) {
+        if let Some(view) = &self.winetricks_log {
+            let buffer = view.buffer();
+            let mut end = buffer.end_iter();
+            buffer.insert(&mut end, line);
+            buffer.insert(&mut end, "\n");
+            view.scroll_to_iter(&mut buffer.end_iter(), 0.0, false, 0.0, 1.0);
+        }
+    }
+
+    fn start_winetricks(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        verbs: Vec<String>,
+    ) {
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let proton_path = match self.runtime_mgr.latest_installed() {
+            Ok(Some(path)) => path,
+            Ok(None) => {
+                eprintln!("No Proton-GE runtime installed");
+                return;
+            }
+            Err(e) => {
+                eprintln!("Failed to resolve Proton-GE runtime: {}", e);
+                return;
+            }
+        };
+
+        let home_path = capsule.home_path.clone();
+        let prefix_path = home_path.join("prefix");
+
+        self.winetricks_jobs.insert(capsule_dir.clone());
+        if let Some(dialog) = &self.winetricks_dialog {
+            dialog.set_response_sensitive(ResponseType::Accept, false);
+        }
+        self.append_winetricks_log(&format!("Running winetricks {}", verbs.join(" ")));
+        self.refresh_game_cards();
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let label = format!("Before winetricks {}", verbs.join(", "));
+            if let Err(e) = SnapshotManager::new(&home_path).create(&label, true) {
+                eprintln!("Failed to snapshot prefix before winetricks: {}", e);
+            }
+
+            let success = match Winetricks::locate_or_download() {
+                Ok(winetricks) => {
+                    let mut cmd = winetricks.command(&prefix_path, &proton_path, &verbs);
+                    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+                    match cmd.spawn() {
+                        Ok(mut child) => {
+                            let stderr_reader = child.stderr.take().map(|stderr| {
+                                let sender_inner = sender_clone.clone();
+                                thread::spawn(move || {
+                                    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                                        let _ = sender_inner.input(MainWindowMsg::WinetricksLog(line));
+                                    }
+                                })
+                            });
+                            if let Some(stdout) = child.stdout.take() {
+                                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                                    let _ = sender_clone.input(MainWindowMsg::WinetricksLog(line));
+                                }
+                            }
+                            if let Some(reader) = stderr_reader {
+                                let _ = reader.join();
+                            }
+                            child.wait().map(|status| status.success()).unwrap_or(false)
+                        }
+                        Err(e) => {
+                            let _ = sender_clone.input(MainWindowMsg::WinetricksLog(format!(
+                                "Failed to start winetricks: {}",
+                                e
+                            )));
+                            false
+                        }
+                    }
+                }
+                Err(e) => {
+                    let _ = sender_clone.input(MainWindowMsg::WinetricksLog(format!(
+                        "winetricks is not available: {}",
+                        e
+                    )));
+                    false
+                }
+            };
+
+            let _ = sender_clone.input(MainWindowMsg::WinetricksFinished {
+                capsule_dir,
+                verbs,
+                success,
+            });
+        });
+    }
+
+    const VIRTUAL_DESKTOP_SIZES: [&'static str; 10] = [
+        "640x480",
+        "800x600",
+        "1024x768",
+        "1280x720",
+        "1280x800",
+        "1280x1024",
+        "1366x768",
+        "1600x900",
+        "1920x1080",
+        "2560x1440",
+    ];
+
+    /// Resolution picker for the virtual desktop, on `default_size` unless
+    /// the game has a size saved. A saved size that is not in the preset
+    /// list is added to it.
+    fn virtual_desktop_dropdown(current: Option<&str>, default_size: &str) -> DropDown {
+        let mut sizes: Vec<&str> = Self::VIRTUAL_DESKTOP_SIZES.to_vec();
+        if let Some(current) = current.filter(|size| !sizes.contains(size)) {
+            sizes.push(current);
+        }
+        let dropdown = DropDown::from_strings(&sizes);
+        let selected = current.unwrap_or(default_size);
+        if let Some(index) = sizes.iter().position(|size| *size == selected) {
+            dropdown.set_selected(index as u32);
+        }
+        dropdown
+    }
+
+    /// "Default" followed by the SDL drivers; a saved driver outside the
+    /// list is added to it.
+    fn sdl_driver_dropdown(current: Option<&str>) -> DropDown {
+        let mut drivers = vec!["Default"];
+        drivers.extend(SDL_VIDEO_DRIVERS);
+        if let Some(current) = current.filter(|driver| !drivers[1..].contains(driver)) {
+            drivers.push(current);
+        }
+        let dropdown = DropDown::from_strings(&drivers);
+        if let Some(index) = current.and_then(|current| drivers.iter().position(|driver| *driver == current)) {
+            dropdown.set_selected(index as u32);
+        }
+        dropdown
+    }
+
+    fn selected_sdl_driver(dropdown: &DropDown) -> Option<String> {
+        if dropdown.selected() == 0 {
+            return None;
+        }
+        dropdown
+            .selected_item()
+            .and_downcast::<gtk4::StringObject>()
+            .map(|item| item.string().to_string())
+    }
+
+    /// The ticked `WINEDEBUG` channels followed by the typed ones.
+    fn selected_wine_debug(checks: &[(&str, CheckButton)], entry: &Entry) -> Option<String> {
+        let mut channels: Vec<String> = checks
+            .iter()
+            .filter(|(_, check)| check.is_active())
+            .map(|(channel, _)| channel.to_string())
+            .collect();
+        channels.extend(
+            entry
+                .text()
+                .split(',')
+                .map(str::trim)
+                .filter(|channel| !channel.is_empty())
+                .map(str::to_string),
+        );
+        if channels.is_empty() {
+            None
+        } else {
+            Some(channels.join(","))
+        }
+    }
+
+    fn optional_text(entry: &Entry) -> Option<String> {
+        let text = entry.text().trim().to_string();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+
+    fn fps_limit_value(spin: &SpinButton) -> Option<u32> {
+        Some(spin.value_as_int()).filter(|&limit| limit > 0).map(|limit| limit as u32)
+    }
+
+    fn selected_virtual_desktop(check: &CheckButton, dropdown: &DropDown) -> Option<String> {
+        if !check.is_active() {
+            return None;
+        }
+        dropdown
+            .selected_item()
+            .and_downcast::<gtk4::StringObject>()
+            .map(|item| item.string().to_string())
+    }
+
+    fn append_dll_override_row(
+        rows_box: &Box,
+        rows: &Rc<RefCell<Vec<DllOverrideRow>>>,
+        entry: Option<&DllOverride>,
+    ) {
+        let container = Box::new(Orientation::Horizontal, 8);
+        let dll_entry = Entry::new();
+        dll_entry.set_hexpand(true);
+        dll_entry.set_placeholder_text(Some("e.g., d3d9"));
+        let labels: Vec<&str> = DllOverrideMode::ALL.iter().map(|mode| mode.label()).collect();
+        let mode_dropdown = DropDown::from_strings(&labels);
+        if let Some(entry) = entry {
+            dll_entry.set_text(&entry.dll);
+            let index = DllOverrideMode::ALL
+                .iter()
+                .position(|mode| *mode == entry.mode)
+                .unwrap_or(0);
+            mode_dropdown.set_selected(index as u32);
+        }
+        let remove_button = Button::with_label("Remove");
+        remove_button.add_css_class("flat");
+
+        container.append(&dll_entry);
+        container.append(&mode_dropdown);
+        container.append(&remove_button);
+        rows_box.append(&container);
+
+        let rows_box_clone = rows_box.clone();
+        let rows_clone = rows.clone();
+        let container_clone = container.clone();
+        remove_button.connect_clicked(move |_| {
+            rows_box_clone.remove(&container_clone);
+            rows_clone
+                .borrow_mut()
+                .retain(|row| row.container != container_clone);
+        });
+
+        rows.borrow_mut().push(DllOverrideRow {
+            container,
+            dll_entry,
+            mode_dropdown,
+        });
+    }
+
+    fn collect_dll_overrides(rows: &[DllOverrideRow]) -> Vec<DllOverride> {
+        rows.iter()
+            .filter_map(|row| {
+                let dll = row.dll_entry.text().trim().trim_end_matches(".dll").to_string();
+                if dll.is_empty() {
+                    return None;
+                }
+                let mode = DllOverrideMode::ALL
+                    .get(row.mode_dropdown.selected() as usize)
+                    .copied()
+                    .unwrap_or(DllOverrideMode::NativeBuiltin);
+                Some(DllOverride { dll, mode })
+            })
+            .collect()
+    }
+
+    fn snapshot_row_text(info: &SnapshotInfo) -> (String, String) {
+        let title = if info.label.is_empty() {
+            "Snapshot".to_string()
+        } else {
+            info.label.clone()
+        };
+        let created = chrono::DateTime::parse_from_rfc3339(&info.created_at)
+            .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| info.created_at.clone());
+        let mut detail = format!("{} • {}", created, file_ops::format_bytes(info.size));
+        if info.automatic {
+            detail.push_str(" • automatic");
+        }
+        (title, detail)
+    }
+
+    fn open_local_fix_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.local_fix_dialog.is_some() {
+            return;
+        }
+
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        let fix = match capsule.metadata.game_id.as_deref().and_then(LocalFix::for_game) {
+            Some(fix) => fix,
+            None => {
+                eprintln!("Set a GAMEID for {} before writing a local protonfix", capsule.name);
+                return;
+            }
+        };
+
+        let dialog = Dialog::builder()
+            .title("Local Protonfix")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .default_width(680)
+            .default_height(520)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        if fix.exists() {
+            dialog.add_button("Delete", ResponseType::Reject);
+        }
+        dialog.add_button("Save", ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some(&format!("Local protonfix for {}", capsule.name)));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+        let mut hint_text = format!(
+            "Saved as {}. protonfixes imports it and calls main() whenever the game starts \
+             with GAMEID {}, through umu-run or Proton directly.",
+            fix.path.display(),
+            fix.game_id
+        );
+        if capsule.metadata.protonfixes_disable {
+            hint_text.push_str(" Protonfixes is disabled for this game, so the fix won't run.");
+        }
+        let hint = Label::new(Some(&hint_text));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+
+        let buffer = TextBuffer::new(None);
+        let tags = buffer.tag_table();
+        tags.add(
+            &TextTag::builder()
+                .name("keyword")
+                .foreground("#c061cb")
+                .weight(700)
+                .build(),
+        );
+        tags.add(&TextTag::builder().name("string").foreground("#2ec27e").build());
+        tags.add(
+            &TextTag::builder()
+                .name("comment")
+                .foreground("#9a9996")
+                .style(gtk4::pango::Style::Italic)
+                .build(),
+        );
+        buffer.connect_changed(Self::highlight_python);
+        buffer.set_text(&fix.load().unwrap_or_else(|| fix.template(&capsule.name)));
+
+        let text_view = TextView::with_buffer(&buffer);
+        text_view.set_monospace(true);
+        text_view.set_wrap_mode(WrapMode::None);
+        text_view.set_left_margin(6);
+        text_view.set_top_margin(6);
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_child(Some(&text_view));
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&scroller);
+        content.append(&layout);
+
+        dialog.connect_response(move |dialog, response| {
+            let source = match response {
+                ResponseType::Accept => {
+                    Some(buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string())
+                }
+                ResponseType::Reject => Some(String::new()),
+                _ => None,
+            };
+            if let Some(source) = source {
+                sender.input(MainWindowMsg::SaveLocalFix {
+                    capsule_dir: capsule_dir.clone(),
+                    source,
+                });
+            }
+            sender.input(MainWindowMsg::LocalFixDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.local_fix_dialog = Some(dialog);
+    }
+
+    /// Re-apply the editor's keyword, string and comment tags.
+    fn highlight_python(buffer: &TextBuffer) {
+        let (start, end) = buffer.bounds();
+        buffer.remove_all_tags(&start, &end);
+        let text = buffer.text(&start, &end, false);
+        for span in python_syntax(&text) {
+            let tag = match span.kind {
+                SyntaxKind::Keyword => "keyword",
+                SyntaxKind::String => "string",
+                SyntaxKind::Comment => "comment",
+            };
+            buffer.apply_tag_by_name(
+                tag,
+                &buffer.iter_at_offset(span.start as i32),
+                &buffer.iter_at_offset(span.end as i32),
+            );
+        }
+    }
+
+    fn open_snapshots_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.snapshots_dialog.is_some() {
+            return;
+        }
+
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        let snapshots = SnapshotManager::for_capsule(&capsule).list().unwrap_or_else(|e| {
+            eprintln!("Failed to list snapshots: {}", e);
+            Vec::new()
+        });
+
+        let dialog = Dialog::builder()
+            .title("Prefix Snapshots")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(520);
+        dialog.set_default_height(420);
+        dialog.add_button("Close", ResponseType::Close);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some(&format!("Snapshots of {}", capsule.name)));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let hint = Label::new(Some(
+            "Snapshots cover the Wine prefix, not the game folder. One is taken automatically before dependency installs and runtime changes.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+
+        let listbox = ListBox::new();
+        listbox.set_selection_mode(SelectionMode::Single);
+        for info in &snapshots {
+            let (title_text, detail_text) = Self::snapshot_row_text(info);
+            let row = ListBoxRow::new();
+            let row_box = Box::new(Orientation::Vertical, 4);
+            row_box.set_margin_all(8);
+            let title_label = Label::new(Some(&title_text));
+            title_label.set_halign(gtk4::Align::Start);
+            title_label.set_css_classes(&["card-title"]);
+            let detail_label = Label::new(Some(&detail_text));
+            detail_label.set_halign(gtk4::Align::Start);
+            detail_label.set_css_classes(&["muted"]);
+            row_box.append(&title_label);
+            row_box.append(&detail_label);
+            row.set_child(Some(&row_box));
+            listbox.append(&row);
+        }
+        if snapshots.is_empty() {
+            let empty = Label::new(Some("No snapshots yet."));
+            empty.set_margin_all(8);
+            empty.set_css_classes(&["muted"]);
+            listbox.set_placeholder(Some(&empty));
+        }
+
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_child(Some(&listbox));
+
+        let row_actions = Box::new(Orientation::Horizontal, 8);
+        let restore_button = Button::with_label("Restore selected");
+        restore_button.add_css_class("destructive-action");
+        let delete_button = Button::with_label("Delete selected");
+        delete_button.add_css_class("flat");
+        row_actions.append(&restore_button);
+        row_actions.append(&delete_button);
+
+        let create_row = Box::new(Orientation::Horizontal, 8);
+        let label_entry = Entry::new();
+        label_entry.set_hexpand(true);
+        label_entry.set_placeholder_text(Some("Label for a new snapshot"));
+        let create_button = Button::with_label("Take snapshot");
+        create_button.add_css_class("suggested-action");
+        create_row.append(&label_entry);
+        create_row.append(&create_button);
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&scroller);
+        layout.append(&row_actions);
+        layout.append(&create_row);
+        content.append(&layout);
+
+        let ids: Rc<Vec<String>> = Rc::new(snapshots.iter().map(|info| info.id.clone()).collect());
+        let selected_id = {
+            let listbox = listbox.clone();
+            let ids = ids.clone();
+            move || {
+                listbox
+                    .selected_row()
+                    .and_then(|row| usize::try_from(row.index()).ok())
+                    .and_then(|index| ids.get(index).cloned())
+            }
+        };
+        let selected_id = Rc::new(selected_id);
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        let selected = selected_id.clone();
+        restore_button.connect_clicked(move |_| {
+            if let Some(id) = selected() {
+                sender_clone.input(MainWindowMsg::SnapshotRequested {
+                    capsule_dir: capsule_dir_clone.clone(),
+                    action: SnapshotAction::Restore(id),
+                });
+                dialog_clone.close();
+            }
+        });
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        let selected = selected_id.clone();
+        delete_button.connect_clicked(move |_| {
+            if let Some(id) = selected() {
+                sender_clone.input(MainWindowMsg::SnapshotRequested {
+                    capsule_dir: capsule_dir_clone.clone(),
+                    action: SnapshotAction::Delete(id),
+                });
+                dialog_clone.close();
+            }
+        });
+
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let dialog_clone = dialog.clone();
+        create_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SnapshotRequested {
+                capsule_dir: capsule_dir_clone.clone(),
+                action: SnapshotAction::Create(label_entry.text().to_string()),
+            });
+            dialog_clone.close();
+        });
+
+        let sender_clone = sender.clone();
+        dialog.connect_close_request(move |_| {
+            sender_clone.input(MainWindowMsg::SnapshotsDialogClosed);
+            glib::Propagation::Proceed
+        });
+        dialog.connect_response(|dialog, _| {
+            dialog.close();
+        });
+
+        dialog.show();
+        self.snapshots_dialog = Some(dialog);
+    }
+
+    fn hltb_text(lookup: Option<&HltbLookup>) -> String {
+        match lookup.map(|lookup| &lookup.times) {
+            None => "Not looked up yet.".to_string(),
+            Some(None) => "No match on HowLongToBeat.".to_string(),
+            Some(Some(times)) => format!("{}\nMatched \"{}\"", times.describe(), times.game_name),
+        }
+    }
+
+    fn igdb_text(info: Option<&IgdbInfo>) -> String {
+        let info = match info {
+            Some(info) => info,
+            None => return "Not looked up yet.".to_string(),
+        };
+        let mut lines = vec![format!("Matched \"{}\"", info.name)];
+        if let Some(date) = info.release_date_text() {
+            lines.push(format!("Released {}", date));
+        }
+        if let Some(developer) = &info.developer {
+            lines.push(format!("Developed by {}", developer));
+        }
+        if !info.genres.is_empty() {
+            lines.push(info.genres.join(", "));
+        }
+        lines.join("\n")
+    }
+
+    fn open_game_details_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.details_dialog.is_some() {
+            return;
+        }
+
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let dialog = Dialog::builder()
+            .title("Game Details")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(480);
+        dialog.add_button("Close", ResponseType::Close);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some(capsule.metadata.title()));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+        layout.append(&title);
+
+        let not_set = "Not set".to_string();
+        let mut facts = Vec::new();
+        if capsule.metadata.title() != capsule.name {
+            facts.push(("Folder name", capsule.name.clone()));
+        }
+        if let Some(developer) = capsule.metadata.developer.clone() {
+            facts.push(("Developer", developer));
+        }
+        if let Some(year) = capsule.metadata.release_year {
+            facts.push(("Released", year.to_string()));
+        }
+        facts.extend([
+            (
+                "Store",
+                capsule.metadata.store.clone().unwrap_or_else(|| not_set.clone()),
+            ),
+            (
+                "UMU game ID",
+                capsule.metadata.game_id.clone().unwrap_or_else(|| not_set.clone()),
+            ),
+        ]);
+        if let Some(tier) = Self::capsule_app_id(&capsule)
+            .and_then(|app_id| self.protondb_ratings.get(&app_id))
+            .and_then(|rating| rating.tier)
+        {
+            facts.push(("ProtonDB", tier.label().to_string()));
+        }
+        facts.push((
+            "Game folder",
+            capsule
+                .game_folder()
+                .map(|folder| folder.display().to_string())
+                .unwrap_or(not_set),
+        ));
+        if !capsule.metadata.patches.is_empty() {
+            let patches: Vec<String> = capsule
+                .metadata
+                .patches
+                .iter()
+                .map(|patch| {
+                    let date = chrono::DateTime::parse_from_rfc3339(&patch.applied_at)
+                        .map(|date| date.format("%Y-%m-%d").to_string())
+                        .unwrap_or_else(|_| patch.applied_at.clone());
+                    let failed = if patch.success { "" } else { ", failed" };
+                    format!("{} ({}{})", patch.file_name(), date, failed)
+                })
+                .collect();
+            facts.push(("Patches/DLC", patches.join("\n")));
+        }
+        let screenshots_dir = capsule.screenshots_dir();
+        facts.push((
+            "Screenshots",
+            format!(
+                "{} in {}",
+                Screenshots::count(&screenshots_dir),
+                screenshots_dir.display()
+            ),
+        ));
+        for (name, value) in facts {
+            let row = Box::new(Orientation::Horizontal, 8);
+            let name_label = Label::new(Some(name));
+            name_label.set_css_classes(&["muted"]);
+            name_label.set_width_chars(12);
+            name_label.set_xalign(0.0);
+            let value_label = Label::new(Some(&value));
+            value_label.set_halign(gtk4::Align::Start);
+            value_label.set_wrap(true);
+            value_label.set_selectable(true);
+            row.append(&name_label);
+            row.append(&value_label);
+            layout.append(&row);
+        }
+
+        let history = PlayHistory::load(&capsule);
+        let history_title = Label::new(Some("Play history"));
+        history_title.set_halign(gtk4::Align::Start);
+        history_title.set_css_classes(&["section-title"]);
+        history_title.set_margin_top(8);
+        layout.append(&history_title);
+
+        let mut history_lines = vec![match history.average_session() {
+            Some(average) => format!(
+                "Launched {} {} • Average session {} • Total {}",
+                history.launch_count(),
+                if history.launch_count() == 1 { "time" } else { "times" },
+                play_history::format_duration(average),
+                play_history::format_duration(history.total_played())
+            ),
+            None => "Never launched from LinuxBoy.".to_string(),
+        }];
+        if let Some(crash) = history.last_crash() {
+            let code = match crash.exit_code {
+                Some(code) => format!("exit code {}", code),
+                None => "killed".to_string(),
+            };
+            history_lines.push(format!(
+                "Last crash: {} ({}, after {})",
+                Self::format_unix_time(crash.started as i64),
+                code,
+                play_history::format_duration(Duration::from_secs(crash.duration_secs))
+            ));
+        } else if history.launch_count() > 0 {
+            history_lines.push("No crashes recorded.".to_string());
+        }
+        let history_label = Label::new(Some(&history_lines.join("\n")));
+        history_label.set_halign(gtk4::Align::Start);
+        history_label.set_wrap(true);
+        history_label.set_xalign(0.0);
+        layout.append(&history_label);
+        if history.is_crashing() {
+            let crashing_label = Label::new(Some(
+                "The last three launches exited with an error. Prefix Doctor may find the cause.",
+            ));
+            crashing_label.set_halign(gtk4::Align::Start);
+            crashing_label.set_wrap(true);
+            crashing_label.set_css_classes(&["status-warning"]);
+            layout.append(&crashing_label);
+        }
+
+        let hltb_title = Label::new(Some("How long to beat"));
+        hltb_title.set_halign(gtk4::Align::Start);
+        hltb_title.set_css_classes(&["section-title"]);
+        hltb_title.set_margin_top(8);
+        layout.append(&hltb_title);
+
+        let cached = HowLongToBeat::cached(&capsule.name);
+        let hltb_label = Label::new(Some(&Self::hltb_text(cached.as_ref())));
+        hltb_label.set_halign(gtk4::Align::Start);
+        hltb_label.set_wrap(true);
+        hltb_label.set_xalign(0.0);
+        layout.append(&hltb_label);
+
+        let hltb_buttons = Box::new(Orientation::Horizontal, 8);
+        let lookup_button = Button::with_label(if cached.is_some() {
+            "Look up again"
+        } else {
+            "Look up"
+        });
+        lookup_button.set_tooltip_text(Some("Search howlongtobeat.com for this game"));
+        let page_button = Button::with_label("Open on HowLongToBeat");
+        page_button.set_visible(cached.as_ref().is_some_and(|lookup| lookup.times.is_some()));
+        hltb_buttons.append(&lookup_button);
+        hltb_buttons.append(&page_button);
+        layout.append(&hltb_buttons);
+
+        let igdb_title = Label::new(Some("IGDB"));
+        igdb_title.set_halign(gtk4::Align::Start);
+        igdb_title.set_css_classes(&["section-title"]);
+        igdb_title.set_margin_top(8);
+        layout.append(&igdb_title);
+
+        let igdb = capsule.metadata.igdb.clone();
+        let igdb_row = Box::new(Orientation::Horizontal, 12);
+        let cover_path = IgdbInfo::cover_path(&capsule.home_path);
+        if igdb.is_some() && cover_path.exists() {
+            let cover = Picture::for_filename(&cover_path);
+            cover.set_can_shrink(true);
+            cover.set_size_request(132, 187);
+            cover.set_valign(gtk4::Align::Start);
+            igdb_row.append(&cover);
+        }
+        let igdb_text = Box::new(Orientation::Vertical, 4);
+        igdb_text.set_hexpand(true);
+        let igdb_label = Label::new(Some(&Self::igdb_text(igdb.as_ref())));
+        igdb_label.set_halign(gtk4::Align::Start);
+        igdb_label.set_wrap(true);
+        igdb_label.set_xalign(0.0);
+        igdb_text.append(&igdb_label);
+        if let Some(summary) = igdb.as_ref().and_then(|info| info.summary.as_deref()) {
+            let summary_label = Label::new(Some(summary));
+            summary_label.set_halign(gtk4::Align::Start);
+            summary_label.set_wrap(true);
+            summary_label.set_xalign(0.0);
+            summary_label.set_selectable(true);
+            summary_label.set_css_classes(&["muted"]);
+            igdb_text.append(&summary_label);
+        }
+        igdb_row.append(&igdb_text);
+        layout.append(&igdb_row);
+
+        let igdb_buttons = Box::new(Orientation::Horizontal, 8);
+        let igdb_lookup_button = Button::with_label(if igdb.is_some() {
+            "Look up again on IGDB"
+        } else {
+            "Look up on IGDB"
+        });
+        let igdb_settings = IgdbSettings::load();
+        if igdb_settings.is_configured() {
+            igdb_lookup_button.set_tooltip_text(Some(
+                "Fetch the description, genres, release date and cover from IGDB",
+            ));
+        } else {
+            igdb_lookup_button.set_sensitive(false);
+            igdb_lookup_button
+                .set_tooltip_text(Some("Add your IGDB client ID and secret in Preferences"));
+        }
+        let igdb_page_button = Button::with_label("Open on IGDB");
+        let igdb_url = igdb.as_ref().and_then(|info| info.url.clone());
+        igdb_page_button.set_visible(igdb_url.is_some());
+        igdb_buttons.append(&igdb_lookup_button);
+        igdb_buttons.append(&igdb_page_button);
+        layout.append(&igdb_buttons);
+        content.append(&layout);
+
+        let sender_clone = sender.clone();
+        let name = capsule.name.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let hltb_label_clone = hltb_label.clone();
+        lookup_button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            hltb_label_clone.set_text("Looking up...");
+            let sender_clone = sender_clone.clone();
+            let name = name.clone();
+            let capsule_dir = capsule_dir_clone.clone();
+            thread::spawn(move || {
+                let result = HowLongToBeat::lookup(&name).map_err(|e| e.to_string());
+                sender_clone.input(MainWindowMsg::HowLongToBeatFetched { capsule_dir, result });
+            });
+        });
+
+        let sender_clone = sender.clone();
+        let name = capsule.name.clone();
+        page_button.connect_clicked(move |_| {
+            let lookup = HowLongToBeat::cached(&name);
+            if let Some(times) = lookup.and_then(|lookup| lookup.times) {
+                sender_clone.input(MainWindowMsg::OpenUrl(times.page_url()));
+            }
+        });
+
+        let sender_clone = sender.clone();
+        let game_title = capsule.metadata.title().to_string();
+        let home_path = capsule.home_path.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let igdb_label_clone = igdb_label.clone();
+        igdb_lookup_button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            igdb_label_clone.set_text("Looking up...");
+            let sender_clone = sender_clone.clone();
+            let settings = igdb_settings.clone();
+            let game_title = game_title.clone();
+            let home_path = home_path.clone();
+            let capsule_dir = capsule_dir_clone.clone();
+            thread::spawn(move || {
+                let result = Igdb::new(settings).lookup(&game_title).map_err(|e| e.to_string());
+                if let Ok(Some(info)) = &result {
+                    let dest = IgdbInfo::cover_path(&home_path);
+                    if let Err(e) = Igdb::download_cover(info, &dest) {
+                        eprintln!("Failed to download the IGDB cover: {}", e);
+                    }
+                }
+                sender_clone.input(MainWindowMsg::IgdbFetched { capsule_dir, result });
+            });
+        });
+
+        let sender_clone = sender.clone();
+        igdb_page_button.connect_clicked(move |_| {
+            if let Some(url) = &igdb_url {
+                sender_clone.input(MainWindowMsg::OpenUrl(url.clone()));
+            }
+        });
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, _| {
+            sender_clone.input(MainWindowMsg::GameDetailsClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.details_dialog = Some(dialog);
+        self.details_capsule = Some(capsule_dir);
+        self.details_hltb_label = Some(hltb_label);
+        self.details_hltb_buttons = Some((lookup_button, page_button));
+        self.details_igdb = Some((igdb_label, igdb_lookup_button));
+    }
+
+    fn open_diagnostics_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.diagnostics_dialog.is_some() {
+            return;
+        }
+
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        self.system_check = SystemCheck::check();
+        let findings = PrefixDoctor::examine(&capsule, &self.system_check);
+        let busy = self.is_capsule_busy(&capsule_dir);
+
+        let dialog = Dialog::builder()
+            .title("Prefix Doctor")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(520);
+        dialog.set_default_height(420);
+        dialog.add_button("Close", ResponseType::Close);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some(&format!("Diagnostics for {}", capsule.name)));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+        layout.append(&title);
+
+        let listbox = ListBox::new();
+        listbox.set_selection_mode(SelectionMode::None);
+        for finding in findings {
+            let row = ListBoxRow::new();
+            let row_box = Box::new(Orientation::Horizontal, 8);
+            row_box.set_margin_all(8);
+
+            let icon = Image::from_icon_name(match finding.severity {
+                Severity::Error => "dialog-error-symbolic",
+                Severity::Warning => "dialog-warning-symbolic",
+            });
+            icon.set_valign(gtk4::Align::Start);
+
+            let text_box = Box::new(Orientation::Vertical, 4);
+            text_box.set_hexpand(true);
+            let summary = Label::new(Some(&finding.summary));
+            summary.set_halign(gtk4::Align::Start);
+            summary.set_css_classes(&["card-title"]);
+            let detail = Label::new(Some(&finding.detail));
+            detail.set_halign(gtk4::Align::Start);
+            detail.set_wrap(true);
+            detail.set_xalign(0.0);
+            detail.set_css_classes(&["muted"]);
+            text_box.append(&summary);
+            text_box.append(&detail);
+
+            row_box.append(&icon);
+            row_box.append(&text_box);
+
+            if let Some(fix) = finding.fix {
+                let fix_button = Button::with_label(fix.label());
+                fix_button.set_valign(gtk4::Align::Center);
+                // Fixes that touch the prefix wait until nothing else is using it.
+                fix_button.set_sensitive(
+                    !busy
+                        || matches!(
+                            fix,
+                            DiagnosticFix::SelectExecutable | DiagnosticFix::OpenSystemSetup
+                        ),
+                );
+                let sender_clone = sender.clone();
+                let capsule_dir_clone = capsule_dir.clone();
+                let dialog_clone = dialog.clone();
+                fix_button.connect_clicked(move |_| {
+                    sender_clone.input(MainWindowMsg::DiagnosticsDialogClosed);
+                    sender_clone.input(MainWindowMsg::ApplyDiagnosticFix {
+                        capsule_dir: capsule_dir_clone.clone(),
+                        fix: fix.clone(),
+                    });
+                    dialog_clone.close();
+                });
+                row_box.append(&fix_button);
+            }
+
+            row.set_child(Some(&row_box));
+            listbox.append(&row);
+        }
+        let empty = Label::new(Some("No problems found."));
+        empty.set_margin_all(8);
+        empty.set_css_classes(&["muted"]);
+        listbox.set_placeholder(Some(&empty));
+
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_child(Some(&listbox));
+        layout.append(&scroller);
+
+        let bundle_button = Button::with_label("Generate support bundle...");
+        bundle_button.set_halign(gtk4::Align::Start);
+        bundle_button.set_tooltip_text(Some(
+            "Collect logs, settings and system details into one file to attach to an issue",
+        ));
+        layout.append(&bundle_button);
+        content.append(&layout);
+
+        let root_window = self.root_window.clone();
+        let sender_clone = sender.clone();
+        let capsule_dir_clone = capsule_dir.clone();
+        let default_bundle_name = SupportBundle::default_file_name(&capsule);
+        bundle_button.connect_clicked(move |_| {
+            let chooser = FileChooserNative::builder()
+                .title("Save Support Bundle")
+                .action(FileChooserAction::Save)
+                .accept_label("Save")
+                .cancel_label("Cancel")
+                .transient_for(&root_window)
+                .build();
+            chooser.set_current_name(&default_bundle_name);
+
+            let sender_inner = sender_clone.clone();
+            let capsule_dir_inner = capsule_dir_clone.clone();
+            chooser.connect_response(move |chooser, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = chooser.file().and_then(|file| file.path()) {
+                        sender_inner.input(MainWindowMsg::GenerateSupportBundle {
+                            capsule_dir: capsule_dir_inner.clone(),
+                            dest: path,
+                        });
+                    }
+                }
+                chooser.destroy();
+            });
+
+            chooser.show();
+        });
+
+        let sender_clone = sender.clone();
+        dialog.connect_close_request(move |_| {
+            sender_clone.input(MainWindowMsg::DiagnosticsDialogClosed);
+            glib::Propagation::Proceed
+        });
+        dialog.connect_response(|dialog, _| {
+            dialog.close();
+        });
+
+        dialog.show();
+        self.diagnostics_dialog = Some(dialog);
+    }
+
+    /// Show the command `start_game` would run for the capsule, without
+    /// running it or preparing the prefix.
+    fn open_launch_command_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.launch_command_dialog.is_some() {
+            return;
+        }
+
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        let proton_path = match self.runtime_mgr.latest_installed() {
+            Ok(Some(path)) => path,
+            Ok(None) => {
+                eprintln!("No Proton-GE runtime installed");
+                return;
+            }
+            Err(e) => {
+                eprintln!("Failed to resolve Proton-GE runtime: {}", e);
+                return;
+            }
+        };
+        let prefix_path = capsule.home_path.join("prefix");
+
+        let mut notes = Vec::new();
+        if capsule.metadata.custom_command().is_some() {
+            notes.push("# Custom launch command".to_string());
+        } else {
+            match Runner::detect() {
+                Ok(Runner::Umu) => {}
+                Ok(runner) => notes.push(format!("# umu-run not found; using {}", runner.label())),
+                Err(e) => notes.push(format!("# {}", e)),
+            }
+        }
+        let text = match self.launch_command(&capsule, &prefix_path, &proton_path, None) {
+            Ok(cmd) => {
+                notes.push(LaunchPreview::render(&cmd));
+                notes.join("\n")
+            }
+            Err(e) => e,
+        };
+
+        let dialog = Dialog::builder()
+            .title("Launch Command")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(640);
+        dialog.set_default_height(420);
+        dialog.add_button("Copy", ResponseType::Apply);
+        dialog.add_button("Close", ResponseType::Close);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some(&format!("Launching {}", capsule.name)));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+        let hint = Label::new(Some(
+            "What Play runs, ready to paste into a terminal. Values of variables that look \
+             like passwords or tokens are hidden, and the prefix is prepared before the game \
+             starts.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_xalign(0.0);
+        hint.set_css_classes(&["muted"]);
+
+        let text_view = TextView::new();
+        text_view.set_editable(false);
+        text_view.set_monospace(true);
+        text_view.set_wrap_mode(WrapMode::WordChar);
+        text_view.buffer().set_text(&text);
+        let scroller = ScrolledWindow::new();
+        scroller.set_vexpand(true);
+        scroller.set_child(Some(&text_view));
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&scroller);
+        content.append(&layout);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Apply {
+                dialog.clipboard().set_text(&text);
+                return;
+            }
+            sender.input(MainWindowMsg::LaunchCommandDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.launch_command_dialog = Some(dialog);
+    }
+
+    fn open_repair_dialog(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        action: RepairAction,
+    ) {
+        if self.repair_dialog.is_some() {
+            return;
+        }
+
+        let name = Capsule::load_from_dir(&capsule_dir)
+            .map(|capsule| capsule.name)
+            .unwrap_or_default();
+
+        let dialog = Dialog::builder()
+            .title(action.label())
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(420);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button(action.label(), ResponseType::Accept);
+        if let Some(button) = dialog.widget_for_response(ResponseType::Accept) {
+            button.add_css_class(if action == RepairAction::RebuildPrefix {
+                "destructive-action"
+            } else {
+                "suggested-action"
+            });
+        }
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some(&format!("{} for {}?", action.label(), name)));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let description = Label::new(Some(action.description()));
+        description.set_halign(gtk4::Align::Start);
+        description.set_wrap(true);
+        description.set_xalign(0.0);
+        description.set_css_classes(&["muted"]);
+
+        layout.append(&title);
+        layout.append(&description);
+        content.append(&layout);
+
+        let sender_clone = sender.clone();
+        dialog.connect_close_request(move |_| {
+            sender_clone.input(MainWindowMsg::RepairDialogClosed);
+            glib::Propagation::Proceed
+        });
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender_clone.input(MainWindowMsg::RepairConfirmed {
+                    capsule_dir: capsule_dir.clone(),
+                    action,
+                });
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+        self.repair_dialog = Some(dialog);
+    }
+
+    fn start_repair(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        action: RepairAction,
+    ) {
+        if self.is_capsule_busy(&capsule_dir) {
+            return;
+        }
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let steps = action.steps();
+        let proton_path = if steps.contains(&RepairStep::InitializePrefix) {
+            if let Err(e) = Runner::detect() {
+                eprintln!("{}", e);
+                return;
+            }
+            match self.runtime_mgr.latest_installed() {
+                Ok(Some(path)) => Some(path),
+                Ok(None) => {
+                    eprintln!("No Proton-GE runtime installed");
+                    return;
+                }
+                Err(e) => {
+                    eprintln!("Failed to resolve Proton-GE runtime: {}", e);
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.open_copy_progress_dialog(action.label(), cancel.clone());
+        self.repair_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let prefix_path = capsule.home_path.join("prefix");
+            let mut tools = RepairTools::new(&capsule);
+            let mut summary = Vec::new();
+            let mut result = Ok(());
+            for (index, step) in steps.iter().enumerate() {
+                if cancel.load(Ordering::Relaxed) {
+                    result = Err("Repair cancelled".to_string());
+                    break;
+                }
+                let _ = sender_clone.input(MainWindowMsg::RepairProgress {
+                    step: step.label().to_string(),
+                    fraction: index as f64 / steps.len() as f64,
+                });
+                let outcome = match (step, proton_path.as_ref()) {
+                    (RepairStep::InitializePrefix, Some(proton_path)) => {
+                        if Self::run_umu_preflight(&prefix_path, proton_path, &capsule.metadata) {
+                            Ok("Prefix initialized".to_string())
+                        } else {
+                            Err("umu could not initialize the prefix".to_string())
+                        }
+                    }
+                    _ => tools.run(*step).map_err(|e| format!("{:#}", e)),
+                };
+                match outcome {
+                    Ok(message) => summary.push(message),
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+            let result = result.map(|_| {
+                format!("{} for {}: {}", action.label(), capsule.name, summary.join(", "))
+            });
+            let _ = sender_clone.input(MainWindowMsg::PrefixRepairFinished { capsule_dir, result });
+        });
+    }
+
+    /// Run umu against the capsule so it creates any missing prefix files.
+    fn start_prefix_initialize(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.is_capsule_busy(&capsule_dir) {
+            return;
+        }
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let proton_path = match self.runner_runtime() {
+            Some(path) => path,
+            None => return,
+        };
+
+        self.repair_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let prefix_path = capsule.home_path.join("prefix");
+            let result = if let Err(e) = fs::create_dir_all(&prefix_path) {
+                Err(format!("Failed to create prefix folder: {}", e))
+            } else if Self::run_umu_preflight(&prefix_path, &proton_path, &capsule.metadata) {
+                Ok(format!("Recreated prefix for {}", capsule.name))
+            } else {
+                Err("umu could not initialize the prefix".to_string())
+            };
+            let _ = sender_clone.input(MainWindowMsg::PrefixRepairFinished { capsule_dir, result });
+        });
+    }
+
+    fn start_snapshot_job(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        action: SnapshotAction,
+    ) {
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        self.snapshot_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let manager = SnapshotManager::for_capsule(&capsule);
+            let result = match action {
+                SnapshotAction::Create(label) => manager
+                    .create(&label, false)
+                    .map(|info| format!("Created snapshot {}", info.id)),
+                SnapshotAction::Restore(id) => manager
+                    .restore(&id)
+                    .map(|_| format!("Restored snapshot {}", id)),
+                SnapshotAction::Delete(id) => manager
+                    .delete(&id)
+                    .map(|_| format!("Deleted snapshot {}", id)),
+            }
+            .map_err(|e| e.to_string());
+            let _ = sender_clone.input(MainWindowMsg::SnapshotFinished { capsule_dir, result });
+        });
+    }
+
+    fn start_cloud_sync(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        direction: SyncDirection,
+    ) {
+        if self.cloud_sync_jobs.contains(&capsule_dir) {
+            return;
+        }
+        if self.active_games.contains_key(&capsule_dir) {
+            eprintln!("Close the game before syncing saves");
+            return;
+        }
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        self.cloud_sync_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+
+        let sync = CloudSync::new(self.cloud_sync_config.clone());
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let result = sync
+                .sync(&capsule, direction)
+                .map_err(|e| e.to_string());
+            let _ = sender_clone.input(MainWindowMsg::CloudSyncFinished { capsule_dir, result });
+        });
+    }
+
+    fn format_unix_time(secs: i64) -> String {
+        chrono::DateTime::from_timestamp(secs, 0)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    fn open_sync_conflict_dialog(
+        &self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        local_modified: i64,
+        remote_modified: i64,
+    ) {
+        let dialog = Dialog::builder()
+            .title("Save Conflict")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Decide later", ResponseType::Cancel);
+        dialog.add_button("Use cloud saves", ResponseType::Other(1));
+        dialog.add_button("Keep local saves", ResponseType::Other(2));
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some("Saves changed here and in the cloud"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let detail = Label::new(Some(&format!(
+            "Local saves: {}\nCloud saves: {}\n\nThe version you don't keep will be overwritten.",
+            Self::format_unix_time(local_modified),
+            Self::format_unix_time(remote_modified)
+        )));
+        detail.set_halign(gtk4::Align::Start);
+        detail.set_wrap(true);
+        detail.set_css_classes(&["muted"]);
+
+        layout.append(&title);
+        layout.append(&detail);
+        content.append(&layout);
+
+        dialog.connect_response(move |dialog, response| {
+            let direction = match response {
+                ResponseType::Other(1) => Some(SyncDirection::Download),
+                ResponseType::Other(2) => Some(SyncDirection::Upload),
+                _ => None,
+            };
+            if let Some(direction) = direction {
+                sender.input(MainWindowMsg::SyncSaves {
+                    capsule_dir: capsule_dir.clone(),
+                    direction,
+                });
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    }
+
+    fn open_cloud_sync_dialog(&mut self, sender: ComponentSender<Self>) {
+        if self.cloud_sync_dialog.is_some() {
+            return;
+        }
+
+        let config = self.cloud_sync_config.clone();
+        let dialog = Dialog::builder()
+            .title("Cloud Sync")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Save", ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some("Save sync remote"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let hint = Label::new(Some(
+            "Saves are synced when a game with cloud sync enabled exits. Enable it per game in Game Settings.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+
+        let disabled_check = CheckButton::with_label("Disabled");
+        let rclone_check = CheckButton::with_label("rclone remote");
+        rclone_check.set_group(Some(&disabled_check));
+        let webdav_check = CheckButton::with_label("WebDAV");
+        webdav_check.set_group(Some(&disabled_check));
+        match config.backend {
+            CloudBackend::Disabled => disabled_check.set_active(true),
+            CloudBackend::Rclone => rclone_check.set_active(true),
+            CloudBackend::WebDav => webdav_check.set_active(true),
+        }
+
+        let rclone_entry = Entry::new();
+        rclone_entry.set_placeholder_text(Some("gdrive:LinuxBoy"));
+        rclone_entry.set_text(&config.rclone_remote);
+
+        let webdav_url_entry = Entry::new();
+        webdav_url_entry.set_placeholder_text(Some("https://cloud.example.com/remote.php/dav/files/me/LinuxBoy"));
+        webdav_url_entry.set_text(&config.webdav_url);
+        let webdav_user_entry = Entry::new();
+        webdav_user_entry.set_placeholder_text(Some("Username"));
+        webdav_user_entry.set_text(&config.webdav_username);
+        let webdav_password_entry = Entry::new();
+        webdav_password_entry.set_placeholder_text(Some("Password"));
+        webdav_password_entry.set_visibility(false);
+        webdav_password_entry.set_text(&config.webdav_password);
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&disabled_check);
+        layout.append(&rclone_check);
+        layout.append(&rclone_entry);
+        layout.append(&webdav_check);
+        layout.append(&webdav_url_entry);
+        layout.append(&webdav_user_entry);
+        layout.append(&webdav_password_entry);
+        content.append(&layout);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                let backend = if rclone_check.is_active() {
+                    CloudBackend::Rclone
+                } else if webdav_check.is_active() {
+                    CloudBackend::WebDav
+                } else {
+                    CloudBackend::Disabled
+                };
+                sender.input(MainWindowMsg::CloudSyncSettingsSaved(CloudSyncConfig {
+                    backend,
+                    rclone_remote: rclone_entry.text().trim().to_string(),
+                    webdav_url: webdav_url_entry.text().trim().to_string(),
+                    webdav_username: webdav_user_entry.text().trim().to_string(),
+                    webdav_password: webdav_password_entry.text().to_string(),
+                }));
+            }
+            sender.input(MainWindowMsg::CloudSyncDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.cloud_sync_dialog = Some(dialog);
+    }
+
+    /// Apply the template called `name` to a capsule and save it.
+    fn apply_template(&self, capsule_dir: &Path, name: &str) -> bool {
+        let template = match self.templates.find(name) {
+            Some(template) => template,
+            None => return false,
+        };
+        match Capsule::load_from_dir(capsule_dir) {
+            Ok(mut capsule) => {
+                template.profile.apply(&mut capsule.metadata);
+                if let Err(e) = capsule.save_metadata() {
+                    eprintln!("Failed to update metadata: {}", e);
+                    return false;
+                }
+                println!("Applied template {} to {}", name, capsule.name);
+                true
+            }
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                false
+            }
+        }
+    }
+
+    fn open_template_name_dialog(&mut self, sender: ComponentSender<Self>, capsule_dir: PathBuf) {
+        if self.template_name_dialog.is_some() {
+            return;
+        }
+
+        let current_name = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule.name,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let dialog = Dialog::builder()
+            .title("Save as Template")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(420);
+        dialog.set_resizable(false);
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Save", ResponseType::Accept);
+        dialog.set_default_response(ResponseType::Accept);
+
+        let content = dialog.content_area();
+        content.set_margin_all(16);
+        content.set_spacing(10);
+        let label = Label::new(Some("Template name"));
+        label.set_halign(gtk4::Align::Start);
+        label.set_css_classes(&["section-title"]);
+        let entry = Entry::new();
+        entry.set_hexpand(true);
+        entry.set_text(&current_name);
+        entry.set_activates_default(true);
+        let hint = Label::new(Some("A template with the same name is replaced."));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_css_classes(&["muted"]);
+        content.append(&label);
+        content.append(&entry);
+        content.append(&hint);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender_clone.input(MainWindowMsg::SaveTemplate {
+                    capsule_dir: capsule_dir.clone(),
+                    name: entry.text().to_string(),
+                });
+            }
+            sender_clone.input(MainWindowMsg::TemplateNameDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.template_name_dialog = Some(dialog);
+    }
+
+    fn open_preferences_dialog(&mut self, sender: ComponentSender<Self>) {
+        if self.preferences_dialog.is_some() {
+            return;
+        }
+
+        let settings = self.deck_settings.clone();
+        let discord_settings = self.discord_settings.clone();
+        let runner_settings = RunnerSettings::load();
+        let log_settings = LogSettings::load();
+        let igdb_settings = IgdbSettings::load();
+        let defaults = self.new_game_defaults.clone();
+        let dialog = Dialog::builder()
+            .title("Preferences")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Save", ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let new_games_title = Label::new(Some("New games"));
+        new_games_title.set_halign(gtk4::Align::Start);
+        new_games_title.set_css_classes(&["section-title"]);
+
+        let template_names = self.templates.names();
+        let mut template_options = vec!["None"];
+        template_options.extend(template_names.iter().map(String::as_str));
+        let template_dropdown = DropDown::from_strings(&template_options);
+        template_dropdown.set_hexpand(true);
+        let current_template = self
+            .templates
+            .default_template
+            .as_ref()
+            .and_then(|name| template_names.iter().position(|option| option == name))
+            .map(|index| index as u32 + 1)
+            .unwrap_or(0);
+        template_dropdown.set_selected(current_template);
+        let delete_template_button = Button::with_label("Delete Template");
+        delete_template_button.set_tooltip_text(Some("Delete the selected template"));
+        delete_template_button.set_sensitive(current_template > 0);
+        let delete_button_clone = delete_template_button.clone();
+        template_dropdown.connect_selected_notify(move |dropdown| {
+            delete_button_clone.set_sensitive(dropdown.selected() > 0);
+        });
+        let sender_clone = sender.clone();
+        let template_dropdown_clone = template_dropdown.clone();
+        delete_template_button.connect_clicked(move |button| {
+            let selected = template_dropdown_clone.selected();
+            if selected == 0 {
+                return;
+            }
+            let model = match template_dropdown_clone
+                .model()
+                .and_downcast::<gtk4::StringList>()
+            {
+                Some(model) => model,
+                None => return,
+            };
+            let name = match model.string(selected) {
+                Some(name) => name,
+                None => return,
+            };
+            sender_clone.input(MainWindowMsg::DeleteTemplate(name.to_string()));
+            template_dropdown_clone.set_selected(0);
+            model.remove(selected);
+            button.set_sensitive(false);
+        });
+        let template_label = Label::new(Some("Template"));
+        let template_row = Box::new(Orientation::Horizontal, 8);
+        template_row.append(&template_label);
+        template_row.append(&template_dropdown);
+        template_row.append(&delete_template_button);
+        let template_hint = Label::new(Some(
+            "Applied to every game you add. Save a template from a game's settings.",
+        ));
+        template_hint.set_halign(gtk4::Align::Start);
+        template_hint.set_wrap(true);
+        template_hint.set_css_classes(&["muted"]);
+
+        let default_store_label = Label::new(Some("Store"));
+        let default_store_entry = Entry::new();
+        default_store_entry.set_placeholder_text(Some("e.g., steam, gog, egs, none"));
+        default_store_entry.set_hexpand(true);
+        if let Some(store) = &defaults.store {
+            default_store_entry.set_text(store);
+        }
+        let default_store_row = Box::new(Orientation::Horizontal, 8);
+        default_store_row.append(&default_store_label);
+        default_store_row.append(&default_store_entry);
+
+        let default_vcredist_check =
+            CheckButton::with_label("Install VC++ Redistributables (AIO)");
+        default_vcredist_check.set_active(defaults.install_vcredist);
+        let default_dxweb_check = CheckButton::with_label("Install DirectX (June 2010) Redist");
+        default_dxweb_check.set_active(defaults.install_dxweb);
+        let default_xalia_check =
+            CheckButton::with_label("Enable Xalia controller UI layer (may disable mouse)");
+        default_xalia_check.set_active(defaults.xalia_enabled);
+        let default_pf_disable_check = CheckButton::with_label("Disable Protonfixes");
+        default_pf_disable_check.set_active(defaults.protonfixes_disable);
+
+        let default_env_label = Label::new(Some("Environment variables (KEY=VALUE, one per line)"));
+        default_env_label.set_halign(gtk4::Align::Start);
+        let default_env_view = TextView::new();
+        default_env_view.set_monospace(true);
+        default_env_view.buffer().set_text(&defaults.env_vars_text());
+        let default_env_scroller = ScrolledWindow::new();
+        default_env_scroller.set_min_content_height(72);
+        default_env_scroller.set_child(Some(&default_env_view));
+
+        let defaults_hint = Label::new(Some(
+            "Used for every game you add; a store found in the UMU database wins over the \
+             one set here, and the template's settings win over these. Games already in \
+             the library keep their settings.",
+        ));
+        defaults_hint.set_halign(gtk4::Align::Start);
+        defaults_hint.set_wrap(true);
+        defaults_hint.set_css_classes(&["muted"]);
+
+        layout.append(&new_games_title);
+        layout.append(&default_store_row);
+        layout.append(&default_vcredist_check);
+        layout.append(&default_dxweb_check);
+        layout.append(&default_xalia_check);
+        layout.append(&default_pf_disable_check);
+        layout.append(&default_env_label);
+        layout.append(&default_env_scroller);
+        layout.append(&defaults_hint);
+        layout.append(&template_row);
+        layout.append(&template_hint);
+
+        let deck_title = Label::new(Some("Steam Deck"));
+        deck_title.set_halign(gtk4::Align::Start);
+        deck_title.set_css_classes(&["section-title"]);
+        deck_title.set_margin_top(8);
+
+        let deck_status = Label::new(Some(&if self.steam_deck.detected() {
+            format!("Detected: {}", self.steam_deck.label())
+        } else {
+            "These settings apply on a Steam Deck or another SteamOS device.".to_string()
+        }));
+        deck_status.set_halign(gtk4::Align::Start);
+        deck_status.set_wrap(true);
+        deck_status.set_css_classes(&["muted"]);
+
+        let defaults_check =
+            CheckButton::with_label("Deck defaults for new games (1280x800 virtual desktop)");
+        defaults_check.set_active(settings.tailored_defaults);
+
+        let sd_card = SteamDeck::sd_card_mount();
+        let sd_check = CheckButton::with_label(&match &sd_card {
+            Some(mount) => format!("Keep the library on the microSD card ({})", mount.display()),
+            None => "Keep the library on the microSD card".to_string(),
+        });
+        sd_check.set_active(settings.sd_card_library);
+        if sd_card.is_none() {
+            // Still allow turning it off if it was enabled before.
+            sd_check.set_sensitive(sd_check.is_active());
+            sd_check.set_tooltip_text(Some("No microSD card is mounted"));
+        }
+        let sd_hint = Label::new(Some(
+            "Games already in the library stay where they are; use Move... on a card to \
+             bring them along.",
+        ));
+        sd_hint.set_halign(gtk4::Align::Start);
+        sd_hint.set_wrap(true);
+        sd_hint.set_css_classes(&["muted"]);
+
+        let steam_check = CheckButton::with_label("Offer to add games to Steam after install");
+        steam_check.set_active(settings.prompt_add_to_steam);
+
+        for check in [&defaults_check, &sd_check, &steam_check] {
+            if !self.steam_deck.detected() {
+                check.set_sensitive(false);
+            }
+        }
+
+        layout.append(&deck_title);
+        layout.append(&deck_status);
+        layout.append(&defaults_check);
+        layout.append(&sd_check);
+        layout.append(&sd_hint);
+        layout.append(&steam_check);
+
+        let runner_title = Label::new(Some("Launcher"));
+        runner_title.set_halign(gtk4::Align::Start);
+        runner_title.set_css_classes(&["section-title"]);
+        runner_title.set_margin_top(8);
+
+        let fallback_labels: Vec<&str> = FallbackRunner::ALL
+            .iter()
+            .map(|fallback| fallback.label())
+            .collect();
+        let fallback_dropdown = DropDown::from_strings(&fallback_labels);
+        fallback_dropdown.set_hexpand(true);
+        fallback_dropdown.set_selected(
+            FallbackRunner::ALL
+                .iter()
+                .position(|fallback| *fallback == runner_settings.fallback)
+                .unwrap_or(0) as u32,
+        );
+        let fallback_label = Label::new(Some("Without umu-run"));
+        let fallback_row = Box::new(Orientation::Horizontal, 8);
+        fallback_row.append(&fallback_label);
+        fallback_row.append(&fallback_dropdown);
+
+        let runner_hint = Label::new(Some(if self.system_check.umu_installed {
+            "umu-run is installed and starts every game. The fallback is used on systems \
+             where umu-launcher can't be installed."
+        } else {
+            "umu-run is not installed. Proton can still run games directly, without the \
+             Steam Runtime, or system Wine can run them without Proton's fixes."
+        }));
+        runner_hint.set_halign(gtk4::Align::Start);
+        runner_hint.set_wrap(true);
+        runner_hint.set_css_classes(&["muted"]);
+
+        layout.append(&runner_title);
+        layout.append(&fallback_row);
+        layout.append(&runner_hint);
+
+        let logs_title = Label::new(Some("Game Logs"));
+        logs_title.set_halign(gtk4::Align::Start);
+        logs_title.set_css_classes(&["section-title"]);
+        logs_title.set_margin_top(8);
+
+        let log_size_spin = SpinButton::with_range(1.0, 1024.0, 1.0);
+        log_size_spin.set_value(log_settings.max_size_mb as f64);
+        let log_size_row = Box::new(Orientation::Horizontal, 8);
+        log_size_row.append(&Label::new(Some("Rotate each log at (MB)")));
+        log_size_row.append(&log_size_spin);
+        let log_files_spin = SpinButton::with_range(0.0, 20.0, 1.0);
+        log_files_spin.set_value(log_settings.max_files as f64);
+        let log_files_row = Box::new(Orientation::Horizontal, 8);
+        log_files_row.append(&Label::new(Some("Older logs kept per game")));
+        log_files_row.append(&log_files_spin);
+        let logs_hint = Label::new(Some(
+            "Every launch starts a new last_launch.log in the game's capsule home and the \
+             previous ones are numbered. Clear logs on a game card removes them all.",
+        ));
+        logs_hint.set_halign(gtk4::Align::Start);
+        logs_hint.set_wrap(true);
+        logs_hint.set_css_classes(&["muted"]);
+
+        layout.append(&logs_title);
+        layout.append(&log_size_row);
+        layout.append(&log_files_row);
+        layout.append(&logs_hint);
+
+        let discord_title = Label::new(Some("Discord"));
+        discord_title.set_halign(gtk4::Align::Start);
+        discord_title.set_css_classes(&["section-title"]);
+        discord_title.set_margin_top(8);
+
+        let discord_check = CheckButton::with_label("Show the game I'm playing on Discord");
+        discord_check.set_active(discord_settings.enabled);
+
+        let client_id_label = Label::new(Some("Application ID"));
+        client_id_label.set_halign(gtk4::Align::Start);
+        let client_id_entry = Entry::new();
+        client_id_entry.set_text(&discord_settings.client_id);
+        client_id_entry.set_placeholder_text(Some("e.g. 1234567890123456789"));
+        client_id_entry.set_hexpand(true);
+        let client_id_row = Box::new(Orientation::Horizontal, 8);
+        client_id_row.append(&client_id_label);
+        client_id_row.append(&client_id_entry);
+        client_id_row.set_sensitive(discord_check.is_active());
+        let client_id_row_toggle = client_id_row.clone();
+        discord_check.connect_toggled(move |check| {
+            client_id_row_toggle.set_sensitive(check.is_active());
+        });
+
+        let discord_hint = Label::new(Some(
+            "Create an application at discord.com/developers and paste its ID here. \
+             Discord shows the game as \"Playing <game> via LinuxBoy\" with the time played.",
+        ));
+        discord_hint.set_halign(gtk4::Align::Start);
+        discord_hint.set_wrap(true);
+        discord_hint.set_css_classes(&["muted"]);
+
+        layout.append(&discord_title);
+        layout.append(&discord_check);
+        layout.append(&client_id_row);
+        layout.append(&discord_hint);
+
+        let igdb_title = Label::new(Some("IGDB"));
+        igdb_title.set_halign(gtk4::Align::Start);
+        igdb_title.set_css_classes(&["section-title"]);
+        igdb_title.set_margin_top(8);
+
+        let igdb_id_label = Label::new(Some("Client ID"));
+        igdb_id_label.set_halign(gtk4::Align::Start);
+        let igdb_id_entry = Entry::new();
+        igdb_id_entry.set_text(&igdb_settings.client_id);
+        igdb_id_entry.set_hexpand(true);
+        let igdb_id_row = Box::new(Orientation::Horizontal, 8);
+        igdb_id_row.append(&igdb_id_label);
+        igdb_id_row.append(&igdb_id_entry);
+
+        let igdb_secret_label = Label::new(Some("Client secret"));
+        igdb_secret_label.set_halign(gtk4::Align::Start);
+        let igdb_secret_entry = Entry::new();
+        igdb_secret_entry.set_text(&igdb_settings.client_secret);
+        igdb_secret_entry.set_visibility(false);
+        igdb_secret_entry.set_hexpand(true);
+        let igdb_secret_row = Box::new(Orientation::Horizontal, 8);
+        igdb_secret_row.append(&igdb_secret_label);
+        igdb_secret_row.append(&igdb_secret_entry);
+
+        let igdb_hint = Label::new(Some(
+            "Register an application at dev.twitch.tv/console and paste its client ID and \
+             secret here to look games up on IGDB from Game Details. Leave empty to turn \
+             lookups off.",
+        ));
+        igdb_hint.set_halign(gtk4::Align::Start);
+        igdb_hint.set_wrap(true);
+        igdb_hint.set_css_classes(&["muted"]);
+
+        layout.append(&igdb_title);
+        layout.append(&igdb_id_row);
+        layout.append(&igdb_secret_row);
+        layout.append(&igdb_hint);
+        content.append(&layout);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender.input(MainWindowMsg::DeckSettingsSaved(DeckSettings {
+                    tailored_defaults: defaults_check.is_active(),
+                    sd_card_library: sd_check.is_active(),
+                    prompt_add_to_steam: steam_check.is_active(),
+                }));
+                sender.input(MainWindowMsg::DiscordSettingsSaved(DiscordSettings {
+                    enabled: discord_check.is_active(),
+                    client_id: client_id_entry.text().trim().to_string(),
+                }));
+                sender.input(MainWindowMsg::LogSettingsSaved(LogSettings {
+                    max_size_mb: log_size_spin.value_as_int().max(1) as u64,
+                    max_files: log_files_spin.value_as_int().max(0) as u32,
+                }));
+                sender.input(MainWindowMsg::IgdbSettingsSaved(IgdbSettings {
+                    client_id: igdb_id_entry.text().trim().to_string(),
+                    client_secret: igdb_secret_entry.text().trim().to_string(),
+                }));
+                sender.input(MainWindowMsg::RunnerSettingsSaved(RunnerSettings {
+                    fallback: FallbackRunner::ALL
+                        .get(fallback_dropdown.selected() as usize)
+                        .copied()
+                        .unwrap_or_default(),
+                }));
+                let default_template = template_dropdown
+                    .selected_item()
+                    .and_downcast::<gtk4::StringObject>()
+                    .map(|item| item.string().to_string())
+                    .filter(|_| template_dropdown.selected() > 0);
+                sender.input(MainWindowMsg::DefaultTemplateChosen(default_template));
+                let buffer = default_env_view.buffer();
+                let env_text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+                sender.input(MainWindowMsg::NewGameDefaultsSaved(NewGameDefaults {
+                    store: MainWindow::optional_text(&default_store_entry),
+                    install_vcredist: default_vcredist_check.is_active(),
+                    install_dxweb: default_dxweb_check.is_active(),
+                    xalia_enabled: default_xalia_check.is_active(),
+                    protonfixes_disable: default_pf_disable_check.is_active(),
+                    env_vars: NewGameDefaults::parse_env_vars(&env_text),
+                }));
+            }
+            sender.input(MainWindowMsg::PreferencesDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.preferences_dialog = Some(dialog);
+    }
+
+    /// Ask whether to add a newly installed game to Steam, on a Deck with
+    /// the prompt turned on.
+    fn offer_add_to_steam(&mut self, sender: ComponentSender<Self>, capsule: &Capsule) {
+        if self.add_to_steam_dialog.is_some()
+            || self.launch_only.is_some()
+            || !self.steam_deck.detected()
+            || !self.deck_settings.prompt_add_to_steam
+            || SteamShortcuts::shortcuts_file().is_none()
+        {
+            return;
+        }
+
+        let dialog = Dialog::builder()
+            .title("Add to Steam")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Not Now", ResponseType::Cancel);
+        dialog.add_button("Add to Steam", ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let message = Label::new(Some(&format!(
+            "Add {} to your Steam library so it can be started from Game Mode?",
+            capsule.name
+        )));
+        message.set_halign(gtk4::Align::Start);
+        message.set_wrap(true);
+        layout.append(&message);
+        if SteamShortcuts::steam_running() {
+            let hint = Label::new(Some(
+                "Steam is running. Restart it afterwards for the game to show up.",
+            ));
+            hint.set_halign(gtk4::Align::Start);
+            hint.set_wrap(true);
+            hint.set_css_classes(&["muted"]);
+            layout.append(&hint);
+        }
+        content.append(&layout);
+
+        let capsule_dir = capsule.capsule_dir.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender.input(MainWindowMsg::AddToSteam(capsule_dir.clone()));
+            }
+            sender.input(MainWindowMsg::AddToSteamDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.add_to_steam_dialog = Some(dialog);
+    }
+
+    /// Offer one launch with safe settings after the game crashed on start,
+    /// to tell a bad setting apart from a broken game or prefix. When the
+    /// prefix was snapshotted before a risky change since the last launch
+    /// that worked, offer to undo that change too. `safe_mode_failed` means
+    /// the crash happened with safe settings already, so only the undo is
+    /// offered.
+    fn offer_safe_retry(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: &Path,
+        safe_mode_failed: bool,
+    ) {
+        // Started from Steam, the window closes with the game.
+        if self.safe_retry_dialog.is_some() || self.launch_only.is_some() {
+            return;
+        }
+        let capsule = match Capsule::load_from_dir(capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+        let undo_snapshot = PlayHistory::load(&capsule)
+            .last_success()
+            .map(|session| session.started + session.duration_secs)
+            .and_then(|since| {
+                SnapshotManager::for_capsule(&capsule).latest_automatic_since(since)
+            });
+        if safe_mode_failed && undo_snapshot.is_none() {
+            eprintln!(
+                "{:?} crashed on start even with safe settings; the prefix or the game files \
+                 are the more likely cause",
+                capsule_dir
+            );
+            return;
+        }
+
+        let dialog = Dialog::builder()
+            .title("Game Crashed")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Not Now", ResponseType::Cancel);
+        if undo_snapshot.is_some() {
+            dialog.add_button("Undo Last Change", ResponseType::Other(1));
+        }
+        if !safe_mode_failed {
+            dialog.add_button("Retry with Safe Settings", ResponseType::Accept);
+        }
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let message = Label::new(Some(&if safe_mode_failed {
+            format!(
+                "{} closed with an error right after starting, even with safe settings.",
+                capsule.name
+            )
+        } else {
+            format!(
+                "{} closed with an error right after starting. Try it once with safe settings?",
+                capsule.name
+            )
+        }));
+        message.set_halign(gtk4::Align::Start);
+        message.set_wrap(true);
+        layout.append(&message);
+        if !safe_mode_failed {
+            let hint = Label::new(Some(
+                "This run turns off Xalia, gamescope and MangoHud, uses WineD3D instead of DXVK \
+                 and starts the game in a window. Your settings are not changed; if the game \
+                 works this way, turn those back on one at a time to find the culprit.",
+            ));
+            hint.set_halign(gtk4::Align::Start);
+            hint.set_wrap(true);
+            hint.set_css_classes(&["muted"]);
+            layout.append(&hint);
+        }
+        if let Some(info) = &undo_snapshot {
+            let (title, detail) = Self::snapshot_row_text(info);
+            let undo_hint = Label::new(Some(&format!(
+                "The prefix changed since the game last worked. Undo Last Change restores \
+                 the snapshot \"{}\" ({}); game files and saves in the game folder are kept.",
+                title, detail
+            )));
+            undo_hint.set_halign(gtk4::Align::Start);
+            undo_hint.set_wrap(true);
+            undo_hint.set_css_classes(&["muted"]);
+            layout.append(&undo_hint);
+        }
+        content.append(&layout);
+
+        let capsule_dir = capsule_dir.to_path_buf();
+        let undo_id = undo_snapshot.map(|info| info.id);
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender.input(MainWindowMsg::LaunchGameSafe(capsule_dir.clone()));
+            } else if response == ResponseType::Other(1) {
+                if let Some(id) = undo_id.clone() {
+                    sender.input(MainWindowMsg::SnapshotRequested {
+                        capsule_dir: capsule_dir.clone(),
+                        action: SnapshotAction::Restore(id),
+                    });
+                }
+            }
+            sender.input(MainWindowMsg::SafeRetryDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.safe_retry_dialog = Some(dialog);
+    }
+
+    fn add_to_steam(capsule: &Capsule) {
+        let result = SteamShortcuts::linuxboy_shortcut(&capsule.name, &capsule.capsule_dir)
+            .and_then(|shortcut| SteamShortcuts::add(&shortcut));
+        match result {
+            Ok(true) if SteamShortcuts::steam_running() => {
+                println!("Restart Steam to see {} in the library", capsule.name)
+            }
+            Ok(true) => {}
+            Ok(false) => println!("{} is already in Steam", capsule.name),
+            Err(e) => eprintln!("Failed to add {} to Steam: {:#}", capsule.name, e),
+        }
+    }
+
+    fn open_network_dialog(&mut self, sender: ComponentSender<Self>) {
+        if self.network_dialog.is_some() {
+            return;
+        }
+
+        let settings = ProxySettings::load();
+        let dialog = Dialog::builder()
+            .title("Network")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Save", ResponseType::Accept);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some("Proxy"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let hint = Label::new(Some(
+            "Used for UMU database sync, GitHub lookups and downloads. Without a proxy here, \
+             the HTTP_PROXY, HTTPS_PROXY and NO_PROXY environment variables apply.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+
+        let enabled_check = CheckButton::with_label("Use a proxy");
+        enabled_check.set_active(settings.enabled);
+
+        let address_row = Box::new(Orientation::Horizontal, 8);
+        let host_entry = Entry::new();
+        host_entry.set_hexpand(true);
+        host_entry.set_placeholder_text(Some("proxy.example.com"));
+        host_entry.set_text(&settings.host);
+        let port_spin = SpinButton::with_range(0.0, 65535.0, 1.0);
+        port_spin.set_value(settings.port as f64);
+        address_row.append(&host_entry);
+        address_row.append(&port_spin);
+
+        let user_entry = Entry::new();
+        user_entry.set_placeholder_text(Some("Username (optional)"));
+        user_entry.set_text(&settings.username);
+        let password_entry = Entry::new();
+        password_entry.set_placeholder_text(Some("Password"));
+        password_entry.set_visibility(false);
+        password_entry.set_text(&settings.password);
+
+        let fields = [
+            address_row.clone().upcast::<gtk4::Widget>(),
+            user_entry.clone().upcast(),
+            password_entry.clone().upcast(),
+        ];
+        for field in &fields {
+            field.set_sensitive(settings.enabled);
+        }
+        enabled_check.connect_toggled(move |check| {
+            for field in &fields {
+                field.set_sensitive(check.is_active());
+            }
+        });
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&enabled_check);
+        layout.append(&address_row);
+        layout.append(&user_entry);
+        layout.append(&password_entry);
+        content.append(&layout);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender.input(MainWindowMsg::NetworkSettingsSaved(ProxySettings {
+                    enabled: enabled_check.is_active(),
+                    host: host_entry.text().trim().to_string(),
+                    port: port_spin.value() as u16,
+                    username: user_entry.text().trim().to_string(),
+                    password: password_entry.text().to_string(),
+                }));
+            }
+            sender.input(MainWindowMsg::NetworkDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.network_dialog = Some(dialog);
+    }
+
+    /// Scan every prefix for files other prefixes hold too and offer to
+    /// make the copies share one file's data.
+    fn open_storage_dialog(&mut self, sender: ComponentSender<Self>) {
+        if self.storage_dialog.is_some() {
+            return;
+        }
+
+        let dialog = Dialog::builder()
+            .title("Storage")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.set_default_width(460);
+        dialog.add_button("Close", ResponseType::Close);
+        dialog.add_button("Deduplicate", ResponseType::Accept);
+        dialog.set_response_sensitive(ResponseType::Accept, false);
+        if let Some(button) = dialog.widget_for_response(ResponseType::Accept) {
+            button.add_css_class("suggested-action");
+        }
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let title = Label::new(Some("Shared prefix files"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_css_classes(&["section-title"]);
+
+        let hint = Label::new(Some(
+            "Every prefix holds its own copy of the Proton prefix files. Identical copies \
+             can share one file's data through reflinks on filesystems that support them, \
+             such as Btrfs and XFS. Game folders and user data are left alone.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_xalign(0.0);
+        hint.set_css_classes(&["muted"]);
+
+        let status = Label::new(Some("Scanning prefixes..."));
+        status.set_halign(gtk4::Align::Start);
+        status.set_wrap(true);
+        status.set_xalign(0.0);
+
+        let progress = ProgressBar::new();
+
+        let hardlink_check =
+            CheckButton::with_label("Use hardlinks where reflinks aren't supported");
+        hardlink_check.set_tooltip_text(Some(
+            "Hardlinked copies are one file: a game that changes it in place changes it \
+             for every prefix",
+        ));
+
+        layout.append(&title);
+        layout.append(&hint);
+        layout.append(&status);
+        layout.append(&progress);
+        layout.append(&hardlink_check);
+        content.append(&layout);
+
+        let sender_clone = sender.clone();
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                sender_clone.input(MainWindowMsg::DedupeRequested(DedupeOptions {
+                    allow_hardlinks: hardlink_check.is_active(),
+                }));
+                return;
+            }
+            sender_clone.input(MainWindowMsg::StorageDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.storage_dialog = Some(dialog);
+        self.storage_status_label = Some(status);
+        self.storage_progress_bar = Some(progress);
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.dedupe_scan_cancel = Some(cancel.clone());
+        let capsules = self.capsules.clone();
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let mut last_report = Instant::now();
+            let result = PrefixDedupe::scan(&capsules, &mut |hashed, total| {
+                if last_report.elapsed() >= Duration::from_millis(100) {
+                    last_report = Instant::now();
+                    let _ = sender_clone.input(MainWindowMsg::DedupeScanProgress { hashed, total });
+                }
+                !cancel.load(Ordering::Relaxed)
+            })
+            .map_err(|e| e.to_string());
+            let _ = sender_clone.input(MainWindowMsg::DedupeScanFinished(result));
+        });
+    }
+
+    /// Offer to restore capsules whose metadata.json no longer parses, which
+    /// would otherwise just vanish from the library.
+    fn open_metadata_recovery_dialog(&mut self, sender: ComponentSender<Self>, damaged: Vec<PathBuf>) {
+        if self.metadata_recovery_dialog.is_some() {
+            return;
+        }
+        self.recovery_offered.extend(damaged.iter().cloned());
+
+        let dialog = Dialog::builder()
+            .title("Damaged Games")
+            .modal(true)
+            .transient_for(&self.root_window)
+            .build();
+        dialog.add_button("Close", ResponseType::Close);
+
+        let content = dialog.content_area();
+        let layout = Box::new(Orientation::Vertical, 8);
+        layout.set_margin_all(12);
+
+        let hint = Label::new(Some(
+            "These games have a missing or unreadable metadata.json and are hidden from the library. \
+             Restoring puts back the last good copy; the damaged file is kept as metadata.json.damaged.",
+        ));
+        hint.set_halign(gtk4::Align::Start);
+        hint.set_wrap(true);
+        hint.set_css_classes(&["muted"]);
+        layout.append(&hint);
+
+        for capsule_dir in damaged {
+            let row = Box::new(Orientation::Horizontal, 8);
+            let name = capsule_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| capsule_dir.to_string_lossy().to_string());
+            let label = Label::new(Some(&name));
+            label.set_halign(gtk4::Align::Start);
+            label.set_hexpand(true);
+            row.append(&label);
+
+            let restore_button = Button::with_label("Restore from backup");
+            if Capsule::has_metadata_backup(&capsule_dir) {
+                let sender_clone = sender.clone();
+                restore_button.connect_clicked(move |button| {
+                    button.set_sensitive(false);
+                    sender_clone.input(MainWindowMsg::RecoverCapsuleMetadata(capsule_dir.clone()));
+                });
+            } else {
+                restore_button.set_sensitive(false);
+                restore_button.set_tooltip_text(Some("No backup of this game's metadata"));
+            }
+            row.append(&restore_button);
+            layout.append(&row);
+        }
+        content.append(&layout);
+
+        dialog.connect_response(move |dialog, _| {
+            sender.input(MainWindowMsg::MetadataRecoveryDialogClosed);
+            dialog.close();
+        });
+
+        dialog.show();
+        self.metadata_recovery_dialog = Some(dialog);
+    }
+
+    fn start_installer(
+        &mut self,
+        sender: &ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        mut metadata: CapsuleMetadata,
+        installer_path: PathBuf,
+    ) {
+        let proton_path = match self.runner_runtime() {
+            Some(path) => path,
+            None => return,
+        };
+
+        // The installer's payload is usually compressed, so it needs at least
+        // twice its own size once unpacked.
+        let installer_size = fs::metadata(&installer_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        let install_target = metadata
+            .game_dir
+            .as_deref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| capsule_dir.clone());
+        if let Err(e) =
+            file_ops::ensure_free_space(&install_target, installer_size * INSTALLER_SPACE_FACTOR)
+        {
+            eprintln!("Not starting installer: {}", e);
+            return;
+        }
+
+        let home_path = capsule_dir.join(format!("{}.AppImage.home", metadata.name));
+        let prefix_path = home_path.join("prefix");
+        if let Err(e) = fs::create_dir_all(prefix_path.join("drive_c")) {
+            eprintln!("Failed to create prefix: {}", e);
+            return;
+        }
+        if let Err(e) = fs::create_dir_all(prefix_path.join("games")) {
+            eprintln!("Failed to create games folder: {}", e);
+            return;
+        }
+        if let Some(game_dir) = metadata.game_dir.as_deref() {
+            let path = PathBuf::from(game_dir);
+            if let Err(e) = fs::create_dir_all(&path) {
+                eprintln!("Failed to create default game folder: {}", e);
+                return;
+            }
+        }
+
+        let installer_text = installer_path.to_string_lossy().to_string();
+        // Another file than the recorded one needs its own checksum.
+        if metadata.installer_path.as_deref() != Some(installer_text.as_str()) {
+            metadata.installer_sha256 = None;
+        }
+        let hash_installer = metadata.installer_sha256.is_none();
+        metadata.installer_path = Some(installer_text);
+        metadata.install_state = InstallState::Installing;
+
+        let capsule = Capsule {
+            name: metadata.name.clone(),
+            capsule_dir: capsule_dir.clone(),
+            home_path,
+            metadata: metadata.clone(),
+        };
+
+        if let Err(e) = capsule.save_metadata() {
+            eprintln!("Failed to save metadata: {}", e);
+            return;
+        }
+
+        self.preparing_installs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+
+        if hash_installer {
+            let sender_clone = sender.clone();
+            let capsule_dir = capsule_dir.clone();
+            let installer_path = installer_path.clone();
+            thread::spawn(move || {
+                let result = InstallerSource::sha256(&installer_path).map_err(|e| e.to_string());
+                sender_clone.input(MainWindowMsg::InstallerHashed {
+                    capsule_dir,
+                    installer_path,
+                    result,
+                });
+            });
+        }
+
+        let env_metadata = metadata.clone();
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            println!("Preloading UMU runtime...");
+            if !Self::run_umu_preflight(&prefix_path, &proton_path, &env_metadata) {
+                eprintln!("UMU runtime preload failed.");
+                let _ = sender_clone.input(MainWindowMsg::InstallerFinished {
+                    capsule_dir,
+                    success: false,
+                });
+                return;
+            }
+
+            let mut cmd =
+                Self::installer_command(&prefix_path, &proton_path, &env_metadata, &installer_path);
+
+            unsafe {
+                cmd.pre_exec(|| {
+                    libc::setpgid(0, 0);
+                    Ok(())
+                });
+            }
+
+            let mut child = match cmd.spawn() {
+                Ok(child) => child,
+                Err(e) => {
+                    eprintln!("Failed to launch installer: {}", e);
+                    let _ = sender_clone.input(MainWindowMsg::InstallerFinished {
+                        capsule_dir,
+                        success: false,
+                    });
+                    return;
+                }
+            };
+
+            let pid = child.id() as i32;
+            let pgid = unsafe { libc::getpgid(pid) };
+            if pgid > 0 {
+                let _ = sender_clone.input(MainWindowMsg::InstallerStarted {
+                    capsule_dir: capsule_dir.clone(),
+                    pgid,
+                });
+            }
+
+            let success = child.wait().map(|status| status.success()).unwrap_or(false);
+            let _ = sender_clone.input(MainWindowMsg::InstallerFinished {
+                capsule_dir,
+                success,
+            });
+        });
+    }
+
+    /// The command running `installer_path` in the prefix, `.msi` files
+    /// through msiexec with the MSI options in `metadata`.
+    fn installer_command(
+        prefix_path: &PathBuf,
+        proton_path: &PathBuf,
+        metadata: &CapsuleMetadata,
+        installer_path: &Path,
+    ) -> Command {
+        let mut cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
+        // Avoid Xalia UI automation errors during installers.
+        cmd.env("PROTON_USE_XALIA", "0");
+        let in_desktop = Self::push_virtual_desktop(&mut cmd, metadata);
+        if Self::is_msi_file(installer_path) {
+            cmd.arg("msiexec");
+            cmd.arg("/i");
+            cmd.arg(Self::host_path_to_windows(installer_path));
+            if metadata.msi_silent {
+                cmd.arg("/qn");
+            }
+            cmd.args(&metadata.msi_properties);
+        } else if in_desktop {
+            cmd.arg(Self::host_path_to_windows(installer_path));
+        } else {
+            cmd.arg(installer_path);
+        }
+        host::command(cmd)
+    }
+
+    /// The Proton-GE runtime Windows programs are run with, or `None` after
+    /// saying why they can't be run. System Wine needs none, so it gets an
+    /// empty path when no runtime is installed.
+    fn runner_runtime(&self) -> Option<PathBuf> {
+        let runner = match Runner::detect() {
+            Ok(runner) => runner,
+            Err(e) => {
+                eprintln!("{}", e);
+                return None;
+            }
+        };
+        match self.runtime_mgr.latest_installed() {
+            Ok(Some(path)) => Some(path),
+            Ok(None) if runner == Runner::Wine => Some(PathBuf::new()),
+            Ok(None) => {
+                eprintln!("No Proton-GE runtime installed");
+                None
+            }
+            Err(e) => {
+                eprintln!("Failed to resolve Proton-GE runtime: {}", e);
+                None
+            }
+        }
+    }
+
+    fn umu_base_command(
+        prefix_path: &PathBuf,
+        proton_path: &PathBuf,
+        metadata: &CapsuleMetadata,
+    ) -> Command {
+        // Callers check `Runner::detect` first; umu-run stays the default.
+        Runner::detect()
+            .unwrap_or(Runner::Umu)
+            .command(prefix_path, proton_path, metadata)
+    }
+
+    /// Turn on GameMode and MangoHud for the game when enabled and installed.
+    /// This is what `gamemoderun` and `mangohud` do, minus the wrapper
+    /// scripts, so umu-run stays the program. The FPS cap goes to MangoHud
+    /// when it runs, since it limits every graphics API, and to DXVK otherwise.
+    fn push_gaming_tools(&self, cmd: &mut Command, metadata: &CapsuleMetadata) {
+        if metadata.gamemode_enabled && self.system_check.gamemode_installed {
+            let preload = match std::env::var("LD_PRELOAD") {
+                Ok(existing) if !existing.is_empty() => {
+                    format!("libgamemodeauto.so.0:{}", existing)
+                }
+                _ => "libgamemodeauto.so.0".to_string(),
+            };
+            cmd.env("LD_PRELOAD", preload);
+        }
+        let mangohud = metadata.mangohud_enabled && self.system_check.mangohud_installed;
+        if mangohud {
+            cmd.env("MANGOHUD", "1");
+        }
+        match metadata.fps_limit.filter(|&limit| limit > 0) {
+            Some(limit) if mangohud => {
+                let existing = cmd
+                    .get_envs()
+                    .find(|(key, _)| *key == "MANGOHUD_CONFIG")
+                    .and_then(|(_, value)| value)
+                    .map(|value| value.to_string_lossy().to_string())
+                    .filter(|value| !value.is_empty());
+                let config = match existing {
+                    Some(existing) => format!("{},fps_limit={}", existing, limit),
+                    None => format!("fps_limit={}", limit),
+                };
+                cmd.env("MANGOHUD_CONFIG", config);
+            }
+            Some(_) => {
+                for option in metadata.fps_limit_dxvk_sets() {
+                    cmd.arg(format!("-pf_dxvk_set={}", option));
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Start the program inside `explorer /desktop` when the capsule uses a
+    /// virtual desktop. Returns true if it did, in which case the program must
+    /// be passed as a Windows path.
+    fn push_virtual_desktop(cmd: &mut Command, metadata: &CapsuleMetadata) -> bool {
+        match metadata.virtual_desktop.as_deref().map(str::trim) {
+            Some(size) if !size.is_empty() => {
+                cmd.arg("explorer");
+                cmd.arg(format!("/desktop=LinuxBoy,{}", size));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn install_directx_redist(
+        prefix_path: &PathBuf,
+        proton_path: &PathBuf,
+        metadata: &CapsuleMetadata,
+        redist_path: &Path,
+        sender: &ComponentSender<Self>,
+    ) -> bool {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let temp_dir_name = format!("linuxboy-dxredist-{}", nanos);
+        let host_temp_dir = prefix_path
+            .join("drive_c")
+            .join("linuxboy-temp")
+            .join(&temp_dir_name);
+        let windows_temp_dir = format!("C:\\\\linuxboy-temp\\\\{}", temp_dir_name);
+
+        if let Err(e) = fs::create_dir_all(&host_temp_dir) {
+            eprintln!("Failed to create DirectX temp dir: {}", e);
+            sender.input(MainWindowMsg::DependencyLog(format!(
+                "Failed to create DirectX temp dir: {}",
+                e
+            )));
+            return false;
+        }
+
+        let extract_arg = format!("/T:{}", windows_temp_dir);
+        let mut extract_cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
+        extract_cmd.env("PROTON_USE_XALIA", "0");
+        extract_cmd.arg(redist_path);
+        extract_cmd.arg("/Q");
+        extract_cmd.arg(extract_arg);
+        extract_cmd.arg("/C");
+        sender.input(MainWindowMsg::DependencyLog("Extracting DirectX redist...".to_string()));
+        if !Self::run_dependency_command(host::command(extract_cmd), sender) {
+            let _ = fs::remove_dir_all(&host_temp_dir);
+            return false;
+        }
+
+        let dxsetup_path = host_temp_dir.join("DXSETUP.exe");
+        if !dxsetup_path.is_file() {
+            eprintln!("DirectX redist extraction missing DXSETUP.exe");
+            sender.input(MainWindowMsg::DependencyLog(
+                "DirectX redist extraction missing DXSETUP.exe".to_string(),
+            ));
+            let _ = fs::remove_dir_all(&host_temp_dir);
+            return false;
+        }
+
+        let mut install_cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
+        install_cmd.env("PROTON_USE_XALIA", "0");
+        install_cmd.arg(&dxsetup_path);
+        install_cmd.arg("/silent");
+        sender.input(MainWindowMsg::DependencyLog("Running DXSETUP.exe /silent".to_string()));
+        let success = Self::run_dependency_command(host::command(install_cmd), sender);
+        let _ = fs::remove_dir_all(&host_temp_dir);
+        success
+    }
+
+    /// Import `content` as a .reg file with `regedit /S`. The file is staged
+    /// under drive_c so Wine sees it at a plain Windows path.
+    fn import_registry(
+        prefix_path: &PathBuf,
+        proton_path: &PathBuf,
+        metadata: &CapsuleMetadata,
+        content: &[u8],
+    ) -> Result<(), String> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let file_name = format!("linuxboy-{}.reg", nanos);
+        let host_temp_dir = prefix_path.join("drive_c").join("linuxboy-temp");
+        let host_path = host_temp_dir.join(&file_name);
+        fs::create_dir_all(&host_temp_dir)
+            .and_then(|_| fs::write(&host_path, content))
+            .map_err(|e| format!("Failed to stage registry file: {}", e))?;
+
+        let mut cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
+        cmd.env("PROTON_USE_XALIA", "0");
+        cmd.arg("regedit");
+        cmd.arg("/S");
+        cmd.arg(format!("C:\\linuxboy-temp\\{}", file_name));
+        let result = match host::command(cmd).status() {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => Err(format!("regedit exited with {}", status)),
+            Err(e) => Err(format!("Failed to run regedit: {}", e)),
+        };
+        let _ = fs::remove_file(&host_path);
+        result
+    }
+
+    fn start_registry_import(
+        &mut self,
+        sender: ComponentSender<Self>,
+        capsule_dir: PathBuf,
+        content: Vec<u8>,
+        snapshot_label: Option<String>,
+        success_message: String,
+    ) {
+        let capsule = match Capsule::load_from_dir(&capsule_dir) {
+            Ok(capsule) => capsule,
+            Err(e) => {
+                eprintln!("Failed to load capsule: {}", e);
+                return;
+            }
+        };
+
+        let proton_path = match self.runner_runtime() {
+            Some(path) => path,
+            None => return,
+        };
+
+        self.registry_jobs.insert(capsule_dir.clone());
+        self.refresh_game_cards();
+
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            let prefix_path = capsule.home_path.join("prefix");
+            if let Some(label) = snapshot_label {
+                if let Err(e) = SnapshotManager::new(&capsule.home_path).create(&label, true) {
+                    eprintln!("Failed to snapshot prefix before registry import: {}", e);
+                }
+            }
+            let result = Self::import_registry(&prefix_path, &proton_path, &capsule.metadata, &content)
+                .map(|_| format!("{} for {}", success_message, capsule.name));
+            let _ = sender_clone.input(MainWindowMsg::RegistryJobFinished { capsule_dir, result });
+        });
+    }
+
+    fn run_umu_preflight(
+        prefix_path: &PathBuf,
+        proton_path: &PathBuf,
+        metadata: &CapsuleMetadata,
+    ) -> bool {
+        let mut cmd = Self::umu_base_command(prefix_path, proton_path, metadata);
+        // Avoid Xalia UI automation errors during preflight.
+        cmd.env("PROTON_USE_XALIA", "0");
+        // Run a harmless command to force prefix/runtime initialization.
+        cmd.arg("cmd");
+        cmd.arg("/c");
+        cmd.arg("exit");
+        match host::command(cmd).status() {
+            Ok(status) => status.success(),
+            Err(e) => {
+                eprintln!("Failed to preload UMU runtime: {}", e);
+                false
+            }
+        }
+    }
+
+    fn update_selection_bar(&self) {
+        let active = self.select_mode && !self.tv_mode;
+        self.select_button.set_visible(!self.tv_mode);
+        self.select_button.set_label(if self.select_mode { "Done" } else { "Select" });
+        self.selection_bar.set_visible(active);
+        self.selection_count_label.set_label(&match self.selected_games.len() {
+            0 => "Select games for a batch action".to_string(),
+            1 => "1 game selected".to_string(),
+            count => format!("{} games selected", count),
+        });
+        self.selection_actions.set_sensitive(!self.selected_games.is_empty());
+    }
+
+    /// Selected capsules, in library order.
+    fn selected_capsule_dirs(&self) -> Vec<PathBuf> {
+        self.capsules
+            .iter()
+            .map(|capsule| capsule.capsule_dir.clone())
+            .filter(|dir| self.selected_games.contains(dir))
+            .collect()
+    }
+
+    fn rebuild_selection_template_menu(&self, sender: ComponentSender<Self>) {
+        while let Some(child) = self.selection_template_list.first_child() {
+            self.selection_template_list.remove(&child);
+        }
+        let names = self.templates.names();
+        self.selection_template_menu.set_sensitive(!names.is_empty());
+        self.selection_template_menu.set_tooltip_text(Some(if names.is_empty() {
+            "Save a template from a game's settings first"
+        } else {
+            "Replace the launch settings of the selected games"
+        }));
+        for name in names {
+            let button = Button::with_label(&name);
+            button.add_css_class("flat");
+            button.set_halign(gtk4::Align::Fill);
+            if let Some(child) = button.child() {
+                child.set_halign(gtk4::Align::Start);
+            }
+            let sender_clone = sender.clone();
+            let menu = self.selection_template_menu.clone();
+            button.connect_clicked(move |_| {
+                menu.popdown();
+                sender_clone.input(MainWindowMsg::BatchApplyTemplate(name.clone()));
+            });
+            self.selection_template_list.append(&button);
+        }
+    }
+
+    fn card_state(&self, capsule_dir: &Path) -> CardState {
+        CardState {
+            tv_mode: self.tv_mode,
+            select_mode: self.select_mode && !self.tv_mode,
+            selected: self.selected_games.contains(capsule_dir),
+            installer_running: self.active_installs.contains_key(capsule_dir),
+            preparing: self.preparing_installs.contains(capsule_dir),
+            deps_running: self.dependency_installs.contains(capsule_dir),
+            snapshot_running: self.snapshot_jobs.contains(capsule_dir),
+            winetricks_running: self.winetricks_jobs.contains(capsule_dir),
+            registry_running: self.registry_jobs.contains(capsule_dir),
+            shutdown_running: self.shutdown_jobs.contains(capsule_dir),
+            repair_running: self.repair_jobs.contains(capsule_dir),
+            uninstall_running: self.uninstall_jobs.contains(capsule_dir),
+            patch_running: self.patch_jobs.contains(capsule_dir),
+            mods_running: self.mod_jobs.contains(capsule_dir),
+            archive_running: self.archive_jobs.contains(capsule_dir),
+            sync_running: self.cloud_sync_jobs.contains(capsule_dir),
+            game_running: self.active_games.contains_key(capsule_dir),
+        }
+    }
+
+    /// Switch between the desktop list and the big-screen layout: fullscreen,
+    /// one horizontal row of large tiles, and only what a controller needs.
+    fn apply_tv_mode(&self) {
+        let cards = self.game_cards.widget();
+        if self.tv_mode {
+            self.root_window.fullscreen();
+            self.root_window.add_css_class("tv-mode");
+            cards.set_orientation(Orientation::Horizontal);
+            cards.set_spacing(24);
+            self.library_page.set_halign(gtk4::Align::Fill);
+            self.library_body.set_halign(gtk4::Align::Fill);
+            self.library_body.set_width_request(-1);
+        } else {
+            self.root_window.unfullscreen();
+            self.root_window.remove_css_class("tv-mode");
+            cards.set_orientation(Orientation::Vertical);
+            cards.set_spacing(0);
+            self.library_page.set_halign(gtk4::Align::Start);
+            self.library_body.set_halign(gtk4::Align::Start);
+            self.library_body.set_width_request(840);
+        }
+    }
+
+    fn empty_library_card() -> Box {
+        let empty = Box::new(Orientation::Horizontal, 12);
+        empty.set_margin_all(8);
+        empty.set_css_classes(&["card"]);
+
+        let icon = Image::from_icon_name("applications-games-symbolic");
+        icon.set_pixel_size(28);
+        icon.set_halign(gtk4::Align::Start);
+        icon.set_valign(gtk4::Align::Start);
+
+        let text = Box::new(Orientation::Vertical, 6);
+        text.set_hexpand(true);
+
+        let title = Label::new(Some("No games yet"));
+        title.set_css_classes(&["card-title"]);
+        title.set_halign(gtk4::Align::Start);
+
+        let subtitle = Label::new(Some(
+            "Add an installer to create your first portable capsule.",
+        ));
+        subtitle.set_css_classes(&["muted"]);
+        subtitle.set_halign(gtk4::Align::Start);
+        subtitle.set_wrap(true);
+
+        text.append(&title);
+        text.append(&subtitle);
+
+        empty.append(&icon);
+        empty.append(&text);
+        empty
+    }
+
+    fn capsule_app_id(capsule: &Capsule) -> Option<u32> {
+        capsule
+            .metadata
+            .game_id
+            .as_deref()
+            .and_then(ProtonDb::steam_app_id)
+    }
+
+    /// Look up ProtonDB ratings for games matched to a Steam app ID that
+    /// aren't cached or whose cached rating is stale, one at a time.
+    fn fetch_protondb_ratings(&mut self, sender: ComponentSender<Self>) {
+        let mut app_ids: Vec<u32> = self
+            .capsules
+            .iter()
+            .filter_map(Self::capsule_app_id)
+            .filter(|app_id| {
+                !self.protondb_fetching.contains(app_id)
+                    && !self
+                        .protondb_ratings
+                        .get(app_id)
+                        .is_some_and(ProtonDbRating::is_fresh)
+            })
+            .collect();
+        app_ids.sort_unstable();
+        app_ids.dedup();
+        if app_ids.is_empty() {
+            return;
+        }
+        self.protondb_fetching.extend(app_ids.iter().copied());
+        let sender_clone = sender.clone();
+        thread::spawn(move || {
+            for app_id in app_ids {
+                let result = ProtonDb::fetch(app_id).map_err(|e| e.to_string());
+                sender_clone.input(MainWindowMsg::ProtonDbRatingFetched { app_id, result });
+            }
+        });
+    }
+
+    /// Bring the game cards in line with `capsules` and the running jobs.
+    /// Cards are matched by capsule folder and only redrawn when what they
+    /// show changed, so the list keeps its scroll position.
+    fn refresh_game_cards(&mut self) {
+        self.empty_library_card.set_visible(self.capsules.is_empty());
+        self.selected_games
+            .retain(|dir| self.capsules.iter().any(|capsule| &capsule.capsule_dir == dir));
+        self.update_selection_bar();
+        self.capsules.sort_by_cached_key(|capsule| capsule.metadata.sort_key());
+
+        let wanted: Vec<(CardInfo, CardState)> = self
+            .capsules
+            .iter()
+            .map(|capsule| {
+                let mut info = CardInfo::from_capsule(capsule);
+                info.crashing = self.crashing_games.contains(&capsule.capsule_dir);
+                info.protondb = Self::capsule_app_id(capsule).and_then(|app_id| {
+                    let tier = self.protondb_ratings.get(&app_id)?.tier?;
+                    Some((app_id, tier))
+                });
+                (info, self.card_state(&capsule.capsule_dir))
+            })
+            .collect();
+
+        let mut cards = self.game_cards.guard();
+        let mut index = 0;
+        while index < cards.len() {
+            let kept = cards.get(index).is_some_and(|card| {
+                wanted
+                    .iter()
+                    .any(|(info, _)| &info.capsule_dir == card.capsule_dir())
+            });
+            if kept {
+                index += 1;
+            } else {
+                cards.remove(index);
+            }
+        }
+
+        for (position, (info, state)) in wanted.into_iter().enumerate() {
+            let existing = (position..cards.len()).find(|&index| {
+                cards
+                    .get(index)
+                    .is_some_and(|card| card.capsule_dir() == &info.capsule_dir)
+            });
+            match existing {
+                Some(index) => {
+                    if index != position {
+                        cards.move_to(index, position);
+                    }
+                    let current = cards
+                        .get(position)
+                        .is_some_and(|card| card.is_current(&info, &state));
+                    if !current {
+                        if let Some(card) = cards.get_mut(position) {
+                            card.set(info, state);
+                        }
+                    }
+                }
+                None => {
+                    cards.insert(position, (info, state));
+                }
+            }
+        }
+    }
+}
+
+#[allow(unused_assignments)]
+#[relm4::component(pub)]
+impl SimpleComponent for MainWindow {
+    type Init = LaunchOptions;
+    type Input = MainWindowMsg;
+    type Output = ();
+
+    view! {
+        #[root]
+        ApplicationWindow {
+            set_title: Some("LinuxBoy"),
+            set_default_width: 840,
+            set_default_height: 720,
+
+            #[wrap(Some)]
+            set_child = &Box {
+                set_orientation: Orientation::Vertical,
+                set_spacing: 0,
+                set_hexpand: true,
+                set_vexpand: true,
+
+                // Header bar
+                append = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 12,
+                    set_margin_start: 20,
+                    set_margin_end: 20,
+                    set_margin_top: 24,
+                    set_margin_bottom: 14,
+                    set_css_classes: &["topbar"],
+
+                    append = &Image {
+                        set_icon_name: Some("applications-games-symbolic"),
+                        set_pixel_size: 28,
+                    },
+
+                    append = &Box {
+                        set_orientation: Orientation::Vertical,
+                        set_spacing: 0,
+
+                        append = &Label {
+                            set_label: "LinuxBoy",
+                            set_css_classes: &["app-title"],
+                            set_halign: gtk4::Align::Start,
+                        },
+                    },
+
+                    append = &Box {
+                        set_hexpand: true,
+                    },
+
+                    append = &Button {
+                        set_css_classes: &["secondary"],
+                        #[watch]
+                        set_label: if model.tv_mode { "Exit TV Mode" } else { "TV Mode" },
+                        set_tooltip_text: Some("Fullscreen layout for the couch and a controller"),
+                        connect_clicked => MainWindowMsg::ToggleTvMode,
+                    },
+
+                    append = &Button {
+                        set_css_classes: &["accent"],
+                        #[watch]
+                        set_visible: !model.tv_mode,
+                        #[wrap(Some)]
+                        set_child = &Box {
+                            set_orientation: Orientation::Horizontal,
+                            set_spacing: 6,
+
+                            append = &Image {
+                                set_icon_name: Some("list-add-symbolic"),
+                                set_pixel_size: 16,
+                            },
+
+                            append = &Label {
+                                set_label: "Add Game",
+                            },
+                        },
+                        connect_clicked => MainWindowMsg::OpenAddGame,
+                    },
+                },
+
+                // Main content area
+                append = &Box {
+                    set_orientation: Orientation::Vertical,
+                    set_hexpand: true,
+                    set_vexpand: true,
+                    set_margin_start: 12,
+                    set_margin_end: 12,
+                    set_margin_top: 10,
+
+                    #[local_ref]
+                    library_page -> Box {},
+                },
+
+                // Status bar
+                append = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 12,
+                    set_margin_start: 20,
+                    set_margin_end: 20,
+                    set_margin_top: 24,
+                    set_margin_bottom: 28,
+                    set_css_classes: &["status-bar"],
+                    #[watch]
+                    set_visible: !model.tv_mode,
+
+                    append = &Label {
+                        #[watch]
+                        set_label: &format!("{} games", model.capsules.len()),
+                        set_css_classes: &["muted"],
+                    },
+
+                    append = &Box {
+                        set_hexpand: true,
+                    },
+
+                    append = &Button {
+                        set_label: "Cloud Sync",
+                        set_css_classes: &["secondary"],
+                        connect_clicked => MainWindowMsg::OpenCloudSyncSettings,
+                    },
+
+                    append = &Button {
+                        set_label: "Network",
+                        set_css_classes: &["secondary"],
+                        connect_clicked => MainWindowMsg::OpenNetworkSettings,
+                    },
+
+                    append = &Button {
+                        set_label: "Storage",
+                        set_css_classes: &["secondary"],
+                        connect_clicked => MainWindowMsg::OpenStorage,
+                    },
+
+                    append = &Button {
+                        set_label: "Preferences",
+                        set_css_classes: &["secondary"],
+                        connect_clicked => MainWindowMsg::OpenPreferences,
+                    },
+
+                    append = &Button {
+                        #[watch]
+                        set_label: &match model.system_check.status {
+                            SystemStatus::AllInstalled => "System Ready",
+                            SystemStatus::PartiallyInstalled => "Setup Incomplete",
+                            SystemStatus::NothingInstalled => "Setup Required",
+                        },
+                        #[watch]
+                        set_css_classes: &match model.system_check.status {
+                            SystemStatus::AllInstalled => ["pill", "pill-installed"],
+                            SystemStatus::PartiallyInstalled => ["pill", "pill-warning"],
+                            SystemStatus::NothingInstalled => ["pill", "pill-missing"],
+                        },
+                        #[watch]
+                        set_tooltip_text: Some(&model.system_check.status_message()),
+                        set_halign: gtk4::Align::End,
+                        connect_clicked => MainWindowMsg::OpenSystemSetup,
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        options: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let steam_deck = SteamDeck::detect();
+        let deck_settings = DeckSettings::load();
+        let discord_settings = DiscordSettings::load();
+        if steam_deck.detected() {
+            println!("Running on {}", steam_deck.label());
+        }
+        let games_dir = Self::library_dir(&steam_deck, &deck_settings);
+
+        // Check system on startup
+        let system_check = SystemCheck::check();
+        println!("System check: {:?}", system_check.status);
+
+        let games_list = Box::new(Orientation::Vertical, 16);
+        games_list.set_margin_all(0);
+        games_list.set_valign(gtk4::Align::Start);
+        games_list.set_hexpand(true);
+
+        // Shown once a scan finds nothing, not while the first one runs.
+        let empty_library_card = Self::empty_library_card();
+        empty_library_card.set_visible(false);
+        games_list.append(&empty_library_card);
+
+        let game_cards = FactoryVecDeque::builder()
+            .launch(Box::new(Orientation::Vertical, 0))
+            .forward(sender.input_sender(), |msg| msg);
+        games_list.append(game_cards.widget());
+
+        let library_count_label = Label::new(None);
+        library_count_label.set_css_classes(&["muted"]);
+        library_count_label.set_halign(gtk4::Align::Start);
+
+        let library_page = Box::new(Orientation::Vertical, 16);
+        library_page.set_margin_all(20);
+        library_page.set_hexpand(true);
+        library_page.set_vexpand(true);
+        library_page.set_halign(gtk4::Align::Start);
+
+        let library_header = Box::new(Orientation::Horizontal, 12);
+        library_header.set_hexpand(true);
+        library_header.set_halign(gtk4::Align::Start);
+
+        let library_icon = Image::from_icon_name("folder-open-symbolic");
+        library_icon.set_pixel_size(24);
+
+        let library_title = Label::new(Some("Library"));
+        library_title.set_css_classes(&["section-title"]);
+        library_title.set_halign(gtk4::Align::Start);
+
+        let library_spacer = Box::new(Orientation::Horizontal, 0);
+        library_spacer.set_hexpand(true);
+
+        let select_button = Button::with_label("Select");
+        select_button.set_css_classes(&["secondary"]);
+        select_button.set_tooltip_text(Some("Pick several games to back up, change or delete"));
+        let sender_clone = sender.clone();
+        select_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::ToggleSelectMode);
+        });
+
+        library_header.append(&library_icon);
+        library_header.append(&library_title);
+        library_header.append(&library_spacer);
+        library_header.append(&library_count_label);
+        library_header.append(&select_button);
+
+        // Batch actions for the games picked in select mode.
+        let selection_bar = Box::new(Orientation::Horizontal, 8);
+        selection_bar.set_visible(false);
+        let selection_count_label = Label::new(None);
+        selection_count_label.set_css_classes(&["muted"]);
+        selection_count_label.set_hexpand(true);
+        selection_count_label.set_halign(gtk4::Align::Start);
+        let select_all_button = Button::with_label("Select All");
+        select_all_button.add_css_class("flat");
+        let batch_backup_button = Button::with_label("Back Up...");
+        batch_backup_button.add_css_class("flat");
+        batch_backup_button.set_tooltip_text(Some("Back up each selected game into a folder"));
+        let selection_template_list = Box::new(Orientation::Vertical, 2);
+        let selection_template_popover = Popover::new();
+        selection_template_popover.set_child(Some(&selection_template_list));
+        let selection_template_menu = MenuButton::new();
+        selection_template_menu.set_label("Apply Template");
+        selection_template_menu.add_css_class("flat");
+        selection_template_menu.set_popover(Some(&selection_template_popover));
+        let batch_delete_button = Button::with_label("Delete...");
+        batch_delete_button.add_css_class("destructive-action");
+        let selection_actions = Box::new(Orientation::Horizontal, 8);
+        selection_actions.append(&batch_backup_button);
+        selection_actions.append(&selection_template_menu);
+        selection_actions.append(&batch_delete_button);
+        selection_bar.append(&selection_count_label);
+        selection_bar.append(&select_all_button);
+        selection_bar.append(&selection_actions);
+
+        let sender_clone = sender.clone();
+        select_all_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::SelectAllGames);
+        });
+        let sender_clone = sender.clone();
+        batch_backup_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::BatchBackup);
+        });
+        let sender_clone = sender.clone();
+        batch_delete_button.connect_clicked(move |_| {
+            sender_clone.input(MainWindowMsg::BatchDelete);
+        });
+
+        let library_body = Box::new(Orientation::Vertical, 0);
+        library_body.set_halign(gtk4::Align::Start);
+        library_body.set_hexpand(true);
+        library_body.set_vexpand(true);
+        library_body.set_width_request(840);
+
+        let games_scroller = ScrolledWindow::new();
+        games_scroller.set_hexpand(true);
+        games_scroller.set_vexpand(true);
+        games_scroller.set_child(Some(&games_list));
+        library_body.append(&games_scroller);
+
+        library_page.append(&library_header);
+        library_page.append(&selection_bar);
+        library_page.append(&library_body);
+
+        let library_monitor = Self::watch_games_dir(&games_dir, sender.clone());
+        let model = MainWindow {
+            capsules: Vec::new(),
+            scan_generation: 0,
+            games_dir,
+            _library_monitor: library_monitor,
+            system_check,
+            system_setup_dialog: None,
+            runtime_mgr: RuntimeManager::new(),
+            add_game_dialog: None,
+            game_path_dialog: None,
+            capsule_folder_dialog: None,
+            backup_file_dialog: None,
+            export_dialog: None,
+            heroic_dialog: None,
+            name_dialog: None,
+            rename_dialog: None,
+            game_info_dialog: None,
+            duplicate_dialog: None,
+            snapshots_dialog: None,
+            local_fix_dialog: None,
+            diagnostics_dialog: None,
+            launch_command_dialog: None,
+            details_dialog: None,
+            details_capsule: None,
+            details_hltb_label: None,
+            details_hltb_buttons: None,
+            details_igdb: None,
+            repair_dialog: None,
+            winetricks_dialog: None,
+            winetricks_log: None,
+            cloud_sync_dialog: None,
+            network_dialog: None,
+            storage_dialog: None,
+            storage_status_label: None,
+            storage_progress_bar: None,
+            dedupe_scan_cancel: None,
+            dedupe_report: None,
+            dedupe_running: false,
+            metadata_recovery_dialog: None,
+            recovery_offered: HashSet::new(),
+            settings_dialog: None,
+            settings_exe_field: None,
+            executable_picker: None,
+            umu_match_dialog: None,
+            existing_game_dialog: None,
+            installer_check_dialog: None,
+            mods_dialog: None,
+            mods_capsule: None,
+            mods_list: None,
+            mods_status: None,
+            mods_link_check: None,
+            umu_fixes_label: None,
+            umu_fixes_key: None,
+            protonfixes_cache: HashMap::new(),
+            dependency_dialog: None,
+            dependency_progress_dialog: None,
+            dependency_step_label: None,
+            dependency_progress_bar: None,
+            dependency_log: None,
+            dependency_cancel: None,
+            existing_location_dialog: None,
+            prefix_import_dialog: None,
+            copy_dialog: None,
+            copy_status_label: None,
+            copy_progress_bar: None,
+            pending_add_mode: None,
+            pending_game_path: None,
+            pending_source_folder: None,
+            pending_game_name: None,
+            pending_game_id: None,
+            pending_store: None,
+            pending_msi_silent: false,
+            pending_win32_prefix: false,
+            pending_msi_properties: Vec::new(),
+            pending_settings_capsule: None,
+            active_installs: HashMap::new(),
+            active_games: HashMap::new(),
+            playing: Vec::new(),
+            preparing_installs: HashSet::new(),
+            dependency_installs: HashSet::new(),
+            snapshot_jobs: HashSet::new(),
+            winetricks_jobs: HashSet::new(),
+            registry_jobs: HashSet::new(),
+            shutdown_jobs: HashSet::new(),
+            repair_jobs: HashSet::new(),
+            uninstall_jobs: HashSet::new(),
+            patch_jobs: HashSet::new(),
+            mod_jobs: HashSet::new(),
+            uninstall_dialog: None,
+            archive_jobs: HashSet::new(),
+            winetricks_verbs: Vec::new(),
+            winetricks_pending: None,
+            cloud_sync_jobs: HashSet::new(),
+            cloud_sync_config: CloudSyncConfig::load(),
+            umu_entries: Vec::new(),
+            umu_loaded: false,
+            umu_load_error: None,
+            protondb_ratings: ProtonDb::load_cache(),
+            protondb_fetching: HashSet::new(),
+            crashing_games: HashSet::new(),
+            game_cards,
+            empty_library_card,
+            library_count_label,
+            library_page: library_page.clone(),
+            library_body: library_body.clone(),
+            tv_mode: options.tv_mode,
+            steam_deck,
+            deck_settings,
+            discord: discord_settings
+                .is_configured()
+                .then(|| DiscordPresence::start(&discord_settings.client_id)),
+            discord_settings,
+            preferences_dialog: None,
+            add_to_steam_dialog: None,
+            safe_retry_dialog: None,
+            templates: ConfigTemplates::load(),
+            new_game_defaults: NewGameDefaults::load(),
+            template_name_dialog: None,
+            select_mode: false,
+            selected_games: HashSet::new(),
+            select_button,
+            selection_bar,
+            selection_count_label,
+            selection_actions,
+            selection_template_menu,
+            selection_template_list,
+            delete_dialog: None,
+            safe_mode_games: HashSet::new(),
+            launch_only: options.launch.clone(),
+            root_window: root.clone(),
+        };
+
+        model.update_library_labels();
+        model.apply_tv_mode();
+
+        let widgets = view_output!();
+
+        // Load capsules on startup
+        sender.input(MainWindowMsg::LoadCapsules);
+        if let Some(capsule_dir) = options.launch {
+            sender.input(MainWindowMsg::LaunchGame(capsule_dir));
+        }
+        Self::start_umu_db_sync(sender.clone());
+        let gamepad_sender = sender.clone();
+        GamepadWatcher::spawn(move |action| gamepad_sender.input(MainWindowMsg::Gamepad(action)));
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            MainWindowMsg::LoadCapsules => {
+                // Reading every metadata.json can take a while on a slow
+                // disk, so scan off the GTK thread and show games as they come.
+                self.scan_generation += 1;
+                let generation = self.scan_generation;
+                let games_dir = self.games_dir.clone();
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let mut first = true;
+                    let result = Capsule::scan_directory(&games_dir, 8, &mut |capsules| {
+                        sender_clone.input(MainWindowMsg::CapsulesScanned {
+                            generation,
+                            capsules,
+                            first,
+                        });
+                        first = false;
+                    })
+                    .map(|found| (found, Capsule::scan_damaged(&games_dir)))
+                    .map_err(|e| e.to_string());
+                    sender_clone.input(MainWindowMsg::CapsuleScanFinished { generation, result });
+                });
+            }
+            MainWindowMsg::CapsulesScanned {
+                generation,
+                capsules,
+                first,
+            } => {
+                if generation != self.scan_generation {
+                    return;
+                }
+                if first {
+                    self.crashing_games.clear();
+                }
+                for capsule in &capsules {
+                    if PlayHistory::load(capsule).is_crashing() {
+                        self.crashing_games.insert(capsule.capsule_dir.clone());
+                    }
+                }
+                // Keep the old list up until the first results replace it.
+                if first {
+                    self.capsules = capsules;
+                } else {
+                    self.capsules.extend(capsules);
+                }
+                self.update_library_labels();
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::CapsuleScanFinished { generation, result } => {
+                if generation != self.scan_generation {
+                    return;
+                }
+                match result {
+                    Ok((found, damaged)) => {
+                        if found == 0 {
+                            self.capsules.clear();
+                            self.update_library_labels();
+                            self.refresh_game_cards();
+                        }
+                        println!("Loaded {} capsules", found);
+                        self.fetch_protondb_ratings(sender.clone());
+
+                        let damaged: Vec<PathBuf> = damaged
+                            .into_iter()
+                            .filter(|dir| !self.recovery_offered.contains(dir))
+                            .collect();
+                        if !damaged.is_empty() {
+                            self.open_metadata_recovery_dialog(sender.clone(), damaged);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsules: {}", e);
+                    }
+                }
+            }
+            MainWindowMsg::OpenAddGame => {
+                println!("Open add game dialog");
+                self.open_add_game_dialog(sender);
+            }
+            MainWindowMsg::AddGameModeChosen(mode) => {
+                self.add_game_dialog = None;
+                self.pending_add_mode = Some(mode);
+                self.open_game_path_dialog(sender, mode);
+            }
+            MainWindowMsg::GamePathSelected(path) => {
+                self.game_path_dialog = None;
+                if self.pending_add_mode == Some(AddGameMode::Prefix) {
+                    match Self::resolve_wine_prefix(&path) {
+                        Some(prefix) => self.pending_game_path = Some(prefix),
+                        None => {
+                            eprintln!("{:?} is not a Wine prefix (no drive_c folder)", path);
+                            sender.input(MainWindowMsg::AddGameCancelled);
+                            return;
+                        }
+                    }
+                } else {
+                    self.pending_game_path = Some(path);
+                }
+                self.open_name_dialog(sender);
+            }
+            MainWindowMsg::AddGameCancelled => {
+                self.add_game_dialog = None;
+                self.game_path_dialog = None;
+                self.name_dialog = None;
+                if let Some(dialog) = &self.umu_match_dialog {
+                    dialog.close();
+                }
+                if let Some(dialog) = self.existing_game_dialog.take() {
+                    dialog.close();
+                }
+                if let Some(dialog) = &self.existing_location_dialog {
+                    dialog.close();
+                }
+                if let Some(dialog) = &self.prefix_import_dialog {
+                    dialog.close();
+                }
+                self.umu_match_dialog = None;
+                self.umu_fixes_label = None;
+                self.existing_location_dialog = None;
+                self.prefix_import_dialog = None;
+                self.pending_add_mode = None;
+                self.pending_game_path = None;
+                self.pending_source_folder = None;
+                self.pending_game_name = None;
+                self.pending_game_id = None;
+                self.pending_store = None;
+                self.pending_msi_silent = false;
+                self.pending_win32_prefix = false;
+                self.pending_msi_properties.clear();
+                println!("Add game cancelled");
+            }
+            MainWindowMsg::ExistingSourceFolderSelected(path) => {
+                self.game_path_dialog = None;
+                self.pending_source_folder = Some(path);
+                let game_name = match self.pending_game_name.clone() {
+                    Some(name) => name,
+                    None => {
+                        eprintln!("No pending game name available");
+                        return;
+                    }
+                };
+                self.open_existing_game_location_dialog(sender, game_name);
+            }
+            MainWindowMsg::ExistingSourceFolderCancelled => {
+                self.pending_win32_prefix = false;
+                self.game_path_dialog = None;
+                self.pending_source_folder = None;
+                self.pending_game_id = None;
+                self.pending_store = None;
+                self.pending_game_name = None;
+                self.pending_game_path = None;
+            }
+            MainWindowMsg::ExistingGameLocationConfirmed { folder, transfer } => {
+                self.existing_location_dialog = None;
+                self.finalize_existing_game(sender, folder, transfer);
+            }
+            MainWindowMsg::CopyProgress { copied, total } => {
+                if let Some(progress) = &self.copy_progress_bar {
+                    let fraction = if total > 0 {
+                        (copied as f64 / total as f64).min(1.0)
+                    } else {
+                        0.0
+                    };
+                    progress.set_fraction(fraction);
+                }
+                if let Some(status) = &self.copy_status_label {
+                    status.set_label(&format!(
+                        "{} / {}",
+                        file_ops::format_bytes(copied),
+                        file_ops::format_bytes(total)
+                    ));
+                }
+            }
+            MainWindowMsg::ExistingGameCopyFinished { job, result } => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(()) => self.complete_existing_game(sender, job),
+                    Err(e) => self.abandon_new_capsule(
+                        &job.capsule_dir,
+                        &format!("Failed to copy game files: {}", e),
+                    ),
+                }
+            }
+            MainWindowMsg::PrefixImportConfirmed(transfer) => {
+                self.prefix_import_dialog = None;
+                self.finalize_prefix_import(sender, transfer);
+            }
+            MainWindowMsg::PrefixImportCancelled => {
+                self.prefix_import_dialog = None;
+                self.pending_add_mode = None;
+                self.pending_game_path = None;
+                self.pending_game_name = None;
+                self.pending_game_id = None;
+                self.pending_store = None;
+            }
+            MainWindowMsg::PrefixImportFinished { job, result } => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(()) => self.complete_prefix_import(sender, job),
+                    Err(e) => {
+                        eprintln!("Failed to import prefix: {}", e);
+                        if let Err(e) = fs::remove_dir_all(&job.capsule_dir) {
+                            eprintln!("Failed to clean up capsule: {}", e);
+                        }
+                    }
+                }
+            }
+            MainWindowMsg::ExistingGameLocationCancelled => {
+                self.pending_win32_prefix = false;
+                self.existing_location_dialog = None;
+                self.pending_source_folder = None;
+                self.pending_game_id = None;
+                self.pending_store = None;
+                self.pending_game_name = None;
+                self.pending_game_path = None;
+            }
+            MainWindowMsg::MsiOptionsConfirmed { silent, properties } => {
+                self.pending_msi_silent = silent;
+                self.pending_msi_properties = properties;
+            }
+            MainWindowMsg::PrefixArchChosen { win32 } => {
+                self.pending_win32_prefix = win32;
+            }
+            MainWindowMsg::GameNameConfirmed(name) => {
+                self.name_dialog = None;
+                let name = Self::sanitize_name(&name);
+                if name.is_empty() {
+                    eprintln!("Game name cannot be empty");
+                    return;
+                }
+                if self.pending_game_path.is_none() {
+                    eprintln!("No game path selected");
+                    return;
+                }
+                let add_mode = match self.pending_add_mode {
+                    Some(mode) => mode,
+                    None => {
+                        eprintln!("Add game mode not set");
+                        return;
+                    }
+                };
+
+                self.pending_game_name = Some(name.clone());
+                let duplicate = self.pending_game_path.as_deref().and_then(|path| {
+                    DuplicateFinder::find(&self.capsules, &name, path)
+                });
+                match duplicate {
+                    Some(duplicate) => self.open_existing_game_dialog(sender, duplicate, add_mode),
+                    None => self.continue_add_game(sender, name, add_mode),
+                }
+            }
+            MainWindowMsg::DuplicateGameChosen {
+                capsule_dir,
+                choice,
+            } => {
+                self.existing_game_dialog = None;
+                match choice {
+                    DuplicateChoice::OpenExisting => {
+                        sender.input(MainWindowMsg::AddGameCancelled);
+                        sender.input(MainWindowMsg::OpenGameDetails(capsule_dir));
+                    }
+                    DuplicateChoice::Reinstall => {
+                        self.reinstall_into_existing(sender, capsule_dir);
+                    }
+                    DuplicateChoice::CreateDuplicate => {
+                        let (name, add_mode) =
+                            match (self.pending_game_name.clone(), self.pending_add_mode) {
+                                (Some(name), Some(mode)) => (name, mode),
+                                _ => {
+                                    eprintln!("No pending game to add");
+                                    return;
+                                }
+                            };
+                        self.continue_add_game(sender, name, add_mode);
+                    }
+                }
+            }
+            MainWindowMsg::InstallerFinished { capsule_dir, success } => {
+                self.preparing_installs.remove(&capsule_dir);
+                self.active_installs.remove(&capsule_dir);
+                if success {
+                    let mut needs_exe = false;
+                    let mut prompt_deps = false;
+                    let mut deps_metadata: Option<CapsuleMetadata> = None;
+                    match Capsule::load_from_dir(&capsule_dir) {
+                        Ok(mut capsule) => {
+                            needs_exe = capsule.metadata.executables.main.path.trim().is_empty();
+                            if needs_exe {
+                                let candidates = Self::executable_candidates(&capsule, false);
+                                if let Some(guess) = Self::confident_guess(&candidates) {
+                                    Self::apply_executable_guess(&mut capsule.metadata, guess.clone());
+                                    needs_exe = false;
+                                }
+                            }
+                            capsule.metadata.install_state = InstallState::Installed;
+                            prompt_deps =
+                                self.should_prompt_dependencies(&capsule_dir, &capsule.metadata);
+                            deps_metadata = Some(capsule.metadata.clone());
+                            if let Err(e) = capsule.save_metadata() {
+                                eprintln!("Failed to update metadata: {}", e);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to load capsule: {}", e);
+                        }
+                    }
+
+                    if prompt_deps {
+                        if needs_exe {
+                            self.pending_settings_capsule = Some(capsule_dir.clone());
+                        }
+                        if let Some(metadata) = deps_metadata {
+                            self.open_dependency_dialog(sender.clone(), capsule_dir.clone(), metadata);
+                        }
+                    } else if needs_exe {
+                        self.choose_executable(sender.clone(), capsule_dir.clone());
+                    }
+                    println!("Installer completed for {:?}", capsule_dir);
+                } else {
+                    eprintln!("Installer failed for {:?}", capsule_dir);
+                }
+                sender.input(MainWindowMsg::LoadCapsules);
+            }
+            MainWindowMsg::UmuDatabaseLoaded(entries) => {
+                self.umu_entries = entries;
+                self.umu_loaded = true;
+                self.umu_load_error = None;
+                println!("UMU database loaded ({} entries).", self.umu_entries.len());
+            }
+            MainWindowMsg::UmuDatabaseFailed(error) => {
+                self.umu_loaded = true;
+                self.umu_load_error = Some(error.clone());
+                eprintln!("UMU database load failed: {}", error);
+            }
+            MainWindowMsg::UmuMatchChosen { game_id, store } => {
+                match self.pending_add_mode {
+                    Some(AddGameMode::Installer) => {
+                        self.finalize_pending_game(sender, game_id, store);
+                    }
+                    Some(AddGameMode::Existing) => {
+                        if self.pending_game_name.is_none() {
+                            eprintln!("No pending game name for existing game");
+                            return;
+                        }
+                        self.pending_game_id = game_id;
+                        self.pending_store = store;
+                        self.open_existing_source_folder_dialog(sender);
+                    }
+                    Some(AddGameMode::Prefix) => {
+                        if self.pending_game_name.is_none() {
+                            eprintln!("No pending game name for imported prefix");
+                            return;
+                        }
+                        self.pending_game_id = game_id;
+                        self.pending_store = store;
+                        self.open_prefix_import_dialog(sender);
+                    }
+                    None => {
+                        eprintln!("Add game mode not set");
+                    }
+                }
+            }
+            MainWindowMsg::UmuMatchDialogClosed => {
+                self.umu_match_dialog = None;
+                self.umu_fixes_label = None;
+                self.umu_fixes_key = None;
+            }
+            MainWindowMsg::LoadProtonFixes(entry) => {
+                let key = format!(
+                    "{}/{}",
+                    Self::umu_store_name(&entry),
+                    entry.umu_id.as_deref().unwrap_or_default()
+                );
+                self.umu_fixes_key = Some(key.clone());
+                if let Some(cached) = self.protonfixes_cache.get(&key) {
+                    self.show_protonfixes(&Ok(cached.clone()));
+                    return;
+                }
+                if let Some(label) = &self.umu_fixes_label {
+                    label.set_text("Looking up protonfixes...");
+                }
+
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let result = ProtonFixes::fetch(&entry).map_err(|e| format!("{:#}", e));
+                    let _ = sender_clone.input(MainWindowMsg::ProtonFixesLoaded { key, result });
+                });
+            }
+            MainWindowMsg::ProtonFixesLoaded { key, result } => {
+                match &result {
+                    Ok(summary) => {
+                        self.protonfixes_cache.insert(key.clone(), summary.clone());
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load protonfixes for {}: {}", key, e);
+                    }
+                }
+                if self.umu_fixes_key.as_deref() == Some(key.as_str()) {
+                    self.show_protonfixes(&result);
+                }
+            }
+            MainWindowMsg::DependenciesSelected {
+                capsule_dir,
+                install_vcredist,
+                install_dxweb,
+                install_redistributables,
+            } => {
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(mut capsule) => {
+                        let wanted = Self::wanted_redistributables(
+                            install_vcredist,
+                            install_dxweb,
+                            &install_redistributables,
+                        );
+                        capsule.metadata.install_vcredist = install_vcredist;
+                        capsule.metadata.install_dxweb = install_dxweb;
+                        capsule.metadata.install_redistributables = install_redistributables;
+                        if let Err(e) = capsule.save_metadata() {
+                            eprintln!("Failed to update metadata: {}", e);
+                        }
+                        self.start_dependency_install(
+                            sender.clone(),
+                            capsule_dir,
+                            capsule.metadata.clone(),
+                            wanted,
+                            false,
+                        );
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                    }
+                }
+            }
+            MainWindowMsg::OpenDependencies(capsule_dir) => {
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => {
+                        self.open_dependency_dialog(sender, capsule_dir, capsule.metadata);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                    }
+                }
+            }
+            MainWindowMsg::InstallDependency {
+                capsule_dir,
+                id,
+                force,
+            } => {
+                let redist = match Redistributable::get(&id) {
+                    Some(redist) => redist,
+                    None => {
+                        eprintln!("Unknown dependency: {}", id);
+                        return;
+                    }
+                };
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => {
+                        self.start_dependency_install(
+                            sender,
+                            capsule_dir,
+                            capsule.metadata,
+                            vec![redist],
+                            force,
+                        );
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                    }
+                }
+            }
+            MainWindowMsg::DependencyStep { label, fraction } => {
+                if let Some(step_label) = &self.dependency_step_label {
+                    step_label.set_text(&label);
+                }
+                if let Some(progress_bar) = &self.dependency_progress_bar {
+                    progress_bar.set_fraction(fraction);
+                }
+            }
+            MainWindowMsg::DependencyLog(line) => {
+                self.append_dependency_log(&line);
+            }
+            MainWindowMsg::CancelDependencyInstall => {
+                if let Some(cancel) = &self.dependency_cancel {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                if let Some(step_label) = &self.dependency_step_label {
+                    step_label.set_text("Cancelling after the current installer finishes...");
+                }
+            }
+            MainWindowMsg::DependenciesFinished {
+                capsule_dir,
+                installed,
+                failed,
+                cancelled,
+            } => {
+                self.dependency_installs.remove(&capsule_dir);
+                if let Some(step_label) = &self.dependency_step_label {
+                    let label_names = |ids: &[String]| {
+                        ids.iter()
+                            .map(|dep| {
+                                Redistributable::get(dep)
+                                    .map(|redist| redist.label().to_string())
+                                    .unwrap_or_else(|| dep.clone())
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    let names = label_names(&installed);
+                    let mut text = if installed.is_empty() && cancelled {
+                        "Cancelled".to_string()
+                    } else if installed.is_empty() && failed.is_empty() {
+                        "No dependencies were installed; see the log below".to_string()
+                    } else if installed.is_empty() {
+                        String::new()
+                    } else if cancelled {
+                        format!("Cancelled after installing {}", names)
+                    } else {
+                        format!("Installed {}", names)
+                    };
+                    if !failed.is_empty() {
+                        if !text.is_empty() {
+                            text.push_str(". ");
+                        }
+                        text.push_str(&format!(
+                            "Failed: {}. Retry them from Install dependencies in the game's \
+                             settings; the log below shows why.",
+                            label_names(&failed)
+                        ));
+                    }
+                    step_label.set_text(&text);
+                }
+                if let Some(progress_bar) = &self.dependency_progress_bar {
+                    progress_bar.set_fraction(1.0);
+                }
+                if let Some(dialog) = &self.dependency_progress_dialog {
+                    dialog.set_response_sensitive(ResponseType::Cancel, false);
+                    dialog.set_response_sensitive(ResponseType::Close, true);
+                }
+                self.dependency_cancel = None;
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(mut capsule) => {
+                        let mut updated = false;
+                        for dep in installed {
+                            let failed_before = capsule.metadata.redistributables_failed.len();
+                            capsule
+                                .metadata
+                                .redistributables_failed
+                                .retain(|item| item != &dep);
+                            if capsule.metadata.redistributables_failed.len() != failed_before {
+                                updated = true;
+                            }
+                            if !capsule.metadata.redistributables_installed.contains(&dep) {
+                                capsule.metadata.redistributables_installed.push(dep);
+                                updated = true;
+                            }
+                        }
+                        for dep in failed {
+                            if !capsule.metadata.redistributables_failed.contains(&dep) {
+                                capsule.metadata.redistributables_failed.push(dep);
+                                updated = true;
+                            }
+                        }
+                        if updated {
+                            if let Err(e) = capsule.save_metadata() {
+                                eprintln!("Failed to update metadata: {}", e);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                    }
+                }
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::DependencyProgressClosed => {
+                self.dependency_progress_dialog = None;
+                self.dependency_step_label = None;
+                self.dependency_progress_bar = None;
+                self.dependency_log = None;
+            }
+            MainWindowMsg::DependenciesDialogClosed => {
+                self.dependency_dialog = None;
+                if let Some(capsule_dir) = self.pending_settings_capsule.take() {
+                    self.choose_executable(sender, capsule_dir);
+                }
+            }
+            MainWindowMsg::LaunchGame(capsule_dir) => {
+                if self.active_games.contains_key(&capsule_dir) {
+                    return;
+                }
+                self.start_game(sender, capsule_dir, false, false);
+            }
+            MainWindowMsg::LaunchGameSafe(capsule_dir) => {
+                if self.active_games.contains_key(&capsule_dir) {
+                    return;
+                }
+                self.start_game(sender, capsule_dir, true, false);
+            }
+            MainWindowMsg::LaunchGameInTerminal(capsule_dir) => {
+                if self.active_games.contains_key(&capsule_dir) {
+                    return;
+                }
+                self.start_game(sender, capsule_dir, false, true);
+            }
+            MainWindowMsg::RuntimeSnapshotFinished {
+                capsule_dir,
+                runtime,
+                safe_mode,
+                in_terminal,
+            } => {
+                self.snapshot_jobs.remove(&capsule_dir);
+                self.refresh_game_cards();
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(mut capsule) => {
+                        capsule.metadata.wine_version = Some(runtime);
+                        if let Err(e) = capsule.save_metadata() {
+                            eprintln!("Failed to update metadata: {}", e);
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                        return;
+                    }
+                }
+                if self.active_games.contains_key(&capsule_dir) {
+                    return;
+                }
+                self.start_game(sender, capsule_dir, safe_mode, in_terminal);
+            }
+            MainWindowMsg::SafeRetryDialogClosed => {
+                self.safe_retry_dialog = None;
+            }
+            MainWindowMsg::SaveTemplatePrompt(capsule_dir) => {
+                self.open_template_name_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::SaveTemplate { capsule_dir, name } => {
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    return;
+                }
+                let capsule = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule,
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                        return;
+                    }
+                };
+                self.templates.insert(ConfigTemplate {
+                    name: name.clone(),
+                    profile: LaunchProfile::from_metadata(&capsule.metadata),
+                });
+                match self.templates.save() {
+                    Ok(()) => println!("Saved template {} from {}", name, capsule.name),
+                    Err(e) => eprintln!("Failed to save templates: {}", e),
+                }
+                if self.select_mode {
+                    self.rebuild_selection_template_menu(sender);
+                }
+            }
+            MainWindowMsg::TemplateNameDialogClosed => {
+                self.template_name_dialog = None;
+            }
+            MainWindowMsg::ApplyTemplate { capsule_dir, name } => {
+                if self.apply_template(&capsule_dir, &name) {
+                    sender.input(MainWindowMsg::LoadCapsules);
+                }
+            }
+            MainWindowMsg::DeleteTemplate(name) => {
+                self.templates.remove(&name);
+                match self.templates.save() {
+                    Ok(()) => println!("Deleted template {}", name),
+                    Err(e) => eprintln!("Failed to save templates: {}", e),
+                }
+                if self.select_mode {
+                    self.rebuild_selection_template_menu(sender);
+                }
+            }
+            MainWindowMsg::DefaultTemplateChosen(name) => {
+                if self.templates.default_template == name {
+                    return;
+                }
+                self.templates.default_template = name;
+                if let Err(e) = self.templates.save() {
+                    eprintln!("Failed to save templates: {}", e);
+                }
+            }
+            MainWindowMsg::NewGameDefaultsSaved(defaults) => {
+                if let Err(e) = defaults.save() {
+                    eprintln!("Failed to save new game defaults: {}", e);
+                    return;
+                }
+                self.new_game_defaults = defaults;
+            }
+            MainWindowMsg::ToggleSelectMode => {
+                self.select_mode = !self.select_mode;
+                self.selected_games.clear();
+                if self.select_mode {
+                    self.rebuild_selection_template_menu(sender);
+                }
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::SetGameSelected {
+                capsule_dir,
+                selected,
+            } => {
+                if selected {
+                    self.selected_games.insert(capsule_dir);
+                } else {
+                    self.selected_games.remove(&capsule_dir);
+                }
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::SelectAllGames => {
+                if self.selected_games.len() == self.capsules.len() {
+                    self.selected_games.clear();
+                } else {
+                    self.selected_games = self
+                        .capsules
+                        .iter()
+                        .map(|capsule| capsule.capsule_dir.clone())
+                        .collect();
+                }
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::BatchBackup => {
+                if !self.selected_games.is_empty() {
+                    self.open_batch_backup_folder_dialog(sender);
+                }
+            }
+            MainWindowMsg::BatchBackupFolderSelected(folder) => {
+                self.start_batch_backup(sender, folder);
+            }
+            MainWindowMsg::BatchBackupFinished(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            MainWindowMsg::BatchApplyTemplate(name) => {
+                let mut applied = false;
+                for capsule_dir in self.selected_capsule_dirs() {
+                    applied |= self.apply_template(&capsule_dir, &name);
+                }
+                if applied {
+                    sender.input(MainWindowMsg::LoadCapsules);
+                }
+            }
+            MainWindowMsg::BatchDelete => {
+                let capsule_dirs = self.selected_capsule_dirs();
+                if !capsule_dirs.is_empty() {
+                    self.open_delete_dialog(sender, capsule_dirs);
+                }
+            }
+            MainWindowMsg::GameStarted { capsule_dir, pgid } => {
+                let game = Capsule::load_from_dir(&capsule_dir)
+                    .map(|capsule| capsule.metadata.title().to_string())
+                    .unwrap_or_else(|_| capsule_dir.to_string_lossy().to_string());
+                let started = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_secs() as i64)
+                    .unwrap_or(0);
+                self.playing.retain(|(dir, _)| dir != &capsule_dir);
+                self.playing.push((capsule_dir.clone(), Activity { game, started }));
+                self.active_games.insert(capsule_dir, pgid);
+                self.update_discord_presence();
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::GameFinished { capsule_dir, success } => {
+                self.active_games.remove(&capsule_dir);
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_secs() as i64)
+                    .unwrap_or(0);
+                let quick_crash = !success
+                    && self.playing.iter().any(|(dir, activity)| {
+                        dir == &capsule_dir && now - activity.started < QUICK_CRASH_SECS
+                    });
+                let was_safe_mode = self.safe_mode_games.remove(&capsule_dir);
+                if quick_crash {
+                    self.offer_safe_retry(sender.clone(), &capsule_dir, was_safe_mode);
+                }
+                self.playing.retain(|(dir, _)| dir != &capsule_dir);
+                self.update_discord_presence();
+                let crashing = Capsule::load_from_dir(&capsule_dir)
+                    .map(|capsule| PlayHistory::load(&capsule).is_crashing())
+                    .unwrap_or(false);
+                if crashing {
+                    self.crashing_games.insert(capsule_dir.clone());
+                } else {
+                    self.crashing_games.remove(&capsule_dir);
+                }
+                if success {
+                    println!("Game finished for {:?}", capsule_dir);
+                } else {
+                    eprintln!("Game failed for {:?}", capsule_dir);
+                }
+                let sync_enabled = Capsule::load_from_dir(&capsule_dir)
+                    .map(|capsule| capsule.metadata.cloud_sync)
+                    .unwrap_or(false);
+                if sync_enabled && self.cloud_sync_config.is_configured() {
+                    self.start_cloud_sync(sender.clone(), capsule_dir, SyncDirection::Auto);
+                } else {
+                    self.refresh_game_cards();
+                    self.quit_if_launch_done(&capsule_dir);
+                }
+            }
+            MainWindowMsg::InstallerStarted { capsule_dir, pgid } => {
+                self.preparing_installs.remove(&capsule_dir);
+                self.active_installs.insert(capsule_dir, pgid);
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::EditGame(capsule_dir) => {
+                self.open_game_settings_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::SaveGameSettings {
+                capsule_dir,
+                exe_path,
+                working_dir,
+                game_id,
+                store,
+                install_vcredist,
+                install_dxweb,
+                install_redistributables,
+                protonfixes_disable,
+                xalia_enabled,
+                gamemode_enabled,
+                mangohud_enabled,
+                fps_limit,
+                gamescope_enabled,
+                gamescope_hdr,
+                gamescope_vrr,
+                screenshots_dir,
+                proton_wayland,
+                force_x11,
+                sdl_video_driver,
+                sandbox_enabled,
+                sandbox_network,
+                block_network,
+                command_template,
+                wine_debug,
+                wine_debug_once,
+                protonfixes_replace_cmds,
+                protonfixes_dxvk_sets,
+                dll_overrides,
+                virtual_desktop,
+                locale,
+                lc_all,
+                timezone,
+                cloud_sync,
+            } => {
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(mut capsule) => {
+                        let first_executable =
+                            capsule.metadata.executables.main.path.trim().is_empty()
+                                && !exe_path.trim().is_empty();
+                        capsule.metadata.executables.main.working_dir = working_dir;
+                        if !exe_path.trim().is_empty() {
+                            capsule.metadata.executables.main.path = exe_path;
+                            capsule.metadata.install_state = InstallState::Installed;
+                        } else {
+                            capsule.metadata.executables.main.path = exe_path;
+                        }
+                        capsule.metadata.game_id = game_id;
+                        capsule.metadata.store = store;
+                        capsule.metadata.install_vcredist = install_vcredist;
+                        capsule.metadata.install_dxweb = install_dxweb;
+                        capsule.metadata.install_redistributables = install_redistributables;
+                        capsule.metadata.protonfixes_disable = protonfixes_disable;
+                        capsule.metadata.xalia_enabled = xalia_enabled;
+                        capsule.metadata.gamemode_enabled = gamemode_enabled;
+                        capsule.metadata.mangohud_enabled = mangohud_enabled;
+                        capsule.metadata.fps_limit = fps_limit;
+                        capsule.metadata.gamescope_enabled = gamescope_enabled;
+                        capsule.metadata.gamescope_hdr = gamescope_hdr;
+                        capsule.metadata.gamescope_vrr = gamescope_vrr;
+                        capsule.metadata.screenshots_dir = screenshots_dir;
+                        capsule.metadata.proton_wayland = proton_wayland;
+                        capsule.metadata.force_x11 = force_x11;
+                        capsule.metadata.sdl_video_driver = sdl_video_driver;
+                        capsule.metadata.sandbox_enabled = sandbox_enabled;
+                        capsule.metadata.sandbox_network = sandbox_network;
+                        capsule.metadata.block_network = block_network;
+                        capsule.metadata.command_template = command_template;
+                        capsule.metadata.wine_debug = wine_debug;
+                        capsule.metadata.wine_debug_once = wine_debug_once;
+                        capsule.metadata.protonfixes_replace_cmds = protonfixes_replace_cmds;
+                        capsule.metadata.protonfixes_dxvk_sets = protonfixes_dxvk_sets;
+                        capsule.metadata.dll_overrides = dll_overrides;
+                        capsule.metadata.virtual_desktop = virtual_desktop;
+                        capsule.metadata.locale = locale;
+                        capsule.metadata.lc_all = lc_all;
+                        capsule.metadata.timezone = timezone;
+                        capsule.metadata.cloud_sync = cloud_sync;
+                        if let Err(e) = capsule.save_metadata() {
+                            eprintln!("Failed to update metadata: {}", e);
+                        } else {
+                            println!("Updated settings for {}", capsule.name);
+                            sender.input(MainWindowMsg::LoadCapsules);
+                            if first_executable {
+                                self.offer_add_to_steam(sender.clone(), &capsule);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                    }
+                }
+            }
+            MainWindowMsg::SettingsDialogClosed => {
+                self.settings_dialog = None;
+                self.settings_exe_field = None;
+            }
+            MainWindowMsg::ExecutableChosen { capsule_dir, choice } => match choice {
+                Some(guess) => match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(mut capsule) => {
+                        println!("Selected main executable: {:?}", guess.path);
+                        Self::apply_executable_guess(&mut capsule.metadata, guess);
+                        if let Err(e) = capsule.save_metadata() {
+                            eprintln!("Failed to update metadata: {}", e);
+                        } else {
+                            self.offer_add_to_steam(sender.clone(), &capsule);
+                        }
+                        sender.input(MainWindowMsg::LoadCapsules);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                    }
+                },
+                None => self.open_game_settings_dialog(sender, capsule_dir),
+            },
+            MainWindowMsg::ExecutablePickerClosed => {
+                self.executable_picker = None;
+            }
+            MainWindowMsg::RescanExecutables(capsule_dir) => {
+                // Walking a large prefix takes a while, keep it off the GTK thread.
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let path = match Capsule::load_from_dir(&capsule_dir) {
+                        Ok(capsule) => Self::executable_candidates(&capsule, true)
+                            .into_iter()
+                            .next()
+                            .map(|guess| guess.path),
+                        Err(e) => {
+                            eprintln!("Failed to load capsule: {}", e);
+                            None
+                        }
+                    };
+                    sender_clone.input(MainWindowMsg::ExecutablesRescanned { capsule_dir, path });
+                });
+            }
+            MainWindowMsg::ExecutablesRescanned { capsule_dir, path } => {
+                if let Some(field) = self
+                    .settings_exe_field
+                    .as_ref()
+                    .filter(|field| field.capsule_dir == capsule_dir)
+                {
+                    field.rescan_button.set_sensitive(true);
+                    field.rescan_button.set_label("Re-scan");
+                    match path {
+                        Some(path) => field.entry.set_text(&path.to_string_lossy()),
+                        None => field.entry.set_placeholder_text(Some("No executables found")),
+                    }
+                }
+            }
+            MainWindowMsg::DeleteGame(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot delete a capsule while it is busy");
+                    return;
+                }
+                self.open_delete_dialog(sender, vec![capsule_dir]);
+            }
+            MainWindowMsg::DeleteConfirmed {
+                capsule_dirs,
+                options,
+                trash,
+            } => {
+                self.start_capsule_removal(sender, capsule_dirs, options, trash);
+            }
+            MainWindowMsg::CapsuleRemovalFinished(result) => {
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("Failed to delete capsule: {}", e),
+                }
+                sender.input(MainWindowMsg::LoadCapsules);
+            }
+            MainWindowMsg::DeleteDialogClosed => {
+                self.delete_dialog = None;
+            }
+            MainWindowMsg::InstallPatch(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot install a patch while the capsule is busy");
+                    return;
+                }
+                self.open_patch_chooser(sender, capsule_dir);
+            }
+            MainWindowMsg::RunPatchInstaller {
+                capsule_dir,
+                installer_path,
+            } => {
+                self.start_patch_installer(sender, capsule_dir, installer_path);
+            }
+            MainWindowMsg::PatchInstallerFinished { capsule_dir, patch } => {
+                self.patch_jobs.remove(&capsule_dir);
+                self.refresh_game_cards();
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(mut capsule) => {
+                        let verb = if patch.success { "Installed" } else { "Failed to install" };
+                        println!("{} {} into {}", verb, patch.file_name(), capsule.name);
+                        capsule.metadata.patches.push(patch);
+                        if let Err(e) = capsule.save_metadata() {
+                            eprintln!("Failed to update metadata: {}", e);
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to load capsule: {}", e),
+                }
+            }
+            MainWindowMsg::OpenMods(capsule_dir) => {
+                if self.mods_capsule.as_ref() != Some(&capsule_dir) {
+                    self.open_mods_dialog(sender.clone(), capsule_dir.clone());
+                }
+                if self.mods_capsule.as_ref() != Some(&capsule_dir)
+                    || self.mod_jobs.contains(&capsule_dir)
+                {
+                    return;
+                }
+                if let Some(status) = &self.mods_status {
+                    status.set_text("Reading mods...");
+                }
+                self.load_mods(&sender, capsule_dir);
+            }
+            MainWindowMsg::ModsLoaded {
+                capsule_dir,
+                result,
+                status,
+            } => {
+                if self.mod_jobs.remove(&capsule_dir) {
+                    self.refresh_game_cards();
+                }
+                if self.mods_capsule.as_ref() != Some(&capsule_dir) {
+                    return;
+                }
+                if let Some(list) = &self.mods_list {
+                    list.set_sensitive(true);
+                }
+                let text = match &result {
+                    Ok(entries) => {
+                        self.fill_mods_list(&sender, entries);
+                        status.unwrap_or_default()
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to read mods: {}", e);
+                        e.clone()
+                    }
+                };
+                if let Some(label) = &self.mods_status {
+                    label.set_text(&text);
+                }
+            }
+            MainWindowMsg::ToggleMod {
+                capsule_dir,
+                id,
+                enable,
+            } => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    if let Some(label) = &self.mods_status {
+                        label.set_text("Close the game and wait for other tasks to finish first.");
+                    }
+                    // Puts the checkbox back.
+                    self.load_mods(&sender, capsule_dir);
+                    return;
+                }
+                let mode = match &self.mods_link_check {
+                    Some(check) if check.is_active() => DeployMode::Symlink,
+                    _ => DeployMode::Copy,
+                };
+                self.mod_jobs.insert(capsule_dir.clone());
+                self.refresh_game_cards();
+                if let Some(list) = &self.mods_list {
+                    list.set_sensitive(false);
+                }
+                if let Some(label) = &self.mods_status {
+                    label.set_text(if enable { "Enabling..." } else { "Disabling..." });
+                }
+
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let manager = Capsule::load_from_dir(&capsule_dir)
+                        .and_then(|capsule| ModManager::new(&capsule));
+                    let manager = match manager {
+                        Ok(manager) => manager,
+                        Err(e) => {
+                            sender_clone.input(MainWindowMsg::ModsLoaded {
+                                capsule_dir,
+                                result: Err(format!("{:#}", e)),
+                                status: None,
+                            });
+                            return;
+                        }
+                    };
+                    let status = if enable {
+                        manager
+                            .enable(&id, mode)
+                            .map(|count| format!("Enabled {} ({} files)", id, count))
+                    } else {
+                        manager
+                            .disable(&id)
+                            .map(|count| format!("Disabled {} ({} files restored)", id, count))
+                    };
+                    let status = match status {
+                        Ok(status) => {
+                            println!("{}", status);
+                            status
+                        }
+                        Err(e) => {
+                            let verb = if enable { "enable" } else { "disable" };
+                            eprintln!("Failed to {} mod {}: {:#}", verb, id, e);
+                            format!("Couldn't {} {}: {:#}", verb, id, e)
+                        }
+                    };
+                    let result = manager.sync().map_err(|e| format!("{:#}", e));
+                    sender_clone.input(MainWindowMsg::ModsLoaded {
+                        capsule_dir,
+                        result,
+                        status: Some(status),
+                    });
+                });
+            }
+            MainWindowMsg::ModsDialogClosed => {
+                self.mods_dialog = None;
+                self.mods_capsule = None;
+                self.mods_list = None;
+                self.mods_status = None;
+                self.mods_link_check = None;
+            }
+            MainWindowMsg::UninstallGame(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot uninstall a game while its capsule is busy");
+                    return;
+                }
+                self.open_uninstall_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::RunUninstaller {
+                capsule_dir,
+                uninstaller,
+            } => {
+                self.start_uninstaller(sender, capsule_dir, uninstaller);
+            }
+            MainWindowMsg::UninstallerFinished { capsule_dir, result } => {
+                self.uninstall_jobs.remove(&capsule_dir);
+                match result {
+                    Ok(true) => match Capsule::load_from_dir(&capsule_dir) {
+                        Ok(mut capsule) => {
+                            // Back to setup: Resume setup reinstalls, Finish setup picks the
+                            // executable again.
+                            let main = &mut capsule.metadata.executables.main;
+                            main.path.clear();
+                            main.args.clear();
+                            main.original_shortcut = None;
+                            main.working_dir = None;
+                            capsule.metadata.install_state = InstallState::Installing;
+                            match capsule.save_metadata() {
+                                Ok(()) => {
+                                    println!("Uninstalled {}; the capsule is kept", capsule.name)
+                                }
+                                Err(e) => eprintln!("Failed to update metadata: {}", e),
+                            }
+                            sender.input(MainWindowMsg::LoadCapsules);
+                        }
+                        Err(e) => eprintln!("Failed to load capsule: {}", e),
+                    },
+                    Ok(false) => println!("The game is still installed; the capsule is unchanged"),
+                    Err(e) => eprintln!("Uninstaller failed: {}", e),
+                }
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::UninstallDialogClosed => {
+                self.uninstall_dialog = None;
+            }
+            MainWindowMsg::ArchiveGame(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot archive a game while its capsule is busy");
+                    return;
+                }
+                self.start_game_archive(sender, capsule_dir);
+            }
+            MainWindowMsg::ArchiveFinished { capsule_dir, result } => {
+                self.archive_jobs.remove(&capsule_dir);
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(info) => println!(
+                        "Archived {:?}: {} packed to {}",
+                        capsule_dir,
+                        file_ops::format_bytes(info.unpacked_size),
+                        file_ops::format_bytes(info.packed_size)
+                    ),
+                    Err(e) => eprintln!("Failed to archive game: {}", e),
+                }
+                sender.input(MainWindowMsg::LoadCapsules);
+            }
+            MainWindowMsg::UnpackFinished {
+                capsule_dir,
+                result,
+                play,
+            } => {
+                self.archive_jobs.remove(&capsule_dir);
+                self.close_copy_progress_dialog();
+                sender.input(MainWindowMsg::LoadCapsules);
+                match result {
+                    Ok(()) => {
+                        println!("Unpacked {:?}", capsule_dir);
+                        if play {
+                            sender.input(MainWindowMsg::LaunchGame(capsule_dir));
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to unpack game: {}", e),
+                }
+            }
+            MainWindowMsg::MoveCapsule(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot move a capsule while it is running");
+                    return;
+                }
+                self.open_move_capsule_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::MoveCapsuleTargetSelected {
+                capsule_dir,
+                target_dir,
+            } => {
+                self.start_capsule_move(sender, capsule_dir, target_dir);
+            }
+            MainWindowMsg::CapsuleMoveFinished { capsule_dir, result } => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(new_dir) => println!("Moved capsule {:?} to {:?}", capsule_dir, new_dir),
+                    Err(e) => eprintln!("Failed to move capsule: {}", e),
+                }
+                sender.input(MainWindowMsg::LoadCapsules);
+            }
+            MainWindowMsg::RenameCapsule(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot rename a capsule while it is running");
+                    return;
+                }
+                self.open_rename_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::RenameCapsuleConfirmed {
+                capsule_dir,
+                new_name,
+            } => {
+                let new_name = Self::sanitize_name(&new_name);
+                if new_name.is_empty() {
+                    eprintln!("Game name cannot be empty");
+                    return;
+                }
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => {
+                        // The folder may carry a -N suffix the name doesn't.
+                        if capsule.name == new_name && capsule.capsule_dir.ends_with(&new_name) {
+                            return;
+                        }
+                        match capsule.rename(&new_name) {
+                            Ok(renamed) => {
+                                println!("Renamed {} to {}", capsule.name, renamed.name);
+                                sender.input(MainWindowMsg::LoadCapsules);
+                            }
+                            Err(e) => eprintln!("Failed to rename capsule: {}", e),
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                    }
                 }
-                if let Some(dialog) = &self.existing_location_dialog {
-                    dialog.close();
+            }
+            MainWindowMsg::DuplicateCapsule(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot duplicate a capsule while it is running");
+                    return;
                 }
-                self.umu_match_dialog = None;
-                self.existing_location_dialog = None;
-                self.pending_add_mode = None;
-                self.pending_game_path = None;
-                self.pending_source_folder = None;
-                self.pending_game_name = None;
-                self.pending_game_id = None;
-                self.pending_store = None;
-                println!("Add game cancelled");
+                self.open_duplicate_dialog(sender, capsule_dir);
             }
-            MainWindowMsg::ExistingSourceFolderSelected(path) => {
-                self.game_path_dialog = None;
-                self.pending_source_folder = Some(path);
-                let game_name = match self.pending_game_name.clone() {
-                    Some(name) => name,
-                    None => {
-                        eprintln!("No pending game name available");
-                        return;
-                    }
-                };
-                self.open_existing_game_location_dialog(sender, game_name);
+            MainWindowMsg::DuplicateCapsuleConfirmed {
+                capsule_dir,
+                new_name,
+                share_game_files,
+            } => {
+                let new_name = Self::sanitize_name(&new_name);
+                if new_name.is_empty() {
+                    eprintln!("Game name cannot be empty");
+                    return;
+                }
+                self.start_capsule_duplicate(sender, capsule_dir, new_name, share_game_files);
             }
-            MainWindowMsg::ExistingSourceFolderCancelled => {
-                self.game_path_dialog = None;
-                self.pending_source_folder = None;
-                self.pending_game_id = None;
-                self.pending_store = None;
-                self.pending_game_name = None;
-                self.pending_game_path = None;
+            MainWindowMsg::OpenSnapshots(capsule_dir) => {
+                self.open_snapshots_dialog(sender, capsule_dir);
             }
-            MainWindowMsg::ExistingGameLocationConfirmed(folder) => {
-                self.existing_location_dialog = None;
-                self.finalize_existing_game(sender, folder);
+            MainWindowMsg::SnapshotRequested {
+                capsule_dir,
+                action,
+            } => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot change snapshots while the capsule is busy");
+                    return;
+                }
+                self.start_snapshot_job(sender, capsule_dir, action);
             }
-            MainWindowMsg::ExistingGameLocationCancelled => {
-                self.existing_location_dialog = None;
-                self.pending_source_folder = None;
-                self.pending_game_id = None;
-                self.pending_store = None;
-                self.pending_game_name = None;
-                self.pending_game_path = None;
+            MainWindowMsg::SnapshotFinished { capsule_dir, result } => {
+                self.snapshot_jobs.remove(&capsule_dir);
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("Snapshot operation failed: {}", e),
+                }
+                self.refresh_game_cards();
+                self.open_snapshots_dialog(sender, capsule_dir);
             }
-            MainWindowMsg::GameNameConfirmed(name) => {
-                self.name_dialog = None;
-                let name = Self::sanitize_name(&name);
-                if name.is_empty() {
-                    eprintln!("Game name cannot be empty");
+            MainWindowMsg::OpenWinetricks(capsule_dir) => {
+                if !self.winetricks_verbs.is_empty() {
+                    self.open_winetricks_dialog(sender, capsule_dir);
                     return;
                 }
-                if self.pending_game_path.is_none() {
-                    eprintln!("No game path selected");
+                if self.winetricks_pending.replace(capsule_dir).is_some() {
                     return;
                 }
-                let add_mode = match self.pending_add_mode {
-                    Some(mode) => mode,
-                    None => {
-                        eprintln!("Add game mode not set");
-                        return;
-                    }
-                };
-
-                self.pending_game_name = Some(name.clone());
-                let matches = self.find_umu_matches(&name);
-                if !matches.is_empty() {
-                    self.open_umu_match_dialog(sender, name, matches);
-                } else {
-                    match add_mode {
-                        AddGameMode::Installer => {
-                            self.finalize_pending_game(sender, None, None);
-                        }
-                        AddGameMode::Existing => {
-                            self.pending_game_id = None;
-                            self.pending_store = None;
-                            self.open_existing_source_folder_dialog(sender);
+                println!("Loading winetricks verbs...");
+                let proton_path = self.runtime_mgr.latest_installed().ok().flatten();
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let result = Winetricks::locate_or_download()
+                        .and_then(|winetricks| winetricks.list_verbs(proton_path.as_deref()))
+                        .map_err(|e| e.to_string());
+                    let _ = sender_clone.input(MainWindowMsg::WinetricksVerbsLoaded(result));
+                });
+            }
+            MainWindowMsg::WinetricksVerbsLoaded(result) => {
+                let capsule_dir = self.winetricks_pending.take();
+                match result {
+                    Ok(verbs) => {
+                        self.winetricks_verbs = verbs;
+                        if let Some(capsule_dir) = capsule_dir {
+                            self.open_winetricks_dialog(sender, capsule_dir);
                         }
                     }
+                    Err(e) => eprintln!("Failed to load winetricks: {}", e),
                 }
             }
-            MainWindowMsg::InstallerFinished { capsule_dir, success } => {
-                self.preparing_installs.remove(&capsule_dir);
-                self.active_installs.remove(&capsule_dir);
+            MainWindowMsg::RunWinetricks { capsule_dir, verbs } => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot run winetricks while the capsule is busy");
+                    return;
+                }
+                self.start_winetricks(sender, capsule_dir, verbs);
+            }
+            MainWindowMsg::WinetricksLog(line) => {
+                self.append_winetricks_log(&line);
+            }
+            MainWindowMsg::WinetricksFinished {
+                capsule_dir,
+                verbs,
+                success,
+            } => {
+                self.winetricks_jobs.remove(&capsule_dir);
                 if success {
-                    let mut needs_exe = false;
-                    let mut prompt_deps = false;
-                    let mut deps_metadata: Option<CapsuleMetadata> = None;
                     match Capsule::load_from_dir(&capsule_dir) {
                         Ok(mut capsule) => {
-                            needs_exe = capsule.metadata.executables.main.path.trim().is_empty();
-                            if needs_exe {
-                                if let Some(guess) = Self::guess_executable(&capsule) {
-                                    capsule.metadata.executables.main.path =
-                                        guess.path.to_string_lossy().to_string();
-                                    capsule.metadata.executables.main.original_shortcut = guess
-                                        .shortcut
-                                        .map(|path| path.to_string_lossy().to_string());
-                                    needs_exe = false;
+                            let logged = Winetricks::installed_verbs(&capsule.home_path.join("prefix"));
+                            for verb in verbs.iter().chain(logged.iter()) {
+                                if !capsule.metadata.winetricks_verbs.contains(verb) {
+                                    capsule.metadata.winetricks_verbs.push(verb.clone());
                                 }
                             }
-                            capsule.metadata.install_state = InstallState::Installed;
-                            prompt_deps = self.should_prompt_dependencies(&capsule.metadata);
-                            deps_metadata = Some(capsule.metadata.clone());
                             if let Err(e) = capsule.save_metadata() {
                                 eprintln!("Failed to update metadata: {}", e);
                             }
                         }
-                        Err(e) => {
-                            eprintln!("Failed to load capsule: {}", e);
+                        Err(e) => eprintln!("Failed to load capsule: {}", e),
+                    }
+                    self.append_winetricks_log(&format!("Installed {}", verbs.join(", ")));
+                } else {
+                    eprintln!("winetricks failed for {:?}", capsule_dir);
+                    self.append_winetricks_log("winetricks failed; see the log above");
+                }
+                if let Some(dialog) = &self.winetricks_dialog {
+                    dialog.set_response_sensitive(ResponseType::Accept, true);
+                }
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::WinetricksDialogClosed => {
+                self.winetricks_dialog = None;
+                self.winetricks_log = None;
+            }
+            MainWindowMsg::ImportRegistryFile { capsule_dir, path } => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot import registry file while the capsule is busy");
+                    return;
+                }
+                let content = match fs::read(&path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        eprintln!("Failed to read {:?}: {}", path, e);
+                        return;
+                    }
+                };
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "registry file".to_string());
+                self.start_registry_import(
+                    sender,
+                    capsule_dir,
+                    content,
+                    Some(format!("Before importing {}", file_name)),
+                    format!("Imported {}", file_name),
+                );
+            }
+            MainWindowMsg::ApplyRegistryTweaks {
+                capsule_dir,
+                current,
+                desired,
+            } => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot update the registry while the capsule is busy");
+                    return;
+                }
+                let patch = PrefixRegistry::tweaks_patch(&current, &desired);
+                self.start_registry_import(
+                    sender,
+                    capsule_dir,
+                    patch.into_bytes(),
+                    None,
+                    "Updated registry settings".to_string(),
+                );
+            }
+            MainWindowMsg::RegistryJobFinished { capsule_dir, result } => {
+                self.registry_jobs.remove(&capsule_dir);
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("Registry update failed: {}", e),
+                }
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::ExportSaves { capsule_dir, dest } => {
+                let capsule = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule,
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                        return;
+                    }
+                };
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let locations = SaveManager::detect(&capsule);
+                    let result = SaveManager::export(&capsule, &locations, &dest)
+                        .map(|count| format!("Exported {} save folder(s) to {:?}", count, dest))
+                        .map_err(|e| e.to_string());
+                    let _ = sender_clone.input(MainWindowMsg::SavesTransferFinished(result));
+                });
+            }
+            MainWindowMsg::ImportSaves { capsule_dir, archive } => {
+                if self.active_games.contains_key(&capsule_dir) {
+                    eprintln!("Close the game before importing saves");
+                    return;
+                }
+                let capsule = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule,
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                        return;
+                    }
+                };
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let result = SaveManager::import(&capsule, &archive)
+                        .map(|_| format!("Imported saves from {:?}", archive))
+                        .map_err(|e| e.to_string());
+                    let _ = sender_clone.input(MainWindowMsg::SavesTransferFinished(result));
+                });
+            }
+            MainWindowMsg::SavesTransferFinished(result) => match result {
+                Ok(message) => println!("{}", message),
+                Err(e) => eprintln!("Save transfer failed: {}", e),
+            },
+            MainWindowMsg::OpenHeroicImport => {
+                self.open_heroic_import_dialog(sender);
+            }
+            MainWindowMsg::HeroicGameChosen { game, copy_prefix } => {
+                self.start_heroic_import(sender, game, copy_prefix);
+            }
+            MainWindowMsg::HeroicDialogClosed => {
+                self.heroic_dialog = None;
+            }
+            MainWindowMsg::HeroicImportFinished(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(capsule_dir) => println!("Imported Heroic game into {:?}", capsule_dir),
+                    Err(e) => eprintln!("Failed to import Heroic game: {}", e),
+                }
+                sender.input(MainWindowMsg::LoadCapsules);
+            }
+            MainWindowMsg::ExportCapsule(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot export a capsule while it is busy");
+                    return;
+                }
+                self.open_export_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::ExportOptionsConfirmed {
+                capsule_dir,
+                compression,
+                incremental,
+            } => {
+                self.open_export_destination_dialog(sender, capsule_dir, compression, incremental);
+            }
+            MainWindowMsg::ExportDestinationSelected {
+                capsule_dir,
+                dest,
+                compression,
+                incremental,
+            } => {
+                self.start_capsule_export(sender, capsule_dir, dest, compression, incremental);
+            }
+            MainWindowMsg::ExportDialogClosed => {
+                self.export_dialog = None;
+            }
+            MainWindowMsg::CapsuleExportFinished(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("Failed to export capsule: {}", e),
+                }
+            }
+            MainWindowMsg::ExportAppImage(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot export a capsule while it is busy");
+                    return;
+                }
+                self.open_appimage_destination_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::AppImageDestinationSelected { capsule_dir, dest } => {
+                self.start_appimage_export(sender, capsule_dir, dest);
+            }
+            MainWindowMsg::AppImageExportFinished(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("Failed to export AppImage: {}", e),
+                }
+            }
+            MainWindowMsg::ExportPortableBundle(capsule_dir) => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot export a capsule while it is busy");
+                    return;
+                }
+                self.open_portable_bundle_dialog(sender, Some(capsule_dir));
+            }
+            MainWindowMsg::PortableBundleTargetSelected {
+                capsule_dir,
+                parent,
+            } => {
+                self.start_portable_bundle_export(sender, capsule_dir, parent);
+            }
+            MainWindowMsg::PortableBundleExportFinished(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("Failed to export portable bundle: {}", e),
+                }
+            }
+            MainWindowMsg::ImportPortableBundle => {
+                self.open_portable_bundle_dialog(sender, None);
+            }
+            MainWindowMsg::PortableBundleSelected(bundle_dir) => {
+                self.start_portable_bundle_import(sender, bundle_dir);
+            }
+            MainWindowMsg::ImportCapsule => {
+                self.open_backup_file_dialog(sender, "Import Capsule", "Import", false);
+            }
+            MainWindowMsg::ImportArchiveSelected(archive_path) => {
+                self.start_capsule_import(sender, archive_path);
+            }
+            MainWindowMsg::CapsuleImportFinished(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(capsule_dir) => println!("Imported capsule into {:?}", capsule_dir),
+                    Err(e) => eprintln!("Failed to import capsule: {}", e),
+                }
+                sender.input(MainWindowMsg::LoadCapsules);
+            }
+            MainWindowMsg::VerifyBackup => {
+                self.open_backup_file_dialog(sender, "Verify Backup", "Verify", true);
+            }
+            MainWindowMsg::VerifyArchiveSelected(archive_path) => {
+                self.start_backup_verify(sender, archive_path);
+            }
+            MainWindowMsg::BackupVerifyFinished(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(report) if report.is_ok() => println!("{}", report.summary()),
+                    Ok(report) => {
+                        eprintln!("{}", report.summary());
+                        for path in report.corrupt.iter().chain(report.missing.iter()) {
+                            eprintln!("  {}", path);
                         }
                     }
-
-                    if prompt_deps {
-                        if needs_exe {
-                            self.pending_settings_capsule = Some(capsule_dir.clone());
+                    Err(e) => eprintln!("Failed to verify backup: {}", e),
+                }
+            }
+            MainWindowMsg::RecordFileManifest(capsule_dir) => {
+                self.start_integrity_job(sender, capsule_dir, false);
+            }
+            MainWindowMsg::FileManifestRecorded(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(count) => println!("Recorded file manifest for {} files", count),
+                    Err(e) => eprintln!("Failed to record file manifest: {}", e),
+                }
+            }
+            MainWindowMsg::VerifyGameFiles(capsule_dir) => {
+                self.start_integrity_job(sender, capsule_dir, true);
+            }
+            MainWindowMsg::GameFilesVerified(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(report) if report.is_clean() => println!("{}", report.summary()),
+                    Ok(report) => {
+                        eprintln!("{}", report.summary());
+                        for path in &report.modified {
+                            eprintln!("  modified: {}", path);
                         }
-                        if let Some(metadata) = deps_metadata {
-                            self.open_dependency_dialog(sender.clone(), capsule_dir.clone(), metadata);
+                        for path in &report.missing {
+                            eprintln!("  missing: {}", path);
                         }
-                    } else if needs_exe {
-                        self.open_game_settings_dialog(sender.clone(), capsule_dir.clone());
                     }
-                    println!("Installer completed for {:?}", capsule_dir);
+                    Err(e) => eprintln!("Failed to verify game files: {}", e),
+                }
+            }
+            MainWindowMsg::BackupFileDialogClosed => {
+                self.backup_file_dialog = None;
+            }
+            MainWindowMsg::OpenCloudSyncSettings => {
+                self.open_cloud_sync_dialog(sender);
+            }
+            MainWindowMsg::CloudSyncSettingsSaved(config) => {
+                if let Err(e) = config.save() {
+                    eprintln!("Failed to save cloud sync settings: {}", e);
                 } else {
-                    eprintln!("Installer failed for {:?}", capsule_dir);
+                    println!("Saved cloud sync settings");
+                }
+                self.cloud_sync_config = config;
+            }
+            MainWindowMsg::CloudSyncDialogClosed => {
+                self.cloud_sync_dialog = None;
+            }
+            MainWindowMsg::OpenNetworkSettings => {
+                self.open_network_dialog(sender);
+            }
+            MainWindowMsg::NetworkSettingsSaved(settings) => {
+                if let Err(e) = settings.save() {
+                    eprintln!("Failed to save network settings: {}", e);
+                } else {
+                    println!("Saved network settings");
+                }
+            }
+            MainWindowMsg::RecoverCapsuleMetadata(capsule_dir) => {
+                match Capsule::recover_metadata(&capsule_dir) {
+                    Ok(capsule) => println!("Restored {} to the library", capsule.name),
+                    Err(e) => eprintln!("Failed to recover metadata: {:#}", e),
                 }
                 sender.input(MainWindowMsg::LoadCapsules);
             }
-            MainWindowMsg::UmuDatabaseLoaded(entries) => {
-                self.umu_entries = entries;
-                self.umu_loaded = true;
-                self.umu_load_error = None;
-                println!("UMU database loaded ({} entries).", self.umu_entries.len());
+            MainWindowMsg::MetadataRecoveryDialogClosed => {
+                self.metadata_recovery_dialog = None;
             }
-            MainWindowMsg::UmuDatabaseFailed(error) => {
-                self.umu_loaded = true;
-                self.umu_load_error = Some(error.clone());
-                eprintln!("UMU database load failed: {}", error);
+            MainWindowMsg::NetworkDialogClosed => {
+                self.network_dialog = None;
             }
-            MainWindowMsg::UmuMatchChosen { game_id, store } => {
-                match self.pending_add_mode {
-                    Some(AddGameMode::Installer) => {
-                        self.finalize_pending_game(sender, game_id, store);
+            MainWindowMsg::OpenStorage => {
+                self.open_storage_dialog(sender);
+            }
+            MainWindowMsg::DedupeScanProgress { hashed, total } => {
+                if let Some(progress) = &self.storage_progress_bar {
+                    if total > 0 {
+                        progress.set_fraction((hashed as f64 / total as f64).min(1.0));
                     }
-                    Some(AddGameMode::Existing) => {
-                        if self.pending_game_name.is_none() {
-                            eprintln!("No pending game name for existing game");
-                            return;
-                        }
-                        self.pending_game_id = game_id;
-                        self.pending_store = store;
-                        self.open_existing_source_folder_dialog(sender);
+                }
+                if let Some(status) = &self.storage_status_label {
+                    status.set_label(&format!(
+                        "Comparing prefix files: {} / {}",
+                        file_ops::format_bytes(hashed),
+                        file_ops::format_bytes(total)
+                    ));
+                }
+            }
+            MainWindowMsg::DedupeScanFinished(result) => {
+                self.dedupe_scan_cancel = None;
+                if self.storage_dialog.is_none() {
+                    return;
+                }
+                if let Some(progress) = &self.storage_progress_bar {
+                    progress.set_visible(false);
+                }
+                let text = match &result {
+                    Ok(report) => report.summary(),
+                    Err(e) => format!("Scan failed: {}", e),
+                };
+                if let Some(status) = &self.storage_status_label {
+                    status.set_label(&text);
+                }
+                let ready = matches!(&result, Ok(report) if report.reclaimable_bytes() > 0);
+                if let Some(dialog) = &self.storage_dialog {
+                    dialog.set_response_sensitive(ResponseType::Accept, ready);
+                }
+                self.dedupe_report = result.ok();
+            }
+            MainWindowMsg::DedupeRequested(options) => {
+                if !self.active_games.is_empty() || !self.active_installs.is_empty() {
+                    eprintln!("Close running games and installers before deduplicating");
+                    return;
+                }
+                let report = match self.dedupe_report.take() {
+                    Some(report) => report,
+                    None => return,
+                };
+                if let Some(dialog) = &self.storage_dialog {
+                    dialog.set_response_sensitive(ResponseType::Accept, false);
+                }
+                if let Some(status) = &self.storage_status_label {
+                    status.set_label("Deduplicating prefix files...");
+                }
+                self.dedupe_running = true;
+                self.refresh_game_cards();
+
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let result =
+                        PrefixDedupe::run(&report, options).map_err(|e| format!("{:#}", e));
+                    let _ = sender_clone.input(MainWindowMsg::DedupeFinished(result));
+                });
+            }
+            MainWindowMsg::DedupeFinished(result) => {
+                self.dedupe_running = false;
+                let text = match result {
+                    Ok(outcome) => outcome.summary(),
+                    Err(e) => format!("Deduplication failed: {}", e),
+                };
+                println!("{}", text);
+                if let Some(status) = &self.storage_status_label {
+                    status.set_label(&text);
+                }
+                self.refresh_game_cards();
+            }
+            MainWindowMsg::StorageDialogClosed => {
+                if let Some(cancel) = self.dedupe_scan_cancel.take() {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                self.storage_dialog = None;
+                self.storage_status_label = None;
+                self.storage_progress_bar = None;
+                self.dedupe_report = None;
+            }
+            MainWindowMsg::SyncSaves { capsule_dir, direction } => {
+                self.start_cloud_sync(sender, capsule_dir, direction);
+            }
+            MainWindowMsg::CloudSyncFinished { capsule_dir, result } => {
+                self.cloud_sync_jobs.remove(&capsule_dir);
+                match result {
+                    Ok(SyncOutcome::Conflict {
+                        local_modified,
+                        remote_modified,
+                    }) => {
+                        self.open_sync_conflict_dialog(
+                            sender.clone(),
+                            capsule_dir,
+                            local_modified,
+                            remote_modified,
+                        );
                     }
-                    None => {
-                        eprintln!("Add game mode not set");
+                    Ok(outcome) => {
+                        println!("Cloud sync for {:?}: {:?}", capsule_dir, outcome);
+                        self.quit_if_launch_done(&capsule_dir);
+                    }
+                    Err(e) => {
+                        eprintln!("Cloud sync failed for {:?}: {}", capsule_dir, e);
+                        self.quit_if_launch_done(&capsule_dir);
                     }
                 }
+                self.refresh_game_cards();
             }
-            MainWindowMsg::UmuMatchDialogClosed => {
-                self.umu_match_dialog = None;
+            MainWindowMsg::SnapshotsDialogClosed => {
+                self.snapshots_dialog = None;
             }
-            MainWindowMsg::DependenciesSelected {
+            MainWindowMsg::OpenLocalFix(capsule_dir) => {
+                self.open_local_fix_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::SaveLocalFix { capsule_dir, source } => {
+                let fix = Capsule::load_from_dir(&capsule_dir)
+                    .ok()
+                    .and_then(|capsule| capsule.metadata.game_id)
+                    .and_then(|game_id| LocalFix::for_game(&game_id));
+                match fix.map(|fix| fix.save(&source).map(|_| fix)) {
+                    Some(Ok(fix)) if source.trim().is_empty() => {
+                        println!("Removed local protonfix {:?}", fix.path)
+                    }
+                    Some(Ok(fix)) => println!("Saved local protonfix {:?}", fix.path),
+                    Some(Err(e)) => eprintln!("Failed to save local protonfix: {:#}", e),
+                    None => eprintln!("The game has no GAMEID to name a local protonfix after"),
+                }
+            }
+            MainWindowMsg::LocalFixDialogClosed => {
+                self.local_fix_dialog = None;
+            }
+            MainWindowMsg::OpenDiagnostics(capsule_dir) => {
+                self.open_diagnostics_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::OpenGameDetails(capsule_dir) => {
+                self.open_game_details_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::GameDetailsClosed => {
+                self.details_dialog = None;
+                self.details_capsule = None;
+                self.details_hltb_label = None;
+                self.details_hltb_buttons = None;
+                self.details_igdb = None;
+            }
+            MainWindowMsg::IgdbFetched {
                 capsule_dir,
-                install_vcredist,
-                install_dxweb,
-                force,
+                result,
             } => {
-                match Capsule::load_from_dir(&capsule_dir) {
-                    Ok(mut capsule) => {
-                        capsule.metadata.install_vcredist = install_vcredist;
-                        capsule.metadata.install_dxweb = install_dxweb;
-                        if let Err(e) = capsule.save_metadata() {
-                            eprintln!("Failed to update metadata: {}", e);
+                let found = match result {
+                    Ok(Some(info)) => Ok(info),
+                    Ok(None) => Err("No match on IGDB.".to_string()),
+                    Err(e) => {
+                        eprintln!("IGDB lookup failed: {}", e);
+                        Err(format!("Lookup failed: {}", e))
+                    }
+                };
+                let info = match found {
+                    Ok(info) => info,
+                    Err(text) => {
+                        if self.details_capsule.as_ref() == Some(&capsule_dir) {
+                            if let Some((label, button)) = &self.details_igdb {
+                                label.set_text(&text);
+                                button.set_sensitive(true);
+                            }
                         }
-                        self.start_dependency_install(
-                            sender.clone(),
-                            capsule_dir,
-                            capsule.metadata.clone(),
-                            install_vcredist,
-                            install_dxweb,
-                            force,
-                        );
+                        return;
                     }
+                };
+                let mut capsule = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule,
                     Err(e) => {
                         eprintln!("Failed to load capsule: {}", e);
+                        return;
+                    }
+                };
+                // Only fills what the user left empty.
+                if capsule.metadata.release_year.is_none() {
+                    capsule.metadata.release_year = info.release_year();
+                }
+                if capsule.metadata.developer.is_none() {
+                    capsule.metadata.developer = info.developer.clone();
+                }
+                println!("Matched {} to \"{}\" on IGDB", capsule.metadata.title(), info.name);
+                capsule.metadata.igdb = Some(info);
+                if let Err(e) = capsule.save_metadata() {
+                    eprintln!("Failed to update metadata: {}", e);
+                    return;
+                }
+                sender.input(MainWindowMsg::LoadCapsules);
+                if self.details_capsule.as_ref() == Some(&capsule_dir) {
+                    // Rebuilt to show the cover and description.
+                    if let Some(dialog) = self.details_dialog.take() {
+                        dialog.close();
                     }
+                    self.details_capsule = None;
+                    self.details_hltb_label = None;
+                    self.details_hltb_buttons = None;
+                    self.details_igdb = None;
+                    self.open_game_details_dialog(sender, capsule_dir);
                 }
             }
-            MainWindowMsg::DependenciesFinished { capsule_dir, installed } => {
-                self.dependency_installs.remove(&capsule_dir);
-                match Capsule::load_from_dir(&capsule_dir) {
-                    Ok(mut capsule) => {
-                        let mut updated = false;
-                        for dep in installed {
-                            if !capsule.metadata.redistributables_installed.contains(&dep) {
-                                capsule.metadata.redistributables_installed.push(dep);
-                                updated = true;
-                            }
+            MainWindowMsg::HowLongToBeatFetched {
+                capsule_dir,
+                result,
+            } => {
+                if self.details_capsule.as_ref() != Some(&capsule_dir) {
+                    return;
+                }
+                let (lookup_button, page_button) = match &self.details_hltb_buttons {
+                    Some(buttons) => buttons,
+                    None => return,
+                };
+                lookup_button.set_sensitive(true);
+                if let Some(label) = &self.details_hltb_label {
+                    match &result {
+                        Ok(lookup) => {
+                            label.set_text(&Self::hltb_text(Some(lookup)));
+                            lookup_button.set_label("Look up again");
+                            page_button.set_visible(lookup.times.is_some());
                         }
-                        if updated {
-                            if let Err(e) = capsule.save_metadata() {
-                                eprintln!("Failed to update metadata: {}", e);
-                            }
+                        Err(e) => {
+                            eprintln!("HowLongToBeat lookup failed: {}", e);
+                            label.set_text(&format!("Lookup failed: {}", e));
                         }
                     }
+                }
+            }
+            MainWindowMsg::DiagnosticsDialogClosed => {
+                self.diagnostics_dialog = None;
+            }
+            MainWindowMsg::ShowLaunchCommand(capsule_dir) => {
+                self.open_launch_command_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::LaunchCommandDialogClosed => {
+                self.launch_command_dialog = None;
+            }
+            MainWindowMsg::ClearLogs(capsule_dir) => {
+                // A running game still writes to its log.
+                if self.active_games.contains_key(&capsule_dir) {
+                    return;
+                }
+                let result = Capsule::load_from_dir(&capsule_dir)
+                    .and_then(|capsule| Ok((LaunchLog::clear(&capsule)?, capsule.name)));
+                match result {
+                    Ok((freed, name)) => println!(
+                        "Cleared the logs of {} ({} freed)",
+                        name,
+                        file_ops::format_bytes(freed)
+                    ),
+                    Err(e) => eprintln!("Failed to clear logs: {}", e),
+                }
+            }
+            MainWindowMsg::GenerateSupportBundle { capsule_dir, dest } => {
+                let capsule = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule,
                     Err(e) => {
                         eprintln!("Failed to load capsule: {}", e);
+                        return;
                     }
-                }
-                self.rebuild_games_list(sender.clone());
+                };
+                let system_check = self.system_check.clone();
+                let runtimes = self.runtime_mgr.list_installed().unwrap_or_default();
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let result = SupportBundle::write(&capsule, &system_check, &runtimes, &dest)
+                        .map(|_| dest)
+                        .map_err(|e| format!("{:#}", e));
+                    let _ = sender_clone.input(MainWindowMsg::SupportBundleFinished(result));
+                });
             }
-            MainWindowMsg::DependenciesDialogClosed => {
-                self.dependency_dialog = None;
-                if let Some(capsule_dir) = self.pending_settings_capsule.take() {
+            MainWindowMsg::SupportBundleFinished(result) => match result {
+                Ok(path) => println!("Saved support bundle to {:?}", path),
+                Err(e) => eprintln!("Failed to create support bundle: {}", e),
+            },
+            MainWindowMsg::ApplyDiagnosticFix { capsule_dir, fix } => match fix {
+                DiagnosticFix::SelectExecutable => {
                     self.open_game_settings_dialog(sender, capsule_dir);
                 }
-            }
-            MainWindowMsg::LaunchGame(capsule_dir) => {
-                if self.active_games.contains_key(&capsule_dir) {
+                DiagnosticFix::InitializePrefix => {
+                    self.start_prefix_initialize(sender, capsule_dir);
+                }
+                DiagnosticFix::OpenSystemSetup => {
+                    sender.input(MainWindowMsg::OpenSystemSetup);
+                }
+                DiagnosticFix::RemoveBrokenLinks(links) => {
+                    match PrefixDoctor::remove_broken_links(&links) {
+                        Ok(count) => println!("Removed {} broken symlinks", count),
+                        Err(e) => eprintln!("Failed to remove broken symlinks: {}", e),
+                    }
+                    sender.input(MainWindowMsg::OpenDiagnostics(capsule_dir));
+                }
+            },
+            MainWindowMsg::RepairRequested {
+                capsule_dir,
+                action,
+            } => {
+                if self.is_capsule_busy(&capsule_dir) {
+                    eprintln!("Cannot repair a capsule while it is busy");
                     return;
                 }
-                self.start_game(sender, capsule_dir);
+                self.open_repair_dialog(sender, capsule_dir, action);
             }
-            MainWindowMsg::GameStarted { capsule_dir, pgid } => {
-                self.active_games.insert(capsule_dir, pgid);
-                self.rebuild_games_list(sender.clone());
+            MainWindowMsg::RepairConfirmed {
+                capsule_dir,
+                action,
+            } => {
+                self.start_repair(sender, capsule_dir, action);
             }
-            MainWindowMsg::GameFinished { capsule_dir, success } => {
-                self.active_games.remove(&capsule_dir);
-                if success {
-                    println!("Game finished for {:?}", capsule_dir);
-                } else {
-                    eprintln!("Game failed for {:?}", capsule_dir);
+            MainWindowMsg::RepairDialogClosed => {
+                self.repair_dialog = None;
+            }
+            MainWindowMsg::RepairProgress { step, fraction } => {
+                if let Some(progress) = &self.copy_progress_bar {
+                    progress.set_fraction(fraction);
+                }
+                if let Some(status) = &self.copy_status_label {
+                    status.set_label(&step);
                 }
-                self.rebuild_games_list(sender.clone());
             }
-            MainWindowMsg::InstallerStarted { capsule_dir, pgid } => {
-                self.preparing_installs.remove(&capsule_dir);
-                self.active_installs.insert(capsule_dir, pgid);
-                self.rebuild_games_list(sender.clone());
+            MainWindowMsg::PrefixRepairFinished { capsule_dir, result } => {
+                self.close_copy_progress_dialog();
+                self.repair_jobs.remove(&capsule_dir);
+                match result {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("Prefix repair failed: {}", e),
+                }
+                self.refresh_game_cards();
             }
-            MainWindowMsg::EditGame(capsule_dir) => {
-                self.open_game_settings_dialog(sender, capsule_dir);
+            MainWindowMsg::DuplicateDialogClosed => {
+                self.duplicate_dialog = None;
             }
-            MainWindowMsg::SaveGameSettings {
+            MainWindowMsg::CapsuleDuplicateFinished(result) => {
+                self.close_copy_progress_dialog();
+                match result {
+                    Ok(new_dir) => println!("Duplicated capsule to {:?}", new_dir),
+                    Err(e) => eprintln!("Failed to duplicate capsule: {}", e),
+                }
+                sender.input(MainWindowMsg::LoadCapsules);
+            }
+            MainWindowMsg::RenameDialogClosed => {
+                self.rename_dialog = None;
+            }
+            MainWindowMsg::EditGameInfo(capsule_dir) => {
+                self.open_game_info_dialog(sender, capsule_dir);
+            }
+            MainWindowMsg::GameInfoSaved {
                 capsule_dir,
-                exe_path,
-                game_id,
-                store,
-                install_vcredist,
-                install_dxweb,
-                protonfixes_disable,
-                xalia_enabled,
-                protonfixes_tricks,
-                protonfixes_replace_cmds,
-                protonfixes_dxvk_sets,
+                display_title,
+                sort_title,
+                release_year,
+                developer,
+            } => match Capsule::load_from_dir(&capsule_dir) {
+                Ok(mut capsule) => {
+                    capsule.metadata.display_title = display_title;
+                    capsule.metadata.sort_title = sort_title;
+                    capsule.metadata.release_year = release_year;
+                    capsule.metadata.developer = developer;
+                    if let Err(e) = capsule.save_metadata() {
+                        eprintln!("Failed to update metadata: {}", e);
+                        return;
+                    }
+                    println!("Updated the details of {}", capsule.metadata.title());
+                    sender.input(MainWindowMsg::LoadCapsules);
+                }
+                Err(e) => {
+                    eprintln!("Failed to load capsule: {}", e);
+                }
+            },
+            MainWindowMsg::GameInfoDialogClosed => {
+                self.game_info_dialog = None;
+            }
+            MainWindowMsg::CapsuleFolderDialogClosed => {
+                self.capsule_folder_dialog = None;
+            }
+            MainWindowMsg::ResumeInstall(capsule_dir) => {
+                if self.preparing_installs.contains(&capsule_dir) {
+                    return;
+                }
+                let capsule = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule,
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                        return;
+                    }
+                };
+                // Hashing a large installer takes a while; the card shows it
+                // as preparing meanwhile.
+                self.preparing_installs.insert(capsule_dir.clone());
+                self.refresh_game_cards();
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let result =
+                        InstallerSource::check(&capsule.metadata).map_err(|e| e.to_string());
+                    sender_clone.input(MainWindowMsg::InstallerChecked {
+                        capsule_dir,
+                        result,
+                    });
+                });
+            }
+            MainWindowMsg::InstallerChecked {
+                capsule_dir,
+                result,
             } => {
-                match Capsule::load_from_dir(&capsule_dir) {
-                    Ok(mut capsule) => {
-                        if !exe_path.trim().is_empty() {
-                            capsule.metadata.executables.main.path = exe_path;
-                            capsule.metadata.install_state = InstallState::Installed;
-                        } else {
-                            capsule.metadata.executables.main.path = exe_path;
-                        }
-                        capsule.metadata.game_id = game_id;
-                        capsule.metadata.store = store;
-                        capsule.metadata.install_vcredist = install_vcredist;
-                        capsule.metadata.install_dxweb = install_dxweb;
-                        capsule.metadata.protonfixes_disable = protonfixes_disable;
-                        capsule.metadata.xalia_enabled = xalia_enabled;
-                        capsule.metadata.protonfixes_tricks = protonfixes_tricks;
-                        capsule.metadata.protonfixes_replace_cmds = protonfixes_replace_cmds;
-                        capsule.metadata.protonfixes_dxvk_sets = protonfixes_dxvk_sets;
-                        if let Err(e) = capsule.save_metadata() {
-                            eprintln!("Failed to update metadata: {}", e);
-                        } else {
-                            println!("Updated settings for {}", capsule.name);
-                            sender.input(MainWindowMsg::LoadCapsules);
+                self.preparing_installs.remove(&capsule_dir);
+                self.refresh_game_cards();
+                match result {
+                    Ok(InstallerCheck::Unchanged(installer_path))
+                    | Ok(InstallerCheck::Unrecorded(installer_path)) => {
+                        match Capsule::load_from_dir(&capsule_dir) {
+                            Ok(capsule) => {
+                                self.start_installer(
+                                    &sender,
+                                    capsule_dir,
+                                    capsule.metadata,
+                                    installer_path,
+                                );
+                                self.refresh_game_cards();
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to load capsule: {}", e);
+                            }
                         }
                     }
+                    Ok(check) => self.open_installer_check_dialog(sender, capsule_dir, check),
                     Err(e) => {
-                        eprintln!("Failed to load capsule: {}", e);
+                        eprintln!("Failed to check the installer: {}", e);
                     }
                 }
             }
-            MainWindowMsg::SettingsDialogClosed => {
-                self.settings_dialog = None;
+            MainWindowMsg::RelocateInstaller(capsule_dir) => {
+                self.open_installer_chooser(sender, capsule_dir);
             }
-            MainWindowMsg::DeleteGame(capsule_dir) => {
-                if let Err(e) = fs::remove_dir_all(&capsule_dir) {
-                    eprintln!("Failed to delete capsule: {}", e);
-                } else {
-                    println!("Deleted capsule {:?}", capsule_dir);
-                    sender.input(MainWindowMsg::LoadCapsules);
+            MainWindowMsg::ResumeInstallWith {
+                capsule_dir,
+                installer_path,
+            } => match Capsule::load_from_dir(&capsule_dir) {
+                Ok(mut capsule) => {
+                    // Picked knowingly, so its checksum is recorded anew.
+                    capsule.metadata.installer_sha256 = None;
+                    self.start_installer(&sender, capsule_dir, capsule.metadata, installer_path);
+                    self.refresh_game_cards();
+                }
+                Err(e) => {
+                    eprintln!("Failed to load capsule: {}", e);
                 }
+            },
+            MainWindowMsg::InstallerCheckDialogClosed => {
+                self.installer_check_dialog = None;
             }
-            MainWindowMsg::ResumeInstall(capsule_dir) => {
-                match Capsule::load_from_dir(&capsule_dir) {
-                    Ok(capsule) => {
-                        let installer_path = capsule
-                            .metadata
-                            .installer_path
-                            .as_ref()
-                            .map(PathBuf::from);
-                        if let Some(installer_path) = installer_path {
-                            self.start_installer(
-                                &sender,
-                                capsule_dir,
-                                capsule.metadata.clone(),
-                                installer_path,
-                            );
-                            self.rebuild_games_list(sender.clone());
-                        } else {
-                            eprintln!("No installer path found for {}", capsule.name);
-                        }
+            MainWindowMsg::InstallerHashed {
+                capsule_dir,
+                installer_path,
+                result,
+            } => {
+                let sha256 = match result {
+                    Ok(sha256) => sha256,
+                    Err(e) => {
+                        eprintln!("Failed to hash the installer: {}", e);
+                        return;
                     }
+                };
+                let mut capsule = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule,
                     Err(e) => {
                         eprintln!("Failed to load capsule: {}", e);
+                        return;
                     }
+                };
+                // Another installer may have been picked since.
+                let recorded = capsule.metadata.installer_path.as_deref().map(Path::new);
+                if recorded != Some(installer_path.as_path()) {
+                    return;
                 }
+                capsule.metadata.installer_sha256 = Some(sha256);
+                if let Err(e) = capsule.save_metadata() {
+                    eprintln!("Failed to update metadata: {}", e);
+                    return;
+                }
+                println!("Recorded the installer checksum for {}", capsule.metadata.title());
             }
             MainWindowMsg::KillInstall(capsule_dir) => {
                 if let Some(pgid) = self.active_installs.remove(&capsule_dir) {
@@ -3051,8 +13608,105 @@ impl SimpleComponent for MainWindow {
                         libc::kill(-pgid, libc::SIGKILL);
                     }
                     println!("Killed installer for {:?}", capsule_dir);
-                    self.rebuild_games_list(sender.clone());
+                    self.refresh_game_cards();
+                }
+            }
+            MainWindowMsg::ShutDownPrefix(capsule_dir) => {
+                if self.shutdown_jobs.contains(&capsule_dir) {
+                    return;
+                }
+                let capsule = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule,
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                        return;
+                    }
+                };
+                let proton_path = match self.runtime_mgr.latest_installed() {
+                    Ok(path) => path,
+                    Err(e) => {
+                        eprintln!("Failed to resolve Proton-GE runtime: {}", e);
+                        None
+                    }
+                };
+                // Game and installer threads notice their process exiting and
+                // clean up their own entries.
+                let pgids: Vec<i32> = self
+                    .active_games
+                    .get(&capsule_dir)
+                    .into_iter()
+                    .chain(self.active_installs.get(&capsule_dir))
+                    .copied()
+                    .collect();
+
+                self.shutdown_jobs.insert(capsule_dir.clone());
+                self.refresh_game_cards();
+
+                let sender_clone = sender.clone();
+                thread::spawn(move || {
+                    let prefix_path = capsule.home_path.join("prefix");
+                    let killed = PrefixProcesses::new(&prefix_path)
+                        .shutdown(proton_path.as_deref(), &pgids);
+                    let _ = sender_clone
+                        .input(MainWindowMsg::PrefixShutDownFinished { capsule_dir, killed });
+                });
+            }
+            MainWindowMsg::RunWineTool { capsule_dir, tool } => {
+                self.run_wine_tool(&capsule_dir, tool);
+            }
+            MainWindowMsg::OpenFolder(path) => {
+                let uri = gio::File::for_path(&path).uri();
+                if let Err(e) =
+                    gio::AppInfo::launch_default_for_uri(&uri, None::<&gio::AppLaunchContext>)
+                {
+                    eprintln!("Failed to open {:?}: {}", path, e);
+                }
+            }
+            MainWindowMsg::ViewScreenshots(capsule_dir) => {
+                let dir = match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => capsule.screenshots_dir(),
+                    Err(e) => {
+                        eprintln!("Failed to load capsule: {}", e);
+                        return;
+                    }
+                };
+                if let Err(e) = fs::create_dir_all(&dir) {
+                    eprintln!("Failed to create {:?}: {}", dir, e);
+                    return;
+                }
+                sender.input(MainWindowMsg::OpenFolder(dir));
+            }
+            MainWindowMsg::OpenUrl(url) => {
+                if let Err(e) =
+                    gio::AppInfo::launch_default_for_uri(&url, None::<&gio::AppLaunchContext>)
+                {
+                    eprintln!("Failed to open {}: {}", url, e);
+                }
+            }
+            MainWindowMsg::ProtonDbRatingFetched { app_id, result } => {
+                self.protondb_fetching.remove(&app_id);
+                match result {
+                    Ok(rating) => {
+                        self.protondb_ratings.insert(app_id, rating);
+                        if let Err(e) = ProtonDb::save_cache(&self.protondb_ratings) {
+                            eprintln!("Failed to save ProtonDB cache: {}", e);
+                        }
+                        self.refresh_game_cards();
+                    }
+                    Err(e) => eprintln!("ProtonDB lookup for {} failed: {}", app_id, e),
+                }
+            }
+            MainWindowMsg::PrefixShutDownFinished { capsule_dir, killed } => {
+                self.shutdown_jobs.remove(&capsule_dir);
+                if killed > 0 {
+                    println!(
+                        "Shut down prefix for {:?} ({} leftover processes killed)",
+                        capsule_dir, killed
+                    );
+                } else {
+                    println!("Shut down prefix for {:?}", capsule_dir);
                 }
+                self.refresh_game_cards();
             }
             MainWindowMsg::MarkInstallComplete(capsule_dir) => {
                 match Capsule::load_from_dir(&capsule_dir) {
@@ -3064,6 +13718,8 @@ impl SimpleComponent for MainWindow {
                         }
                         if capsule.metadata.executables.main.path.trim().is_empty() {
                             self.open_game_settings_dialog(sender.clone(), capsule_dir);
+                        } else {
+                            self.offer_add_to_steam(sender.clone(), &capsule);
                         }
                         sender.input(MainWindowMsg::LoadCapsules);
                     }
@@ -3097,6 +13753,85 @@ impl SimpleComponent for MainWindow {
             MainWindowMsg::SystemSetupOutput(SystemSetupOutput::SystemCheckUpdated(system_check)) => {
                 self.system_check = system_check;
             }
+            MainWindowMsg::Gamepad(action) => Self::handle_gamepad(action),
+            MainWindowMsg::OpenPreferences => {
+                self.open_preferences_dialog(sender);
+            }
+            MainWindowMsg::DeckSettingsSaved(settings) => {
+                if let Err(e) = settings.save() {
+                    eprintln!("Failed to save Steam Deck settings: {}", e);
+                    return;
+                }
+                println!("Saved Steam Deck settings");
+                self.deck_settings = settings;
+                let games_dir = Self::library_dir(&self.steam_deck, &self.deck_settings);
+                if games_dir != self.games_dir {
+                    println!("Library moved to {:?}", games_dir);
+                    self._library_monitor = Self::watch_games_dir(&games_dir, sender.clone());
+                    self.games_dir = games_dir;
+                    sender.input(MainWindowMsg::LoadCapsules);
+                }
+            }
+            MainWindowMsg::DiscordSettingsSaved(settings) => {
+                if let Err(e) = settings.save() {
+                    eprintln!("Failed to save Discord settings: {}", e);
+                    return;
+                }
+                println!("Saved Discord settings");
+                let restart = settings.client_id.trim() != self.discord_settings.client_id.trim();
+                if !settings.is_configured() {
+                    // Clear what is showing before the connection goes away.
+                    if let Some(discord) = self.discord.take() {
+                        discord.update(None);
+                    }
+                } else if restart || self.discord.is_none() {
+                    self.discord = Some(DiscordPresence::start(&settings.client_id));
+                }
+                self.discord_settings = settings;
+                self.update_discord_presence();
+            }
+            MainWindowMsg::RunnerSettingsSaved(settings) => {
+                if let Err(e) = settings.save() {
+                    eprintln!("Failed to save runner settings: {}", e);
+                    return;
+                }
+                println!("Saved runner settings");
+            }
+            MainWindowMsg::LogSettingsSaved(settings) => {
+                if let Err(e) = settings.save() {
+                    eprintln!("Failed to save log settings: {}", e);
+                    return;
+                }
+                println!("Saved log settings");
+            }
+            MainWindowMsg::IgdbSettingsSaved(settings) => {
+                if let Err(e) = settings.save() {
+                    eprintln!("Failed to save IGDB settings: {}", e);
+                    return;
+                }
+                println!("Saved IGDB settings");
+            }
+            MainWindowMsg::PreferencesDialogClosed => {
+                self.preferences_dialog = None;
+            }
+            MainWindowMsg::AddToSteam(capsule_dir) => {
+                match Capsule::load_from_dir(&capsule_dir) {
+                    Ok(capsule) => Self::add_to_steam(&capsule),
+                    Err(e) => eprintln!("Failed to load capsule: {}", e),
+                }
+            }
+            MainWindowMsg::AddToSteamDialogClosed => {
+                self.add_to_steam_dialog = None;
+            }
+            MainWindowMsg::ToggleTvMode => {
+                self.tv_mode = !self.tv_mode;
+                self.apply_tv_mode();
+                self.refresh_game_cards();
+                if self.tv_mode {
+                    // Controller-first: start on the first game's Play button.
+                    self.game_cards.widget().child_focus(DirectionType::TabForward);
+                }
+            }
         }
     }
 
@@ -1,2 +1,3 @@
+pub mod game_card;
 pub mod main_window;
 pub mod system_setup_dialog;
@@ -1,20 +1,50 @@
 use gtk4::prelude::*;
-use gtk4::{Dialog, Box, Label, Button, Image, Orientation, ProgressBar};
+use gtk4::{Dialog, Box, Label, Button, Image, Orientation, ProgressBar, SpinButton};
 use gtk4::gdk;
 use relm4::{ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+use crate::core::kernel::KernelCapabilities;
+use crate::core::package_manager::{GamingTool, ENABLE_I386_SCRIPT};
+use crate::core::redist::Redistributable;
 use crate::core::system_checker::SystemCheck;
-use crate::core::runtime_manager::RuntimeManager;
+use crate::core::runtime_manager::{DownloadCancelled, DownloadSettings, RuntimeManager};
+use crate::core::umu_installer::UmuInstallMethod;
+use crate::utils::host;
+
+/// After this long without progress the download is shown as stalled.
+const STALL_WARNING_SECS: u64 = 5;
 
 #[derive(Debug)]
 pub enum SystemSetupMsg {
     DownloadProton { reinstall: bool },
     DownloadProgress { status: String, progress: f64 },  // status text and 0.0-1.0 progress
     DownloadVersion(String),
+    /// Seconds since the download last reported progress.
+    DownloadStalled(u64),
     DownloadComplete,
     DownloadError(String),
+    CancelDownload,
+    DownloadCancelled,
+    /// Download speed cap in KB/s, 0 for none.
+    SetRateLimit(u64),
     CopySetupScript { reinstall: bool },
+    EnableI386,
+    I386Enabled(Result<(), String>),
+    InstallPackages,
+    PackagesInstalled(Result<(), String>),
+    CopyInstallCommand,
+    InstallUmu,
+    UmuInstalled(Result<(), String>),
+    InstallTool(GamingTool),
+    ToolInstalled(GamingTool, Result<(), String>),
+    RaiseMaxMapCount,
+    MaxMapCountRaised(Result<(), String>),
+    DownloadRedists,
+    RedistProgress { status: String, progress: f64 },
+    RedistsDownloaded(Result<(), String>),
     RefreshStatus,
     Refresh(SystemCheck),
     Close,
@@ -32,11 +62,27 @@ pub struct SystemSetupDialog {
     download_status: String,
     download_progress: f64,  // 0.0 to 1.0
     download_version: Option<String>,
+    download_stalled_secs: u64,
+    download_cancel: Arc<AtomicBool>,
+    download_settings: DownloadSettings,
     is_downloading: bool,
     proton_installed_version: Option<String>,
     umu_installed_version: Option<String>,
     umu_status_markup: String,
     proton_status_markup: String,
+    is_installing_packages: bool,
+    is_enabling_i386: bool,
+    package_status: String,
+    umu_install_method: Option<UmuInstallMethod>,
+    is_installing_umu: bool,
+    umu_install_status: String,
+    installing_tool: Option<GamingTool>,
+    tool_status: String,
+    is_raising_map_count: bool,
+    kernel_status: String,
+    is_downloading_redists: bool,
+    redist_status: String,
+    redist_progress: f64,
 }
 
 impl SystemSetupDialog {
@@ -55,7 +101,9 @@ impl SystemSetupDialog {
     }
 
     fn command_output(cmd: &str, args: &[&str]) -> Option<String> {
-        let output = Command::new(cmd).args(args).output().ok()?;
+        let mut command = Command::new(cmd);
+        command.args(args);
+        let output = host::command(command).output().ok()?;
         if !output.status.success() {
             return None;
         }
@@ -77,6 +125,17 @@ impl SystemSetupDialog {
         Self::command_output("umu-run", &["--version"])
     }
 
+    /// Progress bar text: the percentage, plus how long nothing has
+    /// arrived once the download looks stalled.
+    fn progress_text(&self) -> String {
+        let percent = format!("{:.0}%", self.download_progress * 100.0);
+        if self.download_stalled_secs >= STALL_WARNING_SECS {
+            format!("{} · no data for {}s", percent, self.download_stalled_secs)
+        } else {
+            percent
+        }
+    }
+
     fn update_status_markup(&mut self) {
         self.umu_status_markup = if self.system_check.umu_installed {
             if let Some(version) = &self.umu_installed_version {
@@ -337,6 +396,23 @@ impl SimpleComponent for SystemSetupDialog {
                                 set_halign: gtk4::Align::Start,
                                 set_wrap: true,
                             },
+
+                            append = &Label {
+                                #[watch]
+                                set_visible: !model.system_check.umu_installed
+                                    || !model.umu_install_status.is_empty(),
+                                #[watch]
+                                set_label: &if !model.umu_install_status.is_empty() {
+                                    model.umu_install_status.clone()
+                                } else if let Some(method) = model.umu_install_method {
+                                    format!("Install method: {}", method.label())
+                                } else {
+                                    "No install method found. Install flatpak, pipx or python3.".to_string()
+                                },
+                                set_css_classes: &["muted"],
+                                set_halign: gtk4::Align::Start,
+                                set_wrap: true,
+                            },
                         },
 
                         append = &Box {
@@ -363,7 +439,23 @@ impl SimpleComponent for SystemSetupDialog {
 
                                 append = &Button {
                                     #[watch]
-                                    set_visible: !model.system_check.umu_installed,
+                                    set_visible: !model.system_check.umu_installed
+                                        && model
+                                            .umu_install_method
+                                            .map(|method| method.installs_in_app())
+                                            .unwrap_or(false),
+                                    #[watch]
+                                    set_label: if model.is_installing_umu { "Installing..." } else { "Install" },
+                                    set_css_classes: &["accent"],
+                                    #[watch]
+                                    set_sensitive: !model.is_installing_umu,
+                                    connect_clicked => SystemSetupMsg::InstallUmu,
+                                },
+
+                                append = &Button {
+                                    #[watch]
+                                    set_visible: !model.system_check.umu_installed
+                                        && model.umu_install_method == Some(UmuInstallMethod::SetupScript),
                                     set_label: "Copy setup cmd",
                                     set_css_classes: &["secondary"],
                                     connect_clicked => SystemSetupMsg::CopySetupScript { reinstall: false },
@@ -494,6 +586,23 @@ impl SimpleComponent for SystemSetupDialog {
                                 set_halign: gtk4::Align::Start,
                                 set_wrap: true,
                             },
+
+                            append = &ProgressBar {
+                                #[watch]
+                                set_visible: model.is_downloading_redists,
+                                #[watch]
+                                set_fraction: model.redist_progress,
+                            },
+
+                            append = &Label {
+                                #[watch]
+                                set_visible: !model.redist_status.is_empty(),
+                                #[watch]
+                                set_label: &model.redist_status,
+                                set_css_classes: &["muted"],
+                                set_halign: gtk4::Align::Start,
+                                set_wrap: true,
+                            },
                         },
 
                         append = &Box {
@@ -523,22 +632,282 @@ impl SimpleComponent for SystemSetupDialog {
                             },
 
                             append = &Button {
-                                set_label: "Copy setup cmd",
+                                set_label: "Download",
                                 set_css_classes: &["secondary"],
-                                connect_clicked => SystemSetupMsg::CopySetupScript { reinstall: false },
+                                #[watch]
+                                set_visible: !model.system_check.vcredist_cached
+                                    || !model.system_check.dxweb_cached,
+                                #[watch]
+                                set_sensitive: !model.is_downloading_redists,
+                                connect_clicked => SystemSetupMsg::DownloadRedists,
+                            },
+                        },
+                    },
+
+                    // GameMode
+                    append = &Box {
+                        set_orientation: Orientation::Horizontal,
+                        set_spacing: 12,
+                        set_hexpand: true,
+                        set_css_classes: &["card", "setup-row"],
+
+                        append = &Image {
+                            set_icon_name: Some("power-profile-performance-symbolic"),
+                            set_pixel_size: 24,
+                        },
+
+                        append = &Box {
+                            set_orientation: Orientation::Vertical,
+                            set_spacing: 6,
+                            set_hexpand: true,
+
+                            append = &Label {
+                                set_label: "GameMode (Optional)",
+                                set_css_classes: &["card-title"],
+                                set_halign: gtk4::Align::Start,
+                            },
+
+                            append = &Label {
+                                set_label: GamingTool::GameMode.description(),
+                                set_css_classes: &["muted"],
+                                set_halign: gtk4::Align::Start,
+                                set_wrap: true,
+                            },
+                        },
+
+                        append = &Box {
+                            set_orientation: Orientation::Vertical,
+                            set_spacing: 6,
+                            set_halign: gtk4::Align::End,
+                            set_valign: gtk4::Align::Center,
+
+                            append = &Label {
+                                #[watch]
+                                set_markup: if model.system_check.gamemode_installed {
+                                    "<span foreground='#2ecc71'>✓ Installed</span>"
+                                } else {
+                                    "<span foreground='#f39c12'>✗ Not installed</span>"
+                                },
+                                #[watch]
+                                set_css_classes: if model.system_check.gamemode_installed {
+                                    &["pill", "pill-installed"]
+                                } else {
+                                    &["pill", "pill-warning"]
+                                },
+                                set_halign: gtk4::Align::End,
+                            },
+
+                            append = &Button {
+                                #[watch]
+                                set_visible: !model.system_check.gamemode_installed,
+                                #[watch]
+                                set_label: if model.installing_tool == Some(GamingTool::GameMode) {
+                                    "Installing..."
+                                } else {
+                                    "Install"
+                                },
+                                set_css_classes: &["accent"],
+                                #[watch]
+                                set_sensitive: model.system_check.package_manager.is_some()
+                                    && model.installing_tool.is_none(),
+                                connect_clicked => SystemSetupMsg::InstallTool(GamingTool::GameMode),
+                            },
+                        },
+                    },
+
+                    // MangoHud
+                    append = &Box {
+                        set_orientation: Orientation::Horizontal,
+                        set_spacing: 12,
+                        set_hexpand: true,
+                        set_css_classes: &["card", "setup-row"],
+
+                        append = &Image {
+                            set_icon_name: Some("utilities-system-monitor-symbolic"),
+                            set_pixel_size: 24,
+                        },
+
+                        append = &Box {
+                            set_orientation: Orientation::Vertical,
+                            set_spacing: 6,
+                            set_hexpand: true,
+
+                            append = &Label {
+                                set_label: "MangoHud (Optional)",
+                                set_css_classes: &["card-title"],
+                                set_halign: gtk4::Align::Start,
+                            },
+
+                            append = &Label {
+                                set_label: GamingTool::MangoHud.description(),
+                                set_css_classes: &["muted"],
+                                set_halign: gtk4::Align::Start,
+                                set_wrap: true,
+                            },
+                        },
+
+                        append = &Box {
+                            set_orientation: Orientation::Vertical,
+                            set_spacing: 6,
+                            set_halign: gtk4::Align::End,
+                            set_valign: gtk4::Align::Center,
+
+                            append = &Label {
+                                #[watch]
+                                set_markup: if model.system_check.mangohud_installed {
+                                    "<span foreground='#2ecc71'>✓ Installed</span>"
+                                } else {
+                                    "<span foreground='#f39c12'>✗ Not installed</span>"
+                                },
+                                #[watch]
+                                set_css_classes: if model.system_check.mangohud_installed {
+                                    &["pill", "pill-installed"]
+                                } else {
+                                    &["pill", "pill-warning"]
+                                },
+                                set_halign: gtk4::Align::End,
+                            },
+
+                            append = &Button {
+                                #[watch]
+                                set_visible: !model.system_check.mangohud_installed,
+                                #[watch]
+                                set_label: if model.installing_tool == Some(GamingTool::MangoHud) {
+                                    "Installing..."
+                                } else {
+                                    "Install"
+                                },
+                                set_css_classes: &["accent"],
+                                #[watch]
+                                set_sensitive: model.system_check.package_manager.is_some()
+                                    && model.installing_tool.is_none(),
+                                connect_clicked => SystemSetupMsg::InstallTool(GamingTool::MangoHud),
+                            },
+                        },
+                    },
+
+                    append = &Label {
+                        #[watch]
+                        set_visible: !model.tool_status.is_empty(),
+                        #[watch]
+                        set_label: &model.tool_status,
+                        set_halign: gtk4::Align::Start,
+                        set_wrap: true,
+                    },
+
+                    // Kernel
+                    append = &Box {
+                        set_orientation: Orientation::Horizontal,
+                        set_spacing: 12,
+                        set_hexpand: true,
+                        set_css_classes: &["card", "setup-row"],
+
+                        append = &Image {
+                            set_icon_name: Some("system-run-symbolic"),
+                            set_pixel_size: 24,
+                        },
+
+                        append = &Box {
+                            set_orientation: Orientation::Vertical,
+                            set_spacing: 6,
+                            set_hexpand: true,
+
+                            append = &Label {
+                                #[watch]
+                                set_label: &format!(
+                                    "Kernel {}",
+                                    model.system_check.kernel.release.as_deref().unwrap_or("")
+                                ),
+                                set_css_classes: &["card-title"],
+                                set_halign: gtk4::Align::Start,
+                            },
+
+                            append = &Label {
+                                #[watch]
+                                set_label: model.system_check.kernel.futex_explanation(),
+                                set_css_classes: &["muted"],
+                                set_halign: gtk4::Align::Start,
+                                set_wrap: true,
+                            },
+
+                            append = &Label {
+                                #[watch]
+                                set_label: &model.system_check.kernel.max_map_count_explanation(),
+                                set_css_classes: &["muted"],
+                                set_halign: gtk4::Align::Start,
+                                set_wrap: true,
+                            },
+
+                            append = &Label {
+                                #[watch]
+                                set_visible: !model.kernel_status.is_empty(),
+                                #[watch]
+                                set_label: &model.kernel_status,
+                                set_halign: gtk4::Align::Start,
+                                set_wrap: true,
+                            },
+                        },
+
+                        append = &Box {
+                            set_orientation: Orientation::Vertical,
+                            set_spacing: 6,
+                            set_halign: gtk4::Align::End,
+                            set_valign: gtk4::Align::Center,
+
+                            append = &Label {
+                                #[watch]
+                                set_markup: if model.system_check.kernel.futex_waitv {
+                                    "<span foreground='#2ecc71'>✓ fsync</span>"
+                                } else {
+                                    "<span foreground='#f39c12'>✗ esync only</span>"
+                                },
+                                #[watch]
+                                set_css_classes: if model.system_check.kernel.futex_waitv {
+                                    &["pill", "pill-installed"]
+                                } else {
+                                    &["pill", "pill-warning"]
+                                },
+                                set_halign: gtk4::Align::End,
+                            },
+
+                            append = &Label {
+                                #[watch]
+                                set_markup: if model.system_check.kernel.max_map_count_ok() {
+                                    "<span foreground='#2ecc71'>✓ max_map_count</span>"
+                                } else {
+                                    "<span foreground='#f39c12'>✗ max_map_count low</span>"
+                                },
+                                #[watch]
+                                set_css_classes: if model.system_check.kernel.max_map_count_ok() {
+                                    &["pill", "pill-installed"]
+                                } else {
+                                    &["pill", "pill-warning"]
+                                },
+                                set_halign: gtk4::Align::End,
+                            },
+
+                            append = &Button {
+                                #[watch]
+                                set_visible: !model.system_check.kernel.max_map_count_ok(),
+                                #[watch]
+                                set_label: if model.is_raising_map_count { "Applying..." } else { "Raise limit" },
+                                set_css_classes: &["accent"],
+                                #[watch]
+                                set_sensitive: !model.is_raising_map_count,
+                                connect_clicked => SystemSetupMsg::RaiseMaxMapCount,
                             },
                         },
                     },
                 },
 
-                // Missing APT packages section
+                // Missing system packages section
                 append = &Box {
                     set_orientation: Orientation::Vertical,
                     set_spacing: 8,
                     set_margin_top: 8,
                     set_css_classes: &["card"],
                     #[watch]
-                    set_visible: !model.system_check.missing_apt_packages.is_empty(),
+                    set_visible: !model.system_check.missing_packages.is_empty(),
 
                     append = &Box {
                         set_orientation: Orientation::Horizontal,
@@ -557,7 +926,16 @@ impl SimpleComponent for SystemSetupDialog {
                     },
 
                     append = &Label {
-                        set_label: "Install the following packages to enable graphics support:",
+                        #[watch]
+                        set_label: &match model.system_check.package_manager {
+                            Some(manager) => format!(
+                                "Install the following {} packages to enable graphics support:",
+                                manager.name()
+                            ),
+                            None => "No supported package manager found (apt, dnf, pacman, zypper). \
+                                     Install the equivalent of these packages for your distribution:"
+                                .to_string(),
+                        },
                         set_css_classes: &["muted"],
                         set_halign: gtk4::Align::Start,
                         set_wrap: true,
@@ -565,17 +943,78 @@ impl SimpleComponent for SystemSetupDialog {
 
                     append = &Label {
                         #[watch]
-                        set_label: &model.system_check.missing_apt_packages.join(" "),
+                        set_label: &model.system_check.missing_packages.join(" "),
                         set_halign: gtk4::Align::Start,
                         set_selectable: true,
                         set_wrap: true,
                     },
 
-                    append = &Button {
+                    append = &Label {
+                        #[watch]
+                        set_visible: model.system_check.package_warning.is_some(),
+                        #[watch]
+                        set_label: model.system_check.package_warning.as_deref().unwrap_or(""),
+                        set_css_classes: &["muted"],
                         set_halign: gtk4::Align::Start,
-                        set_label: "Copy setup command",
-                        set_css_classes: &["secondary"],
-                        connect_clicked => SystemSetupMsg::CopySetupScript { reinstall: false },
+                        set_wrap: true,
+                    },
+
+                    append = &Box {
+                        set_orientation: Orientation::Horizontal,
+                        set_spacing: 8,
+                        #[watch]
+                        set_visible: model.system_check.needs_i386,
+
+                        append = &Label {
+                            set_label: "32-bit (i386) packages are not enabled yet; they are needed for 32-bit games.",
+                            set_css_classes: &["muted"],
+                            set_halign: gtk4::Align::Start,
+                            set_hexpand: true,
+                            set_wrap: true,
+                        },
+
+                        append = &Button {
+                            #[watch]
+                            set_label: if model.is_enabling_i386 { "Enabling..." } else { "Enable 32-bit support" },
+                            set_css_classes: &["secondary"],
+                            #[watch]
+                            set_sensitive: !model.is_enabling_i386 && !model.is_installing_packages,
+                            connect_clicked => SystemSetupMsg::EnableI386,
+                        },
+                    },
+
+                    append = &Box {
+                        set_orientation: Orientation::Horizontal,
+                        set_spacing: 8,
+
+                        append = &Button {
+                            #[watch]
+                            set_label: if model.is_installing_packages { "Installing..." } else { "Install packages" },
+                            set_css_classes: &["accent"],
+                            #[watch]
+                            set_sensitive: model.system_check.package_manager.is_some()
+                                && !model.system_check.needs_i386
+                                && !model.is_installing_packages
+                                && !model.is_enabling_i386,
+                            connect_clicked => SystemSetupMsg::InstallPackages,
+                        },
+
+                        append = &Button {
+                            set_label: "Copy install command",
+                            set_css_classes: &["secondary"],
+                            #[watch]
+                            set_sensitive: model.system_check.package_manager.is_some(),
+                            connect_clicked => SystemSetupMsg::CopyInstallCommand,
+                        },
+                    },
+
+                    append = &Label {
+                        #[watch]
+                        set_visible: !model.package_status.is_empty(),
+                        #[watch]
+                        set_label: &model.package_status,
+                        set_halign: gtk4::Align::Start,
+                        set_wrap: true,
                     },
                 },
 
@@ -624,12 +1063,30 @@ impl SimpleComponent for SystemSetupDialog {
                         set_halign: gtk4::Align::Start,
                     },
 
-                    append = &ProgressBar {
+                    append = &Box {
+                        set_orientation: Orientation::Horizontal,
+                        set_spacing: 8,
                         #[watch]
                         set_visible: model.is_downloading,
-                        #[watch]
-                        set_fraction: model.download_progress,
-                        set_show_text: true,
+
+                        append = &ProgressBar {
+                            set_hexpand: true,
+                            set_valign: gtk4::Align::Center,
+                            #[watch]
+                            set_fraction: model.download_progress,
+                            #[watch]
+                            set_text: Some(&model.progress_text()),
+                            set_show_text: true,
+                        },
+
+                        append = &Button {
+                            set_label: "Cancel",
+                            set_css_classes: &["secondary"],
+                            set_tooltip_text: Some("Stop the download; the next download resumes where it stopped"),
+                            #[watch]
+                            set_sensitive: model.download_progress < 0.9,
+                            connect_clicked => SystemSetupMsg::CancelDownload,
+                        },
                     },
                 },
 
@@ -638,6 +1095,24 @@ impl SimpleComponent for SystemSetupDialog {
                     set_vexpand: true,
                 },
 
+                append = &Box {
+                    set_orientation: Orientation::Horizontal,
+                    set_spacing: 8,
+
+                    append = &Label {
+                        set_label: "Download speed limit (KB/s, 0 = unlimited)",
+                        set_css_classes: &["muted"],
+                        set_halign: gtk4::Align::Start,
+                    },
+
+                    append = &SpinButton::with_range(0.0, 1_000_000.0, 100.0) {
+                        set_value: model.download_settings.max_rate_kbps.unwrap_or(0) as f64,
+                        connect_value_changed[sender] => move |spin| {
+                            sender.input(SystemSetupMsg::SetRateLimit(spin.value() as u64));
+                        },
+                    },
+                },
+
                 // Bottom buttons
                 append = &Box {
                     set_orientation: Orientation::Horizontal,
@@ -680,17 +1155,34 @@ impl SimpleComponent for SystemSetupDialog {
             None
         };
 
+        let umu_install_method = UmuInstallMethod::detect(system_check.package_manager);
         let mut model = SystemSetupDialog {
             system_check,
             runtime_mgr,
             download_status: String::new(),
             download_progress: 0.0,
             download_version: None,
+            download_stalled_secs: 0,
+            download_cancel: Arc::new(AtomicBool::new(false)),
+            download_settings: DownloadSettings::load(),
             is_downloading: false,
             proton_installed_version,
             umu_installed_version,
             umu_status_markup: String::new(),
             proton_status_markup: String::new(),
+            is_installing_packages: false,
+            is_enabling_i386: false,
+            package_status: String::new(),
+            umu_install_method,
+            is_installing_umu: false,
+            umu_install_status: String::new(),
+            installing_tool: None,
+            tool_status: String::new(),
+            is_raising_map_count: false,
+            kernel_status: String::new(),
+            is_downloading_redists: false,
+            redist_status: String::new(),
+            redist_progress: 0.0,
         };
 
         model.update_status_markup();
@@ -715,14 +1207,18 @@ impl SimpleComponent for SystemSetupDialog {
                 }
                 self.download_progress = 0.0;
                 self.download_version = None;
+                self.download_stalled_secs = 0;
+                self.download_cancel = Arc::new(AtomicBool::new(false));
                 
                 let runtime_mgr = self.runtime_mgr.clone();
                 let sender_clone = sender.clone();
+                let cancel = self.download_cancel.clone();
                 
                 enum DownloadUpdate {
                     Progress { status: String, progress: f64 },
                     Version(String),
                     Complete,
+                    Cancelled,
                     Error(String),
                 }
 
@@ -742,13 +1238,16 @@ impl SimpleComponent for SystemSetupDialog {
                             });
                             
                             // Install with progress callbacks that send to channel
-                            match runtime_mgr.install_proton_ge(&release, reinstall, |status, progress| {
+                            match runtime_mgr.install_proton_ge(&release, reinstall, &cancel, |status, progress| {
                                 let _ = tx.send(DownloadUpdate::Progress { status, progress });
                             }) {
                                 Ok(path) => {
                                     println!("✓ Proton-GE installed successfully to: {:?}", path);
                                     let _ = tx.send(DownloadUpdate::Complete);
                                 }
+                                Err(e) if e.is::<DownloadCancelled>() => {
+                                    let _ = tx.send(DownloadUpdate::Cancelled);
+                                }
                                 Err(e) => {
                                     eprintln!("✗ Installation failed: {}", e);
                                     let _ = tx.send(DownloadUpdate::Error(e.to_string()));
@@ -763,12 +1262,30 @@ impl SimpleComponent for SystemSetupDialog {
                 });
                 
                 // Poll the channel from GTK main thread
+                let mut last_update = std::time::Instant::now();
+                let mut reported_stall = 0;
+                // Past 0.9 the archive is being extracted, which reports no progress.
+                let mut downloading = true;
                 glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
                     // Drain all available messages
                     let mut last_msg = None;
                     while let Ok(msg) = rx.try_recv() {
                         last_msg = Some(msg);
                     }
+
+                    if let Some(DownloadUpdate::Progress { progress, .. }) = &last_msg {
+                        downloading = *progress < 0.9;
+                    }
+                    if last_msg.is_some() {
+                        last_update = std::time::Instant::now();
+                        reported_stall = 0;
+                    } else if downloading {
+                        let stalled = last_update.elapsed().as_secs();
+                        if stalled >= STALL_WARNING_SECS && stalled != reported_stall {
+                            reported_stall = stalled;
+                            let _ = sender_clone.input(SystemSetupMsg::DownloadStalled(stalled));
+                        }
+                    }
                     
                     if let Some(update) = last_msg {
                         match update {
@@ -785,6 +1302,10 @@ impl SimpleComponent for SystemSetupDialog {
                                 let _ = sender_clone.input(SystemSetupMsg::DownloadComplete);
                                 return glib::ControlFlow::Break;
                             }
+                            DownloadUpdate::Cancelled => {
+                                let _ = sender_clone.input(SystemSetupMsg::DownloadCancelled);
+                                return glib::ControlFlow::Break;
+                            }
                             DownloadUpdate::Error(error) => {
                                 let _ = sender_clone.input(SystemSetupMsg::DownloadError(error));
                                 return glib::ControlFlow::Break;
@@ -803,6 +1324,11 @@ impl SimpleComponent for SystemSetupDialog {
             SystemSetupMsg::DownloadProgress { status, progress } => {
                 self.download_status = status;
                 self.download_progress = progress;
+                self.download_stalled_secs = 0;
+            }
+
+            SystemSetupMsg::DownloadStalled(seconds) => {
+                self.download_stalled_secs = seconds;
             }
             
             SystemSetupMsg::DownloadComplete => {
@@ -828,12 +1354,292 @@ impl SimpleComponent for SystemSetupDialog {
                 self.download_progress = 0.0;
             }
 
+            SystemSetupMsg::CancelDownload => {
+                if self.is_downloading {
+                    println!("Cancelling Proton-GE download...");
+                    self.download_cancel.store(true, Ordering::Relaxed);
+                    self.download_status = "Cancelling...".to_string();
+                }
+            }
+
+            SystemSetupMsg::DownloadCancelled => {
+                self.is_downloading = false;
+                self.download_status =
+                    "Download cancelled. The partial file is kept for the next download.".to_string();
+                self.download_stalled_secs = 0;
+            }
+
+            SystemSetupMsg::SetRateLimit(kbps) => {
+                // Read back first so mirrors edited by hand are kept.
+                self.download_settings = DownloadSettings::load();
+                self.download_settings.max_rate_kbps = if kbps == 0 { None } else { Some(kbps) };
+                if let Err(e) = self.download_settings.save() {
+                    eprintln!("Failed to save download settings: {}", e);
+                }
+            }
+
             SystemSetupMsg::CopySetupScript { reinstall } => {
                 let command = Self::setup_script_command(reinstall);
                 Self::copy_to_clipboard(&command);
                 println!("Copied to clipboard: {}", command);
             }
 
+            SystemSetupMsg::EnableI386 => {
+                if self.is_enabling_i386 {
+                    return;
+                }
+                println!("Enabling i386 multiarch: {}", ENABLE_I386_SCRIPT);
+                self.is_enabling_i386 = true;
+                self.package_status = "Enabling 32-bit support...".to_string();
+
+                let sender_clone = sender.clone();
+                std::thread::spawn(move || {
+                    let mut pkexec = Command::new("pkexec");
+                    pkexec.args(["sh", "-c", ENABLE_I386_SCRIPT]);
+                    let result = match host::command(pkexec).status() {
+                        Ok(status) if status.success() => Ok(()),
+                        Ok(status) => Err(format!("{} exited with {}", ENABLE_I386_SCRIPT, status)),
+                        Err(e) => Err(format!("Failed to run pkexec: {}", e)),
+                    };
+                    sender_clone.input(SystemSetupMsg::I386Enabled(result));
+                });
+            }
+
+            SystemSetupMsg::I386Enabled(result) => {
+                self.is_enabling_i386 = false;
+                match result {
+                    Ok(()) => {
+                        println!("✓ i386 multiarch enabled");
+                        self.package_status = "✓ 32-bit support enabled".to_string();
+                        sender.input(SystemSetupMsg::RefreshStatus);
+                    }
+                    Err(e) => {
+                        eprintln!("✗ Enabling i386 failed: {}", e);
+                        self.package_status = format!("✗ Error: {}", e);
+                    }
+                }
+            }
+
+            SystemSetupMsg::InstallPackages => {
+                let manager = match self.system_check.package_manager {
+                    Some(manager) => manager,
+                    None => return,
+                };
+                if self.is_installing_packages
+                    || self.is_enabling_i386
+                    || self.system_check.needs_i386
+                    || self.system_check.missing_packages.is_empty()
+                {
+                    return;
+                }
+                let script = manager.install_script(&self.system_check.missing_packages);
+                println!("Installing packages with {}: {}", manager.name(), script);
+                self.is_installing_packages = true;
+                self.package_status = format!("Installing with {}...", manager.name());
+
+                let sender_clone = sender.clone();
+                std::thread::spawn(move || {
+                    // pkexec asks for the password through the desktop's polkit agent.
+                    let mut pkexec = Command::new("pkexec");
+                    pkexec.args(["sh", "-c", &script]);
+                    let result = match host::command(pkexec).status() {
+                        Ok(status) if status.success() => Ok(()),
+                        Ok(status) => Err(format!("{} exited with {}", script, status)),
+                        Err(e) => Err(format!(
+                            "Failed to run pkexec ({}). Copy the install command and run it in a terminal.",
+                            e
+                        )),
+                    };
+                    sender_clone.input(SystemSetupMsg::PackagesInstalled(result));
+                });
+            }
+
+            SystemSetupMsg::PackagesInstalled(result) => {
+                self.is_installing_packages = false;
+                match result {
+                    Ok(()) => {
+                        println!("✓ System packages installed");
+                        self.package_status = "✓ Packages installed".to_string();
+                        sender.input(SystemSetupMsg::RefreshStatus);
+                    }
+                    Err(e) => {
+                        eprintln!("✗ Package install failed: {}", e);
+                        self.package_status = format!("✗ Error: {}", e);
+                    }
+                }
+            }
+
+            SystemSetupMsg::InstallUmu => {
+                let method = match self.umu_install_method {
+                    Some(method) if method.installs_in_app() => method,
+                    _ => return,
+                };
+                if self.is_installing_umu {
+                    return;
+                }
+                println!("Installing umu-launcher via {}", method.label());
+                self.is_installing_umu = true;
+                self.umu_install_status = format!("Installing via {}...", method.label());
+
+                let sender_clone = sender.clone();
+                std::thread::spawn(move || {
+                    let result = method.install().map_err(|e| format!("{:#}", e));
+                    sender_clone.input(SystemSetupMsg::UmuInstalled(result));
+                });
+            }
+
+            SystemSetupMsg::UmuInstalled(result) => {
+                self.is_installing_umu = false;
+                match result {
+                    Ok(()) => {
+                        println!("✓ umu-launcher installed");
+                        self.umu_install_status = "✓ umu-launcher installed".to_string();
+                        sender.input(SystemSetupMsg::RefreshStatus);
+                    }
+                    Err(e) => {
+                        eprintln!("✗ umu-launcher install failed: {}", e);
+                        self.umu_install_status = format!("✗ Error: {}", e);
+                    }
+                }
+            }
+
+            SystemSetupMsg::DownloadRedists => {
+                if self.is_downloading_redists {
+                    return;
+                }
+                self.is_downloading_redists = true;
+                self.redist_progress = 0.0;
+                self.redist_status = "Preparing download...".to_string();
+
+                let sender_clone = sender.clone();
+                std::thread::spawn(move || {
+                    let cancel = AtomicBool::new(false);
+                    let mut failures = Vec::new();
+                    for redist in Redistributable::defaults() {
+                        if redist.is_cached() {
+                            continue;
+                        }
+                        if let Err(e) = redist.download(false, &cancel, |status, progress| {
+                            sender_clone.input(SystemSetupMsg::RedistProgress { status, progress });
+                        }) {
+                            eprintln!("✗ {} download failed: {:#}", redist.label(), e);
+                            failures.push(format!("{}: {:#}", redist.label(), e));
+                        }
+                    }
+                    let result = if failures.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(failures.join("; "))
+                    };
+                    sender_clone.input(SystemSetupMsg::RedistsDownloaded(result));
+                });
+            }
+
+            SystemSetupMsg::RedistProgress { status, progress } => {
+                self.redist_status = status;
+                self.redist_progress = progress;
+            }
+
+            SystemSetupMsg::RedistsDownloaded(result) => {
+                self.is_downloading_redists = false;
+                match result {
+                    Ok(()) => {
+                        println!("✓ Redistributables cached");
+                        self.redist_status = "✓ Redistributables downloaded".to_string();
+                    }
+                    Err(e) => {
+                        self.redist_status = format!("✗ Error: {}", e);
+                    }
+                }
+                // Refresh either way: one of the two may have succeeded.
+                sender.input(SystemSetupMsg::RefreshStatus);
+            }
+
+            SystemSetupMsg::InstallTool(tool) => {
+                let manager = match self.system_check.package_manager {
+                    Some(manager) => manager,
+                    None => return,
+                };
+                if self.installing_tool.is_some() {
+                    return;
+                }
+                let script = manager.install_script(&tool.packages(manager));
+                println!("Installing {} with {}: {}", tool.label(), manager.name(), script);
+                self.installing_tool = Some(tool);
+                self.tool_status = format!("Installing {}...", tool.label());
+
+                let sender_clone = sender.clone();
+                std::thread::spawn(move || {
+                    let mut pkexec = Command::new("pkexec");
+                    pkexec.args(["sh", "-c", &script]);
+                    let result = match host::command(pkexec).status() {
+                        Ok(status) if status.success() => Ok(()),
+                        Ok(status) => Err(format!("{} exited with {}", script, status)),
+                        Err(e) => Err(format!("Failed to run pkexec: {}", e)),
+                    };
+                    sender_clone.input(SystemSetupMsg::ToolInstalled(tool, result));
+                });
+            }
+
+            SystemSetupMsg::ToolInstalled(tool, result) => {
+                self.installing_tool = None;
+                match result {
+                    Ok(()) => {
+                        println!("✓ {} installed", tool.label());
+                        self.tool_status = format!("✓ {} installed", tool.label());
+                        sender.input(SystemSetupMsg::RefreshStatus);
+                    }
+                    Err(e) => {
+                        eprintln!("✗ {} install failed: {}", tool.label(), e);
+                        self.tool_status = format!("✗ Error: {}", e);
+                    }
+                }
+            }
+
+            SystemSetupMsg::RaiseMaxMapCount => {
+                if self.is_raising_map_count {
+                    return;
+                }
+                let script = KernelCapabilities::max_map_count_fix_script();
+                println!("Raising vm.max_map_count: {}", script);
+                self.is_raising_map_count = true;
+                self.kernel_status = "Raising vm.max_map_count...".to_string();
+
+                let sender_clone = sender.clone();
+                std::thread::spawn(move || {
+                    let mut pkexec = Command::new("pkexec");
+                    pkexec.args(["sh", "-c", &script]);
+                    let result = match host::command(pkexec).status() {
+                        Ok(status) if status.success() => Ok(()),
+                        Ok(status) => Err(format!("{} exited with {}", script, status)),
+                        Err(e) => Err(format!("Failed to run pkexec: {}", e)),
+                    };
+                    sender_clone.input(SystemSetupMsg::MaxMapCountRaised(result));
+                });
+            }
+
+            SystemSetupMsg::MaxMapCountRaised(result) => {
+                self.is_raising_map_count = false;
+                match result {
+                    Ok(()) => {
+                        println!("✓ vm.max_map_count raised");
+                        self.kernel_status = "✓ vm.max_map_count raised".to_string();
+                        sender.input(SystemSetupMsg::RefreshStatus);
+                    }
+                    Err(e) => {
+                        eprintln!("✗ Raising vm.max_map_count failed: {}", e);
+                        self.kernel_status = format!("✗ Error: {}", e);
+                    }
+                }
+            }
+
+            SystemSetupMsg::CopyInstallCommand => {
+                if let Some(command) = self.system_check.install_command() {
+                    Self::copy_to_clipboard(&command);
+                    println!("Copied to clipboard: {}", command);
+                }
+            }
+
             SystemSetupMsg::RefreshStatus => {
                 self.system_check = SystemCheck::check();
                 if self.system_check.proton_installed {
@@ -0,0 +1,150 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Total size in bytes of the regular files below `path`. Symlinks are not followed.
+pub fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Copy a directory tree, reporting each written chunk. Symlinks are recreated
+/// rather than followed (Wine prefixes rely on them). The callback returns false
+/// to abort the copy.
+pub fn copy_dir_with_progress(
+    src: &Path,
+    dest: &Path,
+    on_chunk: &mut dyn FnMut(u64) -> bool,
+) -> io::Result<()> {
+    copy_dir_excluding(src, dest, &[], on_chunk)
+}
+
+/// Same as `copy_dir_with_progress`, but skips any entry whose source path is in
+/// `exclude`.
+pub fn copy_dir_excluding(
+    src: &Path,
+    dest: &Path,
+    exclude: &[PathBuf],
+    on_chunk: &mut dyn FnMut(u64) -> bool,
+) -> io::Result<()> {
+    if !dest.exists() {
+        fs::create_dir_all(dest)?;
+    }
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let from = entry.path();
+        let to = dest.join(entry.file_name());
+        if exclude.iter().any(|path| path == &from) {
+            continue;
+        }
+        if file_type.is_symlink() {
+            let target = fs::read_link(&from)?;
+            std::os::unix::fs::symlink(target, &to)?;
+            continue;
+        }
+        if file_type.is_dir() {
+            copy_dir_excluding(&from, &to, exclude, on_chunk)?;
+            continue;
+        }
+
+        let mut reader = fs::File::open(&from)?;
+        let mut writer = fs::File::create(&to)?;
+        let mut buffer = vec![0u8; 256 * 1024];
+        loop {
+            let bytes_read = reader.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..bytes_read])?;
+            if !on_chunk(bytes_read as u64) {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "Copy cancelled"));
+            }
+        }
+        fs::set_permissions(&to, reader.metadata()?.permissions())?;
+    }
+    Ok(())
+}
+
+/// Human-readable size used in progress labels.
+pub fn format_bytes(bytes: u64) -> String {
+    const GB: u64 = 1_073_741_824;
+    const MB: u64 = 1_048_576;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else {
+        format!("{} MB", bytes / MB)
+    }
+}
+
+/// True when `path` itself is a symlink (without following it).
+pub fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// True when `path` is a directory with anything in it.
+pub fn has_entries(path: &Path) -> bool {
+    fs::read_dir(path)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false)
+}
+
+/// Fail with the needed and available sizes when the filesystem holding
+/// `path` has less than `required` bytes free, instead of running into ENOSPC
+/// halfway through a copy. `path` may not exist yet; its nearest existing
+/// parent is checked. Passes when the free space can't be read.
+pub fn ensure_free_space(path: &Path, required: u64) -> io::Result<()> {
+    let existing = path.ancestors().find(|ancestor| ancestor.exists()).unwrap_or(path);
+    match disk_space(existing) {
+        Some((available, _)) if available < required => Err(io::Error::new(
+            io::ErrorKind::StorageFull,
+            format!(
+                "Not enough free space on {}: {} needed, {} available",
+                existing.display(),
+                format_bytes(required),
+                format_bytes(available)
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// `(available, total)` bytes on the filesystem holding `path`.
+pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    let block_size = stats.f_frsize as u64;
+    Some((
+        stats.f_bavail as u64 * block_size,
+        stats.f_blocks as u64 * block_size,
+    ))
+}
+
+/// Write a file only its owner can read, for settings holding passwords or
+/// API secrets. A file left readable by an older version is tightened too.
+pub fn write_private(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(content.as_ref())
+}
@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// True when LinuxBoy runs inside a Flatpak sandbox.
+pub fn is_flatpak() -> bool {
+    static FLATPAK: OnceLock<bool> = OnceLock::new();
+    *FLATPAK.get_or_init(|| Path::new("/.flatpak-info").exists())
+}
+
+/// Turn `cmd` into one that runs on the host. Inside Flatpak, umu-run, Wine,
+/// package managers and pkexec only exist outside the sandbox, so the command
+/// goes through `flatpak-spawn --host`, with its environment and working
+/// directory passed as flags. Outside Flatpak `cmd` is returned unchanged.
+///
+/// Call this after setting env, args and working directory, and before
+/// stdio or `pre_exec`, which are not carried over.
+pub fn command(cmd: Command) -> Command {
+    if !is_flatpak() {
+        return cmd;
+    }
+
+    let mut host = Command::new("flatpak-spawn");
+    host.arg("--host");
+    if let Some(dir) = cmd.get_current_dir() {
+        host.arg(format!("--directory={}", dir.display()));
+    }
+    for (key, value) in cmd.get_envs() {
+        // Removed variables have no flatpak-spawn equivalent.
+        if let Some(value) = value {
+            host.arg(format!(
+                "--env={}={}",
+                key.to_string_lossy(),
+                value.to_string_lossy()
+            ));
+        }
+    }
+    host.arg(cmd.get_program());
+    host.args(cmd.get_args());
+    host
+}
+
+/// Whether `name` is on the host's PATH.
+pub fn has_command(name: &str) -> bool {
+    let mut which = Command::new("which");
+    which.arg(name);
+    command(which)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Where a host file such as `/etc/os-release` is visible. Flatpak mounts
+/// the host's `/etc` under `/run/host` when it is exposed at all.
+pub fn host_file(path: &str) -> PathBuf {
+    if is_flatpak() {
+        let mounted = Path::new("/run/host").join(path.trim_start_matches('/'));
+        if mounted.exists() {
+            return mounted;
+        }
+    }
+    PathBuf::from(path)
+}
+
+/// `preferred` if the app can write there, otherwise a folder of the same
+/// name in the sandbox's data dir. A Flatpak without home access can still
+/// use `~/.var/app/<id>/data`, which the host sees at the same path.
+pub fn writable_dir(preferred: PathBuf) -> PathBuf {
+    if !is_flatpak() || fs::create_dir_all(&preferred).is_ok() {
+        return preferred;
+    }
+    let name = preferred
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_start_matches('.').to_string())
+        .unwrap_or_else(|| "linuxboy".to_string());
+    match dirs::data_dir() {
+        Some(data_dir) => data_dir.join(name),
+        None => preferred,
+    }
+}
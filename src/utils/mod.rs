@@ -1 +1,2 @@
-// Utils module - currently empty
+pub mod file_ops;
+pub mod host;